@@ -0,0 +1,100 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// Benchmarks of the operations which don't need to connect to the network
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use safe_auth::{
+    decode_req, diff_authed_apps, inspect_auth_req, AuthBackend, AuthedAppsList, FakeBackend,
+};
+use safe_core::ipc::req::{AppExchangeInfo, IpcReq};
+use safe_core::ipc::Permission;
+use std::collections::BTreeSet;
+
+static APP_AUTH_REQ: &str = "bAAAAAACNZZQ4WAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAAAAAAAAAAAAAB";
+static CONT_AUTH_REQ: &str = "bAAAAAADLZ663OAIAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAEAAAAAAAAAAAAYAAAAAAAAAAAX3NOVZWSYYCAAAAAAAAAAAACAAAAABAAAAAA4AAAAAAAAAAAX3QOVRGY2LDAEAAAAAAAAAAAAAAAAAAC";
+
+// Number of apps in the lists of authorised apps rendered
+const AUTHED_APPS_COUNT: usize = 100;
+
+fn gen_authed_apps(count: usize) -> Vec<AuthedAppsList> {
+    (0..count)
+        .map(|i| {
+            let mut perms = BTreeSet::new();
+            let _ = perms.insert(Permission::Read);
+            let _ = perms.insert(Permission::Insert);
+            AuthedAppsList {
+                app: AppExchangeInfo {
+                    id: format!("net.maidsafe.bench.{}", i),
+                    scope: None,
+                    name: format!("Bench app {}", i),
+                    vendor: "MaidSafe.net Ltd".to_string(),
+                },
+                perms: vec![
+                    ("_public".to_string(), perms.clone()),
+                    ("_music".to_string(), perms),
+                ],
+            }
+        })
+        .collect()
+}
+
+fn bench_decode(c: &mut Criterion) {
+    c.bench_function("decode app auth req", |b| {
+        b.iter(|| decode_req(black_box(APP_AUTH_REQ)))
+    });
+    c.bench_function("decode invalid auth req", |b| {
+        b.iter(|| decode_req(black_box("invalid-auth-req")))
+    });
+    c.bench_function("inspect containers auth req", |b| {
+        b.iter(|| inspect_auth_req(black_box(CONT_AUTH_REQ)))
+    });
+}
+
+// There is no policy engine yet, the decision on each request is taken by the allow callback,
+// so we measure what it takes to evaluate a request and apply the decision to an account
+fn bench_policy_evaluation(c: &mut Criterion) {
+    fn allow_only_public(req: IpcReq) -> bool {
+        match req {
+            IpcReq::Auth(auth_req) => auth_req.containers.keys().all(|name| name == "_public"),
+            _ => false,
+        }
+    }
+
+    c.bench_function("evaluate and grant app auth req", |b| {
+        let backend = FakeBackend::default();
+        b.iter(|| backend.authorise_app(black_box(APP_AUTH_REQ), &allow_only_public))
+    });
+    c.bench_function("evaluate and deny containers auth req", |b| {
+        let backend = FakeBackend::default();
+        b.iter(|| backend.authorise_app(black_box(CONT_AUTH_REQ), &allow_only_public))
+    });
+}
+
+fn bench_authed_apps_rendering(c: &mut Criterion) {
+    let apps = gen_authed_apps(AUTHED_APPS_COUNT);
+    let rendered = apps.clone();
+    c.bench_function("render authed apps as json", move |b| {
+        b.iter(|| serde_json::to_string(black_box(&rendered)))
+    });
+
+    let mut changed = gen_authed_apps(AUTHED_APPS_COUNT);
+    let _ = changed.remove(0);
+    let _ = changed[0].perms.pop();
+    c.bench_function("diff authed apps", move |b| {
+        b.iter(|| diff_authed_apps(black_box(&apps), black_box(&changed)))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_decode,
+    bench_policy_evaluation,
+    bench_authed_apps_rendering
+);
+criterion_main!(benches);
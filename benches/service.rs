@@ -0,0 +1,45 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// Benchmarks of the login and of the Authenticator service, which need the `testing` feature,
+// and preferably the `mock-network` one so the network latency doesn't affect the results
+
+use criterion::{black_box, criterion_group, criterion_main, Benchmark, Criterion};
+use safe_auth::testing::{create_test_account, spawn_daemon, APP_AUTH_REQ};
+use safe_auth::{log_in, FakeBackend};
+use std::rc::Rc;
+use std::time::Duration;
+
+fn bench_login(c: &mut Criterion) {
+    let (_, account) = create_test_account().unwrap();
+    let benchmark = Benchmark::new("log in", move |b| {
+        b.iter(|| log_in(black_box(&account.secret), black_box(&account.password)).unwrap())
+    })
+    .sample_size(10)
+    .measurement_time(Duration::from_secs(30));
+    c.bench("login", benchmark);
+}
+
+fn bench_daemon(c: &mut Criterion) {
+    // A fake backend is used so only the handling of the requests by the service is measured
+    let daemon = Rc::new(spawn_daemon(Some(Box::new(FakeBackend::default())), &|_| true).unwrap());
+    let root_daemon = Rc::clone(&daemon);
+    c.bench_function("daemon root", move |b| {
+        b.iter(|| root_daemon.get("/").unwrap())
+    });
+    let authorise_daemon = Rc::clone(&daemon);
+    c.bench_function("daemon authorise", move |b| {
+        b.iter(|| authorise_daemon.authorise(black_box(APP_AUTH_REQ)).unwrap())
+    });
+    c.bench_function("daemon authorise invalid req", move |b| {
+        b.iter(|| daemon.authorise(black_box("invalid-auth-req")).unwrap_err())
+    });
+}
+
+criterion_group!(benches, bench_login, bench_daemon);
+criterion_main!(benches);
@@ -0,0 +1,372 @@
+# The messages of the safe_auth CLI, in English, which is also the language used for the
+# messages missing from any other translation.
+
+## Authorisation requests. These are the prompts the user decides on what an app is allowed
+## to do with their account: a mistranslation may lead them to grant permissions they didn't
+## intend to, so they must convey exactly the same as the English ones.
+
+auth-req-received = The following application authorisation request was received:
+containers-req-received = The following authorisation request for containers was received:
+share-mdata-req-received = The following authorisation request to share a MutableData was received:
+unregistered-req-received = An authorisation request from an unregistered application was received, to read public data only.
+# The own container is created for the app, which is given all the permissions on it
+own-container = Own container: { $container } (created for the app, with all the permissions)
+no-own-container = Own container: none
+default-containers = Default containers: { $containers }
+mdata-type-tag = Type tag: { $type_tag }
+mdata-name = XoR name: { $name }
+mdata-permissions = Permissions:{ $permissions }
+# The letter to be typed for allowing it must be the one between brackets
+allow-auth-prompt = Allow authorisation? [y/N]:
+allow-auth-yes = y
+# Asked for the requests to authorise an app, the letters to be typed for allowing it must be
+# the ones of allow-auth-yes and allow-auth-temporary
+allow-auth-temporary-prompt = Allow authorisation? [y/t/N] (t: only for { $hours } hours):
+allow-auth-temporary = t
+# An app whose id is the one of a known app, which the user can recognise the vendor of
+app-known = Recognised app: '{ $app_id }' is an app of { $vendor }
+# The note the user attached to the app with `apps label`
+app-label = Your label for '{ $app_id }': { $label }
+# Shown loudly, as the app is likely impersonating the known one to be granted its permissions
+app-mimicking = WARNING! '{ $app_id }' is not a known app, but it looks like the known app '{ $known_id }' ({ $known_name }). It may be impersonating it: only allow it if you are sure of where this request comes from.
+# The warnings of the risky requests, shown right before the prompt
+warning-vendor-mismatch = WARNING! The app '{ $app_id }' is already authorised with the vendor '{ $authorised_vendor }', but this request claims the vendor '{ $vendor }'. Another app may be using its id.
+warning-full-access = WARNING! The app '{ $app_id }' requests every container with all the permissions, which gives it full control of your data.
+warning-repeated-after-denial = WARNING! The app '{ $app_id }' sent this request again after it was denied { $denials } time(s) in the last 5 minutes.
+warning-possible-replay = WARNING! This exact request of '{ $app_id }' was already decided on { $minutes_ago } minute(s) ago. Another app may be replaying it: only allow it if you are sure of where it comes from.
+# Asked for each MutableData of a request to share them, the letter to be typed for allowing
+# it must be the one of allow-auth-yes
+share-mdata-item-prompt = Share the MutableData { $name } (type tag { $type_tag })? [y/N]:
+auth-denied-by-policy = This kind of authorisation request is denied by the config file.
+auth-allowed-by-policy = This kind of authorisation request is allowed by the config file.
+auth-denied-by-denylist = This application is on the denylist, its authorisation requests are denied.
+# The kind is one of Auth, Containers, Unregistered or ShareMData
+auth-rate-limited = An authorisation request ({ $kind }) was denied, too many of them were received in the last minute.
+auth-allowed = Authorisation will be allowed...
+auth-denied = Authorisation will be denied...
+auth-allowed-temporarily = Authorisation will be allowed for { $hours } hours, the app is revoked afterwards...
+grant-expired = The temporary grant of app '{ $app_id }' expired, its permissions were revoked
+prompt-timed-out = No answer was given within { $seconds } seconds.
+auth-decided-from-toast = The request was decided from its notification.
+
+## Verification of the user, shown by the system's dialog after "safe_auth is trying to"
+## with Touch ID, or after "Authentication is required to" with polkit
+
+verify-auth-reason = allow the authorisation request of '{ $app_id }'
+verify-unregistered-reason = allow the authorisation request of an unregistered app
+verify-revoke-reason = revoke the permissions of '{ $app_id }'
+
+## Terminal title while prompts are waiting
+
+attention-title = safe_auth: { $count } authorisation request(s) waiting
+
+## Toast notifications of the auth requests waiting, with buttons deciding on them. The app
+## chose its id, so the text must tell the user to review the request in the terminal.
+
+toast-title = safe_auth: authorisation request
+# The kind is one of Auth, Containers or ShareMData
+toast-auth-req = '{ $app_id }' sent an authorisation request ({ $kind }). Review it in the terminal before allowing it.
+toast-unregistered-req = An unregistered application requests to read public data.
+toast-allow = Allow
+toast-deny = Deny
+decision-sent = The decision was sent to the Authenticator service.
+
+## Credentials
+
+prompt-secret = Secret:
+prompt-password = Password:
+warn-plaintext-credentials = Warning! Storing your secret/password in plaintext in a config file is not secure.
+credentials-expired = The credentials of the '{ $provider }' provider expired, they were stored { $days } days ago. Enter them again to keep using them.
+credentials-rotation-reminder = Consider changing your account's password, and storing the new one in place of the expired one.
+safe-key-rejected = The account could not be created: { $error }
+safe-key-retry-prompt = Type another secret key to pay for the account, or press Enter to give up:
+# The letter(s) to be typed for getting a fresh secret key are the ones of safe-key-invite,
+# or of safe-key-test-coins with the mock network
+safe-key-retry-prompt-fresh = Type another secret key to pay for the account, { $answer } to get a fresh one from { $source }, or press Enter to give up:
+safe-key-invite = f
+safe-key-test-coins = t
+safe-key-test-coins-source = the mock network, preloaded with test-coins
+safe-key-retrying = Trying again in { $seconds } seconds...
+
+## Tables
+
+header-id = Id
+header-name = Name
+header-vendor = Vendor
+header-permissions = Permissions
+header-permissions-requested = Permissions requested
+header-mdata-requested = MutableData's requested to share
+header-authed-apps = Authorised Applications
+header-acc-summary = Account Summary
+header-network = Network
+header-balance = Balance
+header-authed-apps-count = Authorised apps
+header-containers = Containers
+header-storage-used = Storage used
+header-last-authorised = Last authorisation
+header-network-name = Network name
+header-contacts = Contacts
+header-discrepancies = Discrepancies
+header-change = Change
+header-status = Status
+header-profile = Profile
+header-config-file = Config file
+header-credentials = Credentials
+header-daemon = Authenticator service
+header-last-login = Last login
+header-receipt = Receipt
+header-request-hash = Request hash
+header-kind = Kind
+header-decision = Decision
+header-time = Time
+header-public-key = Public key
+header-approver = Approved by
+header-activity = Account Activity
+header-event = Activity
+header-source = Known from
+header-raw-request = Request
+header-decoded-request = Decoded request
+app-known-mark = (known app)
+app-label-mark = Label: { $label }
+app-mimicking-mark = (LOOKS LIKE '{ $known_id }')
+own-container-mark = (own container)
+change-removed = Not authorised anymore
+change-added = Newly authorised
+change-perms-from = Permissions changed from:
+change-perms-to = to:
+
+## Messages
+
+acc-created = Account was created successfully!
+logged-in = Logged in the SAFE Network successfully!
+service-in-use = Using the Authenticator service running on port { $port }
+auth-non-interactive = The { $kind } request was denied, it can't be prompted for with --non-interactive
+app-revoked = Authorised permissions were revoked for app '{ $app_id }'
+revoke-failed = Failed to revoke the permissions of app '{ $app_id }': { $error }
+revoke-summary = Revoked { $revoked } of { $total } applications
+backup-written = Account backup written to '{ $file }'
+backup-restored = Account backup restored from '{ $file }'
+network-stored = Network '{ $name }' was stored successfully
+no-networks = No networks have been stored
+no-discrepancies = No discrepancies were found with the currently authorised apps
+no-activity = No activity was recorded for the account yet
+apps-unchanged = The authorised apps didn't change
+snapshot-written = Snapshot of the authorised apps written to '{ $file }'
+auth-response = Authorisation response string: { $resp }
+test-coins-key-pair = SafeKey created and preloaded with test-coins. Owner key pair generated:
+key-public = Public Key = { $key }
+key-owner = Owner Key = { $key }
+key-encryption = Encryption Key = { $key }
+key-secret = Secret Key = { $key }
+acc-balance = Balance: { $balance }
+policy-pulled = The policy was pulled from '{ $url }' and verified, { $apps } app(s) are on its denylist
+legacy-migrated = Imported from '{ $file }': { $labels } label(s), { $grants } grant time(s) and { $denied } app(s) added to the denylist
+warn-deprecated-flag = Warning! { $flag } is deprecated, use the { $cmd } command instead.
+warn-lang-unavailable = Warning! The language '{ $lang }' is not available, the messages are in English. The available ones are: { $locales }
+
+## Errors the CLI exits with, the description given by the operation which failed
+
+error-authenticator = Error: { $description }
+error-invalid-input = Invalid input: { $description }
+error-credentials = Invalid credentials: { $description }
+error-network = Network error: { $description }
+error-ipc-decode = Invalid request: { $description }
+error-policy-denied = Denied by the policies: { $description }
+error-io = File error: { $description }
+error-incompatible-version = Incompatible app: { $description }
+error-timeout = Timed out: { $description }
+error-cancelled = Cancelled: { $description }
+
+## Status
+
+status-no-config = None
+status-credentials-prompt = Asked when logging in
+status-daemon-not-running = Not running
+status-never-logged-in = Not recorded
+status-no-balance = Not available, the account was not logged in to
+
+## Account summary
+
+summary-storage-unknown = Not known, no balance was recorded for the account yet
+summary-never-authorised = No app was authorised yet
+activity-time-unknown = Unknown
+activity-allowed = Allowed the { $kind } request
+activity-denied = Denied the { $kind } request
+activity-revoked = Revoked
+activity-expired = Temporary grant expired
+activity-authorised = Authorised
+activity-approved-by = { $event }, approved by the { $approver }
+activity-unregistered = (unregistered app)
+activity-source-audit = Audit log
+activity-source-service = Authenticator service
+activity-source-network = Network
+
+## Permission reviews, reminded by the Authenticator service
+
+review-reminder = Time to review your permissions: { $count } app(s) are authorised: { $app_ids }
+review-stale = { $count } of them didn't send any request in the last { $days } days: { $app_ids }. Revoke the ones you don't use anymore with `safe_auth --revoke <app id>`.
+
+## Receipts
+
+receipt-verified = The receipt is valid: the request was { $decision } on { $time } by the profile '{ $profile }'
+
+## Revocation
+
+select-apps-help = Type the numbers of the apps to (un)select, some letters of their id, name or vendor to find them, or { $all } to list them all again. Press Enter once done.
+select-apps-prompt = Select:
+select-apps-no-match = No authorised app matches '{ $filter }'
+select-apps-invalid-number = There is no app number { $number }
+# The letter to be typed for confirming it must be the one of allow-auth-yes
+revoke-confirm-prompt = Revoke the permissions of { $count } app(s): { $app_ids }? [y/N]:
+revoke-none-selected = No application was selected, none was revoked
+
+## Editing the permissions of an app
+
+edit-app-help = Type the numbers of the permissions to (un)check, the unchecked ones are removed from the app. Press Enter once done.
+edit-app-prompt = Edit:
+edit-app-invalid-number = There is no permission number { $number }
+# The letter to be typed for confirming it must be the one of allow-auth-yes
+edit-app-confirm-prompt = Remove { $count } permission(s) from '{ $app_id }': { $perms }? It is revoked, then authorised again with the other ones. [y/N]:
+edit-app-unchanged = The permissions of '{ $app_id }' were not changed
+edit-app-done = The permissions of '{ $app_id }' were changed
+
+## Labels of the apps
+
+label-set = The label of '{ $app_id }' was set to "{ $label }"
+label-removed = The label of '{ $app_id }' was removed
+
+## Guardian of another account
+
+guardian-approval-request = The account you are the guardian of asks you to approve an operation
+# The letter to be typed for confirming it must be the one of allow-auth-yes
+guardian-revoke-all-prompt = Approve revoking all the { $count } app(s) of the account: { $app_ids }? [y/N]:
+guardian-approved = The operation was approved
+guardian-denied = The operation was not approved
+guardian-non-interactive = The operation was not approved, it can't be prompted for with --non-interactive
+
+## Interactive shell
+
+shell-help = Commands: apps, balance, auth <req>, revoke <app id>..., history, history clear, help, exit
+shell-prompt = safe_auth>
+shell-error = Error: { $error }
+
+## Listening for the auth requests handed off by the apps
+
+listening = Listening for auth requests on { $address }
+
+## Dashboard
+
+tui-requests = Auth requests ({ $count })
+tui-no-requests = No auth request is waiting
+tui-loading = Loading...
+tui-help = Tab: switch pane, Up/Down: select, a: allow, d: deny, r: revoke, u: refresh, q: quit
+# The letter to be typed for confirming it must be the one of allow-auth-yes
+tui-revoke-confirm = Revoke the permissions of '{ $app_id }'? [y/N]
+tui-revoking = Revoking the permissions of '{ $app_id }'...
+tui-request-allowed = The request of '{ $app_id }' was allowed
+tui-request-denied = The request of '{ $app_id }' was denied
+tui-request-expired = The request of '{ $app_id }' timed out before it was decided on
+tui-error = Error: { $error }
+
+## Restoring a snapshot of the authorised apps
+
+snapshot-nothing-to-restore = All the apps of the snapshot have their permissions already
+snapshot-own-container = Own container
+# The letter to be typed for confirming it must be the one of allow-auth-yes
+snapshot-restore-prompt = Grant the permissions again to { $count } app(s)? [y/N]:
+snapshot-not-restored = The snapshot was not restored
+snapshot-app-restored = The permissions of the snapshot were granted again to app '{ $app_id }'
+
+## Operations, reported when interrupted with Ctrl+C
+
+op-create-acc = Create SAFE Network account
+op-log-in = Log in to the SAFE Network
+op-authorise = Authorise application
+op-revoke = Revoke applications
+op-authed-apps = Fetch list of authorised applications
+op-apps-diff = Compare snapshot of authorised applications
+op-apps-export = Export snapshot of authorised applications
+op-apps-restore = Restore snapshot of authorised applications
+op-apps-edit = Edit permissions of application
+op-apps-label = Label application
+op-account-backup = Back up account
+op-account-restore = Restore account backup
+op-account-keys = Fetch account keys
+op-account-summary = Summarise account
+op-account-activity = Fetch account activity
+op-account-rotate-keys = Rotate account keys
+op-balance = Fetch account balance
+op-headless = Run headless authenticator
+op-service = Run Authenticator service
+op-shell = Run interactive shell
+op-listen = Listen for auth requests
+op-dashboard = Run dashboard
+interrupted = Interrupted!
+op-completed = Completed: { $op }
+op-not-completed = Not completed: { $op }
+op-not-completed-step = Not completed: { $op } ({ $step })
+
+## Updates
+
+update-not-supported = The update command is not supported for the development build.
+update-checking = Checking for updates...
+update-up-to-date = Current version { $current } is up to date
+update-available = A newer version is available: { $latest } (current version is { $current })
+update-downloading = Downloading version { $version }...
+update-done = Updated to version { $version }
+update-notice = A newer version of safe_auth is available: { $latest } (run `safe_auth update` to install it)
+
+## Man pages
+
+man-page-written = Man page written to '{ $file }'
+
+## Doctor
+
+doctor-status-ok = {"  "}OK{"  "}
+doctor-status-warning = WARN{"  "}
+doctor-status-failed = FAIL{"  "}
+doctor-status-skipped = SKIP{"  "}
+doctor-fix = Fix: { $fix }
+doctor-check-config = Config file
+doctor-check-keyring = Keyring
+doctor-check-network = Network
+doctor-check-daemon_port = Authenticator service port
+doctor-check-clock = Clock
+doctor-check-caches = Caches
+doctor-config-none = No config file given with --config
+doctor-config-ok = The config file '{ $file }' is valid
+doctor-config-unreadable = The config file '{ $file }' can't be read: { $error }
+doctor-config-unreadable-fix = Check that '{ $file }' exists and that your user can read it
+doctor-config-invalid = The config file '{ $file }' is not valid: { $error }
+doctor-config-invalid-fix = Fix the config file's JSON, the README lists the fields it can have
+doctor-config-permissions = The config file '{ $file }' has your credentials but other users can read it
+doctor-config-permissions-fix = Make it only readable by you with `chmod 600 { $file }`, or read the credentials from a keyring with a `command` credential provider
+doctor-keyring-none = No keyring is used, the credentials are read from the environment, the config file or the prompt
+doctor-keyring-ok = The commands of the { $count } `command` credential providers are available
+doctor-keyring-missing = The command '{ $program }' of a `command` credential provider was not found
+doctor-keyring-missing-fix = Install '{ $program }', or set its full path in the config file's `credentials`
+doctor-keyring-native-ok = The system's keyring is read with '{ $program }'
+doctor-keyring-native-missing = The command '{ $program }' the `keyring` credential provider reads the system's keyring with was not found
+doctor-keyring-native-missing-fix = Install '{ $program }', e.g. with the `libsecret-tools` package
+doctor-keyring-unsupported = The `keyring` credential provider can't read a keyring on this system
+doctor-keyring-unsupported-fix = Read the credentials from a keyring with a `command` credential provider instead
+doctor-network-mock = This build connects to the mock network, which is always available
+doctor-network-unknown = No network named '{ $name }' was found
+doctor-network-unknown-fix = Store it with `safe_auth networks add { $name } <crust config file>`
+doctor-network-no-config = No crust config was found at '{ $file }'
+doctor-network-no-config-fix = Set the SAFE_CRUST_CONFIG_PATH environment variable to the directory of the network's crust config, or use a stored network with --network
+doctor-network-no-contacts = The crust config '{ $file }' has no valid contacts
+doctor-network-reachable = { $reachable } of the { $total } contacts of the network are reachable
+doctor-network-unreachable-fix = Check your internet connection and firewall, or get an up to date crust config for the network
+doctor-daemon-unavailable = This build doesn't have the Authenticator service
+doctor-daemon-port-free = The port { $port } is free for the Authenticator service
+doctor-daemon-port-in-use = The port { $port } is already in use by another process
+doctor-daemon-port-error = The port { $port } can't be listened on: { $error }
+doctor-daemon-port-in-use-fix = Stop the process using the port { $port }, or run the service on another one with --daemon <port>
+doctor-clock-no-reference = The clock couldn't be compared, no time reference could be reached
+doctor-clock-ok = The clock is in sync
+doctor-clock-skew = The clock is off by { $seconds } seconds
+doctor-clock-skew-fix = Synchronise the clock, e.g. by enabling NTP, as requests with a wrong time may be rejected
+doctor-caches-ok = No stale caches were found
+doctor-caches-stale = The bootstrap cache '{ $file }' wasn't updated for over 30 days
+doctor-caches-stale-fix = Connect once with --fresh-bootstrap to discard the cached contacts
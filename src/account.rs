@@ -0,0 +1,725 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::apps::{authed_apps, restore_authed_apps, AuthedAppsDiff, AuthedAppsList};
+use crate::cancel::CancellationToken;
+use crate::errors::{login_error, Error, SAFE_KEY_ERROR};
+use crate::helpers::{bytes_to_hex, pk_to_hex, sk_from_hex};
+use crate::progress::{no_progress, report, ProgressReporter};
+use crate::timeouts::{with_timeout, Operation};
+use bincode::{deserialize, serialize};
+use futures::Future;
+use log::{debug, info};
+use safe_authenticator::{
+    access_container, errors::AuthError, run as auth_run_helper, Authenticator,
+};
+use safe_core::client::Client;
+use safe_core::utils::{symmetric_decrypt, symmetric_encrypt};
+use serde::{Deserialize, Serialize};
+
+// Version of the format used to serialise the account backups
+const BACKUP_FORMAT_VERSION: u64 = 1;
+
+// Names of the operations reported to the progress reporters
+const CREATE_ACC_OP: &str = "Create account";
+const LOGIN_OP: &str = "Log in";
+
+// Information about the account's balance and its default containers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountInfo {
+    pub balance: String,
+    pub containers: Vec<String>,
+}
+
+// Public keys associated with the logged in client, hex encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountKeys {
+    pub public_key: String,
+    pub owner_key: String,
+    pub encryption_key: String,
+}
+
+// Content of an account backup, it's stored encrypted with the account's secret key
+#[derive(Serialize, Deserialize)]
+struct AccountBackup {
+    version: u64,
+    authed_apps: Vec<AuthedAppsList>,
+}
+
+/// # Create Account
+/// Creates a new account on the SAFE Network.
+/// Returns an error if an account exists or if there was some
+/// problem during the account creation process.
+///
+/// Note: This does _not_ perform any strength checks on the
+/// strings used to create the account.
+///
+/// ## Example
+/// ```
+/// use safe_auth::create_acc;
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// # fn main() -> Result<(), safe_auth::Error> {
+///     let my_secret = "mysecretstring";
+///     let my_password = "mypassword";
+/// #   let my_secret = &(random_str());
+/// #   let my_password = &(random_str());
+/// #   let secret_key = threshold_crypto::SecretKey::random();
+/// #   let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// #   let sk = &sk;
+/// #   safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+///     let auth = create_acc(sk, my_secret, my_password)?;
+/// #   Ok(())
+/// # }
+///```
+///
+/// ## Error Example
+/// If an account with same secret already exists,
+/// the function will return an error:
+/// ```
+/// use safe_auth::create_acc;
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let acc_not_created = create_acc(sk, my_secret, my_password);
+/// match acc_not_created {
+///    Ok(_) => assert!(false), // This should not pass
+///    Err(err) => {
+///         assert!(err.to_string().contains("Failed to create an account"));
+///     }
+/// }
+///```
+pub fn create_acc(sk: &str, secret: &str, password: &str) -> Result<Authenticator, Error> {
+    create_acc_with_progress(sk, secret, password, &no_progress)
+}
+
+/// # Create Account reporting its progress
+///
+/// Same as `create_acc` but the progress of each of its steps is reported to `progress`.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{create_acc_with_progress, OperationProgress};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// let auth = create_acc_with_progress(sk, my_secret, my_password, &|progress: &OperationProgress| {
+///     println!("[{}/{}] {}", progress.completed, progress.total, progress.step);
+/// });
+/// match auth {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+///```
+pub fn create_acc_with_progress(
+    sk: &str,
+    secret: &str,
+    password: &str,
+    progress: &dyn ProgressReporter,
+) -> Result<Authenticator, Error> {
+    create(sk, secret, password, progress, None)
+}
+
+/// # Create Account, unless it's cancelled
+///
+/// Same as `create_acc_with_progress` but it fails with a `Cancelled` error as soon as
+/// `cancel` is cancelled, e.g. from another thread if the network doesn't respond.
+/// Note the account may still be stored on the network if it was cancelled too late.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{create_acc_cancellable, CancellationToken, Error, OperationProgress};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// let cancel = CancellationToken::new();
+/// cancel.cancel();
+/// let auth = create_acc_cancellable(sk, my_secret, my_password, &|_: &OperationProgress| {}, &cancel);
+/// match auth {
+///    Err(Error::Cancelled(_)) => assert!(true), // This should pass
+///    _ => assert!(false)
+/// }
+///```
+pub fn create_acc_cancellable(
+    sk: &str,
+    secret: &str,
+    password: &str,
+    progress: &dyn ProgressReporter,
+    cancel: &CancellationToken,
+) -> Result<Authenticator, Error> {
+    create(sk, secret, password, progress, Some(cancel))
+}
+
+fn create(
+    sk: &str,
+    secret: &str,
+    password: &str,
+    progress: &dyn ProgressReporter,
+    cancel: Option<&CancellationToken>,
+) -> Result<Authenticator, Error> {
+    debug!("Attempting to create a SAFE account...");
+    report(progress, CREATE_ACC_OP, "Decoding the secret key", 0, 2);
+    let secret_key = sk_from_hex(sk)
+        .map_err(|err| Error::CredentialsError(format!("{}: {}", SAFE_KEY_ERROR, err)))?;
+    let secret = secret.to_string();
+    let password = password.to_string();
+    report(
+        progress,
+        CREATE_ACC_OP,
+        "Storing the account on the network",
+        1,
+        2,
+    );
+    let auth = with_timeout(Operation::CreateAcc, cancel, move || {
+        match Authenticator::create_acc(secret, password, secret_key.into(), || {
+            // eprintln!("{}", "Disconnected from network");
+        }) {
+            Ok(auth) => {
+                debug!("Returning account just created");
+                Ok(auth)
+            }
+            Err(err) => Err(login_error("Failed to create an account", err)),
+        }
+    })?;
+    report(progress, CREATE_ACC_OP, "Account created", 2, 2);
+    Ok(auth)
+}
+
+/// # Log in
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and return an `Authenticator` instance.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::log_in;
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let logged_in = log_in(my_secret, my_password);
+/// match logged_in {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+///```
+///
+/// ## Error Example
+/// If the account does not exist, the function will return an appropriate error:
+///```
+/// # use safe_auth::log_in;
+/// let not_logged_in = log_in("non", "existant");
+/// match not_logged_in {
+///    Ok(_) => assert!(false), // This should not pass
+///    Err(err) => {
+///         assert!(err.to_string().contains("Failed to log in"));
+///    }
+/// }
+///```
+pub fn log_in(secret: &str, password: &str) -> Result<Authenticator, Error> {
+    log_in_with_progress(secret, password, &no_progress)
+}
+
+/// # Log in reporting its progress
+///
+/// Same as `log_in` but the progress of each of its steps is reported to `progress`.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in_with_progress, OperationProgress};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let logged_in = log_in_with_progress(my_secret, my_password, &|progress: &OperationProgress| {
+///     println!("[{}/{}] {}", progress.completed, progress.total, progress.step);
+/// });
+/// match logged_in {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+///```
+pub fn log_in_with_progress(
+    secret: &str,
+    password: &str,
+    progress: &dyn ProgressReporter,
+) -> Result<Authenticator, Error> {
+    login(secret, password, progress, None)
+}
+
+/// # Log in, unless it's cancelled
+///
+/// Same as `log_in_with_progress` but it fails with a `Cancelled` error as soon as
+/// `cancel` is cancelled, e.g. from another thread if the network doesn't respond.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{log_in_cancellable, CancellationToken, Error, OperationProgress};
+/// let cancel = CancellationToken::new();
+/// cancel.cancel();
+/// let logged_in = log_in_cancellable("mysecretstring", "mypassword", &|_: &OperationProgress| {}, &cancel);
+/// match logged_in {
+///    Err(Error::Cancelled(_)) => assert!(true), // This should pass
+///    _ => assert!(false)
+/// }
+///```
+pub fn log_in_cancellable(
+    secret: &str,
+    password: &str,
+    progress: &dyn ProgressReporter,
+    cancel: &CancellationToken,
+) -> Result<Authenticator, Error> {
+    login(secret, password, progress, Some(cancel))
+}
+
+fn login(
+    secret: &str,
+    password: &str,
+    progress: &dyn ProgressReporter,
+    cancel: Option<&CancellationToken>,
+) -> Result<Authenticator, Error> {
+    debug!("Attempting to log in...");
+    let secret = secret.to_string();
+    let password = password.to_string();
+    report(
+        progress,
+        LOGIN_OP,
+        "Fetching the account from the network",
+        0,
+        1,
+    );
+    let auth = with_timeout(
+        Operation::Login,
+        cancel,
+        move || match Authenticator::login(secret, password, || info!("Disconnected from network"))
+        {
+            Ok(auth) => {
+                debug!("Returning logged-in Authenticator instance");
+                Ok(auth)
+            }
+            Err(err) => Err(login_error("Failed to log in", err)),
+        },
+    )?;
+    report(progress, LOGIN_OP, "Logged in", 1, 1);
+    Ok(auth)
+}
+
+/// # Create a backup of the account
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and generate a backup of the list of applications
+/// authorised so far, together with their permissions. The backup is encrypted
+/// with the account's secret symmetric key, thus it can only be restored
+/// by logging in with the same account.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, backup_account};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let backup = backup_account(&authenticator);
+/// match backup {
+///    Ok(bytes) => assert!(!bytes.is_empty()), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn backup_account(authenticator: &Authenticator) -> Result<Vec<u8>, Error> {
+    debug!("Attempting to create a backup of the account...");
+    let backup = AccountBackup {
+        version: BACKUP_FORMAT_VERSION,
+        authed_apps: authed_apps(authenticator)?,
+    };
+
+    auth_run_helper(authenticator, move |client| -> Result<Vec<u8>, AuthError> {
+        let plaintext = serialize(&backup)?;
+        let encrypted = symmetric_encrypt(&plaintext, &client.secret_symmetric_key(), None)?;
+        debug!("Account backup successfully generated");
+        Ok(encrypted)
+    })
+    .map_err(|err| {
+        Error::AuthenticatorError(format!("Failed to create the account backup: {}", err))
+    })
+}
+
+/// # Restore a backup of the account
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and restore a backup previously generated with `backup_account`.
+/// The applications of the backup which are not authorised anymore are authorised again,
+/// together with their own containers, and the ones missing some of the permissions are
+/// granted them; the apps authorised afterwards and the permissions granted since are kept.
+/// The differences found before restoring it are returned, where the `removed` list
+/// contains the apps which were in the backup but were not authorised anymore, and the
+/// `added` list the ones which were authorised afterwards.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, backup_account, restore_account};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let backup = backup_account(&authenticator).unwrap();
+/// let discrepancies = restore_account(&authenticator, backup);
+/// match discrepancies {
+///    Ok(diff) => assert!(diff.is_empty()), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn restore_account(
+    authenticator: &Authenticator,
+    backup: Vec<u8>,
+) -> Result<AuthedAppsDiff, Error> {
+    debug!("Attempting to restore a backup of the account...");
+    let backup = auth_run_helper(
+        authenticator,
+        move |client| -> Result<AccountBackup, AuthError> {
+            let plaintext = symmetric_decrypt(&backup, &client.secret_symmetric_key())?;
+            let backup = deserialize::<AccountBackup>(&plaintext)?;
+            Ok(backup)
+        },
+    )
+    .map_err(|err| {
+        Error::InvalidInput(format!(
+            "Failed to decrypt the account backup, it may belong to a different account: {}",
+            err
+        ))
+    })?;
+
+    if backup.version != BACKUP_FORMAT_VERSION {
+        return Err(Error::InvalidInput(format!(
+            "Account backup format version {} is not supported",
+            backup.version
+        )));
+    }
+
+    let diff = restore_authed_apps(authenticator, &backup.authed_apps)?;
+    debug!(
+        "Returning discrepancies restored from the backup: {:?}",
+        diff
+    );
+    Ok(diff)
+}
+
+/// # Get information about the account
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and get the account's current balance and the
+/// list of default containers it owns.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, acc_info};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let info = acc_info(&authenticator);
+/// match info {
+///    Ok(info) => assert!(!info.containers.is_empty()), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn acc_info(authenticator: &Authenticator) -> Result<AccountInfo, Error> {
+    debug!("Attempting to fetch the account's information...");
+    auth_run_helper(authenticator, move |client| {
+        let c2 = client.clone();
+        client
+            .get_balance(None)
+            .map_err(AuthError::from)
+            .and_then(move |balance| {
+                access_container::fetch_authenticator_entry(&c2).map(move |(_, entries)| {
+                    let mut containers: Vec<String> = entries.keys().cloned().collect();
+                    containers.sort();
+                    let info = AccountInfo {
+                        balance: balance.to_string(),
+                        containers,
+                    };
+                    debug!("Returning account's information: {:?}", info);
+                    info
+                })
+            })
+    })
+    .map_err(|err| {
+        Error::NetworkError(format!(
+            "Failed to fetch the account's information: {}",
+            err
+        ))
+    })
+}
+
+/// # Get the public keys of the account
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and get the public signing and encryption keys
+/// associated with the logged in client, as well as the account's owner key.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, acc_keys};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let keys = acc_keys(&authenticator);
+/// match keys {
+///    Ok(keys) => assert!(!keys.public_key.is_empty()), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn acc_keys(authenticator: &Authenticator) -> Result<AccountKeys, Error> {
+    debug!("Attempting to fetch the account's public keys...");
+    auth_run_helper(
+        authenticator,
+        move |client| -> Result<AccountKeys, AuthError> {
+            let keys = AccountKeys {
+                public_key: pk_to_hex(&client.public_key()),
+                owner_key: pk_to_hex(&client.owner_key()),
+                encryption_key: bytes_to_hex(&client.public_encryption_key().to_bytes()),
+            };
+            debug!("Returning account's public keys: {:?}", keys);
+            Ok(keys)
+        },
+    )
+    .map_err(|err| {
+        Error::AuthenticatorError(format!("Failed to fetch the account's keys: {}", err))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{acc_info, acc_keys, backup_account, create_acc, log_in, restore_account};
+    use crate::apps::{authed_apps, authorise_app, revoke_app};
+    use crate::errors::Error;
+    use crate::test_utils::{gen_random_sk_hex, random_str, APP_AUTH_REQ, APP_ID};
+    use safe_core::client::test_create_balance;
+    use safe_nd::{ClientFullId, Coins};
+    use std::str::FromStr;
+
+    #[test]
+    fn account_creation_and_login_test() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("10").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        // successfully create an account
+        let acc_created = create_acc(sk, my_secret, my_password);
+        match acc_created {
+            Ok(_) => (),
+            Err(err) => std::panic::panic_any(err),
+        }
+
+        // fail to create an account with same secret
+        let acc_not_created = create_acc(sk, my_secret, my_password);
+        match acc_not_created {
+            Ok(_) => panic!("Account shouldn't have been created successfully"),
+            Err(err) => assert_eq!(
+                err,
+                Error::CredentialsError(
+                    "Failed to create an account: SndError(LoginPacketExists)".to_string()
+                )
+            ),
+        }
+
+        // successfully log in
+        let auth = log_in(my_secret, my_password);
+        match auth {
+            Ok(_) => (),
+            Err(err) => std::panic::panic_any(err),
+        }
+
+        // fail to log in with invalid secret
+        let other_secret = &(random_str());
+        let auth = log_in(other_secret, my_password);
+        match auth {
+            Ok(_) => panic!("Shouldn't have logged in sucessfully"),
+            Err(err) => assert_eq!(
+                err,
+                Error::CredentialsError(
+                    "Failed to log in: SndError(NoSuchLoginPacket)".to_string()
+                )
+            ),
+        }
+
+        // fail to log in with invalid password
+        let other_password = &(random_str());
+        let auth = log_in(my_secret, other_password);
+        match auth {
+            Ok(_) => panic!("Shouldn't have logged in sucessfully"),
+            Err(err) => assert_eq!(err, Error::CredentialsError("Failed to log in: CoreError(Symmetric decryption failure - CoreError::SymmetricDecipherFailure)".to_string())),
+        }
+    }
+
+    #[test]
+    fn acc_info_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("10").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+        let info = unwrap!(acc_info(&auth));
+        assert!(!info.balance.is_empty());
+        assert!(info.containers.contains(&"_public".to_string()));
+    }
+
+    #[test]
+    fn acc_keys_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("10").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+        let keys = unwrap!(acc_keys(&auth));
+        assert!(!keys.public_key.is_empty());
+        assert!(!keys.owner_key.is_empty());
+        assert!(!keys.encryption_key.is_empty());
+
+        // the same keys are returned after logging in again with the account
+        let auth = unwrap!(log_in(my_secret, my_password));
+        let keys_after_login = unwrap!(acc_keys(&auth));
+        assert_eq!(keys.public_key, keys_after_login.public_key);
+        assert_eq!(keys.owner_key, keys_after_login.owner_key);
+        assert_eq!(keys.encryption_key, keys_after_login.encryption_key);
+    }
+
+    #[test]
+    fn backup_and_restore_account_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("60").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+        authorise_app(&auth, APP_AUTH_REQ, &|_| true)
+            .expect("Failed to authorise an app before creating the backup");
+        let backup = unwrap!(backup_account(&auth));
+
+        // restoring the backup right away shall report no discrepancies
+        let diff = unwrap!(restore_account(&auth, backup.clone()));
+        assert!(diff.is_empty());
+
+        // after revoking the app, restoring the backup into the account left without
+        // any app reports it as removed and authorises it again
+        revoke_app(&auth, APP_ID.to_string())
+            .expect("Failed to revoke the previously authorised app");
+        assert!(unwrap!(authed_apps(&auth)).is_empty());
+        let diff = unwrap!(restore_account(&auth, backup.clone()));
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].app.id, APP_ID);
+        assert!(diff.added.is_empty());
+        assert!(diff.changed.is_empty());
+        let apps = unwrap!(authed_apps(&auth));
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app.id, APP_ID);
+        let diff = unwrap!(restore_account(&auth, backup.clone()));
+        assert!(diff.is_empty());
+
+        // a backup can't be restored using a different account
+        let (other_sk, other_secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(other_secret_key.clone()),
+            Coins::from_str("10").unwrap(),
+        )
+        .unwrap();
+        let other_auth = unwrap!(create_acc(other_sk, &(random_str()), &(random_str())));
+        match restore_account(&other_auth, backup) {
+            Ok(_) => panic!("Backup shouldn't have been restored with a different account"),
+            Err(err) => assert!(err
+                .to_string()
+                .starts_with("Failed to decrypt the account backup")),
+        }
+    }
+}
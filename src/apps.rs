@@ -0,0 +1,1542 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::backend::AuthBackend;
+use crate::cancel::CancellationToken;
+use crate::decode::decode_auth_req;
+use crate::errors::Error;
+use crate::helpers::{run_async, AsyncResult};
+use crate::inspect::app_container_name;
+use crate::pacing::Pacer;
+use crate::progress::{report, ProgressReporter};
+use crate::timeouts::{run_with_timeout, Operation};
+use crate::AuthAllowPrompt;
+use bincode::deserialize;
+use futures::{future, stream, Future, IntoFuture, Stream};
+use log::{debug, info};
+use safe_authenticator::access_container::update_container_perms;
+use safe_authenticator::ipc::decode_ipc_msg;
+use safe_authenticator::revocation::revoke_app as safe_authenticator_revoke_app;
+use safe_authenticator::{
+    access_container, app_auth::authenticate, config, errors::AuthError, run as auth_run_helper,
+    AuthClient, Authenticator,
+};
+use safe_core::client::Client;
+use safe_core::core_structs::{access_container_enc_key, AccessContainerEntry};
+use safe_core::ipc::req::{
+    AppExchangeInfo, AuthReq, ContainerPermissions, ContainersReq, IpcReq, ShareMDataReq,
+};
+use safe_core::ipc::resp::IpcResp;
+use safe_core::ipc::{encode_msg, IpcError, IpcMsg};
+use safe_core::utils::symmetric_decrypt;
+use safe_core::{client as safe_core_client, CoreError};
+use safe_nd::{AppPermissions, MDataAddress};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Name of the batch revocation operation reported to the progress reporters
+const REVOKE_APPS_OP: &str = "Revoke applications";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthedAppsList {
+    pub app: AppExchangeInfo,
+    pub perms: Vec<(String, ContainerPermissions)>,
+}
+
+// An app found in two lists of authorised apps but with different permissions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppPermsChange {
+    pub app: AppExchangeInfo,
+    pub old_perms: Vec<(String, ContainerPermissions)>,
+    pub new_perms: Vec<(String, ContainerPermissions)>,
+}
+
+// Differences found between an old and a new list of authorised apps
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AuthedAppsDiff {
+    pub added: Vec<AuthedAppsList>,
+    pub removed: Vec<AuthedAppsList>,
+    pub changed: Vec<AppPermsChange>,
+}
+
+impl AuthedAppsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// # Authorise an application
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and authorise an application.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, authorise_app};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let auth_response = authorise_app(&authenticator, auth_req, &|_| true);
+/// match auth_response {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+///```
+/// ## Error Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, authorise_app};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// /// Using an invalid auth request string
+/// let auth_req = "invalid-auth-req-string";
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let auth_response = authorise_app(&authenticator, auth_req, &|_| true);
+/// match auth_response {
+///    Ok(_) => assert!(false), // This should not pass
+///    Err(err) => assert!(err.to_string().contains("EncodeDecodeError"))
+/// }
+///```
+pub fn authorise_app(
+    authenticator: &Authenticator,
+    req: &str,
+    allow: &'static AuthAllowPrompt,
+) -> Result<String, Error> {
+    authorise(authenticator, req, allow, None)
+}
+
+/// # Authorise an application, unless it's cancelled
+///
+/// Same as `authorise_app` but it fails with a `Cancelled` error as soon as `cancel` is cancelled,
+/// e.g. from another thread if the network doesn't respond. Note the requests already sent to
+/// the network are not undone, thus the application may still end up being authorised.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, authorise_app_cancellable, CancellationToken, Error};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let cancel = CancellationToken::new();
+/// cancel.cancel();
+/// let auth_response = authorise_app_cancellable(&authenticator, auth_req, &|_| true, &cancel);
+/// match auth_response {
+///    Err(Error::Cancelled(_)) => assert!(true), // This should pass
+///    _ => assert!(false)
+/// }
+///```
+pub fn authorise_app_cancellable(
+    authenticator: &Authenticator,
+    req: &str,
+    allow: &'static AuthAllowPrompt,
+    cancel: &CancellationToken,
+) -> Result<String, Error> {
+    authorise(authenticator, req, allow, Some(cancel))
+}
+
+fn authorise(
+    authenticator: &Authenticator,
+    req: &str,
+    allow: &'static AuthAllowPrompt,
+    cancel: Option<&CancellationToken>,
+) -> Result<String, Error> {
+    let req_msg = decode_auth_req(req)?;
+    let ipc_msg = run_with_timeout(authenticator, Operation::Authorise, cancel, move |client| {
+        decode_ipc_msg(client, req_msg)
+    })?
+    .map_err(|err| Error::NetworkError(format!("Failed decoding the auth request: {}", err)))?
+    .map_err(|(error_code, description, _err)| {
+        Error::IpcDecodeError(format!(
+            "Failed decoding the auth request: {} - {:?}",
+            error_code, description
+        ))
+    })?;
+
+    match auth_action(ipc_msg, allow)? {
+        AuthAction::Deny(req_id) => gen_auth_denied_response(req_id),
+        AuthAction::App(req_id, app_auth_req) => {
+            gen_auth_response(authenticator, req_id, app_auth_req, cancel)
+        }
+        AuthAction::Containers(req_id, cont_req) => {
+            gen_cont_auth_response(authenticator, req_id, cont_req, cancel)
+        }
+        AuthAction::Unregistered(req_id) => gen_unreg_auth_response(req_id),
+        AuthAction::ShareMData(req_id, share_mdata_req) => {
+            gen_shared_md_auth_response(authenticator, req_id, share_mdata_req, cancel)
+        }
+    }
+}
+
+/// # Authorise an application without blocking
+///
+/// Same as `authorise_app` but it returns a future which resolves with the
+/// authorisation response, so many operations can be driven concurrently.
+/// The allow/deny callback is invoked from the authenticator's event loop, and
+/// the timeouts set with `set_timeouts` are not applied to the returned future.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use futures::Future;
+/// use safe_auth::{log_in, authorise_app_async};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// /// Using a valid auth request string
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let auth_response = authorise_app_async(&authenticator, auth_req, &|_| true).wait();
+/// match auth_response {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+///```
+pub fn authorise_app_async(
+    authenticator: &Authenticator,
+    req: &str,
+    allow: &'static AuthAllowPrompt,
+) -> AsyncResult<String> {
+    let req_msg = match decode_auth_req(req) {
+        Ok(msg) => msg,
+        Err(err) => return Box::new(future::err(err)),
+    };
+    run_async(authenticator, move |client| {
+        let client = client.clone();
+        decode_ipc_msg(&client, req_msg)
+            .map_err(|err| {
+                Error::NetworkError(format!("Failed decoding the auth request: {}", err))
+            })
+            .and_then(
+                move |ipc_msg| -> Box<dyn Future<Item = String, Error = Error>> {
+                    let action = ipc_msg
+                        .map_err(|(error_code, description, _err)| {
+                            Error::IpcDecodeError(format!(
+                                "Failed decoding the auth request: {} - {:?}",
+                                error_code, description
+                            ))
+                        })
+                        .and_then(|ipc_msg| auth_action(ipc_msg, allow));
+
+                    match action {
+                        Ok(action) => auth_action_future(&client, action),
+                        Err(err) => Box::new(future::err(err)),
+                    }
+                },
+            )
+    })
+}
+
+/// # Get the list of applications authorised by this account
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and get the list of all the applications that have
+/// been authorised so far.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::{create_acc, authorise_app};
+/// use safe_auth::{log_in, authed_apps};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account which has been used
+/// /// to authorise some application already:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// # let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// # authorise_app(&authenticator, auth_req, &|_| true).unwrap();
+/// /// Get the list of authorised apps
+/// let authed_apps = authed_apps(&authenticator);
+/// match authed_apps {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+///```
+pub fn authed_apps(authenticator: &Authenticator) -> Result<Vec<AuthedAppsList>, Error> {
+    debug!("Attempting to fetch list of authorised apps...");
+    let authed_apps = auth_run_helper(authenticator, authed_apps_future).map_err(|err| {
+        Error::NetworkError(format!(
+            "Failed to fetch the list of authorised apps: {}",
+            err
+        ))
+    })?;
+
+    Ok(authed_apps)
+}
+
+/// # Get the list of applications authorised by this account without blocking
+///
+/// Same as `authed_apps` but it returns a future which resolves with the
+/// list of applications authorised, so many operations can be driven concurrently.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use futures::Future;
+/// use safe_auth::{log_in, authed_apps_async};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account's secret and password:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let authed_apps = authed_apps_async(&authenticator).wait();
+/// match authed_apps {
+///    Ok(apps) => assert!(apps.is_empty()), // This should pass
+///    Err(_) => assert!(false)
+/// }
+///```
+pub fn authed_apps_async(authenticator: &Authenticator) -> AsyncResult<Vec<AuthedAppsList>> {
+    debug!("Attempting to fetch list of authorised apps...");
+    run_async(authenticator, move |client| {
+        authed_apps_future(client).map_err(|err| {
+            Error::NetworkError(format!(
+                "Failed to fetch the list of authorised apps: {}",
+                err
+            ))
+        })
+    })
+}
+
+/// # Revoke all permissions from an application
+///
+/// Using an account already created, you can log in to
+/// the SAFE Network and revoke all permissions previously granted to an
+/// application by providing its ID.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::{create_acc, authorise_app};
+/// use safe_auth::{log_in, revoke_app};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account which has been used
+/// /// to authorise some application already:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// # let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// # authorise_app(&authenticator, auth_req, &|_| true).unwrap();
+/// /// Revoke all permissions from app with ID `net.maidsafe.cli`
+/// let revoked = revoke_app(&authenticator, String::from("net.maidsafe.cli"));
+/// match revoked {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+///
+/// ## Error Example
+/// ```
+/// # use safe_auth::{create_acc, authorise_app};
+/// use safe_auth::{log_in, revoke_app};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account which has been used
+/// /// to authorise some application already:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// /// Try to revoke permissions with an incorrect app ID
+/// let revoked = revoke_app(&authenticator, String::from("invalid-app-id"));
+/// match revoked {
+///    Ok(_) => assert!(false), // This should not pass
+///    Err(err) => assert!(err.to_string().contains("UnknownApp"))
+/// }
+///```
+pub fn revoke_app(authenticator: &Authenticator, app_id: String) -> Result<(), Error> {
+    revoke(authenticator, app_id, None)
+}
+
+/// # Revoke all permissions from an application, unless it's cancelled
+///
+/// Same as `revoke_app` but it fails with a `Cancelled` error as soon as `cancel` is cancelled.
+/// Note the requests already sent to the network are not undone, thus the permissions
+/// may still end up being revoked.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::create_acc;
+/// use safe_auth::{log_in, revoke_app_cancellable, CancellationToken, Error};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// let cancel = CancellationToken::new();
+/// cancel.cancel();
+/// let revoked = revoke_app_cancellable(&authenticator, String::from("net.maidsafe.cli"), &cancel);
+/// match revoked {
+///    Err(Error::Cancelled(_)) => assert!(true), // This should pass
+///    _ => assert!(false)
+/// }
+///```
+pub fn revoke_app_cancellable(
+    authenticator: &Authenticator,
+    app_id: String,
+    cancel: &CancellationToken,
+) -> Result<(), Error> {
+    revoke(authenticator, app_id, Some(cancel))
+}
+
+fn revoke(
+    authenticator: &Authenticator,
+    app_id: String,
+    cancel: Option<&CancellationToken>,
+) -> Result<(), Error> {
+    run_with_timeout(authenticator, Operation::Revoke, cancel, move |client| {
+        revoke_future(client, app_id)
+    })?
+    .map_err(revoke_error)
+}
+
+/// # Revoke all permissions from an application without blocking
+///
+/// Same as `revoke_app` but it returns a future which resolves once the
+/// permissions were revoked, so many operations can be driven concurrently.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::{create_acc, authorise_app};
+/// use futures::Future;
+/// use safe_auth::{log_in, revoke_app_async};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account which has been used
+/// /// to authorise some application already:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// # let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// # authorise_app(&authenticator, auth_req, &|_| true).unwrap();
+/// /// Revoke all permissions from app with ID `net.maidsafe.cli`
+/// let revoked = revoke_app_async(&authenticator, String::from("net.maidsafe.cli")).wait();
+/// match revoked {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn revoke_app_async(authenticator: &Authenticator, app_id: String) -> AsyncResult<()> {
+    run_async(authenticator, move |client| {
+        revoke_future(client, app_id).map_err(revoke_error)
+    })
+}
+
+/// # Revoke all permissions from several applications
+///
+/// Revokes the permissions of each of the applications, one after the other,
+/// reporting to `progress` before each of them is revoked. It stops at the
+/// first application which fails to be revoked.
+///
+/// ## Example
+/// ```
+/// # use safe_auth::{create_acc, authorise_app};
+/// use safe_auth::{log_in, revoke_apps, OperationProgress};
+/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
+/// /// Using an already existing account which has been used
+/// /// to authorise some application already:
+/// let my_secret = "mysecretstring";
+/// let my_password = "mypassword";
+/// # let my_secret = &(random_str());
+/// # let my_password = &(random_str());
+/// # let secret_key = threshold_crypto::SecretKey::random();
+/// # let sk: String = bincode::serialize(&threshold_crypto::serde_impl::SerdeSecret(&secret_key)).unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+/// # let sk = &sk;
+/// # safe_core::client::test_create_balance(&secret_key.into(), "10".parse().unwrap()).unwrap();
+/// # create_acc(sk, my_secret, my_password).unwrap();
+/// let authenticator = log_in(my_secret, my_password).unwrap();
+/// # let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// # authorise_app(&authenticator, auth_req, &|_| true).unwrap();
+/// let app_ids = vec![String::from("net.maidsafe.cli")];
+/// let revoked = revoke_apps(&authenticator, &app_ids, &|progress: &OperationProgress| {
+///     println!("[{}/{}] {}", progress.completed, progress.total, progress.step);
+/// });
+/// match revoked {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn revoke_apps(
+    authenticator: &Authenticator,
+    app_ids: &[String],
+    progress: &dyn ProgressReporter,
+) -> Result<(), Error> {
+    revoke_many(authenticator, app_ids, progress, None)
+}
+
+/// # Revoke all permissions from several applications, unless it's cancelled
+///
+/// Same as `revoke_apps` but it fails with a `Cancelled` error as soon as `cancel` is
+/// cancelled, leaving the remaining applications authorised.
+pub fn revoke_apps_cancellable(
+    authenticator: &Authenticator,
+    app_ids: &[String],
+    progress: &dyn ProgressReporter,
+    cancel: &CancellationToken,
+) -> Result<(), Error> {
+    revoke_many(authenticator, app_ids, progress, Some(cancel))
+}
+
+fn revoke_many(
+    authenticator: &Authenticator,
+    app_ids: &[String],
+    progress: &dyn ProgressReporter,
+    cancel: Option<&CancellationToken>,
+) -> Result<(), Error> {
+    let total = app_ids.len();
+    let mut pacer = Pacer::current();
+    for (completed, app_id) in app_ids.iter().enumerate() {
+        let step = format!("Revoking application '{}'", app_id);
+        report(progress, REVOKE_APPS_OP, &step, completed, total);
+        pacer.run(|| revoke(authenticator, app_id.clone(), cancel))?;
+    }
+    let step = format!("{} application(s) revoked", total);
+    report(progress, REVOKE_APPS_OP, &step, total, total);
+    Ok(())
+}
+
+/// # Compare two lists of authorised apps
+///
+/// Returns the apps which are only in the `new` list (`added`), the ones which are
+/// only in the `old` list (`removed`), and the ones which are in both but with
+/// a different set of permissions (`changed`).
+pub fn diff_authed_apps(old: &[AuthedAppsList], new: &[AuthedAppsList]) -> AuthedAppsDiff {
+    let mut diff = AuthedAppsDiff::default();
+    for old_app in old.iter() {
+        match new.iter().find(|new_app| new_app.app.id == old_app.app.id) {
+            Some(new_app) => {
+                if sorted_perms(old_app) != sorted_perms(new_app) {
+                    diff.changed.push(AppPermsChange {
+                        app: new_app.app.clone(),
+                        old_perms: old_app.perms.clone(),
+                        new_perms: new_app.perms.clone(),
+                    });
+                }
+            }
+            None => diff.removed.push(old_app.clone()),
+        }
+    }
+
+    for new_app in new.iter() {
+        if !old.iter().any(|old_app| old_app.app.id == new_app.app.id) {
+            diff.added.push(new_app.clone());
+        }
+    }
+
+    diff
+}
+
+fn sorted_perms(authed_app: &AuthedAppsList) -> Vec<(String, ContainerPermissions)> {
+    let mut perms = authed_app.perms.clone();
+    perms.sort();
+    perms
+}
+
+// The permissions of an app of a snapshot to grant again
+#[derive(Debug)]
+pub(crate) struct Regrant {
+    pub(crate) app: AppExchangeInfo,
+    // The permissions requested for each container, the ones of the snapshot and the current ones
+    pub(crate) containers: HashMap<String, ContainerPermissions>,
+    // If the app is not authorised anymore, and whether it had its own container
+    pub(crate) register: bool,
+    pub(crate) app_container: bool,
+}
+
+impl Regrant {
+    // The encoded authorisation request granting the permissions
+    pub(crate) fn req(&self, req_id: u32) -> Result<String, Error> {
+        let req = if self.register {
+            // The snapshots don't have the app's permissions on the account's balance,
+            // the app has to request them itself if it needs them
+            IpcReq::Auth(AuthReq {
+                app: self.app.clone(),
+                app_container: self.app_container,
+                app_permissions: AppPermissions {
+                    transfer_coins: false,
+                    perform_mutations: false,
+                    get_balance: false,
+                },
+                containers: self.containers.clone(),
+            })
+        } else {
+            IpcReq::Containers(ContainersReq {
+                app: self.app.clone(),
+                containers: self.containers.clone(),
+            })
+        };
+        encode_msg(&IpcMsg::Req {
+            req_id,
+            request: req,
+        })
+        .map_err(|err| {
+            Error::AuthenticatorError(format!(
+                "Failed to encode the authorisation request of '{}': {:?}",
+                self.app.id, err
+            ))
+        })
+    }
+}
+
+// The apps of the snapshot which are not authorised anymore, or are missing some permissions
+pub(crate) fn regrants(snapshot: &[AuthedAppsList], live: &[AuthedAppsList]) -> Vec<Regrant> {
+    let mut regrants = vec![];
+    for authed_app in snapshot.iter() {
+        let live_app = live.iter().find(|live| live.app.id == authed_app.app.id);
+        let mut containers = HashMap::new();
+        for (cont, perms) in authed_app.perms.iter() {
+            let live_perms = live_app
+                .and_then(|live| live.perms.iter().find(|(name, _)| name == cont))
+                .map(|(_, perms)| perms.clone())
+                .unwrap_or_default();
+            if !perms.is_subset(&live_perms) {
+                let _ = containers.insert(cont.clone(), perms | &live_perms);
+            }
+        }
+
+        match live_app {
+            Some(_) if containers.is_empty() => {}
+            Some(_) => regrants.push(Regrant {
+                app: authed_app.app.clone(),
+                containers,
+                register: false,
+                app_container: false,
+            }),
+            None => {
+                // The app's own container is created by the authenticator when it's requested
+                let own_container = app_container_name(&authed_app.app.id);
+                let app_container = containers.remove(&own_container).is_some();
+                regrants.push(Regrant {
+                    app: authed_app.app.clone(),
+                    containers,
+                    register: true,
+                    app_container,
+                });
+            }
+        }
+    }
+    regrants
+}
+
+// Grants again the permissions of the apps of a backup which aren't authorised anymore,
+// or are missing some of them, returning the differences found before granting them
+pub(crate) fn restore_authed_apps(
+    backend: &dyn AuthBackend,
+    backup: &[AuthedAppsList],
+) -> Result<AuthedAppsDiff, Error> {
+    let current_apps = backend.authed_apps()?;
+    let diff = diff_authed_apps(backup, &current_apps);
+    for (req_id, regrant) in regrants(backup, &current_apps).iter().enumerate() {
+        let _ = backend.authorise_app(&regrant.req(req_id as u32)?, &|_| true)?;
+        debug!("Restored the permissions of app '{}'", regrant.app.id);
+    }
+    Ok(diff)
+}
+
+// Authorisation to be performed for a decoded request, once it was allowed or denied
+pub(crate) enum AuthAction {
+    Deny(u32),
+    App(u32, AuthReq),
+    Containers(u32, ContainersReq),
+    Unregistered(u32),
+    ShareMData(u32, ShareMDataReq),
+}
+
+// Helper function to find out the authorisation to be performed for a decoded
+// request, querying the `allow` callback if the request shall be allowed
+pub(crate) fn auth_action(
+    ipc_msg: IpcMsg,
+    allow: &'static AuthAllowPrompt,
+) -> Result<AuthAction, Error> {
+    match ipc_msg {
+        IpcMsg::Req {
+            request: IpcReq::Auth(app_auth_req),
+            req_id,
+        } => {
+            info!("Request was recognised as a general app auth request");
+            debug!("Decoded request (req_id={:?}): {:?}", req_id, app_auth_req);
+            debug!("Checking if the authorisation shall be allowed...");
+            if !allow(IpcReq::Auth(app_auth_req.clone())) {
+                debug!("Authorisation request was denied!");
+                return Ok(AuthAction::Deny(req_id));
+            }
+
+            debug!("Allowed!. Attempting to authorise application...");
+            Ok(AuthAction::App(req_id, app_auth_req))
+        }
+        IpcMsg::Req {
+            request: IpcReq::Containers(cont_req),
+            req_id,
+        } => {
+            info!("Request was recognised as a containers auth request");
+            debug!("Decoded request (req_id={:?}): {:?}", req_id, cont_req);
+
+            debug!("Checking if the containers authorisation shall be allowed...");
+            if !allow(IpcReq::Containers(cont_req.clone())) {
+                debug!("Authorisation request was denied!");
+                return Ok(AuthAction::Deny(req_id));
+            }
+
+            debug!("Allowed!. Attempting to grant permissions to the containers...");
+            Ok(AuthAction::Containers(req_id, cont_req))
+        }
+        IpcMsg::Req {
+            request: IpcReq::Unregistered(user_data),
+            req_id,
+        } => {
+            info!("Request was recognised as an unregistered auth request");
+            debug!("Decoded request (req_id={:?}): {:?}", req_id, user_data);
+
+            debug!("Checking if the authorisation shall be allowed...");
+            if !allow(IpcReq::Unregistered(user_data)) {
+                debug!("Authorisation request was denied!");
+                return Ok(AuthAction::Deny(req_id));
+            }
+
+            debug!("Allowed!");
+            Ok(AuthAction::Unregistered(req_id))
+        }
+        IpcMsg::Req {
+            request: IpcReq::ShareMData(share_mdata_req),
+            req_id,
+        } => {
+            info!("Request was recognised as a share MD auth request");
+            debug!(
+                "Decoded request (req_id={:?}): {:?}",
+                req_id, share_mdata_req
+            );
+
+            debug!("Checking if the authorisation to share a MD shall be allowed...");
+            if !allow(IpcReq::ShareMData(share_mdata_req.clone())) {
+                debug!("Authorisation request was denied!");
+                return Ok(AuthAction::Deny(req_id));
+            }
+
+            debug!("Allowed!. Attempting to grant permissions to the MD...");
+            Ok(AuthAction::ShareMData(req_id, share_mdata_req))
+        }
+        IpcMsg::Resp { .. } | IpcMsg::Revoked { .. } | IpcMsg::Err(..) => {
+            Err(Error::IpcDecodeError(String::from(
+                "The request was not recognised as a valid auth request",
+            )))
+        }
+    }
+}
+
+// Helper function to generate an app authorisation response
+pub(crate) fn gen_auth_denied_response(req_id: u32) -> Result<String, Error> {
+    debug!("Encoding auth denied response...");
+    let resp = encode_msg(&IpcMsg::Resp {
+        req_id,
+        response: IpcResp::Auth(Err(IpcError::AuthDenied)),
+    })
+    .map_err(|err| {
+        Error::AuthenticatorError(format!("Failed to encode the response: {:?}", err))
+    })?;
+    debug!("Returning auth response generated");
+
+    Ok(resp)
+}
+
+// Helper function to generate an app authorisation response
+fn gen_auth_response(
+    authenticator: &Authenticator,
+    req_id: u32,
+    auth_req: AuthReq,
+    cancel: Option<&CancellationToken>,
+) -> Result<String, Error> {
+    run_with_timeout(authenticator, Operation::Authorise, cancel, move |client| {
+        auth_response_future(client, req_id, auth_req)
+    })?
+    .map_err(response_error)
+}
+
+// Helper functions returning the future which generates each type of authorisation response
+fn auth_response_future(
+    client: &AuthClient,
+    req_id: u32,
+    auth_req: AuthReq,
+) -> Box<dyn Future<Item = String, Error = AuthError>> {
+    Box::new(
+        authenticate(client, auth_req).and_then(move |auth_granted| {
+            debug!("Encoding response...");
+            let resp = encode_msg(&IpcMsg::Resp {
+                req_id,
+                response: IpcResp::Auth(Ok(auth_granted)),
+            })?;
+            debug!("Returning auth response generated");
+
+            Ok(resp)
+        }),
+    )
+}
+
+// Helper function to generate a containers authorisation response
+fn gen_cont_auth_response(
+    authenticator: &Authenticator,
+    req_id: u32,
+    cont_req: ContainersReq,
+    cancel: Option<&CancellationToken>,
+) -> Result<String, Error> {
+    run_with_timeout(authenticator, Operation::Authorise, cancel, move |client| {
+        cont_auth_response_future(client, req_id, cont_req)
+    })?
+    .map_err(response_error)
+}
+
+fn cont_auth_response_future(
+    client: &AuthClient,
+    req_id: u32,
+    cont_req: ContainersReq,
+) -> Box<dyn Future<Item = String, Error = AuthError>> {
+    let permissions = cont_req.containers.clone();
+    let app_id = cont_req.app.id.clone();
+    let c2 = client.clone();
+    let c3 = client.clone();
+    let c4 = client.clone();
+
+    let future = config::get_app(client, &app_id)
+        .and_then(move |app| {
+            let sign_pk = app.keys.public_key();
+            update_container_perms(&c2, permissions, sign_pk).map(move |perms| (app, perms))
+        })
+        .and_then(move |(app, mut perms)| {
+            let app_keys = app.keys;
+            access_container::fetch_entry(&c3, &app_id, app_keys.clone()).then(move |res| {
+                let version = match res {
+                    // Updating an existing entry
+                    Ok((version, Some(mut existing_perms))) => {
+                        for (key, val) in perms {
+                            let _ = existing_perms.insert(key, val);
+                        }
+                        perms = existing_perms;
+                        version + 1
+                    }
+
+                    // Adding a new access container entry
+                    Ok((_, None))
+                    | Err(AuthError::CoreError(CoreError::DataError(
+                        safe_nd::Error::NoSuchEntry,
+                    ))) => 0,
+
+                    // Error has occurred while trying to get an
+                    // existing entry
+                    Err(e) => return Err(e),
+                };
+                Ok((version, app_id, app_keys, perms))
+            })
+        })
+        .and_then(move |(version, app_id, app_keys, perms)| {
+            access_container::put_entry(&c4, &app_id, &app_keys, &perms, version)
+        })
+        .and_then(move |_| {
+            debug!("Encoding response...");
+            let resp = encode_msg(&IpcMsg::Resp {
+                req_id,
+                response: IpcResp::Containers(Ok(())),
+            })?;
+
+            debug!("Returning containers auth response generated");
+            Ok(resp)
+        })
+        .map_err(AuthError::from);
+    Box::new(future)
+}
+
+// Helper function to generate an unregistered authorisation response
+fn gen_unreg_auth_response(req_id: u32) -> Result<String, Error> {
+    let bootstrap_cfg = safe_core_client::bootstrap_config()
+        .map_err(|err| Error::IoError(format!("Failed to read the bootstrap config: {}", err)))?;
+
+    debug!("Encoding response... {:?}", bootstrap_cfg);
+    let resp = encode_msg(&IpcMsg::Resp {
+        req_id,
+        response: IpcResp::Unregistered(Ok(bootstrap_cfg)),
+    })
+    .map_err(|err| {
+        Error::AuthenticatorError(format!("Failed to encode the response: {:?}", err))
+    })?;
+
+    debug!("Returning unregistered auth response generated");
+    Ok(resp)
+}
+
+// Helper function to generate an authorisation response for sharing MD
+fn gen_shared_md_auth_response(
+    authenticator: &Authenticator,
+    req_id: u32,
+    share_mdata_req: ShareMDataReq,
+    cancel: Option<&CancellationToken>,
+) -> Result<String, Error> {
+    run_with_timeout(authenticator, Operation::Authorise, cancel, move |client| {
+        shared_md_auth_response_future(client, req_id, share_mdata_req)
+    })?
+    .map_err(response_error)
+}
+
+fn shared_md_auth_response_future(
+    client: &AuthClient,
+    req_id: u32,
+    share_mdata_req: ShareMDataReq,
+) -> Box<dyn Future<Item = String, Error = AuthError>> {
+    let client_cloned0 = client.clone();
+    let client_cloned1 = client.clone();
+    let future = config::get_app(client, &share_mdata_req.app.id).and_then(move |app_info| {
+        let user = app_info.keys.public_key();
+        let num_mdata = share_mdata_req.mdata.len();
+        stream::iter_ok(share_mdata_req.mdata)
+            .map(move |mdata| {
+                client_cloned0
+                    .get_seq_mdata_shell(mdata.name, mdata.type_tag)
+                    .map(|md| (md.version(), mdata))
+            })
+            .buffer_unordered(num_mdata)
+            .map(move |(version, mdata)| {
+                let address = MDataAddress::Seq {
+                    name: mdata.name,
+                    tag: mdata.type_tag,
+                };
+                client_cloned1.set_mdata_user_permissions(address, user, mdata.perms, version + 1)
+            })
+            .buffer_unordered(num_mdata)
+            .map_err(AuthError::from)
+            .for_each(|()| Ok(()))
+            .and_then(move |()| {
+                debug!("Encoding response...");
+                let resp = encode_msg(&IpcMsg::Resp {
+                    req_id,
+                    response: IpcResp::ShareMData(Ok(())),
+                })?;
+
+                debug!("Returning shared MD auth response generated");
+                Ok(resp)
+            })
+    });
+    Box::new(future)
+}
+
+// Helper function to perform the authorisation once it was allowed or denied,
+// resolving with the response to be sent to the app
+fn auth_action_future(
+    client: &AuthClient,
+    action: AuthAction,
+) -> Box<dyn Future<Item = String, Error = Error>> {
+    match action {
+        AuthAction::Deny(req_id) => Box::new(gen_auth_denied_response(req_id).into_future()),
+        AuthAction::App(req_id, app_auth_req) => {
+            Box::new(auth_response_future(client, req_id, app_auth_req).map_err(response_error))
+        }
+        AuthAction::Containers(req_id, cont_req) => {
+            Box::new(cont_auth_response_future(client, req_id, cont_req).map_err(response_error))
+        }
+        AuthAction::Unregistered(req_id) => Box::new(gen_unreg_auth_response(req_id).into_future()),
+        AuthAction::ShareMData(req_id, share_mdata_req) => Box::new(
+            shared_md_auth_response_future(client, req_id, share_mdata_req).map_err(response_error),
+        ),
+    }
+}
+
+fn response_error(err: AuthError) -> Error {
+    Error::AuthenticatorError(format!("Failed to generate response: {}", err))
+}
+
+// Helper function to fetch the list of authorised apps from the access container
+fn authed_apps_future(
+    client: &AuthClient,
+) -> Box<dyn Future<Item = Vec<AuthedAppsList>, Error = AuthError>> {
+    let c2 = client.clone();
+    let c3 = client.clone();
+    let future = config::list_apps(client)
+        .map(move |(_, auth_cfg)| (c2.access_container(), auth_cfg))
+        .and_then(move |(access_container, auth_cfg)| {
+            c3.list_seq_mdata_entries(access_container.name(), access_container.type_tag())
+                .map_err(From::from)
+                .map(move |entries| (access_container, entries, auth_cfg))
+        })
+        .and_then(move |(access_container, entries, auth_cfg)| {
+            let nonce = access_container
+                .nonce()
+                .ok_or_else(|| AuthError::from("No nonce on access container's MDataInfo"))?;
+
+            let mut apps = Vec::new();
+            for app in auth_cfg.values() {
+                let key = access_container_enc_key(&app.info.id, &app.keys.enc_key, nonce)?;
+
+                // Empty entry means it has been deleted.
+                let entry = match entries.get(&key) {
+                    Some(entry) if !entry.data.is_empty() => Some(entry),
+                    _ => None,
+                };
+
+                let mut cont_perms = Vec::new();
+                if let Some(entry) = entry {
+                    let plaintext = symmetric_decrypt(&entry.data, &app.keys.enc_key)?;
+                    let app_access = deserialize::<AccessContainerEntry>(&plaintext)?;
+
+                    for (key, (_mdata_info, perms)) in app_access.into_iter() {
+                        cont_perms.push((key, perms));
+                    }
+
+                    apps.push(AuthedAppsList {
+                        app: app.info.clone(),
+                        perms: cont_perms,
+                    });
+                }
+            }
+
+            debug!("Returning list of authorised applications: {:?}", apps);
+            Ok(apps)
+        })
+        .map_err(AuthError::from);
+    Box::new(future)
+}
+
+// Helper function to revoke all the permissions granted to an app
+fn revoke_future(
+    client: &AuthClient,
+    app_id: String,
+) -> Box<dyn Future<Item = (), Error = AuthError>> {
+    Box::new(
+        safe_authenticator_revoke_app(client, &app_id).and_then(move |_| {
+            debug!("Application sucessfully revoked: {}", app_id);
+            Ok(())
+        }),
+    )
+}
+
+fn revoke_error(err: AuthError) -> Error {
+    Error::AuthenticatorError(format!("Failed to revoke permissions: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        authed_apps, authed_apps_async, authorise_app, authorise_app_async, diff_authed_apps,
+        regrants, revoke_app, revoke_app_async, revoke_apps, AuthedAppsDiff, AuthedAppsList,
+    };
+    use crate::account::create_acc;
+    use crate::backend::{AuthBackend, FakeBackend};
+    use crate::errors::Error;
+    use crate::progress::OperationProgress;
+    use crate::test_utils::{
+        gen_random_sk_hex, random_str, APP_AUTH_REQ, APP_ID, APP_NAME, CONT_AUTH_REQ,
+    };
+    use futures::Future;
+    use safe_core::client::test_create_balance;
+    use safe_core::ipc::req::{AppExchangeInfo, IpcReq};
+    use safe_core::ipc::Permission;
+    use safe_nd::{ClientFullId, Coins};
+    use std::collections::{BTreeSet, HashMap};
+    use std::str::FromStr;
+    use std::sync::Mutex;
+
+    #[test]
+    fn authorise_apps_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("20").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+
+        // fail to authorise app with invalid request
+        let invalid_auth_req = "fddfds";
+        let auth_response = authorise_app(&auth, invalid_auth_req, &|_| true);
+        match auth_response {
+            Ok(_) => panic!("It should have failed to authorise"),
+            Err(err) => assert_eq!(
+                err,
+                Error::IpcDecodeError(
+                    "Failed to decode the auth request string: EncodeDecodeError".to_string()
+                )
+            ),
+        }
+
+        // successfully authorise a registered app auth request
+        let auth_response = authorise_app(&auth, APP_AUTH_REQ, &|_| true);
+        match auth_response {
+            Ok(res) => assert!(!res.is_empty()),
+            Err(err) => std::panic::panic_any(err),
+        }
+
+        // successfully authorise an unregistered app auth request
+        /*let unreg_auth_req =
+            "bAAAAAAFVMRTRUAQAAAACMAAAAAAAAAAANZSXILTNMFUWI43BMZSS45DFON2C4YLVORUGK3TUNFRWC5DPOIXGG3DJFZUWIAI";
+        let unreg_auth_res = "bAEAAAAFVMRTRUAQAAAAAAAAAAAAQAAAAAAAAAAAOAAAAAAAAAAADCMRXFYYC4MBOGE5DSOJZHEAAAAAAAAAAAAAB";
+        let auth_response = authorise_app(&auth, unreg_auth_req, &|_| true);
+        match auth_response {
+            Ok(res) => assert_eq!(res, unreg_auth_res),
+            Err(err) => panic!(err),
+        }*/
+
+        // successfully authorise containers request
+        let cont_auth_res = "bAEAAAADLZ663OAIAAAAAAAAAAAAQ";
+        let auth_response = authorise_app(&auth, CONT_AUTH_REQ, &|_| true);
+        match auth_response {
+            Ok(res) => assert_eq!(res, cont_auth_res),
+            Err(err) => std::panic::panic_any(err),
+        }
+
+        // fail to authorise share MD request for an inexisting MD
+        let shared_md_auth_req = "bAAAAAAFG5WDFKAYAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSACAAAAAAAAAAACATQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAI";
+        let auth_response = authorise_app(&auth, shared_md_auth_req, &|_| true);
+        match auth_response {
+            Ok(_) => panic!("It should have failed to authorise to share MD"),
+            Err(err) => assert_eq!(
+                err,
+                Error::AuthenticatorError(
+                    "Failed to generate response: Core error: Data error -> Requested data not found"
+                        .to_string()
+                )
+            ),
+        }
+
+        // fail to authorise containers request for an inexisting container
+        let invalid_cont_auth_req = "bAAAAAAG5SZSKYAIAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSACAAAAAAAAAAABAAAAAAAAAAAAX3JNZ3GC3DJMQBAAAAAAAAAAAABAAAAAAQAAAAAC";
+        let auth_response = authorise_app(&auth, invalid_cont_auth_req, &|_| true);
+        match auth_response {
+            Ok(_) => panic!("It should have failed to authorise invalid container request"),
+            Err(err) => assert_eq!(
+                err,
+                Error::AuthenticatorError(
+                    "Failed to generate response: \'_invalid\' not found in the access container"
+                        .to_string()
+                )
+            ),
+        }
+
+        // fail to authorise app with invalid request due to inexisting container in the list of requested perms
+        /* TODO: this doesn't fail as it was expected
+        let invalid_app_auth_req = "bAAAAAAF5Q66DAAAAAAACMAAAAAAAAAAANZSXILTNMFUWI43BMZSS45DFON2C4YLVORUGK3TUNFRWC5DPOIXGG3DJFZUWIAILAAAAAAAAAAAF65DFON2F643DN5YGKGYAAAAAAAAAABJHK43UEBAXK5DIMVXHI2LDMF2G64RAINGESICUMVZXIEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAIAAAAAAAAAAAF62LOOZQWY2LEAEAAAAAAAAAAAAAAAAAAC";
+        let auth_response = authorise_app(&auth, invalid_app_auth_req, &|_| true);
+        match auth_response {
+            Ok(_) => panic!("It should have failed to authorise app request"),
+            Err(err) => assert_eq!(err, "Failed to generate response: \'_invalid_container\' not found in the access container"),
+        }*/
+    }
+
+    #[test]
+    fn deny_authorisation_reqs_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("30").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+
+        // verify app info passed to allow/deny callback for auth requests, and verify the AuthDenied response
+        let auth_denied_encoded_response = "bAEAAAACNZZQ4WAAAAAAACAAAAAAAAAAAAE";
+        let auth_response = authorise_app(&auth, APP_AUTH_REQ, &|auth_req| {
+            let (app_exchange_info, containers) = match auth_req {
+                IpcReq::Auth(app_auth_req) => {
+                    assert_eq!(app_auth_req.app_container, false);
+                    (app_auth_req.app, app_auth_req.containers)
+                }
+                _ => panic!("Auth req info not received"),
+            };
+            assert_eq!(app_exchange_info.id, APP_ID);
+            assert_eq!(app_exchange_info.name, APP_NAME);
+            assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
+            assert_eq!(containers, HashMap::new());
+            false
+        });
+        match auth_response {
+            Ok(res) => assert_eq!(res, auth_denied_encoded_response),
+            Err(_) => panic!("It should have returned an AuthDenied response rather than erroing"),
+        };
+
+        authorise_app(&auth, APP_AUTH_REQ, &|_| true).expect("Failed to authorise an app");
+        let auth_denied_encoded_response = "bAEAAAADLZ663OAAAAAAACAAAAAAAAAAAAE";
+        let auth_response = authorise_app(&auth, CONT_AUTH_REQ, &|authed_apps_res| {
+            let (app_exchange_info, containers) = match authed_apps_res {
+                IpcReq::Containers(cont_req) => (cont_req.app, cont_req.containers),
+                _ => panic!("Containers auth req info not received"),
+            };
+
+            assert_eq!(app_exchange_info.id, APP_ID);
+            assert_eq!(app_exchange_info.name, APP_NAME);
+            assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
+
+            let mut music_perms = BTreeSet::new();
+            music_perms.insert(Permission::Insert);
+            music_perms.insert(Permission::Update);
+
+            let mut public_perms = BTreeSet::new();
+            public_perms.insert(Permission::Read);
+
+            let mut cont_perms = HashMap::new();
+            cont_perms.insert("_music".to_string(), music_perms);
+            cont_perms.insert("_public".to_string(), public_perms);
+            assert_eq!(containers, cont_perms);
+
+            false
+        });
+        match auth_response {
+            Ok(res) => assert_eq!(res, auth_denied_encoded_response),
+            Err(err) => panic!(
+                "It should have returned an AuthDenied response rather than erroing: {:?}",
+                err
+            ),
+        }
+    }
+
+    #[test]
+    fn authed_apps_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("40").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+
+        // list of authorised apps shall be empty with a freshly created account
+        let authed_apps_res = authed_apps(&auth);
+        match authed_apps_res {
+            Ok(authed_vec) => assert_eq!(authed_vec.len(), 0), // This should pass
+            Err(_) => panic!("It should have retieved the list of authorised apps"),
+        }
+
+        // after authorising an app it is returned in the list retrieved by authed_apps
+        authorise_app(&auth, APP_AUTH_REQ, &|_| true)
+            .expect("Failed to authorise an app before calling authed_apps");
+        let authed_apps_res = authed_apps(&auth);
+        match authed_apps_res {
+            Ok(authed_vec) => {
+                assert_eq!(authed_vec.len(), 1);
+                let app_exchange_info = &authed_vec[0].app;
+                assert_eq!(app_exchange_info.id, APP_ID);
+                assert_eq!(app_exchange_info.name, APP_NAME);
+                assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
+                assert!(authed_vec[0].perms.is_empty());
+            }
+            Err(_) => panic!("It should have retrieved the list of authorised apps"),
+        }
+
+        // after authorising a containers auth request it is returned in the permissions list retrieved by authed_apps
+        authorise_app(&auth, CONT_AUTH_REQ, &|_| true)
+            .expect("Failed to authorise containers auth req before calling authed_apps");
+        let authed_apps_res = authed_apps(&auth);
+        match authed_apps_res {
+            Ok(mut authed_vec) => {
+                assert_eq!(authed_vec.len(), 1);
+                let app_exchange_info = &authed_vec[0].app;
+                assert_eq!(app_exchange_info.id, APP_ID);
+                assert_eq!(app_exchange_info.name, APP_NAME);
+                assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
+
+                let mut public_perms = BTreeSet::new();
+                public_perms.insert(Permission::Read);
+
+                let mut music_perms = BTreeSet::new();
+                music_perms.insert(Permission::Insert);
+                music_perms.insert(Permission::Update);
+
+                let mut cont_perms = vec![
+                    ("_public".to_string(), public_perms),
+                    ("_music".to_string(), music_perms),
+                ];
+                cont_perms.sort();
+                authed_vec[0].perms.sort();
+                assert_eq!(authed_vec[0].perms, cont_perms);
+            }
+            Err(_) => panic!("It should have retrieved the list of authorised apps"),
+        }
+    }
+
+    #[test]
+    fn revoke_app_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("50").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+
+        // after revoking an app it is removed from the list retrieved by authed_apps
+        authorise_app(&auth, APP_AUTH_REQ, &|_| true)
+            .expect("Failed to authorise an app before calling authed_apps");
+        revoke_app(&auth, APP_ID.to_string())
+            .expect("Failed to revoke the previously authorised app");
+
+        let authed_apps_res = authed_apps(&auth);
+        match authed_apps_res {
+            Ok(authed_vec) => assert_eq!(authed_vec.len(), 0),
+            Err(_) => panic!("It should have retrieved the list of authorised apps"),
+        }
+
+        // the progress of a batch revocation is reported before each app is revoked
+        authorise_app(&auth, APP_AUTH_REQ, &|_| true)
+            .expect("Failed to authorise an app before calling revoke_apps");
+        let reported = Mutex::new(Vec::new());
+        let app_ids = vec![APP_ID.to_string()];
+        unwrap!(revoke_apps(
+            &auth,
+            &app_ids,
+            &|progress: &OperationProgress| { unwrap!(reported.lock()).push(progress.clone()) }
+        ));
+        let reported = unwrap!(reported.into_inner());
+        assert_eq!(reported.len(), 2);
+        assert_eq!(reported[0].completed, 0);
+        assert_eq!(reported[0].total, 1);
+        assert!(reported[1].is_finished());
+
+        // it stops at the first app which fails to be revoked
+        let app_ids = vec!["invalid-app-id".to_string(), APP_ID.to_string()];
+        let reported = Mutex::new(Vec::new());
+        let revoked = revoke_apps(&auth, &app_ids, &|progress: &OperationProgress| {
+            unwrap!(reported.lock()).push(progress.clone())
+        });
+        assert!(revoked.is_err());
+        assert_eq!(unwrap!(reported.into_inner()).len(), 1);
+    }
+
+    #[test]
+    fn async_operations_tests() {
+        let (sk, secret_key) = &gen_random_sk_hex();
+        test_create_balance(
+            &ClientFullId::from(secret_key.clone()),
+            Coins::from_str("70").unwrap(),
+        )
+        .unwrap();
+        let my_secret = &(random_str());
+        let my_password = &(random_str());
+
+        let auth = unwrap!(create_acc(sk, my_secret, my_password));
+
+        // fail to authorise app with invalid request
+        match authorise_app_async(&auth, "fddfds", &|_| true).wait() {
+            Ok(_) => panic!("It should have failed to authorise the invalid request"),
+            Err(err) => assert_eq!(
+                err,
+                Error::IpcDecodeError(
+                    "Failed to decode the auth request string: EncodeDecodeError".to_string()
+                )
+            ),
+        }
+
+        // several operations can be driven concurrently
+        let auth_response = authorise_app_async(&auth, APP_AUTH_REQ, &|_| true);
+        let (auth_response, _) = unwrap!(auth_response.join(authed_apps_async(&auth)).wait());
+        assert!(!auth_response.is_empty());
+        let authed_vec = unwrap!(authed_apps_async(&auth).wait());
+        assert_eq!(authed_vec.len(), 1);
+        assert_eq!(authed_vec[0].app.id, APP_ID);
+
+        unwrap!(revoke_app_async(&auth, APP_ID.to_string()).wait());
+        let authed_vec = unwrap!(authed_apps_async(&auth).wait());
+        assert_eq!(authed_vec.len(), 0);
+    }
+
+    #[test]
+    fn diff_authed_apps_tests() {
+        let gen_app = |id: &str, perms: Vec<(String, BTreeSet<Permission>)>| AuthedAppsList {
+            app: AppExchangeInfo {
+                id: id.to_string(),
+                scope: None,
+                name: APP_NAME.to_string(),
+                vendor: "MaidSafe.net Ltd".to_string(),
+            },
+            perms,
+        };
+
+        let mut read_perms = BTreeSet::new();
+        read_perms.insert(Permission::Read);
+        let mut insert_perms = BTreeSet::new();
+        insert_perms.insert(Permission::Insert);
+
+        let old = vec![
+            gen_app("app.removed", vec![]),
+            gen_app(
+                "app.unchanged",
+                vec![("_public".to_string(), read_perms.clone())],
+            ),
+            gen_app(
+                "app.changed",
+                vec![("_music".to_string(), read_perms.clone())],
+            ),
+        ];
+        let new = vec![
+            gen_app("app.unchanged", vec![("_public".to_string(), read_perms)]),
+            gen_app("app.changed", vec![("_music".to_string(), insert_perms)]),
+            gen_app("app.added", vec![]),
+        ];
+
+        let diff = diff_authed_apps(&old, &new);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].app.id, "app.removed");
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].app.id, "app.added");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].app.id, "app.changed");
+
+        assert!(diff_authed_apps(&old, &old).is_empty());
+
+        // the diff can be serialised and deserialised back
+        let serialised = unwrap!(serde_json::to_string(&diff));
+        let deserialised: AuthedAppsDiff = unwrap!(serde_json::from_str(&serialised));
+        assert_eq!(deserialised.removed[0].app.id, "app.removed");
+        assert_eq!(deserialised.added[0].app.id, "app.added");
+        assert_eq!(deserialised.changed[0].new_perms, diff.changed[0].new_perms);
+    }
+
+    fn authed_app(id: &str, perms: &[(&str, &[Permission])]) -> AuthedAppsList {
+        AuthedAppsList {
+            app: AppExchangeInfo {
+                id: id.to_string(),
+                scope: None,
+                name: id.to_string(),
+                vendor: String::from("MaidSafe.net Ltd"),
+            },
+            perms: perms
+                .iter()
+                .map(|(cont, perms)| (cont.to_string(), perms.iter().cloned().collect()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn regrants_tests() {
+        let snapshot = vec![
+            authed_app(
+                "net.maidsafe.cli",
+                &[("_public", &[Permission::Read, Permission::Insert])],
+            ),
+            authed_app(
+                "org.example.chat",
+                &[
+                    ("_documents", &[Permission::Read]),
+                    ("apps/org.example.chat", &[Permission::Read]),
+                ],
+            ),
+            authed_app("org.example.notes", &[("_documents", &[Permission::Read])]),
+        ];
+        let live = vec![
+            authed_app(
+                "net.maidsafe.cli",
+                &[("_public", &[Permission::Read, Permission::Update])],
+            ),
+            authed_app("org.example.notes", &[("_documents", &[Permission::Read])]),
+        ];
+
+        let regrants = regrants(&snapshot, &live);
+        assert_eq!(regrants.len(), 2);
+        // The current permissions are kept
+        assert!(!regrants[0].register);
+        assert_eq!(
+            regrants[0].containers["_public"],
+            [Permission::Read, Permission::Insert, Permission::Update]
+                .iter()
+                .cloned()
+                .collect()
+        );
+        assert!(regrants[1].register);
+        assert!(regrants[1].app_container);
+        assert_eq!(regrants[1].containers.len(), 1);
+
+        // Granted through the same requests as the apps' own ones
+        let backend = FakeBackend::with_apps(live);
+        for (req_id, regrant) in regrants.iter().enumerate() {
+            let req = unwrap!(regrant.req(req_id as u32));
+            let _ = unwrap!(backend.authorise_app(&req, &|_| true));
+        }
+        let authed_apps = unwrap!(backend.authed_apps());
+        assert_eq!(authed_apps.len(), 3);
+        assert_eq!(authed_apps[0].perms[0].1.len(), 3);
+        assert_eq!(authed_apps[2].app.id, "org.example.chat");
+    }
+}
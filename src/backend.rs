@@ -0,0 +1,382 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::account::{
+    acc_info, acc_keys, backup_account, restore_account, AccountInfo, AccountKeys,
+};
+use crate::apps::{
+    auth_action, authed_apps, authorise_app, gen_auth_denied_response, restore_authed_apps,
+    revoke_app, AuthAction, AuthedAppsDiff, AuthedAppsList,
+};
+use crate::decode::decode_auth_req;
+use crate::errors::Error;
+use crate::inspect::app_container_name;
+use crate::AuthAllowPrompt;
+use log::debug;
+use maidsafe_utilities::serialisation::{deserialise, serialise};
+use safe_authenticator::Authenticator;
+use safe_core::ipc::req::{AppExchangeInfo, ContainerPermissions};
+use safe_core::ipc::resp::IpcResp;
+use safe_core::ipc::{encode_msg, IpcMsg, Permission};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// Prefix of the responses generated by the `FakeBackend` for the requests it can't encode
+const FAKE_RESPONSE_PREFIX: &str = "fake-auth-granted";
+
+/// # Operations on a logged in account
+///
+/// The CLI and the Authenticator service perform all the operations on the account
+/// through this trait. It's implemented by `Authenticator`, which sends them to the
+/// SAFE Network, and by `FakeBackend`, which keeps the account in memory so the code
+/// using it can be tested without a network or a mock vault. The backends are shared by
+/// threads, e.g. a `SharedBackend` lists the apps while the user decides on an authorisation.
+pub trait AuthBackend: Send + Sync {
+    /// Same as `authorise_app`
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error>;
+    /// Same as `authed_apps`
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error>;
+    /// Same as `revoke_app`
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error>;
+    /// Same as `acc_info`
+    fn acc_info(&self) -> Result<AccountInfo, Error>;
+    /// Same as `acc_keys`
+    fn acc_keys(&self) -> Result<AccountKeys, Error>;
+    /// Same as `backup_account`
+    fn backup_account(&self) -> Result<Vec<u8>, Error>;
+    /// Same as `restore_account`
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error>;
+}
+
+impl AuthBackend for Authenticator {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        authorise_app(self, req, allow)
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        authed_apps(self)
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        revoke_app(self, app_id.to_string())
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        acc_info(self)
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        acc_keys(self)
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        backup_account(self)
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        restore_account(self, backup)
+    }
+}
+
+// So the backends chosen at run time can be wrapped, e.g. with a `WarningBackend`
+impl AuthBackend for Box<dyn AuthBackend> {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        (**self).authorise_app(req, allow)
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        (**self).authed_apps()
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        (**self).revoke_app(app_id)
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        (**self).acc_info()
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        (**self).acc_keys()
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        (**self).backup_account()
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        (**self).restore_account(backup)
+    }
+}
+
+/// # In-memory account
+///
+/// An `AuthBackend` which doesn't connect to any network. The auth requests are decoded
+/// and allowed or denied as usual, and the apps granted are kept in memory.
+///
+/// Note: the responses to the app and unregistered auth requests can't be used to
+/// connect to the network, they only tell the id of the request granted,
+/// e.g. `fake-auth-granted:<req_id>:<app_id>`.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{AuthBackend, FakeBackend};
+/// let backend = FakeBackend::default();
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// let auth_response = backend.authorise_app(auth_req, &|_| true);
+/// match auth_response {
+///    Ok(_) => assert_eq!(backend.authed_apps().unwrap().len(), 1), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub struct FakeBackend {
+    apps: Mutex<Vec<AuthedAppsList>>,
+    info: AccountInfo,
+    keys: AccountKeys,
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        let containers = [
+            "_documents",
+            "_downloads",
+            "_music",
+            "_pictures",
+            "_public",
+            "_publicNames",
+            "_videos",
+        ];
+        Self {
+            apps: Mutex::new(vec![]),
+            info: AccountInfo {
+                balance: "0".to_string(),
+                containers: containers.iter().map(|name| name.to_string()).collect(),
+            },
+            keys: AccountKeys {
+                public_key: "00".repeat(48),
+                owner_key: "00".repeat(48),
+                encryption_key: "00".repeat(32),
+            },
+        }
+    }
+}
+
+impl FakeBackend {
+    /// Creates an account which has already authorised the apps provided
+    pub fn with_apps(apps: Vec<AuthedAppsList>) -> Self {
+        Self {
+            apps: Mutex::new(apps),
+            ..Self::default()
+        }
+    }
+
+    // Sets the permissions of an app to the containers, authorising the app if it wasn't yet
+    fn grant(
+        &self,
+        app: AppExchangeInfo,
+        containers: HashMap<String, ContainerPermissions>,
+        register: bool,
+    ) -> Result<(), Error> {
+        let mut apps = unwrap!(self.apps.lock());
+        let index = match apps.iter().position(|authed| authed.app.id == app.id) {
+            Some(index) => index,
+            None if register => {
+                apps.push(AuthedAppsList { app, perms: vec![] });
+                apps.len() - 1
+            }
+            None => {
+                return Err(Error::AuthenticatorError(format!(
+                    "Failed to generate response: app '{}' is not registered",
+                    app.id
+                )))
+            }
+        };
+        let perms = &mut apps[index].perms;
+        for (name, container_perms) in containers {
+            perms.retain(|(existing, _)| *existing != name);
+            perms.push((name, container_perms));
+        }
+        perms.sort();
+        Ok(())
+    }
+}
+
+impl AuthBackend for FakeBackend {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        let ipc_msg = decode_auth_req(req)?;
+        match auth_action(ipc_msg, allow)? {
+            AuthAction::Deny(req_id) => gen_auth_denied_response(req_id),
+            AuthAction::App(req_id, mut auth_req) => {
+                let app_id = auth_req.app.id.clone();
+                if auth_req.app_container {
+                    // Like the authenticator, the app is given all the permissions on its own
+                    let _ = auth_req.containers.insert(
+                        app_container_name(&app_id),
+                        [
+                            Permission::Read,
+                            Permission::Insert,
+                            Permission::Update,
+                            Permission::Delete,
+                            Permission::ManagePermissions,
+                        ]
+                        .iter()
+                        .cloned()
+                        .collect(),
+                    );
+                }
+                self.grant(auth_req.app, auth_req.containers, true)?;
+                debug!("Fake backend authorised app '{}'", app_id);
+                Ok(format!("{}:{}:{}", FAKE_RESPONSE_PREFIX, req_id, app_id))
+            }
+            AuthAction::Containers(req_id, cont_req) => {
+                self.grant(cont_req.app, cont_req.containers, false)?;
+                encode_response(req_id, IpcResp::Containers(Ok(())))
+            }
+            AuthAction::Unregistered(req_id) => {
+                Ok(format!("{}:{}:unregistered", FAKE_RESPONSE_PREFIX, req_id))
+            }
+            AuthAction::ShareMData(req_id, _) => {
+                encode_response(req_id, IpcResp::ShareMData(Ok(())))
+            }
+        }
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        Ok(unwrap!(self.apps.lock()).clone())
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        let mut apps = unwrap!(self.apps.lock());
+        let count = apps.len();
+        apps.retain(|authed| authed.app.id != app_id);
+        if apps.len() == count {
+            return Err(Error::AuthenticatorError(format!(
+                "Failed to revoke permissions: app '{}' is not registered",
+                app_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        Ok(self.info.clone())
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        Ok(self.keys.clone())
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        serialise(&*unwrap!(self.apps.lock())).map_err(|err| {
+            Error::AuthenticatorError(format!("Failed to create the account backup: {}", err))
+        })
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        let backup_apps = deserialise::<Vec<AuthedAppsList>>(&backup).map_err(|err| {
+            Error::InvalidInput(format!("Failed to decode the account backup: {}", err))
+        })?;
+        restore_authed_apps(self, &backup_apps)
+    }
+}
+
+// Helper function to encode a response to a request
+fn encode_response(req_id: u32, resp: IpcResp) -> Result<String, Error> {
+    encode_msg(&IpcMsg::Resp {
+        req_id,
+        response: resp,
+    })
+    .map_err(|err| Error::AuthenticatorError(format!("Failed to encode the response: {:?}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthBackend, FakeBackend};
+    use crate::errors::Error;
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID, CONT_AUTH_REQ};
+
+    #[test]
+    fn fake_backend_authorise_tests() {
+        let backend = FakeBackend::default();
+
+        // Containers can't be granted to an app not authorised yet
+        match backend.authorise_app(CONT_AUTH_REQ, &|_| true) {
+            Err(Error::AuthenticatorError(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // Denied requests leave the account untouched
+        let denied = unwrap!(backend.authorise_app(APP_AUTH_REQ, &|_| false));
+        assert!(!denied.is_empty());
+        assert!(unwrap!(backend.authed_apps()).is_empty());
+
+        let granted = unwrap!(backend.authorise_app(APP_AUTH_REQ, &|_| true));
+        assert!(granted.ends_with(APP_ID));
+        let apps = unwrap!(backend.authed_apps());
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app.id, APP_ID);
+        assert!(apps[0].perms.is_empty());
+
+        let _ = unwrap!(backend.authorise_app(CONT_AUTH_REQ, &|_| true));
+        let apps = unwrap!(backend.authed_apps());
+        let names: Vec<&str> = apps[0]
+            .perms
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["_music", "_public"]);
+
+        match backend.authorise_app("invalid-auth-req", &|_| true) {
+            Err(Error::IpcDecodeError(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fake_backend_revoke_and_backup_tests() {
+        let backend = FakeBackend::default();
+        let _ = unwrap!(backend.authorise_app(APP_AUTH_REQ, &|_| true));
+        let backup = unwrap!(backend.backup_account());
+        assert!(unwrap!(backend.restore_account(backup.clone())).is_empty());
+
+        unwrap!(backend.revoke_app(APP_ID));
+        assert!(unwrap!(backend.authed_apps()).is_empty());
+        match backend.revoke_app(APP_ID) {
+            Err(Error::AuthenticatorError(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let diff = unwrap!(backend.restore_account(backup.clone()));
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].app.id, APP_ID);
+        // the app was authorised again
+        assert!(unwrap!(backend.restore_account(backup)).is_empty());
+
+        match backend.restore_account(vec![1, 2, 3]) {
+            Err(Error::InvalidInput(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fake_backend_restore_into_empty_account_test() {
+        let backend = FakeBackend::default();
+        let _ = unwrap!(backend.authorise_app(APP_AUTH_REQ, &|_| true));
+        let _ = unwrap!(backend.authorise_app(CONT_AUTH_REQ, &|_| true));
+        let backup = unwrap!(backend.backup_account());
+
+        // the apps and their containers are recreated in an account without any app
+        let empty = FakeBackend::default();
+        let diff = unwrap!(empty.restore_account(backup));
+        assert_eq!(diff.removed.len(), 1);
+        let apps = unwrap!(empty.authed_apps());
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app.id, APP_ID);
+        assert_eq!(apps[0].perms, unwrap!(backend.authed_apps())[0].perms);
+    }
+}
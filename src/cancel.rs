@@ -0,0 +1,149 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::account::{AccountInfo, AccountKeys};
+use crate::apps::{
+    authorise_app_cancellable, revoke_app_cancellable, AuthedAppsDiff, AuthedAppsList,
+};
+use crate::backend::AuthBackend;
+use crate::errors::Error;
+use crate::AuthAllowPrompt;
+use log::debug;
+use safe_authenticator::Authenticator;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// # Cancel the operations in progress
+///
+/// Passed to the `_cancellable` versions of the operations which connect to the network,
+/// e.g. `log_in_cancellable`, which fail with a `Cancelled` error as soon as it's cancelled.
+/// It can be cloned, e.g. to cancel it from another thread, with all the clones
+/// being cancelled together. Once cancelled it can't be reset.
+///
+/// ## Example
+/// ```
+/// use safe_auth::CancellationToken;
+/// let cancel = CancellationToken::new();
+/// let cancel_from_handler = cancel.clone();
+/// cancel_from_handler.cancel();
+/// assert!(cancel.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        debug!("Cancelling the operations in progress");
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// # Cancel the operations performed on a logged in account
+///
+/// An `AuthBackend` which authorises and revokes the apps with the `_cancellable`
+/// versions of the operations, thus they fail with a `Cancelled` error once the
+/// token provided is cancelled. The rest of the operations are not cancellable.
+pub struct CancellableAuthenticator {
+    authenticator: Authenticator,
+    cancel: CancellationToken,
+}
+
+impl CancellableAuthenticator {
+    pub fn new(authenticator: Authenticator, cancel: CancellationToken) -> Self {
+        Self {
+            authenticator,
+            cancel,
+        }
+    }
+}
+
+impl AuthBackend for CancellableAuthenticator {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        authorise_app_cancellable(&self.authenticator, req, allow, &self.cancel)
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.authenticator.authed_apps()
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        revoke_app_cancellable(&self.authenticator, app_id.to_string(), &self.cancel)
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.authenticator.acc_info()
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.authenticator.acc_keys()
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.authenticator.backup_account()
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.authenticator.restore_account(backup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CancellationToken;
+    use crate::errors::Error;
+    use crate::timeouts::{with_timeout, Operation};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn cancel_operation_in_progress_test() {
+        let cancel = CancellationToken::new();
+        let cancel_from_thread = cancel.clone();
+        let _ = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            cancel_from_thread.cancel();
+        });
+
+        // An operation which never finishes on its own, like a login to an unreachable network
+        let started = Instant::now();
+        let result = with_timeout(Operation::Login, Some(&cancel), || {
+            thread::sleep(Duration::from_secs(60));
+            Ok(())
+        });
+        match result {
+            Err(Error::Cancelled(_)) => assert!(started.elapsed() < Duration::from_secs(5)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cancelled_token_tests() {
+        let cancel = CancellationToken::new();
+        // Operations finishing before it's cancelled are not affected
+        assert_eq!(
+            unwrap!(with_timeout(Operation::Revoke, Some(&cancel), || Ok(1))),
+            1
+        );
+
+        cancel.cancel();
+        match with_timeout(Operation::Revoke, Some(&cancel), || -> Result<(), Error> {
+            panic!("It should not have been run")
+        }) {
+            Err(Error::Cancelled(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The timeline of the `account activity` command, the oldest first. It merges the local audit
+// log, i.e. the receipts of the decisions on the auth requests of the profile, with the apps
+// revoked and the grants expired which the Authenticator service of the profile kept in its
+// event history, when its `event_history` persists it, and with what the network tells of the
+// account.
+//
+// The network doesn't keep a history of the operations on an account, only their outcome: the
+// apps authorised without a receipt, e.g. from another device or before the receipts were
+// recorded, are listed first, at an unknown time.
+
+use super::instances::profile_entry;
+use super::receipts::{receipts, Receipt};
+use super::EVENTS_AREA;
+use crate::ipc::ReqKind;
+use crate::storage::storage;
+use crate::{AuthEvent, AuthedAppsList, Error, EventHistory, HistoryEntry};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Allowed,
+    Denied,
+    Revoked,
+    // A temporary grant expired, and its app was revoked
+    Expired,
+    // An app authorised on the network, without a receipt
+    Authorised,
+}
+
+// Where an activity is known from
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivitySource {
+    Audit,
+    Service,
+    Network,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Activity {
+    /// Seconds since the UNIX epoch, none if it isn't known
+    pub timestamp: Option<u64>,
+    pub activity: ActivityKind,
+    /// The id of the app, none for the unregistered requests
+    pub app_id: Option<String>,
+    /// The kind of the auth request decided on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ReqKind>,
+    /// Who else than the user made the decision, e.g. "guardian"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approver: Option<String>,
+    pub source: ActivitySource,
+}
+
+// The timeline of the profile, given the apps authorised on the network
+pub fn account_activity(
+    profile: &str,
+    authed_apps: &[AuthedAppsList],
+) -> Result<Vec<Activity>, Error> {
+    // All the events persisted are read, whatever the capacity of the service
+    let events = EventHistory::stored(
+        usize::MAX,
+        storage()?,
+        EVENTS_AREA,
+        &profile_entry(profile, "json"),
+    )?
+    .after(None, usize::MAX)
+    .events;
+    Ok(timeline(&receipts(profile)?, &events, authed_apps))
+}
+
+fn timeline(
+    receipts: &[Receipt],
+    events: &[HistoryEntry],
+    authed_apps: &[AuthedAppsList],
+) -> Vec<Activity> {
+    let mut activities: Vec<Activity> = receipts
+        .iter()
+        .map(|receipt| Activity {
+            timestamp: Some(receipt.timestamp),
+            activity: if receipt.decision == "allowed" {
+                ActivityKind::Allowed
+            } else {
+                ActivityKind::Denied
+            },
+            app_id: receipt.app_id.clone(),
+            kind: receipt.kind,
+            approver: receipt.approver.clone(),
+            source: ActivitySource::Audit,
+        })
+        .collect();
+
+    // The events of the service persisted before their time was recorded can't be placed
+    activities.extend(events.iter().filter_map(|entry| {
+        let (activity, app_id) = match &entry.event {
+            AuthEvent::Revoked { app_id } => (ActivityKind::Revoked, app_id),
+            AuthEvent::GrantExpired { app_id } => (ActivityKind::Expired, app_id),
+            _ => return None,
+        };
+        Some(Activity {
+            timestamp: Some(entry.time?),
+            activity,
+            app_id: Some(app_id.clone()),
+            kind: None,
+            approver: None,
+            source: ActivitySource::Service,
+        })
+    }));
+
+    let allowed: HashSet<&str> = receipts
+        .iter()
+        .filter(|receipt| receipt.decision == "allowed")
+        .filter_map(|receipt| receipt.app_id.as_deref())
+        .collect();
+    activities.extend(
+        authed_apps
+            .iter()
+            .filter(|authed| !allowed.contains(authed.app.id.as_str()))
+            .map(|authed| Activity {
+                timestamp: None,
+                activity: ActivityKind::Authorised,
+                app_id: Some(authed.app.id.clone()),
+                kind: None,
+                approver: None,
+                source: ActivitySource::Network,
+            }),
+    );
+
+    // The unknown times first, the sort keeping the order of the ones at the same time
+    activities.sort_by_key(|activity| activity.timestamp);
+    activities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{timeline, ActivityKind, ActivitySource};
+    use crate::cli::receipts::Receipt;
+    use crate::ipc::ReqKind;
+    use crate::{AuthEvent, AuthedAppsList, HistoryEntry};
+    use safe_core::ipc::req::AppExchangeInfo;
+
+    fn authed(id: &str) -> AuthedAppsList {
+        AuthedAppsList {
+            app: AppExchangeInfo {
+                id: id.to_string(),
+                scope: None,
+                name: id.to_string(),
+                vendor: String::from("Example"),
+            },
+            perms: vec![],
+        }
+    }
+
+    fn receipt(app_id: Option<&str>, decision: &str, timestamp: u64) -> Receipt {
+        Receipt {
+            request_hash: String::from("00"),
+            kind: Some(ReqKind::Auth),
+            decision: decision.to_string(),
+            timestamp,
+            public_key: String::new(),
+            signature: String::new(),
+            raw_request: None,
+            app_id: app_id.map(String::from),
+            approver: None,
+        }
+    }
+
+    fn revoked(app_id: &str, time: Option<u64>) -> HistoryEntry {
+        HistoryEntry {
+            cursor: 1,
+            time,
+            event: AuthEvent::Revoked {
+                app_id: app_id.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn timeline_tests() {
+        let receipts = vec![
+            receipt(Some("chat"), "allowed", 100),
+            receipt(Some("mail"), "denied", 300),
+            receipt(None, "allowed", 300),
+        ];
+        let events = vec![
+            revoked("chat", Some(200)),
+            // Persisted before the time was recorded
+            revoked("mail", None),
+            HistoryEntry {
+                cursor: 2,
+                time: Some(250),
+                event: AuthEvent::Locked,
+            },
+        ];
+        let apps = vec![authed("chat"), authed("legacy")];
+
+        let activities = timeline(&receipts, &events, &apps);
+        let summary: Vec<(Option<u64>, ActivityKind, Option<&str>, ActivitySource)> = activities
+            .iter()
+            .map(|activity| {
+                (
+                    activity.timestamp,
+                    activity.activity,
+                    activity.app_id.as_deref(),
+                    activity.source,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    None,
+                    ActivityKind::Authorised,
+                    Some("legacy"),
+                    ActivitySource::Network
+                ),
+                (
+                    Some(100),
+                    ActivityKind::Allowed,
+                    Some("chat"),
+                    ActivitySource::Audit
+                ),
+                (
+                    Some(200),
+                    ActivityKind::Revoked,
+                    Some("chat"),
+                    ActivitySource::Service
+                ),
+                (
+                    Some(300),
+                    ActivityKind::Denied,
+                    Some("mail"),
+                    ActivitySource::Audit
+                ),
+                (
+                    Some(300),
+                    ActivityKind::Allowed,
+                    None,
+                    ActivitySource::Audit
+                ),
+            ]
+        );
+        assert_eq!(activities[1].kind, Some(ReqKind::Auth));
+
+        let serialised = unwrap!(serde_json::to_string(&activities[2]));
+        assert_eq!(
+            serialised,
+            r#"{"timestamp":200,"activity":"revoked","app_id":"chat","source":"service"}"#
+        );
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// Drawing the user's attention to the prompts of the auth requests, e.g. the ones of the
+// Authenticator service running in a background terminal, as set in the `attention` field of
+// the config file. While prompts are waiting for an answer, the terminal's title tells how
+// many, and the previous title is restored once they are all answered. The terminal's bell
+// is rung, and the urgency hint of its X11 window set, when a prompt starts waiting. On Windows,
+// the prompts of the Authenticator service raise a toast notification too, whose buttons decide
+// on the request (see the `toast` module).
+//
+// The escape sequences are written to stderr, and only if it's the process' own console.
+
+use super::io::Console;
+use super::toast::{Toast, Toasts};
+use crate::PendingDecision;
+use log::debug;
+use serde::Deserialize;
+use std::env;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+const BELL: &str = "\x07";
+// xterm's sequences to save and restore the window title, supported by most terminals
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub struct AttentionSettings {
+    #[serde(default)]
+    pub bell: bool,
+    #[serde(default)]
+    pub title: bool,
+    #[serde(default)]
+    pub urgent: bool,
+    #[serde(default)]
+    pub toast: bool,
+}
+
+// The number of prompts waiting, shared by the prompts of all the requests
+#[derive(Clone, Default)]
+pub struct Attention {
+    settings: AttentionSettings,
+    pending: Arc<Mutex<usize>>,
+    toasts: Option<Toasts>,
+}
+
+// Keeps the prompt counted as waiting, and its toast raised, until it's dropped
+pub struct Waiting<'a> {
+    attention: &'a Attention,
+    console: &'a Console,
+    toast: Option<Toast>,
+}
+
+impl Attention {
+    pub fn new(settings: AttentionSettings) -> Self {
+        Self {
+            settings,
+            pending: Arc::default(),
+            toasts: None,
+        }
+    }
+
+    // The toasts raised for the prompts, if the settings enable them
+    pub fn with_toasts<F: FnOnce() -> Toasts>(mut self, toasts: F) -> Self {
+        if self.settings.toast {
+            self.toasts = Some(toasts());
+        }
+        self
+    }
+
+    // The prompt waiting, its toast telling the description of the request
+    pub fn waiting<'a>(&'a self, console: &'a Console, description: &str) -> Waiting<'a> {
+        self.update(console, true);
+        if self.settings.urgent && console.is_console() {
+            set_urgency_hint();
+        }
+        Waiting {
+            attention: self,
+            console,
+            toast: self
+                .toasts
+                .as_ref()
+                .map(|toasts| toasts.raise(console, description)),
+        }
+    }
+
+    // Counts a prompt as waiting, or as answered, and updates the terminal
+    fn update(&self, console: &Console, waiting: bool) {
+        let (previous, pending) = {
+            let mut pending = match self.pending.lock() {
+                Ok(pending) => pending,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let previous = *pending;
+            *pending = if waiting {
+                previous + 1
+            } else {
+                previous.saturating_sub(1)
+            };
+            (previous, *pending)
+        };
+        if !console.is_console() {
+            return;
+        }
+        let title = tr!(console, "attention-title", count = pending);
+        let escapes = escapes(&self.settings, previous, pending, &title);
+        if !escapes.is_empty() {
+            console.err(format_args!("{}", escapes));
+        }
+    }
+}
+
+impl<'a> Waiting<'a> {
+    // The decision taken with the buttons of the toast, if any
+    pub fn decision(&mut self) -> Option<&mut PendingDecision> {
+        self.toast.as_mut().map(Toast::decision)
+    }
+}
+
+impl<'a> Drop for Waiting<'a> {
+    fn drop(&mut self) {
+        self.attention.update(self.console, false);
+    }
+}
+
+// The escape sequences to write when the number of prompts waiting changes
+fn escapes(settings: &AttentionSettings, previous: usize, pending: usize, title: &str) -> String {
+    let mut escapes = String::new();
+    if settings.title {
+        if previous == 0 && pending > 0 {
+            escapes += PUSH_TITLE;
+        }
+        if pending > 0 {
+            escapes += &format!("\x1b]2;{}\x07", title);
+        } else if previous > 0 {
+            escapes += POP_TITLE;
+        }
+    }
+    if settings.bell && pending > previous {
+        escapes += BELL;
+    }
+    escapes
+}
+
+// The terminal's window is the one set in `WINDOWID` by the X11 terminal emulators,
+// and its hint is removed by the window manager once it's focused
+fn set_urgency_hint() {
+    let window = match env::var("WINDOWID") {
+        Ok(window) => window,
+        Err(_) => return,
+    };
+    let result = Command::new("xdotool")
+        .args(["set_window", "--urgency", "1", &window])
+        .status();
+    if let Err(err) = result {
+        debug!("The urgency hint of the window couldn't be set: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escapes, Attention, AttentionSettings};
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::cli::toast::Toasts;
+    use crate::PendingDecisions;
+
+    #[test]
+    fn escapes_tests() {
+        let settings = AttentionSettings {
+            bell: true,
+            title: true,
+            urgent: false,
+            toast: false,
+        };
+        let title = "safe_auth: 1 request waiting";
+        assert_eq!(
+            escapes(&settings, 0, 1, title),
+            "\x1b[22;0t\x1b]2;safe_auth: 1 request waiting\x07\x07"
+        );
+        assert_eq!(
+            escapes(&settings, 2, 1, title),
+            "\x1b]2;safe_auth: 1 request waiting\x07"
+        );
+        assert_eq!(escapes(&settings, 1, 0, title), "\x1b[23;0t");
+
+        let settings = AttentionSettings::default();
+        assert!(escapes(&settings, 0, 1, title).is_empty());
+    }
+
+    #[test]
+    fn attention_tests() {
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        let attention = Attention::new(AttentionSettings {
+            bell: true,
+            title: true,
+            urgent: true,
+            toast: false,
+        });
+        {
+            let mut waiting = attention.waiting(&console, "A request");
+            assert_eq!(*unwrap!(attention.pending.lock()), 1);
+            assert!(waiting.decision().is_none());
+        }
+        assert_eq!(*unwrap!(attention.pending.lock()), 0);
+        // Nothing is written to streams which are not the console
+        assert!(output.stderr().is_empty());
+
+        // The toasts are raised only if the settings enable them
+        let decisions = PendingDecisions::default();
+        let attention = Attention::new(AttentionSettings::default())
+            .with_toasts(|| Toasts::new("default", decisions.clone()));
+        assert!(attention
+            .waiting(&console, "A request")
+            .decision()
+            .is_none());
+        let attention = Attention::new(AttentionSettings {
+            toast: true,
+            ..AttentionSettings::default()
+        })
+        .with_toasts(|| Toasts::new("default", decisions.clone()));
+        let mut waiting = attention.waiting(&console, "A request");
+        let id = unwrap!(waiting.decision()).id();
+        assert!(decisions.decide(id, false));
+        assert_eq!(unwrap!(waiting.decision()).decided(), Some(false));
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// What the binary was built with, printed by `--capabilities` so the wrapper tools and
+// installers can adapt to the build they find. New fields can be added, but the existing
+// ones are never removed nor change their meaning.
+
+use super::credentials::keyring_backends;
+use super::i18n::available_locales;
+use super::output::OUTPUT_FORMATS;
+use super::verification::available_verifications;
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct Capabilities {
+    pub version: &'static str,
+    /// If it connects to the mock network rather than to the SAFE Network
+    pub mock_network: bool,
+    /// If the `--mock` flag is available, to use an in-memory account at runtime
+    pub mock_account: bool,
+    /// The transports the Authenticator service (`--daemon`) can be reached with,
+    /// empty if it was built without it
+    pub daemon_transports: Vec<&'static str>,
+    pub headless: bool,
+    pub output_formats: Vec<&'static str>,
+    /// The formats of the progress written to stderr with `--progress`
+    pub progress_formats: Vec<&'static str>,
+    /// Where the credentials can be read from, see the `credentials` setting of the config file
+    pub credential_providers: Vec<&'static str>,
+    /// The keyrings the credentials can be read from with the `keyring` credential provider,
+    /// they can still be read from any other with a `command` one
+    pub keyring_backends: Vec<&'static str>,
+    /// Second factors which can be required to log in
+    pub two_factor_methods: Vec<&'static str>,
+    /// If tables are printed with borders and colours
+    pub pretty_tables: bool,
+    /// The languages the prompts and messages are available in, see `--lang`
+    pub languages: Vec<&'static str>,
+    /// How the user can be verified before allowing requests and revoking apps, see the
+    /// `verify_user` setting of the config file
+    pub user_verifications: Vec<&'static str>,
+    /// If the `--strict` flag is available, to rely on the output contract
+    pub strict: bool,
+    /// If the `--quiet` flag is available, to only get the data on stdout
+    pub quiet: bool,
+    /// If the full-screen dashboard of the `tui` command is available
+    pub dashboard: bool,
+    /// If the `--non-interactive` flag is available, to fail rather than prompting the user
+    pub non_interactive: bool,
+    /// If the `--color` flag is available, to force or disable the colours of the output
+    pub color: bool,
+}
+
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        mock_network: cfg!(feature = "mock-network"),
+        mock_account: true,
+        daemon_transports: if cfg!(feature = "daemon") {
+            vec!["http"]
+        } else {
+            vec![]
+        },
+        headless: true,
+        output_formats: OUTPUT_FORMATS.to_vec(),
+        progress_formats: vec!["json"],
+        credential_providers: vec!["env", "file", "command", "keyring", "prompt"],
+        keyring_backends: keyring_backends(),
+        two_factor_methods: vec![],
+        pretty_tables: cfg!(feature = "pretty"),
+        languages: available_locales(),
+        user_verifications: available_verifications(),
+        strict: true,
+        quiet: true,
+        dashboard: cfg!(feature = "dashboard"),
+        non_interactive: true,
+        color: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capabilities;
+
+    #[test]
+    fn capabilities_tests() {
+        let serialised = unwrap!(serde_json::to_value(capabilities()));
+        assert_eq!(serialised["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(serialised["mock_network"], cfg!(feature = "mock-network"));
+        assert_eq!(serialised["output_formats"][2], "json");
+        assert_eq!(serialised["output_formats"][3], "yaml");
+        assert!(serialised["two_factor_methods"].as_array().is_some());
+        assert_eq!(serialised["dashboard"], cfg!(feature = "dashboard"));
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The colours of the output, chosen with `--color`: 'auto' (default) only colours what's written
+// to a terminal, so the output piped to another program is plain text, 'always' and 'never'
+// force it either way. The `NO_COLOR` environment variable, or a `dumb` terminal, disables them
+// in 'auto'.
+//
+// The tables are coloured by prettytable, from the styles of their cells, e.g. the permissions
+// granted in green and the dangerous ones in red, and the prompts' messages with ANSI escapes.
+
+use safe_core::ipc::req::ContainerPermissions;
+use safe_core::ipc::Permission;
+use std::env;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(choice: &str) -> Result<Self, Self::Err> {
+        match choice {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(format!(
+                "'{}' is not a colour choice, it must be 'auto', 'always' or 'never'",
+                other
+            )),
+        }
+    }
+}
+
+impl ColorChoice {
+    // Whether the output written to the stream is coloured, `terminal` telling if it's one
+    pub fn colors(self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => terminal && colors_allowed(),
+        }
+    }
+}
+
+// Whether the environment lets the output be coloured, see https://no-color.org
+fn colors_allowed() -> bool {
+    env::var_os("NO_COLOR").is_none() && env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    // What's granted or allowed
+    Green,
+    // What's denied or dangerous
+    Red,
+}
+
+// Whether the permissions are shown as dangerous, i.e. they include `ManagePermissions`, which
+// lets the app give access to the container to anyone
+pub fn is_dangerous(perms: &ContainerPermissions) -> bool {
+    perms.contains(&Permission::ManagePermissions)
+}
+
+// The text in the colour, with the ANSI escapes of the terminals
+pub fn paint(text: &str, color: Color) -> String {
+    let code = match color {
+        Color::Green => 32,
+        Color::Red => 31,
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{paint, Color, ColorChoice};
+
+    #[test]
+    fn color_choice_tests() {
+        assert_eq!(unwrap!("auto".parse::<ColorChoice>()), ColorChoice::Auto);
+        assert_eq!(unwrap!("never".parse::<ColorChoice>()), ColorChoice::Never);
+        assert!("yes".parse::<ColorChoice>().is_err());
+
+        assert!(ColorChoice::Always.colors(false));
+        assert!(!ColorChoice::Never.colors(true));
+        // Piped output is never coloured unless it's forced
+        assert!(!ColorChoice::Auto.colors(false));
+    }
+
+    #[test]
+    fn paint_tests() {
+        assert_eq!(paint("Allowed", Color::Green), "\x1b[32mAllowed\x1b[0m");
+        assert_eq!(paint("Denied", Color::Red), "\x1b[31mDenied\x1b[0m");
+    }
+}
@@ -0,0 +1,921 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The sources the account's credentials are read from. Each of them is a `CredentialProvider`,
+// and they are tried in the order set in the config file's `credentials` field, e.g.
+// `["env", {"command": ["pass", "show", "safe"]}, "prompt"]`, until one provides them.
+//
+// The `keyring` provider reads them from the system's keyring, i.e. the Secret Service on Linux
+// and the login keychain on macOS, with the `secret-tool` and `security` commands they come with,
+// so no library has to be linked. Each profile has its own service, `safe_auth.<profile>`, with
+// the secret and the password stored for its `secret` and `password` accounts.
+//
+// The credentials stored, i.e. provided without asking the user, can be given a maximum age
+// with the config file's `credential_expiry`: once they were used for longer, the user has to
+// enter them again with the interactive providers, e.g. so a keyring entry which leaked long
+// ago isn't enough to log in. The providers can't tell how old their credentials are, so the
+// time each of them was first provided is recorded in the profile's `credentials` entry, by a
+// fingerprint of the provider's name and of the credentials, which are never written. The
+// fingerprint is derived with salted PBKDF2, so the credentials can't be guessed from it any
+// faster than by trying to log in. Entering the same credentials again renews them, while the
+// stored ones which changed, e.g. after rotating the password, are new.
+
+use super::instances::profile_entry;
+use super::io::Console;
+use crate::helpers::bytes_to_hex;
+use crate::storage::{storage, Storage};
+use crate::Error;
+use hmac::Hmac;
+use log::{debug, info, warn};
+use pbkdf2::pbkdf2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CREDENTIALS_AREA: &str = "credentials";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+// The work factor of the fingerprints, recorded with them so it can be raised
+const FINGERPRINT_ROUNDS: usize = 100_000;
+const FINGERPRINT_LEN: usize = 32;
+const KEYRING_SERVICE: &str = "safe_auth";
+
+// Providers tried when the config file doesn't set them, which is how the CLI always behaved
+const DEFAULT_SOURCES: [CredentialSource; 3] = [
+    CredentialSource::Env,
+    CredentialSource::File,
+    CredentialSource::Prompt,
+];
+
+#[derive(Deserialize, Debug)]
+struct Environment {
+    safe_auth_secret: Option<String>,
+    safe_auth_password: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct LoginDetails {
+    pub secret: String,
+    pub password: String,
+}
+
+// The fields of the config file related to the credentials
+#[derive(Deserialize, Debug, Default)]
+struct CredentialsSettings {
+    secret: Option<String>,
+    password: Option<String>,
+    credentials: Option<Vec<CredentialSource>>,
+    credential_expiry: Option<CredentialExpiry>,
+}
+
+// The `credential_expiry` field of the config file, e.g.
+// `{"max_age_days": 90, "rotation_reminder": true}`
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CredentialExpiry {
+    pub max_age_days: u64,
+    // Whether the user is reminded to change their password once the credentials expired
+    #[serde(default)]
+    pub rotation_reminder: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct AgesFile {
+    salt: String,
+    // The work factor the fingerprints were derived with, none for the SHA-256 ones of the
+    // older versions
+    #[serde(default)]
+    rounds: usize,
+    // The time, in seconds since the Unix epoch, each fingerprint was first provided
+    first_provided: HashMap<String, u64>,
+}
+
+// The times the stored credentials were first provided, loaded from the profile's entry of
+// the storage
+pub struct CredentialAges {
+    stored: Option<(Arc<dyn Storage>, String)>,
+    ages: AgesFile,
+}
+
+impl CredentialAges {
+    pub fn load(profile: &str) -> Result<Self, Error> {
+        Self::load_from(storage()?, profile)
+    }
+
+    fn load_from(storage: Arc<dyn Storage>, profile: &str) -> Result<Self, Error> {
+        let entry = profile_entry(profile, "json");
+        let ages = match storage.read(CREDENTIALS_AREA, &entry)? {
+            Some(content) => serde_json::from_slice(&content).map_err(|err| {
+                Error::InvalidInput(format!(
+                    "The credentials' ages '{}' are not valid: {}",
+                    entry, err
+                ))
+            })?,
+            None => AgesFile::default(),
+        };
+        // The fingerprints derived otherwise can't be found again, their credentials are new
+        let ages = if ages.rounds == FINGERPRINT_ROUNDS {
+            ages
+        } else {
+            AgesFile::default()
+        };
+        Ok(Self {
+            stored: Some((storage, entry)),
+            ages,
+        })
+    }
+
+    // Ages which are not stored anywhere, e.g. for testing
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            stored: None,
+            ages: AgesFile::default(),
+        }
+    }
+
+    fn fingerprint(&mut self, provider: &str, details: &LoginDetails) -> String {
+        if self.ages.salt.is_empty() {
+            let salt: [u8; 16] = rand::thread_rng().gen();
+            self.ages.salt = bytes_to_hex(&salt);
+            self.ages.rounds = FINGERPRINT_ROUNDS;
+        }
+        // The lengths are given, so moving characters between the parts gives another one
+        let credentials = format!(
+            "{}:{}{}:{}{}",
+            provider.len(),
+            provider,
+            details.secret.len(),
+            details.secret,
+            details.password
+        );
+        let mut fingerprint = [0; FINGERPRINT_LEN];
+        pbkdf2::<Hmac<Sha256>>(
+            credentials.as_bytes(),
+            self.ages.salt.as_bytes(),
+            self.ages.rounds,
+            &mut fingerprint,
+        );
+        bytes_to_hex(&fingerprint)
+    }
+
+    // How long ago, in seconds, the credentials were first provided by the provider, now if
+    // they never were
+    fn age(&mut self, provider: &str, details: &LoginDetails, now: u64) -> u64 {
+        let fingerprint = self.fingerprint(provider, details);
+        let first_provided = *self.ages.first_provided.entry(fingerprint).or_insert(now);
+        now.saturating_sub(first_provided)
+    }
+
+    fn renew(&mut self, provider: &str, details: &LoginDetails, now: u64) {
+        let fingerprint = self.fingerprint(provider, details);
+        let _ = self.ages.first_provided.insert(fingerprint, now);
+    }
+
+    // Failing to record the ages only makes the credentials look newer
+    fn save(&self) {
+        let (storage, entry) = match &self.stored {
+            Some(stored) => stored,
+            None => return,
+        };
+        let result = serde_json::to_vec(&self.ages)
+            .map_err(|err| Error::AuthenticatorError(err.to_string()))
+            .and_then(|content| storage.write_private(CREDENTIALS_AREA, entry, &content));
+        if let Err(err) = result {
+            warn!(
+                "The credentials' ages couldn't be written to '{}': {}",
+                entry, err
+            );
+        }
+    }
+}
+
+// The maximum age of the stored credentials, and when they were first provided
+pub struct ExpiryPolicy {
+    pub expiry: CredentialExpiry,
+    pub ages: CredentialAges,
+}
+
+// The providers which can be set in the config file, e.g. `"env"` or `{"command": [...]}`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialSource {
+    /// The `SAFE_AUTH_SECRET` and `SAFE_AUTH_PASSWORD` environment variables
+    Env,
+    /// The `secret` and `password` fields of the config file
+    File,
+    /// The secret and password printed, one per line, by a command, e.g. one which
+    /// reads them from the system's keyring or from a password manager's agent
+    Command(Vec<String>),
+    /// The `secret` and `password` accounts of the profile's service in the system's keyring
+    Keyring,
+    /// Ask the user to enter them
+    Prompt,
+}
+
+/// A source the account's credentials can be read from
+pub trait CredentialProvider {
+    /// Name of the provider for the logs, e.g. "env"
+    fn name(&self) -> &str;
+
+    /// Returns the credentials, or `None` if this provider doesn't have them,
+    /// in which case the next provider is tried
+    fn credentials(&self, console: &Console) -> Result<Option<LoginDetails>, Error>;
+
+    /// Whether the user is asked for the credentials, which is not the case by default
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+pub struct EnvProvider;
+
+impl CredentialProvider for EnvProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn credentials(&self, _console: &Console) -> Result<Option<LoginDetails>, Error> {
+        let environment = envy::from_env::<Environment>().map_err(|err| {
+            Error::InvalidInput(format!("Failed to read the environment variables: {}", err))
+        })?;
+        // Variables set but empty are the same as not set
+        let secret = environment
+            .safe_auth_secret
+            .filter(|secret| !secret.is_empty());
+        let password = environment
+            .safe_auth_password
+            .filter(|password| !password.is_empty());
+        match (secret, password) {
+            (Some(secret), Some(password)) => {
+                info!("Using secret and password from provided ENV vars: SAFE_AUTH_SECRET and SAFE_AUTH_PASSWORD");
+                Ok(Some(LoginDetails { secret, password }))
+            }
+            (None, None) => Ok(None),
+            _ => Err(Error::CredentialsError("Both the secret and password environment variables must be set to be used for SAFE login.".to_string())),
+        }
+    }
+}
+
+pub struct FileProvider {
+    secret: Option<String>,
+    password: Option<String>,
+}
+
+impl CredentialProvider for FileProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn credentials(&self, console: &Console) -> Result<Option<LoginDetails>, Error> {
+        let (secret, password) = match (&self.secret, &self.password) {
+            (Some(secret), Some(password)) => (secret.clone(), password.clone()),
+            (None, None) => return Ok(None),
+            _ => {
+                return Err(Error::CredentialsError(
+                    "The config file must have both the secret and password fields".to_string(),
+                ))
+            }
+        };
+
+        errln!(console, "{}", tr!(console, "warn-plaintext-credentials"));
+        if secret.is_empty() {
+            return Err(Error::CredentialsError(
+                "The config files's secret field cannot be empty".to_string(),
+            ));
+        }
+        if password.is_empty() {
+            return Err(Error::CredentialsError(
+                "The config files's password field cannot be empty".to_string(),
+            ));
+        }
+        Ok(Some(LoginDetails { secret, password }))
+    }
+}
+
+pub struct CommandProvider {
+    command: Vec<String>,
+}
+
+impl CredentialProvider for CommandProvider {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    fn credentials(&self, _console: &Console) -> Result<Option<LoginDetails>, Error> {
+        let (program, args) = match self.command.split_first() {
+            Some(command) => command,
+            None => {
+                return Err(Error::InvalidInput(
+                    "The command to read the credentials from can't be empty".to_string(),
+                ))
+            }
+        };
+        info!(
+            "Reading secret and password from the output of '{}'",
+            program
+        );
+        let output = Command::new(program).args(args).output().map_err(|err| {
+            Error::IoError(format!("Failed to run the command '{}': {}", program, err))
+        })?;
+        if !output.status.success() {
+            // Its output is not reported as it may contain part of the credentials
+            return Err(Error::CredentialsError(format!(
+                "The command '{}' failed to provide the credentials ({})",
+                program, output.status
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        match (lines.next(), lines.next()) {
+            (Some(secret), Some(password)) => Ok(Some(LoginDetails {
+                secret: secret.to_string(),
+                password: password.to_string(),
+            })),
+            _ => Err(Error::CredentialsError(format!(
+                "The command '{}' must print the secret and the password, one per line",
+                program
+            ))),
+        }
+    }
+}
+
+// The keyrings the `keyring` provider reads from on this system
+pub fn keyring_backends() -> Vec<&'static str> {
+    let mut backends = vec![];
+    if cfg!(target_os = "macos") {
+        backends.push("keychain");
+    }
+    if cfg!(target_os = "linux") {
+        backends.push("secret_service");
+    }
+    backends
+}
+
+// The command the `keyring` provider reads the keyring with, none if no keyring is supported
+pub fn keyring_program() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        Some("security")
+    } else if cfg!(target_os = "linux") {
+        Some("secret-tool")
+    } else {
+        None
+    }
+}
+
+// The service the credentials of the profile are stored for in the keyring
+pub fn keyring_service(profile: &str) -> String {
+    format!("{}.{}", KEYRING_SERVICE, profile)
+}
+
+pub struct KeyringProvider {
+    service: String,
+}
+
+impl KeyringProvider {
+    // The value stored for the account, none if there is no such entry
+    fn lookup(&self, account: &str) -> Result<Option<String>, Error> {
+        let program = keyring_program().ok_or_else(|| {
+            Error::CredentialsError(
+                "No keyring is supported on this system, it can be read with a `command` credential provider instead".to_string(),
+            )
+        })?;
+        let mut command = Command::new(program);
+        if cfg!(target_os = "macos") {
+            let _ = command.args([
+                "find-generic-password",
+                "-s",
+                &self.service,
+                "-a",
+                account,
+                "-w",
+            ]);
+        } else {
+            let _ = command.args(["lookup", "service", &self.service, "account", account]);
+        }
+        let output = command.output().map_err(|err| {
+            Error::IoError(format!("Failed to run the command '{}': {}", program, err))
+        })?;
+        // `security` exits with `errSecItemNotFound` and `secret-tool` without an error message
+        // when the entry isn't stored
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match output.status.code() {
+            Some(0) => Ok(Some(
+                String::from_utf8_lossy(&output.stdout)
+                    .trim_end_matches(&['\r', '\n'][..])
+                    .to_string(),
+            )),
+            Some(44) if cfg!(target_os = "macos") => Ok(None),
+            Some(1) if stderr.trim().is_empty() => Ok(None),
+            _ => Err(Error::CredentialsError(format!(
+                "The keyring failed to provide the credentials of '{}' ({}): {}",
+                self.service,
+                output.status,
+                stderr.trim()
+            ))),
+        }
+    }
+}
+
+impl CredentialProvider for KeyringProvider {
+    fn name(&self) -> &str {
+        "keyring"
+    }
+
+    fn credentials(&self, _console: &Console) -> Result<Option<LoginDetails>, Error> {
+        info!(
+            "Reading secret and password from the keyring's service '{}'",
+            self.service
+        );
+        match (self.lookup("secret")?, self.lookup("password")?) {
+            (Some(secret), Some(password)) => Ok(Some(LoginDetails { secret, password })),
+            (None, None) => Ok(None),
+            _ => Err(Error::CredentialsError(format!(
+                "The keyring's service '{}' must have both the secret and password accounts",
+                self.service
+            ))),
+        }
+    }
+}
+
+pub struct PromptProvider;
+
+impl CredentialProvider for PromptProvider {
+    fn name(&self) -> &str {
+        "prompt"
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+
+    fn credentials(&self, console: &Console) -> Result<Option<LoginDetails>, Error> {
+        let prompt = format!("{} ", tr!(console, "prompt-secret"));
+        let secret = console.read_password(&prompt).map_err(|err| {
+            Error::IoError(format!(
+                "Failed reading 'secret' string from input: {}",
+                err
+            ))
+        })?;
+        let prompt = format!("{} ", tr!(console, "prompt-password"));
+        let password = console.read_password(&prompt).map_err(|err| {
+            Error::IoError(format!(
+                "Failed reading 'password' string from input: {}",
+                err
+            ))
+        })?;
+        Ok(Some(LoginDetails { secret, password }))
+    }
+}
+
+fn read_settings(config_file: &Option<String>) -> Result<CredentialsSettings, Error> {
+    match config_file {
+        Some(config_file_str) => {
+            let file = fs::File::open(config_file_str)
+                .map_err(|err| Error::IoError(format!("Error reading config file. {}", err)))?;
+            serde_json::from_reader(file).map_err(|err| {
+                Error::InvalidInput(format!(
+                    "Format of the config file is not valid and couldn't be parsed: {}",
+                    err
+                ))
+            })
+        }
+        None => Ok(CredentialsSettings::default()),
+    }
+}
+
+// Builds the providers set in the config file, or the default ones, reading the credentials of
+// the profile
+pub fn credential_providers(
+    config_file: &Option<String>,
+    profile: &str,
+) -> Result<Vec<Box<dyn CredentialProvider>>, Error> {
+    let mut settings = read_settings(config_file)?;
+    let sources = settings
+        .credentials
+        .take()
+        .unwrap_or_else(|| DEFAULT_SOURCES.to_vec());
+    let providers = sources
+        .into_iter()
+        .map(|source| -> Box<dyn CredentialProvider> {
+            match source {
+                CredentialSource::Env => Box::new(EnvProvider),
+                CredentialSource::File => Box::new(FileProvider {
+                    secret: settings.secret.clone(),
+                    password: settings.password.clone(),
+                }),
+                CredentialSource::Command(command) => Box::new(CommandProvider { command }),
+                CredentialSource::Keyring => Box::new(KeyringProvider {
+                    service: keyring_service(profile),
+                }),
+                CredentialSource::Prompt => Box::new(PromptProvider),
+            }
+        })
+        .collect();
+    Ok(providers)
+}
+
+// The commands of the `command` providers set in the config file
+pub fn credential_commands(config_file: &Option<String>) -> Result<Vec<Vec<String>>, Error> {
+    let settings = read_settings(config_file)?;
+    let commands = settings
+        .credentials
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|source| match source {
+            CredentialSource::Command(command) => Some(command),
+            _ => None,
+        })
+        .collect();
+    Ok(commands)
+}
+
+// Whether the `keyring` provider is set in the config file
+pub fn uses_keyring(config_file: &Option<String>) -> Result<bool, Error> {
+    Ok(read_settings(config_file)?
+        .credentials
+        .unwrap_or_default()
+        .contains(&CredentialSource::Keyring))
+}
+
+// The policy set in the config file, if any
+pub fn expiry_policy(
+    config_file: &Option<String>,
+    profile: &str,
+) -> Result<Option<ExpiryPolicy>, Error> {
+    match read_settings(config_file)?.credential_expiry {
+        Some(expiry) => Ok(Some(ExpiryPolicy {
+            expiry,
+            ages: CredentialAges::load(profile)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+// Tries each of the providers in order, until one provides the credentials. The stored ones
+// older than the policy's maximum age are entered again with the interactive providers.
+pub fn resolve_credentials(
+    providers: &[Box<dyn CredentialProvider>],
+    console: &Console,
+    policy: Option<&mut ExpiryPolicy>,
+) -> Result<LoginDetails, Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    resolve_credentials_at(providers, console, policy, now)
+}
+
+fn resolve_credentials_at(
+    providers: &[Box<dyn CredentialProvider>],
+    console: &Console,
+    policy: Option<&mut ExpiryPolicy>,
+    now: u64,
+) -> Result<LoginDetails, Error> {
+    for provider in providers {
+        // With --non-interactive the credentials must be provided without asking the user
+        if provider.is_interactive() && !console.is_interactive() {
+            debug!("The '{}' provider is not interactive", provider.name());
+            continue;
+        }
+        if let Some(details) = provider.credentials(console)? {
+            debug!("Credentials provided by the '{}' provider", provider.name());
+            check_not_empty(&details)?;
+            let policy = match policy {
+                Some(policy) if !provider.is_interactive() => policy,
+                _ => return Ok(details),
+            };
+            let age = policy.ages.age(provider.name(), &details, now);
+            if age <= policy.expiry.max_age_days * SECONDS_PER_DAY {
+                policy.ages.save();
+                return Ok(details);
+            }
+
+            errln!(
+                console,
+                "{}",
+                tr!(
+                    console,
+                    "credentials-expired",
+                    provider = provider.name(),
+                    days = age / SECONDS_PER_DAY
+                )
+            );
+            for interactive in providers
+                .iter()
+                .filter(|provider| provider.is_interactive() && console.is_interactive())
+            {
+                if let Some(entered) = interactive.credentials(console)? {
+                    check_not_empty(&entered)?;
+                    if entered == details {
+                        policy.ages.renew(provider.name(), &details, now);
+                    }
+                    policy.ages.save();
+                    if policy.expiry.rotation_reminder {
+                        errln!(console, "{}", tr!(console, "credentials-rotation-reminder"));
+                    }
+                    return Ok(entered);
+                }
+            }
+            return Err(Error::CredentialsError(format!(
+                "The credentials of the '{}' provider are older than {} days and must be \
+                 entered again, but none of the credential providers set asks for them",
+                provider.name(),
+                policy.expiry.max_age_days
+            )));
+        }
+        debug!(
+            "No credentials provided by the '{}' provider",
+            provider.name()
+        );
+    }
+
+    let names: Vec<&str> = providers.iter().map(|provider| provider.name()).collect();
+    if !console.is_interactive() {
+        return Err(Error::CredentialsError(format!(
+            "None of the credential providers set provided the secret and password without prompting for them, as --non-interactive was given: {}. Set SAFE_AUTH_SECRET and SAFE_AUTH_PASSWORD, or give them in the config file.",
+            names.join(", ")
+        )));
+    }
+    Err(Error::CredentialsError(format!(
+        "None of the credential providers set provided the secret and password: {}",
+        names.join(", ")
+    )))
+}
+
+fn check_not_empty(details: &LoginDetails) -> Result<(), Error> {
+    if details.secret.is_empty() || details.password.is_empty() {
+        return Err(Error::CredentialsError(String::from(
+            "Neither the secret nor password can be empty.",
+        )));
+    }
+    Ok(())
+}
+
+// Tries each of the providers which don't ask the user, returning the name of the first one
+// which provides the credentials, e.g. for the `status` command to tell if it can log in
+pub fn stored_credentials(
+    providers: &[Box<dyn CredentialProvider>],
+    console: &Console,
+) -> Result<Option<(String, LoginDetails)>, Error> {
+    for provider in providers
+        .iter()
+        .filter(|provider| !provider.is_interactive())
+    {
+        if let Some(details) = provider.credentials(console)? {
+            if !details.secret.is_empty() && !details.password.is_empty() {
+                return Ok(Some((provider.name().to_string(), details)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+pub fn get_login_details(
+    config_file: &Option<String>,
+    profile: &str,
+    console: &Console,
+) -> Result<LoginDetails, Error> {
+    let mut policy = expiry_policy(config_file, profile)?;
+    resolve_credentials(
+        &credential_providers(config_file, profile)?,
+        console,
+        policy.as_mut(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        keyring_service, resolve_credentials, resolve_credentials_at, stored_credentials,
+        CredentialAges, CredentialExpiry, CredentialProvider, CredentialSource,
+        CredentialsSettings, ExpiryPolicy, FileProvider, LoginDetails, PromptProvider,
+        SECONDS_PER_DAY,
+    };
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::{Error, MemoryStorage, Storage};
+    use std::sync::Arc;
+
+    struct NoCredentials;
+
+    impl CredentialProvider for NoCredentials {
+        fn name(&self) -> &str {
+            "none"
+        }
+
+        fn credentials(&self, _console: &Console) -> Result<Option<LoginDetails>, Error> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn resolve_credentials_tests() {
+        let console = Console::new(Box::new(CapturedIo::new("my-secret\nmy-password\n")));
+        let file = FileProvider {
+            secret: Some("file-secret".to_string()),
+            password: Some("file-password".to_string()),
+        };
+
+        // The first provider which has the credentials is used
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![
+            Box::new(NoCredentials),
+            Box::new(file),
+            Box::new(PromptProvider),
+        ];
+        let details = unwrap!(resolve_credentials(&providers, &console, None));
+        assert_eq!(details.secret, "file-secret");
+
+        let providers: Vec<Box<dyn CredentialProvider>> =
+            vec![Box::new(NoCredentials), Box::new(PromptProvider)];
+        let details = unwrap!(resolve_credentials(&providers, &console, None));
+        assert_eq!(
+            details,
+            LoginDetails {
+                secret: "my-secret".to_string(),
+                password: "my-password".to_string()
+            }
+        );
+
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![Box::new(NoCredentials)];
+        match resolve_credentials(&providers, &console, None) {
+            Err(Error::CredentialsError(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // A file with only one of the fields is not valid
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![Box::new(FileProvider {
+            secret: Some("file-secret".to_string()),
+            password: None,
+        })];
+        assert!(resolve_credentials(&providers, &console, None).is_err());
+    }
+
+    #[test]
+    fn non_interactive_credentials_tests() {
+        let console = Console::new(Box::new(CapturedIo::new("my-secret\nmy-password\n")));
+        console.set_non_interactive(true);
+        let file = FileProvider {
+            secret: Some("file-secret".to_string()),
+            password: Some("file-password".to_string()),
+        };
+
+        // The user is never prompted, even if the prompt comes first
+        let providers: Vec<Box<dyn CredentialProvider>> =
+            vec![Box::new(PromptProvider), Box::new(file)];
+        let details = unwrap!(resolve_credentials(&providers, &console, None));
+        assert_eq!(details.secret, "file-secret");
+
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![Box::new(PromptProvider)];
+        match resolve_credentials(&providers, &console, None) {
+            Err(Error::CredentialsError(description)) => {
+                assert!(description.contains("--non-interactive"))
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn stored_credentials_tests() {
+        let console = Console::new(Box::new(CapturedIo::new("my-secret\nmy-password\n")));
+
+        // The user is never asked for them
+        let providers: Vec<Box<dyn CredentialProvider>> =
+            vec![Box::new(NoCredentials), Box::new(PromptProvider)];
+        assert!(unwrap!(stored_credentials(&providers, &console)).is_none());
+
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![
+            Box::new(PromptProvider),
+            Box::new(FileProvider {
+                secret: Some("file-secret".to_string()),
+                password: Some("file-password".to_string()),
+            }),
+        ];
+        let (provider, details) = unwrap!(unwrap!(stored_credentials(&providers, &console)));
+        assert_eq!(provider, "file");
+        assert_eq!(details.secret, "file-secret");
+    }
+
+    #[test]
+    fn credential_expiry_tests() {
+        let file = || -> Box<dyn CredentialProvider> {
+            Box::new(FileProvider {
+                secret: Some("file-secret".to_string()),
+                password: Some("file-password".to_string()),
+            })
+        };
+        let mut policy = ExpiryPolicy {
+            expiry: CredentialExpiry {
+                max_age_days: 90,
+                rotation_reminder: true,
+            },
+            ages: CredentialAges::in_memory(),
+        };
+        let day = |days: u64| 1_000_000 + days * SECONDS_PER_DAY;
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![file(), Box::new(PromptProvider)];
+
+        // The stored credentials are used until they are older than 90 days
+        let io = CapturedIo::new("file-secret\nfile-password\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        for days in &[0, 90] {
+            let details = unwrap!(resolve_credentials_at(
+                &providers,
+                &console,
+                Some(&mut policy),
+                day(*days)
+            ));
+            assert_eq!(details.secret, "file-secret");
+        }
+        assert!(!output.stderr().contains("expired"));
+
+        // Then they are entered again, which renews them
+        let details = unwrap!(resolve_credentials_at(
+            &providers,
+            &console,
+            Some(&mut policy),
+            day(91)
+        ));
+        assert_eq!(details.secret, "file-secret");
+        assert!(output.stderr().contains(
+            "The credentials of the 'file' provider expired, they were stored 91 days ago"
+        ));
+        assert!(output.stderr().contains("changing your account's password"));
+        let details = unwrap!(resolve_credentials_at(
+            &providers,
+            &console,
+            Some(&mut policy),
+            day(150)
+        ));
+        assert_eq!(details.secret, "file-secret");
+
+        // They can't be used if the user can't be asked for them
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![file()];
+        match resolve_credentials_at(&providers, &console, Some(&mut policy), day(200)) {
+            Err(Error::CredentialsError(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        // Nor do they expire without a policy
+        assert!(resolve_credentials_at(&providers, &console, None, day(200)).is_ok());
+    }
+
+    #[test]
+    fn stored_credential_ages_tests() {
+        let storage = Arc::new(MemoryStorage::new());
+        let details = LoginDetails {
+            secret: "secret".to_string(),
+            password: "password".to_string(),
+        };
+        let mut ages = unwrap!(CredentialAges::load_from(storage.clone(), "work"));
+        assert_eq!(ages.age("file", &details, 1_000), 0);
+        ages.save();
+
+        // The ages are found again, without the credentials
+        let content = unwrap!(unwrap!(storage.read("credentials", "work.json")));
+        assert!(!String::from_utf8_lossy(&content).contains("secret"));
+        let mut ages = unwrap!(CredentialAges::load_from(storage.clone(), "work"));
+        assert_eq!(ages.age("file", &details, 1_500), 500);
+        let mut ages = unwrap!(CredentialAges::load_from(storage.clone(), "home"));
+        assert_eq!(ages.age("file", &details, 1_500), 0);
+
+        // The SHA-256 fingerprints of the older versions are dropped
+        unwrap!(storage.write_private(
+            "credentials",
+            "legacy.json",
+            br#"{"salt": "00", "first_provided": {"ab": 1000}}"#
+        ));
+        let mut ages = unwrap!(CredentialAges::load_from(storage, "legacy"));
+        assert_eq!(ages.age("file", &details, 1_500), 0);
+        assert_eq!(ages.ages.first_provided.len(), 1);
+    }
+
+    #[test]
+    fn credentials_settings_tests() {
+        let settings: CredentialsSettings = unwrap!(serde_json::from_str(
+            r#"{"credentials": ["env", {"command": ["pass", "show", "safe"]}, "keyring", "prompt"]}"#
+        ));
+        assert_eq!(
+            unwrap!(settings.credentials),
+            vec![
+                CredentialSource::Env,
+                CredentialSource::Command(vec![
+                    "pass".to_string(),
+                    "show".to_string(),
+                    "safe".to_string()
+                ]),
+                CredentialSource::Keyring,
+                CredentialSource::Prompt,
+            ]
+        );
+        assert!(settings.secret.is_none());
+        assert_eq!(keyring_service("work"), "safe_auth.work");
+    }
+}
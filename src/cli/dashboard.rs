@@ -0,0 +1,760 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The full-screen dashboard of `safe_auth tui`, with panes for the auth requests waiting for a
+// decision, the authorised apps and the account's balance. The Authenticator service of the
+// profile runs in the background, and its prompt queues the requests it receives for the
+// dashboard, waiting for them to be allowed or denied with the keys:
+// Tab to switch pane, Up/Down to select, `a` to allow, `d` to deny, `r` to revoke the app
+// selected, `u` to refresh and `q` to quit, which denies the requests still waiting.
+//
+// The requests are decided on the same as with the other prompts: the kinds and apps the config
+// file denies are denied, the rate limits and timeouts apply, and the user is verified once a
+// request is allowed. The dashboard's state is kept apart from the terminal, which is only
+// drawn on with the `dashboard` feature.
+//
+// The service holds the session while a request waits, so the apps and the balance are fetched
+// by a worker thread, not to block the dashboard, which couldn't decide on the request otherwise.
+
+// Only the tests drive the dashboard's state without the `dashboard` feature
+#![cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+
+use super::helpers::{
+    own_container_line, req_app_id, PromptDecision, PromptMode, PromptPolicies, RateLimits,
+};
+use super::io::Console;
+use super::verification::{verify_user, UserVerification};
+use crate::ipc::ReqKind;
+use crate::{AuthAllowPrompt, AuthBackend, AuthedAppsList, Error, SharedBackend};
+use safe_core::ipc::req::IpcReq;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often the apps and the balance are fetched again
+const REFRESH_PERIOD: Duration = Duration::from_secs(30);
+
+// An auth request received by the service, whose prompt waits for the decision
+pub struct PendingRequest {
+    req: IpcReq,
+    // When the prompt stops waiting and takes the default decision, if it has a timeout
+    expires: Option<Instant>,
+    decision: Sender<bool>,
+}
+
+// The prompt of the service, queuing the requests to be decided on for the dashboard
+pub fn dashboard_prompt(
+    console: &Console,
+    policies: PromptPolicies,
+    verification: Option<UserVerification>,
+) -> (Arc<AuthAllowPrompt>, Receiver<PendingRequest>) {
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let rate_limits = RateLimits::default();
+    let console = console.downgrade();
+    let prompt: Arc<AuthAllowPrompt> = Arc::new(move |req: IpcReq| {
+        let console = match console.upgrade() {
+            Some(console) => console,
+            None => return false,
+        };
+        if !rate_limits.admit(&req, &policies) || policies.denies(&req) {
+            return false;
+        }
+        if policies.mode_for(&req) == PromptMode::Allow {
+            return true;
+        }
+        let policy = policies.for_req(&req);
+        let timeout = policy
+            .and_then(|policy| policy.timeout)
+            .map(Duration::from_secs);
+        let default = policy.and_then(|policy| policy.default) == Some(PromptDecision::Allow);
+        let reason = match req_app_id(&req) {
+            Some(app_id) => tr!(console, "verify-auth-reason", app_id = app_id),
+            None => tr!(console, "verify-unregistered-reason"),
+        };
+
+        let (decision, answer) = mpsc::channel();
+        let pending = PendingRequest {
+            req,
+            expires: timeout.map(|timeout| Instant::now() + timeout),
+            decision,
+        };
+        let queued = match sender.lock() {
+            Ok(sender) => sender.send(pending).is_ok(),
+            Err(_) => false,
+        };
+        let allowed = queued
+            && match timeout {
+                Some(timeout) => answer.recv_timeout(timeout).unwrap_or(default),
+                None => answer.recv().unwrap_or(false),
+            };
+        allowed && verify_user(verification, &reason).is_ok()
+    });
+    (prompt, receiver)
+}
+
+// The operations on the account, performed by the worker
+enum Task {
+    Refresh,
+    // The app revoked, and the reason shown when verifying the user
+    Revoke(String, String),
+}
+
+// The outcomes of the worker's operations
+enum Update {
+    Apps(Result<Vec<AuthedAppsList>, Error>),
+    Balance(Result<String, Error>),
+    Revoked(String, Result<(), Error>),
+}
+
+fn spawn_worker(
+    backend: SharedBackend,
+    verification: Option<UserVerification>,
+) -> (Sender<Task>, Receiver<Update>) {
+    let (tasks, task_receiver) = mpsc::channel();
+    let (update_sender, updates) = mpsc::channel();
+    let _ = thread::spawn(move || {
+        for task in task_receiver {
+            let sent = match task {
+                Task::Refresh => update_sender
+                    .send(Update::Apps(backend.authed_apps()))
+                    .and_then(|_| {
+                        let balance = backend.acc_info().map(|info| info.balance);
+                        update_sender.send(Update::Balance(balance))
+                    }),
+                Task::Revoke(app_id, reason) => {
+                    let result = verify_user(verification, &reason)
+                        .and_then(|()| backend.revoke_app(&app_id));
+                    update_sender.send(Update::Revoked(app_id, result))
+                }
+            };
+            // The dashboard was closed
+            if sent.is_err() {
+                return;
+            }
+        }
+    });
+    (tasks, updates)
+}
+
+// The keys the dashboard reacts to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Tab,
+    Esc,
+    Char(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pane {
+    Requests,
+    Apps,
+}
+
+// What the dashboard has to do after a key was pressed
+#[derive(Debug, PartialEq)]
+enum Action {
+    Refresh,
+    Revoke(String),
+    Quit,
+}
+
+struct Dashboard {
+    apps: Vec<AuthedAppsList>,
+    balance: Option<String>,
+    requests: Vec<PendingRequest>,
+    pane: Pane,
+    selected_app: usize,
+    selected_request: usize,
+    // The app to be revoked once confirmed with `y`
+    confirm_revoke: Option<String>,
+    status: String,
+}
+
+impl Dashboard {
+    fn new() -> Self {
+        Self {
+            apps: vec![],
+            balance: None,
+            requests: vec![],
+            pane: Pane::Requests,
+            selected_app: 0,
+            selected_request: 0,
+            confirm_revoke: None,
+            status: String::new(),
+        }
+    }
+
+    fn key(&mut self, console: &Console, key: Key) -> Option<Action> {
+        if let Some(app_id) = self.confirm_revoke.take() {
+            let yes = tr!(console, "allow-auth-yes").to_lowercase();
+            if key == Key::Char('y') || Some(key) == yes.chars().next().map(Key::Char) {
+                self.status = tr!(console, "tui-revoking", app_id = app_id);
+                return Some(Action::Revoke(app_id));
+            }
+            self.status = String::new();
+            return None;
+        }
+        match (key, self.pane) {
+            (Key::Char('q'), _) | (Key::Esc, _) => return Some(Action::Quit),
+            (Key::Char('u'), _) => return Some(Action::Refresh),
+            (Key::Tab, Pane::Requests) => self.pane = Pane::Apps,
+            (Key::Tab, Pane::Apps) => self.pane = Pane::Requests,
+            (Key::Up, Pane::Requests) => {
+                self.selected_request = self.selected_request.saturating_sub(1)
+            }
+            (Key::Down, Pane::Requests) => {
+                self.selected_request = next(self.selected_request, self.requests.len())
+            }
+            (Key::Up, Pane::Apps) => self.selected_app = self.selected_app.saturating_sub(1),
+            (Key::Down, Pane::Apps) => self.selected_app = next(self.selected_app, self.apps.len()),
+            (Key::Char('a'), Pane::Requests) => self.decide(console, true),
+            (Key::Char('d'), Pane::Requests) => self.decide(console, false),
+            (Key::Char('r'), Pane::Apps) => {
+                if let Some(app_info) = self.apps.get(self.selected_app) {
+                    self.status = tr!(console, "tui-revoke-confirm", app_id = app_info.app.id);
+                    self.confirm_revoke = Some(app_info.app.id.clone());
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // Answers the prompt waiting for the request selected
+    fn decide(&mut self, console: &Console, allowed: bool) {
+        if self.selected_request >= self.requests.len() {
+            return;
+        }
+        let pending = self.requests.remove(self.selected_request);
+        self.selected_request = self
+            .selected_request
+            .min(self.requests.len().saturating_sub(1));
+        let app = req_app_id(&pending.req).unwrap_or("-").to_string();
+        self.status = if pending.decision.send(allowed).is_err() {
+            tr!(console, "tui-request-expired", app_id = app)
+        } else if allowed {
+            tr!(console, "tui-request-allowed", app_id = app)
+        } else {
+            tr!(console, "tui-request-denied", app_id = app)
+        };
+    }
+
+    fn receive(&mut self, pending: PendingRequest) {
+        self.requests.push(pending);
+    }
+
+    // The requests whose prompt took the default decision are removed
+    fn drop_expired(&mut self, now: Instant) {
+        self.requests
+            .retain(|pending| pending.expires.is_none_or(|expires| expires > now));
+        self.selected_request = self
+            .selected_request
+            .min(self.requests.len().saturating_sub(1));
+    }
+
+    fn update(&mut self, console: &Console, update: Update) {
+        match update {
+            Update::Apps(Ok(apps)) => {
+                self.apps = apps;
+                self.selected_app = self.selected_app.min(self.apps.len().saturating_sub(1));
+            }
+            Update::Balance(Ok(balance)) => self.balance = Some(balance),
+            Update::Apps(Err(err)) | Update::Balance(Err(err)) => {
+                self.status = tr!(console, "tui-error", error = err)
+            }
+            Update::Revoked(app_id, Ok(())) => {
+                self.status = tr!(console, "app-revoked", app_id = app_id)
+            }
+            Update::Revoked(app_id, Err(err)) => {
+                self.status = tr!(console, "revoke-failed", app_id = app_id, error = err)
+            }
+        }
+    }
+
+    // The requests still waiting are denied when the dashboard is closed
+    fn close(&mut self) {
+        for pending in self.requests.drain(..) {
+            let _ = pending.decision.send(false);
+        }
+    }
+
+    fn request_lines(&self) -> Vec<String> {
+        self.requests
+            .iter()
+            .map(|pending| {
+                let kind = ReqKind::of(&pending.req);
+                match &pending.req {
+                    IpcReq::Auth(req) => format!("{}: {} ({})", kind, req.app.name, req.app.id),
+                    IpcReq::Containers(req) => {
+                        format!("{}: {} ({})", kind, req.app.name, req.app.id)
+                    }
+                    IpcReq::ShareMData(req) => {
+                        format!("{}: {} ({})", kind, req.app.name, req.app.id)
+                    }
+                    IpcReq::Unregistered(_) => kind.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    // The permissions requested by the request selected
+    fn request_details(&self, console: &Console) -> Vec<String> {
+        let pending = match self.requests.get(self.selected_request) {
+            Some(pending) => pending,
+            None => return vec![tr!(console, "tui-no-requests")],
+        };
+        match &pending.req {
+            IpcReq::Auth(req) => vec![
+                format!("{} - {}", req.app.vendor, req.app.id),
+                own_container_line(console, req),
+                tr!(
+                    console,
+                    "default-containers",
+                    containers = format!("{:?}", req.containers)
+                ),
+            ],
+            IpcReq::Containers(req) => vec![
+                format!("{} - {}", req.app.vendor, req.app.id),
+                format!("{:?}", req.containers),
+            ],
+            IpcReq::ShareMData(req) => {
+                let mut lines = vec![format!("{} - {}", req.app.vendor, req.app.id)];
+                lines.extend(req.mdata.iter().map(|mdata| {
+                    format!("{:?} ({}): {:?}", mdata.name, mdata.type_tag, mdata.perms)
+                }));
+                lines
+            }
+            IpcReq::Unregistered(_) => vec![tr!(console, "unregistered-req-received")],
+        }
+    }
+
+    fn app_lines(&self) -> Vec<String> {
+        self.apps
+            .iter()
+            .map(|app_info| {
+                let perms: Vec<String> = app_info
+                    .perms
+                    .iter()
+                    .map(|(cont, perms)| format!("{}: {:?}", cont, perms))
+                    .collect();
+                format!(
+                    "{} ({}, {}) {}",
+                    app_info.app.id,
+                    app_info.app.name,
+                    app_info.app.vendor,
+                    perms.join(", ")
+                )
+            })
+            .collect()
+    }
+}
+
+fn next(selected: usize, len: usize) -> usize {
+    if selected + 1 < len {
+        selected + 1
+    } else {
+        selected
+    }
+}
+
+// Runs the dashboard until it's quit, the service running in the background if it was started
+pub fn run_dashboard(
+    console: &Console,
+    backend: SharedBackend,
+    requests: Receiver<PendingRequest>,
+    verification: Option<UserVerification>,
+) -> Result<(), Error> {
+    let (tasks, updates) = spawn_worker(backend, verification);
+    let mut dashboard = Dashboard::new();
+    let result = screen::run(console, &mut dashboard, &tasks, &updates, &requests);
+    dashboard.close();
+    result
+}
+
+#[cfg(feature = "dashboard")]
+mod screen {
+    use super::{Action, Dashboard, Key, Pane, PendingRequest, Task, Update, REFRESH_PERIOD};
+    use crate::cli::io::Console;
+    use crate::Error;
+    use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+    use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+    use crossterm::ExecutableCommand;
+    use std::io::{self, Write};
+    use std::sync::mpsc::{Receiver, Sender};
+    use std::time::{Duration, Instant};
+    use tui::backend::CrosstermBackend;
+    use tui::layout::{Constraint, Direction, Layout};
+    use tui::style::{Modifier, Style};
+    use tui::widgets::{Block, Borders, Paragraph, SelectableList, Text, Widget};
+    use tui::Terminal;
+
+    // How long the keys are waited for before the requests and updates are checked again
+    const TICK: Duration = Duration::from_millis(200);
+
+    pub fn run(
+        console: &Console,
+        dashboard: &mut Dashboard,
+        tasks: &Sender<Task>,
+        updates: &Receiver<Update>,
+        requests: &Receiver<PendingRequest>,
+    ) -> Result<(), Error> {
+        let _raw_screen = RawScreen::enter()?;
+        Terminal::new(CrosstermBackend::new(io::stdout()))
+            .map_err(|err| Error::IoError(format!("Failed to open the dashboard: {}", err)))
+            .and_then(|mut screen| {
+                let _ = screen.hide_cursor();
+                let _ = screen.clear();
+                let result = event_loop(console, &mut screen, dashboard, tasks, updates, requests);
+                let _ = screen.show_cursor();
+                result
+            })
+    }
+
+    // The terminal in raw mode on the alternate screen, restored once dropped, e.g. when the
+    // dashboard panics
+    struct RawScreen;
+
+    impl RawScreen {
+        fn enter() -> Result<Self, Error> {
+            terminal::enable_raw_mode().map_err(screen_error)?;
+            let _ = io::stdout().execute(EnterAlternateScreen);
+            Ok(RawScreen)
+        }
+    }
+
+    impl Drop for RawScreen {
+        fn drop(&mut self) {
+            let _ = io::stdout().execute(LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+
+    fn event_loop<W: Write>(
+        console: &Console,
+        screen: &mut Terminal<CrosstermBackend<W>>,
+        dashboard: &mut Dashboard,
+        tasks: &Sender<Task>,
+        updates: &Receiver<Update>,
+        requests: &Receiver<PendingRequest>,
+    ) -> Result<(), Error> {
+        let mut refreshed: Option<Instant> = None;
+        loop {
+            if refreshed.is_none_or(|at| at.elapsed() >= REFRESH_PERIOD) {
+                let _ = tasks.send(Task::Refresh);
+                refreshed = Some(Instant::now());
+            }
+            while let Ok(pending) = requests.try_recv() {
+                dashboard.receive(pending);
+            }
+            while let Ok(update) = updates.try_recv() {
+                dashboard.update(console, update);
+            }
+            dashboard.drop_expired(Instant::now());
+            draw(console, screen, dashboard)?;
+
+            if !event::poll(TICK).map_err(screen_error)? {
+                continue;
+            }
+            let key = match event::read().map_err(screen_error)? {
+                // Ctrl+C doesn't interrupt in raw mode, it quits the same as `q`
+                Event::Key(key) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    match key.code {
+                        KeyCode::Char('c') => Key::Esc,
+                        _ => continue,
+                    }
+                }
+                Event::Key(key) => match key.code {
+                    KeyCode::Up => Key::Up,
+                    KeyCode::Down => Key::Down,
+                    KeyCode::Tab => Key::Tab,
+                    KeyCode::Esc => Key::Esc,
+                    KeyCode::Char(c) => Key::Char(c),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            match dashboard.key(console, key) {
+                Some(Action::Quit) => return Ok(()),
+                Some(Action::Refresh) => refreshed = None,
+                Some(Action::Revoke(app_id)) => {
+                    let reason = tr!(console, "verify-revoke-reason", app_id = app_id);
+                    let _ = tasks.send(Task::Revoke(app_id, reason));
+                    refreshed = None;
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn draw<W: Write>(
+        console: &Console,
+        screen: &mut Terminal<CrosstermBackend<W>>,
+        dashboard: &Dashboard,
+    ) -> Result<(), Error> {
+        let requests_title = tr!(console, "tui-requests", count = dashboard.requests.len());
+        let apps_title = tr!(console, "header-authed-apps");
+        let account_title = tr!(console, "header-acc-summary");
+        let balance = match &dashboard.balance {
+            Some(balance) => tr!(console, "acc-balance", balance = balance),
+            None => tr!(console, "tui-loading"),
+        };
+        let request_lines = dashboard.request_lines();
+        let details: Vec<Text> = dashboard
+            .request_details(console)
+            .into_iter()
+            .map(|line| Text::raw(format!("{}\n", line)))
+            .collect();
+        let app_lines = dashboard.app_lines();
+        let footer = [
+            Text::raw(format!("{}\n", dashboard.status)),
+            Text::raw(tr!(console, "tui-help")),
+        ];
+        let pane_style = |pane: Pane| {
+            if dashboard.pane == pane {
+                Style::default().modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            }
+        };
+        let selected_style = Style::default().modifier(Modifier::REVERSED);
+
+        screen
+            .draw(|mut frame| {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(
+                        [
+                            Constraint::Length(3),
+                            Constraint::Percentage(45),
+                            Constraint::Min(5),
+                            Constraint::Length(4),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(frame.size());
+                let request_columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+                    .split(rows[1]);
+
+                Paragraph::new([Text::raw(balance)].iter())
+                    .block(Block::default().borders(Borders::ALL).title(&account_title))
+                    .render(&mut frame, rows[0]);
+                SelectableList::default()
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(&requests_title)
+                            .title_style(pane_style(Pane::Requests)),
+                    )
+                    .items(&request_lines)
+                    .select(Some(dashboard.selected_request).filter(|_| !request_lines.is_empty()))
+                    .highlight_style(selected_style)
+                    .render(&mut frame, request_columns[0]);
+                Paragraph::new(details.iter())
+                    .block(Block::default().borders(Borders::ALL))
+                    .wrap(true)
+                    .render(&mut frame, request_columns[1]);
+                SelectableList::default()
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(&apps_title)
+                            .title_style(pane_style(Pane::Apps)),
+                    )
+                    .items(&app_lines)
+                    .select(Some(dashboard.selected_app).filter(|_| !app_lines.is_empty()))
+                    .highlight_style(selected_style)
+                    .render(&mut frame, rows[2]);
+                Paragraph::new(footer.iter())
+                    .block(Block::default().borders(Borders::TOP))
+                    .wrap(true)
+                    .render(&mut frame, rows[3]);
+            })
+            .map_err(|err| Error::IoError(format!("Failed to draw the dashboard: {}", err)))
+    }
+
+    fn screen_error(err: crossterm::ErrorKind) -> Error {
+        Error::IoError(format!("Failed to use the terminal: {}", err))
+    }
+}
+
+// Not reached, the `tui` command is rejected before logging in without the `dashboard` feature
+#[cfg(not(feature = "dashboard"))]
+mod screen {
+    use super::{Dashboard, PendingRequest, Task, Update};
+    use crate::cli::io::Console;
+    use crate::Error;
+    use std::sync::mpsc::{Receiver, Sender};
+
+    pub fn run(
+        _console: &Console,
+        _dashboard: &mut Dashboard,
+        _tasks: &Sender<Task>,
+        _updates: &Receiver<Update>,
+        _requests: &Receiver<PendingRequest>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dashboard_prompt, Action, Dashboard, Key, PendingRequest};
+    use crate::cli::helpers::PromptPolicies;
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::AuthedAppsList;
+    use safe_core::ipc::req::{AppExchangeInfo, AuthReq, IpcReq};
+    use safe_nd::AppPermissions;
+    use std::collections::HashMap;
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn app(id: &str) -> AppExchangeInfo {
+        AppExchangeInfo {
+            id: id.to_string(),
+            scope: None,
+            name: "Test".to_string(),
+            vendor: "MaidSafe".to_string(),
+        }
+    }
+
+    fn auth_req(app_id: &str) -> IpcReq {
+        IpcReq::Auth(AuthReq {
+            app: app(app_id),
+            app_container: false,
+            app_permissions: AppPermissions {
+                transfer_coins: false,
+                perform_mutations: false,
+                get_balance: false,
+            },
+            containers: HashMap::new(),
+        })
+    }
+
+    fn pending(app_id: &str, expires: Option<Instant>) -> (PendingRequest, Receiver<bool>) {
+        let (decision, answer) = mpsc::channel();
+        let pending = PendingRequest {
+            req: auth_req(app_id),
+            expires,
+            decision,
+        };
+        (pending, answer)
+    }
+
+    #[test]
+    fn dashboard_requests_tests() {
+        let console = Console::new(Box::new(CapturedIo::new("")));
+        let mut dashboard = Dashboard::new();
+        let (first, first_answer) = pending("net.maidsafe.first", None);
+        let (second, second_answer) = pending("net.maidsafe.second", None);
+        let (third, third_answer) = pending("net.maidsafe.third", None);
+        dashboard.receive(first);
+        dashboard.receive(second);
+        dashboard.receive(third);
+        assert_eq!(dashboard.request_lines().len(), 3);
+
+        assert_eq!(dashboard.key(&console, Key::Down), None);
+        assert_eq!(dashboard.key(&console, Key::Char('a')), None);
+        assert!(unwrap!(second_answer.try_recv()));
+        assert!(dashboard.status.contains("net.maidsafe.second"));
+
+        // The selection stays on the last request once the one selected was decided on
+        assert_eq!(dashboard.key(&console, Key::Down), None);
+        assert_eq!(dashboard.key(&console, Key::Char('d')), None);
+        assert!(!unwrap!(third_answer.try_recv()));
+        assert_eq!(dashboard.selected_request, 0);
+
+        // The requests still waiting are denied when the dashboard is quit
+        assert_eq!(dashboard.key(&console, Key::Char('q')), Some(Action::Quit));
+        dashboard.close();
+        assert!(!unwrap!(first_answer.try_recv()));
+        assert!(dashboard.requests.is_empty());
+        assert_eq!(dashboard.request_details(&console).len(), 1);
+    }
+
+    #[test]
+    fn dashboard_expired_requests_tests() {
+        let mut dashboard = Dashboard::new();
+        let now = Instant::now();
+        let (expired, _expired_answer) = pending("net.maidsafe.expired", Some(now));
+        let (waiting, _waiting_answer) =
+            pending("net.maidsafe.waiting", Some(now + Duration::from_secs(60)));
+        dashboard.receive(expired);
+        dashboard.receive(waiting);
+        dashboard.drop_expired(now);
+        assert_eq!(dashboard.requests.len(), 1);
+        assert!(dashboard.request_lines()[0].contains("net.maidsafe.waiting"));
+    }
+
+    #[test]
+    fn dashboard_revoke_tests() {
+        let console = Console::new(Box::new(CapturedIo::new("")));
+        let mut dashboard = Dashboard::new();
+        dashboard.apps = vec![
+            AuthedAppsList {
+                app: app("net.maidsafe.first"),
+                perms: vec![],
+            },
+            AuthedAppsList {
+                app: app("net.maidsafe.second"),
+                perms: vec![],
+            },
+        ];
+
+        // Revoking is only available in the apps pane
+        assert_eq!(dashboard.key(&console, Key::Char('r')), None);
+        assert!(dashboard.confirm_revoke.is_none());
+        assert_eq!(dashboard.key(&console, Key::Tab), None);
+        assert_eq!(dashboard.key(&console, Key::Down), None);
+        assert_eq!(dashboard.key(&console, Key::Down), None);
+        assert_eq!(dashboard.selected_app, 1);
+
+        // Any other key than `y` cancels it
+        assert_eq!(dashboard.key(&console, Key::Char('r')), None);
+        assert!(dashboard.status.contains("net.maidsafe.second"));
+        assert_eq!(dashboard.key(&console, Key::Char('n')), None);
+        assert!(dashboard.confirm_revoke.is_none());
+
+        assert_eq!(dashboard.key(&console, Key::Char('r')), None);
+        assert_eq!(
+            dashboard.key(&console, Key::Char('y')),
+            Some(Action::Revoke("net.maidsafe.second".to_string()))
+        );
+        assert_eq!(
+            dashboard.key(&console, Key::Char('u')),
+            Some(Action::Refresh)
+        );
+    }
+
+    #[test]
+    fn dashboard_prompt_tests() {
+        let console = Console::new(Box::new(CapturedIo::new("")));
+        let policies: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "containers": { "mode": "deny" }, "denylist": ["net.maidsafe.denied"] }"#
+        ));
+        let (prompt, requests) = dashboard_prompt(&console, policies, None);
+
+        // Denied and allowed without being queued
+        assert!(!prompt(auth_req("net.maidsafe.denied")));
+        assert!(prompt(IpcReq::Unregistered(vec![])));
+        assert!(requests.try_recv().is_err());
+
+        let answer = thread::spawn(move || {
+            let pending = unwrap!(requests.recv());
+            unwrap!(pending.decision.send(true));
+        });
+        assert!(prompt(auth_req("net.maidsafe.test")));
+        unwrap!(answer.join());
+    }
+}
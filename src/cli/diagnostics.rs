@@ -0,0 +1,358 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The crash reports. When the binary panics, rather than the raw panic message, a report with
+// the version, the OS, the command line and the most recent logs of this crate is written to
+// a file, and the user is told how to file an issue with it.
+//
+// The secrets known to the process, i.e. the account's secret and password and the secret keys
+// used, are registered with `register_secret` as soon as they are read, and every occurrence of
+// them is replaced in the report, as well as the values of the arguments which take a secret and
+// any argument shaped like a secret key, e.g. one given to the wrong flag. Only the logs at info
+// level and above are kept, the debug ones of the SAFE libraries and of this crate's requests
+// and responses being too detailed to be shared.
+//
+// The logs are the ones enabled with `RUST_LOG`, and those of the CLI and of the SAFE libraries
+// it logs in and connects to the network with, at the level of the `-v` flags given, so the
+// users debugging a failed login don't need to know its syntax.
+
+use crate::decode::is_recovering;
+use lazy_static::lazy_static;
+use log::{debug, Level, LevelFilter, Log, Metadata, Record};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// How many of the most recent log lines are kept for the report
+const MAX_RECENT_LOGS: usize = 200;
+// Arguments whose value is a secret
+const SECRET_ARGS: [&str; 1] = ["--sk"];
+// The length of the shortest hex encoded secret key
+const MIN_SECRET_KEY_LEN: usize = 64;
+const REDACTED: &str = "<redacted>";
+const ISSUES_URL: &str = "https://github.com/maidsafe/safe-authenticator-cli/issues";
+// The targets whose logs are shown with `-v`
+const VERBOSE_TARGETS: [&str; 3] = ["safe_auth", "safe_authenticator", "safe_core"];
+
+// Whether the logger of `init_logger` is the one installed, rather than an embedder's own
+static LOGGER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    static ref RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+    // The logger of the levels set with `-v`, on top of the one of `RUST_LOG`
+    static ref VERBOSE_LOGGER: RwLock<Option<env_logger::Logger>> = RwLock::new(None);
+}
+
+// Makes sure the secret never ends up in a crash report
+pub fn register_secret(secret: &str) {
+    if secret.is_empty() {
+        return;
+    }
+    if let Ok(mut secrets) = SECRETS.lock() {
+        if !secrets.iter().any(|known| known == secret) {
+            secrets.push(secret.to_string());
+        }
+    }
+}
+
+// Whether the text contains a secret registered, or any word shaped like a secret key
+pub fn contains_secret(text: &str) -> bool {
+    if text.split_whitespace().any(is_secret_key) {
+        return true;
+    }
+    match SECRETS.lock() {
+        Ok(secrets) => secrets.iter().any(|secret| text.contains(secret.as_str())),
+        // Without knowing them, it could be any
+        Err(_) => true,
+    }
+}
+
+// Forwards the logs to env_logger, keeping the most recent ones of this crate for the
+// crash reports. The ones of the dependencies and below info level are never kept.
+struct RecentLogsLogger {
+    inner: env_logger::Logger,
+}
+
+fn is_kept(metadata: &Metadata) -> bool {
+    metadata.level() <= Level::Info && metadata.target().starts_with("safe_auth")
+}
+
+fn verbose_enabled(metadata: &Metadata) -> bool {
+    match VERBOSE_LOGGER.read() {
+        Ok(verbose) => verbose
+            .as_ref()
+            .is_some_and(|verbose| verbose.enabled(metadata)),
+        Err(_) => false,
+    }
+}
+
+impl Log for RecentLogsLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        is_kept(metadata) || self.inner.enabled(metadata) || verbose_enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if is_kept(record.metadata()) {
+            if let Ok(mut logs) = RECENT_LOGS.lock() {
+                if logs.len() == MAX_RECENT_LOGS {
+                    let _ = logs.pop_front();
+                }
+                logs.push_back(format!(
+                    "{} {}: {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                ));
+            }
+        }
+        // Each record is logged once, even if both loggers enable it
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        } else if let Ok(verbose) = VERBOSE_LOGGER.read() {
+            if let Some(verbose) = verbose.as_ref() {
+                verbose.log(record);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// # Initialise the logger
+///
+/// Initialises the logger, configured as env_logger with the `RUST_LOG` environment variable,
+/// which also keeps the most recent logs of this crate for the crash reports written by the
+/// panic handler installed with `install_panic_handler`. The `-v` flags of the CLI then show
+/// more of them.
+pub fn init_logger() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter().max(LevelFilter::Info);
+    if log::set_boxed_logger(Box::new(RecentLogsLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+        LOGGER_INSTALLED.store(true, Ordering::SeqCst);
+    }
+}
+
+// The level of the logs shown with the number of `-v` flags given, none without any
+fn verbosity_level(occurrences: u64) -> Option<LevelFilter> {
+    match occurrences {
+        0 => None,
+        1 => Some(LevelFilter::Info),
+        2 => Some(LevelFilter::Debug),
+        _ => Some(LevelFilter::Trace),
+    }
+}
+
+// Shows the logs of the CLI and the SAFE libraries at the level of the `-v` flags given, unless
+// the logger installed isn't the one of `init_logger`
+pub fn set_verbosity(occurrences: u64) {
+    let level = match verbosity_level(occurrences) {
+        Some(level) => level,
+        None => return,
+    };
+    if !LOGGER_INSTALLED.load(Ordering::SeqCst) {
+        return;
+    }
+    let mut builder = env_logger::Builder::new();
+    for target in VERBOSE_TARGETS.iter() {
+        builder.filter_module(target, level);
+    }
+    if let Ok(mut verbose) = VERBOSE_LOGGER.write() {
+        *verbose = Some(builder.build());
+    }
+    if log::max_level() < level {
+        log::set_max_level(level);
+    }
+}
+
+/// # Install the panic handler
+///
+/// Replaces the raw panic message by a crash report written to a file, which leaves out the
+/// credentials and secret keys, and instructions for filing an issue with it.
+/// The default panic message and backtrace are still printed if `RUST_BACKTRACE` is set.
+///
+/// ## Example
+/// ```
+/// safe_auth::init_logger();
+/// safe_auth::install_panic_handler();
+/// let result = safe_auth::run_with_args(&["safe_auth", "--help"], safe_auth::StdIo::default());
+/// assert!(result.is_success());
+/// ```
+pub fn install_panic_handler() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // The string failing to be decoded is reported instead
+        if is_recovering() {
+            debug!("Recovered from a panic: {}", panic_message(info));
+            return;
+        }
+        if env::var_os("RUST_BACKTRACE").is_some() {
+            default_hook(info);
+            return;
+        }
+
+        let args: Vec<String> = env::args().collect();
+        // The locks are not waited for, in case the panic happened while holding them
+        let logs: Vec<String> = RECENT_LOGS
+            .try_lock()
+            .map(|logs| logs.iter().cloned().collect())
+            .unwrap_or_default();
+        let secrets: Vec<String> = SECRETS
+            .try_lock()
+            .map(|secrets| secrets.clone())
+            .unwrap_or_default();
+        let report = crash_report(&args, &panic_message(info), &logs, &secrets);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let path = env::temp_dir().join(format!("safe_auth-crash-{}.txt", now));
+        match fs::write(&path, &report) {
+            Ok(()) => eprintln!(
+                "safe_auth crashed unexpectedly. A report was written to {}\n\
+                 Please file an issue at {} attaching it, after reviewing it. \
+                 Your secret, password and secret keys were left out of it.",
+                path.display(),
+                ISSUES_URL
+            ),
+            Err(err) => eprintln!(
+                "safe_auth crashed unexpectedly, and the report couldn't be written ({}).\n\
+                 Please file an issue at {} with the following report:\n\n{}",
+                err, ISSUES_URL, report
+            ),
+        }
+    }));
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => (*message).to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "Unknown panic".to_string(),
+        },
+    };
+    match info.location() {
+        Some(location) => format!("{} at {}:{}", message, location.file(), location.line()),
+        None => message,
+    }
+}
+
+// Whether the argument could be a secret key, hex encoded
+fn is_secret_key(arg: &str) -> bool {
+    arg.len() >= MIN_SECRET_KEY_LEN && arg.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// The content of the crash report, with all the secrets replaced
+fn crash_report(args: &[String], message: &str, logs: &[String], secrets: &[String]) -> String {
+    let mut command: Vec<&str> = vec![];
+    let mut secret_value = false;
+    for arg in args {
+        if secret_value
+            || is_secret_key(arg)
+            || SECRET_ARGS
+                .iter()
+                .any(|name| arg.starts_with(&format!("{}=", name)))
+        {
+            command.push(REDACTED);
+        } else {
+            command.push(arg);
+        }
+        secret_value = SECRET_ARGS.contains(&arg.as_str());
+    }
+
+    let report = format!(
+        "safe_auth crash report\n\n\
+         Version: {}\n\
+         OS: {} ({})\n\
+         Command: {}\n\
+         Panic: {}\n\n\
+         Recent logs:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::OS,
+        env::consts::ARCH,
+        command.join(" "),
+        message,
+        logs.join("\n")
+    );
+
+    // The longest first, in case a secret contains another
+    let mut secrets: Vec<&String> = secrets.iter().collect();
+    secrets.sort_by_key(|secret| Reverse(secret.len()));
+    secrets.into_iter().fold(report, |report, secret| {
+        report.replace(secret.as_str(), REDACTED)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crash_report, verbosity_level};
+    use log::LevelFilter;
+
+    #[test]
+    fn verbosity_level_tests() {
+        assert_eq!(verbosity_level(0), None);
+        assert_eq!(verbosity_level(1), Some(LevelFilter::Info));
+        assert_eq!(verbosity_level(2), Some(LevelFilter::Debug));
+        assert_eq!(verbosity_level(3), Some(LevelFilter::Trace));
+        assert_eq!(verbosity_level(5), Some(LevelFilter::Trace));
+    }
+
+    #[test]
+    fn crash_report_tests() {
+        let secret_key = "0f".repeat(32);
+        let args: Vec<String> = vec![
+            "safe_auth",
+            "--sk",
+            "a1b2c3",
+            "--sk=d4e5f6",
+            "-a",
+            "--req",
+            &secret_key,
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        let logs = vec![
+            "INFO safe_auth::cli: Logging in".to_string(),
+            "INFO safe_auth::cli: Got my-password back".to_string(),
+        ];
+        let secrets = vec!["my-secret".to_string(), "my-password".to_string()];
+        let report = crash_report(&args, "Failed with my-secret", &logs, &secrets);
+
+        assert!(report.contains(env!("CARGO_PKG_VERSION")));
+        assert!(
+            report.contains("Command: safe_auth --sk <redacted> <redacted> -a --req <redacted>")
+        );
+        assert!(report.contains("Panic: Failed with <redacted>"));
+        assert!(report.contains("Got <redacted> back"));
+        for secret in &["a1b2c3", "d4e5f6", "my-secret", "my-password", &secret_key] {
+            assert!(!report.contains(secret));
+        }
+
+        // Short hex values, e.g. the IDs of the receipts, are left
+        let args = vec![
+            "safe_auth".to_string(),
+            "audit".to_string(),
+            "show".to_string(),
+            "a1b2c3".to_string(),
+        ];
+        let report = crash_report(&args, "Failed", &[], &[]);
+        assert!(report.contains("Command: safe_auth audit show a1b2c3"));
+    }
+}
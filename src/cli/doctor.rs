@@ -0,0 +1,547 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The `doctor` command. It checks the local environment the CLI runs in, without logging in,
+// and tells how to fix each of the problems found. The outcome of each check is reported as
+// message ids rather than text, so the table format prints them in the user's language while
+// the `json` one always prints them in English.
+
+use super::credentials::{credential_commands, keyring_program, uses_keyring};
+use super::helpers::get_settings;
+#[cfg(not(feature = "mock-network"))]
+use super::helpers::CRUST_CONFIG_FILE_NAME;
+#[cfg(not(feature = "mock-network"))]
+use super::networks::network_path;
+#[cfg(not(feature = "mock-network"))]
+use super::{CRUST_CONFIG_PATH_ENV_VAR, DEFAULT_SEARCH_PATH};
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::io;
+use std::net::TcpListener;
+#[cfg(not(feature = "mock-network"))]
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// The port of the Authenticator service checked when none is given, the one of the examples
+pub const DEFAULT_DAEMON_PORT: u16 = 41805;
+// How long each of the network contacts is given to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+// Clock skew tolerated before the clock is reported as wrong
+const MAX_CLOCK_SKEW_SECS: u64 = 5 * 60;
+// Bootstrap caches not updated for longer than this are reported as stale
+const STALE_CACHE_SECS: u64 = 30 * 24 * 60 * 60;
+// The server whose clock the local one is compared with
+const TIME_REFERENCE_URL: &str = "https://api.github.com";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+    /// The check doesn't apply to this build or environment
+    Skipped,
+}
+
+// A message to be printed in the user's language, see the `tr!` macro
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub id: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    fn new(id: &'static str) -> Self {
+        Self { id, args: vec![] }
+    }
+
+    fn arg<T: ToString>(mut self, name: &'static str, value: T) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    /// Id of the check, e.g. "config"
+    pub check: &'static str,
+    pub status: CheckStatus,
+    pub detail: Message,
+    /// How to fix the problem found, if any
+    pub fix: Option<Message>,
+}
+
+impl CheckResult {
+    fn new(check: &'static str, status: CheckStatus, detail: Message) -> Self {
+        Self {
+            check,
+            status,
+            detail,
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: Message) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+// The arguments of the CLI the checks depend on
+pub struct DoctorOptions<'a> {
+    pub config_file: &'a Option<String>,
+    pub network: &'a Option<String>,
+    pub port: u16,
+}
+
+pub fn run_checks(options: &DoctorOptions) -> Vec<CheckResult> {
+    vec![
+        check_config(options.config_file),
+        check_keyring(options.config_file),
+        check_network(options.network),
+        check_daemon_port(options.port),
+        check_clock(),
+        check_caches(),
+    ]
+}
+
+fn check_config(config_file: &Option<String>) -> CheckResult {
+    let file = match config_file {
+        Some(file) => file,
+        None => {
+            return CheckResult::new(
+                "config",
+                CheckStatus::Skipped,
+                Message::new("doctor-config-none"),
+            )
+        }
+    };
+    let content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(err) => {
+            return CheckResult::new(
+                "config",
+                CheckStatus::Failed,
+                Message::new("doctor-config-unreadable")
+                    .arg("file", file)
+                    .arg("error", err),
+            )
+            .fix(Message::new("doctor-config-unreadable-fix").arg("file", file))
+        }
+    };
+    let parsed = serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|err| err.to_string())
+        .and_then(|value| {
+            get_settings(config_file)
+                .and_then(|_| credential_commands(config_file))
+                .map(|_| value)
+                .map_err(|err| err.to_string())
+        });
+    let value = match parsed {
+        Ok(value) => value,
+        Err(err) => {
+            return CheckResult::new(
+                "config",
+                CheckStatus::Failed,
+                Message::new("doctor-config-invalid")
+                    .arg("file", file)
+                    .arg("error", err),
+            )
+            .fix(Message::new("doctor-config-invalid-fix"))
+        }
+    };
+
+    let has_credentials = value.get("secret").is_some() || value.get("password").is_some();
+    if has_credentials && is_readable_by_others(Path::new(file)) {
+        return CheckResult::new(
+            "config",
+            CheckStatus::Warning,
+            Message::new("doctor-config-permissions").arg("file", file),
+        )
+        .fix(Message::new("doctor-config-permissions-fix").arg("file", file));
+    }
+    CheckResult::new(
+        "config",
+        CheckStatus::Ok,
+        Message::new("doctor-config-ok").arg("file", file),
+    )
+}
+
+#[cfg(unix)]
+fn is_readable_by_others(file: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(file)
+        .map(|metadata| metadata.permissions().mode() & 0o077 != 0)
+        .unwrap_or(false)
+}
+
+// The permissions of the files are not checked on other platforms
+#[cfg(not(unix))]
+fn is_readable_by_others(_file: &Path) -> bool {
+    false
+}
+
+// The keyrings are read with the command of the `keyring` credential provider, or with the
+// commands of the `command` ones, so these are the ones which must be available
+fn check_keyring(config_file: &Option<String>) -> CheckResult {
+    // The errors are already reported by the config check
+    let commands = credential_commands(config_file).unwrap_or_default();
+    let native = uses_keyring(config_file).unwrap_or(false);
+    if commands.is_empty() && !native {
+        return CheckResult::new(
+            "keyring",
+            CheckStatus::Skipped,
+            Message::new("doctor-keyring-none"),
+        );
+    }
+
+    if native {
+        match keyring_program() {
+            None => {
+                return CheckResult::new(
+                    "keyring",
+                    CheckStatus::Failed,
+                    Message::new("doctor-keyring-unsupported"),
+                )
+                .fix(Message::new("doctor-keyring-unsupported-fix"))
+            }
+            Some(program) if find_program(program).is_none() => {
+                return CheckResult::new(
+                    "keyring",
+                    CheckStatus::Failed,
+                    Message::new("doctor-keyring-native-missing").arg("program", program),
+                )
+                .fix(Message::new("doctor-keyring-native-missing-fix").arg("program", program))
+            }
+            Some(program) if commands.is_empty() => {
+                return CheckResult::new(
+                    "keyring",
+                    CheckStatus::Ok,
+                    Message::new("doctor-keyring-native-ok").arg("program", program),
+                )
+            }
+            Some(_) => (),
+        }
+    }
+
+    for command in commands.iter() {
+        let program = command.first().map(String::as_str).unwrap_or("");
+        if find_program(program).is_none() {
+            return CheckResult::new(
+                "keyring",
+                CheckStatus::Failed,
+                Message::new("doctor-keyring-missing").arg("program", program),
+            )
+            .fix(Message::new("doctor-keyring-missing-fix").arg("program", program));
+        }
+    }
+    CheckResult::new(
+        "keyring",
+        CheckStatus::Ok,
+        Message::new("doctor-keyring-ok").arg("count", commands.len()),
+    )
+}
+
+// The path of the program, as it would be found when run
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    if program.is_empty() {
+        return None;
+    }
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return if path.is_file() {
+            Some(path.to_path_buf())
+        } else {
+            None
+        };
+    }
+    let dirs = env::var_os("PATH")?;
+    env::split_paths(&dirs)
+        .flat_map(|dir| vec![dir.join(program), dir.join(format!("{}.exe", program))])
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(feature = "mock-network")]
+fn check_network(_network: &Option<String>) -> CheckResult {
+    CheckResult::new(
+        "network",
+        CheckStatus::Skipped,
+        Message::new("doctor-network-mock"),
+    )
+}
+
+// The crust config is looked up where the CLI tells crust to look it up first
+#[cfg(not(feature = "mock-network"))]
+fn check_network(network: &Option<String>) -> CheckResult {
+    let dir = match (network, env::var(CRUST_CONFIG_PATH_ENV_VAR)) {
+        (Some(name), _) => match network_path(name) {
+            Ok(path) => path,
+            Err(_) => {
+                return CheckResult::new(
+                    "network",
+                    CheckStatus::Failed,
+                    Message::new("doctor-network-unknown").arg("name", name),
+                )
+                .fix(Message::new("doctor-network-unknown-fix").arg("name", name))
+            }
+        },
+        (None, Ok(path)) => PathBuf::from(path),
+        (None, Err(_)) => PathBuf::from(DEFAULT_SEARCH_PATH),
+    };
+    let config_file = dir.join(CRUST_CONFIG_FILE_NAME);
+    let crust_config = fs::read_to_string(&config_file)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+    let crust_config = match crust_config {
+        Some(crust_config) => crust_config,
+        None => {
+            return CheckResult::new(
+                "network",
+                CheckStatus::Warning,
+                Message::new("doctor-network-no-config").arg("file", config_file.display()),
+            )
+            .fix(Message::new("doctor-network-no-config-fix"))
+        }
+    };
+
+    let contacts: Vec<SocketAddr> = crust_config["hard_coded_contacts"]
+        .as_array()
+        .map(|contacts| {
+            contacts
+                .iter()
+                .filter_map(|contact| contact.as_str())
+                .filter_map(|contact| contact.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    if contacts.is_empty() {
+        return CheckResult::new(
+            "network",
+            CheckStatus::Failed,
+            Message::new("doctor-network-no-contacts").arg("file", config_file.display()),
+        )
+        .fix(Message::new("doctor-network-no-config-fix"));
+    }
+
+    let reachable = contacts
+        .iter()
+        .filter(|contact| TcpStream::connect_timeout(contact, CONNECT_TIMEOUT).is_ok())
+        .count();
+    let detail = Message::new("doctor-network-reachable")
+        .arg("reachable", reachable)
+        .arg("total", contacts.len());
+    if reachable == 0 {
+        CheckResult::new("network", CheckStatus::Failed, detail)
+            .fix(Message::new("doctor-network-unreachable-fix"))
+    } else {
+        CheckResult::new("network", CheckStatus::Ok, detail)
+    }
+}
+
+fn check_daemon_port(port: u16) -> CheckResult {
+    if cfg!(not(feature = "daemon")) {
+        return CheckResult::new(
+            "daemon_port",
+            CheckStatus::Skipped,
+            Message::new("doctor-daemon-unavailable"),
+        );
+    }
+    match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(_) => CheckResult::new(
+            "daemon_port",
+            CheckStatus::Ok,
+            Message::new("doctor-daemon-port-free").arg("port", port),
+        ),
+        Err(ref err) if err.kind() == io::ErrorKind::AddrInUse => CheckResult::new(
+            "daemon_port",
+            CheckStatus::Failed,
+            Message::new("doctor-daemon-port-in-use").arg("port", port),
+        )
+        .fix(Message::new("doctor-daemon-port-in-use-fix").arg("port", port)),
+        Err(err) => CheckResult::new(
+            "daemon_port",
+            CheckStatus::Failed,
+            Message::new("doctor-daemon-port-error")
+                .arg("port", port)
+                .arg("error", err),
+        )
+        .fix(Message::new("doctor-daemon-port-in-use-fix").arg("port", port)),
+    }
+}
+
+fn check_clock() -> CheckResult {
+    let reference = reqwest::Client::builder()
+        .timeout(CONNECT_TIMEOUT)
+        .build()
+        .and_then(|client| client.head(TIME_REFERENCE_URL).send())
+        .ok()
+        .and_then(|response| {
+            response
+                .headers()
+                .get(reqwest::header::DATE)
+                .and_then(|date| date.to_str().ok())
+                .and_then(parse_http_date)
+        });
+    let reference = match reference {
+        Some(reference) => reference,
+        None => {
+            return CheckResult::new(
+                "clock",
+                CheckStatus::Skipped,
+                Message::new("doctor-clock-no-reference"),
+            )
+        }
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let skew = now.abs_diff(reference);
+    if skew > MAX_CLOCK_SKEW_SECS {
+        CheckResult::new(
+            "clock",
+            CheckStatus::Failed,
+            Message::new("doctor-clock-skew").arg("seconds", skew),
+        )
+        .fix(Message::new("doctor-clock-skew-fix"))
+    } else {
+        CheckResult::new("clock", CheckStatus::Ok, Message::new("doctor-clock-ok"))
+    }
+}
+
+// Seconds since the UNIX epoch of an HTTP date, e.g. "Sun, 06 Nov 1994 08:49:37 GMT"
+fn parse_http_date(date: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    if parts.len() != 6 || parts[5] != "GMT" {
+        return None;
+    }
+    let day: u64 = parts[1].parse().ok()?;
+    let month = MONTHS.iter().position(|month| *month == parts[2])? as u64 + 1;
+    let year: u64 = parts[3].parse().ok()?;
+    let time: Vec<u64> = parts[4]
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    if time.len() != 3 || year < 1970 {
+        return None;
+    }
+
+    // Days since the epoch of the civil date, counting the years from March
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).checked_sub(719_468)?;
+    Some(days * 86_400 + time[0] * 3_600 + time[1] * 60 + time[2])
+}
+
+// The bootstrap caches of crust which weren't updated for long, whose contacts may be gone
+fn check_caches() -> CheckResult {
+    let now = SystemTime::now();
+    let dirs = vec![
+        config_file_handler::user_app_dir(),
+        config_file_handler::system_cache_dir(),
+    ];
+    let stale: Vec<PathBuf> = dirs
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(Result::ok))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with("safe_auth.") && name.ends_with(".bootstrap.cache")
+                })
+        })
+        .filter(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age.as_secs() > STALE_CACHE_SECS)
+        })
+        .collect();
+
+    match stale.first() {
+        Some(cache) => CheckResult::new(
+            "caches",
+            CheckStatus::Warning,
+            Message::new("doctor-caches-stale").arg("file", cache.display()),
+        )
+        .fix(Message::new("doctor-caches-stale-fix")),
+        None => CheckResult::new("caches", CheckStatus::Ok, Message::new("doctor-caches-ok")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_config, parse_http_date, CheckStatus};
+    use std::fs;
+
+    #[test]
+    fn parse_http_date_tests() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(784_111_777)
+        );
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(
+            parse_http_date("Tue, 29 Feb 2000 12:00:00 GMT"),
+            Some(951_825_600)
+        );
+        assert_eq!(parse_http_date("06 Nov 1994 08:49:37"), None);
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+    }
+
+    #[test]
+    fn check_config_tests() {
+        assert_eq!(check_config(&None).status, CheckStatus::Skipped);
+
+        let missing = Some("no-such-config.json".to_string());
+        let result = check_config(&missing);
+        assert_eq!(result.status, CheckStatus::Failed);
+        assert!(result.fix.is_some());
+
+        let invalid = std::env::temp_dir().join("safe_auth-doctor-invalid.json");
+        unwrap!(fs::write(&invalid, "{ not json"));
+        let result = check_config(&Some(invalid.display().to_string()));
+        assert_eq!(result.detail.id, "doctor-config-invalid");
+        let _ = fs::remove_file(invalid);
+
+        let result = check_config(&Some("tests/test.config.json".to_string()));
+        assert_ne!(result.status, CheckStatus::Failed);
+    }
+
+    #[cfg(feature = "daemon")]
+    #[test]
+    fn check_daemon_port_tests() {
+        use super::check_daemon_port;
+        use std::net::TcpListener;
+
+        let listener = unwrap!(TcpListener::bind("127.0.0.1:0"));
+        let port = unwrap!(listener.local_addr()).port();
+        let result = check_daemon_port(port);
+        assert_eq!(result.status, CheckStatus::Failed);
+        assert_eq!(result.detail.id, "doctor-daemon-port-in-use");
+        drop(listener);
+        assert_eq!(check_daemon_port(port).status, CheckStatus::Ok);
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The interactive editor of the permissions of an app, with `apps edit`. Each permission the
+// app has on a container is listed numbered and checked, and each line typed (un)checks the
+// ones with the numbers typed. An empty line ends the editing, which then has to be confirmed.
+// The app's own container is a single entry, as it's either granted with all the permissions
+// or not at all. It's line based like the selection of `revoke --interactive`.
+//
+// The permissions can't be removed from an app other than by revoking it, so the app is then
+// revoked and authorised again with exactly the permissions left checked, as `apps restore`
+// does for the apps not authorised anymore.
+
+use super::io::Console;
+use super::select::parse_numbers;
+use crate::apps::Regrant;
+use crate::{app_container_name, AuthedAppsList};
+use safe_core::ipc::req::ContainerPermissions;
+use safe_core::ipc::Permission;
+use std::collections::HashMap;
+
+// A permission of the app on a container, or its own container with all of them
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    container: String,
+    perm: Option<Permission>,
+}
+
+// The permissions checked once edited, if any was unchecked and the changes were confirmed
+pub fn edit_permissions(
+    console: &Console,
+    authed_app: &AuthedAppsList,
+) -> Option<HashMap<String, ContainerPermissions>> {
+    let entries = entries(authed_app);
+    let mut checked = vec![true; entries.len()];
+    outln!(console, "{}", tr!(console, "edit-app-help"));
+    loop {
+        for (number, entry) in entries.iter().enumerate() {
+            outln!(
+                console,
+                "{:>3}) [{}] {}",
+                number + 1,
+                if checked[number] { 'x' } else { ' ' },
+                describe(console, entry)
+            );
+        }
+
+        console.out(format_args!("{} ", tr!(console, "edit-app-prompt")));
+        let line = match console.read_line() {
+            Ok(line) if !line.is_empty() => line,
+            _ => return None,
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        for number in parse_numbers(line).unwrap_or_default() {
+            match checked.get_mut(number.wrapping_sub(1)) {
+                Some(checked) => *checked = !*checked,
+                None => outln!(
+                    console,
+                    "{}",
+                    tr!(console, "edit-app-invalid-number", number = number)
+                ),
+            }
+        }
+    }
+
+    let removed: Vec<String> = entries
+        .iter()
+        .zip(&checked)
+        .filter(|(_, checked)| !**checked)
+        .map(|(entry, _)| describe(console, entry))
+        .collect();
+    if removed.is_empty() {
+        return None;
+    }
+    console.out(format_args!(
+        "{} ",
+        tr!(
+            console,
+            "edit-app-confirm-prompt",
+            count = removed.len(),
+            app_id = authed_app.app.id,
+            perms = removed.join(", ")
+        )
+    ));
+    // Failing to read the answer is the same as not confirming it
+    let answer = console.read_line().unwrap_or_default();
+    let answer = answer.trim().to_lowercase();
+    if answer != "y" && answer != tr!(console, "allow-auth-yes").to_lowercase() {
+        return None;
+    }
+
+    let mut perms: HashMap<String, ContainerPermissions> = HashMap::new();
+    for (entry, _) in entries
+        .iter()
+        .zip(&checked)
+        .filter(|(_, checked)| **checked)
+    {
+        let cont_perms = perms.entry(entry.container.clone()).or_default();
+        if entry.perm.is_some() {
+            cont_perms.extend(entry.perm.iter().cloned());
+        } else {
+            cont_perms.extend(own_container_perms(authed_app, &entry.container));
+        }
+    }
+    Some(perms)
+}
+
+// The authorisation of the app with exactly the permissions given, once it's revoked, none if
+// it has no permissions left
+pub fn regrant(
+    authed_app: &AuthedAppsList,
+    mut perms: HashMap<String, ContainerPermissions>,
+) -> Option<Regrant> {
+    let app_container = perms.remove(&own_container(authed_app)).is_some();
+    if perms.is_empty() && !app_container {
+        return None;
+    }
+    Some(Regrant {
+        app: authed_app.app.clone(),
+        containers: perms,
+        register: true,
+        app_container,
+    })
+}
+
+fn own_container(authed_app: &AuthedAppsList) -> String {
+    app_container_name(&authed_app.app.id)
+}
+
+fn own_container_perms(authed_app: &AuthedAppsList, container: &str) -> ContainerPermissions {
+    authed_app
+        .perms
+        .iter()
+        .find(|(name, _)| name == container)
+        .map(|(_, perms)| perms.clone())
+        .unwrap_or_default()
+}
+
+// The entries of the app's permissions, sorted by container
+fn entries(authed_app: &AuthedAppsList) -> Vec<Entry> {
+    let own_container = own_container(authed_app);
+    let mut perms = authed_app.perms.clone();
+    perms.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut entries = vec![];
+    for (container, cont_perms) in perms {
+        if container == own_container {
+            entries.push(Entry {
+                container,
+                perm: None,
+            });
+            continue;
+        }
+        for perm in cont_perms {
+            entries.push(Entry {
+                container: container.clone(),
+                perm: Some(perm),
+            });
+        }
+    }
+    entries
+}
+
+fn describe(console: &Console, entry: &Entry) -> String {
+    match &entry.perm {
+        Some(perm) => format!("{}: {:?}", entry.container, perm),
+        None => format!(
+            "{} ({})",
+            entry.container,
+            tr!(console, "snapshot-own-container")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_permissions, regrant};
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::AuthedAppsList;
+    use safe_core::ipc::req::AppExchangeInfo;
+    use safe_core::ipc::Permission;
+
+    fn chat() -> AuthedAppsList {
+        AuthedAppsList {
+            app: AppExchangeInfo {
+                id: String::from("org.example.chat"),
+                scope: None,
+                name: String::from("Chat"),
+                vendor: String::from("Example"),
+            },
+            perms: vec![
+                (
+                    String::from("_public"),
+                    vec![Permission::Read, Permission::Insert]
+                        .into_iter()
+                        .collect(),
+                ),
+                (
+                    String::from("apps/org.example.chat"),
+                    vec![Permission::Read, Permission::Update]
+                        .into_iter()
+                        .collect(),
+                ),
+                (
+                    String::from("_music"),
+                    vec![Permission::Read].into_iter().collect(),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn edit_permissions_tests() {
+        let app = chat();
+
+        // The entries are sorted by container: _music, _public and the own container
+        let io = CapturedIo::new("1 3\n3\n2 9\n\ny\n");
+        let output = io.output();
+        let perms = unwrap!(edit_permissions(&Console::new(Box::new(io)), &app));
+        assert!(output.stdout().contains("  1) [x] _music: Read"));
+        assert!(output
+            .stdout()
+            .contains("  4) [x] apps/org.example.chat (Own container)"));
+        assert!(output.stdout().contains("There is no permission number 9"));
+        assert!(output.stdout().contains("Remove 2 permission(s)"));
+        assert!(!perms.contains_key("_music"));
+        assert_eq!(
+            perms["_public"],
+            vec![Permission::Insert].into_iter().collect()
+        );
+        assert_eq!(perms["apps/org.example.chat"].len(), 2);
+
+        let granted = unwrap!(regrant(&app, perms));
+        assert!(granted.register && granted.app_container);
+        assert_eq!(granted.containers.len(), 1);
+
+        // Unchanged, not confirmed, and the input ending before it's confirmed
+        for input in &["\n", "1\n\nn\n", "1\n"] {
+            let io = CapturedIo::new(input);
+            assert!(edit_permissions(&Console::new(Box::new(io)), &app).is_none());
+        }
+
+        // Nothing is granted again if everything was removed
+        let io = CapturedIo::new("1 2 3 4\n\ny\n");
+        let perms = unwrap!(edit_permissions(&Console::new(Box::new(io)), &app));
+        assert!(perms.is_empty());
+        assert!(regrant(&app, perms).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The filters of the list of authorised apps, e.g. `--apps --with-permission Delete` to find
+// the apps which can delete data, or `--apps --container _documents` the ones with access to
+// the documents. An app is kept if any of its containers matches, and only its containers
+// which match are kept, so the listing tells where the apps can do it.
+//
+// The container can be a pattern where `*` matches any characters, e.g. `apps/*` for the
+// apps' own containers, which are named after the apps' IDs.
+
+use crate::AuthedAppsList;
+use safe_core::ipc::Permission;
+
+// The permission given with `--with-permission`, its case being ignored
+pub fn parse_permission(permission: &str) -> Result<Permission, String> {
+    match permission.to_lowercase().as_str() {
+        "read" => Ok(Permission::Read),
+        "insert" => Ok(Permission::Insert),
+        "update" => Ok(Permission::Update),
+        "delete" => Ok(Permission::Delete),
+        "managepermissions" | "manage_permissions" | "manage-permissions" => {
+            Ok(Permission::ManagePermissions)
+        }
+        _ => Err(format!(
+            "'{}' is not a permission, it must be one of Read, Insert, Update, Delete or ManagePermissions",
+            permission
+        )),
+    }
+}
+
+// The apps with the permission on any of their containers
+pub fn with_permission(apps: Vec<AuthedAppsList>, permission: Permission) -> Vec<AuthedAppsList> {
+    apps.into_iter()
+        .filter_map(|mut authed_app| {
+            authed_app
+                .perms
+                .retain(|(_, perms)| perms.contains(&permission));
+            if authed_app.perms.is_empty() {
+                None
+            } else {
+                Some(authed_app)
+            }
+        })
+        .collect()
+}
+
+// The apps with any permission on a container matching the pattern
+pub fn in_container(apps: Vec<AuthedAppsList>, pattern: &str) -> Vec<AuthedAppsList> {
+    apps.into_iter()
+        .filter_map(|mut authed_app| {
+            authed_app
+                .perms
+                .retain(|(cont, perms)| !perms.is_empty() && matches_pattern(pattern, cont));
+            if authed_app.perms.is_empty() {
+                None
+            } else {
+                Some(authed_app)
+            }
+        })
+        .collect()
+}
+
+// Whether the name matches the pattern, where `*` matches any characters
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    if !name.starts_with(first) {
+        return false;
+    }
+    let mut rest = &name[first.len()..];
+    let mut parts: Vec<&str> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // No `*`, so it must be the same name
+        None => return rest.is_empty(),
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{in_container, matches_pattern, parse_permission, with_permission};
+    use crate::AuthedAppsList;
+    use safe_core::ipc::req::AppExchangeInfo;
+    use safe_core::ipc::Permission;
+
+    fn authed_app(id: &str, perms: &[(&str, &[Permission])]) -> AuthedAppsList {
+        AuthedAppsList {
+            app: AppExchangeInfo {
+                id: id.to_string(),
+                scope: None,
+                name: id.to_string(),
+                vendor: String::from("MaidSafe.net Ltd"),
+            },
+            perms: perms
+                .iter()
+                .map(|(cont, perms)| (cont.to_string(), perms.iter().cloned().collect()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn with_permission_tests() {
+        assert_eq!(unwrap!(parse_permission("delete")), Permission::Delete);
+        assert_eq!(
+            unwrap!(parse_permission("ManagePermissions")),
+            Permission::ManagePermissions
+        );
+        assert!(parse_permission("Write").is_err());
+
+        let apps = vec![
+            authed_app(
+                "net.maidsafe.cli",
+                &[
+                    ("_public", &[Permission::Read]),
+                    ("_documents", &[Permission::Read, Permission::Delete]),
+                ],
+            ),
+            authed_app("org.example.chat", &[("_public", &[Permission::Read])]),
+        ];
+        let apps = with_permission(apps, Permission::Delete);
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app.id, "net.maidsafe.cli");
+        // Only the containers where it can delete are listed
+        assert_eq!(apps[0].perms.len(), 1);
+        assert_eq!(apps[0].perms[0].0, "_documents");
+    }
+
+    #[test]
+    fn in_container_tests() {
+        assert!(matches_pattern("_documents", "_documents"));
+        assert!(!matches_pattern("_documents", "_documents2"));
+        assert!(matches_pattern("apps/*", "apps/net.maidsafe.cli"));
+        assert!(matches_pattern("apps/*.cli", "apps/net.maidsafe.cli"));
+        assert!(matches_pattern("*maidsafe*", "apps/net.maidsafe.cli"));
+        assert!(!matches_pattern("apps/*.cli", "apps/net.maidsafe.chat"));
+        assert!(!matches_pattern("a*a", "a"));
+
+        let apps = vec![
+            authed_app(
+                "net.maidsafe.cli",
+                &[
+                    ("_public", &[Permission::Read]),
+                    ("apps/net.maidsafe.cli", &[Permission::Read]),
+                ],
+            ),
+            authed_app("org.example.chat", &[("_documents", &[Permission::Read])]),
+        ];
+        let own = in_container(apps.clone(), "apps/*");
+        assert_eq!(own.len(), 1);
+        assert_eq!(own[0].perms[0].0, "apps/net.maidsafe.cli");
+        let documents = in_container(apps, "_documents");
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].app.id, "org.example.chat");
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The temporary grants, made by answering `t` to the prompt of a request to authorise an app:
+// the app is authorised the same as with `y`, but it's revoked once the grant expires, after
+// the config file's `temporary_grant_hours`, 24 by default. It's meant for the one-off tools,
+// which shouldn't keep their access to the account.
+//
+// The grants are stored with the time they expire in the `grants` area of the storage, i.e.
+// `grants/<profile>.json` of the user's app directory by default. The Authenticator service checks every minute for the ones which expired, and
+// the other invocations when they log in, as long as no service is running for the profile.
+// Each app revoked is written to stderr and emitted as a `GrantExpired` event. An app that
+// couldn't be revoked, e.g. as the network is down, is tried again the next time, while the
+// grant of an app which isn't authorised anymore is dropped.
+
+use super::instances::profile_entry;
+use super::io::WeakConsole;
+use crate::storage::{storage, Storage};
+use crate::{AuthBackend, AuthEvent, Error, EventBus, SharedBackend};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const GRANTS_AREA: &str = "grants";
+const SECONDS_PER_HOUR: u64 = 60 * 60;
+const DEFAULT_GRANT_HOURS: u64 = 24;
+// How often the service checks whether a grant expired
+const CHECK_PERIOD: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TemporaryGrant {
+    app_id: String,
+    // Seconds since the UNIX epoch
+    expires_at: u64,
+}
+
+// The temporary grants of a profile. Its clones share the same lock, so the grants recorded by
+// the service's prompt aren't lost while the expired ones are removed.
+#[derive(Clone)]
+pub struct TemporaryGrants {
+    storage: Arc<dyn Storage>,
+    entry: String,
+    hours: u64,
+    lock: Arc<Mutex<()>>,
+}
+
+impl TemporaryGrants {
+    pub fn for_profile(profile: &str, hours: Option<u64>) -> Result<Self, Error> {
+        let hours = hours.unwrap_or(DEFAULT_GRANT_HOURS);
+        if hours == 0 {
+            return Err(Error::InvalidInput(String::from(
+                "The `temporary_grant_hours` of the config file must be at least 1",
+            )));
+        }
+        Ok(Self::new(storage()?, profile_entry(profile, "json"), hours))
+    }
+
+    fn new(storage: Arc<dyn Storage>, entry: String, hours: u64) -> Self {
+        Self {
+            storage,
+            entry,
+            hours,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    // How long the grants last
+    pub fn hours(&self) -> u64 {
+        self.hours
+    }
+
+    // Records the grant of the app, replacing its previous one, returning when it expires
+    pub fn record(&self, app_id: &str) -> Result<u64, Error> {
+        self.record_at(app_id, now())
+    }
+
+    fn record_at(&self, app_id: &str, now: u64) -> Result<u64, Error> {
+        let _lock = unwrap!(self.lock.lock());
+        let expires_at = now + self.hours * SECONDS_PER_HOUR;
+        let mut grants = self.read()?;
+        grants.retain(|grant| grant.app_id != app_id);
+        grants.push(TemporaryGrant {
+            app_id: app_id.to_string(),
+            expires_at,
+        });
+        self.write(&grants)?;
+        Ok(expires_at)
+    }
+
+    // The ids of the apps whose grant expired
+    fn expired(&self, now: u64) -> Result<Vec<String>, Error> {
+        let _lock = unwrap!(self.lock.lock());
+        Ok(self
+            .read()?
+            .into_iter()
+            .filter(|grant| grant.expires_at <= now)
+            .map(|grant| grant.app_id)
+            .collect())
+    }
+
+    // Removes the grants of the apps, unless they were granted again in the meantime
+    fn remove(&self, app_ids: &[String], now: u64) -> Result<(), Error> {
+        let _lock = unwrap!(self.lock.lock());
+        let mut grants = self.read()?;
+        grants.retain(|grant| grant.expires_at > now || !app_ids.contains(&grant.app_id));
+        self.write(&grants)
+    }
+
+    fn read(&self) -> Result<Vec<TemporaryGrant>, Error> {
+        let content = match self.storage.read(GRANTS_AREA, &self.entry)? {
+            Some(content) => content,
+            None => return Ok(vec![]),
+        };
+        serde_json::from_slice(&content).map_err(|err| {
+            Error::IoError(format!(
+                "The temporary grants '{}' are not valid: {}",
+                self.entry, err
+            ))
+        })
+    }
+
+    fn write(&self, grants: &[TemporaryGrant]) -> Result<(), Error> {
+        let content = serde_json::to_vec(grants).map_err(|err| {
+            Error::IoError(format!("Failed to serialise the temporary grants: {}", err))
+        })?;
+        self.storage.write(GRANTS_AREA, &self.entry, &content)
+    }
+}
+
+// Checks in the background whether a grant expired, for as long as the service runs
+pub fn spawn_expiries(
+    grants: TemporaryGrants,
+    backend: SharedBackend,
+    events: EventBus,
+    console: WeakConsole,
+) {
+    let _ = thread::spawn(move || loop {
+        revoke_expired(&grants, &backend, Some(&events), &console);
+        thread::sleep(CHECK_PERIOD);
+    });
+}
+
+// Revokes the apps whose grant expired. The failures are only logged, the apps being tried
+// again the next time.
+pub fn revoke_expired(
+    grants: &TemporaryGrants,
+    backend: &dyn AuthBackend,
+    events: Option<&EventBus>,
+    console: &WeakConsole,
+) {
+    let now = now();
+    let expired = match grants.expired(now) {
+        Ok(expired) => expired,
+        Err(err) => {
+            warn!("The expired temporary grants couldn't be checked: {}", err);
+            return;
+        }
+    };
+    if expired.is_empty() {
+        return;
+    }
+    let authed_apps = match backend.authed_apps() {
+        Ok(authed_apps) => authed_apps,
+        Err(err) => {
+            warn!("The expired temporary grants weren't revoked: {}", err);
+            return;
+        }
+    };
+
+    let mut done = vec![];
+    for app_id in expired {
+        if !authed_apps.iter().any(|authed| authed.app.id == app_id) {
+            debug!("The app '{}' of an expired grant is not authorised", app_id);
+            done.push(app_id);
+            continue;
+        }
+        if let Err(err) = backend.revoke_app(&app_id) {
+            warn!(
+                "The app '{}' of an expired grant wasn't revoked: {}",
+                app_id, err
+            );
+            continue;
+        }
+        if let Some(console) = console.upgrade() {
+            errln!(
+                console,
+                "{}",
+                tr!(console, "grant-expired", app_id = app_id)
+            );
+        }
+        if let Some(events) = events {
+            events.emit(AuthEvent::GrantExpired {
+                app_id: app_id.clone(),
+            });
+        }
+        done.push(app_id);
+    }
+    if let Err(err) = grants.remove(&done, now) {
+        warn!("The expired temporary grants couldn't be removed: {}", err);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TemporaryGrants, SECONDS_PER_HOUR};
+    use crate::storage::MemoryStorage;
+    use std::sync::Arc;
+
+    #[test]
+    fn temporary_grants_tests() {
+        let storage = Arc::new(MemoryStorage::new());
+        let grants = TemporaryGrants::new(storage, String::from("work.json"), 24);
+        let day = 24 * SECONDS_PER_HOUR;
+
+        assert_eq!(
+            unwrap!(grants.record_at("net.maidsafe.first", 1000)),
+            1000 + day
+        );
+        let _ = unwrap!(grants.record_at("net.maidsafe.second", 2000));
+        assert!(unwrap!(grants.expired(1000 + day - 1)).is_empty());
+        assert_eq!(
+            unwrap!(grants.expired(1000 + day)),
+            vec!["net.maidsafe.first"]
+        );
+
+        // Granted again, it expires a period after the new grant
+        let _ = unwrap!(grants.record_at("net.maidsafe.first", 1500));
+        assert!(unwrap!(grants.expired(1000 + day)).is_empty());
+        assert_eq!(unwrap!(grants.expired(2000 + day)).len(), 2);
+
+        // The grants made since the expired ones were listed are kept
+        let expired = unwrap!(grants.expired(2000 + day));
+        let _ = unwrap!(grants.record_at("net.maidsafe.second", 2000 + day));
+        unwrap!(grants.remove(&expired, 2000 + day));
+        assert_eq!(
+            unwrap!(grants.expired(4000 + 2 * day)),
+            vec!["net.maidsafe.second"]
+        );
+    }
+}
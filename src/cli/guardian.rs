@@ -0,0 +1,267 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The co-approval of the high-risk operations by a guardian, i.e. the Authenticator service of
+// another device or person, enabled with the `guardian` field of the config file, e.g.
+// `{"url": "http://192.168.1.20:41805", "token": "..."}`. Revoking all the apps authorised at
+// once, and allowing an auth request sharing mutable data with the `ManagePermissions`
+// permission, fail unless the guardian approves them too. A guardian which can't be reached
+// doesn't approve anything.
+//
+// The guardian's service is the one run with the `guarding` field of its config file, with the
+// same token, e.g. `{"token": "..."}`: it prompts its user for the operations sent, over the
+// same authenticated channel as the CLI's own requests to a service. Both decisions are
+// recorded in the receipts of the profile, the guardian's one with the `guardian` approver.
+//
+// The guardian is asked first, so the user isn't prompted for a request it denies.
+
+use super::color::Color;
+use super::helpers::{prompt_to_allow_auth, PromptPolicies};
+use super::instances::ServiceClient;
+use super::io::Console;
+use super::receipts::record_decision;
+use crate::{
+    decode_req, needs_approval, AccountInfo, AccountKeys, Approval, ApprovalPrompt,
+    AuthAllowPrompt, AuthBackend, AuthedAppsDiff, AuthedAppsList, Error,
+};
+use log::warn;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+// The approver of the guardian's decisions in the receipts
+const GUARDIAN_APPROVER: &str = "guardian";
+
+// The `guardian` field of the config file, of the service co-approving the operations
+#[derive(Deserialize, Debug, Clone)]
+pub struct GuardianSettings {
+    pub url: String,
+    pub token: String,
+}
+
+// The `guarding` field of the config file, of the account the service is the guardian of
+#[derive(Deserialize, Debug, Clone)]
+pub struct GuardingSettings {
+    pub token: String,
+}
+
+pub struct Guardian {
+    client: ServiceClient,
+    profile: String,
+}
+
+impl Guardian {
+    pub fn new(settings: &GuardianSettings, profile: &str) -> Result<Self, Error> {
+        Ok(Self {
+            client: ServiceClient::with_url(&settings.url, &settings.token)?,
+            profile: profile.to_string(),
+        })
+    }
+
+    // Fails unless the guardian approves the operation, recording its decision
+    pub fn approve(&self, approval: &Approval) -> Result<(), Error> {
+        let approved = self.client.approve(approval).map_err(|err| {
+            Error::NetworkError(format!(
+                "The guardian couldn't be asked to approve the operation: {}",
+                err
+            ))
+        })?;
+        let recorded = approval_req(approval).and_then(|req| {
+            record_decision(&self.profile, &req, Some(GUARDIAN_APPROVER), approved)
+        });
+        if let Err(err) = recorded {
+            warn!("The decision of the guardian couldn't be recorded: {}", err);
+        }
+        if approved {
+            Ok(())
+        } else {
+            Err(Error::PolicyDenied(String::from(
+                "The guardian didn't approve the operation",
+            )))
+        }
+    }
+
+    // The revocations of all the apps authorised are approved by the guardian, and recorded
+    // with the user's decision, as no receipt records the revocations otherwise
+    pub fn approve_revocations(
+        &self,
+        backend: &dyn AuthBackend,
+        app_ids: &[String],
+    ) -> Result<(), Error> {
+        let authed_apps = backend.authed_apps()?;
+        if !revokes_all(&authed_apps, app_ids) {
+            return Ok(());
+        }
+        let approval = Approval::RevokeAll {
+            app_ids: app_ids.to_vec(),
+        };
+        self.approve(&approval)?;
+        record_decision(&self.profile, &approval_req(&approval)?, None, true)
+    }
+}
+
+// The request recorded in the receipts: the auth request itself, so its receipts are found
+// with it, otherwise the encoded approval
+fn approval_req(approval: &Approval) -> Result<String, Error> {
+    match approval {
+        Approval::ShareMData { req } => Ok(req.clone()),
+        Approval::RevokeAll { .. } => approval.encode(),
+    }
+}
+
+// Whether the apps are all the ones authorised, revoking a single app isn't revoking them all
+fn revokes_all(authed_apps: &[AuthedAppsList], app_ids: &[String]) -> bool {
+    let revoked: HashSet<&str> = app_ids.iter().map(String::as_str).collect();
+    authed_apps.len() > 1
+        && authed_apps
+            .iter()
+            .all(|authed| revoked.contains(authed.app.id.as_str()))
+}
+
+// Requires the guardian's approval for the auth requests which need it
+pub struct GuardianBackend {
+    inner: Box<dyn AuthBackend>,
+    guardian: Arc<Guardian>,
+}
+
+impl GuardianBackend {
+    pub fn new(inner: Box<dyn AuthBackend>, guardian: Arc<Guardian>) -> Self {
+        Self { inner, guardian }
+    }
+}
+
+impl AuthBackend for GuardianBackend {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        if let Ok((_, ipc_req)) = decode_req(req) {
+            if needs_approval(&ipc_req) {
+                self.guardian.approve(&Approval::ShareMData {
+                    req: req.to_string(),
+                })?;
+            }
+        }
+        self.inner.authorise_app(req, allow)
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.inner.authed_apps()
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        self.inner.revoke_app(app_id)
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.inner.acc_info()
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.inner.acc_keys()
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.inner.backup_account()
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.inner.restore_account(backup)
+    }
+}
+
+// The prompt of the guardian's service for the operations of the account it guards, keeping
+// only a weak reference to the streams.
+pub fn approval_prompt(console: &Console) -> Arc<ApprovalPrompt> {
+    let console = console.downgrade();
+    Arc::new(move |approval: &Approval| match console.upgrade() {
+        Some(console) => ask_approval(&console, approval),
+        None => false,
+    })
+}
+
+fn ask_approval(console: &Console, approval: &Approval) -> bool {
+    outln!(console, "{}", tr!(console, "guardian-approval-request"));
+    if !console.is_interactive() {
+        errln!(console, "{}", tr!(console, "guardian-non-interactive"));
+        return false;
+    }
+    let approved = match approval {
+        // Only the requests which need the approval are approved, an owner can't get another
+        // one approved in place of the request shown
+        Approval::ShareMData { req } => match decode_req(req) {
+            Ok((_, ipc_req)) if needs_approval(&ipc_req) => {
+                prompt_to_allow_auth(console, ipc_req, &PromptPolicies::default(), &[])
+            }
+            _ => false,
+        },
+        Approval::RevokeAll { app_ids } => {
+            console.out(format_args!(
+                "{} ",
+                tr!(
+                    console,
+                    "guardian-revoke-all-prompt",
+                    count = app_ids.len(),
+                    app_ids = app_ids.join(", ")
+                )
+            ));
+            // Failing to read the answer is the same as not approving it
+            let answer = console.read_line().unwrap_or_default();
+            let answer = answer.trim().to_lowercase();
+            answer == "y" || answer == tr!(console, "allow-auth-yes").to_lowercase()
+        }
+    };
+    if approved {
+        outln!(
+            console,
+            "{}",
+            console.paint(&tr!(console, "guardian-approved"), Color::Green)
+        );
+    } else {
+        outln!(
+            console,
+            "{}",
+            console.paint(&tr!(console, "guardian-denied"), Color::Red)
+        );
+    }
+    approved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::revokes_all;
+    use crate::AuthedAppsList;
+    use safe_core::ipc::req::AppExchangeInfo;
+
+    fn authed(id: &str) -> AuthedAppsList {
+        AuthedAppsList {
+            app: AppExchangeInfo {
+                id: id.to_string(),
+                scope: None,
+                name: id.to_string(),
+                vendor: String::from("Example"),
+            },
+            perms: vec![],
+        }
+    }
+
+    #[test]
+    fn revokes_all_tests() {
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let apps = vec![authed("net.maidsafe.cli"), authed("org.example.chat")];
+
+        assert!(revokes_all(
+            &apps,
+            &ids(&["org.example.chat", "net.maidsafe.cli"])
+        ));
+        assert!(revokes_all(
+            &apps,
+            &ids(&["net.maidsafe.cli", "org.example.chat", "revoked"])
+        ));
+        assert!(!revokes_all(&apps, &ids(&["net.maidsafe.cli"])));
+        // The single app authorised
+        assert!(!revokes_all(&apps[..1], &ids(&["net.maidsafe.cli"])));
+    }
+}
@@ -0,0 +1,1479 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+extern crate serde;
+extern crate serde_json;
+
+use super::attention::AttentionSettings;
+use super::color::{is_dangerous, Color};
+use super::guardian::{GuardianSettings, GuardingSettings};
+use super::i18n::Localiser;
+use super::io::{Console, WeakConsole};
+use super::known_apps::{recognise, KnownApp, Recognition};
+use super::labels::labels;
+use super::policy::PolicySource;
+use super::receipts::AuditSettings;
+use super::review::ReviewSettings;
+use super::table::{Row, Table};
+use super::update::VersionCheckSettings;
+use super::verification::UserVerification;
+use crate::ipc::ReqKind;
+use crate::storage::{entry_file, storage};
+use crate::{
+    app_container_name, needs_approval, CancellationToken, Error, OperationProgress,
+    OperationTimeouts, PacingSettings, PendingDecision, ProgressReporter, RequestWarning,
+};
+use lazy_static::lazy_static;
+use log::debug;
+use safe_core::ipc::req::{AppExchangeInfo, AuthReq, IpcReq};
+use safe_nd::MDataAction;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Duration, Instant};
+
+// Exit code used when the execution is interrupted by the user, e.g. with Ctrl+C
+pub const EXIT_CODE_INTERRUPTED: i32 = 130;
+
+// Exit code used when the arguments can't be parsed, as the arguments parser used to exit with
+pub const EXIT_CODE_INVALID_ARGS: i32 = 1;
+
+// The period over which the requests are counted for the `max_per_minute` of their kind
+const RATE_LIMIT_PERIOD: Duration = Duration::from_secs(60);
+// How often a prompt checks if its request was decided elsewhere, e.g. with its toast
+const DECISION_CHECK_PERIOD: Duration = Duration::from_millis(250);
+
+// Exit code used for each kind of error, so scripts can tell the failures apart
+pub fn exit_code(err: &Error) -> i32 {
+    match err {
+        Error::AuthenticatorError(_) => 1,
+        Error::InvalidInput(_) => 2,
+        Error::CredentialsError(_) => 3,
+        Error::NetworkError(_) => 4,
+        Error::IpcDecodeError(_) => 5,
+        Error::PolicyDenied(_) => 6,
+        Error::IoError(_) => 7,
+        Error::IncompatibleVersion(_) => 8,
+        Error::Timeout(_) => 9,
+        Error::Cancelled(_) => EXIT_CODE_INTERRUPTED,
+    }
+}
+
+// The message the error is reported with, its description being left as it is
+pub fn error_message_id(err: &Error) -> &'static str {
+    match err {
+        Error::AuthenticatorError(_) => "error-authenticator",
+        Error::InvalidInput(_) => "error-invalid-input",
+        Error::CredentialsError(_) => "error-credentials",
+        Error::NetworkError(_) => "error-network",
+        Error::IpcDecodeError(_) => "error-ipc-decode",
+        Error::PolicyDenied(_) => "error-policy-denied",
+        Error::IoError(_) => "error-io",
+        Error::IncompatibleVersion(_) => "error-incompatible-version",
+        Error::Timeout(_) => "error-timeout",
+        Error::Cancelled(_) => "error-cancelled",
+    }
+}
+
+pub const CRUST_CONFIG_FILE_NAME: &str = "safe_auth.crust.config";
+// The area of the storage of the crust configs generated with the bootstrap cache enabled
+const BOOTSTRAP_CACHE_AREA: &str = "bootstrap_cache";
+
+// Settings read from the config file apart from the credentials
+#[derive(Deserialize, Debug, Default)]
+pub struct ConfigSettings {
+    pub timeouts: Option<OperationTimeouts>,
+    pub version_check: Option<VersionCheckSettings>,
+    pub prompts: Option<PromptPolicies>,
+    pub verify_user: Option<UserVerification>,
+    pub attention: Option<AttentionSettings>,
+    pub pacing: Option<PacingSettings>,
+    pub known_apps: Option<Vec<KnownApp>>,
+    pub audit: Option<AuditSettings>,
+    pub event_history: Option<EventHistorySettings>,
+    pub permission_review: Option<ReviewSettings>,
+    pub policy_source: Option<PolicySource>,
+    pub guardian: Option<GuardianSettings>,
+    pub guarding: Option<GuardingSettings>,
+    // How long the temporary grants last
+    pub temporary_grant_hours: Option<u64>,
+    // How many of the commands typed in the shell are kept in its history, 0 to keep none
+    pub shell_history: Option<usize>,
+    // The invite service the fresh invite tokens are fetched from, when the one given can't
+    // pay for the account. With the mock network they are preloaded with test-coins instead.
+    #[cfg_attr(feature = "mock-network", allow(dead_code))]
+    pub invite_url: Option<String>,
+}
+
+// The `event_history` settings of the config file, of the events kept by the Authenticator
+// service for the clients which missed them, e.g. `{"capacity": 200, "persist": true}`
+#[derive(Deserialize, Debug, Default)]
+pub struct EventHistorySettings {
+    pub capacity: Option<usize>,
+    // Whether the events are kept across the restarts of the service
+    #[serde(default)]
+    pub persist: bool,
+}
+
+// The decision taken on an auth request which isn't answered in time
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptDecision {
+    Allow,
+    Deny,
+}
+
+// How a kind of auth request is decided on
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptMode {
+    // A single y/N prompt
+    #[default]
+    Prompt,
+    // Allowed without prompting, the default of the unregistered requests
+    Allow,
+    // A prompt for each MutableData of the requests to share them, the other kinds of requests
+    // have a single prompt
+    PerItem,
+    // Denied without prompting, even with `--allow-all-auth`
+    Deny,
+}
+
+// How long, in seconds, the prompt of a kind of auth request waits for the answer, and the
+// decision taken if it's not answered in time, which is to deny it unless otherwise set.
+// Without a timeout the prompt waits for the answer indefinitely. The requests received once
+// `max_per_minute` of them were received in the last minute are denied without prompting.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PromptPolicy {
+    pub timeout: Option<u64>,
+    pub default: Option<PromptDecision>,
+    pub mode: Option<PromptMode>,
+    pub max_per_minute: Option<usize>,
+}
+
+// The prompt policy of each kind of auth request. The unregistered requests are allowed
+// without prompting unless they have a policy, or a policy with the `allow` mode.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PromptPolicies {
+    pub auth: Option<PromptPolicy>,
+    pub containers: Option<PromptPolicy>,
+    pub share_mdata: Option<PromptPolicy>,
+    pub unregistered: Option<PromptPolicy>,
+    // The IDs of the apps whose requests are denied without prompting
+    #[serde(default)]
+    pub denylist: Vec<String>,
+}
+
+impl PromptPolicies {
+    pub fn for_req(&self, req: &IpcReq) -> Option<&PromptPolicy> {
+        match req {
+            IpcReq::Auth(_) => self.auth.as_ref(),
+            IpcReq::Containers(_) => self.containers.as_ref(),
+            IpcReq::ShareMData(_) => self.share_mdata.as_ref(),
+            IpcReq::Unregistered(_) => self.unregistered.as_ref(),
+        }
+    }
+
+    // How the request is decided on, the unregistered requests without a policy are allowed
+    pub fn mode_for(&self, req: &IpcReq) -> PromptMode {
+        match (self.for_req(req), req) {
+            (Some(policy), _) => policy.mode.unwrap_or_default(),
+            (None, IpcReq::Unregistered(_)) => PromptMode::Allow,
+            (None, _) => PromptMode::Prompt,
+        }
+    }
+
+    // Sets the timeout of the prompts of all the kinds of requests, keeping their default
+    // decision. The unregistered requests are still allowed without prompting if they have
+    // no policy.
+    pub fn set_timeout(&mut self, timeout: u64) {
+        for policy in [&mut self.auth, &mut self.containers, &mut self.share_mdata] {
+            policy.get_or_insert_with(PromptPolicy::default).timeout = Some(timeout);
+        }
+        if let Some(policy) = &mut self.unregistered {
+            policy.timeout = Some(timeout);
+        }
+    }
+
+    // Whether the request is denied without prompting, its kind or its app being denied
+    pub fn denies(&self, req: &IpcReq) -> bool {
+        self.mode_for(req) == PromptMode::Deny || self.denylisted(req)
+    }
+
+    // Whether the app which sent the request is on the denylist
+    pub fn denylisted(&self, req: &IpcReq) -> bool {
+        req_app_id(req).is_some_and(|app_id| self.denylist.iter().any(|denied| denied == app_id))
+    }
+
+    // Whether any kind of request or app is denied without prompting, or rate limited, in which
+    // case the requests can't be allowed without checking them even with `--allow-all-auth`
+    pub fn restricts_any(&self) -> bool {
+        let restricts_kind = [
+            &self.auth,
+            &self.containers,
+            &self.share_mdata,
+            &self.unregistered,
+        ]
+        .iter()
+        .filter_map(|policy| policy.as_ref())
+        .any(|policy| policy.mode == Some(PromptMode::Deny) || policy.max_per_minute.is_some());
+        restricts_kind || !self.denylist.is_empty()
+    }
+}
+
+// The requests of each kind received in the last minute, so the kinds with a `max_per_minute`
+// are denied once they exceed it
+#[derive(Default)]
+pub struct RateLimits {
+    received: Mutex<HashMap<ReqKind, VecDeque<Instant>>>,
+}
+
+impl RateLimits {
+    // Whether the request is within the rate limit of its kind, counting it if it is
+    pub fn admit(&self, req: &IpcReq, policies: &PromptPolicies) -> bool {
+        let max = match policies
+            .for_req(req)
+            .and_then(|policy| policy.max_per_minute)
+        {
+            Some(max) => max,
+            None => return true,
+        };
+        let mut received = unwrap!(self.received.lock());
+        let received = received
+            .entry(ReqKind::of(req))
+            .or_insert_with(VecDeque::new);
+        while received
+            .front()
+            .is_some_and(|time| time.elapsed() >= RATE_LIMIT_PERIOD)
+        {
+            let _ = received.pop_front();
+        }
+        if received.len() >= max {
+            return false;
+        }
+        received.push_back(Instant::now());
+        true
+    }
+}
+
+// The format of the progress written to stderr with `--progress`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressFormat {
+    // A JSON object per line, for the GUIs driving the CLI
+    Json,
+}
+
+impl FromStr for ProgressFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "json" => Ok(ProgressFormat::Json),
+            other => Err(format!(
+                "'{}' is not a progress format, it must be 'json'",
+                other
+            )),
+        }
+    }
+}
+
+// A line written with `--progress json`, e.g.
+// `{"stage":"Log in to the SAFE Network","percent":50,"message":"Connecting to the network"}`
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ProgressEvent<'a> {
+    // The operation of the CLI in flight
+    pub stage: &'a str,
+    pub percent: usize,
+    // The step of the operation which started, or its outcome
+    pub message: &'a str,
+}
+
+#[derive(Default)]
+struct Progress {
+    completed: Vec<String>,
+    in_flight: Option<String>,
+    // Step of the operation in flight, as reported by the library
+    step: Option<String>,
+    // Where the progress events are written with `--progress json`, weakly referenced as the
+    // tracker is kept by the Ctrl+C handler
+    json: Option<WeakConsole>,
+    // The percentage of the operation in flight last written
+    percent: Option<usize>,
+}
+
+impl Progress {
+    fn emit(&mut self, stage: &str, percent: usize, message: &str) {
+        let console = match self.json.as_ref().and_then(WeakConsole::upgrade) {
+            Some(console) => console,
+            None => return,
+        };
+        self.percent = Some(percent);
+        let event = ProgressEvent {
+            stage,
+            percent,
+            message,
+        };
+        if let Ok(line) = serde_json::to_string(&event) {
+            errln!(console, "{}", line);
+        }
+    }
+}
+
+// Keeps track of the operations performed, so they can be reported if the execution is
+// interrupted, and writes their progress to stderr with `--progress json`
+#[derive(Default, Clone)]
+pub struct ProgressTracker(Arc<Mutex<Progress>>);
+
+impl ProgressTracker {
+    pub fn new(console: &Console, format: Option<ProgressFormat>) -> Self {
+        let tracker = Self::default();
+        if let Some(ProgressFormat::Json) = format {
+            unwrap!(tracker.0.lock()).json = Some(console.downgrade());
+        }
+        tracker
+    }
+
+    pub fn start(&self, op: &str) {
+        let mut progress = unwrap!(self.0.lock());
+        progress.in_flight = Some(op.to_string());
+        progress.step = None;
+        progress.percent = None;
+        progress.emit(op, 0, op);
+    }
+
+    pub fn done(&self) {
+        let mut progress = unwrap!(self.0.lock());
+        progress.step = None;
+        if let Some(op) = progress.in_flight.take() {
+            // Unless the library already reported the outcome of the operation
+            if progress.percent != Some(100) {
+                progress.emit(&op, 100, &op);
+            }
+            progress.completed.push(op);
+        }
+    }
+}
+
+impl ProgressReporter for ProgressTracker {
+    fn report(&self, step: &OperationProgress) {
+        let mut progress = unwrap!(self.0.lock());
+        progress.step = if step.is_finished() {
+            None
+        } else {
+            Some(format!(
+                "step {}/{}: {}",
+                step.completed + 1,
+                step.total,
+                step.step
+            ))
+        };
+        let stage = progress
+            .in_flight
+            .clone()
+            .unwrap_or_else(|| step.operation.clone());
+        let percent = (step.completed * 100)
+            .checked_div(step.total)
+            .unwrap_or(100);
+        progress.emit(&stage, percent, &step.step);
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct AccountSummary {
+    pub network: String,
+    pub balance: String,
+    /// The share of the balance the account was funded with which was spent storing its data,
+    /// in percent, if it's known
+    pub storage_used_percent: Option<u64>,
+    pub authed_apps: usize,
+    pub containers: Vec<String>,
+    /// The time the latest auth request was allowed, in seconds since the UNIX epoch
+    pub last_authorised: Option<u64>,
+}
+
+// The outcome of the revocation of each of the apps of the `revoke` command
+#[derive(Serialize, Debug)]
+pub struct RevokeOutcome {
+    pub app_id: String,
+    pub revoked: bool,
+    pub error: Option<Error>,
+}
+
+// The app ids listed in a file, one per line or as a JSON array, e.g. an audit's report.
+// The empty lines and the ones starting with `#` are ignored.
+pub fn read_app_ids(file: &Path) -> Result<Vec<String>, Error> {
+    let content = fs::read_to_string(file)
+        .map_err(|err| Error::IoError(format!("Failed to read the applications' IDs: {}", err)))?;
+    if content.trim_start().starts_with('[') {
+        return serde_json::from_str(&content).map_err(|err| {
+            Error::InvalidInput(format!(
+                "The applications' IDs file is not a valid JSON array of strings: {}",
+                err
+            ))
+        });
+    }
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(feature = "mock-network")]
+pub fn network_name() -> String {
+    "Mock network".to_string()
+}
+
+#[cfg(not(feature = "mock-network"))]
+pub fn network_name() -> String {
+    "SAFE Network".to_string()
+}
+
+// Whether the execution was interrupted with Ctrl+C since it was last checked
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+// The handler can only be set once for the process, even if the CLI is run many times
+static INTERRUPT_HANDLER: Once = Once::new();
+
+// What the Ctrl+C handler reports and cancels, the ones of the latest run
+struct Interruptible {
+    tracker: ProgressTracker,
+    cancel: CancellationToken,
+    localiser: Localiser,
+}
+
+lazy_static! {
+    static ref INTERRUPTIBLE: Mutex<Option<Interruptible>> = Mutex::new(None);
+}
+
+// Installs a Ctrl+C handler which reports the operations completed and the one
+// which was interrupted, and cancels it with the token provided, so it fails with a
+// Cancelled error. The run then returns it rather than the process exiting right away, so
+// the registration of the service, if any, is removed, and the CLI exits with the
+// EXIT_CODE_INTERRUPTED exit code.
+pub fn install_interrupt_handler(
+    tracker: ProgressTracker,
+    cancel: CancellationToken,
+    localiser: Localiser,
+) -> Result<(), Error> {
+    *unwrap!(INTERRUPTIBLE.lock()) = Some(Interruptible {
+        tracker,
+        cancel,
+        localiser,
+    });
+    let mut result = Ok(());
+    INTERRUPT_HANDLER.call_once(|| {
+        result = ctrlc::set_handler(on_interrupt)
+            .map_err(|err| Error::IoError(format!("Failed to set Ctrl+C handler: {}", err)));
+    });
+    result
+}
+
+fn on_interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    let interruptible = unwrap!(INTERRUPTIBLE.lock());
+    let Interruptible {
+        tracker,
+        cancel,
+        localiser,
+    } = match interruptible.as_ref() {
+        Some(interruptible) => interruptible,
+        None => return,
+    };
+    {
+        let progress = unwrap!(tracker.0.lock());
+        eprintln!("\n{}", tr!(localiser, "interrupted"));
+        for op in progress.completed.iter() {
+            eprintln!("{}", tr!(localiser, "op-completed", op = op));
+        }
+        if let Some(op) = &progress.in_flight {
+            match &progress.step {
+                Some(step) => eprintln!(
+                    "{}",
+                    tr!(localiser, "op-not-completed-step", op = op, step = step)
+                ),
+                None => eprintln!("{}", tr!(localiser, "op-not-completed", op = op)),
+            }
+        }
+    }
+    cancel.cancel();
+}
+
+// Whether the execution was interrupted with Ctrl+C, e.g. while the user was prompted
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+// Same as `is_interrupted`, the next run not being interrupted from now on
+pub fn take_interrupted() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+// The crust config found in the directory given
+fn read_crust_config(crust_config_path: &str) -> Result<serde_json::Value, Error> {
+    let config_file = Path::new(crust_config_path).join(CRUST_CONFIG_FILE_NAME);
+    let file = fs::File::open(&config_file).map_err(|err| {
+        Error::IoError(format!(
+            "Failed to read crust config from '{}': {}",
+            config_file.display(),
+            err
+        ))
+    })?;
+    serde_json::from_reader(file).map_err(|err| {
+        Error::InvalidInput(format!("Format of the crust config is not valid: {}", err))
+    })
+}
+
+// The name of the network the crust config in the directory given connects to, if it has one
+pub fn configured_network_name(crust_config_path: &str) -> Option<String> {
+    read_crust_config(crust_config_path)
+        .ok()
+        .and_then(|crust_config| crust_config["network_name"].as_str().map(String::from))
+}
+
+// Generates a copy of the crust config found in `crust_config_path` with crust's
+// bootstrap cache enabled, so subsequent connections to the same network skip the
+// slow discovery phase. It returns the path where the generated config was stored,
+// to be used as the additional search path for the crust config file.
+pub fn enable_bootstrap_cache(crust_config_path: &str, fresh: bool) -> Result<String, Error> {
+    let mut crust_config = read_crust_config(crust_config_path)?;
+
+    let network = crust_config["network_name"]
+        .as_str()
+        .unwrap_or("default")
+        .to_string();
+    let cache_name = format!("safe_auth.{}.bootstrap.cache", network);
+
+    if fresh {
+        // Discard any contact cached by crust from previous connections to this network
+        let cache_dirs = vec![
+            config_file_handler::user_app_dir(),
+            config_file_handler::system_cache_dir(),
+        ];
+        for dir in cache_dirs.into_iter().filter_map(Result::ok) {
+            match fs::remove_file(dir.join(&cache_name)) {
+                Err(ref err) if err.kind() != io::ErrorKind::NotFound => {
+                    return Err(Error::IoError(format!(
+                        "Failed to remove bootstrap cache: {}",
+                        err
+                    )));
+                }
+                _ => {}
+            }
+        }
+        debug!("Bootstrap cache for network '{}' was cleared", network);
+    }
+
+    crust_config["bootstrap_cache_name"] = serde_json::Value::String(cache_name);
+    let config_str = serde_json::to_string_pretty(&crust_config)
+        .map_err(|err| Error::IoError(format!("Failed to serialise crust config: {}", err)))?;
+    let entry = format!("{}/{}", network, CRUST_CONFIG_FILE_NAME);
+    storage()?
+        .write(BOOTSTRAP_CACHE_AREA, &entry, config_str.as_bytes())
+        .map_err(|err| Error::IoError(format!("Failed to write crust config: {}", err)))?;
+
+    // safe_core reads the crust config from a file
+    entry_file(BOOTSTRAP_CACHE_AREA, &entry)?
+        .and_then(|file| file.parent().map(|dir| dir.display().to_string()))
+        .ok_or_else(|| Error::IoError(String::from("Failed to write crust config")))
+}
+
+pub fn get_settings(config_file: &Option<String>) -> Result<ConfigSettings, Error> {
+    let config_file_str = match config_file {
+        Some(config_file_str) => config_file_str,
+        None => return Ok(ConfigSettings::default()),
+    };
+
+    let file = fs::File::open(config_file_str)
+        .map_err(|err| Error::IoError(format!("Error reading config file. {}", err)))?;
+    let settings: ConfigSettings = serde_json::from_reader(file).map_err(|err| {
+        Error::InvalidInput(format!(
+            "Format of the config file is not valid and couldn't be parsed: {}",
+            err
+        ))
+    })?;
+
+    Ok(settings)
+}
+
+// The id of the app which sent the request, unless it's unregistered
+pub fn req_app_id(req: &IpcReq) -> Option<&str> {
+    match req {
+        IpcReq::Auth(app_auth_req) => Some(&app_auth_req.app.id),
+        IpcReq::Containers(cont_req) => Some(&cont_req.app.id),
+        IpcReq::ShareMData(share_mdata_req) => Some(&share_mdata_req.app.id),
+        IpcReq::Unregistered(_) => None,
+    }
+}
+
+// The answer to the prompt of an auth request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthAnswer {
+    Denied,
+    Allowed,
+    // Allowed until the temporary grant expires, only for the requests to authorise an app
+    AllowedTemporarily,
+}
+
+pub fn prompt_to_allow_auth(
+    console: &Console,
+    req: IpcReq,
+    policies: &PromptPolicies,
+    warnings: &[RequestWarning],
+) -> bool {
+    prompt_for_auth(console, req, policies, warnings, None, None) != AuthAnswer::Denied
+}
+
+// Same as `prompt_to_allow_auth`, the requests to authorise an app being also allowed
+// temporarily if the hours of the temporary grants are given. The request is decided on
+// with the decision given if it's taken while the prompt waits, e.g. with its toast.
+pub fn prompt_for_auth(
+    console: &Console,
+    req: IpcReq,
+    policies: &PromptPolicies,
+    warnings: &[RequestWarning],
+    temporary_hours: Option<u64>,
+    mut decision: Option<&mut PendingDecision>,
+) -> AuthAnswer {
+    let policy = policies.for_req(&req);
+    match &req {
+        IpcReq::Auth(app_auth_req) => {
+            outln!(console, "{}", tr!(console, "auth-req-received"));
+            let mut table = Table::new();
+            table.add_row(req_header(console, "header-permissions-requested"));
+            table.add_row(req_row(
+                &app_auth_req.app,
+                format!(
+                    "{}\n{}",
+                    own_container_line(console, app_auth_req),
+                    tr!(
+                        console,
+                        "default-containers",
+                        containers = format!("{:?}", app_auth_req.containers)
+                    )
+                ),
+                app_auth_req.containers.values().any(is_dangerous),
+            ));
+            console.print_table(&table);
+            print_recognition(console, &app_auth_req.app.id, &app_auth_req.app.name);
+        }
+        IpcReq::Containers(cont_req) => {
+            outln!(console, "{}", tr!(console, "containers-req-received"));
+            outln!(console, "{:?}", cont_req);
+            let mut table = Table::new();
+            table.add_row(req_header(console, "header-permissions-requested"));
+            table.add_row(req_row(
+                &cont_req.app,
+                format!("{:?}", cont_req.containers),
+                cont_req.containers.values().any(is_dangerous),
+            ));
+            console.print_table(&table);
+            print_recognition(console, &cont_req.app.id, &cont_req.app.name);
+        }
+        IpcReq::ShareMData(share_mdata_req) => {
+            outln!(console, "{}", tr!(console, "share-mdata-req-received"));
+            let mut row = String::from("");
+            for mdata in share_mdata_req.mdata.iter() {
+                row += &format!(
+                    "{}\n{}",
+                    tr!(console, "mdata-type-tag", type_tag = mdata.type_tag),
+                    tr!(console, "mdata-name", name = format!("{:?}", mdata.name))
+                );
+                let insert_perm = if mdata.perms.is_allowed(MDataAction::Insert) {
+                    " Insert"
+                } else {
+                    ""
+                };
+                let update_perm = if mdata.perms.is_allowed(MDataAction::Update) {
+                    " Update"
+                } else {
+                    ""
+                };
+                let delete_perm = if mdata.perms.is_allowed(MDataAction::Delete) {
+                    " Delete"
+                } else {
+                    ""
+                };
+                let manage_perm = if mdata.perms.is_allowed(MDataAction::ManagePermissions) {
+                    " ManagePermissions"
+                } else {
+                    ""
+                };
+                let permissions = format!(
+                    "{}{}{}{}",
+                    insert_perm, update_perm, delete_perm, manage_perm
+                );
+                row += &format!(
+                    "\n{}\n\n",
+                    tr!(console, "mdata-permissions", permissions = permissions)
+                );
+            }
+            let mut table = Table::new();
+            table.add_row(req_header(console, "header-mdata-requested"));
+            table.add_row(req_row(&share_mdata_req.app, row, needs_approval(&req)));
+            console.print_table(&table);
+            print_recognition(console, &share_mdata_req.app.id, &share_mdata_req.app.name);
+        }
+        IpcReq::Unregistered(_) => {
+            // we simply allow unregistered authorisation requests, unless told otherwise
+            if policies.mode_for(&req) == PromptMode::Allow {
+                return AuthAnswer::Allowed;
+            }
+            outln!(console, "{}", tr!(console, "unregistered-req-received"));
+        }
+    };
+    print_warnings(console, warnings);
+
+    let answer = match (policies.mode_for(&req), &req) {
+        _ if policies.denylisted(&req) => {
+            outln!(console, "{}", tr!(console, "auth-denied-by-denylist"));
+            AuthAnswer::Denied
+        }
+        (PromptMode::Allow, _) => {
+            outln!(console, "{}", tr!(console, "auth-allowed-by-policy"));
+            AuthAnswer::Allowed
+        }
+        (PromptMode::Deny, _) => {
+            outln!(console, "{}", tr!(console, "auth-denied-by-policy"));
+            AuthAnswer::Denied
+        }
+        // All the MutableData must be allowed, the first one denied denies the request
+        (PromptMode::PerItem, IpcReq::ShareMData(share_mdata_req)) => {
+            let allowed = share_mdata_req.mdata.iter().all(|mdata| {
+                let question = tr!(
+                    console,
+                    "share-mdata-item-prompt",
+                    name = format!("{:?}", mdata.name),
+                    type_tag = mdata.type_tag
+                );
+                ask(console, &question, policy, decision.as_deref_mut())
+            });
+            if allowed {
+                AuthAnswer::Allowed
+            } else {
+                AuthAnswer::Denied
+            }
+        }
+        (_, IpcReq::Auth(_)) if temporary_hours.is_some() => {
+            let question = tr!(
+                console,
+                "allow-auth-temporary-prompt",
+                hours = temporary_hours.unwrap_or(0)
+            );
+            ask_answer(console, &question, policy, true, decision)
+        }
+        _ => ask_answer(
+            console,
+            &tr!(console, "allow-auth-prompt"),
+            policy,
+            false,
+            decision,
+        ),
+    };
+
+    match answer {
+        AuthAnswer::Allowed => outln!(
+            console,
+            "{}",
+            console.paint(&tr!(console, "auth-allowed"), Color::Green)
+        ),
+        AuthAnswer::AllowedTemporarily => outln!(
+            console,
+            "{}",
+            console.paint(
+                &tr!(
+                    console,
+                    "auth-allowed-temporarily",
+                    hours = temporary_hours.unwrap_or(0)
+                ),
+                Color::Green
+            )
+        ),
+        AuthAnswer::Denied => outln!(
+            console,
+            "{}",
+            console.paint(&tr!(console, "auth-denied"), Color::Red)
+        ),
+    }
+    answer
+}
+
+// Asks the question until the policy's timeout, if any, expires, or the decision given is taken
+fn ask(
+    console: &Console,
+    question: &str,
+    policy: Option<&PromptPolicy>,
+    decision: Option<&mut PendingDecision>,
+) -> bool {
+    ask_answer(console, question, policy, false, decision) == AuthAnswer::Allowed
+}
+
+// Same as `ask`, the answer of allow-auth-temporary allowing the request temporarily if it's
+// offered
+fn ask_answer(
+    console: &Console,
+    question: &str,
+    policy: Option<&PromptPolicy>,
+    temporary: bool,
+    decision: Option<&mut PendingDecision>,
+) -> AuthAnswer {
+    console.out(format_args!("{} ", question));
+    let timeout = policy.and_then(|policy| policy.timeout);
+    let reply = read_reply(console, timeout.map(Duration::from_secs), decision);
+    // The line typed after Ctrl+C, e.g. to get the prompt to return, is not an answer
+    if is_interrupted() {
+        return AuthAnswer::Denied;
+    }
+    match reply {
+        Ok(Reply::Decided(allowed)) => {
+            outln!(console);
+            outln!(console, "{}", tr!(console, "auth-decided-from-toast"));
+            if allowed {
+                AuthAnswer::Allowed
+            } else {
+                AuthAnswer::Denied
+            }
+        }
+        Ok(Reply::Typed(prompt)) if temporary && is_temporary(console, &prompt) => {
+            AuthAnswer::AllowedTemporarily
+        }
+        Ok(Reply::Typed(prompt)) if is_allowed(console, &prompt) => AuthAnswer::Allowed,
+        Ok(Reply::Typed(_)) => AuthAnswer::Denied,
+        Ok(Reply::TimedOut) => {
+            outln!(console);
+            outln!(
+                console,
+                "{}",
+                tr!(console, "prompt-timed-out", seconds = timeout.unwrap_or(0))
+            );
+            if policy.and_then(|policy| policy.default) == Some(PromptDecision::Allow) {
+                AuthAnswer::Allowed
+            } else {
+                AuthAnswer::Denied
+            }
+        }
+        // Failing to read the answer is the same as not allowing it
+        Err(_) => AuthAnswer::Denied,
+    }
+}
+
+// What a prompt got: the line typed, nothing before the timeout, or the decision taken
+// meanwhile, e.g. with the toast of the request
+enum Reply {
+    Typed(String),
+    TimedOut,
+    Decided(bool),
+}
+
+// The line typed before the timeout, if any, the decision given being checked periodically
+// while waiting for it
+fn read_reply(
+    console: &Console,
+    timeout: Option<Duration>,
+    decision: Option<&mut PendingDecision>,
+) -> io::Result<Reply> {
+    let decision = match decision {
+        Some(decision) => decision,
+        None => {
+            return Ok(match console.read_line_timeout(timeout)? {
+                Some(line) => Reply::Typed(line),
+                None => Reply::TimedOut,
+            })
+        }
+    };
+    let started = Instant::now();
+    loop {
+        if let Some(allowed) = decision.decided() {
+            return Ok(Reply::Decided(allowed));
+        }
+        let period = match timeout {
+            Some(timeout) => match timeout.checked_sub(started.elapsed()) {
+                Some(left) if !left.is_zero() => left.min(DECISION_CHECK_PERIOD),
+                _ => return Ok(Reply::TimedOut),
+            },
+            None => DECISION_CHECK_PERIOD,
+        };
+        if let Some(line) = console.read_line_timeout(Some(period))? {
+            return Ok(Reply::Typed(line));
+        }
+        if is_interrupted() {
+            return Ok(Reply::TimedOut);
+        }
+    }
+}
+
+// The container the app gets if it requests its own one, as `app_container` alone doesn't
+// tell the user that it's a new container named after the app with all the permissions on it
+pub fn own_container_line(console: &Console, auth_req: &AuthReq) -> String {
+    if auth_req.app_container {
+        tr!(
+            console,
+            "own-container",
+            container = app_container_name(&auth_req.app.id)
+        )
+    } else {
+        tr!(console, "no-own-container")
+    }
+}
+
+// Shows the user's label of the app, marks the known apps, and flags loudly the unknown ones
+// mimicking a known app
+fn print_recognition(console: &Console, app_id: &str, name: &str) {
+    if let Some(label) = labels().get(app_id) {
+        outln!(
+            console,
+            "{}",
+            tr!(console, "app-label", app_id = app_id, label = label)
+        );
+    }
+    match recognise(app_id, name) {
+        Recognition::Known(known) => outln!(
+            console,
+            "{}",
+            tr!(console, "app-known", app_id = app_id, vendor = known.vendor)
+        ),
+        Recognition::Mimicking(known) => {
+            let mut table = Table::new();
+            table.add_row(row![bFr->tr!(
+                console,
+                "app-mimicking",
+                app_id = app_id,
+                known_id = known.id,
+                known_name = known.name
+            )]);
+            console.print_table(&table);
+        }
+        Recognition::Unknown => {}
+    }
+}
+
+// The risky patterns found in the request, in red right before the prompt so they aren't missed
+fn print_warnings(console: &Console, warnings: &[RequestWarning]) {
+    if warnings.is_empty() {
+        return;
+    }
+    let mut table = Table::new();
+    for warning in warnings {
+        let text = match warning {
+            RequestWarning::VendorMismatch {
+                app_id,
+                vendor,
+                authorised_vendor,
+            } => tr!(
+                console,
+                "warning-vendor-mismatch",
+                app_id = app_id,
+                vendor = vendor,
+                authorised_vendor = authorised_vendor
+            ),
+            RequestWarning::FullAccess { app_id } => {
+                tr!(console, "warning-full-access", app_id = app_id)
+            }
+            RequestWarning::RepeatedAfterDenial { app_id, denials } => tr!(
+                console,
+                "warning-repeated-after-denial",
+                app_id = app_id,
+                denials = denials
+            ),
+            RequestWarning::PossibleReplay {
+                app_id,
+                seconds_ago,
+            } => tr!(
+                console,
+                "warning-possible-replay",
+                app_id = app_id,
+                minutes_ago = seconds_ago / 60
+            ),
+        };
+        table.add_row(row![bFr->text]);
+    }
+    console.print_table(&table);
+}
+
+fn is_allowed(console: &Console, prompt: &str) -> bool {
+    // The English answer is always accepted, in case the translated one can't be typed
+    let answer = typed_answer(prompt);
+    answer == "y" || answer == tr!(console, "allow-auth-yes").to_lowercase()
+}
+
+fn is_temporary(console: &Console, prompt: &str) -> bool {
+    let answer = typed_answer(prompt);
+    answer == "t" || answer == tr!(console, "allow-auth-temporary").to_lowercase()
+}
+
+// The answer typed, without the line ending
+fn typed_answer(prompt: &str) -> String {
+    let mut prompt = prompt.to_string();
+    if let Some('\n') = prompt.chars().next_back() {
+        prompt.pop();
+    }
+    if let Some('\r') = prompt.chars().next_back() {
+        prompt.pop();
+    }
+    prompt.to_lowercase()
+}
+
+// The row of an auth request, the permissions requested in red if any is dangerous
+// The cells' styles are left out of the rows without the `pretty` feature
+#[cfg_attr(not(feature = "pretty"), allow(clippy::if_same_then_else))]
+fn req_row(app: &AppExchangeInfo, permissions: String, dangerous: bool) -> Row {
+    if dangerous {
+        row![app.id, app.name, app.vendor, Fr->permissions]
+    } else {
+        row![app.id, app.name, app.vendor, permissions]
+    }
+}
+
+// The header of the table of an auth request, the last column being the permissions requested
+fn req_header(console: &Console, last_column: &str) -> Row {
+    row![
+        bFg->tr!(console, "header-id"),
+        bFg->tr!(console, "header-name"),
+        bFg->tr!(console, "header-vendor"),
+        bFg->tr!(console, last_column)
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        install_interrupt_handler, prompt_for_auth, prompt_to_allow_auth, read_app_ids,
+        take_interrupted, AuthAnswer, ProgressFormat, ProgressTracker, PromptDecision, PromptMode,
+        PromptPolicies, RateLimits,
+    };
+    use crate::cli::color::ColorChoice;
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::{CancellationToken, Error, OperationProgress, PendingDecisions, ProgressReporter};
+    use safe_core::ipc::req::{AppExchangeInfo, AuthReq, IpcReq, ShareMData, ShareMDataReq};
+    use safe_nd::{AppPermissions, MDataAction, MDataPermissionSet, XorName};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn prompt_policies_tests() {
+        let policies: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "auth": { "timeout": 120 }, "share_mdata": { "timeout": 10, "default": "deny" },
+                "unregistered": { "default": "allow" } }"#
+        ));
+        let unregistered = IpcReq::Unregistered(vec![]);
+        let policy = unwrap!(policies.for_req(&unregistered));
+        assert_eq!(policy.timeout, None);
+        assert_eq!(policy.default, Some(PromptDecision::Allow));
+        assert!(policies.containers.is_none());
+
+        // The unregistered requests are only prompted for if they have a policy
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        assert!(prompt_to_allow_auth(
+            &console,
+            IpcReq::Unregistered(vec![]),
+            &PromptPolicies::default(),
+            &[]
+        ));
+        assert!(output.stdout().is_empty());
+
+        let io = CapturedIo::new("n\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        assert!(!prompt_to_allow_auth(
+            &console,
+            unregistered,
+            &policies,
+            &[]
+        ));
+        assert!(output.stdout().contains("unregistered application"));
+        assert!(output.stdout().contains("Authorisation will be denied..."));
+    }
+
+    fn auth_req(app_container: bool) -> IpcReq {
+        IpcReq::Auth(AuthReq {
+            app: AppExchangeInfo {
+                id: "net.maidsafe.test".to_string(),
+                scope: None,
+                name: "Test".to_string(),
+                vendor: "MaidSafe".to_string(),
+            },
+            app_container,
+            app_permissions: AppPermissions {
+                transfer_coins: false,
+                perform_mutations: false,
+                get_balance: false,
+            },
+            containers: HashMap::new(),
+        })
+    }
+
+    #[test]
+    fn temporary_prompt_tests() {
+        let policies = PromptPolicies::default();
+        let io = CapturedIo::new("t\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        assert_eq!(
+            prompt_for_auth(&console, auth_req(false), &policies, &[], Some(24), None),
+            AuthAnswer::AllowedTemporarily
+        );
+        assert!(output.stdout().contains("[y/t/N] (t: only for 24 hours):"));
+        assert!(output.stdout().contains("allowed for 24 hours"));
+
+        let console = Console::new(Box::new(CapturedIo::new("y\n")));
+        assert_eq!(
+            prompt_for_auth(&console, auth_req(false), &policies, &[], Some(24), None),
+            AuthAnswer::Allowed
+        );
+
+        // Only offered for the requests to authorise an app, if the grants are available
+        let console = Console::new(Box::new(CapturedIo::new("t\n")));
+        assert_eq!(
+            prompt_for_auth(&console, auth_req(false), &policies, &[], None, None),
+            AuthAnswer::Denied
+        );
+        let policies: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "unregistered": { "mode": "prompt" } }"#
+        ));
+        let console = Console::new(Box::new(CapturedIo::new("t\n")));
+        assert_eq!(
+            prompt_for_auth(
+                &console,
+                IpcReq::Unregistered(vec![]),
+                &policies,
+                &[],
+                Some(24),
+                None
+            ),
+            AuthAnswer::Denied
+        );
+    }
+
+    #[test]
+    fn decided_prompt_tests() {
+        // The decision taken meanwhile is the answer, rather than the line typed
+        let decisions = PendingDecisions::default();
+        let mut decision = decisions.add();
+        assert!(decisions.decide(decision.id(), true));
+        let io = CapturedIo::new("n\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        let policies = PromptPolicies::default();
+        assert_eq!(
+            prompt_for_auth(
+                &console,
+                auth_req(false),
+                &policies,
+                &[],
+                None,
+                Some(&mut decision)
+            ),
+            AuthAnswer::Allowed
+        );
+        assert!(output.stdout().contains("decided from its notification"));
+
+        let mut decision = decisions.add();
+        let console = Console::new(Box::new(CapturedIo::new("y\n")));
+        assert_eq!(
+            prompt_for_auth(
+                &console,
+                auth_req(false),
+                &policies,
+                &[],
+                None,
+                Some(&mut decision)
+            ),
+            AuthAnswer::Allowed
+        );
+        assert_eq!(decision.decided(), None);
+    }
+
+    #[test]
+    fn own_container_prompt_tests() {
+        let io = CapturedIo::new("n\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        assert!(!prompt_to_allow_auth(
+            &console,
+            auth_req(true),
+            &PromptPolicies::default(),
+            &[]
+        ));
+        assert!(output
+            .stdout()
+            .contains("Own container: apps/net.maidsafe.test (created for the app"));
+
+        let io = CapturedIo::new("n\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        assert!(!prompt_to_allow_auth(
+            &console,
+            auth_req(false),
+            &PromptPolicies::default(),
+            &[]
+        ));
+        assert!(output.stdout().contains("Own container: none"));
+    }
+
+    #[test]
+    fn denylist_tests() {
+        let policies: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "auth": { "mode": "allow" }, "denylist": ["net.maidsafe.test"] }"#
+        ));
+        assert!(policies.denylisted(&auth_req(false)));
+        assert!(policies.denies(&auth_req(false)));
+        assert!(!policies.denies(&IpcReq::Unregistered(vec![])));
+        assert!(policies.restricts_any());
+
+        // Denied without prompting, even though its kind is allowed
+        let io = CapturedIo::new("y\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        assert!(!prompt_to_allow_auth(
+            &console,
+            auth_req(false),
+            &policies,
+            &[]
+        ));
+        assert!(output.stdout().contains("on the denylist"));
+        assert!(!output.stdout().contains("Allow authorisation?"));
+    }
+
+    #[test]
+    fn interrupt_handler_tests() {
+        // Each run installs it, e.g. the commands of several `run_with_args`
+        let console = Console::new(Box::new(CapturedIo::new("")));
+        for _ in 0..2 {
+            let cancel = CancellationToken::new();
+            unwrap!(install_interrupt_handler(
+                ProgressTracker::default(),
+                cancel.clone(),
+                console.localiser()
+            ));
+            assert!(!cancel.is_cancelled());
+        }
+        assert!(!take_interrupted());
+    }
+
+    #[test]
+    fn prompt_timeout_tests() {
+        let mut policies: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "auth": { "timeout": 120, "default": "allow" }, "share_mdata": { "mode": "per_item" } }"#
+        ));
+        policies.set_timeout(30);
+
+        // The timeout is set for all the kinds prompted for, keeping their policies
+        let auth = unwrap!(policies.auth.as_ref());
+        assert_eq!(auth.timeout, Some(30));
+        assert_eq!(auth.default, Some(PromptDecision::Allow));
+        let share_mdata = unwrap!(policies.share_mdata.as_ref());
+        assert_eq!(share_mdata.timeout, Some(30));
+        assert_eq!(share_mdata.mode, Some(PromptMode::PerItem));
+        assert_eq!(unwrap!(policies.containers.as_ref()).timeout, Some(30));
+        assert_eq!(unwrap!(policies.containers.as_ref()).default, None);
+        // The unregistered requests are still allowed without prompting
+        assert!(policies.unregistered.is_none());
+        assert_eq!(
+            policies.mode_for(&IpcReq::Unregistered(vec![])),
+            PromptMode::Allow
+        );
+        assert!(!policies.restricts_any());
+    }
+
+    #[test]
+    fn unregistered_policies_tests() {
+        let unregistered = IpcReq::Unregistered(vec![]);
+        let mode = |json: &str| {
+            let policies: PromptPolicies = unwrap!(serde_json::from_str(json));
+            policies.mode_for(&unregistered)
+        };
+        assert_eq!(mode("{}"), PromptMode::Allow);
+        assert_eq!(mode(r#"{ "unregistered": {} }"#), PromptMode::Prompt);
+        assert_eq!(
+            mode(r#"{ "unregistered": { "mode": "allow" } }"#),
+            PromptMode::Allow
+        );
+        assert_eq!(
+            mode(r#"{ "unregistered": { "mode": "deny" } }"#),
+            PromptMode::Deny
+        );
+
+        // Allowed without prompting, until the rate limit is reached
+        let policies: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "unregistered": { "mode": "allow", "max_per_minute": 2 } }"#
+        ));
+        assert!(policies.restricts_any());
+        assert!(!PromptPolicies::default().restricts_any());
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        assert!(prompt_to_allow_auth(
+            &console,
+            IpcReq::Unregistered(vec![]),
+            &policies,
+            &[]
+        ));
+        assert!(output.stdout().is_empty());
+
+        let rate_limits = RateLimits::default();
+        assert!(rate_limits.admit(&unregistered, &policies));
+        assert!(rate_limits.admit(&unregistered, &policies));
+        assert!(!rate_limits.admit(&unregistered, &policies));
+        // Nor are the requests without a limit
+        let default = PromptPolicies::default();
+        for _ in 0..3 {
+            assert!(rate_limits.admit(&unregistered, &default));
+        }
+    }
+
+    #[test]
+    fn share_mdata_prompt_modes_tests() {
+        let mdata = |type_tag| ShareMData {
+            type_tag,
+            name: XorName([0; 32]),
+            perms: MDataPermissionSet::new().allow(MDataAction::Insert),
+        };
+        let req = || {
+            IpcReq::ShareMData(ShareMDataReq {
+                app: AppExchangeInfo {
+                    id: "org.example.chat".to_string(),
+                    scope: None,
+                    name: "Chat".to_string(),
+                    vendor: "Example Ltd".to_string(),
+                },
+                mdata: vec![mdata(15_000), mdata(15_001)],
+            })
+        };
+        let prompt = |policies: &PromptPolicies, answers: &str| {
+            let io = CapturedIo::new(answers);
+            let output = io.output();
+            let console = Console::new(Box::new(io));
+            let allowed = prompt_to_allow_auth(&console, req(), policies, &[]);
+            (allowed, output.stdout())
+        };
+
+        // Each MutableData is confirmed, the first one denied denies the request
+        let per_item: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "share_mdata": { "mode": "per_item" } }"#
+        ));
+        let (allowed, stdout) = prompt(&per_item, "y\ny\n");
+        assert!(allowed);
+        assert!(stdout.contains("(type tag 15000)? [y/N]:"));
+        assert!(stdout.contains("(type tag 15001)? [y/N]:"));
+        let (allowed, stdout) = prompt(&per_item, "n\ny\n");
+        assert!(!allowed);
+        assert!(!stdout.contains("(type tag 15001)? [y/N]:"));
+
+        // Denied without reading any answer
+        let deny: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "share_mdata": { "mode": "deny" } }"#
+        ));
+        assert!(deny.denies(&req()) && deny.restricts_any());
+        assert!(!deny.denies(&IpcReq::Unregistered(vec![])));
+        let (allowed, stdout) = prompt(&deny, "y\n");
+        assert!(!allowed);
+        assert!(stdout.contains("denied by the config file"));
+        assert!(!stdout.contains("[y/N]"));
+    }
+
+    #[test]
+    fn colored_decision_tests() {
+        let policies: PromptPolicies = unwrap!(serde_json::from_str(r#"{ "unregistered": {} }"#));
+        let decide = |color: ColorChoice, answer: &str| {
+            let io = CapturedIo::new(answer);
+            let output = io.output();
+            let console = Console::new(Box::new(io));
+            console.set_color(color);
+            let _ = prompt_to_allow_auth(&console, IpcReq::Unregistered(vec![]), &policies, &[]);
+            output.stdout()
+        };
+        assert!(decide(ColorChoice::Always, "y\n").contains("\x1b[32m"));
+        assert!(decide(ColorChoice::Always, "n\n").contains("\x1b[31m"));
+        // The captured streams are not a terminal
+        assert!(!decide(ColorChoice::Auto, "y\n").contains('\x1b'));
+        assert!(!decide(ColorChoice::Never, "y\n").contains('\x1b'));
+    }
+
+    #[test]
+    fn read_app_ids_tests() {
+        let file = env::temp_dir().join("safe_auth-read-app-ids-tests.txt");
+        unwrap!(fs::write(
+            &file,
+            "# Apps not used anymore\nnet.maidsafe.cli\n\n  org.example.chat  \n"
+        ));
+        assert_eq!(
+            unwrap!(read_app_ids(&file)),
+            vec!["net.maidsafe.cli", "org.example.chat"]
+        );
+
+        unwrap!(fs::write(&file, "[\"net.maidsafe.cli\"]"));
+        assert_eq!(unwrap!(read_app_ids(&file)), vec!["net.maidsafe.cli"]);
+
+        unwrap!(fs::write(&file, "[\"net.maidsafe.cli\", 1]"));
+        match read_app_ids(&file) {
+            Err(Error::InvalidInput(_)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        let _ = fs::remove_file(&file);
+    }
+
+    #[test]
+    fn json_progress_tests() {
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        let progress = ProgressTracker::new(&console, Some(ProgressFormat::Json));
+        let step = |step: &str, completed| OperationProgress {
+            operation: String::from("Log in"),
+            step: step.to_string(),
+            completed,
+            total: 2,
+        };
+        progress.start("Log in to the SAFE Network");
+        progress.report(&step("Connecting to the network", 1));
+        progress.report(&step("Logged in", 2));
+        progress.done();
+        // Completed without the library reporting it
+        progress.start("Fetch list of authorised applications");
+        progress.done();
+
+        let events: Vec<Value> = output
+            .stderr()
+            .lines()
+            .map(|line| unwrap!(serde_json::from_str(line)))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                json!({"stage": "Log in to the SAFE Network", "percent": 0, "message": "Log in to the SAFE Network"}),
+                json!({"stage": "Log in to the SAFE Network", "percent": 50, "message": "Connecting to the network"}),
+                json!({"stage": "Log in to the SAFE Network", "percent": 100, "message": "Logged in"}),
+                json!({"stage": "Fetch list of authorised applications", "percent": 0, "message": "Fetch list of authorised applications"}),
+                json!({"stage": "Fetch list of authorised applications", "percent": 100, "message": "Fetch list of authorised applications"}),
+            ]
+        );
+
+        // Nothing is written without `--progress json`
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let progress = ProgressTracker::new(&Console::new(Box::new(io)), None);
+        progress.start("Log in to the SAFE Network");
+        progress.done();
+        assert!(output.stderr().is_empty());
+        assert_eq!(
+            "JSON".parse::<ProgressFormat>(),
+            Err(String::from(
+                "'JSON' is not a progress format, it must be 'json'"
+            ))
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The history of the commands typed in the shell, kept across its sessions in the
+// `shell_history` area of the storage, i.e. `shell_history/<profile>.json` of the user's app
+// directory by default. It's searched with Ctrl+R at the prompt of a console.
+//
+// Only the config file's `shell_history` most recent commands are kept, 500 by default, and none
+// with 0. A command containing a secret known to the process, e.g. the account's password typed
+// at the prompt by mistake, or anything shaped like a secret key, is never written to it.
+
+use super::diagnostics::contains_secret;
+use super::instances::profile_entry;
+use crate::storage::{storage, Storage};
+use crate::Error;
+use std::sync::Arc;
+
+const HISTORY_AREA: &str = "shell_history";
+const DEFAULT_HISTORY_LEN: usize = 500;
+
+pub struct History {
+    storage: Arc<dyn Storage>,
+    entry: String,
+    len: usize,
+    commands: Vec<String>,
+}
+
+impl History {
+    pub fn for_profile(profile: &str, len: Option<usize>) -> Result<Self, Error> {
+        Self::new(
+            storage()?,
+            profile_entry(profile, "json"),
+            len.unwrap_or(DEFAULT_HISTORY_LEN),
+        )
+    }
+
+    fn new(storage: Arc<dyn Storage>, entry: String, len: usize) -> Result<Self, Error> {
+        let mut commands: Vec<String> = match storage.read(HISTORY_AREA, &entry)? {
+            Some(content) => serde_json::from_slice(&content).map_err(|err| {
+                Error::IoError(format!("Failed to read the shell's history: {}", err))
+            })?,
+            None => vec![],
+        };
+        // The ones beyond a shorter length set since they were written
+        let excess = commands.len().saturating_sub(len);
+        let _ = commands.drain(..excess);
+        Ok(Self {
+            storage,
+            entry,
+            len,
+            commands,
+        })
+    }
+
+    // The commands, the most recent last
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    // Keeps the command typed, unless it's empty, the same as the previous one or contains
+    // a secret
+    pub fn add(&mut self, line: &str) -> Result<(), Error> {
+        let command = line.trim();
+        if self.len == 0
+            || command.is_empty()
+            || self.commands.last().map(String::as_str) == Some(command)
+            || contains_secret(command)
+        {
+            return Ok(());
+        }
+        self.commands.push(command.to_string());
+        if self.commands.len() > self.len {
+            let _ = self.commands.remove(0);
+        }
+        self.write()
+    }
+
+    pub fn clear(&mut self) -> Result<(), Error> {
+        self.commands.clear();
+        self.storage.remove(HISTORY_AREA, &self.entry)
+    }
+
+    fn write(&self) -> Result<(), Error> {
+        let content = serde_json::to_vec(&self.commands).map_err(|err| {
+            Error::IoError(format!("Failed to write the shell's history: {}", err))
+        })?;
+        self.storage
+            .write_private(HISTORY_AREA, &self.entry, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+    use crate::cli::diagnostics::register_secret;
+    use crate::storage::MemoryStorage;
+    use std::sync::Arc;
+
+    #[test]
+    fn history_tests() {
+        let storage = Arc::new(MemoryStorage::new());
+        let entry = String::from("work.json");
+        let mut history = unwrap!(History::new(storage.clone(), entry.clone(), 3));
+
+        unwrap!(history.add("apps\n"));
+        unwrap!(history.add("apps"));
+        unwrap!(history.add(" \n"));
+        unwrap!(history.add("balance"));
+        assert_eq!(history.commands(), ["apps", "balance"]);
+
+        // Never the secrets, nor the secret keys
+        register_secret("history-tests-password");
+        unwrap!(history.add("history-tests-password"));
+        unwrap!(history.add(&format!("revoke {}", "a1".repeat(32))));
+        assert_eq!(history.commands().len(), 2);
+
+        // Only the most recent ones are kept, and read again by the next sessions
+        unwrap!(history.add("revoke net.maidsafe.cli"));
+        unwrap!(history.add("help"));
+        let history = unwrap!(History::new(storage.clone(), entry.clone(), 3));
+        assert_eq!(
+            history.commands(),
+            ["balance", "revoke net.maidsafe.cli", "help"]
+        );
+        let mut history = unwrap!(History::new(storage.clone(), entry.clone(), 1));
+        assert_eq!(history.commands(), ["help"]);
+
+        unwrap!(history.clear());
+        assert!(history.commands().is_empty());
+        assert!(unwrap!(History::new(storage.clone(), entry.clone(), 3))
+            .commands()
+            .is_empty());
+
+        // None is kept with a length of 0
+        let mut history = unwrap!(History::new(storage, entry, 0));
+        unwrap!(history.add("apps"));
+        assert!(history.commands().is_empty());
+    }
+}
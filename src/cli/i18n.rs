@@ -0,0 +1,216 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The translations of the prompts, tables and messages printed for humans, as Fluent
+// resources found in `locales/<locale>/safe_auth.ftl`. The language is the one given with
+// `--lang`, or the one of the environment (`LC_ALL`, `LC_MESSAGES` or `LANG`), and the messages
+// missing from its translation are printed in English. The outputs meant to be parsed, i.e.
+// the `plain` and `json` formats, are never translated.
+
+use fluent_bundle::{FluentBundle, FluentResource, FluentValue};
+use lazy_static::lazy_static;
+use log::debug;
+use std::collections::HashMap;
+use std::env;
+
+// The language the messages are written in, used for the ones missing from a translation
+const DEFAULT_LOCALE: &str = "en-US";
+
+// The translations built into the binary, the default one first
+const LOCALES: [(&str, &str); 1] = [(
+    DEFAULT_LOCALE,
+    include_str!("../../locales/en-US/safe_auth.ftl"),
+)];
+
+// Environment variables the user's locale is read from, in order of precedence
+const LOCALE_ENV_VARS: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+lazy_static! {
+    static ref RESOURCES: Vec<(&'static str, FluentResource)> = LOCALES
+        .iter()
+        .map(|(locale, source)| {
+            let resource = match FluentResource::try_new(source.to_string()) {
+                Ok(resource) => resource,
+                // The messages which could be parsed are still used
+                Err((resource, errors)) => {
+                    debug!("Errors parsing the '{}' messages: {:?}", locale, errors);
+                    resource
+                }
+            };
+            (*locale, resource)
+        })
+        .collect();
+}
+
+pub fn available_locales() -> Vec<&'static str> {
+    LOCALES.iter().map(|(locale, _)| *locale).collect()
+}
+
+// The locales the messages are looked up in, the one chosen first and the default one last
+#[derive(Clone, Debug)]
+pub struct Localiser {
+    locales: Vec<&'static str>,
+}
+
+impl Default for Localiser {
+    fn default() -> Self {
+        Self::with_locale(DEFAULT_LOCALE)
+    }
+}
+
+impl Localiser {
+    // The locale of the environment, or the default one if it's not available
+    pub fn detect() -> Self {
+        let requested = LOCALE_ENV_VARS
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty());
+        match requested.as_ref().and_then(|value| negotiate(value)) {
+            Some(locale) => Self::with_locale(locale),
+            None => Self::default(),
+        }
+    }
+
+    // The locale asked for with `--lang`, none if it's not available
+    pub fn for_lang(lang: &str) -> Option<Self> {
+        negotiate(lang).map(Self::with_locale)
+    }
+
+    fn with_locale(locale: &'static str) -> Self {
+        let mut locales = vec![locale];
+        if locale != DEFAULT_LOCALE {
+            locales.push(DEFAULT_LOCALE);
+        }
+        Self { locales }
+    }
+
+    pub fn locale(&self) -> &str {
+        self.locales[0]
+    }
+
+    // The message with the id and arguments given, from the first locale which has it
+    pub fn text(&self, id: &str, args: &[(&str, String)]) -> String {
+        let fluent_args: HashMap<&str, FluentValue> = args
+            .iter()
+            .map(|(name, value)| (*name, FluentValue::from(value.as_str())))
+            .collect();
+        for locale in self.locales.iter() {
+            let resource = match RESOURCES.iter().find(|(name, _)| name == locale) {
+                Some((_, resource)) => resource,
+                None => continue,
+            };
+            let mut bundle = FluentBundle::new(&[*locale]);
+            let _ = bundle.add_resource(resource);
+            if let Some((text, errors)) = bundle.format(id, Some(&fluent_args)) {
+                if !errors.is_empty() {
+                    debug!("Errors formatting the '{}' message: {:?}", id, errors);
+                }
+                // The arguments are not isolated, the output is not meant for bidi text layout
+                return text.replace(&['\u{2068}', '\u{2069}'][..], "");
+            }
+        }
+        debug!("No message found with id '{}'", id);
+        id.to_string()
+    }
+}
+
+// The available locale matching the one requested, e.g. "es_ES.UTF-8" matches "es-ES",
+// and otherwise one of the same language, e.g. "es" or "es-AR" match "es-ES"
+fn negotiate(requested: &str) -> Option<&'static str> {
+    let requested = requested
+        .split(['.', '@'])
+        .next()
+        .unwrap_or("")
+        .replace('_', "-");
+    if requested.is_empty() || requested == "C" || requested == "POSIX" {
+        return None;
+    }
+    let language = requested.split('-').next().unwrap_or("");
+    let available = available_locales();
+    available
+        .iter()
+        .find(|locale| locale.eq_ignore_ascii_case(&requested))
+        .or_else(|| {
+            available.iter().find(|locale| {
+                locale
+                    .split('-')
+                    .next()
+                    .is_some_and(|lang| lang.eq_ignore_ascii_case(language))
+            })
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{available_locales, negotiate, Localiser, RESOURCES};
+
+    #[test]
+    fn negotiate_tests() {
+        assert_eq!(negotiate("en-US"), Some("en-US"));
+        assert_eq!(negotiate("en_US.UTF-8"), Some("en-US"));
+        assert_eq!(negotiate("en_GB@euro"), Some("en-US"));
+        assert_eq!(negotiate("EN"), Some("en-US"));
+        assert_eq!(negotiate("C"), None);
+        assert_eq!(negotiate(""), None);
+        assert_eq!(negotiate("xx-YY"), None);
+
+        assert!(Localiser::for_lang("xx").is_none());
+        assert_eq!(unwrap!(Localiser::for_lang("en_GB")).locale(), "en-US");
+    }
+
+    #[test]
+    fn localiser_text_tests() {
+        let localiser = Localiser::default();
+        assert_eq!(localiser.locale(), "en-US");
+        assert_eq!(
+            localiser.text("auth-allowed", &[]),
+            "Authorisation will be allowed..."
+        );
+        assert_eq!(
+            localiser.text("app-revoked", &[("app_id", "net.maidsafe.cli".to_string())]),
+            "Authorised permissions were revoked for app 'net.maidsafe.cli'"
+        );
+        // Unknown messages are printed as their id
+        assert_eq!(localiser.text("not-a-message", &[]), "not-a-message");
+    }
+
+    // All the translations have the security sensitive messages, and no others
+    #[test]
+    fn translations_tests() {
+        let ids_of = |locale: &str| -> Vec<String> {
+            let source = unwrap!(std::fs::read_to_string(format!(
+                "locales/{}/safe_auth.ftl",
+                locale
+            )));
+            source
+                .lines()
+                .filter(|line| !line.starts_with('#') && !line.starts_with(' '))
+                .filter_map(|line| line.split(" =").next())
+                .filter(|id| !id.is_empty())
+                .map(String::from)
+                .collect()
+        };
+        assert_eq!(RESOURCES.len(), available_locales().len());
+        let default_ids = ids_of("en-US");
+        for locale in available_locales() {
+            let ids = ids_of(locale);
+            for id in ids.iter() {
+                assert!(default_ids.contains(id), "Unknown message '{}'", id);
+            }
+            for id in &["allow-auth-prompt", "allow-auth-yes", "auth-req-received"] {
+                assert!(
+                    ids.contains(&id.to_string()),
+                    "Missing '{}' in {}",
+                    id,
+                    locale
+                );
+            }
+        }
+    }
+}
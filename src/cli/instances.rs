@@ -0,0 +1,430 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The registry of the Authenticator services running, so a single one runs for each profile,
+// and the other invocations of the CLI operate on the account through it rather than opening
+// a second session with the network. The profile is the one given with `--profile`, e.g. one
+// for each account, otherwise the network's name, so there is one service for each network.
+// A service registers itself in the `instances` area of the storage, in an entry named after
+// the profile, e.g. `instances/SAFE_Network.json` with `{"pid", "port", "token", "started_at"}`,
+// which is only readable by the user as the token lets its holder operate on the account.
+//
+// The entry is removed once the service stops. If it's killed instead, the entry is left behind:
+// it's ignored, and replaced by the next service, once its process is not running anymore.
+
+use super::status::{daemon_address, network_label};
+use crate::service_auth::{
+    client_proof, constant_time_eq, decrypt_payload, new_challenge, service_proof,
+    CHALLENGE_HEADER, HMAC_SCHEME, NONCE_HEADER, PROOF_HEADER,
+};
+use crate::storage::{storage, Storage};
+use crate::{
+    AccountInfo, AccountKeys, Approval, AuthAllowPrompt, AuthBackend, AuthedAppsDiff,
+    AuthedAppsList, Error,
+};
+use log::{debug, warn};
+use rand::Rng;
+use reqwest::header::AUTHORIZATION;
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, TcpListener};
+#[cfg(target_os = "linux")]
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The area of the storage of the services' registrations
+const INSTANCES_AREA: &str = "instances";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub pid: u32,
+    pub port: u16,
+    // Sent by the clients to operate on the account through the service
+    pub token: String,
+    // Seconds since the UNIX epoch
+    pub started_at: u64,
+}
+
+// The registration of the service run by this process, removed when it's dropped
+pub struct InstanceLock {
+    storage: Arc<dyn Storage>,
+    entry: String,
+    pub instance: Instance,
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        if let Err(err) = self.storage.remove(INSTANCES_AREA, &self.entry) {
+            debug!(
+                "The registration of the service couldn't be removed: {}",
+                err
+            );
+        }
+    }
+}
+
+// The profile given with `--profile`, otherwise the one of the network
+pub fn profile_name(profile: &Option<String>, network: &Option<String>) -> String {
+    profile.clone().unwrap_or_else(|| network_label(network))
+}
+
+// A port no other process is listening on, for the services of the profiles not given one
+pub fn free_port() -> Result<u16, Error> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|address| address.port())
+        .map_err(|err| Error::IoError(format!("Failed to find a free port: {}", err)))
+}
+
+// The name of the profile's entry within an area of the storage, e.g. `SAFE_Network.json`
+pub fn profile_entry(profile: &str, extension: &str) -> String {
+    let name: String = profile
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}.{}", name, extension)
+}
+
+fn read_instance(storage: &dyn Storage, entry: &str) -> Option<Instance> {
+    storage
+        .read(INSTANCES_AREA, entry)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content?).ok())
+}
+
+// If the process which registered the service is still running. Where it's not known,
+// it's considered running while the service is listening on its port.
+#[cfg(target_os = "linux")]
+fn is_running(instance: &Instance) -> bool {
+    Path::new("/proc").join(instance.pid.to_string()).exists()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_running(instance: &Instance) -> bool {
+    daemon_address(instance.port).is_some()
+}
+
+// The service running for the profile, once it's listening for requests
+pub fn find_instance(profile: &str) -> Option<Instance> {
+    let instance = read_instance(&*storage().ok()?, &profile_entry(profile, "json"))?;
+    if is_running(&instance) && daemon_address(instance.port).is_some() {
+        Some(instance)
+    } else {
+        None
+    }
+}
+
+// Registers the service about to run for the profile, unless another one is already
+// running for it. The registration is created before connecting to the network, so two
+// services started at the same time can't both get it.
+pub fn register_instance(profile: &str, port: u16) -> Result<InstanceLock, Error> {
+    let storage = storage()?;
+    let entry = profile_entry(profile, "json");
+
+    let token: [u8; 32] = rand::thread_rng().gen();
+    let instance = Instance {
+        pid: process::id(),
+        port,
+        token: token.iter().map(|b| format!("{:02x}", b)).collect(),
+        started_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0),
+    };
+    let content = serde_json::to_vec(&instance).map_err(|err| {
+        Error::AuthenticatorError(format!("Failed to serialise the registration: {}", err))
+    })?;
+
+    // The registration is only created if there is none, so the other invocations never
+    // replace the one of a service still running
+    while !storage.create(INSTANCES_AREA, &entry, &content)? {
+        match read_instance(&*storage, &entry) {
+            Some(running) if is_running(&running) => {
+                return Err(Error::InvalidInput(format!(
+                    "An Authenticator service is already running for the profile '{}' on port {} (pid {})",
+                    profile, running.port, running.pid
+                )));
+            }
+            _ => {
+                warn!(
+                    "Replacing the registration of a service which is not running anymore: {}/{}",
+                    INSTANCES_AREA, entry
+                );
+                storage.remove(INSTANCES_AREA, &entry)?;
+            }
+        }
+    }
+    Ok(InstanceLock {
+        storage,
+        entry,
+        instance,
+    })
+}
+
+// Performs the operations on the account through the Authenticator service. The auth
+// requests are decided on by the service, with its own prompt, so they can't be allowed
+// with `--allow-all-auth` by the client.
+pub struct ServiceClient {
+    client: Client,
+    url: String,
+    token: String,
+    allow_all: bool,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: Error,
+}
+
+#[derive(Deserialize)]
+struct ApprovalDecision {
+    approved: bool,
+}
+
+impl ServiceClient {
+    pub fn new(instance: &Instance) -> Result<Self, Error> {
+        Self::with_url(
+            &format!("http://127.0.0.1:{}", instance.port),
+            &instance.token,
+        )
+    }
+
+    // The service at the URL, e.g. the one of a guardian on another device
+    pub fn with_url(url: &str, token: &str) -> Result<Self, Error> {
+        // The service may wait for the user to answer its prompt for as long as it takes
+        let client = Client::builder().timeout(None).build().map_err(|err| {
+            Error::NetworkError(format!("Failed to set up the HTTP client: {}", err))
+        })?;
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            allow_all: false,
+        })
+    }
+
+    // The client of an invocation given `--allow-all-auth`, whose auth requests are refused
+    // rather than prompted for by the service
+    pub fn allowing_all(mut self, allow_all: bool) -> Self {
+        self.allow_all = allow_all;
+        self
+    }
+
+    // Asks the guardian's service to co-approve the operation, waiting for its decision
+    pub fn approve(&self, approval: &Approval) -> Result<bool, Error> {
+        let decision: ApprovalDecision = self.request(
+            Method::POST,
+            &format!("/guardian/approve/{}", approval.encode()?),
+        )?;
+        Ok(decision.approved)
+    }
+
+    // Decides on an auth request the service's prompt waits on
+    pub fn decide(&self, id: u64, allowed: bool) -> Result<(), Error> {
+        let decision = if allowed { "allow" } else { "deny" };
+        let _: serde_json::Value =
+            self.request(Method::POST, &format!("/decide/{}/{}", id, decision))?;
+        Ok(())
+    }
+
+    // Gets a nonce from the service, to prove with it that the client has the token
+    fn nonce(&self) -> Result<String, Error> {
+        self.client
+            .get(&format!("{}/nonce", self.url))
+            .send()
+            .and_then(|response| response.error_for_status()?.text())
+            .map_err(|err| {
+                Error::NetworkError(format!(
+                    "Failed to reach the Authenticator service: {}",
+                    err
+                ))
+            })
+    }
+
+    // Sends the request, returning the body of the response if it succeeded. The service
+    // and the client prove to each other they have the token, without sending it, and the
+    // body of the response is encrypted with it.
+    fn send(&self, method: Method, path: &str) -> Result<String, Error> {
+        let nonce = self.nonce()?;
+        let challenge = new_challenge();
+        let proof = client_proof(&self.token, &nonce, &challenge, method.as_str(), path, b"");
+        let mut response = self
+            .client
+            .request(method, &format!("{}{}", self.url, path))
+            .header(NONCE_HEADER, nonce.as_str())
+            .header(CHALLENGE_HEADER, challenge.as_str())
+            .header(AUTHORIZATION, format!("{}{}", HMAC_SCHEME, proof))
+            .send()
+            .map_err(|err| {
+                Error::NetworkError(format!(
+                    "Failed to reach the Authenticator service: {}",
+                    err
+                ))
+            })?;
+        let service_proof_sent = response
+            .headers()
+            .get(PROOF_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut encrypted = vec![];
+        let _ = response.copy_to(&mut encrypted).map_err(|err| {
+            Error::NetworkError(format!(
+                "Failed to read the response of the Authenticator service: {}",
+                err
+            ))
+        })?;
+        let expected = service_proof(&self.token, &challenge, &encrypted);
+        if !service_proof_sent.is_some_and(|sent| constant_time_eq(&sent, &expected)) {
+            return Err(Error::NetworkError(format!(
+                "The process listening on {} couldn't prove it's the Authenticator service of this profile, another process may be impersonating it",
+                self.url
+            )));
+        }
+        let body = String::from_utf8(decrypt_payload(&self.token, &encrypted)?).map_err(|err| {
+            Error::AuthenticatorError(format!(
+                "The response of the Authenticator service is not valid: {}",
+                err
+            ))
+        })?;
+        if response.status().is_success() {
+            Ok(body)
+        } else {
+            Err(serde_json::from_str::<ErrorBody>(&body)
+                .map(|body| body.error)
+                .unwrap_or(Error::AuthenticatorError(body)))
+        }
+    }
+
+    // Performs an operation on the account, its result being sent as JSON
+    fn request<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: Method,
+        path: &str,
+    ) -> Result<T, Error> {
+        let body = self.send(method, path)?;
+        serde_json::from_str(&body).map_err(|err| {
+            Error::AuthenticatorError(format!(
+                "The response of the Authenticator service is not valid: {}",
+                err
+            ))
+        })
+    }
+
+    fn unsupported<T>(operation: &str) -> Result<T, Error> {
+        Err(Error::InvalidInput(format!(
+            "The account can't be {} through the Authenticator service, use --standalone to log in to it instead",
+            operation
+        )))
+    }
+}
+
+impl AuthBackend for ServiceClient {
+    fn authorise_app(&self, req: &str, _allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        if self.allow_all {
+            return Err(Error::InvalidInput(String::from(
+                "The auth requests sent to the Authenticator service of the profile are decided on with its prompt, so they can't be allowed with --allow-all-auth: pass --standalone to decide on them here",
+            )));
+        }
+        self.send(Method::GET, &format!("/authorise/{}", encode_path(req)))
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.request(Method::GET, "/apps")
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        self.request(Method::POST, &format!("/revoke/{}", encode_path(app_id)))
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.request(Method::GET, "/account")
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.request(Method::GET, "/account/keys")
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        Self::unsupported("backed up")
+    }
+
+    fn restore_account(&self, _backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        Self::unsupported("restored")
+    }
+}
+
+// Percent-encodes a segment of a URL's path
+fn encode_path(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_path, is_running, profile_name, Instance, ServiceClient};
+    use crate::test_utils::APP_AUTH_REQ;
+    use crate::{AuthBackend, Error};
+    use std::process;
+
+    #[test]
+    fn encode_path_tests() {
+        assert_eq!(encode_path("net.maidsafe.cli"), "net.maidsafe.cli");
+        assert_eq!(
+            encode_path("org.example/chat app"),
+            "org.example%2Fchat%20app"
+        );
+    }
+
+    #[test]
+    fn profile_name_tests() {
+        let network = Some(String::from("testnet"));
+        assert_eq!(profile_name(&None, &network), "testnet");
+        assert_eq!(profile_name(&Some(String::from("work")), &network), "work");
+    }
+
+    #[test]
+    fn is_running_tests() {
+        let instance = Instance {
+            pid: process::id(),
+            port: 0,
+            token: String::new(),
+            started_at: 0,
+        };
+        if cfg!(target_os = "linux") {
+            assert!(is_running(&instance));
+        }
+        // Nothing listens on the port 0
+        let stale = Instance {
+            pid: u32::MAX,
+            ..instance
+        };
+        assert!(!is_running(&stale));
+    }
+
+    #[test]
+    fn service_client_allowing_all_test() {
+        // Nothing listens on the port 1, the request is refused before it's sent
+        let client = unwrap!(ServiceClient::with_url("http://127.0.0.1:1", "token"));
+        match client
+            .allowing_all(true)
+            .authorise_app(APP_AUTH_REQ, &|_| true)
+        {
+            Err(Error::InvalidInput(message)) => assert!(message.contains("--standalone")),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+}
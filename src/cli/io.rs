@@ -0,0 +1,646 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::color::{paint, Color, ColorChoice};
+use super::i18n::Localiser;
+use super::table::{render, table_cells, terminal_width, Overflow, Table, TableLayout, TableStyle};
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread;
+use std::time::Duration;
+
+// Same as `println!` but writing to the CLI's stdout
+macro_rules! outln {
+    ($console:expr) => {
+        $console.out(format_args!("\n"))
+    };
+    ($console:expr, $($arg:tt)*) => {
+        $console.out(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+// Same as `eprintln!` but writing to the CLI's stderr
+macro_rules! errln {
+    ($console:expr, $($arg:tt)*) => {
+        $console.err(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+// The message with the id in the user's language, e.g. `tr!(console, "app-revoked", app_id = id)`
+macro_rules! tr {
+    ($localiser:expr, $id:expr) => {
+        $localiser.text($id, &[])
+    };
+    ($localiser:expr, $id:expr, $($name:ident = $value:expr),+ $(,)*) => {
+        $localiser.text($id, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+/// # Input and output of the CLI
+///
+/// The streams the CLI reads the user's input from and writes its output to when it's run
+/// with `run_with_args`. `StdIo` uses the process' standard streams, while tests and
+/// wrappers can provide in-memory ones to capture the output.
+pub trait CliIo: Send {
+    /// The stream the commands, the prompts' answers and the headless commands are read from
+    fn stdin(&mut self) -> &mut dyn BufRead;
+    /// The stream the output of the commands is written to
+    fn stdout(&mut self) -> &mut dyn Write;
+    /// The stream the warnings are written to
+    fn stderr(&mut self) -> &mut dyn Write;
+
+    /// Prompts for a secret, e.g. the account's password. By default it's read from `stdin`.
+    fn read_password(&mut self, prompt: &str) -> io::Result<String> {
+        write!(self.stderr(), "{}", prompt)?;
+        self.stderr().flush()?;
+        let mut secret = String::new();
+        let _ = self.stdin().read_line(&mut secret)?;
+        Ok(secret.trim_end_matches(&['\r', '\n'][..]).to_string())
+    }
+
+    /// Reads a line from `stdin`, or `None` if none is typed before the timeout. By default
+    /// the timeout is ignored, as it's meant for an interactive user typing the line.
+    fn read_line_timeout(&mut self, _timeout: Duration) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        let _ = self.stdin().read_line(&mut line)?;
+        Ok(Some(line))
+    }
+
+    /// Reads a command of the interactive shell, with its line ending, or an empty string at
+    /// the end of the input. The history given, the most recent command last, can be recalled
+    /// while editing it. By default the prompt is written to `stdout` and the line is read from
+    /// `stdin`, without any editing.
+    fn read_command(&mut self, prompt: &str, _history: &[String]) -> io::Result<String> {
+        write!(self.stdout(), "{} ", prompt)?;
+        self.stdout().flush()?;
+        let mut line = String::new();
+        let _ = self.stdin().read_line(&mut line)?;
+        Ok(line)
+    }
+
+    /// If these are the process' own console: the tables are printed with colours,
+    /// and `Ctrl+C` interrupts the execution. It's `false` by default.
+    fn is_console(&self) -> bool {
+        false
+    }
+}
+
+/// The process' standard streams, the passwords are read from the TTY
+pub struct StdIo {
+    stdin: StdinReader,
+    stdout: io::Stdout,
+    stderr: io::Stderr,
+}
+
+impl Default for StdIo {
+    fn default() -> Self {
+        Self {
+            stdin: StdinReader::default(),
+            stdout: io::stdout(),
+            stderr: io::stderr(),
+        }
+    }
+}
+
+impl CliIo for StdIo {
+    fn stdin(&mut self) -> &mut dyn BufRead {
+        &mut self.stdin
+    }
+
+    fn stdout(&mut self) -> &mut dyn Write {
+        &mut self.stdout
+    }
+
+    fn stderr(&mut self) -> &mut dyn Write {
+        &mut self.stderr
+    }
+
+    fn read_line_timeout(&mut self, timeout: Duration) -> io::Result<Option<String>> {
+        self.stdin.read_line_timeout(timeout)
+    }
+
+    fn read_password(&mut self, prompt: &str) -> io::Result<String> {
+        rpassword::read_password_from_tty(Some(prompt))
+    }
+
+    // The line is edited on the terminal, the history being browsed with the arrows and
+    // searched with Ctrl+R, unless the input left by a prompt is to be read first
+    fn read_command(&mut self, prompt: &str, history: &[String]) -> io::Result<String> {
+        if self.stdin.has_buffered() || self.stdin.pending.is_some() {
+            write!(self.stdout, "{} ", prompt)?;
+            self.stdout.flush()?;
+            let mut line = String::new();
+            let _ = self.stdin.read_line(&mut line)?;
+            return Ok(line);
+        }
+        let mut editor = Editor::<()>::new();
+        for command in history {
+            let _ = editor.add_history_entry(command.as_str());
+        }
+        match editor.readline(&format!("{} ", prompt)) {
+            Ok(line) => Ok(format!("{}\n", line)),
+            // Ctrl+D, or Ctrl+C as the terminal doesn't send it as a signal while editing
+            Err(ReadlineError::Eof) | Err(ReadlineError::Interrupted) => Ok(String::new()),
+            Err(ReadlineError::Io(err)) => Err(err),
+            Err(err) => Err(io::Error::other(err.to_string())),
+        }
+    }
+
+    fn is_console(&self) -> bool {
+        true
+    }
+}
+
+// The process' stdin, read a line at a time. A line read with a timeout is read from another
+// thread, which keeps waiting for it once the timeout expires, so the line typed too late is
+// the next one read rather than being lost.
+#[derive(Default)]
+struct StdinReader {
+    line: io::Cursor<Vec<u8>>,
+    pending: Option<Receiver<io::Result<String>>>,
+}
+
+impl StdinReader {
+    fn has_buffered(&self) -> bool {
+        (self.line.position() as usize) < self.line.get_ref().len()
+    }
+
+    fn read_line_timeout(&mut self, timeout: Duration) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.has_buffered() {
+            let _ = self.line.read_line(&mut line)?;
+            return Ok(Some(line));
+        }
+
+        let pending = self.pending.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            let _ = thread::spawn(move || {
+                let mut line = String::new();
+                let _ = sender.send(io::stdin().read_line(&mut line).map(|_| line));
+            });
+            receiver
+        });
+        match pending.recv_timeout(timeout) {
+            Ok(result) => {
+                self.pending = None;
+                result.map(Some)
+            }
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            // The thread can't stop before sending the line, or the error, read
+            Err(RecvTimeoutError::Disconnected) => {
+                self.pending = None;
+                Ok(Some(line))
+            }
+        }
+    }
+}
+
+impl Read for StdinReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = {
+            let available = self.fill_buf()?;
+            let read = available.len().min(buf.len());
+            buf[..read].copy_from_slice(&available[..read]);
+            read
+        };
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for StdinReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if !self.has_buffered() {
+            let line = match self.pending.take() {
+                Some(pending) => pending.recv().unwrap_or_else(|_| Ok(String::new()))?,
+                None => {
+                    let mut line = String::new();
+                    let _ = io::stdin().read_line(&mut line)?;
+                    line
+                }
+            };
+            self.line = io::Cursor::new(line.into_bytes());
+        }
+        self.line.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.line.consume(amount)
+    }
+}
+
+/// # Capture the output of the CLI
+///
+/// In-memory streams for `run_with_args`: the input is read from the string provided,
+/// and the output can be read, once the CLI returns, from the `CapturedOutput` handle.
+pub struct CapturedIo {
+    stdin: io::Cursor<Vec<u8>>,
+    output: CapturedOutput,
+}
+
+impl CapturedIo {
+    pub fn new(stdin: &str) -> Self {
+        Self {
+            stdin: io::Cursor::new(stdin.as_bytes().to_vec()),
+            output: CapturedOutput::default(),
+        }
+    }
+
+    /// A handle to the output written to the streams, it can be kept after they are moved
+    pub fn output(&self) -> CapturedOutput {
+        self.output.clone()
+    }
+}
+
+impl CliIo for CapturedIo {
+    fn stdin(&mut self) -> &mut dyn BufRead {
+        &mut self.stdin
+    }
+
+    fn stdout(&mut self) -> &mut dyn Write {
+        &mut self.output.stdout
+    }
+
+    fn stderr(&mut self) -> &mut dyn Write {
+        &mut self.output.stderr
+    }
+}
+
+/// The output written to a `CapturedIo`
+#[derive(Clone, Default)]
+pub struct CapturedOutput {
+    stdout: SharedBuffer,
+    stderr: SharedBuffer,
+}
+
+impl CapturedOutput {
+    pub fn stdout(&self) -> String {
+        self.stdout.contents()
+    }
+
+    pub fn stderr(&self) -> String {
+        self.stderr.contents()
+    }
+}
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&unwrap!(self.0.lock())).into_owned()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        unwrap!(self.0.lock()).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// The CLI's streams, shared by the commands and the prompts invoked from other threads,
+// e.g. the ones of the Authenticator service. Write errors are ignored, like `println!` does
+// with a closed stdout but without panicking. In strict mode the stdout is reserved for the
+// results of the commands, written with `out_result`, and the rest goes to the stderr. In
+// non-interactive mode the user is never prompted: the passwords can't be read, and the
+// prompts check `is_interactive` before asking anything. The output is coloured as chosen with
+// `set_color`, see the `color` module.
+#[derive(Clone)]
+pub struct Console {
+    io: Arc<Mutex<Box<dyn CliIo>>>,
+    localiser: Arc<Mutex<Localiser>>,
+    table_layout: Arc<Mutex<TableLayout>>,
+    color: Arc<Mutex<ColorChoice>>,
+    strict: Arc<AtomicBool>,
+    non_interactive: Arc<AtomicBool>,
+}
+
+impl Console {
+    pub fn new(io: Box<dyn CliIo>) -> Self {
+        Console {
+            io: Arc::new(Mutex::new(io)),
+            localiser: Arc::new(Mutex::new(Localiser::detect())),
+            table_layout: Arc::default(),
+            color: Arc::default(),
+            strict: Arc::default(),
+            non_interactive: Arc::default(),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Box<dyn CliIo>> {
+        match self.io.lock() {
+            Ok(io) => io,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub fn localiser(&self) -> Localiser {
+        match self.localiser.lock() {
+            Ok(localiser) => localiser.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub fn set_localiser(&self, localiser: Localiser) {
+        match self.localiser.lock() {
+            Ok(mut current) => *current = localiser,
+            Err(poisoned) => *poisoned.into_inner() = localiser,
+        }
+    }
+
+    pub fn set_table_layout(&self, layout: TableLayout) {
+        match self.table_layout.lock() {
+            Ok(mut current) => *current = layout,
+            Err(poisoned) => *poisoned.into_inner() = layout,
+        }
+    }
+
+    fn table_layout(&self) -> TableLayout {
+        match self.table_layout.lock() {
+            Ok(layout) => *layout,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
+    pub fn set_color(&self, choice: ColorChoice) {
+        match self.color.lock() {
+            Ok(mut current) => *current = choice,
+            Err(poisoned) => *poisoned.into_inner() = choice,
+        }
+    }
+
+    // Whether the output is coloured. Unless it's forced, it's only when the stream the output
+    // is written to is the terminal of the process.
+    pub fn colors(&self) -> bool {
+        let choice = match self.color.lock() {
+            Ok(choice) => *choice,
+            Err(poisoned) => *poisoned.into_inner(),
+        };
+        let stream = if self.is_strict() {
+            atty::Stream::Stderr
+        } else {
+            atty::Stream::Stdout
+        };
+        choice.colors(self.is_console() && atty::is(stream))
+    }
+
+    // The text in the colour, if the output is coloured
+    pub fn paint(&self, text: &str, color: Color) -> String {
+        if self.colors() {
+            paint(text, color)
+        } else {
+            text.to_string()
+        }
+    }
+
+    // The message with the id in the user's language, see the `tr!` macro
+    pub fn text(&self, id: &str, args: &[(&str, String)]) -> String {
+        self.localiser().text(id, args)
+    }
+
+    pub fn set_strict(&self, strict: bool) {
+        self.strict.store(strict, Ordering::SeqCst);
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict.load(Ordering::SeqCst)
+    }
+
+    pub fn set_non_interactive(&self, non_interactive: bool) {
+        self.non_interactive
+            .store(non_interactive, Ordering::SeqCst);
+    }
+
+    // Whether the user can be prompted
+    pub fn is_interactive(&self) -> bool {
+        !self.non_interactive.load(Ordering::SeqCst)
+    }
+
+    pub fn out(&self, args: fmt::Arguments) {
+        if self.is_strict() {
+            return self.err(args);
+        }
+        self.out_result(args);
+    }
+
+    // Writes to the stdout even in strict mode
+    pub fn out_result(&self, args: fmt::Arguments) {
+        let mut io = self.lock();
+        let _ = io.stdout().write_fmt(args);
+        let _ = io.stdout().flush();
+    }
+
+    pub fn err(&self, args: fmt::Arguments) {
+        let mut io = self.lock();
+        let _ = io.stderr().write_fmt(args);
+        let _ = io.stderr().flush();
+    }
+
+    pub fn print_table(&self, table: &Table) {
+        let layout = self.table_layout();
+        let strict = self.is_strict();
+        #[cfg(feature = "pretty")]
+        let colors = self.colors();
+        let mut io = self.lock();
+        if !layout.is_default() {
+            // Unless it's given, the style is the one the table would have been printed with
+            let style = match layout.style {
+                Some(style) => style,
+                None if cfg!(feature = "pretty") && io.is_console() => TableStyle::Grid,
+                None => TableStyle::Plain,
+            };
+            let width = layout.overflow.and_then(|_| terminal_width());
+            let overflow = layout.overflow.unwrap_or(Overflow::Wrap);
+            let text = render(&table_cells(table), style, width, overflow);
+            let _ = out_stream(&mut **io, strict).write_all(text.as_bytes());
+            return;
+        }
+        #[cfg(feature = "pretty")]
+        {
+            // The cells' styles are only printed on the terminal, not on the captured streams
+            if io.is_console() && !strict && colors {
+                let _ = table.print_tty(true);
+                return;
+            }
+        }
+        let _ = table.print(out_stream(&mut **io, strict));
+    }
+
+    pub fn read_line(&self) -> io::Result<String> {
+        let mut line = String::new();
+        let _ = self.lock().stdin().read_line(&mut line)?;
+        Ok(line)
+    }
+
+    // The line typed, or `None` if it's not typed before the timeout, if any
+    pub fn read_line_timeout(&self, timeout: Option<Duration>) -> io::Result<Option<String>> {
+        match timeout {
+            Some(timeout) => self.lock().read_line_timeout(timeout),
+            None => self.read_line().map(Some),
+        }
+    }
+
+    pub fn read_password(&self, prompt: &str) -> io::Result<String> {
+        if !self.is_interactive() {
+            return Err(io::Error::other(
+                "the user can't be prompted with --non-interactive",
+            ));
+        }
+        self.lock().read_password(prompt)
+    }
+
+    pub fn read_command(&self, prompt: &str, history: &[String]) -> io::Result<String> {
+        self.lock().read_command(prompt, history)
+    }
+
+    pub fn is_console(&self) -> bool {
+        self.lock().is_console()
+    }
+
+    // A handle which doesn't keep the streams alive, for the prompts which must be 'static
+    pub fn downgrade(&self) -> WeakConsole {
+        WeakConsole {
+            io: Arc::downgrade(&self.io),
+            localiser: Arc::clone(&self.localiser),
+            table_layout: Arc::clone(&self.table_layout),
+            color: Arc::clone(&self.color),
+            strict: Arc::clone(&self.strict),
+            non_interactive: Arc::clone(&self.non_interactive),
+        }
+    }
+
+    // Adapters to the `Read`/`Write` traits, e.g. for the headless mode
+    pub fn reader(&self) -> ConsoleReader {
+        ConsoleReader(self.clone())
+    }
+
+    pub fn writer(&self) -> ConsoleWriter {
+        ConsoleWriter(self.clone())
+    }
+}
+
+// The stream the output which is not a result is written to
+fn out_stream(io: &mut dyn CliIo, strict: bool) -> &mut dyn Write {
+    if strict {
+        io.stderr()
+    } else {
+        io.stdout()
+    }
+}
+
+pub struct WeakConsole {
+    io: Weak<Mutex<Box<dyn CliIo>>>,
+    localiser: Arc<Mutex<Localiser>>,
+    table_layout: Arc<Mutex<TableLayout>>,
+    color: Arc<Mutex<ColorChoice>>,
+    strict: Arc<AtomicBool>,
+    non_interactive: Arc<AtomicBool>,
+}
+
+impl WeakConsole {
+    pub fn upgrade(&self) -> Option<Console> {
+        self.io.upgrade().map(|io| Console {
+            io,
+            localiser: Arc::clone(&self.localiser),
+            table_layout: Arc::clone(&self.table_layout),
+            color: Arc::clone(&self.color),
+            strict: Arc::clone(&self.strict),
+            non_interactive: Arc::clone(&self.non_interactive),
+        })
+    }
+}
+
+pub struct ConsoleReader(Console);
+
+impl Read for ConsoleReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.lock().stdin().read(buf)
+    }
+}
+
+pub struct ConsoleWriter(Console);
+
+impl Write for ConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().stdout().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CapturedIo, CliIo, Console};
+
+    #[test]
+    fn captured_io_tests() {
+        let io = CapturedIo::new("first line\nmy-password\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+
+        outln!(console, "Output {}", 1);
+        errln!(console, "Warning {}", 2);
+        assert_eq!(unwrap!(console.read_line()), "first line\n");
+        assert_eq!(unwrap!(console.read_password("Password: ")), "my-password");
+        assert!(!console.is_console());
+
+        // The streams are released once the console is dropped
+        let weak = console.downgrade();
+        drop(console);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(output.stdout(), "Output 1\n");
+        assert_eq!(output.stderr(), "Warning 2\nPassword: ");
+    }
+
+    #[test]
+    fn strict_console_test() {
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        console.set_strict(true);
+
+        // Only the results are written to the stdout, even from the weak handles
+        outln!(console, "Prompt");
+        unwrap!(console.downgrade().upgrade()).out(format_args!("Message\n"));
+        console.out_result(format_args!("Result\n"));
+        assert_eq!(output.stdout(), "Result\n");
+        assert_eq!(output.stderr(), "Prompt\nMessage\n");
+    }
+
+    #[test]
+    fn non_interactive_console_test() {
+        let io = CapturedIo::new("secret\n");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        console.set_non_interactive(true);
+
+        // The password is not prompted for, nor read
+        assert!(!unwrap!(console.downgrade().upgrade()).is_interactive());
+        assert!(console.read_password("Secret: ").is_err());
+        assert_eq!(output.stderr(), "");
+        assert_eq!(unwrap!(console.read_line()), "secret\n");
+    }
+
+    #[test]
+    fn captured_io_end_of_input_test() {
+        let mut io = CapturedIo::new("");
+        assert_eq!(unwrap!(io.read_password("Secret: ")), "");
+    }
+}
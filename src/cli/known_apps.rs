@@ -0,0 +1,178 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The registry of the well-known apps, so the prompt and the list of the authorised apps can
+// mark the apps recognised, and flag the unknown ones whose id or name mimics a known app's,
+// e.g. `net.maidsafe.c1i` or "SAFE-CLI", as they are likely impersonating it. The registry
+// shipped, `resources/known_apps.json`, is updated with the releases, and is extended with the
+// `known_apps` of the config file.
+//
+// The ids and names are compared by their skeleton: lowercased, without the separators, and
+// with the characters which look alike replaced by a single one, e.g. `0` by `o`.
+
+use lazy_static::lazy_static;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+const SHIPPED_APPS: &str = include_str!("../../resources/known_apps.json");
+// The skeletons at least this long are also mimicking when a single character differs
+const MIN_FUZZY_LEN: usize = 8;
+
+lazy_static! {
+    static ref USER_APPS: RwLock<Vec<KnownApp>> = RwLock::new(Vec::new());
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KnownApp {
+    pub id: String,
+    pub name: String,
+    pub vendor: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Recognition {
+    Known(KnownApp),
+    // An unknown app whose id or name looks like the known app's
+    Mimicking(KnownApp),
+    Unknown,
+}
+
+// Adds the apps of the config file to the registry from now on
+pub fn set_user_apps(apps: &[KnownApp]) {
+    debug!("{} known apps added by the config file", apps.len());
+    if let Ok(mut user_apps) = USER_APPS.write() {
+        *user_apps = apps.to_vec();
+    }
+}
+
+fn known_apps() -> Vec<KnownApp> {
+    let mut apps: Vec<KnownApp> = unwrap!(serde_json::from_str(SHIPPED_APPS));
+    if let Ok(user_apps) = USER_APPS.read() {
+        apps.extend(user_apps.iter().cloned());
+    }
+    apps
+}
+
+pub fn recognise(id: &str, name: &str) -> Recognition {
+    recognise_in(&known_apps(), id, name)
+}
+
+fn recognise_in(apps: &[KnownApp], id: &str, name: &str) -> Recognition {
+    if let Some(app) = apps.iter().find(|app| app.id == id) {
+        return Recognition::Known(app.clone());
+    }
+    let (id, name) = (skeleton(id), skeleton(name));
+    apps.iter()
+        .find(|app| looks_like(&id, &skeleton(&app.id)) || looks_like(&name, &skeleton(&app.name)))
+        .map_or(Recognition::Unknown, |app| {
+            Recognition::Mimicking(app.clone())
+        })
+}
+
+fn skeleton(text: &str) -> String {
+    let text = text.to_lowercase().replace("rn", "m").replace("vv", "w");
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | 'i' | 'j' => 'l',
+            '3' => 'e',
+            '4' => 'a',
+            '5' => 's',
+            '7' => 't',
+            '8' => 'b',
+            c => c,
+        })
+        .collect()
+}
+
+fn looks_like(skeleton: &str, known: &str) -> bool {
+    !skeleton.is_empty()
+        && (skeleton == known
+            || (known.chars().count() >= MIN_FUZZY_LEN && edit_distance(skeleton, known) <= 1))
+}
+
+// The Levenshtein distance between the two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + if a == *b { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, known_apps, recognise_in, KnownApp, Recognition};
+
+    #[test]
+    fn recognise_tests() {
+        let apps = known_apps();
+        let cli = unwrap!(apps.iter().find(|app| app.id == "net.maidsafe.cli")).clone();
+        assert_eq!(
+            recognise_in(&apps, "net.maidsafe.cli", "SAFE CLI"),
+            Recognition::Known(cli.clone())
+        );
+        for (id, name) in &[
+            ("net.maidsafe.c1i", "Some CLI"),
+            ("net.maidsafe-cli", "Some CLI"),
+            ("net.rnaidsafe.cli", "Some CLI"),
+            ("net.maidsafe.clii", "Some CLI"),
+            ("org.example.cli", "SAFE-CLI"),
+            ("org.example.cli", "Safe Cl1"),
+        ] {
+            assert_eq!(
+                recognise_in(&apps, id, name),
+                Recognition::Mimicking(cli.clone()),
+                "{} {}",
+                id,
+                name
+            );
+        }
+        assert_eq!(
+            recognise_in(&apps, "org.example.chat", "Chat"),
+            Recognition::Unknown
+        );
+
+        // The apps of the config file are known too
+        let chat = KnownApp {
+            id: String::from("org.example.chat"),
+            name: String::from("Chat"),
+            vendor: String::from("Example"),
+        };
+        let apps = vec![chat.clone()];
+        assert_eq!(
+            recognise_in(&apps, "org.example.chat", "Chat"),
+            Recognition::Known(chat.clone())
+        );
+        assert_eq!(
+            recognise_in(&apps, "org.example.chats", "Messages"),
+            Recognition::Mimicking(chat)
+        );
+        // The short names only mimic when their skeletons are the same
+        assert_eq!(
+            recognise_in(&apps, "org.example.mail", "Chats"),
+            Recognition::Unknown
+        );
+    }
+
+    #[test]
+    fn edit_distance_tests() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("netmaidsafecli", "netmaidsafecli"), 0);
+        assert_eq!(edit_distance("netmaidsafecli", "netmaidsafeclii"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The notes the user attaches to the apps with `apps label <id> <label>`, e.g. "work laptop
+// browser", to tell apart the apps whose ids look alike, e.g. the same app on several devices.
+// They are only stored locally, in the `labels` area of the storage, i.e. `labels/<profile>.json`
+// of the user's app directory by default, as an object of the labels by app id, and are shown
+// in the list of the authorised apps and in the prompt of the requests of the apps labelled.
+//
+// The labels are read from the storage each time they are shown, so a running Authenticator
+// service shows the ones given since it started.
+
+use super::instances::profile_entry;
+use crate::storage::{storage, Storage};
+use crate::Error;
+use lazy_static::lazy_static;
+use log::warn;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+const LABELS_AREA: &str = "labels";
+
+lazy_static! {
+    // The name of the entry of the profile in use
+    static ref LABELS_ENTRY: RwLock<Option<String>> = RwLock::new(None);
+}
+
+// The labels shown from now on are the ones of the profile
+pub fn use_profile_labels(profile: &str) {
+    if let Ok(mut labels_entry) = LABELS_ENTRY.write() {
+        *labels_entry = Some(profile_entry(profile, "json"));
+    }
+}
+
+// The labels of the profile in use, none if no profile is
+pub fn labels() -> BTreeMap<String, String> {
+    let entry = match LABELS_ENTRY.read().ok().and_then(|entry| entry.clone()) {
+        Some(entry) => entry,
+        None => return BTreeMap::new(),
+    };
+    match storage() {
+        Ok(storage) => read_labels(&*storage, &entry),
+        Err(err) => {
+            warn!("The labels of the apps couldn't be read: {}", err);
+            BTreeMap::new()
+        }
+    }
+}
+
+// The labels of the profile, whether it's in use or not
+pub fn profile_labels(profile: &str) -> Result<BTreeMap<String, String>, Error> {
+    Ok(read_labels(&*storage()?, &profile_entry(profile, "json")))
+}
+
+// Labels the app, or removes its label if none is given
+pub fn set_label(profile: &str, app_id: &str, label: Option<&str>) -> Result<(), Error> {
+    write_label(&*storage()?, &profile_entry(profile, "json"), app_id, label)
+}
+
+// An entry which can't be read, e.g. it was edited by hand, has no labels
+fn read_labels(storage: &dyn Storage, entry: &str) -> BTreeMap<String, String> {
+    let content = match storage.read(LABELS_AREA, entry) {
+        Ok(Some(content)) => content,
+        _ => return BTreeMap::new(),
+    };
+    serde_json::from_slice(&content).unwrap_or_else(|err| {
+        warn!("The labels of the apps '{}' are not valid: {}", entry, err);
+        BTreeMap::new()
+    })
+}
+
+fn write_label(
+    storage: &dyn Storage,
+    entry: &str,
+    app_id: &str,
+    label: Option<&str>,
+) -> Result<(), Error> {
+    let mut labels = read_labels(storage, entry);
+    match label.map(str::trim) {
+        Some(label) if !label.is_empty() => {
+            let _ = labels.insert(app_id.to_string(), label.to_string());
+        }
+        _ => {
+            if labels.remove(app_id).is_none() {
+                return Err(Error::InvalidInput(format!(
+                    "The app '{}' has no label",
+                    app_id
+                )));
+            }
+        }
+    }
+    let content = serde_json::to_vec_pretty(&labels).map_err(|err| {
+        Error::IoError(format!(
+            "Failed to serialise the labels of the apps: {}",
+            err
+        ))
+    })?;
+    storage.write(LABELS_AREA, entry, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_labels, write_label};
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn labels_tests() {
+        let storage = MemoryStorage::new();
+        let entry = "work.json";
+        assert!(read_labels(&storage, entry).is_empty());
+
+        unwrap!(write_label(
+            &storage,
+            entry,
+            "net.maidsafe.cli",
+            Some(" work laptop ")
+        ));
+        unwrap!(write_label(
+            &storage,
+            entry,
+            "org.example.chat",
+            Some("phone")
+        ));
+        unwrap!(write_label(
+            &storage,
+            entry,
+            "org.example.chat",
+            Some("tablet")
+        ));
+        let labels = read_labels(&storage, entry);
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels["net.maidsafe.cli"], "work laptop");
+        assert_eq!(labels["org.example.chat"], "tablet");
+
+        // An empty label removes it, which fails if there was none
+        unwrap!(write_label(&storage, entry, "org.example.chat", Some("")));
+        assert!(write_label(&storage, entry, "org.example.chat", None).is_err());
+        assert!(!read_labels(&storage, entry).contains_key("org.example.chat"));
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The `listen` command, a lightweight alternative to the Authenticator service for an app to
+// hand its auth request off: it listens on localhost, on the port given or a free one, for the
+// auth requests either POSTed as the body to `/authorise`, or sent as `GET /authorise/<req>`
+// the same as to the service, e.g. by the handler of the app's URI. Each request is decided on
+// with the usual prompt and policies, and its response is sent back as the body of the HTTP
+// response, or the error with a `400` status as the service sends it. With `--once` it exits
+// after the first auth request.
+//
+// It's a plain HTTP/1.1 exchange on a `TcpListener`, one connection at a time, so it doesn't
+// require the `daemon` feature.
+
+use super::io::Console;
+use super::output::{Output, OutputFormatter};
+use crate::{AuthAllowPrompt, AuthBackend, Error};
+use log::{debug, warn};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::time::Duration;
+
+// The auth request strings are far shorter, larger bodies are rejected
+const MAX_BODY_SIZE: usize = 64 * 1024;
+// How long the client has to send its request once connected
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, PartialEq)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    body: String,
+}
+
+pub fn run_listener(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    prompt: &'static AuthAllowPrompt,
+    port: u16,
+    once: bool,
+) -> Result<(), Error> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .map_err(|err| Error::IoError(format!("Failed to listen on port {}: {}", port, err)))?;
+    let address = listener
+        .local_addr()
+        .map_err(|err| Error::IoError(format!("Failed to listen: {}", err)))?;
+    // On stderr, so the outputs on stdout are only the responses
+    errln!(console, "{}", tr!(console, "listening", address = address));
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("Failed to accept a connection: {}", err);
+                continue;
+            }
+        };
+        let handled = handle_connection(backend, console, output, prompt, stream);
+        if handled && once {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Whether an auth request was received, in which case it was decided on
+fn handle_connection(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    prompt: &'static AuthAllowPrompt,
+    mut stream: TcpStream,
+) -> bool {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let request = match stream.try_clone() {
+        Ok(reader) => read_request(&mut BufReader::new(reader)),
+        Err(err) => Err(err.to_string()),
+    };
+    let request = match request {
+        Ok(request) => request,
+        Err(err) => {
+            debug!("Invalid HTTP request received: {}", err);
+            let err = Error::InvalidInput(format!("Invalid HTTP request: {}", err));
+            write_error(&mut stream, err);
+            return false;
+        }
+    };
+    let auth_req = match auth_req_of(&request) {
+        Some(auth_req) => auth_req,
+        None => {
+            write_response(&mut stream, "404 Not Found", "text/plain", "Not found");
+            return false;
+        }
+    };
+
+    match backend.authorise_app(&auth_req, prompt) {
+        Ok(response) => {
+            write_response(&mut stream, "200 OK", "text/plain", &response);
+            output.output(console, Output::AuthResponse(&response));
+        }
+        Err(err) => {
+            errln!(console, "{}", err);
+            write_error(&mut stream, err);
+        }
+    }
+    true
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Result<HttpRequest, String> {
+    let mut line = String::new();
+    let _ = reader.read_line(&mut line).map_err(|err| err.to_string())?;
+    let mut words = line.split_whitespace();
+    let (method, path) = match (words.next(), words.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(String::from("no request line")),
+    };
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|err| err.to_string())?;
+        let header = line.trim();
+        if read == 0 || header.is_empty() {
+            break;
+        }
+        if let Some(index) = header.find(':') {
+            let (name, value) = header.split_at(index);
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value[1..]
+                    .trim()
+                    .parse()
+                    .map_err(|_| String::from("invalid Content-Length"))?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(format!("the body is larger than {} bytes", MAX_BODY_SIZE));
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|err| err.to_string())?;
+    let body = String::from_utf8(body).map_err(|_| String::from("the body is not UTF-8"))?;
+    Ok(HttpRequest { method, path, body })
+}
+
+// The auth request string sent, if the request is one to authorise
+fn auth_req_of(request: &HttpRequest) -> Option<String> {
+    let path = request.path.split('?').next().unwrap_or("");
+    let auth_req = match request.method.as_str() {
+        "POST" if path == "/authorise" => request.body.trim(),
+        "GET" if path.starts_with("/authorise/") => &path["/authorise/".len()..],
+        _ => return None,
+    };
+    if auth_req.is_empty() {
+        None
+    } else {
+        Some(auth_req.to_string())
+    }
+}
+
+// Sent as the service does, e.g. {"error":{"kind":"IpcDecodeError","description":"..."}}
+fn write_error(stream: &mut TcpStream, err: Error) {
+    let body = json!({ "error": err }).to_string();
+    write_response(stream, "400 Bad Request", "application/json", &body);
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    if let Err(err) = stream
+        .write_all(response.as_bytes())
+        .and_then(|()| stream.flush())
+    {
+        warn!("Failed to send the response: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{auth_req_of, read_request, run_listener, HttpRequest};
+    use crate::cli::instances::free_port;
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::cli::output::formatter;
+    use crate::test_utils::APP_AUTH_REQ;
+    use crate::FakeBackend;
+    use std::io::{Cursor, Read, Write};
+    use std::net::{Ipv4Addr, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    fn request(method: &str, path: &str, body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn read_request_tests() {
+        let raw = "POST /authorise HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nbAAAA";
+        assert_eq!(
+            unwrap!(read_request(&mut Cursor::new(raw))),
+            request("POST", "/authorise", "bAAAA")
+        );
+        let raw = "GET /authorise/bAAAA HTTP/1.1\r\n\r\n";
+        assert_eq!(
+            unwrap!(read_request(&mut Cursor::new(raw))),
+            request("GET", "/authorise/bAAAA", "")
+        );
+        assert!(read_request(&mut Cursor::new("\r\n")).is_err());
+        let raw = "POST /authorise HTTP/1.1\r\nContent-Length: 99999999\r\n\r\n";
+        assert!(read_request(&mut Cursor::new(raw)).is_err());
+    }
+
+    #[test]
+    fn auth_req_of_tests() {
+        let auth_req = Some(String::from("bAAAA"));
+        assert_eq!(
+            auth_req_of(&request("POST", "/authorise", "bAAAA\n")),
+            auth_req
+        );
+        assert_eq!(
+            auth_req_of(&request("GET", "/authorise/bAAAA", "")),
+            auth_req
+        );
+        assert_eq!(
+            auth_req_of(&request("GET", "/authorise/bAAAA?x=1", "")),
+            auth_req
+        );
+        assert_eq!(auth_req_of(&request("GET", "/authorise/", "")), None);
+        assert_eq!(auth_req_of(&request("POST", "/authorise", "")), None);
+        assert_eq!(auth_req_of(&request("GET", "/favicon.ico", "")), None);
+    }
+
+    #[test]
+    fn listen_once_tests() {
+        let port = unwrap!(free_port());
+        let listener = thread::spawn(move || {
+            let console = Console::new(Box::new(CapturedIo::new("")));
+            let output = unwrap!(formatter("plain"));
+            run_listener(
+                &FakeBackend::default(),
+                &console,
+                &*output,
+                &|_| true,
+                port,
+                true,
+            )
+        });
+
+        let send = |request: String| {
+            let mut stream = loop {
+                match TcpStream::connect((Ipv4Addr::LOCALHOST, port)) {
+                    Ok(stream) => break stream,
+                    Err(_) => thread::sleep(Duration::from_millis(10)),
+                }
+            };
+            unwrap!(stream.write_all(request.as_bytes()));
+            let mut response = String::new();
+            let _ = unwrap!(stream.read_to_string(&mut response));
+            response
+        };
+        // Other requests don't end it
+        assert!(send(String::from("GET / HTTP/1.1\r\n\r\n")).starts_with("HTTP/1.1 404"));
+        let response = send(format!(
+            "POST /authorise HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            APP_AUTH_REQ.len(),
+            APP_AUTH_REQ
+        ));
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        unwrap!(unwrap!(listener.join()));
+    }
+}
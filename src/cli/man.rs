@@ -0,0 +1,266 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The `man` command. The roff man pages of the binary and of each of its subcommands are
+// generated from the help printed by the arguments parser, so they always document the
+// arguments of the build they are generated with. Each page has a section per section of the
+// help, e.g. FLAGS or SUBCOMMANDS, and the subcommands' pages are named as `git`'s ones are,
+// e.g. `safe_auth-account-backup.1`.
+
+use super::io::Console;
+use crate::Error;
+use std::fs;
+use std::path::Path;
+use structopt::clap::{App, AppSettings, ErrorKind};
+
+const BIN_NAME: &str = "safe_auth";
+// The subcommand added by the arguments parser, which has no page
+const HELP_SUBCOMMAND: &str = "help";
+
+#[derive(Debug, PartialEq)]
+pub struct ManPage {
+    // Name of the file, e.g. `safe_auth-account.1`
+    pub file_name: String,
+    pub content: String,
+}
+
+// The help of a command, split into its sections
+#[derive(Default, Debug)]
+struct Help {
+    about: Vec<String>,
+    usage: Vec<String>,
+    // The title of each section, e.g. "FLAGS", and its items
+    sections: Vec<(String, Vec<HelpItem>)>,
+}
+
+#[derive(Default, Debug)]
+struct HelpItem {
+    term: String,
+    description: Vec<String>,
+}
+
+// Writes the pages of the binary and its subcommands to the directory
+pub fn write_man_pages(
+    console: &Console,
+    dir: &Path,
+    app: &dyn Fn() -> App<'static, 'static>,
+) -> Result<(), Error> {
+    let pages = man_pages(app)?;
+    fs::create_dir_all(dir).map_err(|err| {
+        Error::IoError(format!("Failed to create the man pages directory: {}", err))
+    })?;
+    for page in pages {
+        let path = dir.join(&page.file_name);
+        fs::write(&path, page.content)
+            .map_err(|err| Error::IoError(format!("Failed to write the man page: {}", err)))?;
+        outln!(
+            console,
+            "{}",
+            tr!(console, "man-page-written", file = path.display())
+        );
+    }
+    Ok(())
+}
+
+// The page of the binary and of each of its subcommands, the binary's one first
+pub fn man_pages(app: &dyn Fn() -> App<'static, 'static>) -> Result<Vec<ManPage>, Error> {
+    let mut pages = vec![];
+    let mut pending: Vec<Vec<String>> = vec![vec![]];
+    while let Some(path) = pending.pop() {
+        let mut help = parse_help(&help_text(app, &path)?);
+        let mut subcommands: Vec<String> = vec![];
+        for (title, items) in help.sections.iter_mut() {
+            if title == "SUBCOMMANDS" {
+                items.retain(|item| item.term != HELP_SUBCOMMAND);
+                subcommands.extend(items.iter().map(|item| item.term.clone()));
+            }
+        }
+        pages.push(render(&path, &subcommands, &help));
+        // The subcommands are documented in the order they are listed
+        for name in subcommands.into_iter().rev() {
+            let mut subcommand = path.clone();
+            subcommand.push(name);
+            pending.push(subcommand);
+        }
+    }
+    Ok(pages)
+}
+
+fn help_text(app: &dyn Fn() -> App<'static, 'static>, path: &[String]) -> Result<String, Error> {
+    let mut args = vec![BIN_NAME.to_string()];
+    args.extend_from_slice(path);
+    args.push("--help".to_string());
+    // Descriptions on their own lines, as they are on the pages
+    let result = app()
+        .global_setting(AppSettings::NextLineHelp)
+        .get_matches_from_safe(args);
+    match result {
+        Err(err) if err.kind == ErrorKind::HelpDisplayed => Ok(err.message),
+        Err(err) => Err(Error::InvalidInput(err.message)),
+        Ok(_) => Err(Error::InvalidInput(format!(
+            "No help found for the '{}' command",
+            path.join(" ")
+        ))),
+    }
+}
+
+// The first lines are the name, version and description of the command, then each section
+// has a title, e.g. "FLAGS:", followed by the items indented, and their descriptions
+// indented further on the next lines
+fn parse_help(text: &str) -> Help {
+    let mut help = Help::default();
+    let mut header = vec![];
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        if indent == 0 && trimmed.ends_with(':') && trimmed == trimmed.to_uppercase() {
+            let title = trimmed.trim_end_matches(':').to_string();
+            help.sections.push((title, vec![]));
+            continue;
+        }
+
+        match help.sections.last_mut() {
+            None => header.push(trimmed.to_string()),
+            Some((title, _)) if title == "USAGE" => help.usage.push(trimmed.to_string()),
+            Some((_, items)) => match items.last_mut() {
+                // The items not having a short version are indented as if they had it
+                Some(item) if indent > 8 => item.description.push(trimmed.to_string()),
+                _ => items.push(HelpItem {
+                    term: trimmed.to_string(),
+                    description: vec![],
+                }),
+            },
+        }
+    }
+
+    // Apart from the first line, with the name and version, it's the description
+    help.about = header.into_iter().skip(1).collect();
+    help.sections.retain(|(title, _)| title != "USAGE");
+    help
+}
+
+fn render(path: &[String], subcommands: &[String], help: &Help) -> ManPage {
+    let mut name = BIN_NAME.to_string();
+    for command in path {
+        name += "-";
+        name += command;
+    }
+
+    let mut page = format!(
+        ".TH \"{}\" 1 \"\" \"{} {}\" \"User Commands\"\n",
+        name.to_uppercase(),
+        BIN_NAME,
+        env!("CARGO_PKG_VERSION")
+    );
+    page += ".SH NAME\n";
+    page += &format!("{} \\- {}\n", escape(&name), escape(&help.about.join(" ")));
+    page += ".SH SYNOPSIS\n";
+    for usage in help.usage.iter() {
+        page += &format!(".B {}\n", escape(usage));
+    }
+    for (title, items) in help.sections.iter() {
+        page += &format!(".SH {}\n", title);
+        for item in items {
+            page += &format!(".TP\n\\fB{}\\fR\n", escape(&item.term));
+            for line in item.description.iter() {
+                page += &format!("{}\n", escape(line));
+            }
+        }
+    }
+
+    let mut see_also: Vec<String> = vec![];
+    if !path.is_empty() {
+        let parent = name.rsplit_once('-').map(|x| x.0).unwrap_or(BIN_NAME);
+        see_also.push(format!("{}(1)", escape(parent)));
+    }
+    for subcommand in subcommands {
+        see_also.push(format!("{}\\-{}(1)", escape(&name), escape(subcommand)));
+    }
+    if !see_also.is_empty() {
+        page += ".SH SEE ALSO\n";
+        page += &format!("{}\n", see_also.join(", "));
+    }
+
+    ManPage {
+        file_name: format!("{}.1", name),
+        content: page,
+    }
+}
+
+// Text escaped for roff, which must not be read as requests nor escape sequences
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, man_pages};
+    use structopt::clap::{App, Arg, SubCommand};
+
+    fn test_app() -> App<'static, 'static> {
+        App::new("safe_auth")
+            .about("Manage SAFE Network authorisations and accounts.")
+            .arg(
+                Arg::with_name("apps")
+                    .short("a")
+                    .long("apps")
+                    .help("Get list of authorised apps"),
+            )
+            .subcommand(
+                SubCommand::with_name("account")
+                    .about("Manage the SAFE Network account")
+                    .subcommand(
+                        SubCommand::with_name("backup")
+                            .about("Write a backup")
+                            .arg(Arg::with_name("file").required(true).help("The file")),
+                    ),
+            )
+    }
+
+    #[test]
+    fn man_pages_tests() {
+        let pages = unwrap!(man_pages(&test_app));
+        let names: Vec<&str> = pages.iter().map(|page| page.file_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "safe_auth.1",
+                "safe_auth-account.1",
+                "safe_auth-account-backup.1"
+            ]
+        );
+
+        let main = &pages[0].content;
+        assert!(main.starts_with(".TH \"SAFE_AUTH\" 1"));
+        assert!(main.contains("safe_auth \\- Manage SAFE Network authorisations and accounts.\n"));
+        assert!(main
+            .contains(".SH FLAGS\n.TP\n\\fB\\-a, \\-\\-apps\\fR\nGet list of authorised apps\n"));
+        assert!(main.contains(".SH SUBCOMMANDS\n.TP\n\\fBaccount\\fR\n"));
+        assert!(main.contains(".SH SEE ALSO\nsafe_auth\\-account(1)\n"));
+        assert!(!main.contains("\\fBhelp\\fR"));
+
+        let backup = &pages[2].content;
+        assert!(backup.contains(".SH ARGS\n.TP\n\\fB<file>\\fR\nThe file\n"));
+        assert!(backup.contains(".SH SEE ALSO\nsafe_auth\\-account(1)\n"));
+    }
+
+    #[test]
+    fn escape_tests() {
+        assert_eq!(escape("--sk <sk>"), "\\-\\-sk <sk>");
+        assert_eq!(escape(".config"), "\\&.config");
+        assert_eq!(escape("C:\\safe"), "C:\\esafe");
+    }
+}
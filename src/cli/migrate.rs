@@ -0,0 +1,207 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The migration from a legacy authenticator, e.g. the one built into the SAFE Browser, with
+// `migrate <file>`. The apps those authorised are kept in the account, so they're listed as soon
+// as the CLI logs in; what they only kept locally is imported from their state exported as JSON,
+// e.g. `{"apps": [{"id": "<app id>", "label": "Laptop", "granted_at": 1546300800}],
+// "denylist": ["<app id>"]}`, the other fields being ignored:
+// - the labels of the apps which don't have one yet,
+// - the times the apps were granted, recorded as receipts of the `legacy` approver for the
+//   apps which don't have any receipt yet, so the permission reviews don't flag them as never
+//   seen,
+// - the apps denied, added to the profile's denylist.
+// The revoked apps are skipped. Nothing is imported twice, so the migration can be run again,
+// e.g. with a newer export.
+//
+// The denylist imported is stored in the `denylists` area of the storage, i.e.
+// `denylists/<profile>.json` of the user's app directory by default, and merged with the one of
+// the config file's `prompts`.
+
+use super::helpers::PromptPolicies;
+use super::instances::profile_entry;
+use super::labels::{profile_labels, set_label};
+use super::receipts::{last_seen, record_legacy_grant};
+use crate::storage::{storage, Storage};
+use crate::Error;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+const DENYLISTS_AREA: &str = "denylists";
+
+#[derive(Deserialize, Debug, Default)]
+struct LegacyState {
+    #[serde(default)]
+    apps: Vec<LegacyApp>,
+    #[serde(default)]
+    denylist: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyApp {
+    id: String,
+    #[serde(default)]
+    label: Option<String>,
+    // Seconds since the UNIX epoch
+    #[serde(default)]
+    granted_at: Option<u64>,
+    #[serde(default)]
+    revoked: bool,
+}
+
+// What was imported
+#[derive(Debug, Default, PartialEq)]
+pub struct Migration {
+    pub labels: usize,
+    pub grants: usize,
+    pub denied: usize,
+}
+
+// The key of the profile the grants are signed with is unlocked before the first one is imported
+pub fn migrate_legacy_state(
+    file: &Path,
+    profile: &str,
+    unlock: &dyn Fn() -> Result<(), Error>,
+) -> Result<Migration, Error> {
+    let content = fs::read(file).map_err(|err| {
+        Error::IoError(format!(
+            "Failed to read the legacy state '{}': {}",
+            file.display(),
+            err
+        ))
+    })?;
+    let state: LegacyState = serde_json::from_slice(&content).map_err(|err| {
+        Error::InvalidInput(format!(
+            "The legacy state '{}' is not valid: {}",
+            file.display(),
+            err
+        ))
+    })?;
+
+    let labels = profile_labels(profile)?;
+    let seen = last_seen(profile)?;
+    let mut migration = Migration::default();
+    for app in state.apps.iter().filter(|app| !app.revoked) {
+        let label = app.label.as_ref().map(|label| label.trim());
+        if let Some(label) = label {
+            if !label.is_empty() && !labels.contains_key(&app.id) {
+                set_label(profile, &app.id, Some(label))?;
+                migration.labels += 1;
+            }
+        }
+        if let Some(granted_at) = app.granted_at {
+            if !seen.contains_key(&app.id) {
+                if migration.grants == 0 {
+                    unlock()?;
+                }
+                record_legacy_grant(profile, &app.id, granted_at)?;
+                migration.grants += 1;
+            }
+        }
+    }
+    migration.denied = add_to_denylist(
+        &*storage()?,
+        &profile_entry(profile, "json"),
+        &state.denylist,
+    )?;
+    Ok(migration)
+}
+
+// The policies with the denylist imported for the profile, if any
+pub fn with_imported_denylist(
+    mut policies: PromptPolicies,
+    profile: &str,
+) -> Result<PromptPolicies, Error> {
+    for app_id in read_denylist(&*storage()?, &profile_entry(profile, "json"))? {
+        if !policies.denylist.contains(&app_id) {
+            policies.denylist.push(app_id);
+        }
+    }
+    Ok(policies)
+}
+
+fn read_denylist(storage: &dyn Storage, entry: &str) -> Result<Vec<String>, Error> {
+    match storage.read(DENYLISTS_AREA, entry)? {
+        Some(content) => serde_json::from_slice(&content).map_err(|err| {
+            Error::IoError(format!(
+                "The denylist imported '{}' is not valid: {}",
+                entry, err
+            ))
+        }),
+        None => Ok(vec![]),
+    }
+}
+
+// Adds the apps which aren't on the denylist yet, returning how many were added
+fn add_to_denylist(storage: &dyn Storage, entry: &str, app_ids: &[String]) -> Result<usize, Error> {
+    let mut denylist = read_denylist(storage, entry)?;
+    let before = denylist.len();
+    for app_id in app_ids {
+        if !app_id.trim().is_empty() && !denylist.contains(app_id) {
+            denylist.push(app_id.clone());
+        }
+    }
+    let added = denylist.len() - before;
+    if added > 0 {
+        let content = serde_json::to_vec(&denylist)
+            .map_err(|err| Error::IoError(format!("Failed to serialise the denylist: {}", err)))?;
+        storage.write(DENYLISTS_AREA, entry, &content)?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_to_denylist, read_denylist, LegacyState};
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn legacy_state_tests() {
+        let state: LegacyState = unwrap!(serde_json::from_str(
+            r#"{ "apps": [
+                    { "id": "net.maidsafe.browser", "name": "SAFE Browser", "label": "Laptop",
+                      "granted_at": 1546300800 },
+                    { "id": "net.example.old", "revoked": true }
+                ],
+                "denylist": ["net.example.bad"], "version": 2 }"#
+        ));
+        assert_eq!(state.apps.len(), 2);
+        assert_eq!(state.apps[0].label.as_deref(), Some("Laptop"));
+        assert_eq!(state.apps[0].granted_at, Some(1_546_300_800));
+        assert!(!state.apps[0].revoked && state.apps[1].revoked);
+        assert_eq!(state.denylist, vec!["net.example.bad"]);
+
+        let empty: LegacyState = unwrap!(serde_json::from_str("{}"));
+        assert!(empty.apps.is_empty() && empty.denylist.is_empty());
+        assert!(serde_json::from_str::<LegacyState>(r#"{ "apps": [{}] }"#).is_err());
+    }
+
+    #[test]
+    fn denylist_tests() {
+        let storage = MemoryStorage::new();
+        let entry = "work.json";
+        assert!(unwrap!(read_denylist(&storage, entry)).is_empty());
+
+        let app_ids = vec![
+            String::from("net.example.bad"),
+            String::from("net.example.worse"),
+        ];
+        assert_eq!(unwrap!(add_to_denylist(&storage, entry, &app_ids)), 2);
+        // The apps already on it aren't added twice
+        let app_ids = vec![
+            String::from("net.example.bad"),
+            String::from("net.example.new"),
+        ];
+        assert_eq!(unwrap!(add_to_denylist(&storage, entry, &app_ids)), 1);
+        assert_eq!(
+            unwrap!(read_denylist(&storage, entry)),
+            vec!["net.example.bad", "net.example.worse", "net.example.new"]
+        );
+    }
+}
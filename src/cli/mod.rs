@@ -0,0 +1,2651 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! # The `safe_auth` CLI
+//!
+//! All the logic of the CLI lives here so it can be run in-process with `run_with_args`,
+//! the `safe_auth` binary just runs it with the process' arguments and standard streams.
+
+#[macro_use]
+mod io;
+#[macro_use]
+mod table;
+mod activity;
+mod attention;
+mod capabilities;
+mod color;
+mod credentials;
+mod dashboard;
+mod diagnostics;
+mod doctor;
+mod edit;
+mod filter;
+mod grants;
+mod guardian;
+mod helpers;
+mod history;
+mod i18n;
+mod instances;
+mod known_apps;
+mod labels;
+mod listen;
+mod man;
+mod migrate;
+mod networks;
+mod output;
+mod policy;
+mod prompting;
+mod receipts;
+mod review;
+mod safe_key;
+mod select;
+mod shell;
+mod snapshot;
+mod status;
+mod template;
+mod toast;
+mod update;
+mod verification;
+
+pub use self::diagnostics::{init_logger, install_panic_handler};
+pub use self::io::{CapturedIo, CapturedOutput, CliIo, StdIo};
+
+use self::activity::account_activity;
+use self::attention::Attention;
+use self::capabilities::capabilities;
+use self::color::ColorChoice;
+use self::credentials::{
+    credential_providers, get_login_details, stored_credentials, LoginDetails,
+};
+use self::dashboard::{dashboard_prompt, run_dashboard, PendingRequest};
+use self::diagnostics::{register_secret, set_verbosity};
+use self::doctor::{run_checks, CheckStatus, DoctorOptions, DEFAULT_DAEMON_PORT};
+use self::edit::{edit_permissions, regrant};
+use self::filter::{in_container, parse_permission, with_permission};
+use self::grants::{revoke_expired, spawn_expiries, TemporaryGrants};
+use self::guardian::{approval_prompt, Guardian, GuardianBackend};
+use self::helpers::*;
+use self::history::History;
+use self::i18n::{available_locales, Localiser};
+use self::instances::{
+    find_instance, free_port, profile_entry, profile_name, register_instance, InstanceLock,
+    ServiceClient,
+};
+use self::io::Console;
+use self::known_apps::set_user_apps;
+use self::labels::{set_label, use_profile_labels};
+use self::listen::run_listener;
+use self::man::write_man_pages;
+use self::migrate::{migrate_legacy_state, with_imported_denylist};
+use self::networks::{add_network, list_networks, network_config, network_path};
+use self::output::{
+    formatter, Output, OutputFormatter, PlainFormatter, QuietFormatter, StrictFormatter,
+    TemplateFormatter,
+};
+use self::policy::{merged_policies, pull_policy};
+use self::prompting::{session_prompt, PromptingBackend};
+use self::receipts::{
+    decrypt_request, find_receipt, last_authorised, profile_public_keys, unlock_profile,
+    verify_receipt, Receipt, ReceiptsBackend, Signer,
+};
+use self::review::spawn_reminders;
+#[cfg(not(feature = "mock-network"))]
+use self::safe_key::fetch_invite;
+use self::safe_key::{create_with_retries, FreshToken, SAFE_KEY_RETRY_DELAY};
+use self::select::select_apps;
+use self::shell::run_shell;
+use self::snapshot::{read_snapshot, write_snapshot, LIVE_SNAPSHOT};
+use self::status::{
+    daemon_address, format_time, last_login, network_label, record_balance, record_login, Status,
+};
+use self::table::{Overflow, TableLayout, TableStyle};
+use self::toast::{parse_decision_uri, Toasts};
+use self::update::{check_new_version, update_application};
+use self::verification::{verify_user, UserVerification};
+use crate::apps::regrants;
+use crate::ipc::ReqKind;
+use crate::pacing::Pacer;
+use crate::storage::storage;
+use crate::{
+    create_acc_cancellable, decode_req, diff_authed_apps, log_in_cancellable, run_stdio,
+    set_pacing, set_timeouts, AuthAllowPrompt, AuthBackend, AuthedAppsDiff, AuthedAppsList,
+    CancellableAuthenticator, CancellationToken, Error, EventBus, EventHistory, FakeBackend,
+    Guarding, Headless, OperationTimeouts, PendingDecisions, RecordingBackend, ReplayBackend,
+    RequestWarnings, ServiceOptions, SharedBackend, WarningBackend, DEFAULT_HISTORY_CAPACITY,
+};
+use log::{debug, warn};
+use safe_authenticator::Authenticator;
+use safe_core::client::test_create_balance;
+use safe_core::ipc::req::IpcReq;
+use safe_core::ipc::Permission;
+use safe_nd::{ClientFullId, Coins};
+use std::env;
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread;
+use structopt::clap::ErrorKind;
+use structopt::StructOpt;
+use threshold_crypto::{serde_impl::SerdeSecret, SecretKey, PK_SIZE};
+
+const DEFAULT_SEARCH_PATH: &str = "resources/";
+const CRUST_CONFIG_PATH_ENV_VAR: &str = "SAFE_CRUST_CONFIG_PATH";
+// The area of the storage with the persisted event histories of the profiles' services
+const EVENTS_AREA: &str = "events";
+
+#[derive(StructOpt, Debug)]
+/// Manage SAFE Network authorisations and accounts.
+#[structopt(raw(global_settings = "&[structopt::clap::AppSettings::ColoredHelp]"))]
+pub struct CmdArgs {
+    /// A config file to read secret/password from. This is a temporary convenience function, which is not recommended. (Storing login information unencrypted is not secure.)
+    #[structopt(short = "c", long = "config")]
+    config_file_str: Option<String>,
+    /// Deprecated, use the `auth` command: the encoded authorisation request string
+    #[structopt(short = "r", long = "req")]
+    req_str: Option<String>,
+    /// Deprecated, use `create --sk`: the secret key to be used as the default spendable balance that will get created in the new SAFE Network account
+    #[structopt(long = "sk")]
+    sk: Option<String>,
+    /// Deprecated, use `create --test-coins`: create test-coins automatically and use them to pay for the account creation
+    #[structopt(long = "test-coins")]
+    test_coins: bool,
+    /// Deprecated, use `apps list`: get list of authorised apps
+    // Not named after the flag, clap telling the flags named after a subcommand as given with it
+    #[structopt(short = "a", long = "apps")]
+    list_apps: bool,
+    /// Only list the apps with a permission on any container, e.g. 'Delete' for the apps
+    /// which can delete data: 'Read', 'Insert', 'Update', 'Delete' or 'ManagePermissions'
+    #[structopt(
+        long = "with-permission",
+        requires = "apps",
+        parse(try_from_str = "parse_permission")
+    )]
+    with_permission: Option<Permission>,
+    /// Only list the apps with any permission on a container, e.g. '_documents', or on the
+    /// containers matching a pattern where '*' matches any characters, e.g. 'apps/*' for
+    /// the apps' own containers
+    #[structopt(long = "container", requires = "apps")]
+    container: Option<String>,
+    /// Deprecated, use the `revoke` command: the application's ID to revoke all authorised permissions from
+    #[structopt(short = "k", long = "revoke")]
+    app_id: Option<String>,
+    /// Output data serialisation: 'table' (default), 'plain' or 'tsv' (tab separated values),
+    /// 'json', 'jsonl' (a JSON object per line), 'yaml' or 'csv'
+    #[structopt(short = "o", long = "output")]
+    output_fmt: Option<String>,
+    /// The style of the tables: 'grid', 'plain' or 'markdown', otherwise they have borders
+    /// and colours on a terminal
+    #[structopt(long = "table-style")]
+    table_style: Option<TableStyle>,
+    /// Fit the tables to the terminal's width, either wrapping ('wrap') or truncating
+    /// ('truncate') the text of the widest columns
+    #[structopt(long = "table-overflow")]
+    table_overflow: Option<Overflow>,
+    /// Colour the tables and prompts: 'auto' (default) only on a terminal, keeping the output
+    /// piped to other programs plain, 'always' or 'never'
+    #[structopt(long = "color")]
+    color: Option<ColorChoice>,
+    /// Write the progress of the long operations to stderr: 'json' writes a JSON object per
+    /// line, with the stage, the percentage completed and a message, e.g. for a progress bar
+    #[structopt(long = "progress")]
+    progress: Option<ProgressFormat>,
+    /// Sets JSON as output serialisation format (alias of '--output json')
+    #[structopt(long = "json")]
+    output_json: bool,
+    /// Print each record of the output with a template, e.g. '{{app.id}}\t{{app.vendor}}',
+    /// the fields between double braces being the paths of the values in the JSON records
+    #[structopt(
+        long = "format",
+        raw(conflicts_with_all = r#"&["output_fmt", "output_json"]"#)
+    )]
+    format: Option<String>,
+    /// Print the 'plain' output with the fields separated by the unit separator character and
+    /// each record ended with a NUL, so the values can have tabs and new lines, e.g. for `xargs -0`
+    #[structopt(
+        long = "print0",
+        raw(conflicts_with_all = r#"&["output_json", "format"]"#)
+    )]
+    print0: bool,
+    /// Read JSON commands from stdin and write the responses to stdout, one per line,
+    /// to embed the authenticator in another application's UI
+    #[structopt(long = "headless")]
+    headless: bool,
+    /// Deprecated, use `daemon --port`: port number where the Authenticator webservice shall be listening to
+    #[structopt(short = "d", long = "daemon")]
+    port: Option<u16>,
+    /// Flag to automatically allow any authorisation request received,
+    /// otherwise the user is a prompted to allow each request individually
+    #[structopt(
+        short = "y",
+        long = "allow-all-auth",
+        raw(visible_aliases = r#"&["yes", "allow"]"#)
+    )]
+    allow_all: bool,
+    /// Fail the operations connecting to the network, e.g. logging in or revoking an app, which
+    /// don't complete within the number of seconds, rather than waiting indefinitely
+    #[structopt(long = "timeout")]
+    timeout: Option<u64>,
+    /// Deny the authorisation requests which are not answered within the number of seconds,
+    /// rather than waiting for the answer indefinitely
+    #[structopt(long = "prompt-timeout")]
+    prompt_timeout: Option<u64>,
+    /// Log in to the network even if an Authenticator service is running for it, rather than
+    /// operating on the account through the service
+    #[structopt(long = "standalone")]
+    standalone: bool,
+    /// The profile the Authenticator service runs for, and is looked for by the other
+    /// invocations, e.g. to run one for each account, otherwise there is one for each network
+    #[structopt(long = "profile")]
+    profile: Option<String>,
+    /// The name of a stored network definition to connect to, instead of the default network
+    #[structopt(long = "network")]
+    network: Option<String>,
+    /// Ignore the bootstrap contacts cached from previous connections to the network
+    #[structopt(long = "fresh-bootstrap")]
+    fresh_bootstrap: bool,
+    /// Deprecated, use the `update` command: updates the application with a newer version if any are available
+    #[structopt(long = "update")]
+    update_app: bool,
+    /// Print, as JSON, the optional features supported by this build
+    #[structopt(long = "capabilities")]
+    capabilities: bool,
+    /// Record the operations performed on the account, and their outcome, to a file
+    #[structopt(long = "record", parse(from_os_str))]
+    record: Option<PathBuf>,
+    /// Replay the operations recorded in a file instead of connecting to the network
+    #[structopt(long = "replay", parse(from_os_str), conflicts_with = "record")]
+    replay: Option<PathBuf>,
+    /// Use an in-memory mock account instead of connecting to the network, no credentials
+    /// are needed. The responses to the auth requests can't be used to connect to any network.
+    #[structopt(
+        long = "mock",
+        raw(conflicts_with_all = r#"&["replay", "sk", "test_coins"]"#)
+    )]
+    mock: bool,
+    /// The language of the prompts and messages, e.g. 'en-US', otherwise the one of the
+    /// environment is used if available
+    #[structopt(long = "lang")]
+    lang: Option<String>,
+    /// Write the logs of the CLI and the SAFE libraries to stderr: '-v' for the info ones,
+    /// '-vv' for the debug ones and '-vvv' for all of them
+    #[structopt(short = "v", long = "verbose", parse(from_occurrences))]
+    verbose: u64,
+    /// Only write the result of the command to stdout, as JSON unless another parsable
+    /// output is given, and everything else to stderr, including the error as a JSON object
+    #[structopt(long = "strict")]
+    strict: bool,
+    /// Only write the data to stdout, e.g. the auth response or the balance alone unless
+    /// another output is given, and everything else to stderr, e.g. for `$(...)` in scripts
+    #[structopt(short = "q", long = "quiet", conflicts_with = "strict")]
+    quiet: bool,
+    /// Never prompt the user, failing instead when the credentials or a decision on a request
+    /// can't be got without asking for them
+    #[structopt(long = "non-interactive")]
+    non_interactive: bool,
+    #[structopt(subcommand)]
+    cmd: Option<SubCommands>,
+}
+
+#[derive(StructOpt, Debug)]
+enum SubCommands {
+    #[structopt(name = "create")]
+    /// Create a SAFE Network account, paying for it with the safecoins of a SafeKey or with
+    /// test-coins (same as --sk or --test-coins)
+    Create {
+        /// The secret key to be used as the default spendable balance that will get created
+        /// in the new SAFE Network account
+        #[structopt(long = "sk", raw(required_unless = r#""test_coins""#))]
+        sk: Option<String>,
+        /// Create test-coins automatically and use them to pay for the account creation
+        #[structopt(long = "test-coins", conflicts_with = "sk")]
+        test_coins: bool,
+    },
+    #[structopt(name = "login")]
+    /// Log in to the SAFE Network, even if an Authenticator service is running for it, e.g.
+    /// to check the credentials
+    Login,
+    #[structopt(name = "auth")]
+    /// Decide on an authorisation request, and print the response to send back to the
+    /// application (same as --req)
+    Auth {
+        /// The encoded authorisation request string
+        req: String,
+    },
+    #[structopt(name = "balance")]
+    /// Show the balance of the account's default SafeKey
+    Balance,
+    #[structopt(name = "account")]
+    /// Manage the SAFE Network account
+    Account {
+        #[structopt(subcommand)]
+        cmd: AccountSubCommands,
+    },
+    #[structopt(name = "apps")]
+    /// Manage the snapshots of the authorised applications
+    Apps {
+        /// List the authorised applications in a format to be loaded by other tools, e.g.
+        /// 'csv' for spreadsheets (same as `--output <format> apps list`)
+        #[structopt(long = "export")]
+        export: Option<String>,
+        #[structopt(subcommand)]
+        cmd: Option<AppsSubCommands>,
+    },
+    #[structopt(name = "audit")]
+    /// Show and verify the receipts of the decisions on the auth requests
+    Audit {
+        #[structopt(subcommand)]
+        cmd: AuditSubCommands,
+    },
+    #[structopt(name = "revoke")]
+    /// Revoke all authorised permissions from applications
+    Revoke {
+        /// The IDs of the applications to revoke the permissions from, otherwise they are picked
+        /// from the list of the authorised ones the same as with --interactive, when run on the
+        /// console
+        app_ids: Vec<String>,
+        /// Pick the applications from the list of the authorised ones, finding them by
+        /// typing some letters of their ID, name or vendor
+        #[structopt(short = "i", long = "interactive", conflicts_with = "app_ids")]
+        interactive: bool,
+        /// Read the IDs of the applications from a file, one per line or as a JSON array
+        #[structopt(
+            long = "from-file",
+            parse(from_os_str),
+            raw(conflicts_with_all = r#"&["app_ids", "interactive"]"#)
+        )]
+        from_file: Option<PathBuf>,
+    },
+    #[structopt(name = "networks")]
+    /// Manage the stored networks definitions
+    Networks {
+        #[structopt(subcommand)]
+        cmd: NetworksSubCommands,
+    },
+    #[structopt(name = "policy")]
+    /// Manage the policy of the auth requests pulled from a signed bundle, e.g. maintained by
+    /// an organisation
+    Policy {
+        #[structopt(subcommand)]
+        cmd: PolicySubCommands,
+    },
+    #[structopt(name = "migrate")]
+    /// Import what a legacy authenticator, e.g. the SAFE Browser's, kept locally: the labels
+    /// of the apps, the times they were granted and the apps denied
+    Migrate {
+        /// The JSON file the legacy authenticator's state was exported to
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    #[structopt(name = "update")]
+    /// Update the application to the newest release, once its checksum is verified
+    Update {
+        /// Only check if a newer release is available, without installing it
+        #[structopt(long = "check-only")]
+        check_only: bool,
+    },
+    #[structopt(name = "decide")]
+    /// Decide on an auth request the Authenticator service of a profile prompts for, with the
+    /// `safe-auth:decide/...` URI opened by the Allow or Deny button of its toast notification
+    Decide {
+        /// The URI of the button
+        uri: String,
+    },
+    #[structopt(name = "doctor")]
+    /// Check the local environment, e.g. the config file or the network's reachability,
+    /// and tell how to fix the problems found
+    Doctor {
+        /// The port of the Authenticator service to check, otherwise the one given with
+        /// --daemon, or 41805
+        #[structopt(long = "port")]
+        port: Option<u16>,
+    },
+    #[structopt(name = "status")]
+    /// Show an overview: the network used, where the credentials are read from, if the
+    /// Authenticator service is running, the last login and the balance. The user is never
+    /// prompted, the balance is only shown if the credentials are read from elsewhere.
+    Status {
+        /// The port of the Authenticator service to look for, otherwise the one given with
+        /// --daemon, or 41805
+        #[structopt(long = "port")]
+        port: Option<u16>,
+        /// Don't log in to get the balance
+        #[structopt(long = "offline")]
+        offline: bool,
+    },
+    #[structopt(name = "shell")]
+    /// Log in once, then run the commands typed at an interactive prompt: apps, balance,
+    /// auth <req>, revoke <app id>..., help and exit
+    Shell,
+    #[structopt(name = "listen")]
+    /// Log in, then listen on localhost for the auth requests handed off by the apps, POSTed
+    /// to /authorise or sent as GET /authorise/<req>, responding with the auth response once
+    /// decided on
+    Listen {
+        /// The port to listen on, otherwise a free one is picked
+        #[structopt(long = "port")]
+        port: Option<u16>,
+        /// Exit once the first auth request was decided on
+        #[structopt(long = "once")]
+        once: bool,
+    },
+    #[structopt(name = "tui")]
+    /// Log in once, then show a full-screen dashboard of the authorised apps, the balance and
+    /// the auth requests received by the Authenticator service, which runs in the background
+    Tui {
+        /// The port of the service, otherwise a free one is picked
+        #[structopt(long = "port")]
+        port: Option<u16>,
+    },
+    #[structopt(name = "daemon")]
+    /// Run the Authenticator service of the profile, same as --daemon
+    Daemon {
+        /// The port to listen on, otherwise a free one is picked, which the other invocations
+        /// of the profile find
+        #[structopt(long = "port")]
+        port: Option<u16>,
+    },
+    #[structopt(name = "man")]
+    /// Generate the man pages of the safe_auth and of each of its subcommands
+    Man {
+        /// The directory to write the man pages to
+        #[structopt(parse(from_os_str))]
+        dir: PathBuf,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum AppsSubCommands {
+    #[structopt(name = "list")]
+    /// List the authorised applications and their permissions (same as --apps)
+    List {
+        /// Only list the apps with a permission on any container, e.g. 'Delete' for the apps
+        /// which can delete data: 'Read', 'Insert', 'Update', 'Delete' or 'ManagePermissions'
+        #[structopt(long = "with-permission", parse(try_from_str = "parse_permission"))]
+        with_permission: Option<Permission>,
+        /// Only list the apps with any permission on a container, e.g. '_documents', or on
+        /// the containers matching a pattern where '*' matches any characters
+        #[structopt(long = "container")]
+        container: Option<String>,
+    },
+    #[structopt(name = "diff")]
+    /// Report the applications added, removed, or whose permissions changed, between a
+    /// snapshot and another one or the apps currently authorised
+    Diff {
+        /// The snapshot to compare from
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+        /// The snapshot to compare to, or 'live' (the default) for the apps currently
+        /// authorised, which requires logging in
+        new: Option<String>,
+    },
+    #[structopt(name = "export")]
+    /// Write a snapshot of the authorised applications and their permissions to a file
+    Export {
+        /// The file to write the snapshot to
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    #[structopt(name = "restore")]
+    /// Grant again the permissions of a snapshot which the applications don't have anymore,
+    /// e.g. after the account is recovered, once confirmed
+    Restore {
+        /// The file to read the snapshot from
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    #[structopt(name = "edit")]
+    /// Uncheck the permissions of an application to remove, which is then revoked and
+    /// authorised again with exactly the ones left, once confirmed
+    Edit {
+        /// The ID of the application to edit the permissions of
+        app_id: String,
+    },
+    #[structopt(name = "label")]
+    /// Attach a note to an application, e.g. the device it runs on, shown in the list of the
+    /// authorised applications and when it sends a request, only stored on this computer
+    Label {
+        /// The ID of the application to label
+        app_id: String,
+        /// The note, which replaces the one it had
+        #[structopt(raw(required_unless = r#""remove""#))]
+        label: Option<String>,
+        /// Remove the application's label
+        #[structopt(long = "remove", conflicts_with = "label")]
+        remove: bool,
+    },
+}
+
+impl AppsSubCommands {
+    // The operation of the command, as reported when interrupted
+    fn op(&self) -> &'static str {
+        match self {
+            AppsSubCommands::List { .. } => "op-authed-apps",
+            AppsSubCommands::Diff { .. } => "op-apps-diff",
+            AppsSubCommands::Export { .. } => "op-apps-export",
+            AppsSubCommands::Restore { .. } => "op-apps-restore",
+            AppsSubCommands::Edit { .. } => "op-apps-edit",
+            AppsSubCommands::Label { .. } => "op-apps-label",
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+enum AuditSubCommands {
+    #[structopt(name = "verify-receipt")]
+    /// Verify that a receipt of a decision was signed by the key of the profile
+    VerifyReceipt {
+        /// The file with the receipt, or '-' to read it from stdin
+        receipt: String,
+        /// The auth request the receipt must be the one of
+        #[structopt(long = "req")]
+        req: Option<String>,
+    },
+    #[structopt(name = "show")]
+    /// Show the latest receipt of an auth request
+    Show {
+        /// The hash of the auth request, or its first characters
+        id: String,
+        /// Decrypt the auth request archived with the receipt and decode it
+        #[structopt(long = "raw")]
+        raw: bool,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum PolicySubCommands {
+    #[structopt(name = "pull")]
+    /// Fetch a policy bundle, verify its signature with the public key of the config file's
+    /// `policy_source`, and store it to be merged with the config file's `prompts`
+    Pull {
+        /// The URL of the bundle, otherwise the `url` of the config file's `policy_source`
+        url: Option<String>,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum NetworksSubCommands {
+    #[structopt(name = "add")]
+    /// Store a network definition from a crust config file
+    Add {
+        /// The name to give to the network
+        name: String,
+        /// The crust config file with the network's details
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    #[structopt(name = "list")]
+    /// List the names of the stored networks
+    List,
+    #[structopt(name = "show")]
+    /// Show the details of a stored network
+    Show {
+        /// The name of the network
+        name: String,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum AccountSubCommands {
+    #[structopt(name = "backup")]
+    /// Write an encrypted backup of the authorised apps and their permissions to a file
+    Backup {
+        /// The file to write the backup to
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    #[structopt(name = "restore")]
+    /// Authorise again the applications of a backup which are not authorised anymore, or
+    /// grant them the permissions they are missing, and report the discrepancies found
+    Restore {
+        /// The file to read the backup from
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+    #[structopt(name = "keys")]
+    /// Show the public keys associated with the logged in client
+    Keys,
+    #[structopt(name = "summary")]
+    /// Show an overview of the account: network, balance, authorised apps and containers
+    Summary,
+    #[structopt(name = "activity")]
+    /// Show the timeline of the decisions on the auth requests and of the apps revoked, from
+    /// the local audit log and the service's events, merged with the apps authorised
+    Activity,
+    #[structopt(name = "rotate-keys")]
+    /// Generate and register new client keys (not supported by the current SAFE Network backend)
+    RotateKeys,
+}
+
+impl AccountSubCommands {
+    // The operation of the command, as reported when interrupted
+    fn op(&self) -> &'static str {
+        match self {
+            AccountSubCommands::Backup { .. } => "op-account-backup",
+            AccountSubCommands::Restore { .. } => "op-account-restore",
+            AccountSubCommands::Keys => "op-account-keys",
+            AccountSubCommands::Summary => "op-account-summary",
+            AccountSubCommands::Activity => "op-account-activity",
+            AccountSubCommands::RotateKeys => "op-account-rotate-keys",
+        }
+    }
+}
+
+/// Outcome of an execution of the CLI
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliResult {
+    /// The exit code the `safe_auth` exits with, e.g. `3` for invalid credentials
+    pub exit_code: i32,
+    /// The error the execution failed with, if any
+    pub error: Option<Error>,
+}
+
+impl CliResult {
+    pub fn is_success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// # Run the CLI in-process
+///
+/// Runs the `safe_auth` CLI with the arguments provided, the first of them being the
+/// program's name as with `std::env::args`. Its input is read from, and its output written to,
+/// the streams provided, e.g. a `CapturedIo` to capture the output from a test, rather than
+/// spawning a `safe_auth` process and scraping its output.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{run_with_args, CapturedIo};
+/// let io = CapturedIo::new("");
+/// let output = io.output();
+/// let result = run_with_args(&["safe_auth", "--help"], io);
+/// assert!(result.is_success());
+/// assert!(output.stdout().contains("USAGE"));
+///
+/// let result = run_with_args(&["safe_auth", "--output", "xml"], CapturedIo::new(""));
+/// assert_eq!(result.exit_code, 2);
+/// ```
+pub fn run_with_args<I: CliIo + 'static>(args: &[&str], io: I) -> CliResult {
+    let console = Console::new(Box::new(io));
+    // The arguments may not be parsed, but the error is reported as strict anyway
+    let strict = args.contains(&"--strict");
+    let args = match CmdArgs::from_iter_safe(args) {
+        Ok(args) => args,
+        // The help and the version are reported as errors by the arguments parser
+        Err(err) => match err.kind {
+            ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => {
+                outln!(console, "{}", err.message);
+                return CliResult {
+                    exit_code: 0,
+                    error: None,
+                };
+            }
+            _ => {
+                let message = err.message;
+                if strict {
+                    report_strict_error(&console, &Error::InvalidInput(message.clone()));
+                } else {
+                    errln!(console, "{}", message);
+                }
+                return CliResult {
+                    exit_code: EXIT_CODE_INVALID_ARGS,
+                    error: Some(Error::InvalidInput(message)),
+                };
+            }
+        },
+    };
+
+    let strict = args.strict;
+    let result = run(args, &console);
+    // The operation interrupted may have completed anyway, e.g. a prompt answered once
+    // interrupted, but the run is still reported as interrupted
+    let result = match result {
+        Err(err @ Error::Cancelled(_)) => Err(err),
+        _ if take_interrupted() => Err(Error::Cancelled(String::from(
+            "The execution was interrupted",
+        ))),
+        result => result,
+    };
+    match result {
+        Ok(()) => CliResult {
+            exit_code: 0,
+            error: None,
+        },
+        Err(err) => {
+            debug!("safe_auth error: {:?}", err);
+            if strict {
+                report_strict_error(&console, &err);
+            } else {
+                errln!(
+                    console,
+                    "{}",
+                    tr!(console, error_message_id(&err), description = err)
+                );
+            }
+            CliResult {
+                exit_code: exit_code(&err),
+                error: Some(err),
+            }
+        }
+    }
+}
+
+// The error of a strict invocation, as the JSON object of its `kind` and `description`, the
+// same as in the responses of the Authenticator service
+fn report_strict_error(console: &Console, err: &Error) {
+    errln!(
+        console,
+        "{}",
+        serde_json::to_string(err).unwrap_or_else(|_| err.to_string())
+    );
+}
+
+// The legacy flags replaced by the commands, e.g. `--req <req>` by `auth <req>`, are still
+// accepted for the scripts using them, but deprecated: each of them is mapped to the command
+// replacing it, and they are run in the order they used to be, before the command given and
+// the service of `--daemon` after it. A command can't be given with the flags it replaces, as
+// which of them is used would be ambiguous.
+fn legacy_commands(
+    args: &CmdArgs,
+    console: &Console,
+) -> Result<(Vec<SubCommands>, Vec<SubCommands>), Error> {
+    let conflict = |flags: &str, cmd: &str| {
+        Err(Error::InvalidInput(format!(
+            "{} can't be given with the {} command, give its options instead",
+            flags, cmd
+        )))
+    };
+    // On stderr, so they are seen without the logs but don't mix with the commands' output
+    let deprecated = |flag: &str, cmd: &str| {
+        errln!(
+            console,
+            "{}",
+            tr!(console, "warn-deprecated-flag", flag = flag, cmd = cmd)
+        );
+    };
+    let (mut before, mut after) = (vec![], vec![]);
+    if args.update_app {
+        deprecated("--update", "update");
+        before.push(SubCommands::Update { check_only: false });
+    }
+    if args.sk.is_some() || args.test_coins {
+        if let Some(SubCommands::Create { .. }) = &args.cmd {
+            return conflict("--sk and --test-coins", "create");
+        }
+        deprecated("--sk and --test-coins", "create");
+        before.push(SubCommands::Create {
+            sk: args.sk.clone(),
+            test_coins: args.test_coins,
+        });
+    }
+    if let Some(req) = &args.req_str {
+        if let Some(SubCommands::Auth { .. }) = &args.cmd {
+            return conflict("--req", "auth");
+        }
+        deprecated("--req", "auth");
+        before.push(SubCommands::Auth { req: req.clone() });
+    }
+    if let Some(app_id) = &args.app_id {
+        deprecated("--revoke", "revoke");
+        before.push(SubCommands::Revoke {
+            app_ids: vec![app_id.clone()],
+            interactive: false,
+            from_file: None,
+        });
+    }
+    if args.list_apps {
+        if let Some(SubCommands::Apps {
+            cmd: None | Some(AppsSubCommands::List { .. }),
+            ..
+        }) = &args.cmd
+        {
+            return conflict("--apps", "apps list");
+        }
+        deprecated("--apps", "apps list");
+        before.push(SubCommands::Apps {
+            export: None,
+            cmd: Some(AppsSubCommands::List {
+                with_permission: args.with_permission,
+                container: args.container.clone(),
+            }),
+        });
+    }
+    if let Some(port) = args.port {
+        if let Some(SubCommands::Daemon { .. }) = &args.cmd {
+            return Err(Error::InvalidInput(String::from(
+                "--daemon can't be given with the daemon command, give its --port instead",
+            )));
+        }
+        deprecated("--daemon", "daemon");
+        after.push(SubCommands::Daemon { port: Some(port) });
+    }
+    Ok((before, after))
+}
+
+// The commands which take over the console or their own port until they are exited can't be run
+// along with the headless authenticator or the service of --daemon
+fn check_exclusive_commands(args: &CmdArgs) -> Result<(), Error> {
+    if !args.headless && args.port.is_none() {
+        return Ok(());
+    }
+    let conflict = match &args.cmd {
+        Some(SubCommands::Shell) => {
+            "The shell command can't be given with --headless or --daemon, the commands are typed at its prompt"
+        }
+        Some(SubCommands::Listen { .. }) => {
+            "The listen command can't be given with --headless or --daemon, give its --port instead"
+        }
+        Some(SubCommands::Tui { .. }) => {
+            "The tui command can't be given with --headless or --daemon, give its --port instead"
+        }
+        _ => return Ok(()),
+    };
+    Err(Error::InvalidInput(String::from(conflict)))
+}
+
+// The commands to run, the ones of the legacy flags included, once checked against the other
+// arguments. Exporting the apps is listing them with its output.
+fn commands(args: &mut CmdArgs, console: &Console) -> Result<Vec<SubCommands>, Error> {
+    check_exclusive_commands(args)?;
+    let (mut commands, after) = legacy_commands(args, console)?;
+    let cmd = args.cmd.take();
+    match &cmd {
+        Some(SubCommands::Create { .. }) if args.mock => {
+            return Err(Error::InvalidInput(String::from(
+                "The create command can't be given with --mock, the mock account is created in memory",
+            )));
+        }
+        Some(SubCommands::Apps {
+            export: None,
+            cmd: None,
+        }) => {
+            return Err(Error::InvalidInput(String::from(
+                "The apps command needs a subcommand, e.g. `apps list`, or --export",
+            )));
+        }
+        Some(SubCommands::Apps {
+            export: Some(format),
+            cmd: None | Some(AppsSubCommands::List { .. }),
+        }) => {
+            if format != "csv" {
+                return Err(Error::InvalidInput(format!(
+                    "Export format '{}' not supported, only 'csv' is",
+                    format
+                )));
+            }
+            if args.output_fmt.is_some() || args.output_json || args.format.is_some() {
+                return Err(Error::InvalidInput(String::from(
+                    "--output, --json and --format can't be given with the apps --export command, give its options instead",
+                )));
+            }
+            args.output_fmt = Some(format.clone());
+        }
+        Some(SubCommands::Apps {
+            export: Some(_), ..
+        }) => {
+            return Err(Error::InvalidInput(String::from(
+                "--export can only be given with the apps list command",
+            )));
+        }
+        Some(SubCommands::Tui { .. }) if cfg!(not(feature = "dashboard")) => {
+            return Err(Error::InvalidInput(String::from(
+                "The dashboard is not available, safe_auth was built without the 'dashboard' feature",
+            )));
+        }
+        _ => {}
+    }
+    commands.extend(cmd);
+    commands.extend(after);
+    Ok(commands)
+}
+
+fn run(mut args: CmdArgs, console: &Console) -> Result<(), Error> {
+    set_verbosity(args.verbose);
+    if let Some(lang) = &args.lang {
+        // The messages are still understood in English, which isn't worth failing for
+        let localiser = Localiser::for_lang(lang).unwrap_or_else(|| {
+            errln!(
+                console,
+                "{}",
+                tr!(
+                    console,
+                    "warn-lang-unavailable",
+                    lang = lang,
+                    locales = available_locales().join(", ")
+                )
+            );
+            Localiser::default()
+        });
+        debug!("Using the '{}' translation", localiser.locale());
+        console.set_localiser(localiser);
+    }
+    let commands = commands(&mut args, console)?;
+
+    console.set_table_layout(TableLayout {
+        style: args.table_style,
+        overflow: args.table_overflow,
+    });
+    console.set_color(args.color.unwrap_or_default());
+    // The tables are meant for humans, they are not part of the output contract
+    if args.strict || args.quiet {
+        if args.output_fmt.as_ref().is_some_and(|fmt| fmt == "table") {
+            return Err(Error::InvalidInput(format!(
+                "{} can't be given with the 'table' output, only with a parsable one",
+                if args.strict { "--strict" } else { "--quiet" }
+            )));
+        }
+        console.set_strict(true);
+    }
+    console.set_non_interactive(args.non_interactive);
+
+    if args.capabilities {
+        console.out_result(format_args!(
+            "{}\n",
+            serde_json::to_string(&capabilities())
+                .unwrap_or_else(|_| "Failed to serialise output to json".to_string())
+        ));
+        return Ok(());
+    }
+
+    let output = output_formatter(&args)?;
+    let mut run = Run::new(&args, console, &*output, &commands)?;
+    for cmd in commands.iter() {
+        run.command(cmd)?;
+    }
+    run.finish()
+}
+
+// The formatter of the output given with the arguments
+fn output_formatter(args: &CmdArgs) -> Result<Box<dyn OutputFormatter>, Error> {
+    // In headless mode the stdout is only used for the responses to the commands
+    let output: Box<dyn OutputFormatter> = if args.output_json || args.headless {
+        formatter("json")?
+    } else if let Some(template) = &args.format {
+        Box::new(TemplateFormatter::new(template)?)
+    } else if args.print0 {
+        match args.output_fmt.as_ref().map_or("plain", String::as_str) {
+            "plain" | "tsv" => Box::new(PlainFormatter::print0()),
+            other => {
+                return Err(Error::InvalidInput(format!(
+                    "--print0 can only be used with the 'plain' output, not with '{}'",
+                    other
+                )))
+            }
+        }
+    } else if args.strict {
+        formatter(args.output_fmt.as_ref().map_or("json", String::as_str))?
+    } else if args.quiet && args.output_fmt.is_none() {
+        Box::new(QuietFormatter)
+    } else {
+        formatter(args.output_fmt.as_ref().map_or("", String::as_str))?
+    };
+    Ok(if args.strict || args.quiet {
+        Box::new(StrictFormatter::new(output))
+    } else {
+        output
+    })
+}
+
+// The settings of the config file, and the handling of the interruptions, set up once the
+// first command needing them is run
+struct Prepared {
+    settings: ConfigSettings,
+    verification: Option<UserVerification>,
+    progress: ProgressTracker,
+    cancel: CancellationToken,
+}
+
+// The account operated on by the commands, logged in to, or through the service of the profile
+struct Session {
+    backend: Box<dyn AuthBackend>,
+    prompt: &'static AuthAllowPrompt,
+    // The requests of the dashboard's service, which are decided on in the dashboard
+    dashboard_requests: Option<Receiver<PendingRequest>>,
+    guardian: Option<Arc<Guardian>>,
+    grants: TemporaryGrants,
+    events: Option<EventBus>,
+    instance_lock: Option<InstanceLock>,
+    service_port: Option<u16>,
+    profile: String,
+    // The auth requests its service's prompt waits on
+    decisions: PendingDecisions,
+}
+
+// What the commands of a run share. The commands which don't require to be logged in are run
+// without reading the config file, and the session is opened once for all the other ones.
+struct Run<'a> {
+    args: &'a CmdArgs,
+    console: &'a Console,
+    output: &'a dyn OutputFormatter,
+    commands: &'a [SubCommands],
+    // The port of the service run by the daemon command and by the dashboard, if any, a free
+    // one being picked for 0
+    service_port: Option<u16>,
+    // The options of the create command, if the account is created
+    creation: Option<(Option<String>, bool)>,
+    standalone: bool,
+    dashboard: bool,
+    prepared: Option<Prepared>,
+    session: Option<Session>,
+    // Whether a command opened the session, or consumed it
+    connected: bool,
+}
+
+impl<'a> Run<'a> {
+    fn new(
+        args: &'a CmdArgs,
+        console: &'a Console,
+        output: &'a dyn OutputFormatter,
+        commands: &'a [SubCommands],
+    ) -> Result<Self, Error> {
+        let mut run = Self {
+            args,
+            console,
+            output,
+            commands,
+            service_port: None,
+            creation: None,
+            standalone: args.standalone,
+            dashboard: false,
+            prepared: None,
+            session: None,
+            connected: false,
+        };
+        for cmd in commands.iter() {
+            match cmd {
+                SubCommands::Create { sk, test_coins } => {
+                    run.creation = Some((sk.clone(), *test_coins))
+                }
+                SubCommands::Login => run.standalone = true,
+                SubCommands::Daemon { port } => run.service_port = Some(port.unwrap_or(0)),
+                SubCommands::Tui { port } => {
+                    run.dashboard = true;
+                    // The dashboard runs without the service if it's not available
+                    if cfg!(feature = "daemon") {
+                        run.service_port = Some(port.unwrap_or(0));
+                    }
+                    if !console.is_console() {
+                        return Err(Error::InvalidInput(String::from(
+                            "The dashboard can only be shown on a terminal",
+                        )));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if cfg!(not(feature = "daemon")) && run.service_port.is_some() {
+            return Err(Error::InvalidInput(String::from(
+                "The Authenticator service is not available, safe_auth was built without the 'daemon' feature",
+            )));
+        }
+        Ok(run)
+    }
+
+    fn command(&mut self, cmd: &SubCommands) -> Result<(), Error> {
+        let (args, console, output) = (self.args, self.console, self.output);
+        match cmd {
+            // Networks, doctor, audit, update, man and migrate commands, labels, and diffs
+            // between two snapshots, don't require to be logged in
+            SubCommands::Update { check_only } => update_application(console, *check_only),
+            SubCommands::Man { dir } => write_man_pages(console, dir, &CmdArgs::clap),
+            SubCommands::Apps {
+                cmd:
+                    Some(AppsSubCommands::Label {
+                        app_id,
+                        label,
+                        remove,
+                    }),
+                ..
+            } => {
+                let label = if *remove { None } else { label.as_deref() };
+                run_label(args, console, output, app_id, label)
+            }
+            SubCommands::Migrate { file } => run_migrate(args, console, output, file),
+            SubCommands::Apps {
+                cmd:
+                    Some(AppsSubCommands::Diff {
+                        old,
+                        new: Some(new),
+                    }),
+                ..
+            } if new != LIVE_SNAPSHOT => {
+                let old = read_snapshot(old)?;
+                let new = read_snapshot(Path::new(new))?;
+                report_apps_diff(console, output, &diff_authed_apps(&old, &new))
+            }
+            SubCommands::Networks { cmd } => run_networks_subcommand(console, cmd, output),
+            SubCommands::Audit { cmd } => run_audit_subcommand(args, console, output, cmd),
+            SubCommands::Doctor { port } => run_doctor(args, console, output, *port),
+            SubCommands::Decide { uri } => run_decide(console, output, uri),
+            // The policy and status ones read the config file
+            SubCommands::Policy { cmd } => {
+                let prepared = self.prepare()?;
+                run_policy_subcommand(args, console, output, &prepared.settings, cmd)
+            }
+            SubCommands::Status { port, offline } => {
+                let prepared = self.prepare()?;
+                let port = port.or(args.port).unwrap_or(DEFAULT_DAEMON_PORT);
+                let status = account_status(
+                    args,
+                    console,
+                    port,
+                    *offline,
+                    &prepared.progress,
+                    &prepared.cancel,
+                )?;
+                output.output(console, Output::Status(&status));
+                Ok(())
+            }
+            // The account is created, or logged in to, when the session is opened
+            SubCommands::Create { .. } | SubCommands::Login => self.session().map(|_| ()),
+            SubCommands::Auth { req } => {
+                let progress = self.prepare()?.progress.clone();
+                let session = self.session()?;
+                run_auth(session, console, output, &progress, req)
+            }
+            SubCommands::Apps {
+                cmd:
+                    Some(AppsSubCommands::List {
+                        with_permission,
+                        container,
+                    }),
+                ..
+            } => {
+                let progress = self.prepare()?.progress.clone();
+                let session = self.session()?;
+                list_apps(
+                    &*session.backend,
+                    console,
+                    output,
+                    &progress,
+                    *with_permission,
+                    container.as_deref(),
+                )
+            }
+            SubCommands::Apps { cmd: None, .. } => {
+                let progress = self.prepare()?.progress.clone();
+                let session = self.session()?;
+                list_apps(&*session.backend, console, output, &progress, None, None)
+            }
+            SubCommands::Apps { cmd: Some(cmd), .. } => {
+                let (progress, verification) = self.progress_and_verification()?;
+                let session = self.session()?;
+                progress.start(&tr!(console, cmd.op()));
+                let result =
+                    run_apps_subcommand(&*session.backend, console, output, cmd, verification);
+                progress.done();
+                result
+            }
+            SubCommands::Account { cmd } => {
+                let progress = self.prepare()?.progress.clone();
+                let session = self.session()?;
+                progress.start(&tr!(console, cmd.op()));
+                let result = run_account_subcommand(&*session.backend, console, args, output, cmd);
+                progress.done();
+                result
+            }
+            SubCommands::Balance => {
+                let progress = self.prepare()?.progress.clone();
+                let session = self.session()?;
+                progress.start(&tr!(console, "op-balance"));
+                let info = session.backend.acc_info();
+                progress.done();
+                output.output(console, Output::Balance(&info?.balance));
+                Ok(())
+            }
+            SubCommands::Revoke {
+                app_ids,
+                interactive,
+                from_file,
+            } => {
+                let (progress, verification) = self.progress_and_verification()?;
+                let session = self.session()?;
+                progress.start(&tr!(console, "op-revoke"));
+                let result = run_revoke(
+                    session,
+                    console,
+                    output,
+                    app_ids,
+                    *interactive,
+                    from_file.as_deref(),
+                    verification,
+                );
+                progress.done();
+                result
+            }
+            // The shell, the listener and the dashboard run until they are exited, and the
+            // service of the daemon command until it's stopped
+            SubCommands::Shell => {
+                let (progress, verification) = self.progress_and_verification()?;
+                let session = self.take_session()?;
+                let history_len = self.prepare()?.settings.shell_history;
+                let mut history = History::for_profile(&session.profile, history_len)?;
+                progress.start(&tr!(console, "op-shell"));
+                run_shell(
+                    &*session.backend,
+                    console,
+                    output,
+                    session.prompt,
+                    verification,
+                    session.guardian.as_deref(),
+                    &mut history,
+                )
+            }
+            SubCommands::Listen { port, once } => {
+                let progress = self.prepare()?.progress.clone();
+                let session = self.take_session()?;
+                progress.start(&tr!(console, "op-listen"));
+                let port = port.unwrap_or(0);
+                run_listener(
+                    &*session.backend,
+                    console,
+                    output,
+                    session.prompt,
+                    port,
+                    *once,
+                )
+            }
+            SubCommands::Tui { .. } => {
+                let (progress, verification) = self.progress_and_verification()?;
+                let session = self.take_session()?;
+                progress.start(&tr!(console, "op-dashboard"));
+                run_tui(session, console, verification)
+            }
+            SubCommands::Daemon { .. } => {
+                // The headless commands are read from the input instead, as they used to be
+                if self.args.headless {
+                    return Ok(());
+                }
+                let progress = self.prepare()?.progress.clone();
+                let session = self.take_session()?;
+                let prepared = self.prepare()?;
+                progress.start(&tr!(console, "op-service"));
+                run_daemon(session, console, &mut prepared.settings)
+            }
+        }
+    }
+
+    // Once the commands were run the headless commands are read from the input. Without any
+    // command the account is only logged in to.
+    fn finish(&mut self) -> Result<(), Error> {
+        if self.args.headless {
+            let progress = self.prepare()?.progress.clone();
+            let session = self.take_session()?;
+            progress.start(&tr!(self.console, "op-headless"));
+            let headless = Headless::new(session.backend);
+            return run_stdio(
+                &headless,
+                BufReader::new(self.console.reader()),
+                self.console.writer(),
+            );
+        }
+        if self.commands.is_empty() {
+            let _ = self.session()?;
+        }
+        Ok(())
+    }
+
+    fn prepare(&mut self) -> Result<&mut Prepared, Error> {
+        if self.prepared.is_none() {
+            self.prepared = Some(prepare(self.args, self.console, self.commands)?);
+        }
+        Ok(unwrap!(self.prepared.as_mut()))
+    }
+
+    fn progress_and_verification(
+        &mut self,
+    ) -> Result<(ProgressTracker, Option<UserVerification>), Error> {
+        let prepared = self.prepare()?;
+        Ok((prepared.progress.clone(), prepared.verification))
+    }
+
+    fn session(&mut self) -> Result<&Session, Error> {
+        if self.session.is_none() {
+            if self.connected {
+                return Err(Error::InvalidInput(String::from(
+                    "No command can be run after the shell, listen, tui and daemon commands",
+                )));
+            }
+            let (args, console, output) = (self.args, self.console, self.output);
+            let (service_port, creation, standalone, dashboard) = (
+                self.service_port,
+                self.creation.clone(),
+                self.standalone,
+                self.dashboard,
+            );
+            let prepared = self.prepare()?;
+            let session = open_session(
+                args,
+                console,
+                output,
+                prepared,
+                SessionOptions {
+                    service_port,
+                    creation,
+                    standalone,
+                    dashboard,
+                },
+            )?;
+            self.session = Some(session);
+            self.connected = true;
+        }
+        Ok(unwrap!(self.session.as_ref()))
+    }
+
+    fn take_session(&mut self) -> Result<Session, Error> {
+        let _ = self.session()?;
+        Ok(unwrap!(self.session.take()))
+    }
+}
+
+// Reads the config file, applying its settings, and handles the interruptions of the run
+fn prepare(args: &CmdArgs, console: &Console, commands: &[SubCommands]) -> Result<Prepared, Error> {
+    let mut settings = get_settings(&args.config_file_str)?;
+    if args.non_interactive {
+        check_non_interactive(args, commands, settings.prompts.as_ref())?;
+    }
+    let verification = settings.verify_user;
+    if let Some(verification) = verification {
+        verification.ensure_available()?;
+    }
+    let timeouts = match args.timeout {
+        Some(0) => {
+            return Err(Error::InvalidInput(String::from(
+                "--timeout must be at least 1 second",
+            )))
+        }
+        Some(timeout) => OperationTimeouts::all(timeout),
+        None => settings.timeouts.take().unwrap_or_default(),
+    };
+    set_timeouts(&timeouts);
+    set_pacing(&settings.pacing.take().unwrap_or_default());
+    set_user_apps(&settings.known_apps.take().unwrap_or_default());
+    use_profile_labels(&profile_name(&args.profile, &args.network));
+    if let Some(version_check) = &settings.version_check {
+        check_new_version(console, version_check);
+    }
+
+    // From now on, if the execution is interrupted we report what was completed,
+    // and the operation in flight is cancelled
+    let progress = ProgressTracker::new(console, args.progress);
+    let cancel = CancellationToken::new();
+    if console.is_console() {
+        install_interrupt_handler(progress.clone(), cancel.clone(), console.localiser())?;
+    }
+    Ok(Prepared {
+        settings,
+        verification,
+        progress,
+        cancel,
+    })
+}
+
+// How the session is opened, depending on the commands of the run
+struct SessionOptions {
+    service_port: Option<u16>,
+    creation: Option<(Option<String>, bool)>,
+    standalone: bool,
+    dashboard: bool,
+}
+
+fn open_session(
+    args: &CmdArgs,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    prepared: &mut Prepared,
+    options: SessionOptions,
+) -> Result<Session, Error> {
+    let Prepared {
+        settings,
+        verification,
+        progress,
+        cancel,
+    } = prepared;
+    let verification = *verification;
+
+    // When replaying a recording no connection to the network is made,
+    // the outcome of the operations is read from the recording instead,
+    // and the mock account keeps the authorised apps in memory until the CLI exits
+    if args.mock {
+        warn!("Using a mock account, no connection to the network will be made");
+    }
+    // A single Authenticator service runs for each profile, by default one for each network.
+    // The other invocations operate on the account through it, rather than opening a second
+    // session with the network.
+    let uses_network = !args.mock && args.replay.is_none();
+    let profile = profile_name(&args.profile, &args.network);
+    let service_port = match options.service_port {
+        Some(0) => Some(free_port()?),
+        port => port,
+    };
+    let instance_lock = match service_port {
+        Some(port) if uses_network => Some(register_instance(&profile, port)?),
+        _ => None,
+    };
+    let creation = options
+        .creation
+        .as_ref()
+        .map(|(sk, test_coins)| (sk.as_deref(), *test_coins));
+    let instance =
+        if uses_network && service_port.is_none() && !options.standalone && creation.is_none() {
+            find_instance(&profile)
+        } else {
+            None
+        };
+    if let Some(instance) = &instance {
+        debug!(
+            "Using the Authenticator service running with pid {}",
+            instance.pid
+        );
+        output.message(
+            console,
+            &tr!(console, "service-in-use", port = instance.port),
+        );
+    }
+
+    let routed = instance.is_some();
+    let log_in = || connect(args, creation, console, output, progress, cancel);
+    let backend: Box<dyn AuthBackend> = match (&args.replay, &args.record, args.mock, instance) {
+        (Some(file), _, _, _) => Box::new(ReplayBackend::from_file(file)?),
+        (None, Some(file), true, _) => {
+            Box::new(RecordingBackend::new(FakeBackend::default(), file))
+        }
+        (None, None, true, _) => Box::new(FakeBackend::default()),
+        (None, Some(file), false, Some(instance)) => {
+            let client = ServiceClient::new(&instance)?.allowing_all(args.allow_all);
+            Box::new(RecordingBackend::new(client, file))
+        }
+        (None, None, false, Some(instance)) => {
+            Box::new(ServiceClient::new(&instance)?.allowing_all(args.allow_all))
+        }
+        (None, Some(file), false, None) => Box::new(RecordingBackend::new(
+            CancellableAuthenticator::new(log_in()?, cancel.clone()),
+            file,
+        )),
+        (None, None, false, None) => {
+            Box::new(CancellableAuthenticator::new(log_in()?, cancel.clone()))
+        }
+    };
+    // The decisions are signed with the profile's key, but the ones of the service, which
+    // signs them itself, or of mock or replayed accounts
+    let backend: Box<dyn AuthBackend> = if uses_network && !routed {
+        let archive = settings
+            .audit
+            .as_ref()
+            .is_some_and(|audit| audit.archive_requests);
+        Box::new(ReceiptsBackend::new(backend, &profile, archive)?)
+    } else {
+        backend
+    };
+    // The auth requests are checked for risky patterns before they are prompted for, but the
+    // ones sent to the service, which checks them itself
+    let warnings = RequestWarnings::new();
+    let events = match service_port {
+        Some(_) => Some(service_events(
+            &profile,
+            settings.event_history.take().unwrap_or_default(),
+        )?),
+        None => None,
+    };
+    let backend: Box<dyn AuthBackend> = if routed {
+        backend
+    } else {
+        Box::new(WarningBackend::new(
+            backend,
+            warnings.clone(),
+            events.clone(),
+        ))
+    };
+    // The guardian co-approves the high-risk operations on the account, the service asks it
+    // for the auth requests sent to it, and the revocations are approved before being sent
+    let guardian = match &settings.guardian {
+        Some(guardian) if uses_network => Some(Arc::new(Guardian::new(guardian, &profile)?)),
+        _ => None,
+    };
+    let backend: Box<dyn AuthBackend> = match &guardian {
+        Some(guardian) if !routed => Box::new(GuardianBackend::new(backend, guardian.clone())),
+        _ => backend,
+    };
+
+    if args.allow_all {
+        warn!("All authorisation requests will be automatically allowed!");
+    }
+    let policies = merged_policies(
+        settings.prompts.take().unwrap_or_default(),
+        settings.policy_source.as_ref(),
+        &profile,
+    )?;
+    let mut policies = with_imported_denylist(policies, &profile)?;
+    if let Some(timeout) = args.prompt_timeout {
+        if timeout == 0 {
+            return Err(Error::InvalidInput(String::from(
+                "--prompt-timeout must be at least 1 second",
+            )));
+        }
+        policies.set_timeout(timeout);
+    }
+    // The apps allowed temporarily are revoked once their grant expires, by the service if one
+    // runs for the profile
+    let grants = TemporaryGrants::for_profile(&profile, settings.temporary_grant_hours)?;
+    if uses_network && !routed && service_port.is_none() {
+        revoke_expired(&grants, &*backend, None, &console.downgrade());
+    }
+
+    // The requests of the dashboard's service are decided on in the dashboard
+    let decisions = PendingDecisions::default();
+    let (prompt, dashboard_requests) = if options.dashboard {
+        let (prompt, requests) = dashboard_prompt(console, policies, verification);
+        (prompt, Some(requests))
+    } else {
+        // The prompts of the service raise toasts, whose buttons decide through the service
+        let attention = Attention::new(settings.attention.take().unwrap_or_default());
+        let attention = match service_port {
+            Some(_) => attention.with_toasts(|| Toasts::new(&profile, decisions.clone())),
+            None => attention,
+        };
+        let prompt = allow_prompt(
+            console,
+            policies,
+            attention,
+            args.allow_all,
+            verification,
+            warnings,
+            grants.clone(),
+        );
+        (prompt, None)
+    };
+    let backend: Box<dyn AuthBackend> = Box::new(PromptingBackend::new(backend, prompt));
+
+    Ok(Session {
+        backend,
+        prompt: &session_prompt,
+        dashboard_requests,
+        guardian,
+        grants,
+        events,
+        instance_lock,
+        service_port,
+        profile,
+        decisions,
+    })
+}
+
+// Authorises the application of the auth request
+fn run_auth(
+    session: &Session,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    progress: &ProgressTracker,
+    req: &str,
+) -> Result<(), Error> {
+    progress.start(&tr!(console, "op-authorise"));
+    let auth_response = session.backend.authorise_app(req, session.prompt)?;
+    output.output(console, Output::AuthResponse(&auth_response));
+    progress.done();
+    Ok(())
+}
+
+// Lists the authorised apps, only the ones matching the filters given
+fn list_apps(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    progress: &ProgressTracker,
+    permission: Option<Permission>,
+    container: Option<&str>,
+) -> Result<(), Error> {
+    progress.start(&tr!(console, "op-authed-apps"));
+    let mut authed_apps = backend.authed_apps()?;
+    progress.done();
+    if let Some(permission) = permission {
+        authed_apps = with_permission(authed_apps, permission);
+    }
+    if let Some(container) = container {
+        authed_apps = in_container(authed_apps, container);
+    }
+    output.output(console, Output::AuthedApps(&authed_apps));
+    Ok(())
+}
+
+// Shows the dashboard, whose service runs in the background if it's available
+fn run_tui(
+    session: Session,
+    console: &Console,
+    verification: Option<UserVerification>,
+) -> Result<(), Error> {
+    let Session {
+        backend,
+        prompt,
+        dashboard_requests,
+        grants,
+        events,
+        instance_lock,
+        service_port,
+        decisions,
+        ..
+    } = session;
+    let shared = SharedBackend::from_boxed(backend);
+    if let (Some(host_port), Some(events)) = (service_port, events) {
+        let token = instance_lock
+            .as_ref()
+            .map(|lock| lock.instance.token.clone());
+        spawn_expiries(grants, shared.clone(), events.clone(), console.downgrade());
+        let backend: Box<dyn AuthBackend> = Box::new(shared.clone());
+        // The co-approvals of the guarded accounts are prompted for on the console, which the
+        // dashboard takes over, so the dashboard's service can't act as a guardian
+        let options = ServiceOptions {
+            backend: Some(backend),
+            token,
+            events,
+            decisions,
+            ..ServiceOptions::default()
+        };
+        let _ = thread::spawn(move || run_service(host_port, prompt, options));
+    }
+    let requests = unwrap!(dashboard_requests);
+    run_dashboard(console, shared, requests, verification)
+}
+
+// Runs the Authenticator service of the profile until it's stopped
+fn run_daemon(
+    session: Session,
+    console: &Console,
+    settings: &mut ConfigSettings,
+) -> Result<(), Error> {
+    let Session {
+        backend,
+        prompt,
+        grants,
+        events,
+        instance_lock,
+        service_port,
+        profile,
+        decisions,
+        ..
+    } = session;
+    let (host_port, events) = match (service_port, events) {
+        (Some(host_port), Some(events)) => (host_port, events),
+        _ => return Ok(()),
+    };
+    let token = instance_lock
+        .as_ref()
+        .map(|lock| lock.instance.token.clone());
+    // The reminders to review the permissions, and the revocations of the expired grants,
+    // operate on the service's session
+    let shared = SharedBackend::from_boxed(backend);
+    if let Some(review) = settings.permission_review.take() {
+        spawn_reminders(
+            review,
+            &profile,
+            shared.clone(),
+            events.clone(),
+            console.downgrade(),
+        )?;
+    }
+    spawn_expiries(grants, shared.clone(), events.clone(), console.downgrade());
+    let backend: Box<dyn AuthBackend> = Box::new(shared);
+    let guarding = settings.guarding.take().map(|guarding| Guarding {
+        token: guarding.token,
+        prompt: approval_prompt(console),
+    });
+    run_service(
+        host_port,
+        prompt,
+        ServiceOptions {
+            backend: Some(backend),
+            token,
+            events,
+            guarding,
+            decisions,
+        },
+    );
+    Ok(())
+}
+
+fn run_label(
+    args: &CmdArgs,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    app_id: &str,
+    label: Option<&str>,
+) -> Result<(), Error> {
+    let profile = profile_name(&args.profile, &args.network);
+    set_label(&profile, app_id, label)?;
+    let message = match label {
+        Some(label) => tr!(console, "label-set", app_id = app_id, label = label),
+        None => tr!(console, "label-removed", app_id = app_id),
+    };
+    output.message(console, &message);
+    Ok(())
+}
+
+fn run_migrate(
+    args: &CmdArgs,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    file: &Path,
+) -> Result<(), Error> {
+    let profile = profile_name(&args.profile, &args.network);
+    let migration = migrate_legacy_state(file, &profile, &|| {
+        unlock(&args.config_file_str, &profile, console).map(|_| ())
+    })?;
+    output.message(
+        console,
+        &tr!(
+            console,
+            "legacy-migrated",
+            file = file.display(),
+            labels = migration.labels,
+            grants = migration.grants,
+            denied = migration.denied
+        ),
+    );
+    Ok(())
+}
+
+fn run_audit_subcommand(
+    args: &CmdArgs,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    cmd: &AuditSubCommands,
+) -> Result<(), Error> {
+    let profile = profile_name(&args.profile, &args.network);
+    match cmd {
+        AuditSubCommands::VerifyReceipt { receipt, req } => {
+            verify_receipt_file(console, output, &profile, receipt, req)
+        }
+        AuditSubCommands::Show { id, raw } => {
+            show_receipt(console, output, &args.config_file_str, &profile, id, *raw)
+        }
+    }
+}
+
+// Sends the decision taken with a button of a toast to the service of its profile
+fn run_decide(console: &Console, output: &dyn OutputFormatter, uri: &str) -> Result<(), Error> {
+    let (profile, id, allowed) = parse_decision_uri(uri)?;
+    let instance = find_instance(&profile).ok_or_else(|| {
+        Error::InvalidInput(format!(
+            "No Authenticator service is running for the profile '{}'",
+            profile
+        ))
+    })?;
+    ServiceClient::new(&instance)?.decide(id, allowed)?;
+    output.message(console, &tr!(console, "decision-sent"));
+    Ok(())
+}
+
+fn run_doctor(
+    args: &CmdArgs,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    port: Option<u16>,
+) -> Result<(), Error> {
+    let results = run_checks(&DoctorOptions {
+        config_file: &args.config_file_str,
+        network: &args.network,
+        port: port.or(args.port).unwrap_or(DEFAULT_DAEMON_PORT),
+    });
+    output.output(console, Output::Doctor(&results));
+    let failed = results
+        .iter()
+        .filter(|result| result.status == CheckStatus::Failed)
+        .count();
+    if failed > 0 {
+        return Err(Error::AuthenticatorError(format!(
+            "{} of the doctor checks failed",
+            failed
+        )));
+    }
+    Ok(())
+}
+
+fn run_policy_subcommand(
+    args: &CmdArgs,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    settings: &ConfigSettings,
+    cmd: &PolicySubCommands,
+) -> Result<(), Error> {
+    match cmd {
+        PolicySubCommands::Pull { url } => {
+            let profile = profile_name(&args.profile, &args.network);
+            let url = url.as_ref().map(String::as_str);
+            let (url, policies) = pull_policy(settings.policy_source.as_ref(), url, &profile)?;
+            output.message(
+                console,
+                &tr!(
+                    console,
+                    "policy-pulled",
+                    url = url,
+                    apps = policies.denylist.len()
+                ),
+            );
+        }
+    }
+    Ok(())
+}
+
+// The events of the service, with the history of the last ones for the clients which missed
+// them, persisted for the profile if the config file asks for it
+fn service_events(profile: &str, settings: EventHistorySettings) -> Result<EventBus, Error> {
+    let capacity = settings.capacity.unwrap_or(DEFAULT_HISTORY_CAPACITY);
+    let history = if settings.persist {
+        EventHistory::stored(
+            capacity,
+            storage()?,
+            EVENTS_AREA,
+            &profile_entry(profile, "json"),
+        )?
+    } else {
+        EventHistory::new(capacity)
+    };
+    Ok(EventBus::with_history(history))
+}
+
+#[cfg(feature = "daemon")]
+fn run_service(port: u16, prompt: &'static AuthAllowPrompt, options: ServiceOptions) {
+    crate::authd::run_service(port, prompt, options);
+}
+
+// Not reached, the `--daemon` argument is rejected before connecting to the network
+#[cfg(not(feature = "daemon"))]
+fn run_service(_port: u16, _prompt: &'static AuthAllowPrompt, _options: ServiceOptions) {}
+
+// The prompt invoked for each auth request, owned by the session's backend. It only keeps a
+// weak reference to the streams so they are released once the CLI returns.
+// With `--allow-all-auth` there is no prompt, but the user is still verified if required.
+// The grants allowed temporarily are recorded once the user is verified.
+fn allow_prompt(
+    console: &Console,
+    policies: PromptPolicies,
+    attention: Attention,
+    allow_all: bool,
+    verification: Option<UserVerification>,
+    warnings: RequestWarnings,
+    grants: TemporaryGrants,
+) -> Arc<AuthAllowPrompt> {
+    if allow_all && verification.is_none() && !policies.restricts_any() {
+        return Arc::new(|_| true);
+    }
+    let rate_limits = RateLimits::default();
+    let console = console.downgrade();
+    Arc::new(move |req: IpcReq| match console.upgrade() {
+        Some(console) => {
+            if !rate_limits.admit(&req, &policies) {
+                errln!(
+                    console,
+                    "{}",
+                    tr!(console, "auth-rate-limited", kind = ReqKind::of(&req))
+                );
+                return false;
+            }
+            let reason = match req_app_id(&req) {
+                Some(app_id) => tr!(console, "verify-auth-reason", app_id = app_id),
+                None => tr!(console, "verify-unregistered-reason"),
+            };
+            let app_id = req_app_id(&req).map(String::from);
+            let req_warnings = app_id
+                .as_ref()
+                .map(|app_id| warnings.pending(app_id))
+                .unwrap_or_default();
+            // The kinds of requests the config file denies are denied even with --allow-all-auth
+            let answer = if allow_all && policies.denies(&req) {
+                AuthAnswer::Denied
+            } else if allow_all {
+                AuthAnswer::Allowed
+            } else if !console.is_interactive() {
+                // Only the kinds of requests the config file decides on are decided on
+                match policies.mode_for(&req) {
+                    _ if policies.denylisted(&req) => AuthAnswer::Denied,
+                    PromptMode::Allow => AuthAnswer::Allowed,
+                    PromptMode::Deny => AuthAnswer::Denied,
+                    PromptMode::Prompt | PromptMode::PerItem => {
+                        errln!(
+                            console,
+                            "{}",
+                            tr!(console, "auth-non-interactive", kind = ReqKind::of(&req))
+                        );
+                        AuthAnswer::Denied
+                    }
+                }
+            } else {
+                let description = match &app_id {
+                    Some(app_id) => tr!(
+                        console,
+                        "toast-auth-req",
+                        app_id = app_id,
+                        kind = ReqKind::of(&req)
+                    ),
+                    None => tr!(console, "toast-unregistered-req"),
+                };
+                let mut waiting = attention.waiting(&console, &description);
+                let hours = Some(grants.hours());
+                prompt_for_auth(
+                    &console,
+                    req,
+                    &policies,
+                    &req_warnings,
+                    hours,
+                    waiting.decision(),
+                )
+            };
+            let allowed = answer != AuthAnswer::Denied
+                && match verify_user(verification, &reason) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        errln!(console, "{}", err);
+                        false
+                    }
+                };
+            match (answer, app_id) {
+                // Not allowed at all if the grant can't be recorded to be revoked
+                (AuthAnswer::AllowedTemporarily, Some(app_id)) if allowed => {
+                    match grants.record(&app_id) {
+                        Ok(_) => true,
+                        Err(err) => {
+                            errln!(console, "{}", err);
+                            false
+                        }
+                    }
+                }
+                _ => allowed,
+            }
+        }
+        None => false,
+    })
+}
+
+// With --non-interactive, the commands which prompt the user are rejected, and so is the auth
+// request given which would be prompted for, before logging in
+fn check_non_interactive(
+    args: &CmdArgs,
+    commands: &[SubCommands],
+    policies: Option<&PromptPolicies>,
+) -> Result<(), Error> {
+    for cmd in commands.iter() {
+        let interactive_command = match cmd {
+            SubCommands::Shell => Some("shell"),
+            SubCommands::Tui { .. } => Some("tui"),
+            SubCommands::Revoke {
+                interactive: true, ..
+            } => Some("revoke --interactive"),
+            SubCommands::Revoke {
+                app_ids,
+                from_file: None,
+                ..
+            } if app_ids.is_empty() => Some("revoke"),
+            SubCommands::Apps {
+                cmd: Some(AppsSubCommands::Edit { .. }),
+                ..
+            } => Some("apps edit"),
+            SubCommands::Apps {
+                cmd: Some(AppsSubCommands::Restore { .. }),
+                ..
+            } => Some("apps restore"),
+            _ => None,
+        };
+        if let Some(cmd) = interactive_command {
+            return Err(Error::InvalidInput(format!(
+                "The {} command prompts the user, it can't be run with --non-interactive",
+                cmd
+            )));
+        }
+    }
+    if args.allow_all {
+        return Ok(());
+    }
+    for cmd in commands.iter() {
+        let req = match cmd {
+            SubCommands::Auth { req } => req,
+            _ => continue,
+        };
+        let (_, ipc_req) = decode_req(req)?;
+        match policies.map_or(PromptMode::Prompt, |policies| policies.mode_for(&ipc_req)) {
+            PromptMode::Allow | PromptMode::Deny => {}
+            PromptMode::Prompt | PromptMode::PerItem => return Err(Error::InvalidInput(format!(
+                "The {} request would be prompted for, which can't be done with --non-interactive: pass --allow-all-auth, or set the mode of its kind in the `prompts` of the config file",
+                ReqKind::of(&ipc_req)
+            ))),
+        }
+    }
+    Ok(())
+}
+
+// We accept an additional search path for the crust config from a stored network
+// definition or from an env var, or we add "/resources" as additional search path by default
+// The directory of the crust config of the network to connect to
+fn crust_config_dir(args: &CmdArgs) -> Result<String, Error> {
+    Ok(match (&args.network, env::var(CRUST_CONFIG_PATH_ENV_VAR)) {
+        (Some(name), _) => network_path(name)?.display().to_string(),
+        (None, Ok(val)) => val,
+        (None, Err(_)) => String::from(DEFAULT_SEARCH_PATH),
+    })
+}
+
+// The network in use: the one given with `--network`, otherwise the one of the crust config
+fn active_network(args: &CmdArgs) -> String {
+    if let Some(name) = &args.network {
+        return name.clone();
+    }
+    if args.mock || cfg!(feature = "mock-network") {
+        return network_name();
+    }
+    crust_config_dir(args)
+        .ok()
+        .and_then(|dir| configured_network_name(&dir))
+        .unwrap_or_else(network_name)
+}
+
+fn set_crust_config_path(args: &CmdArgs) -> Result<(), Error> {
+    let crust_config_path = crust_config_dir(args)?;
+    // Use a copy of the crust config with the bootstrap cache enabled if possible
+    let crust_config_path = match enable_bootstrap_cache(&crust_config_path, args.fresh_bootstrap) {
+        Ok(cache_path) => cache_path,
+        Err(err) => {
+            debug!("Bootstrap cache couldn't be enabled: {}", err);
+            crust_config_path
+        }
+    };
+    debug!(
+        "Additional search path set for crust config file: {}",
+        crust_config_path
+    );
+    config_file_handler::set_additional_search_path(&crust_config_path);
+    Ok(())
+}
+
+// The credentials of the profile, which its keys are unlocked with
+fn unlock(
+    config_file: &Option<String>,
+    profile: &str,
+    console: &Console,
+) -> Result<LoginDetails, Error> {
+    let login_details = get_login_details(config_file, profile, console)?;
+    register_secret(&login_details.secret);
+    register_secret(&login_details.password);
+    unlock_profile(profile, &login_details)?;
+    Ok(login_details)
+}
+
+// Connects to the network, creating the account or logging in to it
+fn connect(
+    args: &CmdArgs,
+    creation: Option<(Option<&str>, bool)>,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    progress: &ProgressTracker,
+    cancel: &CancellationToken,
+) -> Result<Authenticator, Error> {
+    let profile = profile_name(&args.profile, &args.network);
+    let login_details = unlock(&args.config_file_str, &profile, console)?;
+
+    set_crust_config_path(args)?;
+
+    // If secret key is provided (or --test-coins is passed), create a SAFE account,
+    // otherwise just login. In both cases we use the instantiated authenticator
+    // for all subsequent operations, even for the daemon services.
+    let authenticator: Authenticator;
+    if let Some((_, true)) = creation {
+        let (sk, sk_hex) = test_coins_key()?;
+        progress.start(&tr!(console, "op-create-acc"));
+        authenticator = create_acc_cancellable(
+            &sk_hex,
+            &login_details.secret,
+            &login_details.password,
+            progress,
+            cancel,
+        )?;
+        progress.done();
+        output.message(console, &tr!(console, "acc-created"));
+        output_key_pair(console, output, &sk, &sk_hex);
+    } else if let Some((Some(sk), _)) = creation {
+        register_secret(sk);
+        progress.start(&tr!(console, "op-create-acc"));
+        // The key preloaded with test-coins, with the mock network, if the one given couldn't
+        // pay for the account
+        #[cfg_attr(not(feature = "mock-network"), allow(unused_mut))]
+        let mut fresh: Option<(SecretKey, String)> = None;
+        #[cfg(feature = "mock-network")]
+        let mut get_fresh = || {
+            let (sk, sk_hex) = test_coins_key()?;
+            fresh = Some((sk, sk_hex.clone()));
+            Ok(sk_hex)
+        };
+        #[cfg(feature = "mock-network")]
+        let fresh_token = Some(FreshToken {
+            answer: tr!(console, "safe-key-test-coins"),
+            source: tr!(console, "safe-key-test-coins-source"),
+            get: &mut get_fresh,
+        });
+        #[cfg(not(feature = "mock-network"))]
+        let invite_url = get_settings(&args.config_file_str)?.invite_url;
+        #[cfg(not(feature = "mock-network"))]
+        let mut get_fresh = || fetch_invite(invite_url.as_deref().unwrap_or_default());
+        #[cfg(not(feature = "mock-network"))]
+        let fresh_token = invite_url.as_ref().map(|url| FreshToken {
+            answer: tr!(console, "safe-key-invite"),
+            source: url.clone(),
+            get: &mut get_fresh,
+        });
+        authenticator = create_with_retries(
+            console,
+            sk,
+            SAFE_KEY_RETRY_DELAY,
+            &mut |sk| {
+                create_acc_cancellable(
+                    sk,
+                    &login_details.secret,
+                    &login_details.password,
+                    progress,
+                    cancel,
+                )
+            },
+            fresh_token,
+        )?;
+        progress.done();
+        output.message(console, &tr!(console, "acc-created"));
+        if let Some((sk, sk_hex)) = &fresh {
+            output_key_pair(console, output, sk, sk_hex);
+        }
+    } else {
+        progress.start(&tr!(console, "op-log-in"));
+        authenticator = log_in_cancellable(
+            &login_details.secret,
+            &login_details.password,
+            progress,
+            cancel,
+        )?;
+        progress.done();
+        output.message(console, &tr!(console, "logged-in"));
+    }
+    record_login(&network_label(&args.network));
+
+    Ok(authenticator)
+}
+
+// A new secret key preloaded with test-coins, and its hex encoding
+fn test_coins_key() -> Result<(SecretKey, String), Error> {
+    let sk = SecretKey::random();
+    let sk_serialised = bincode::serialize(&SerdeSecret(&sk))
+        .expect("Failed to serialise the generated secret key");
+    let sk_hex: String = sk_serialised.iter().map(|b| format!("{:02x}", b)).collect();
+    register_secret(&sk_hex);
+    test_create_balance(
+        &ClientFullId::from(sk.clone()),
+        Coins::from_str("10").unwrap(),
+    )
+    .map_err(|err| Error::NetworkError(format!("Failed to preload the test-coins: {:?}", err)))?;
+    Ok((sk, sk_hex))
+}
+
+fn output_key_pair(console: &Console, output: &dyn OutputFormatter, sk: &SecretKey, sk_hex: &str) {
+    let pk_as_bytes: [u8; PK_SIZE] = sk.public_key().to_bytes();
+    let pk_hex: String = pk_as_bytes
+        .to_vec()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    output.output(
+        console,
+        Output::OwnerKeyPair {
+            public_key: &pk_hex,
+            secret_key: sk_hex,
+        },
+    );
+}
+
+// The overview of the `status` command, logging in to get the balance only if the
+// credentials can be read without asking the user
+fn account_status(
+    args: &CmdArgs,
+    console: &Console,
+    port: u16,
+    offline: bool,
+    progress: &ProgressTracker,
+    cancel: &CancellationToken,
+) -> Result<Status, Error> {
+    let network = network_label(&args.network);
+    let profile = profile_name(&args.profile, &args.network);
+    let credentials = stored_credentials(
+        &credential_providers(&args.config_file_str, &profile)?,
+        console,
+    )?;
+    let balance = match &credentials {
+        _ if args.mock => Some(FakeBackend::default().acc_info()?.balance),
+        Some((_, details)) if !offline => {
+            register_secret(&details.secret);
+            register_secret(&details.password);
+            set_crust_config_path(args)?;
+            progress.start(&tr!(console, "op-log-in"));
+            match log_in_cancellable(&details.secret, &details.password, progress, cancel) {
+                Ok(authenticator) => {
+                    progress.done();
+                    record_login(&network);
+                    let backend = CancellableAuthenticator::new(authenticator, cancel.clone());
+                    Some(backend.acc_info()?.balance)
+                }
+                Err(err @ Error::Cancelled(_)) => return Err(err),
+                // The rest of the status is still reported
+                Err(err) => {
+                    warn!("Failed to log in to get the balance: {}", err);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    // The service registered for the profile, otherwise any listening on the port
+    let daemon = find_instance(&profile)
+        .and_then(|instance| daemon_address(instance.port))
+        .or_else(|| daemon_address(port));
+    Ok(Status {
+        profile,
+        last_login: last_login(&network),
+        network,
+        config_file: args.config_file_str.clone(),
+        credentials: credentials.map(|(provider, _)| provider),
+        daemon,
+        balance,
+    })
+}
+
+// Fails if there are differences, so a scheduled check can tell the authorised apps drifted
+fn report_apps_diff(
+    console: &Console,
+    output: &dyn OutputFormatter,
+    diff: &AuthedAppsDiff,
+) -> Result<(), Error> {
+    output.output(console, Output::SnapshotsDiff(diff));
+    if diff.is_empty() {
+        return Ok(());
+    }
+    Err(Error::AuthenticatorError(format!(
+        "The authorised applications changed: {} added, {} removed and {} with different permissions",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    )))
+}
+
+// Lists the permissions to grant again, and grants them once confirmed
+fn restore_snapshot(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    snapshot: &[AuthedAppsList],
+    output: &dyn OutputFormatter,
+    verification: Option<UserVerification>,
+) -> Result<(), Error> {
+    let regrants = regrants(snapshot, &backend.authed_apps()?);
+    if regrants.is_empty() {
+        output.message(console, &tr!(console, "snapshot-nothing-to-restore"));
+        return Ok(());
+    }
+    for regrant in regrants.iter() {
+        let mut containers: Vec<String> = regrant
+            .containers
+            .iter()
+            .map(|(cont, perms)| format!("{}: {:?}", cont, perms))
+            .collect();
+        containers.sort();
+        if regrant.app_container {
+            containers.insert(0, tr!(console, "snapshot-own-container"));
+        }
+        outln!(
+            console,
+            "{} ({})",
+            regrant.app.id,
+            if regrant.register {
+                tr!(console, "change-removed")
+            } else {
+                containers.join(", ")
+            }
+        );
+        if regrant.register && !containers.is_empty() {
+            outln!(console, "    {}", containers.join(", "));
+        }
+    }
+
+    console.out(format_args!(
+        "{} ",
+        tr!(console, "snapshot-restore-prompt", count = regrants.len())
+    ));
+    // Failing to read the answer is the same as not confirming it
+    let answer = console.read_line().unwrap_or_default();
+    let answer = answer.trim().to_lowercase();
+    if answer != "y" && answer != tr!(console, "allow-auth-yes").to_lowercase() {
+        output.message(console, &tr!(console, "snapshot-not-restored"));
+        return Ok(());
+    }
+
+    let mut pacer = Pacer::current();
+    for (req_id, regrant) in regrants.iter().enumerate() {
+        verify_user(
+            verification,
+            &tr!(console, "verify-auth-reason", app_id = regrant.app.id),
+        )?;
+        let req = regrant.req(req_id as u32)?;
+        let _ = pacer.run(|| backend.authorise_app(&req, &|_| true))?;
+        output.message(
+            console,
+            &tr!(console, "snapshot-app-restored", app_id = regrant.app.id),
+        );
+    }
+    Ok(())
+}
+
+fn edit_app(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    app_id: &str,
+    output: &dyn OutputFormatter,
+    verification: Option<UserVerification>,
+) -> Result<(), Error> {
+    let authed_apps = backend.authed_apps()?;
+    let authed_app = authed_apps
+        .iter()
+        .find(|authed_app| authed_app.app.id == app_id)
+        .ok_or_else(|| Error::InvalidInput(format!("The app '{}' is not authorised", app_id)))?;
+    let perms = match edit_permissions(console, authed_app) {
+        Some(perms) => perms,
+        None => {
+            output.message(
+                console,
+                &tr!(console, "edit-app-unchanged", app_id = app_id),
+            );
+            return Ok(());
+        }
+    };
+
+    verify_user(
+        verification,
+        &tr!(console, "verify-revoke-reason", app_id = app_id),
+    )?;
+    let mut pacer = Pacer::current();
+    pacer.run(|| backend.revoke_app(app_id))?;
+    if let Some(regrant) = regrant(authed_app, perms) {
+        verify_user(
+            verification,
+            &tr!(console, "verify-auth-reason", app_id = app_id),
+        )?;
+        let req = regrant.req(0)?;
+        let _ = pacer.run(|| backend.authorise_app(&req, &|_| true))?;
+    }
+    output.message(console, &tr!(console, "edit-app-done", app_id = app_id));
+    Ok(())
+}
+
+fn run_account_subcommand(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    args: &CmdArgs,
+    output: &dyn OutputFormatter,
+    cmd: &AccountSubCommands,
+) -> Result<(), Error> {
+    match cmd {
+        AccountSubCommands::Backup { file } => {
+            let backup = backend.backup_account()?;
+            fs::write(file, backup).map_err(|err| {
+                Error::IoError(format!("Failed to write the account backup: {}", err))
+            })?;
+            output.message(
+                console,
+                &tr!(console, "backup-written", file = file.display()),
+            );
+        }
+        AccountSubCommands::Restore { file } => {
+            let backup = fs::read(file).map_err(|err| {
+                Error::IoError(format!("Failed to read the account backup: {}", err))
+            })?;
+            let diff = backend.restore_account(backup)?;
+            output.message(
+                console,
+                &tr!(console, "backup-restored", file = file.display()),
+            );
+            output.output(console, Output::AppsDiff(&diff));
+        }
+        AccountSubCommands::Keys => {
+            let keys = backend.acc_keys()?;
+            output.output(console, Output::AccountKeys(&keys));
+        }
+        AccountSubCommands::Summary => {
+            let info = backend.acc_info()?;
+            let profile = profile_name(&args.profile, &args.network);
+            let summary = AccountSummary {
+                network: active_network(args),
+                storage_used_percent: record_balance(&profile, &info.balance),
+                balance: info.balance,
+                authed_apps: backend.authed_apps()?.len(),
+                containers: info.containers,
+                last_authorised: last_authorised(&profile)?,
+            };
+            output.output(console, Output::AccountSummary(&summary));
+        }
+        AccountSubCommands::Activity => {
+            let profile = profile_name(&args.profile, &args.network);
+            let activities = account_activity(&profile, &backend.authed_apps()?)?;
+            output.output(console, Output::Activity(&activities));
+        }
+        AccountSubCommands::RotateKeys => {
+            // The safe_authenticator API doesn't expose a way to replace the client keys
+            // stored in the account's login packet, so all we can do is to inform the user
+            return Err(Error::AuthenticatorError(
+                "Client keys rotation is not supported by the SAFE Network authenticator yet"
+                    .to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn run_apps_subcommand(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    cmd: &AppsSubCommands,
+    verification: Option<UserVerification>,
+) -> Result<(), Error> {
+    match cmd {
+        AppsSubCommands::Diff { old, .. } => {
+            let old = read_snapshot(old)?;
+            report_apps_diff(
+                console,
+                output,
+                &diff_authed_apps(&old, &backend.authed_apps()?),
+            )?;
+        }
+        AppsSubCommands::Export { file } => {
+            write_snapshot(file, &backend.authed_apps()?)?;
+            output.message(
+                console,
+                &tr!(console, "snapshot-written", file = file.display()),
+            );
+        }
+        AppsSubCommands::Restore { file } => {
+            let snapshot = read_snapshot(file)?;
+            restore_snapshot(backend, console, &snapshot, output, verification)?;
+        }
+        AppsSubCommands::Edit { app_id } => {
+            edit_app(backend, console, app_id, output, verification)?;
+        }
+        // Run on their own, the list with its filters, and the labels before logging in
+        AppsSubCommands::List { .. } | AppsSubCommands::Label { .. } => {}
+    }
+
+    Ok(())
+}
+
+// Revokes the apps given, or picked on the console, trying all of them even if some fail
+fn run_revoke(
+    session: &Session,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    app_ids: &[String],
+    interactive: bool,
+    from_file: Option<&Path>,
+    verification: Option<UserVerification>,
+) -> Result<(), Error> {
+    let backend = &*session.backend;
+    // Without any IDs the apps are picked on the console, rather than copying their IDs
+    let app_ids = if let Some(file) = from_file {
+        read_app_ids(file)?
+    } else if interactive || (app_ids.is_empty() && console.is_console()) {
+        let authed_apps = backend.authed_apps()?;
+        let selected = select_apps(console, &authed_apps);
+        if selected.is_empty() {
+            output.message(console, &tr!(console, "revoke-none-selected"));
+        }
+        selected
+    } else if app_ids.is_empty() {
+        return Err(Error::InvalidInput(String::from(
+            "No applications to revoke were given, pass their IDs, --from-file or --interactive",
+        )));
+    } else {
+        app_ids.to_vec()
+    };
+    if let Some(guardian) = &session.guardian {
+        guardian.approve_revocations(backend, &app_ids)?;
+    }
+
+    let mut pacer = Pacer::current();
+    let outcomes: Vec<RevokeOutcome> = app_ids
+        .into_iter()
+        .map(|app_id| {
+            let reason = tr!(console, "verify-revoke-reason", app_id = app_id);
+            let result = verify_user(verification, &reason)
+                .and_then(|()| pacer.run(|| backend.revoke_app(&app_id)));
+            if let Err(err) = &result {
+                warn!("Failed to revoke '{}': {}", app_id, err);
+            }
+            RevokeOutcome {
+                app_id,
+                revoked: result.is_ok(),
+                error: result.err(),
+            }
+        })
+        .collect();
+    if !outcomes.is_empty() {
+        output.output(console, Output::Revoked(&outcomes));
+    }
+    // A single app fails with its own error, e.g. if it's not authorised
+    if let [RevokeOutcome {
+        error: Some(err), ..
+    }] = outcomes.as_slice()
+    {
+        return Err(err.clone());
+    }
+    let failed = outcomes.iter().filter(|outcome| !outcome.revoked).count();
+    if failed > 0 {
+        return Err(Error::AuthenticatorError(format!(
+            "{} of the {} applications couldn't be revoked",
+            failed,
+            outcomes.len()
+        )));
+    }
+    Ok(())
+}
+
+fn verify_receipt_file(
+    console: &Console,
+    output: &dyn OutputFormatter,
+    profile: &str,
+    file: &str,
+    req: &Option<String>,
+) -> Result<(), Error> {
+    let content = if file == "-" {
+        console
+            .read_line()
+            .map_err(|err| Error::IoError(format!("Failed to read the receipt: {}", err)))?
+    } else {
+        fs::read_to_string(file).map_err(|err| {
+            Error::IoError(format!("Failed to read the receipt '{}': {}", file, err))
+        })?
+    };
+    let receipt: Receipt = serde_json::from_str(content.trim())
+        .map_err(|err| Error::InvalidInput(format!("The receipt is not valid: {}", err)))?;
+    verify_receipt(
+        &receipt,
+        &profile_public_keys(profile)?,
+        req.as_ref().map(String::as_str),
+    )?;
+    output.message(
+        console,
+        &tr!(
+            console,
+            "receipt-verified",
+            decision = receipt.decision,
+            time = format_time(receipt.timestamp),
+            profile = profile
+        ),
+    );
+    Ok(())
+}
+
+// The receipt must have been signed by the key of the profile, and the archived request is
+// decrypted with the key derived from the credentials of its account
+fn show_receipt(
+    console: &Console,
+    output: &dyn OutputFormatter,
+    config_file: &Option<String>,
+    profile: &str,
+    id: &str,
+    raw: bool,
+) -> Result<(), Error> {
+    let receipt = find_receipt(profile, id)?;
+    verify_receipt(&receipt, &profile_public_keys(profile)?, None)?;
+    let request = if raw {
+        let _ = unlock(config_file, profile, console)?;
+        let req = decrypt_request(profile, Signer::load(profile)?.as_ref(), &receipt)?;
+        let (_, decoded) = decode_req(&req)?;
+        Some((req, decoded))
+    } else {
+        None
+    };
+    output.output(
+        console,
+        Output::Receipt {
+            receipt: &receipt,
+            request: request
+                .as_ref()
+                .map(|(req, decoded)| (req.as_str(), decoded)),
+        },
+    );
+    Ok(())
+}
+
+fn run_networks_subcommand(
+    console: &Console,
+    cmd: &NetworksSubCommands,
+    output: &dyn OutputFormatter,
+) -> Result<(), Error> {
+    match cmd {
+        NetworksSubCommands::Add { name, file } => {
+            add_network(name, file)?;
+            output.message(console, &tr!(console, "network-stored", name = name));
+        }
+        NetworksSubCommands::List => {
+            let networks = list_networks()?;
+            output.output(console, Output::Networks(&networks));
+        }
+        NetworksSubCommands::Show { name } => {
+            let crust_config = network_config(name)?;
+            output.output(
+                console,
+                Output::Network {
+                    name,
+                    crust_config: &crust_config,
+                },
+            );
+        }
+    }
+
+    Ok(())
+}
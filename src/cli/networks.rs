@@ -0,0 +1,73 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::helpers::CRUST_CONFIG_FILE_NAME;
+use crate::storage::{entry_file, storage};
+use crate::Error;
+use log::debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The area of the storage where the networks definitions are stored. Each network is defined
+// by a crust config named after the network, e.g. `<name>/safe_auth.crust.config`, the
+// directory of the network being in the user's app directory with the files storage.
+const NETWORKS_AREA: &str = "networks";
+
+fn network_entry(name: &str) -> String {
+    format!("{}/{}", name, CRUST_CONFIG_FILE_NAME)
+}
+
+fn not_found(name: &str) -> Error {
+    Error::InvalidInput(format!("No network named '{}' was found", name))
+}
+
+// Returns the path of the directory containing the crust config of the network, for
+// safe_core to read it from
+pub fn network_path(name: &str) -> Result<PathBuf, Error> {
+    entry_file(NETWORKS_AREA, &network_entry(name))?
+        .and_then(|file| file.parent().map(Path::to_path_buf))
+        .ok_or_else(|| not_found(name))
+}
+
+// Stores a copy of the crust config file provided as the definition of a network
+pub fn add_network(name: &str, crust_config_file: &Path) -> Result<(), Error> {
+    let config_str = fs::read_to_string(crust_config_file)
+        .map_err(|err| Error::IoError(format!("Failed to read crust config file: {}", err)))?;
+    let _: serde_json::Value = serde_json::from_str(&config_str).map_err(|err| {
+        Error::InvalidInput(format!("Format of the crust config is not valid: {}", err))
+    })?;
+
+    storage()?
+        .write(NETWORKS_AREA, &network_entry(name), config_str.as_bytes())
+        .map_err(|err| Error::IoError(format!("Failed to store network definition: {}", err)))?;
+    debug!("Network '{}' stored", name);
+    Ok(())
+}
+
+// Returns the names of all the networks stored, sorted alphabetically
+pub fn list_networks() -> Result<Vec<String>, Error> {
+    let suffix = format!("/{}", CRUST_CONFIG_FILE_NAME);
+    let networks = storage()?
+        .names(NETWORKS_AREA)?
+        .iter()
+        .filter_map(|entry| entry.strip_suffix(&suffix))
+        .filter(|name| !name.contains('/'))
+        .map(String::from)
+        .collect();
+    Ok(networks)
+}
+
+// Returns the crust config of the network
+pub fn network_config(name: &str) -> Result<serde_json::Value, Error> {
+    let config = storage()?
+        .read(NETWORKS_AREA, &network_entry(name))?
+        .ok_or_else(|| not_found(name))?;
+    serde_json::from_slice(&config).map_err(|err| {
+        Error::InvalidInput(format!("Format of the crust config is not valid: {}", err))
+    })
+}
@@ -0,0 +1,1446 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// How the outcome of the commands is printed. The commands only tell what they output,
+// as an `Output`, and the `OutputFormatter` selected with `--output` renders it, so a new
+// format only needs a new formatter rather than changes to each of the commands.
+
+use super::activity::{Activity, ActivityKind, ActivitySource};
+use super::color::is_dangerous;
+use super::doctor::{CheckResult, CheckStatus, Message};
+use super::helpers::{AccountSummary, RevokeOutcome};
+use super::i18n::Localiser;
+use super::io::{CapturedIo, Console};
+use super::known_apps::{recognise, Recognition};
+use super::labels::labels;
+use super::receipts::Receipt;
+use super::status::{format_time, Status};
+use super::table::{Row, Table};
+use super::template::Template;
+use crate::{app_container_name, AccountKeys, AuthedAppsDiff, AuthedAppsList, Error};
+use safe_core::ipc::req::{AppExchangeInfo, ContainerPermissions, IpcReq};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// The formats which can be selected with `--output`
+pub const OUTPUT_FORMATS: [&str; 6] = ["table", "plain", "json", "yaml", "csv", "jsonl"];
+
+// Everything the commands output, apart from the messages only meant for humans
+pub enum Output<'a> {
+    /// The response to an auth request, to be sent back to the app
+    AuthResponse(&'a str),
+    AuthedApps(&'a [AuthedAppsList]),
+    /// The permissions which changed when an account backup was restored
+    AppsDiff(&'a AuthedAppsDiff),
+    /// The changes between two snapshots of the authorised apps, of the `apps diff` command
+    SnapshotsDiff(&'a AuthedAppsDiff),
+    AccountKeys(&'a AccountKeys),
+    AccountSummary(&'a AccountSummary),
+    /// The timeline of the `account activity` command
+    Activity(&'a [Activity]),
+    /// The balance of the `balance` command
+    Balance(&'a str),
+    /// The overview of the `status` command
+    Status(&'a Status),
+    /// The key pair generated for an account created with test coins, hex encoded
+    OwnerKeyPair {
+        public_key: &'a str,
+        secret_key: &'a str,
+    },
+    Networks(&'a [String]),
+    Network {
+        name: &'a str,
+        crust_config: &'a serde_json::Value,
+    },
+    /// The outcome of the checks of the `doctor` command
+    Doctor(&'a [CheckResult]),
+    /// The outcome of the revocation of each of the apps of the `revoke` command
+    Revoked(&'a [RevokeOutcome]),
+    /// A receipt of the `audit show` command, with the encoded auth request archived with it
+    /// and its decoding if it's shown with `--raw`
+    Receipt {
+        receipt: &'a Receipt,
+        request: Option<(&'a str, &'a IpcReq)>,
+    },
+}
+
+pub trait OutputFormatter {
+    /// Prints a message only meant for humans, e.g. "Logged in the SAFE Network successfully!",
+    /// which the formatters meant to be parsed omit
+    fn message(&self, console: &Console, message: &str);
+
+    fn output(&self, console: &Console, output: Output);
+}
+
+// Returns the formatter for an `--output` value, the empty one being the default format
+pub fn formatter(name: &str) -> Result<Box<dyn OutputFormatter>, Error> {
+    match name {
+        "" | "table" => Ok(Box::new(TableFormatter)),
+        // `tsv` names the plain output after what it prints
+        "plain" | "tsv" => Ok(Box::new(PlainFormatter::default())),
+        "json" => Ok(Box::new(JsonFormatter)),
+        "jsonl" => Ok(Box::new(JsonLinesFormatter)),
+        "yaml" => Ok(Box::new(YamlFormatter)),
+        "csv" => Ok(Box::new(CsvFormatter)),
+        other => Err(Error::InvalidInput(format!(
+            "Output serialisation format '{}' not supported",
+            other
+        ))),
+    }
+}
+
+// Messages and tables for humans, the default format
+pub struct TableFormatter;
+
+impl OutputFormatter for TableFormatter {
+    fn message(&self, console: &Console, message: &str) {
+        outln!(console, "{}", message);
+    }
+
+    fn output(&self, console: &Console, output: Output) {
+        match output {
+            Output::AuthResponse(resp) => {
+                outln!(console, "{}", tr!(console, "auth-response", resp = resp))
+            }
+            Output::AuthedApps(apps) => pretty_print_authed_apps(console, apps),
+            Output::AppsDiff(diff) => pretty_print_apps_diff(console, diff, "no-discrepancies"),
+            Output::SnapshotsDiff(diff) => pretty_print_apps_diff(console, diff, "apps-unchanged"),
+            Output::AccountKeys(keys) => {
+                outln!(
+                    console,
+                    "{}",
+                    tr!(console, "key-public", key = keys.public_key)
+                );
+                outln!(
+                    console,
+                    "{}",
+                    tr!(console, "key-owner", key = keys.owner_key)
+                );
+                outln!(
+                    console,
+                    "{}",
+                    tr!(console, "key-encryption", key = keys.encryption_key)
+                );
+            }
+            Output::AccountSummary(summary) => pretty_print_acc_summary(console, summary),
+            Output::Activity(activities) => pretty_print_activity(console, activities),
+            Output::Balance(balance) => {
+                outln!(
+                    console,
+                    "{}",
+                    tr!(console, "acc-balance", balance = balance)
+                )
+            }
+            Output::Status(status) => pretty_print_status(console, status),
+            Output::OwnerKeyPair {
+                public_key,
+                secret_key,
+            } => {
+                outln!(console, "{}", tr!(console, "test-coins-key-pair"));
+                outln!(console, "{}", tr!(console, "key-public", key = public_key));
+                outln!(console, "{}", tr!(console, "key-secret", key = secret_key));
+            }
+            Output::Networks(networks) => {
+                if networks.is_empty() {
+                    outln!(console, "{}", tr!(console, "no-networks"));
+                }
+                for name in networks.iter() {
+                    outln!(console, "{}", name);
+                }
+            }
+            Output::Network { name, crust_config } => {
+                pretty_print_network(console, name, crust_config)
+            }
+            Output::Doctor(results) => pretty_print_doctor(console, results),
+            Output::Revoked(outcomes) => {
+                for outcome in outcomes.iter() {
+                    match &outcome.error {
+                        None => outln!(
+                            console,
+                            "{}",
+                            tr!(console, "app-revoked", app_id = outcome.app_id)
+                        ),
+                        Some(err) => outln!(
+                            console,
+                            "{}",
+                            tr!(
+                                console,
+                                "revoke-failed",
+                                app_id = outcome.app_id,
+                                error = err
+                            )
+                        ),
+                    }
+                }
+                if outcomes.len() > 1 {
+                    let revoked = outcomes.iter().filter(|outcome| outcome.revoked).count();
+                    outln!(
+                        console,
+                        "{}",
+                        tr!(
+                            console,
+                            "revoke-summary",
+                            revoked = revoked,
+                            total = outcomes.len()
+                        )
+                    );
+                }
+            }
+            Output::Receipt { receipt, request } => pretty_print_receipt(console, receipt, request),
+        }
+    }
+}
+
+// Tab separated values, one record per line, to be processed with the usual shell tools.
+// With `--print0` the fields are separated by the unit separator and the records end with
+// a NUL, e.g. for `xargs -0`, and the names and vendors of the apps are not quoted. The
+// separators found in the values, e.g. in a name chosen by an app, are escaped as `\x1f` and
+// `\0`, and the backslashes as `\\`, so they can't split a value or a record.
+pub struct PlainFormatter {
+    field_separator: char,
+    record_separator: char,
+}
+
+impl Default for PlainFormatter {
+    fn default() -> Self {
+        Self {
+            field_separator: '\t',
+            record_separator: '\n',
+        }
+    }
+}
+
+impl PlainFormatter {
+    pub fn print0() -> Self {
+        Self {
+            field_separator: '\u{1f}',
+            record_separator: '\0',
+        }
+    }
+
+    fn record(&self, console: &Console, fields: &[&str]) {
+        let mut record = String::new();
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                record.push(self.field_separator);
+            }
+            if self.record_separator == '\n' {
+                record.push_str(field);
+            } else {
+                record.push_str(&escape_separators(field));
+            }
+        }
+        record.push(self.record_separator);
+        console.out(format_args!("{}", record));
+    }
+
+    fn authed_apps(&self, console: &Console, authed_apps: &[AuthedAppsList]) {
+        self.record(console, &["APP ID", "NAME", "VENDOR", "PERMISSIONS"]);
+        for app_info in authed_apps.iter() {
+            let (name, vendor) = if self.record_separator == '\n' {
+                (
+                    format!("{:?}", app_info.app.name),
+                    format!("{:?}", app_info.app.vendor),
+                )
+            } else {
+                (app_info.app.name.clone(), app_info.app.vendor.clone())
+            };
+            self.record(
+                console,
+                &[
+                    &app_info.app.id,
+                    &name,
+                    &vendor,
+                    &perms_list(&app_info.perms),
+                ],
+            );
+        }
+    }
+}
+
+// Escapes the separators of `--print0`, and the backslash they are escaped with
+fn escape_separators(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\u{1f}' => escaped.push_str("\\x1f"),
+            '\0' => escaped.push_str("\\0"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl OutputFormatter for PlainFormatter {
+    fn message(&self, _console: &Console, _message: &str) {}
+
+    fn output(&self, console: &Console, output: Output) {
+        match output {
+            Output::AuthResponse(resp) => self.record(console, &[resp]),
+            Output::AuthedApps(apps) => self.authed_apps(console, apps),
+            Output::AppsDiff(diff) | Output::SnapshotsDiff(diff) => {
+                self.record(console, &["APP ID", "CHANGE"]);
+                for app_info in diff.removed.iter() {
+                    self.record(console, &[&app_info.app.id, "removed"]);
+                }
+                for app_info in diff.added.iter() {
+                    self.record(console, &[&app_info.app.id, "added"]);
+                }
+                for change in diff.changed.iter() {
+                    self.record(console, &[&change.app.id, "changed"]);
+                }
+            }
+            Output::AccountKeys(keys) => {
+                self.record(console, &["public_key", &keys.public_key]);
+                self.record(console, &["owner_key", &keys.owner_key]);
+                self.record(console, &["encryption_key", &keys.encryption_key]);
+            }
+            Output::Balance(balance) => self.record(console, &["balance", balance]),
+            Output::AccountSummary(summary) => {
+                self.record(console, &["network", &summary.network]);
+                self.record(console, &["balance", &summary.balance]);
+                self.record(console, &["authed_apps", &summary.authed_apps.to_string()]);
+                self.record(console, &["containers", &summary.containers.join(",")]);
+                self.record(
+                    console,
+                    &[
+                        "storage_used_percent",
+                        &summary
+                            .storage_used_percent
+                            .map_or(String::new(), |percent| percent.to_string()),
+                    ],
+                );
+                self.record(
+                    console,
+                    &[
+                        "last_authorised",
+                        &summary
+                            .last_authorised
+                            .map_or(String::new(), |secs| secs.to_string()),
+                    ],
+                );
+            }
+            Output::Activity(activities) => {
+                for activity in activities.iter() {
+                    self.record(
+                        console,
+                        &[
+                            &activity
+                                .timestamp
+                                .map_or(String::new(), |secs| secs.to_string()),
+                            activity_name(activity.activity),
+                            activity.app_id.as_deref().unwrap_or(""),
+                            source_name(activity.source),
+                        ],
+                    );
+                }
+            }
+            Output::Status(status) => {
+                let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+                self.record(console, &["profile", &status.profile]);
+                self.record(console, &["network", &status.network]);
+                self.record(console, &["config_file", &optional(&status.config_file)]);
+                self.record(console, &["credentials", &optional(&status.credentials)]);
+                self.record(console, &["daemon", &optional(&status.daemon)]);
+                self.record(
+                    console,
+                    &[
+                        "last_login",
+                        &status
+                            .last_login
+                            .map_or(String::new(), |secs| secs.to_string()),
+                    ],
+                );
+                self.record(console, &["balance", &optional(&status.balance)]);
+            }
+            Output::OwnerKeyPair {
+                public_key,
+                secret_key,
+            } => {
+                self.record(console, &["public_key", public_key]);
+                self.record(console, &["secret_key", secret_key]);
+            }
+            Output::Networks(networks) => {
+                for name in networks.iter() {
+                    self.record(console, &[name]);
+                }
+            }
+            Output::Network { name, crust_config } => {
+                self.record(console, &[name, &crust_config.to_string()]);
+            }
+            Output::Doctor(results) => {
+                let english = Localiser::default();
+                for result in results.iter() {
+                    self.record(
+                        console,
+                        &[
+                            result.check,
+                            status_name(result.status),
+                            &english_text(&english, &result.detail),
+                            &result
+                                .fix
+                                .as_ref()
+                                .map_or(String::new(), |fix| english_text(&english, fix)),
+                        ],
+                    );
+                }
+            }
+            Output::Revoked(outcomes) => {
+                for outcome in outcomes.iter() {
+                    match &outcome.error {
+                        None => self.record(console, &[&outcome.app_id, "revoked"]),
+                        Some(err) => {
+                            self.record(console, &[&outcome.app_id, "failed", &err.to_string()])
+                        }
+                    }
+                }
+            }
+            Output::Receipt { receipt, request } => {
+                self.record(console, &["request_hash", &receipt.request_hash]);
+                self.record(
+                    console,
+                    &[
+                        "kind",
+                        &receipt.kind.map_or(String::new(), |kind| kind.to_string()),
+                    ],
+                );
+                self.record(console, &["decision", &receipt.decision]);
+                self.record(console, &["timestamp", &receipt.timestamp.to_string()]);
+                self.record(console, &["public_key", &receipt.public_key]);
+                if let Some(approver) = &receipt.approver {
+                    self.record(console, &["approver", approver]);
+                }
+                if let Some((req, decoded)) = request {
+                    self.record(console, &["request", req]);
+                    self.record(console, &["decoded_request", &format!("{:?}", decoded)]);
+                }
+            }
+        }
+    }
+}
+
+// A JSON document for each output. As it always did, the list of authorised apps
+// is printed as tab separated values instead, not to break the scripts parsing it.
+pub struct JsonFormatter;
+
+impl OutputFormatter for JsonFormatter {
+    fn message(&self, _console: &Console, _message: &str) {}
+
+    fn output(&self, console: &Console, output: Output) {
+        match output {
+            Output::AuthResponse(resp) => outln!(console, "{}", resp),
+            Output::AuthedApps(apps) => PlainFormatter::default().authed_apps(console, apps),
+            Output::AppsDiff(diff) | Output::SnapshotsDiff(diff) => print_json(console, diff),
+            Output::AccountKeys(keys) => print_json(console, keys),
+            Output::AccountSummary(summary) => print_json(console, summary),
+            Output::Activity(activities) => print_json(console, activities),
+            Output::Balance(balance) => print_json(console, &json!({ "balance": balance })),
+            Output::Status(status) => print_json(console, status),
+            Output::OwnerKeyPair {
+                public_key,
+                secret_key,
+            } => print_json(console, &(public_key, secret_key)),
+            Output::Networks(networks) => print_json(console, &networks),
+            Output::Network { crust_config, .. } => outln!(console, "{}", crust_config),
+            Output::Doctor(results) => {
+                let english = Localiser::default();
+                let results: Vec<serde_json::Value> = results
+                    .iter()
+                    .map(|result| {
+                        serde_json::json!({
+                            "check": result.check,
+                            "status": result.status,
+                            "detail": english_text(&english, &result.detail),
+                            "fix": result.fix.as_ref().map(|fix| english_text(&english, fix)),
+                        })
+                    })
+                    .collect();
+                print_json(console, &results);
+            }
+            Output::Revoked(outcomes) => print_json(console, outcomes),
+            Output::Receipt { receipt, request } => {
+                print_json(console, &receipt_value(receipt, request))
+            }
+        }
+    }
+}
+
+// JSON Lines, each record being a standalone JSON object on its own line, e.g. each of the
+// authorised apps or of the doctor checks, for `jq -c` pipelines and log shippers. The records
+// are the same as the ones of `--format`.
+pub struct JsonLinesFormatter;
+
+impl OutputFormatter for JsonLinesFormatter {
+    fn message(&self, _console: &Console, _message: &str) {}
+
+    fn output(&self, console: &Console, output: Output) {
+        for record in records(output) {
+            print_json(console, &record);
+        }
+    }
+}
+
+// A YAML document for each output, e.g. for provisioning tools. Nothing parsed it before, so
+// unlike with `json` the authorised apps are a list of their records, and the outputs which
+// are lists are always a sequence, even with a single item.
+pub struct YamlFormatter;
+
+impl OutputFormatter for YamlFormatter {
+    fn message(&self, _console: &Console, _message: &str) {}
+
+    fn output(&self, console: &Console, output: Output) {
+        let is_list = matches!(
+            output,
+            Output::AuthedApps(_)
+                | Output::Activity(_)
+                | Output::AppsDiff(_)
+                | Output::SnapshotsDiff(_)
+                | Output::Networks(_)
+                | Output::Doctor(_)
+                | Output::Revoked(_)
+        );
+        let mut records = records(output);
+        let value = if is_list {
+            Value::Array(records)
+        } else {
+            records.pop().unwrap_or(Value::Null)
+        };
+        outln!(
+            console,
+            "{}",
+            serde_yaml::to_string(&value)
+                .unwrap_or_else(|_| "Failed to serialise output to yaml".to_string())
+        );
+    }
+}
+
+// RFC 4180 CSV, with a header, to be loaded into spreadsheets. Each authorised app is an
+// `id,name,vendor,permissions,label` row, its permissions flattened to
+// `<container>:<perm>|<perm>` separated by `;`. The other outputs have a column for each field of their records, the
+// values which are not strings as JSON, e.g. the lists.
+pub struct CsvFormatter;
+
+impl CsvFormatter {
+    fn row(&self, console: &Console, fields: &[&str]) {
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        console.out(format_args!("{}\r\n", row.join(",")));
+    }
+}
+
+impl OutputFormatter for CsvFormatter {
+    fn message(&self, _console: &Console, _message: &str) {}
+
+    fn output(&self, console: &Console, output: Output) {
+        if let Output::AuthedApps(apps) = output {
+            let labels = labels();
+            self.row(console, &["id", "name", "vendor", "permissions", "label"]);
+            for app_info in apps.iter() {
+                self.row(
+                    console,
+                    &[
+                        &app_info.app.id,
+                        &app_info.app.name,
+                        &app_info.app.vendor,
+                        &flat_perms(&app_info.perms),
+                        labels.get(&app_info.app.id).map_or("", String::as_str),
+                    ],
+                );
+            }
+            return;
+        }
+
+        let records = records(output);
+        let columns: Vec<String> = match records.first() {
+            Some(Value::Object(fields)) => fields.keys().cloned().collect(),
+            _ => vec![String::from("value")],
+        };
+        self.row(
+            console,
+            &columns.iter().map(String::as_str).collect::<Vec<&str>>(),
+        );
+        for record in records.iter() {
+            let values: Vec<String> = match record {
+                Value::Object(fields) => columns
+                    .iter()
+                    .map(|column| fields.get(column).map_or(String::new(), csv_value))
+                    .collect(),
+                value => vec![csv_value(value)],
+            };
+            self.row(
+                console,
+                &values.iter().map(String::as_str).collect::<Vec<&str>>(),
+            );
+        }
+    }
+}
+
+// Quoted if it has a comma, a double quote or a line break, the double quotes being doubled
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+// E.g. `_public:Read|Insert;apps/net.maidsafe.cli:Read`
+fn flat_perms(perms: &[(String, ContainerPermissions)]) -> String {
+    perms
+        .iter()
+        .map(|(cont, perms)| {
+            let perms: Vec<String> = perms.iter().map(|perm| format!("{:?}", perm)).collect();
+            format!("{}:{}", cont, perms.join("|"))
+        })
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+// Each record rendered with the template given with `--format`, one per line. The records
+// are JSON documents, e.g. each of the authorised apps is
+// `{"app": {"id", "name", "vendor", "scope"}, "permissions": {<container>: [<permission>]}}`
+pub struct TemplateFormatter {
+    template: Template,
+}
+
+impl TemplateFormatter {
+    pub fn new(template: &str) -> Result<Self, Error> {
+        Ok(Self {
+            template: Template::parse(template)?,
+        })
+    }
+}
+
+impl OutputFormatter for TemplateFormatter {
+    fn message(&self, _console: &Console, _message: &str) {}
+
+    fn output(&self, console: &Console, output: Output) {
+        for record in records(output) {
+            outln!(console, "{}", self.template.render(&record));
+        }
+    }
+}
+
+// The formatter of `--strict`, around the parsable one selected: only the outputs are written to
+// the stdout, the messages go to the stderr with everything else the console writes
+pub struct StrictFormatter(Box<dyn OutputFormatter>);
+
+impl StrictFormatter {
+    pub fn new(formatter: Box<dyn OutputFormatter>) -> Self {
+        StrictFormatter(formatter)
+    }
+}
+
+impl OutputFormatter for StrictFormatter {
+    fn message(&self, console: &Console, message: &str) {
+        errln!(console, "{}", message);
+    }
+
+    // The output is rendered on a console of its own, as the strict one's stdout is reserved
+    fn output(&self, console: &Console, output: Output) {
+        let io = CapturedIo::new("");
+        let rendered = io.output();
+        let captured = Console::new(Box::new(io));
+        captured.set_localiser(console.localiser());
+        self.0.output(&captured, output);
+        console.out_result(format_args!("{}", rendered.stdout()));
+        console.err(format_args!("{}", rendered.stderr()));
+    }
+}
+
+// The output of `--quiet` unless another one is given: the data alone, e.g. the auth response
+// or the balance, for the scripts capturing it, and the records of the plain output otherwise
+pub struct QuietFormatter;
+
+impl OutputFormatter for QuietFormatter {
+    fn message(&self, _console: &Console, _message: &str) {}
+
+    fn output(&self, console: &Console, output: Output) {
+        match output {
+            Output::AuthResponse(resp) => outln!(console, "{}", resp),
+            Output::Balance(balance) => outln!(console, "{}", balance),
+            output => PlainFormatter::default().output(console, output),
+        }
+    }
+}
+
+fn records(output: Output) -> Vec<Value> {
+    let authed_app = |app_info: &AuthedAppsList| {
+        let permissions: serde_json::Map<String, Value> = app_info
+            .perms
+            .iter()
+            .map(|(cont, perms)| (cont.clone(), to_value(perms)))
+            .collect();
+        json!({ "app": to_value(&app_info.app), "permissions": permissions })
+    };
+    match output {
+        Output::AuthResponse(resp) => vec![json!({ "response": resp })],
+        Output::AuthedApps(apps) => {
+            let labels = labels();
+            apps.iter()
+                .map(|app_info| {
+                    let mut record = authed_app(app_info);
+                    if let Some(label) = labels.get(&app_info.app.id) {
+                        record["label"] = json!(label);
+                    }
+                    record
+                })
+                .collect()
+        }
+        Output::AppsDiff(diff) | Output::SnapshotsDiff(diff) => {
+            let mut records = vec![];
+            for (change, apps) in &[("removed", &diff.removed), ("added", &diff.added)] {
+                for app_info in apps.iter() {
+                    let mut record = authed_app(app_info);
+                    record["change"] = json!(change);
+                    records.push(record);
+                }
+            }
+            for change in diff.changed.iter() {
+                let mut record = to_value(change);
+                record["change"] = json!("changed");
+                records.push(record);
+            }
+            records
+        }
+        Output::AccountKeys(keys) => vec![to_value(keys)],
+        Output::AccountSummary(summary) => vec![to_value(summary)],
+        Output::Activity(activities) => activities.iter().map(to_value).collect(),
+        Output::Balance(balance) => vec![json!({ "balance": balance })],
+        Output::Status(status) => vec![to_value(status)],
+        Output::OwnerKeyPair {
+            public_key,
+            secret_key,
+        } => vec![json!({ "public_key": public_key, "secret_key": secret_key })],
+        Output::Networks(networks) => networks
+            .iter()
+            .map(|name| json!({ "name": name }))
+            .collect(),
+        Output::Network { name, crust_config } => {
+            vec![json!({ "name": name, "crust_config": crust_config })]
+        }
+        Output::Doctor(results) => {
+            let english = Localiser::default();
+            results
+                .iter()
+                .map(|result| {
+                    json!({
+                        "check": result.check,
+                        "status": result.status,
+                        "detail": english_text(&english, &result.detail),
+                        "fix": result.fix.as_ref().map(|fix| english_text(&english, fix)),
+                    })
+                })
+                .collect()
+        }
+        Output::Revoked(outcomes) => outcomes.iter().map(to_value).collect(),
+        Output::Receipt { receipt, request } => vec![receipt_value(receipt, request)],
+    }
+}
+
+// The receipt, with the archived auth request and its decoding if they are given
+fn receipt_value(receipt: &Receipt, request: Option<(&str, &IpcReq)>) -> Value {
+    let mut value = to_value(receipt);
+    if let Some((req, decoded)) = request {
+        value["request"] = json!(req);
+        value["decoded_request"] = to_value(decoded);
+    }
+    value
+}
+
+fn to_value<T: Serialize + ?Sized>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn activity_name(activity: ActivityKind) -> &'static str {
+    match activity {
+        ActivityKind::Allowed => "allowed",
+        ActivityKind::Denied => "denied",
+        ActivityKind::Revoked => "revoked",
+        ActivityKind::Expired => "expired",
+        ActivityKind::Authorised => "authorised",
+    }
+}
+
+fn source_name(source: ActivitySource) -> &'static str {
+    match source {
+        ActivitySource::Audit => "audit",
+        ActivitySource::Service => "service",
+        ActivitySource::Network => "network",
+    }
+}
+
+fn status_name(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Ok => "ok",
+        CheckStatus::Warning => "warning",
+        CheckStatus::Failed => "failed",
+        CheckStatus::Skipped => "skipped",
+    }
+}
+
+// The outputs meant to be parsed are never translated
+fn english_text(english: &Localiser, message: &Message) -> String {
+    english.text(message.id, &message.args)
+}
+
+fn print_json<T: Serialize + ?Sized>(console: &Console, value: &T) {
+    outln!(
+        console,
+        "{}",
+        serde_json::to_string(value)
+            .unwrap_or_else(|_| "Failed to serialise output to json".to_string())
+    );
+}
+
+fn pretty_print_authed_apps(console: &Console, authed_apps: &[AuthedAppsList]) {
+    let mut table = Table::new();
+    table.add_row(row![bFg->tr!(console, "header-authed-apps")]);
+    table.add_row(row![
+        bFg->tr!(console, "header-id"),
+        bFg->tr!(console, "header-name"),
+        bFg->tr!(console, "header-vendor"),
+        bFg->tr!(console, "header-permissions")
+    ]);
+
+    let labels = labels();
+    let all_app_iterator = authed_apps.iter();
+    for app_info in all_app_iterator {
+        // The app's own container is marked, as its name alone doesn't tell it was created
+        // for the app
+        let own_container = app_container_name(&app_info.app.id);
+        let mut row = String::from("");
+        for (cont, perms) in app_info.perms.iter() {
+            if *cont == own_container {
+                row += &format!(
+                    "{}: {:?} {}\n",
+                    cont,
+                    perms,
+                    tr!(console, "own-container-mark")
+                );
+            } else {
+                row += &format!("{}: {:?}\n", cont, perms);
+            }
+        }
+        let dangerous = app_info.perms.iter().any(|(_, perms)| is_dangerous(perms));
+        let app = &app_info.app;
+        let id = match labels.get(&app.id) {
+            Some(label) => format!(
+                "{}\n{}",
+                app.id,
+                tr!(console, "app-label-mark", label = label)
+            ),
+            None => app.id.clone(),
+        };
+        // The apps mimicking a known app are flagged in red
+        let app_row = match recognise(&app.id, &app.name) {
+            Recognition::Known(_) => app_row(
+                format!("{}\n{}", id, tr!(console, "app-known-mark")),
+                app,
+                row,
+                dangerous,
+            ),
+            Recognition::Mimicking(known) => row![
+                Fr->format!(
+                    "{}\n{}",
+                    id,
+                    tr!(console, "app-mimicking-mark", known_id = known.id)
+                ),
+                Fr->app.name,
+                Fr->app.vendor,
+                Fr->row,
+            ],
+            Recognition::Unknown => app_row(id, app, row, dangerous),
+        };
+        table.add_row(app_row);
+    }
+    console.print_table(&table);
+}
+
+// The row of an authorised app, its permissions in green, or in red if any is dangerous
+// The cells' styles are left out of the rows without the `pretty` feature
+#[cfg_attr(not(feature = "pretty"), allow(clippy::if_same_then_else))]
+fn app_row(id: String, app: &AppExchangeInfo, perms: String, dangerous: bool) -> Row {
+    if dangerous {
+        row![id, app.name, app.vendor, Fr->perms]
+    } else {
+        row![id, app.name, app.vendor, Fg->perms]
+    }
+}
+
+fn pretty_print_acc_summary(console: &Console, summary: &AccountSummary) {
+    let mut table = Table::new();
+    table.add_row(row![bFg->tr!(console, "header-acc-summary")]);
+    table.add_row(row![bFg->tr!(console, "header-network"), summary.network]);
+    table.add_row(row![bFg->tr!(console, "header-balance"), summary.balance]);
+    table.add_row(row![
+        bFg->tr!(console, "header-authed-apps-count"),
+        summary.authed_apps
+    ]);
+    table.add_row(row![
+        bFg->tr!(console, "header-containers"),
+        summary.containers.join("\n")
+    ]);
+    let storage_used = match summary.storage_used_percent {
+        Some(percent) => format!("{}%", percent),
+        None => console.text("summary-storage-unknown", &[]),
+    };
+    table.add_row(row![bFg->tr!(console, "header-storage-used"), storage_used]);
+    let last_authorised = match summary.last_authorised {
+        Some(secs) => format_time(secs),
+        None => console.text("summary-never-authorised", &[]),
+    };
+    table.add_row(row![
+        bFg->tr!(console, "header-last-authorised"),
+        last_authorised
+    ]);
+    console.print_table(&table);
+}
+
+fn pretty_print_activity(console: &Console, activities: &[Activity]) {
+    if activities.is_empty() {
+        outln!(console, "{}", tr!(console, "no-activity"));
+        return;
+    }
+    let mut table = Table::new();
+    table.add_row(row![bFg->tr!(console, "header-activity")]);
+    table.add_row(row![
+        bFg->tr!(console, "header-time"),
+        bFg->tr!(console, "header-event"),
+        bFg->tr!(console, "header-id"),
+        bFg->tr!(console, "header-source")
+    ]);
+    for activity in activities.iter() {
+        let time = match activity.timestamp {
+            Some(secs) => format_time(secs),
+            None => console.text("activity-time-unknown", &[]),
+        };
+        let kind = activity.kind.map_or(String::new(), |kind| kind.to_string());
+        let event = match activity.activity {
+            ActivityKind::Allowed => tr!(console, "activity-allowed", kind = kind),
+            ActivityKind::Denied => tr!(console, "activity-denied", kind = kind),
+            ActivityKind::Revoked => tr!(console, "activity-revoked"),
+            ActivityKind::Expired => tr!(console, "activity-expired"),
+            ActivityKind::Authorised => tr!(console, "activity-authorised"),
+        };
+        let event = match &activity.approver {
+            Some(approver) => tr!(
+                console,
+                "activity-approved-by",
+                event = event,
+                approver = approver
+            ),
+            None => event,
+        };
+        let app_id = match &activity.app_id {
+            Some(app_id) => app_id.clone(),
+            None => console.text("activity-unregistered", &[]),
+        };
+        let source = match activity.source {
+            ActivitySource::Audit => tr!(console, "activity-source-audit"),
+            ActivitySource::Service => tr!(console, "activity-source-service"),
+            ActivitySource::Network => tr!(console, "activity-source-network"),
+        };
+        table.add_row(row![time, event, app_id, source]);
+    }
+    console.print_table(&table);
+}
+
+fn pretty_print_status(console: &Console, status: &Status) {
+    let or_text = |value: &Option<String>, id: &str| match value {
+        Some(value) => value.clone(),
+        None => console.text(id, &[]),
+    };
+    let mut table = Table::new();
+    table.add_row(row![bFg->tr!(console, "header-status")]);
+    table.add_row(row![bFg->tr!(console, "header-profile"), status.profile]);
+    table.add_row(row![bFg->tr!(console, "header-network"), status.network]);
+    table.add_row(row![
+        bFg->tr!(console, "header-config-file"),
+        or_text(&status.config_file, "status-no-config")
+    ]);
+    table.add_row(row![
+        bFg->tr!(console, "header-credentials"),
+        or_text(&status.credentials, "status-credentials-prompt")
+    ]);
+    table.add_row(row![
+        bFg->tr!(console, "header-daemon"),
+        or_text(&status.daemon, "status-daemon-not-running")
+    ]);
+    table.add_row(row![
+        bFg->tr!(console, "header-last-login"),
+        or_text(&status.last_login.map(format_time), "status-never-logged-in")
+    ]);
+    table.add_row(row![
+        bFg->tr!(console, "header-balance"),
+        or_text(&status.balance, "status-no-balance")
+    ]);
+    console.print_table(&table);
+}
+
+fn pretty_print_receipt(console: &Console, receipt: &Receipt, request: Option<(&str, &IpcReq)>) {
+    let mut table = Table::new();
+    table.add_row(row![bFg->tr!(console, "header-receipt")]);
+    table.add_row(row![bFg->tr!(console, "header-request-hash"), receipt.request_hash]);
+    table.add_row(row![
+        bFg->tr!(console, "header-kind"),
+        receipt.kind.map_or(String::new(), |kind| kind.to_string())
+    ]);
+    table.add_row(row![bFg->tr!(console, "header-decision"), receipt.decision]);
+    table.add_row(row![
+        bFg->tr!(console, "header-time"),
+        format_time(receipt.timestamp)
+    ]);
+    table.add_row(row![bFg->tr!(console, "header-public-key"), receipt.public_key]);
+    if let Some(approver) = &receipt.approver {
+        table.add_row(row![bFg->tr!(console, "header-approver"), approver]);
+    }
+    if let Some((req, decoded)) = request {
+        table.add_row(row![bFg->tr!(console, "header-raw-request"), req]);
+        table.add_row(row![
+            bFg->tr!(console, "header-decoded-request"),
+            format!("{:#?}", decoded)
+        ]);
+    }
+    console.print_table(&table);
+}
+
+fn pretty_print_network(console: &Console, name: &str, crust_config: &serde_json::Value) {
+    let contacts = match crust_config["hard_coded_contacts"].as_array() {
+        Some(contacts) => contacts
+            .iter()
+            .filter_map(|contact| contact.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        None => String::from(""),
+    };
+    let mut table = Table::new();
+    table.add_row(row![bFg->tr!(console, "header-network"), name]);
+    table.add_row(row![
+        bFg->tr!(console, "header-network-name"),
+        crust_config["network_name"].as_str().unwrap_or("")
+    ]);
+    table.add_row(row![bFg->tr!(console, "header-contacts"), contacts]);
+    console.print_table(&table);
+}
+
+// The message printed if there are no differences is the one with the id given
+fn pretty_print_apps_diff(console: &Console, diff: &AuthedAppsDiff, unchanged_id: &str) {
+    if diff.is_empty() {
+        outln!(console, "{}", console.text(unchanged_id, &[]));
+        return;
+    }
+
+    let mut table = Table::new();
+    table.add_row(row![bFg->tr!(console, "header-discrepancies")]);
+    table.add_row(row![
+        bFg->tr!(console, "header-id"),
+        bFg->tr!(console, "header-name"),
+        bFg->tr!(console, "header-vendor"),
+        bFg->tr!(console, "header-change")
+    ]);
+    for app_info in diff.removed.iter() {
+        table.add_row(row![
+            app_info.app.id,
+            app_info.app.name,
+            app_info.app.vendor,
+            tr!(console, "change-removed")
+        ]);
+    }
+    for app_info in diff.added.iter() {
+        table.add_row(row![
+            app_info.app.id,
+            app_info.app.name,
+            app_info.app.vendor,
+            tr!(console, "change-added")
+        ]);
+    }
+    for change in diff.changed.iter() {
+        let mut row = format!("{}\n", tr!(console, "change-perms-from"));
+        for (cont, perms) in change.old_perms.iter() {
+            row += &format!("{}: {:?}\n", cont, perms);
+        }
+        row += &format!("{}\n", tr!(console, "change-perms-to"));
+        for (cont, perms) in change.new_perms.iter() {
+            row += &format!("{}: {:?}\n", cont, perms);
+        }
+        table.add_row(row![change.app.id, change.app.name, change.app.vendor, row]);
+    }
+    console.print_table(&table);
+}
+
+fn pretty_print_doctor(console: &Console, results: &[CheckResult]) {
+    for result in results.iter() {
+        let status = match result.status {
+            CheckStatus::Ok => tr!(console, "doctor-status-ok"),
+            CheckStatus::Warning => tr!(console, "doctor-status-warning"),
+            CheckStatus::Failed => tr!(console, "doctor-status-failed"),
+            CheckStatus::Skipped => tr!(console, "doctor-status-skipped"),
+        };
+        outln!(
+            console,
+            "[{}] {}: {}",
+            status,
+            console.text(&format!("doctor-check-{}", result.check), &[]),
+            console.text(result.detail.id, &result.detail.args)
+        );
+        if let Some(fix) = &result.fix {
+            outln!(
+                console,
+                "    {}",
+                tr!(console, "doctor-fix", fix = console.text(fix.id, &fix.args))
+            );
+        }
+    }
+}
+
+// The containers and permissions of an app, e.g. `["_public":Read,"_music":Read|Insert]`
+fn perms_list(perms: &[(String, ContainerPermissions)]) -> String {
+    let mut row = String::from("[");
+    let mut it = perms.iter();
+    while let Some((cont, perms)) = it.next() {
+        row = row + &format!("{:?}:", cont);
+        let mut it2 = perms.iter();
+        while let Some(perm) = it2.next() {
+            row = row + &format!("{:?}", perm);
+            if it2.size_hint().0 > 0 {
+                row += "|";
+            };
+        }
+        if it.size_hint().0 > 0 {
+            row += ",";
+        };
+    }
+    row + "]"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        formatter, CsvFormatter, JsonLinesFormatter, Output, OutputFormatter, PlainFormatter,
+        QuietFormatter, StrictFormatter, TemplateFormatter, YamlFormatter,
+    };
+    use crate::cli::helpers::AccountSummary;
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::{AccountKeys, AuthedAppsList};
+    use safe_core::ipc::req::{AppExchangeInfo, Permission};
+    use serde_json::{json, Value};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn output_formatters_tests() {
+        let keys = AccountKeys {
+            public_key: "aa".to_string(),
+            owner_key: "bb".to_string(),
+            encryption_key: "cc".to_string(),
+        };
+        let mut outputs = vec![];
+        for name in ["table", "plain", "json", "tsv"].iter() {
+            let io = CapturedIo::new("");
+            let output = io.output();
+            let console = Console::new(Box::new(io));
+            let formatter = unwrap!(formatter(name));
+            formatter.message(&console, "Logged in the SAFE Network successfully!");
+            formatter.output(&console, Output::AccountKeys(&keys));
+            outputs.push(output.stdout());
+        }
+
+        assert_eq!(
+            outputs[0],
+            "Logged in the SAFE Network successfully!\nPublic Key = aa\nOwner Key = bb\nEncryption Key = cc\n"
+        );
+        assert_eq!(
+            outputs[1],
+            "public_key\taa\nowner_key\tbb\nencryption_key\tcc\n"
+        );
+        assert_eq!(
+            outputs[2],
+            "{\"public_key\":\"aa\",\"owner_key\":\"bb\",\"encryption_key\":\"cc\"}\n"
+        );
+        assert_eq!(outputs[3], outputs[1]);
+        assert!(formatter("xml").is_err());
+    }
+
+    fn authed_apps(name: &str) -> Vec<AuthedAppsList> {
+        let mut perms = BTreeSet::new();
+        let _ = perms.insert(Permission::Read);
+        vec![AuthedAppsList {
+            app: AppExchangeInfo {
+                id: "net.maidsafe.cli".to_string(),
+                scope: None,
+                name: name.to_string(),
+                vendor: "MaidSafe.net Ltd".to_string(),
+            },
+            perms: vec![("_public".to_string(), perms)],
+        }]
+    }
+
+    #[test]
+    fn plain_formatter_tests() {
+        let apps = authed_apps("SAFE\tCLI");
+        let mut outputs = vec![];
+        for formatter in &[PlainFormatter::default(), PlainFormatter::print0()] {
+            let io = CapturedIo::new("");
+            let output = io.output();
+            let console = Console::new(Box::new(io));
+            formatter.output(&console, Output::AuthedApps(&apps));
+            outputs.push(output.stdout());
+        }
+
+        assert_eq!(
+            outputs[0],
+            "APP ID\tNAME\tVENDOR\tPERMISSIONS\nnet.maidsafe.cli\t\"SAFE\\tCLI\"\t\"MaidSafe.net Ltd\"\t[\"_public\":Read]\n"
+        );
+        assert_eq!(
+            outputs[1],
+            "APP ID\u{1f}NAME\u{1f}VENDOR\u{1f}PERMISSIONS\0net.maidsafe.cli\u{1f}SAFE\tCLI\u{1f}MaidSafe.net Ltd\u{1f}[\"_public\":Read]\0"
+        );
+
+        // The separators of --print0 in the name or the vendor can't split the record
+        let mut apps = authed_apps("SAFE\u{1f}CLI\0v2");
+        apps[0].app.vendor = String::from("Maid\\Safe\u{1f}");
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        PlainFormatter::print0().output(&console, Output::AuthedApps(&apps));
+        let stdout = output.stdout();
+        assert_eq!(
+            stdout,
+            "APP ID\u{1f}NAME\u{1f}VENDOR\u{1f}PERMISSIONS\0net.maidsafe.cli\u{1f}SAFE\\x1fCLI\\0v2\u{1f}Maid\\\\Safe\\x1f\u{1f}[\"_public\":Read]\0"
+        );
+        let records: Vec<Vec<&str>> = stdout
+            .split_terminator('\0')
+            .map(|record| record.split('\u{1f}').collect())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|fields| fields.len() == 4));
+    }
+
+    #[test]
+    fn csv_formatter_tests() {
+        let mut apps = authed_apps("SAFE \"CLI\", v2");
+        let mut perms = BTreeSet::new();
+        let _ = perms.insert(Permission::Read);
+        let _ = perms.insert(Permission::Insert);
+        apps[0].perms.push(("_music".to_string(), perms));
+        apps[0].app.vendor = "MaidSafe\nLtd".to_string();
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        CsvFormatter.message(&console, "Logged in the SAFE Network successfully!");
+        CsvFormatter.output(&console, Output::AuthedApps(&apps));
+        assert_eq!(
+            output.stdout(),
+            "id,name,vendor,permissions,label\r\nnet.maidsafe.cli,\"SAFE \"\"CLI\"\", v2\",\"MaidSafe\nLtd\",_public:Read;_music:Read|Insert,\r\n"
+        );
+
+        // The other outputs have a column for each field of their records
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        CsvFormatter.output(&console, Output::Networks(&["local".to_string()]));
+        assert_eq!(output.stdout(), "name\r\nlocal\r\n");
+    }
+
+    #[test]
+    fn strict_formatter_tests() {
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        console.set_strict(true);
+        let strict = StrictFormatter::new(unwrap!(formatter("json")));
+        strict.message(&console, "Logged in the SAFE Network successfully!");
+        strict.output(&console, Output::Balance("1.5"));
+        assert_eq!(output.stdout(), "{\"balance\":\"1.5\"}\n");
+        assert_eq!(
+            output.stderr(),
+            "Logged in the SAFE Network successfully!\n"
+        );
+    }
+
+    #[test]
+    fn quiet_formatter_tests() {
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        console.set_strict(true);
+        let quiet = StrictFormatter::new(Box::new(QuietFormatter));
+        quiet.message(&console, "Logged in the SAFE Network successfully!");
+        quiet.output(&console, Output::Balance("1.5"));
+        quiet.output(&console, Output::AuthResponse("bAEAAAA"));
+        quiet.output(&console, Output::Networks(&["local".to_string()]));
+        assert_eq!(output.stdout(), "1.5\nbAEAAAA\nlocal\n");
+        assert_eq!(
+            output.stderr(),
+            "Logged in the SAFE Network successfully!\n"
+        );
+    }
+
+    #[test]
+    fn template_formatter_tests() {
+        let apps = authed_apps("SAFE CLI");
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        let formatter = unwrap!(TemplateFormatter::new(
+            "{{app.id}} ({{app.vendor}}): {{permissions._public}}"
+        ));
+        formatter.message(&console, "Logged in the SAFE Network successfully!");
+        formatter.output(&console, Output::AuthedApps(&apps));
+        formatter.output(&console, Output::Networks(&["local".to_string()]));
+        assert_eq!(
+            output.stdout(),
+            "net.maidsafe.cli (MaidSafe.net Ltd): [\"Read\"]\n (): \n"
+        );
+    }
+
+    #[test]
+    fn json_lines_formatter_tests() {
+        let mut apps = authed_apps("SAFE CLI");
+        apps.extend(authed_apps("SAFE\nCLI"));
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let console = Console::new(Box::new(io));
+        JsonLinesFormatter.message(&console, "Logged in the SAFE Network successfully!");
+        JsonLinesFormatter.output(&console, Output::AuthedApps(&apps));
+        JsonLinesFormatter.output(&console, Output::Balance("1.5"));
+
+        let stdout = output.stdout();
+        let lines: Vec<Value> = stdout
+            .lines()
+            .map(|line| unwrap!(serde_json::from_str(line)))
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["app"]["name"], "SAFE CLI");
+        assert_eq!(lines[1]["app"]["name"], "SAFE\nCLI");
+        assert_eq!(lines[1]["permissions"], json!({ "_public": ["Read"] }));
+        assert_eq!(lines[2], json!({ "balance": "1.5" }));
+    }
+
+    #[test]
+    fn yaml_formatter_tests() {
+        let apps = authed_apps("SAFE CLI");
+        let keys = AccountKeys {
+            public_key: "aa".to_string(),
+            owner_key: "bb".to_string(),
+            encryption_key: "cc".to_string(),
+        };
+        let mut outputs = vec![];
+        for out in [Output::AuthedApps(&apps), Output::AccountKeys(&keys)] {
+            let io = CapturedIo::new("");
+            let output = io.output();
+            let console = Console::new(Box::new(io));
+            YamlFormatter.message(&console, "Logged in the SAFE Network successfully!");
+            YamlFormatter.output(&console, out);
+            let value: Value = unwrap!(serde_yaml::from_str(&output.stdout()));
+            outputs.push(value);
+        }
+
+        assert_eq!(
+            outputs[0],
+            json!([{
+                "app": {
+                    "id": "net.maidsafe.cli",
+                    "scope": null,
+                    "name": "SAFE CLI",
+                    "vendor": "MaidSafe.net Ltd"
+                },
+                "permissions": { "_public": ["Read"] }
+            }])
+        );
+        assert_eq!(
+            outputs[1],
+            json!({ "public_key": "aa", "owner_key": "bb", "encryption_key": "cc" })
+        );
+    }
+
+    #[test]
+    fn acc_summary_formatters_tests() {
+        let mut summary = AccountSummary {
+            network: "local".to_string(),
+            balance: "7.5".to_string(),
+            storage_used_percent: Some(25),
+            authed_apps: 2,
+            containers: vec!["_public".to_string(), "_documents".to_string()],
+            last_authorised: Some(1_571_043_900),
+        };
+        let print = |formatter: &dyn OutputFormatter, summary: &AccountSummary| {
+            let io = CapturedIo::new("");
+            let output = io.output();
+            let console = Console::new(Box::new(io));
+            formatter.output(&console, Output::AccountSummary(summary));
+            output.stdout()
+        };
+
+        let table = print(&*unwrap!(formatter("table")), &summary);
+        assert!(table.contains("| Network            | local                   |"));
+        assert!(table.contains("| Containers         | _public                 |"));
+        assert!(table.contains("|                    | _documents              |"));
+        assert!(table.contains("| Storage used       | 25%                     |"));
+        assert!(table.contains("| Last authorisation | 2019-10-14 09:05:00 UTC |"));
+        let plain = "network\tlocal\nbalance\t7.5\nauthed_apps\t2\ncontainers\t_public,_documents\nstorage_used_percent\t25\nlast_authorised\t1571043900\n";
+        assert_eq!(print(&PlainFormatter::default(), &summary), plain);
+        assert_eq!(print(&QuietFormatter, &summary), plain);
+        assert_eq!(
+            print(&PlainFormatter::print0(), &summary),
+            plain.replace('\t', "\u{1f}").replace('\n', "\0")
+        );
+        let record = json!({
+            "network": "local",
+            "balance": "7.5",
+            "storage_used_percent": 25,
+            "authed_apps": 2,
+            "containers": ["_public", "_documents"],
+            "last_authorised": 1_571_043_900,
+        });
+        for name in ["json", "jsonl"].iter() {
+            let json: Value = unwrap!(serde_json::from_str(&print(
+                &*unwrap!(formatter(name)),
+                &summary
+            )));
+            assert_eq!(json, record);
+        }
+        let strict = StrictFormatter::new(unwrap!(formatter("json")));
+        let json: Value = unwrap!(serde_json::from_str(&print(&strict, &summary)));
+        assert_eq!(json, record);
+        let yaml: Value = unwrap!(serde_yaml::from_str(&print(&YamlFormatter, &summary)));
+        assert_eq!(yaml, record);
+        assert_eq!(
+            print(&CsvFormatter, &summary),
+            "authed_apps,balance,containers,last_authorised,network,storage_used_percent\r\n2,7.5,\"[\"\"_public\"\",\"\"_documents\"\"]\",1571043900,local,25\r\n"
+        );
+        let template = unwrap!(TemplateFormatter::new(
+            "{{network}}: {{storage_used_percent}}% used, {{last_authorised}}"
+        ));
+        assert_eq!(print(&template, &summary), "local: 25% used, 1571043900\n");
+
+        // What isn't known yet is told apart from zero
+        summary.storage_used_percent = None;
+        summary.last_authorised = None;
+        let table = print(&*unwrap!(formatter("table")), &summary);
+        assert!(table.contains("| Storage used       | Not known, no balance was recorded"));
+        assert!(table.contains("| Last authorisation | No app was authorised yet"));
+        assert!(print(&PlainFormatter::default(), &summary)
+            .ends_with("storage_used_percent\t\nlast_authorised\t\n"));
+        let json: Value = unwrap!(serde_json::from_str(&print(
+            &*unwrap!(formatter("json")),
+            &summary
+        )));
+        assert_eq!(json["storage_used_percent"], Value::Null);
+        assert_eq!(json["last_authorised"], Value::Null);
+        assert!(print(&CsvFormatter, &summary).ends_with(",,local,\r\n"));
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The policy maintained by someone else, e.g. a family admin or an organisation, pulled with
+// `policy pull <url>`. It's a bundle `{"policy": "<policy>", "signature": "<hex>"}`, the policy
+// being a JSON document the same as the config file's `prompts`, e.g.
+// `{"share_mdata": {"mode": "deny"}, "denylist": ["<app id>"]}`, kept as a string so its
+// signature is checked over its exact text. The bundle is only accepted if signed by the
+// public key pinned in the config file's `policy_source`, which can't come from the bundle.
+//
+// The bundle is stored in the `policies` area of the storage, i.e. `policies/<profile>.json` of
+// the user's app directory by default, and verified
+// again each time it's loaded, so a bundle altered once stored is rejected rather than used.
+// It's merged with the local rules of the config file's `prompts`:
+// - the apps of either denylist are denied,
+// - a kind of request the pulled policy denies is denied,
+// - otherwise the local policy of the kind is used, or the pulled one if there is none.
+// So the pulled policy can only make the local rules stricter, never loosen them.
+
+use super::helpers::{PromptMode, PromptPolicies, PromptPolicy};
+use super::instances::profile_entry;
+use super::receipts::hex_bytes;
+use crate::storage::storage;
+use crate::Error;
+use log::debug;
+use serde::Deserialize;
+use std::time::Duration;
+use threshold_crypto::{PublicKey, Signature, PK_SIZE, SIG_SIZE};
+
+const POLICIES_AREA: &str = "policies";
+// Prefixed to the policy signed, so the signatures can't be mistaken for other ones
+const POLICY_DOMAIN: &str = "safe_auth-policy-v1";
+const PULL_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The `policy_source` settings of the config file, e.g.
+// `{"url": "https://example.com/policy.json", "public_key": "<hex>"}`
+#[derive(Deserialize, Debug, Clone)]
+pub struct PolicySource {
+    // The URL pulled from if `policy pull` isn't given one
+    pub url: Option<String>,
+    // The BLS public key the bundles must be signed with, hex encoded
+    pub public_key: String,
+}
+
+#[derive(Deserialize)]
+struct PolicyBundle {
+    policy: String,
+    signature: String,
+}
+
+// Fetches the bundle, and stores it once verified, returning the URL it was pulled from and
+// its policy
+pub fn pull_policy(
+    source: Option<&PolicySource>,
+    url: Option<&str>,
+    profile: &str,
+) -> Result<(String, PromptPolicies), Error> {
+    let source = source.ok_or_else(|| {
+        Error::InvalidInput(String::from(
+            "No public key to verify the policy with, set the config file's `policy_source`",
+        ))
+    })?;
+    let url = url.or(source.url.as_deref()).ok_or_else(|| {
+        Error::InvalidInput(String::from(
+            "No URL to pull the policy from, give one or set the `url` of the config file's `policy_source`",
+        ))
+    })?;
+
+    let fetch_error =
+        |err: reqwest::Error| Error::NetworkError(format!("Failed to pull the policy: {}", err));
+    let bundle = reqwest::Client::builder()
+        .timeout(PULL_TIMEOUT)
+        .build()
+        .and_then(|client| client.get(url).send())
+        .and_then(|response| response.error_for_status())
+        .and_then(|mut response| response.text())
+        .map_err(fetch_error)?;
+    let policies = verify_bundle(&bundle, &source.public_key)?;
+
+    let entry = profile_entry(profile, "json");
+    storage()?.write(POLICIES_AREA, &entry, bundle.as_bytes())?;
+    debug!("Policy pulled from {} stored as {}", url, entry);
+    Ok((url.to_string(), policies))
+}
+
+// The local policies merged with the pulled ones, if a bundle was pulled. It's only trusted
+// with the public key pinned in the config file, thus ignored without it.
+pub fn merged_policies(
+    local: PromptPolicies,
+    source: Option<&PolicySource>,
+    profile: &str,
+) -> Result<PromptPolicies, Error> {
+    let source = match source {
+        Some(source) => source,
+        None => return Ok(local),
+    };
+    let entry = profile_entry(profile, "json");
+    let bundle = match storage()?.read(POLICIES_AREA, &entry)? {
+        Some(bundle) => String::from_utf8_lossy(&bundle).into_owned(),
+        None => return Ok(local),
+    };
+    let pulled = verify_bundle(&bundle, &source.public_key).map_err(|err| {
+        Error::InvalidInput(format!(
+            "The policy stored as '{}' was rejected, pull it again: {}",
+            entry, err
+        ))
+    })?;
+    Ok(merge_policies(local, pulled))
+}
+
+// The policy of the bundle, if signed by the public key
+fn verify_bundle(bundle: &str, public_key: &str) -> Result<PromptPolicies, Error> {
+    let bundle: PolicyBundle = serde_json::from_str(bundle)
+        .map_err(|err| Error::InvalidInput(format!("The policy bundle is not valid: {}", err)))?;
+    let public_key = hex_bytes(public_key, PK_SIZE)
+        .and_then(|bytes| {
+            let mut public_key = [0; PK_SIZE];
+            public_key.copy_from_slice(&bytes);
+            PublicKey::from_bytes(public_key).ok()
+        })
+        .ok_or_else(|| {
+            Error::InvalidInput(String::from(
+                "The public key of the config file's `policy_source` is not valid",
+            ))
+        })?;
+    let signature = hex_bytes(&bundle.signature, SIG_SIZE)
+        .and_then(|bytes| {
+            let mut signature = [0; SIG_SIZE];
+            signature.copy_from_slice(&bytes);
+            Signature::from_bytes(signature).ok()
+        })
+        .ok_or_else(|| Error::InvalidInput(String::from("The policy's signature is not valid")))?;
+    if !public_key.verify(&signature, signed_content(&bundle.policy)) {
+        return Err(Error::InvalidInput(String::from(
+            "The policy's signature doesn't match, it was altered or not signed by the pinned public key",
+        )));
+    }
+    serde_json::from_str(&bundle.policy)
+        .map_err(|err| Error::InvalidInput(format!("The policy is not valid: {}", err)))
+}
+
+fn signed_content(policy: &str) -> String {
+    format!("{}\n{}", POLICY_DOMAIN, policy)
+}
+
+fn merge_policies(local: PromptPolicies, pulled: PromptPolicies) -> PromptPolicies {
+    let mut denylist = local.denylist;
+    for app_id in pulled.denylist {
+        if !denylist.contains(&app_id) {
+            denylist.push(app_id);
+        }
+    }
+    PromptPolicies {
+        auth: merge_policy(local.auth, pulled.auth),
+        containers: merge_policy(local.containers, pulled.containers),
+        share_mdata: merge_policy(local.share_mdata, pulled.share_mdata),
+        unregistered: merge_policy(local.unregistered, pulled.unregistered),
+        denylist,
+    }
+}
+
+fn merge_policy(local: Option<PromptPolicy>, pulled: Option<PromptPolicy>) -> Option<PromptPolicy> {
+    match (local, pulled) {
+        (_, Some(pulled)) if pulled.mode == Some(PromptMode::Deny) => Some(pulled),
+        (Some(local), _) => Some(local),
+        (None, pulled) => pulled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_policies, signed_content, verify_bundle};
+    use crate::cli::helpers::{PromptMode, PromptPolicies};
+    use crate::helpers::bytes_to_hex;
+    use safe_core::ipc::req::IpcReq;
+    use serde_json::json;
+    use threshold_crypto::SecretKey;
+
+    fn bundle(key: &SecretKey, policy: &str) -> String {
+        let signature = key.sign(signed_content(policy));
+        json!({ "policy": policy, "signature": bytes_to_hex(&signature.to_bytes()) }).to_string()
+    }
+
+    #[test]
+    fn verify_bundle_tests() {
+        let key = SecretKey::random();
+        let public_key = bytes_to_hex(&key.public_key().to_bytes());
+        let policy = r#"{ "share_mdata": { "mode": "deny" }, "denylist": ["net.example.bad"] }"#;
+
+        let policies = unwrap!(verify_bundle(&bundle(&key, policy), &public_key));
+        assert_eq!(policies.denylist, vec!["net.example.bad"]);
+        assert_eq!(unwrap!(policies.share_mdata).mode, Some(PromptMode::Deny));
+
+        // Signed by another key
+        let other = SecretKey::random();
+        assert!(verify_bundle(&bundle(&other, policy), &public_key).is_err());
+
+        // Altered once signed
+        let altered = bundle(&key, policy).replace("net.example.bad", "net.example.good");
+        assert!(verify_bundle(&altered, &public_key).is_err());
+
+        assert!(verify_bundle("{}", &public_key).is_err());
+        assert!(verify_bundle(&bundle(&key, policy), "aabb").is_err());
+    }
+
+    #[test]
+    fn merge_policies_tests() {
+        let local: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "auth": { "mode": "allow" }, "containers": { "timeout": 30 },
+                "denylist": ["net.example.local"] }"#
+        ));
+        let pulled: PromptPolicies = unwrap!(serde_json::from_str(
+            r#"{ "auth": { "mode": "deny" }, "containers": { "mode": "allow" },
+                "share_mdata": { "mode": "per_item" },
+                "denylist": ["net.example.local", "net.example.pulled"] }"#
+        ));
+        let merged = merge_policies(local, pulled);
+
+        // The pulled policy can deny a kind allowed locally, but not allow one
+        assert_eq!(unwrap!(merged.auth.as_ref()).mode, Some(PromptMode::Deny));
+        let containers = unwrap!(merged.containers.as_ref());
+        assert_eq!(containers.mode, None);
+        assert_eq!(containers.timeout, Some(30));
+        assert_eq!(
+            unwrap!(merged.share_mdata.as_ref()).mode,
+            Some(PromptMode::PerItem)
+        );
+        assert!(merged.unregistered.is_none());
+        assert_eq!(
+            merged.denylist,
+            vec!["net.example.local", "net.example.pulled"]
+        );
+        assert!(!merged.denies(&IpcReq::Unregistered(vec![])));
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The prompt of a session's auth requests. The backends need a 'static prompt, so rather than
+// leaking the one of each run, the session's backend owns it, and the session gives its backend
+// `session_prompt`, which decides with the prompt of the innermost `PromptingBackend`
+// authorising on the calling thread. The backends of this crate call the prompt from the
+// thread the authorisation is performed on, as `SharedBackend` requires too, so the prompt is
+// the one of the service's session on each of the service's threads.
+
+use crate::{
+    AccountInfo, AccountKeys, AuthAllowPrompt, AuthBackend, AuthedAppsDiff, AuthedAppsList, Error,
+};
+use log::debug;
+use safe_core::ipc::req::IpcReq;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+thread_local! {
+    // The prompts of the backends authorising on this thread, the innermost last
+    static PROMPTS: RefCell<Vec<Arc<AuthAllowPrompt>>> = RefCell::new(Vec::new());
+}
+
+// Decides on the auth requests with its own prompt, released with it once the session ends
+pub struct PromptingBackend {
+    inner: Box<dyn AuthBackend>,
+    prompt: Arc<AuthAllowPrompt>,
+}
+
+impl PromptingBackend {
+    pub fn new(inner: Box<dyn AuthBackend>, prompt: Arc<AuthAllowPrompt>) -> Self {
+        Self { inner, prompt }
+    }
+}
+
+// The prompt of the backend authorising on this thread, until dropped
+struct Prompting;
+
+impl Prompting {
+    fn start(prompt: &Arc<AuthAllowPrompt>) -> Self {
+        PROMPTS.with(|stack| stack.borrow_mut().push(prompt.clone()));
+        Prompting
+    }
+}
+
+impl Drop for Prompting {
+    fn drop(&mut self) {
+        let _ = PROMPTS.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+// The prompt to give a session's backend. The requests prompted for without a
+// `PromptingBackend` authorising on the thread are denied.
+pub fn session_prompt(req: IpcReq) -> bool {
+    // Not borrowed while the user decides, as the prompt may authorise again
+    let prompt = PROMPTS.with(|stack| stack.borrow().last().cloned());
+    match prompt {
+        Some(prompt) => prompt(req),
+        None => {
+            debug!("The auth request was denied, no session is prompting on this thread");
+            false
+        }
+    }
+}
+
+impl AuthBackend for PromptingBackend {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        let _prompting = Prompting::start(&self.prompt);
+        self.inner.authorise_app(req, allow)
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.inner.authed_apps()
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        self.inner.revoke_app(app_id)
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.inner.acc_info()
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.inner.acc_keys()
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.inner.backup_account()
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.inner.restore_account(backup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{session_prompt, PromptingBackend};
+    use crate::{decode_resp, AuthBackend, FakeBackend, SharedBackend};
+    use safe_core::ipc::resp::IpcResp;
+    use safe_core::ipc::IpcError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    const AUTH_REQ: &str = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+
+    fn denied(resp: &str) -> bool {
+        matches!(
+            decode_resp(resp),
+            Ok((_, IpcResp::Auth(Err(IpcError::AuthDenied))))
+        )
+    }
+
+    #[test]
+    fn prompting_backend_tests() {
+        let prompted = Arc::new(AtomicUsize::new(0));
+        let counter = prompted.clone();
+        let backend = PromptingBackend::new(
+            Box::new(FakeBackend::default()),
+            Arc::new(move |_| {
+                let _ = counter.fetch_add(1, Ordering::SeqCst);
+                false
+            }),
+        );
+        assert!(denied(&unwrap!(
+            backend.authorise_app(AUTH_REQ, &session_prompt)
+        )));
+        assert_eq!(prompted.load(Ordering::SeqCst), 1);
+
+        // Another prompt given decides instead, as with any backend
+        assert!(!denied(
+            &unwrap!(backend.authorise_app(AUTH_REQ, &|_| true))
+        ));
+        assert_eq!(prompted.load(Ordering::SeqCst), 1);
+
+        // Its prompt decides on the threads of a shared backend too, and it's released with it
+        let shared = SharedBackend::new(PromptingBackend::new(
+            Box::new(FakeBackend::default()),
+            Arc::new(|_| true),
+        ));
+        let for_thread = shared.clone();
+        let resp = unwrap!(unwrap!(thread::spawn(
+            move || for_thread.authorise_app(AUTH_REQ, &session_prompt)
+        )
+        .join()));
+        assert!(!denied(&resp));
+        drop(shared);
+        drop(backend);
+        assert_eq!(Arc::strong_count(&prompted), 1);
+
+        // Without a prompting backend the requests are denied
+        let backend = FakeBackend::default();
+        assert!(denied(&unwrap!(
+            backend.authorise_app(AUTH_REQ, &session_prompt)
+        )));
+    }
+}
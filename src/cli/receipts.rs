@@ -0,0 +1,1183 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The receipts of the decisions on the auth requests, so a decision presented later, e.g. by an
+// app or found in a log, can be proven to have been made by this authenticator with
+// `audit verify-receipt`. Each receipt is signed with the key of the profile, generated the
+// first time it's needed, over the SHA-256 hash of the auth request, its kind, the decision and
+// the time it was made. The key is stored in the `keys` area of the storage, i.e.
+// `keys/<profile>.<account id>.key` of the user's app directory by default, only readable by
+// the user and encrypted with a key derived from the credentials of the account, with PBKDF2
+// and the random salt generated for the profile in `keys/<profile>.salt`. The encryption key
+// and the account id are expanded from the key derived with HKDF, so the id tells nothing of
+// the encryption key, and each account used with the profile has its own. Their public keys
+// are listed in `keys/<profile>.pub`, so the receipts can be verified without logging in.
+// The key stored in plain in `keys/<profile>.key` by older versions is encrypted for the first
+// account logged in to, then removed, and the ones sealed with the salt the older versions shared between all
+// the profiles, along with the requests archived with it, are sealed again with the profile's
+// salt once their account is logged in to.
+//
+// The receipts are appended to `receipts/<profile>.jsonl`, one JSON object per line, e.g.
+// `{"request_hash", "kind": "Auth", "decision": "allowed", "timestamp", "public_key",
+// "signature"}`. Every request decided on has one, including the unregistered requests allowed
+// without prompting, so their access can be found in the log.
+//
+// With the `audit` setting `archive_requests` of the config file, the encoded auth request is
+// archived with its receipt too, encrypted with the key derived from the credentials of the
+// account, so a disputed decision can be re-examined later with `audit show --raw`
+// once the account's credentials are given again. Nothing stored in the user's app directory
+// decrypts them. The archived request isn't signed, but once decrypted it must have the hash
+// signed by the receipt.
+//
+// The receipts also record the id of the app which sent the request, unless it's unregistered,
+// so the apps which didn't send any request for a while can be told apart when the permissions
+// are reviewed. It isn't signed either, as it can be checked against the archived request.
+//
+// The decisions of the guardian on the operations it co-approves are recorded too, signed with
+// the key of the profile as the `guardian` approver, next to the user's own decision: the
+// receipts of the auth requests have the hash of the request, and the ones of the other
+// operations, e.g. revoking all the apps, the hash of the encoded `Approval`.
+//
+// The grants imported from a legacy authenticator with `migrate` are recorded as allowed by the
+// `legacy` approver, at the time they were made. Their request wasn't kept, so the hash signed
+// is the one of `legacy:<app id>`.
+
+use super::credentials::LoginDetails;
+use super::helpers::req_app_id;
+use super::instances::profile_entry;
+use crate::helpers::{bytes_to_hex, parse_hex, sk_from_hex};
+use crate::ipc::ReqKind;
+use crate::storage::{storage, Storage};
+use crate::{
+    decode_req, decode_resp, AccountInfo, AccountKeys, AuthAllowPrompt, AuthBackend,
+    AuthedAppsDiff, AuthedAppsList, Error,
+};
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use pbkdf2::pbkdf2;
+use rand::Rng;
+use safe_core::ipc::resp::IpcResp;
+use safe_core::ipc::IpcError;
+use safe_core::utils::{symmetric_decrypt, symmetric_encrypt, SymEncKey, SYM_ENC_KEY_LEN};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use threshold_crypto::serde_impl::SerdeSecret;
+use threshold_crypto::{Ciphertext, PublicKey, SecretKey, Signature, PK_SIZE, SIG_SIZE};
+
+const KEYS_AREA: &str = "keys";
+const RECEIPTS_AREA: &str = "receipts";
+// Prefixed to the content signed, so the signatures can't be mistaken for other ones
+const RECEIPT_DOMAIN: &str = "safe_auth-receipt-v1";
+const LEGACY_APPROVER: &str = "legacy";
+// So guessing the credentials from what was encrypted with the key is slow
+const PROFILE_KEY_ROUNDS: usize = 100_000;
+// The labels of the keys expanded with HKDF from the one derived from the credentials
+const ENCRYPTION_LABEL: &str = "safe_auth-profile-encryption-v2";
+const ACCOUNT_ID_LABEL: &str = "safe_auth-profile-account-id-v2";
+// The key derived by the older versions, with a salt shared by all the profiles, and used both
+// to encrypt and, hashed, as the account id, which was shorter
+const LEGACY_KEY_SALT: &str = "safe_auth-profile-key-v1";
+const LEGACY_KEY_ROUNDS: usize = 20_000;
+const LEGACY_ACCOUNT_ID_LEN: usize = 16;
+
+lazy_static! {
+    // The keys derived from the credentials of the profiles logged in to since the CLI started
+    static ref PROFILE_KEYS: Mutex<HashMap<String, ProfileKey>> = Mutex::new(HashMap::new());
+}
+
+// The keys derived from the credentials of the account used with a profile
+#[derive(Clone)]
+struct ProfileKey {
+    // Encrypts the signing key and the archived requests
+    encryption: SymEncKey,
+    // Tells the accounts used with the profile apart in the names of their entries, hex encoded
+    account_id: String,
+}
+
+impl ProfileKey {
+    fn derive(login_details: &LoginDetails, salt: &[u8]) -> Self {
+        let mut derived = [0; 32];
+        pbkdf2::<Hmac<Sha256>>(
+            &credentials_bytes(login_details),
+            salt,
+            PROFILE_KEY_ROUNDS,
+            &mut derived,
+        );
+        Self {
+            encryption: expand(&derived, ENCRYPTION_LABEL),
+            account_id: bytes_to_hex(&expand(&derived, ACCOUNT_ID_LABEL)[..16]),
+        }
+    }
+}
+
+// The `audit` settings of the config file
+#[derive(Deserialize, Debug, Default)]
+pub struct AuditSettings {
+    /// Whether the encoded auth requests are archived with their receipts
+    #[serde(default)]
+    pub archive_requests: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Receipt {
+    /// The SHA-256 hash of the auth request, hex encoded
+    pub request_hash: String,
+    /// The kind of the auth request, missing from the receipts made before it was recorded
+    /// or of the requests which couldn't be decoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ReqKind>,
+    /// Either "allowed" or "denied"
+    pub decision: String,
+    /// Seconds since the UNIX epoch
+    pub timestamp: u64,
+    /// The public key of the profile which made the decision, hex encoded
+    pub public_key: String,
+    pub signature: String,
+    /// The encoded auth request encrypted symmetrically with the key derived from the credentials
+    /// of the profile, hex encoded, if it was archived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_request: Option<String>,
+    /// The id of the app which sent the request, missing from the receipts made before it was
+    /// recorded or of the unregistered requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    /// Who else than the user made the decision, i.e. "guardian", signed if it's given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approver: Option<String>,
+}
+
+impl Receipt {
+    // The kind is only signed when it's known, so the receipts made before it was recorded
+    // can still be verified, and the approver when there is one
+    fn content(
+        request_hash: &str,
+        kind: Option<ReqKind>,
+        decision: &str,
+        timestamp: u64,
+        approver: Option<&str>,
+    ) -> String {
+        let content = match kind {
+            Some(kind) => format!(
+                "{}\n{}\n{}\n{}\n{}",
+                RECEIPT_DOMAIN, request_hash, kind, decision, timestamp
+            ),
+            None => format!(
+                "{}\n{}\n{}\n{}",
+                RECEIPT_DOMAIN, request_hash, decision, timestamp
+            ),
+        };
+        match approver {
+            Some(approver) => format!("{}\napprover:{}", content, approver),
+            None => content,
+        }
+    }
+
+    // Whether it was signed by its public key
+    pub fn verify(&self) -> Result<(), Error> {
+        let invalid =
+            |what: &str| Error::InvalidInput(format!("The receipt's {} is not valid", what));
+        let public_key = hex_bytes(&self.public_key, PK_SIZE)
+            .and_then(|bytes| {
+                let mut public_key = [0; PK_SIZE];
+                public_key.copy_from_slice(&bytes);
+                PublicKey::from_bytes(public_key).ok()
+            })
+            .ok_or_else(|| invalid("public key"))?;
+        let signature = hex_bytes(&self.signature, SIG_SIZE)
+            .and_then(|bytes| {
+                let mut signature = [0; SIG_SIZE];
+                signature.copy_from_slice(&bytes);
+                Signature::from_bytes(signature).ok()
+            })
+            .ok_or_else(|| invalid("signature"))?;
+        let content = Self::content(
+            &self.request_hash,
+            self.kind,
+            &self.decision,
+            self.timestamp,
+            self.approver.as_deref(),
+        );
+        if public_key.verify(&signature, content) {
+            Ok(())
+        } else {
+            Err(Error::InvalidInput(String::from(
+                "The receipt's signature doesn't match its content, it was altered or not made by this public key",
+            )))
+        }
+    }
+}
+
+// Whether the receipt was signed by one of the keys of the profile, and is the one of the
+// request if it's given
+pub fn verify_receipt(
+    receipt: &Receipt,
+    public_keys: &[String],
+    req: Option<&str>,
+) -> Result<(), Error> {
+    receipt.verify()?;
+    if !public_keys.contains(&receipt.public_key) {
+        return Err(Error::InvalidInput(String::from(
+            "The receipt was not signed by the key of this profile",
+        )));
+    }
+    match req {
+        Some(req) if request_hash(req) != receipt.request_hash => Err(Error::InvalidInput(
+            String::from("The receipt is not the one of this auth request"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// The hex encoded bytes of a key or signature, if it has the size expected
+pub fn hex_bytes(hex: &str, size: usize) -> Option<Vec<u8>> {
+    let bytes = parse_hex(hex);
+    if bytes.len() == size && hex.len() == size * 2 {
+        Some(bytes)
+    } else {
+        None
+    }
+}
+
+pub fn request_hash(req: &str) -> String {
+    bytes_to_hex(&Sha256::digest(req.as_bytes()))
+}
+
+// The latest receipt of the profile whose request hash starts with the id, which must not be
+// the start of the hashes of other requests
+pub fn find_receipt(profile: &str, id: &str) -> Result<Receipt, Error> {
+    let receipts = read_receipts(&*storage()?, profile)?
+        .ok_or_else(|| Error::IoError(format!("The profile '{}' has no receipts yet", profile)))?;
+    let id = id.to_lowercase();
+    let mut found: Option<Receipt> = None;
+    for receipt in receipts {
+        if !receipt.request_hash.starts_with(&id) {
+            continue;
+        }
+        if let Some(previous) = &found {
+            if previous.request_hash != receipt.request_hash {
+                return Err(Error::InvalidInput(format!(
+                    "'{}' is the start of the hashes of several requests, give more of it",
+                    id
+                )));
+            }
+        }
+        found = Some(receipt);
+    }
+    found.ok_or_else(|| {
+        Error::InvalidInput(format!(
+            "No receipt of the profile '{}' has a request hash starting with '{}'",
+            profile, id
+        ))
+    })
+}
+
+// The time of the latest receipt of each app of the profile, in seconds since the UNIX epoch,
+// none if no request was decided on yet
+pub fn last_seen(profile: &str) -> Result<HashMap<String, u64>, Error> {
+    let receipts = read_receipts(&*storage()?, profile)?.unwrap_or_default();
+    let mut last_seen: HashMap<String, u64> = HashMap::new();
+    for receipt in receipts {
+        if let Some(app_id) = receipt.app_id {
+            let seen = last_seen.entry(app_id).or_insert(receipt.timestamp);
+            *seen = (*seen).max(receipt.timestamp);
+        }
+    }
+    Ok(last_seen)
+}
+
+// The receipts of the profile, the oldest first
+pub fn receipts(profile: &str) -> Result<Vec<Receipt>, Error> {
+    Ok(read_receipts(&*storage()?, profile)?.unwrap_or_default())
+}
+
+// The time the latest auth request of the profile was allowed by the user, none if none was
+pub fn last_authorised(profile: &str) -> Result<Option<u64>, Error> {
+    let receipts = read_receipts(&*storage()?, profile)?.unwrap_or_default();
+    Ok(latest_allowed(&receipts))
+}
+
+// The co-approvals of the guardian are not the user's own decisions
+fn latest_allowed(receipts: &[Receipt]) -> Option<u64> {
+    receipts
+        .iter()
+        .filter(|receipt| {
+            receipt.decision == "allowed" && receipt.approver.as_deref() != Some("guardian")
+        })
+        .map(|receipt| receipt.timestamp)
+        .max()
+}
+
+// The receipts of the profile's log, skipping the lines which are not valid receipts, none if
+// it has no log yet
+fn read_receipts(storage: &dyn Storage, profile: &str) -> Result<Option<Vec<Receipt>>, Error> {
+    let log = match storage.read(RECEIPTS_AREA, &profile_entry(profile, "jsonl"))? {
+        Some(log) => log,
+        None => return Ok(None),
+    };
+    let receipts = String::from_utf8_lossy(&log)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(receipt) => Some(receipt),
+            Err(err) => {
+                warn!("Invalid receipt skipped: {}", err);
+                None
+            }
+        })
+        .collect();
+    Ok(Some(receipts))
+}
+
+// Derives the key of the profile from the credentials of its account, once they were given
+pub fn unlock_profile(profile: &str, login_details: &LoginDetails) -> Result<(), Error> {
+    unlock_profile_in(&*storage()?, profile, login_details)
+}
+
+fn unlock_profile_in(
+    storage: &dyn Storage,
+    profile: &str,
+    login_details: &LoginDetails,
+) -> Result<(), Error> {
+    let key = ProfileKey::derive(login_details, &profile_salt(storage, profile)?);
+    // The older key is only derived until the keys it sealed are sealed again
+    if has_legacy_sealed_keys(storage, profile)? {
+        let legacy = legacy_credentials_key(login_details);
+        if let Err(err) = reseal_legacy_key(storage, profile, &legacy, &key) {
+            warn!(
+                "The signing key of the profile '{}' couldn't be sealed again: {}",
+                profile, err
+            );
+        }
+    }
+    if let Ok(mut keys) = PROFILE_KEYS.lock() {
+        let _ = keys.insert(profile.to_string(), key);
+    }
+    Ok(())
+}
+
+// The length of the secret is given, so moving characters of the secret to the password gives
+// another key
+fn credentials_bytes(login_details: &LoginDetails) -> Vec<u8> {
+    format!(
+        "{}:{}{}",
+        login_details.secret.len(),
+        login_details.secret,
+        login_details.password
+    )
+    .into_bytes()
+}
+
+// The HKDF expansion of the key derived from the credentials, a single block being needed
+fn expand(derived: &[u8], label: &str) -> [u8; 32] {
+    // HMAC takes keys of any length
+    let mut mac = unwrap!(Hmac::<Sha256>::new_varkey(derived));
+    mac.input(label.as_bytes());
+    mac.input(&[1]);
+    let mut expanded = [0; 32];
+    expanded.copy_from_slice(&mac.result().code());
+    expanded
+}
+
+// The random salt of the keys derived for the profile, generated the first time it's needed
+fn profile_salt(storage: &dyn Storage, profile: &str) -> Result<Vec<u8>, Error> {
+    let entry = profile_entry(profile, "salt");
+    let salt: [u8; 16] = rand::thread_rng().gen();
+    // Another process may generate it at the same time, the first one is kept
+    let _ = storage.create(KEYS_AREA, &entry, bytes_to_hex(&salt).as_bytes())?;
+    let stored = storage.read(KEYS_AREA, &entry)?.unwrap_or_default();
+    Ok(parse_hex(String::from_utf8_lossy(&stored).trim()))
+}
+
+fn legacy_credentials_key(login_details: &LoginDetails) -> SymEncKey {
+    let mut key = [0; SYM_ENC_KEY_LEN];
+    pbkdf2::<Hmac<Sha256>>(
+        &credentials_bytes(login_details),
+        LEGACY_KEY_SALT.as_bytes(),
+        LEGACY_KEY_ROUNDS,
+        &mut key,
+    );
+    key
+}
+
+// The key of the profile, if its credentials were given
+fn profile_key(profile: &str) -> Result<ProfileKey, Error> {
+    PROFILE_KEYS
+        .lock()
+        .ok()
+        .and_then(|keys| keys.get(profile).cloned())
+        .ok_or_else(|| {
+            Error::CredentialsError(format!(
+                "The credentials of the profile '{}' are needed to decrypt its keys",
+                profile
+            ))
+        })
+}
+
+// The auth request encrypted with the key of the profile, hex encoded
+fn encrypt_request(key: &SymEncKey, req: &str) -> Result<String, Error> {
+    symmetric_encrypt(req.as_bytes(), key, None)
+        .map(|bytes| bytes_to_hex(&bytes))
+        .map_err(|err| Error::AuthenticatorError(format!("Failed to encrypt the request: {}", err)))
+}
+
+// The auth request archived with the receipt, which must be the one it was made for. The
+// requests archived before they were encrypted with the key of the profile are decrypted with
+// its signing key.
+pub fn decrypt_request(
+    profile: &str,
+    signer: Option<&Signer>,
+    receipt: &Receipt,
+) -> Result<String, Error> {
+    let raw_request = receipt.raw_request.as_ref().ok_or_else(|| {
+        Error::InvalidInput(String::from(
+            "The request was not archived with the receipt, `archive_requests` was not enabled when it was made",
+        ))
+    })?;
+    let raw_request = parse_hex(raw_request);
+    let req = symmetric_decrypt(&raw_request, &profile_key(profile)?.encryption)
+        .ok()
+        .or_else(|| signer.and_then(|signer| signer.decrypt_legacy_request(&raw_request)))
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| {
+            Error::InvalidInput(String::from(
+                "The archived request can't be decrypted with the credentials of this profile",
+            ))
+        })?;
+    if request_hash(&req) == receipt.request_hash {
+        Ok(req)
+    } else {
+        Err(Error::InvalidInput(String::from(
+            "The archived request is not the one of the receipt",
+        )))
+    }
+}
+
+// Signs the decisions with the key of a profile
+pub struct Signer {
+    key: SecretKey,
+}
+
+impl Signer {
+    pub fn new(key: SecretKey) -> Self {
+        Self { key }
+    }
+
+    // The key of the profile, if it was generated already, once its credentials were given
+    pub fn load(profile: &str) -> Result<Option<Self>, Error> {
+        Self::load_from(&*storage()?, profile)
+    }
+
+    fn load_from(storage: &dyn Storage, profile: &str) -> Result<Option<Self>, Error> {
+        let profile_key = profile_key(profile)?;
+        if let Some(sealed) = storage.read(KEYS_AREA, &key_entry(profile, &profile_key))? {
+            return Ok(Some(Self::unseal(
+                &sealed,
+                &profile_key.encryption,
+                profile,
+            )?));
+        }
+        match read_legacy_key(storage, profile)? {
+            Some(signer) => {
+                signer.store(storage, profile, &profile_key)?;
+                storage.remove(KEYS_AREA, &profile_entry(profile, "key"))?;
+                info!(
+                    "Signing key of the receipts of the profile '{}' encrypted",
+                    profile
+                );
+                Ok(Some(signer))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn unseal(sealed: &[u8], key: &SymEncKey, profile: &str) -> Result<Self, Error> {
+        let hex = symmetric_decrypt(sealed, key).map_err(|err| {
+            Error::CredentialsError(format!(
+                "The signing key of the profile '{}' can't be decrypted: {}",
+                profile, err
+            ))
+        })?;
+        Ok(Self::new(sk_from_hex(&String::from_utf8_lossy(&hex))?))
+    }
+
+    // The key of the profile, generated if it doesn't have one yet
+    fn for_profile(storage: &dyn Storage, profile: &str) -> Result<Self, Error> {
+        if let Some(signer) = Self::load_from(storage, profile)? {
+            return Ok(signer);
+        }
+        let signer = Self::new(SecretKey::random());
+        signer.store(storage, profile, &profile_key(profile)?)?;
+        info!(
+            "Signing key of the receipts generated for the profile '{}'",
+            profile
+        );
+        Ok(signer)
+    }
+
+    fn store(
+        &self,
+        storage: &dyn Storage,
+        profile: &str,
+        profile_key: &ProfileKey,
+    ) -> Result<(), Error> {
+        let serialised = bincode::serialize(&SerdeSecret(&self.key)).map_err(|err| {
+            Error::AuthenticatorError(format!("Failed to serialise the signing key: {}", err))
+        })?;
+        let sealed = symmetric_encrypt(
+            bytes_to_hex(&serialised).as_bytes(),
+            &profile_key.encryption,
+            None,
+        )
+        .map_err(|err| {
+            Error::AuthenticatorError(format!("Failed to encrypt the signing key: {}", err))
+        })?;
+        storage.write_private(KEYS_AREA, &key_entry(profile, profile_key), &sealed)?;
+        let public_key = self.public_key();
+        if !listed_public_keys(storage, profile)?.contains(&public_key) {
+            storage.append(
+                KEYS_AREA,
+                &profile_entry(profile, "pub"),
+                public_key.as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn public_key(&self) -> String {
+        bytes_to_hex(&self.key.public_key().to_bytes())
+    }
+
+    // The requests used to be archived encrypted with the public key
+    fn decrypt_legacy_request(&self, raw_request: &[u8]) -> Option<Vec<u8>> {
+        bincode::deserialize::<Ciphertext>(raw_request)
+            .ok()
+            .and_then(|ciphertext| self.key.decrypt(&ciphertext))
+    }
+
+    pub fn sign(&self, req: &str, allowed: bool, timestamp: u64) -> Receipt {
+        self.sign_as(req, None, allowed, timestamp)
+    }
+
+    // Signs the decision of someone else than the user, e.g. the guardian
+    pub fn sign_as(
+        &self,
+        req: &str,
+        approver: Option<&str>,
+        allowed: bool,
+        timestamp: u64,
+    ) -> Receipt {
+        let request_hash = request_hash(req);
+        let ipc_req = decode_req(req).ok().map(|(_, ipc_req)| ipc_req);
+        let kind = ipc_req.as_ref().map(ReqKind::of);
+        let app_id = ipc_req
+            .as_ref()
+            .and_then(req_app_id)
+            .map(|app_id| app_id.to_string());
+        let decision = if allowed { "allowed" } else { "denied" };
+        let signature = self.key.sign(Receipt::content(
+            &request_hash,
+            kind,
+            decision,
+            timestamp,
+            approver,
+        ));
+        Receipt {
+            request_hash,
+            kind,
+            decision: decision.to_string(),
+            timestamp,
+            public_key: self.public_key(),
+            signature: bytes_to_hex(&signature.to_bytes()),
+            raw_request: None,
+            app_id,
+            approver: approver.map(str::to_string),
+        }
+    }
+}
+
+// The entry of the signing key of the account the key of the profile was derived from
+fn key_entry(profile: &str, profile_key: &ProfileKey) -> String {
+    profile_entry(profile, &format!("{}.key", profile_key.account_id))
+}
+
+fn legacy_key_entry(profile: &str, legacy: &SymEncKey) -> String {
+    let account_id = bytes_to_hex(&Sha256::digest(legacy)[..8]);
+    profile_entry(profile, &format!("{}.key", account_id))
+}
+
+// Whether the older versions sealed signing keys of the profile, told apart by their shorter
+// account ids
+fn has_legacy_sealed_keys(storage: &dyn Storage, profile: &str) -> Result<bool, Error> {
+    let prefix = profile_entry(profile, "");
+    Ok(storage.names(KEYS_AREA)?.iter().any(|name| {
+        name.strip_prefix(&prefix)
+            .and_then(|name| name.strip_suffix(".key"))
+            .is_some_and(|account_id| account_id.len() == LEGACY_ACCOUNT_ID_LEN)
+    }))
+}
+
+// Seals again the signing key of the account sealed by the older versions, if there is one,
+// and the requests archived with it
+fn reseal_legacy_key(
+    storage: &dyn Storage,
+    profile: &str,
+    legacy: &SymEncKey,
+    profile_key: &ProfileKey,
+) -> Result<(), Error> {
+    let entry = legacy_key_entry(profile, legacy);
+    let sealed = match storage.read(KEYS_AREA, &entry)? {
+        Some(sealed) => sealed,
+        None => return Ok(()),
+    };
+    let signer = Signer::unseal(&sealed, legacy, profile)?;
+    signer.store(storage, profile, profile_key)?;
+    reencrypt_archives(storage, profile, legacy, &profile_key.encryption)?;
+    // Last, so it's done again if it was interrupted
+    storage.remove(KEYS_AREA, &entry)?;
+    info!(
+        "Signing key of the receipts of the profile '{}' sealed with its salt",
+        profile
+    );
+    Ok(())
+}
+
+// The requests archived are not signed, so they can be encrypted again, leaving the other lines
+// of the log as they are
+fn reencrypt_archives(
+    storage: &dyn Storage,
+    profile: &str,
+    legacy: &SymEncKey,
+    key: &SymEncKey,
+) -> Result<(), Error> {
+    let log = profile_entry(profile, "jsonl");
+    let content = match storage.read(RECEIPTS_AREA, &log)? {
+        Some(content) => content,
+        None => return Ok(()),
+    };
+    let mut reencrypted = false;
+    let mut lines = String::new();
+    for line in String::from_utf8_lossy(&content).lines() {
+        let receipt = serde_json::from_str::<Receipt>(line)
+            .ok()
+            .and_then(|mut receipt| {
+                let raw_request = parse_hex(receipt.raw_request.as_ref()?);
+                let req = String::from_utf8(symmetric_decrypt(&raw_request, legacy).ok()?).ok()?;
+                receipt.raw_request = Some(encrypt_request(key, &req).ok()?);
+                serde_json::to_string(&receipt).ok()
+            });
+        match receipt {
+            Some(receipt) => {
+                reencrypted = true;
+                lines.push_str(&receipt);
+            }
+            None => lines.push_str(line),
+        }
+        lines.push('\n');
+    }
+    if reencrypted {
+        storage.write(RECEIPTS_AREA, &log, lines.as_bytes())?;
+    }
+    Ok(())
+}
+
+// The key stored in plain by the older versions, if it wasn't encrypted yet
+fn read_legacy_key(storage: &dyn Storage, profile: &str) -> Result<Option<Signer>, Error> {
+    match storage.read(KEYS_AREA, &profile_entry(profile, "key"))? {
+        Some(hex) => Ok(Some(Signer::new(sk_from_hex(
+            String::from_utf8_lossy(&hex).trim(),
+        )?))),
+        None => Ok(None),
+    }
+}
+
+// The public keys of the profile's signing keys, which can be read without the credentials of
+// the profile
+pub fn profile_public_keys(profile: &str) -> Result<Vec<String>, Error> {
+    public_keys_from(&*storage()?, profile)
+}
+
+fn public_keys_from(storage: &dyn Storage, profile: &str) -> Result<Vec<String>, Error> {
+    let mut public_keys = listed_public_keys(storage, profile)?;
+    if let Some(signer) = read_legacy_key(storage, profile)? {
+        public_keys.push(signer.public_key());
+    }
+    Ok(public_keys)
+}
+
+// The public keys of the keys encrypted
+fn listed_public_keys(storage: &dyn Storage, profile: &str) -> Result<Vec<String>, Error> {
+    Ok(storage
+        .read(KEYS_AREA, &profile_entry(profile, "pub"))?
+        .map(|listed| {
+            String::from_utf8_lossy(&listed)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+// Records a decision on an operation which is not an auth request authorised with a
+// `ReceiptsBackend`, e.g. the guardian's, in the profile's log of receipts
+pub fn record_decision(
+    profile: &str,
+    req: &str,
+    approver: Option<&str>,
+    allowed: bool,
+) -> Result<(), Error> {
+    let storage = storage()?;
+    let receipt = Signer::for_profile(&*storage, profile)?.sign_as(req, approver, allowed, now());
+    append_receipt(&*storage, &profile_entry(profile, "jsonl"), &receipt);
+    Ok(())
+}
+
+// Records the grant of the app made by a legacy authenticator at the time, in the profile's log
+// of receipts
+pub fn record_legacy_grant(profile: &str, app_id: &str, timestamp: u64) -> Result<(), Error> {
+    let storage = storage()?;
+    let signer = Signer::for_profile(&*storage, profile)?;
+    let mut receipt = signer.sign_as(
+        &format!("legacy:{}", app_id),
+        Some(LEGACY_APPROVER),
+        true,
+        timestamp,
+    );
+    receipt.app_id = Some(app_id.to_string());
+    append_receipt(&*storage, &profile_entry(profile, "jsonl"), &receipt);
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+// A receipt which can't be written doesn't fail the request, the app has its response
+fn append_receipt(storage: &dyn Storage, log: &str, receipt: &Receipt) {
+    let result = serde_json::to_vec(receipt)
+        .map_err(|err| err.to_string())
+        .and_then(|line| {
+            storage
+                .append(RECEIPTS_AREA, log, &line)
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        warn!(
+            "The receipt of the decision couldn't be written to '{}': {}",
+            log, err
+        );
+    }
+}
+
+// Signs the decisions on the auth requests authorised with the backend, appending the
+// receipts to the profile's log of receipts, with the requests archived if `archive` is set
+pub struct ReceiptsBackend {
+    inner: Box<dyn AuthBackend>,
+    signer: Signer,
+    storage: Arc<dyn Storage>,
+    log: String,
+    // The key of the profile the requests are archived with, if they are
+    archive: Option<SymEncKey>,
+}
+
+impl ReceiptsBackend {
+    pub fn new(inner: Box<dyn AuthBackend>, profile: &str, archive: bool) -> Result<Self, Error> {
+        let storage = storage()?;
+        Ok(Self {
+            inner,
+            signer: Signer::for_profile(&*storage, profile)?,
+            storage,
+            log: profile_entry(profile, "jsonl"),
+            archive: if archive {
+                Some(profile_key(profile)?.encryption)
+            } else {
+                None
+            },
+        })
+    }
+}
+
+impl AuthBackend for ReceiptsBackend {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        let resp = self.inner.authorise_app(req, allow)?;
+        let allowed = !matches!(
+            decode_resp(&resp),
+            Ok((_, IpcResp::Auth(Err(IpcError::AuthDenied))))
+        );
+        let mut receipt = self.signer.sign(req, allowed, now());
+        if let Some(key) = &self.archive {
+            receipt.raw_request = match encrypt_request(key, req) {
+                Ok(raw_request) => Some(raw_request),
+                Err(err) => {
+                    warn!("The request couldn't be archived with its receipt: {}", err);
+                    None
+                }
+            };
+        }
+        if receipt.kind == Some(ReqKind::Unregistered) {
+            info!(
+                "Unregistered auth request {}, receipt {}",
+                receipt.decision, receipt.request_hash
+            );
+        }
+        append_receipt(&*self.storage, &self.log, &receipt);
+        Ok(resp)
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.inner.authed_apps()
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        self.inner.revoke_app(app_id)
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.inner.acc_info()
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.inner.acc_keys()
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.inner.backup_account()
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.inner.restore_account(backup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        append_receipt, decrypt_request, encrypt_request, key_entry, latest_allowed,
+        legacy_credentials_key, legacy_key_entry, profile_key, profile_salt, public_keys_from,
+        read_receipts, request_hash, unlock_profile_in, verify_receipt, ProfileKey, Receipt,
+        Signer, KEYS_AREA,
+    };
+    use crate::cli::credentials::LoginDetails;
+    use crate::helpers::bytes_to_hex;
+    use crate::ipc::ReqKind;
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID};
+    use crate::{MemoryStorage, Storage};
+    use safe_core::ipc::req::IpcReq;
+    use safe_core::ipc::{encode_msg, IpcMsg};
+    use safe_core::utils::symmetric_encrypt;
+    use threshold_crypto::serde_impl::SerdeSecret;
+    use threshold_crypto::SecretKey;
+
+    #[test]
+    fn receipt_tests() {
+        let signer = Signer::new(SecretKey::random());
+        let receipt = signer.sign("bAAAAAAEXVK4SGAAAAAA", true, 1_571_043_900);
+        assert_eq!(receipt.decision, "allowed");
+        assert_eq!(receipt.request_hash, request_hash("bAAAAAAEXVK4SGAAAAAA"));
+        assert_eq!(receipt.public_key, signer.public_key());
+        unwrap!(receipt.verify());
+
+        let public_keys = vec![signer.public_key()];
+        unwrap!(verify_receipt(&receipt, &public_keys, None));
+        unwrap!(verify_receipt(
+            &receipt,
+            &public_keys,
+            Some("bAAAAAAEXVK4SGAAAAAA")
+        ));
+        assert!(verify_receipt(&receipt, &public_keys, Some("bAAAAAAEXVK4SGAAAAAB")).is_err());
+        // Signed by the key of another profile
+        let other = vec![Signer::new(SecretKey::random()).public_key()];
+        assert!(verify_receipt(&receipt, &other, None).is_err());
+        assert!(verify_receipt(&receipt, &[], None).is_err());
+        // The profile has a key for each of its accounts
+        let both = vec![other[0].clone(), public_keys[0].clone()];
+        unwrap!(verify_receipt(&receipt, &both, None));
+
+        // Any change to what was signed is detected
+        let mut altered = receipt.clone();
+        altered.decision = String::from("denied");
+        assert!(altered.verify().is_err());
+        let mut altered = receipt.clone();
+        altered.timestamp += 1;
+        assert!(altered.verify().is_err());
+        let mut altered = receipt.clone();
+        altered.public_key = Signer::new(SecretKey::random()).public_key();
+        assert!(altered.verify().is_err());
+        let mut altered = receipt;
+        altered.approver = Some(String::from("guardian"));
+        assert!(altered.verify().is_err());
+
+        // The guardian's decision is told apart from the user's
+        let receipt = signer.sign_as(
+            "bAAAAAAEXVK4SGAAAAAA",
+            Some("guardian"),
+            false,
+            1_571_043_900,
+        );
+        assert_eq!(receipt.approver, Some(String::from("guardian")));
+        unwrap!(receipt.verify());
+        let mut altered = receipt;
+        altered.approver = None;
+        assert!(altered.verify().is_err());
+    }
+
+    #[test]
+    fn receipt_kind_tests() {
+        let signer = Signer::new(SecretKey::random());
+        let unregistered = unwrap!(encode_msg(&IpcMsg::Req {
+            req_id: 0,
+            request: IpcReq::Unregistered(vec![]),
+        }));
+        let receipt = signer.sign(&unregistered, true, 1_571_043_900);
+        assert_eq!(receipt.kind, Some(ReqKind::Unregistered));
+        assert_eq!(receipt.app_id, None);
+        unwrap!(receipt.verify());
+        let json = unwrap!(serde_json::to_string(&receipt));
+        assert!(json.contains(r#""kind":"Unregistered""#));
+        assert_eq!(unwrap!(serde_json::from_str::<Receipt>(&json)), receipt);
+
+        // The kind is signed too
+        let mut altered = signer.sign(APP_AUTH_REQ, false, 1_571_043_900);
+        assert_eq!(altered.kind, Some(ReqKind::Auth));
+        assert_eq!(altered.app_id, Some(APP_ID.to_string()));
+        altered.kind = Some(ReqKind::Unregistered);
+        assert!(altered.verify().is_err());
+        altered.kind = None;
+        assert!(altered.verify().is_err());
+    }
+
+    fn login_details(secret: &str, password: &str) -> LoginDetails {
+        LoginDetails {
+            secret: secret.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn archived_request_tests() {
+        let storage = MemoryStorage::new();
+        let signer = Signer::new(SecretKey::random());
+        let mut receipt = signer.sign(APP_AUTH_REQ, true, 1_571_043_900);
+        assert!(decrypt_request("archived", Some(&signer), &receipt).is_err());
+
+        // The key of the profile is only known once its credentials were given
+        let key = ProfileKey::derive(
+            &login_details("secret", "password"),
+            &unwrap!(profile_salt(&storage, "archived")),
+        );
+        receipt.raw_request = Some(unwrap!(encrypt_request(&key.encryption, APP_AUTH_REQ)));
+        assert!(!unwrap!(receipt.raw_request.as_ref()).contains(APP_AUTH_REQ));
+        assert!(decrypt_request("archived", Some(&signer), &receipt).is_err());
+        unwrap!(unlock_profile_in(
+            &storage,
+            "archived",
+            &login_details("secret", "password")
+        ));
+        assert_eq!(
+            unwrap!(decrypt_request("archived", None, &receipt)),
+            APP_AUTH_REQ
+        );
+        // Archiving the request doesn't alter what was signed
+        unwrap!(receipt.verify());
+
+        // Only the credentials of the account can decrypt it
+        unwrap!(unlock_profile_in(
+            &storage,
+            "other",
+            &login_details("secretp", "assword")
+        ));
+        assert!(decrypt_request("other", Some(&signer), &receipt).is_err());
+        // Nor can another request be passed off as the one of the receipt
+        let mut altered = receipt.clone();
+        altered.raw_request = Some(unwrap!(encrypt_request(
+            &key.encryption,
+            "bAAAAAAEXVK4SGAAAAAA"
+        )));
+        assert!(decrypt_request("archived", None, &altered).is_err());
+
+        // The requests archived with the signing key are still decrypted with it
+        let ciphertext = signer.key.public_key().encrypt(APP_AUTH_REQ.as_bytes());
+        receipt.raw_request = Some(bytes_to_hex(&unwrap!(bincode::serialize(&ciphertext))));
+        assert!(decrypt_request("archived", None, &receipt).is_err());
+        assert_eq!(
+            unwrap!(decrypt_request("archived", Some(&signer), &receipt)),
+            APP_AUTH_REQ
+        );
+    }
+
+    #[test]
+    fn stored_receipts_tests() {
+        let storage = MemoryStorage::new();
+        let details = login_details("secret", "password");
+        unwrap!(unlock_profile_in(&storage, "work", &details));
+        unwrap!(unlock_profile_in(&storage, "home", &details));
+        assert!(unwrap!(Signer::load_from(&storage, "work")).is_none());
+        assert!(unwrap!(read_receipts(&storage, "work")).is_none());
+
+        // The key is generated once for the profile
+        let signer = unwrap!(Signer::for_profile(&storage, "work"));
+        let loaded = unwrap!(unwrap!(Signer::load_from(&storage, "work")));
+        assert_eq!(loaded.public_key(), signer.public_key());
+        let other = unwrap!(Signer::for_profile(&storage, "home"));
+        assert_ne!(other.public_key(), signer.public_key());
+
+        let first = signer.sign(APP_AUTH_REQ, true, 1_571_043_900);
+        append_receipt(&storage, "work.jsonl", &first);
+        unwrap!(storage.append("receipts", "work.jsonl", b"not a receipt"));
+        let second = signer.sign("bAAAAAAEXVK4SGAAAAAA", false, 1_571_044_000);
+        append_receipt(&storage, "work.jsonl", &second);
+        assert_eq!(
+            unwrap!(unwrap!(read_receipts(&storage, "work"))),
+            vec![first, second]
+        );
+    }
+
+    #[test]
+    fn latest_allowed_tests() {
+        let signer = Signer::new(SecretKey::random());
+        assert_eq!(latest_allowed(&[]), None);
+
+        let allowed = signer.sign(APP_AUTH_REQ, true, 1_571_043_900);
+        let denied = signer.sign(APP_AUTH_REQ, false, 1_571_044_000);
+        assert_eq!(latest_allowed(std::slice::from_ref(&denied)), None);
+        let receipts = vec![allowed, denied];
+        assert_eq!(latest_allowed(&receipts), Some(1_571_043_900));
+
+        // The guardian's co-approvals are left out
+        let mut receipts = receipts;
+        receipts.push(signer.sign_as(APP_AUTH_REQ, Some("guardian"), true, 1_571_045_000));
+        assert_eq!(latest_allowed(&receipts), Some(1_571_043_900));
+        receipts.push(signer.sign(APP_AUTH_REQ, true, 1_571_046_000));
+        assert_eq!(latest_allowed(&receipts), Some(1_571_046_000));
+    }
+
+    #[test]
+    fn sealed_key_tests() {
+        let storage = MemoryStorage::new();
+        // The key can't be generated without the credentials of the account
+        assert!(Signer::for_profile(&storage, "sealed").is_err());
+
+        unwrap!(unlock_profile_in(
+            &storage,
+            "sealed",
+            &login_details("secret", "password")
+        ));
+        let signer = unwrap!(Signer::for_profile(&storage, "sealed"));
+        let entry = key_entry("sealed", &unwrap!(profile_key("sealed")));
+        let stored = unwrap!(unwrap!(storage.read(KEYS_AREA, &entry)));
+        let serialised = bytes_to_hex(&unwrap!(bincode::serialize(&SerdeSecret(&signer.key))));
+        assert!(!String::from_utf8_lossy(&stored).contains(&serialised));
+        let loaded = unwrap!(unwrap!(Signer::load_from(&storage, "sealed")));
+        assert_eq!(loaded.public_key(), signer.public_key());
+        // The receipts are verified without the credentials
+        assert_eq!(
+            unwrap!(public_keys_from(&storage, "sealed")),
+            vec![signer.public_key()]
+        );
+        assert!(unwrap!(public_keys_from(&storage, "missing")).is_empty());
+
+        // Another account used with the profile has its own key
+        unwrap!(unlock_profile_in(
+            &storage,
+            "sealed",
+            &login_details("secret", "other password")
+        ));
+        assert!(unwrap!(Signer::load_from(&storage, "sealed")).is_none());
+        let other = unwrap!(Signer::for_profile(&storage, "sealed"));
+        assert_ne!(other.public_key(), signer.public_key());
+        assert_eq!(
+            unwrap!(public_keys_from(&storage, "sealed")),
+            vec![signer.public_key(), other.public_key()]
+        );
+        unwrap!(unlock_profile_in(
+            &storage,
+            "sealed",
+            &login_details("secret", "password")
+        ));
+        let loaded = unwrap!(unwrap!(Signer::load_from(&storage, "sealed")));
+        assert_eq!(loaded.public_key(), signer.public_key());
+
+        // The key stored in plain is encrypted once the credentials are given
+        let legacy = Signer::new(SecretKey::random());
+        let serialised = bytes_to_hex(&unwrap!(bincode::serialize(&SerdeSecret(&legacy.key))));
+        unwrap!(storage.write(KEYS_AREA, "legacy.key", serialised.as_bytes()));
+        assert_eq!(
+            unwrap!(public_keys_from(&storage, "legacy")),
+            vec![legacy.public_key()]
+        );
+        assert!(Signer::load_from(&storage, "legacy").is_err());
+        unwrap!(unlock_profile_in(
+            &storage,
+            "legacy",
+            &login_details("secret", "password")
+        ));
+        let loaded = unwrap!(unwrap!(Signer::load_from(&storage, "legacy")));
+        assert_eq!(loaded.public_key(), legacy.public_key());
+        assert_eq!(unwrap!(storage.read(KEYS_AREA, "legacy.key")), None);
+        assert_eq!(
+            unwrap!(public_keys_from(&storage, "legacy")),
+            vec![legacy.public_key()]
+        );
+        let loaded = unwrap!(unwrap!(Signer::load_from(&storage, "legacy")));
+        assert_eq!(loaded.public_key(), legacy.public_key());
+    }
+
+    #[test]
+    fn legacy_sealed_key_tests() {
+        let storage = MemoryStorage::new();
+        let details = login_details("secret", "password");
+
+        // A key sealed, and a request archived, by the older versions
+        let legacy = legacy_credentials_key(&details);
+        let signer = Signer::new(SecretKey::random());
+        let serialised = bytes_to_hex(&unwrap!(bincode::serialize(&SerdeSecret(&signer.key))));
+        let sealed = unwrap!(symmetric_encrypt(serialised.as_bytes(), &legacy, None));
+        let legacy_entry = legacy_key_entry("older", &legacy);
+        unwrap!(storage.write_private(KEYS_AREA, &legacy_entry, &sealed));
+        let mut receipt = signer.sign(APP_AUTH_REQ, true, 1_571_043_900);
+        receipt.raw_request = Some(unwrap!(encrypt_request(&legacy, APP_AUTH_REQ)));
+        append_receipt(&storage, "older.jsonl", &receipt);
+        unwrap!(storage.append("receipts", "older.jsonl", b"not a receipt"));
+
+        // They are sealed again with the salt of the profile once its credentials are given
+        unwrap!(unlock_profile_in(&storage, "older", &details));
+        assert!(unwrap!(storage.read(KEYS_AREA, &legacy_entry)).is_none());
+        let key = unwrap!(profile_key("older"));
+        assert!(unwrap!(storage.read(KEYS_AREA, &key_entry("older", &key))).is_some());
+        let loaded = unwrap!(unwrap!(Signer::load_from(&storage, "older")));
+        assert_eq!(loaded.public_key(), signer.public_key());
+        let receipts = unwrap!(unwrap!(read_receipts(&storage, "older")));
+        assert_eq!(receipts.len(), 1);
+        assert_ne!(receipts[0].raw_request, receipt.raw_request);
+        assert_eq!(
+            unwrap!(decrypt_request("older", None, &receipts[0])),
+            APP_AUTH_REQ
+        );
+        unwrap!(receipts[0].verify());
+        let log = unwrap!(unwrap!(storage.read("receipts", "older.jsonl")));
+        assert!(String::from_utf8_lossy(&log).ends_with("not a receipt\n"));
+
+        // Each profile has its own salt, so the same credentials give other keys
+        unwrap!(unlock_profile_in(&storage, "newer", &details));
+        let other = unwrap!(profile_key("newer"));
+        assert_ne!(other.account_id, key.account_id);
+        assert_ne!(other.encryption, key.encryption);
+        assert_eq!(
+            unwrap!(profile_salt(&storage, "newer")),
+            unwrap!(profile_salt(&storage, "newer"))
+        );
+    }
+}
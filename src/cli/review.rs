@@ -0,0 +1,266 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The reminders of the Authenticator service to review the permissions granted, enabled with
+// the `permission_review` field of the config file, e.g. `{"interval_days": 30}`. Each time the
+// interval elapsed since the last reminder, the apps authorised are summarised, with the ones
+// which didn't send any request for `stale_days` (the interval by default) according to the
+// profile's receipts, as they likely don't need their permissions anymore. The summary is
+// emitted as a `PermissionReview` event, for the front-ends, and written to stderr.
+//
+// The time of the last reminder is stored in the `reviews` area of the storage, i.e.
+// `reviews/<profile>.json` of the user's app directory by default, so the interval goes on
+// across the restarts of the service. The first reminder is given an interval after the
+// service was first run with the reminders enabled.
+
+use super::instances::profile_entry;
+use super::io::WeakConsole;
+use super::receipts::last_seen;
+use crate::storage::{storage, Storage};
+use crate::{AuthBackend, AuthEvent, AuthedAppsList, Error, EventBus, SharedBackend};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const REVIEWS_AREA: &str = "reviews";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+// How often the service checks whether a reminder is due
+const CHECK_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+// The `permission_review` field of the config file
+#[derive(Deserialize, Debug, Clone)]
+pub struct ReviewSettings {
+    pub interval_days: u64,
+    // The apps which didn't send any request for this long are listed as stale
+    pub stale_days: Option<u64>,
+}
+
+impl ReviewSettings {
+    fn stale_days(&self) -> u64 {
+        self.stale_days.unwrap_or(self.interval_days)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ReviewState {
+    // Seconds since the UNIX epoch
+    reminded_at: u64,
+}
+
+// Checks in the background whether a reminder is due, for as long as the service runs
+pub fn spawn_reminders(
+    settings: ReviewSettings,
+    profile: &str,
+    backend: SharedBackend,
+    events: EventBus,
+    console: WeakConsole,
+) -> Result<(), Error> {
+    if settings.interval_days == 0 {
+        return Err(Error::InvalidInput(String::from(
+            "The `interval_days` of the config file's `permission_review` must be at least 1",
+        )));
+    }
+    let storage = storage()?;
+    let entry = profile_entry(profile, "json");
+    let profile = profile.to_string();
+    let _ = thread::spawn(move || loop {
+        let now = now();
+        if is_due(
+            &*storage,
+            &entry,
+            now,
+            settings.interval_days * SECONDS_PER_DAY,
+        ) {
+            remind(&settings, &profile, &backend, &events, &console, now);
+            store_reminded_at(&*storage, &entry, now);
+        }
+        thread::sleep(CHECK_PERIOD);
+    });
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+// Whether the interval elapsed since the last reminder. Without any, the interval starts now.
+fn is_due(storage: &dyn Storage, entry: &str, now: u64, interval_secs: u64) -> bool {
+    let state: Option<ReviewState> = storage
+        .read(REVIEWS_AREA, entry)
+        .ok()
+        .and_then(|content| content)
+        .and_then(|content| serde_json::from_slice(&content).ok());
+    match state {
+        Some(state) => now < state.reminded_at || now - state.reminded_at >= interval_secs,
+        None => {
+            store_reminded_at(storage, entry, now);
+            false
+        }
+    }
+}
+
+fn store_reminded_at(storage: &dyn Storage, entry: &str, now: u64) {
+    let stored = serde_json::to_vec(&ReviewState { reminded_at: now })
+        .map_err(|err| err.to_string())
+        .and_then(|content| {
+            storage
+                .write(REVIEWS_AREA, entry, &content)
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = stored {
+        warn!(
+            "The time of the permission review couldn't be written: {}",
+            err
+        );
+    }
+}
+
+// A reminder which can't be given, e.g. as the network is down, is skipped until the next one
+fn remind(
+    settings: &ReviewSettings,
+    profile: &str,
+    backend: &SharedBackend,
+    events: &EventBus,
+    console: &WeakConsole,
+    now: u64,
+) {
+    let authed_apps = match backend.authed_apps() {
+        Ok(authed_apps) => authed_apps,
+        Err(err) => {
+            warn!("The permission review was skipped: {}", err);
+            return;
+        }
+    };
+    if authed_apps.is_empty() {
+        debug!("No app is authorised, no permission review is needed");
+        return;
+    }
+    let last_seen = last_seen(profile).unwrap_or_else(|err| {
+        warn!(
+            "The receipts couldn't be read for the permission review: {}",
+            err
+        );
+        HashMap::new()
+    });
+    let stale_days = settings.stale_days();
+    let stale = stale_apps(&authed_apps, &last_seen, now, stale_days * SECONDS_PER_DAY);
+    let apps: Vec<String> = authed_apps
+        .iter()
+        .map(|authed| authed.app.id.clone())
+        .collect();
+
+    if let Some(console) = console.upgrade() {
+        errln!(
+            console,
+            "{}",
+            tr!(
+                console,
+                "review-reminder",
+                count = apps.len(),
+                app_ids = apps.join(", ")
+            )
+        );
+        if !stale.is_empty() {
+            errln!(
+                console,
+                "{}",
+                tr!(
+                    console,
+                    "review-stale",
+                    count = stale.len(),
+                    days = stale_days,
+                    app_ids = stale.join(", ")
+                )
+            );
+        }
+    }
+    events.emit(AuthEvent::PermissionReview {
+        apps,
+        stale,
+        stale_days,
+    });
+}
+
+// The ids of the apps authorised which didn't send any request for `stale_secs`, including the
+// ones which never sent any since the receipts record the apps
+fn stale_apps(
+    authed_apps: &[AuthedAppsList],
+    last_seen: &HashMap<String, u64>,
+    now: u64,
+    stale_secs: u64,
+) -> Vec<String> {
+    authed_apps
+        .iter()
+        .filter(|authed| {
+            last_seen
+                .get(&authed.app.id)
+                .is_none_or(|seen| now.saturating_sub(*seen) >= stale_secs)
+        })
+        .map(|authed| authed.app.id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_due, stale_apps, SECONDS_PER_DAY};
+    use crate::storage::MemoryStorage;
+    use crate::AuthedAppsList;
+    use safe_core::ipc::req::AppExchangeInfo;
+    use std::collections::HashMap;
+
+    fn authed(id: &str) -> AuthedAppsList {
+        AuthedAppsList {
+            app: AppExchangeInfo {
+                id: id.to_string(),
+                scope: None,
+                name: id.to_string(),
+                vendor: String::from("Example"),
+            },
+            perms: vec![],
+        }
+    }
+
+    #[test]
+    fn stale_apps_tests() {
+        let now = 100 * SECONDS_PER_DAY;
+        let apps = vec![authed("recent"), authed("old"), authed("never")];
+        let mut last_seen = HashMap::new();
+        let _ = last_seen.insert(String::from("recent"), now - SECONDS_PER_DAY);
+        let _ = last_seen.insert(String::from("old"), now - 40 * SECONDS_PER_DAY);
+        // Apps not authorised anymore are not listed
+        let _ = last_seen.insert(String::from("revoked"), 0);
+
+        assert_eq!(
+            stale_apps(&apps, &last_seen, now, 30 * SECONDS_PER_DAY),
+            vec!["old", "never"]
+        );
+        assert_eq!(
+            stale_apps(&apps, &last_seen, now, 60 * SECONDS_PER_DAY),
+            vec!["never"]
+        );
+    }
+
+    #[test]
+    fn is_due_tests() {
+        let storage = MemoryStorage::new();
+        let entry = "work.json";
+        let interval = 30 * SECONDS_PER_DAY;
+
+        // The interval starts the first time it's checked
+        assert!(!is_due(&storage, entry, 1000, interval));
+        assert!(!is_due(&storage, entry, 1000 + interval - 1, interval));
+        assert!(is_due(&storage, entry, 1000 + interval, interval));
+        // The clock was set back
+        assert!(is_due(&storage, entry, 999, interval));
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The retries of the account creation when the invite token, i.e. the secret key given with
+// `--sk` which pays for the account, can't be used, e.g. as it was already claimed or it was
+// mistyped, so the user can type another one, or get a fresh one, rather than running the whole
+// command again and typing the credentials again. The fresh tokens are fetched from the invite
+// service at the `invite_url` of the config file, or, with the mock network only, preloaded
+// with test-coins. The other errors, e.g. of the network or of a secret already used by an
+// account, are returned right away, as another token wouldn't help.
+//
+// The retries are limited, and each one waits longer than the previous one, so the network
+// and the invite service aren't flooded with account creations, e.g. by a script answering
+// the prompt.
+
+use super::diagnostics::register_secret;
+use super::io::Console;
+use crate::errors::is_safe_key_error;
+use crate::Error;
+use std::thread;
+use std::time::Duration;
+
+pub const MAX_SAFE_KEY_RETRIES: usize = 3;
+// Waited before the first retry, and once more before each of the next ones
+pub const SAFE_KEY_RETRY_DELAY: Duration = Duration::from_secs(5);
+// How long fetching a fresh token from the invite service can take
+const INVITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// A fresh invite token offered at the prompt, given once the user types `answer`
+pub struct FreshToken<'a> {
+    // The word to type, and where the token comes from, as shown in the prompt
+    pub answer: String,
+    pub source: String,
+    pub get: &'a mut dyn FnMut() -> Result<String, Error>,
+}
+
+// Creates the account with the invite token, asking for another one each time it can't be
+// used, and offering the `fresh` one if any.
+pub fn create_with_retries<T>(
+    console: &Console,
+    sk: &str,
+    delay: Duration,
+    create: &mut dyn FnMut(&str) -> Result<T, Error>,
+    mut fresh: Option<FreshToken<'_>>,
+) -> Result<T, Error> {
+    let mut sk = sk.to_string();
+    let mut retries = 0;
+    loop {
+        let err = match create(&sk) {
+            Err(err) if is_safe_key_error(&err) && retries < MAX_SAFE_KEY_RETRIES => err,
+            result => return result,
+        };
+        errln!(
+            console,
+            "{}",
+            tr!(console, "safe-key-rejected", error = err)
+        );
+        let prompt = match &fresh {
+            Some(fresh) => tr!(
+                console,
+                "safe-key-retry-prompt-fresh",
+                answer = fresh.answer.as_str(),
+                source = fresh.source.as_str()
+            ),
+            None => tr!(console, "safe-key-retry-prompt"),
+        };
+        // Failing to read the answer is the same as giving up
+        let answer = console
+            .read_password(&format!("{} ", prompt))
+            .unwrap_or_default();
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Err(err);
+        }
+
+        retries += 1;
+        let wait = delay * retries as u32;
+        if wait > Duration::from_secs(0) {
+            errln!(
+                console,
+                "{}",
+                tr!(console, "safe-key-retrying", seconds = wait.as_secs())
+            );
+            thread::sleep(wait);
+        }
+        sk = match &mut fresh {
+            Some(fresh) if answer.to_lowercase() == fresh.answer.to_lowercase() => (fresh.get)()?,
+            _ => answer.to_string(),
+        };
+        register_secret(&sk);
+    }
+}
+
+// Fetches a fresh invite token from the invite service, which answers a GET of its URL with
+// the hex encoded secret key of a SafeKey able to pay for an account
+#[cfg_attr(feature = "mock-network", allow(dead_code))]
+pub fn fetch_invite(url: &str) -> Result<String, Error> {
+    let fetch_error = |err: reqwest::Error| {
+        Error::NetworkError(format!("Failed to fetch an invite token: {}", err))
+    };
+    let token = reqwest::Client::builder()
+        .timeout(INVITE_TIMEOUT)
+        .build()
+        .and_then(|client| client.get(url).send())
+        .and_then(|response| response.error_for_status())
+        .and_then(|mut response| response.text())
+        .map_err(fetch_error)?;
+    let token = token.trim();
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::NetworkError(format!(
+            "The invite service at {} didn't answer with an invite token",
+            url
+        )));
+    }
+    register_secret(token);
+    Ok(token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_with_retries, fetch_invite, FreshToken, MAX_SAFE_KEY_RETRIES};
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::errors::SAFE_KEY_ERROR;
+    use crate::Error;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    // Only the key "funded" pays for the account
+    fn create(keys: &mut Vec<String>) -> impl FnMut(&str) -> Result<String, Error> + '_ {
+        move |sk| {
+            keys.push(sk.to_string());
+            if sk == "funded" {
+                Ok(sk.to_string())
+            } else {
+                Err(Error::CredentialsError(format!(
+                    "{}: NoSuchBalance",
+                    SAFE_KEY_ERROR
+                )))
+            }
+        }
+    }
+
+    fn fresh_key() -> Result<String, Error> {
+        Ok(String::from("funded"))
+    }
+
+    fn fresh_token(get: &mut dyn FnMut() -> Result<String, Error>) -> Option<FreshToken<'_>> {
+        Some(FreshToken {
+            answer: String::from("f"),
+            source: String::from("the invite service"),
+            get,
+        })
+    }
+
+    #[test]
+    fn create_with_retries_tests() {
+        // Another token typed, then a fresh one
+        let io = CapturedIo::new("spent\nf\n");
+        let output = io.output();
+        let mut keys = vec![];
+        let created = create_with_retries(
+            &Console::new(Box::new(io)),
+            "mistyped",
+            Duration::from_secs(0),
+            &mut create(&mut keys),
+            fresh_token(&mut fresh_key),
+        );
+        assert_eq!(unwrap!(created), "funded");
+        assert_eq!(keys, vec!["mistyped", "spent", "funded"]);
+        assert!(output
+            .stderr()
+            .contains("The secret key can't pay for the account"));
+
+        // Giving up, or retrying too many times, fails with the last error
+        for input in &["\n", "spent\nspent\nspent\nspent\n"] {
+            let io = CapturedIo::new(input);
+            let mut keys = vec![];
+            let created = create_with_retries(
+                &Console::new(Box::new(io)),
+                "spent",
+                Duration::from_secs(0),
+                &mut create(&mut keys),
+                fresh_token(&mut fresh_key),
+            );
+            assert!(created.is_err());
+            assert!(keys.len() <= MAX_SAFE_KEY_RETRIES + 1);
+        }
+
+        // The other errors are not retried
+        let io = CapturedIo::new("funded\n");
+        let mut attempts = 0;
+        let created: Result<(), Error> = create_with_retries(
+            &Console::new(Box::new(io)),
+            "funded",
+            Duration::from_secs(0),
+            &mut |_| {
+                attempts += 1;
+                Err(Error::NetworkError(String::from("Disconnected")))
+            },
+            fresh_token(&mut fresh_key),
+        );
+        assert_eq!(
+            created,
+            Err(Error::NetworkError(String::from("Disconnected")))
+        );
+        assert_eq!(attempts, 1);
+
+        // Without a fresh token to offer, the answers are the tokens typed
+        let io = CapturedIo::new("f\nfunded\n");
+        let output = io.output();
+        let mut keys = vec![];
+        let created = create_with_retries(
+            &Console::new(Box::new(io)),
+            "spent",
+            Duration::from_secs(0),
+            &mut create(&mut keys),
+            None,
+        );
+        assert_eq!(unwrap!(created), "funded");
+        assert_eq!(keys, vec!["spent", "f", "funded"]);
+        assert!(!output.stderr().contains("fresh"));
+    }
+
+    // Serves a single request with the body given
+    fn invite_service(status: &str, body: &str) -> String {
+        let listener = unwrap!(TcpListener::bind("127.0.0.1:0"));
+        let url = format!("http://{}/invite", unwrap!(listener.local_addr()));
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        let _ = thread::spawn(move || {
+            let (mut stream, _) = unwrap!(listener.accept());
+            let mut request = [0; 1024];
+            let _ = unwrap!(stream.read(&mut request));
+            unwrap!(stream.write_all(response.as_bytes()));
+        });
+        url
+    }
+
+    #[test]
+    fn fetch_invite_tests() {
+        let url = invite_service("200 OK", "00a1b2\n");
+        assert_eq!(unwrap!(fetch_invite(&url)), "00a1b2");
+
+        for (status, body) in &[("200 OK", "Come back later"), ("410 Gone", "00a1b2")] {
+            let url = invite_service(status, body);
+            match fetch_invite(&url) {
+                Err(Error::NetworkError(_)) => (), // This should pass
+                other => panic!("Unexpected result: {:?}", other),
+            }
+        }
+    }
+}
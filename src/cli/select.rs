@@ -0,0 +1,242 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The interactive selection of the apps to revoke, with `revoke --interactive`. The authorised
+// apps are listed numbered, and each line typed either (un)selects the apps with the numbers
+// typed, or narrows the list down to the apps fuzzily matching it, i.e. having all of its
+// characters in the same order in their id, name or vendor, the closest matches first.
+// An empty line ends the selection, which then has to be confirmed.
+//
+// It's line based rather than drawn on the terminal, so it works the same on every terminal
+// and with the streams given to `run_with_args`.
+
+use super::io::Console;
+use crate::AuthedAppsList;
+
+// Typed instead of a filter to list all the apps again
+const CLEAR_FILTER: &str = "*";
+
+// The ids of the apps selected, none if the selection isn't confirmed or the input ends
+pub fn select_apps(console: &Console, apps: &[AuthedAppsList]) -> Vec<String> {
+    let mut selected = vec![false; apps.len()];
+    let mut filter = String::new();
+    outln!(
+        console,
+        "{}",
+        tr!(console, "select-apps-help", all = CLEAR_FILTER)
+    );
+    loop {
+        let shown = filter_apps(apps, &filter);
+        if shown.is_empty() {
+            outln!(
+                console,
+                "{}",
+                tr!(console, "select-apps-no-match", filter = filter)
+            );
+        }
+        for (number, index) in shown.iter().enumerate() {
+            let app = &apps[*index].app;
+            outln!(
+                console,
+                "{:>3}) [{}] {} ({}, {})",
+                number + 1,
+                if selected[*index] { 'x' } else { ' ' },
+                app.id,
+                app.name,
+                app.vendor
+            );
+        }
+
+        console.out(format_args!("{} ", tr!(console, "select-apps-prompt")));
+        let line = match console.read_line() {
+            Ok(line) if !line.is_empty() => line,
+            _ => return vec![],
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        match parse_numbers(line) {
+            Some(numbers) => {
+                for number in numbers {
+                    match shown.get(number.wrapping_sub(1)) {
+                        Some(index) => selected[*index] = !selected[*index],
+                        None => outln!(
+                            console,
+                            "{}",
+                            tr!(console, "select-apps-invalid-number", number = number)
+                        ),
+                    }
+                }
+            }
+            None if line == CLEAR_FILTER => filter.clear(),
+            None => filter = line.to_string(),
+        }
+    }
+
+    let app_ids: Vec<String> = apps
+        .iter()
+        .zip(selected)
+        .filter(|(_, selected)| *selected)
+        .map(|(authed_app, _)| authed_app.app.id.clone())
+        .collect();
+    if app_ids.is_empty() {
+        return app_ids;
+    }
+
+    console.out(format_args!(
+        "{} ",
+        tr!(
+            console,
+            "revoke-confirm-prompt",
+            count = app_ids.len(),
+            app_ids = app_ids.join(", ")
+        )
+    ));
+    // Failing to read the answer is the same as not confirming it
+    let answer = console.read_line().unwrap_or_default();
+    let answer = answer.trim().to_lowercase();
+    if answer == "y" || answer == tr!(console, "allow-auth-yes").to_lowercase() {
+        app_ids
+    } else {
+        vec![]
+    }
+}
+
+// The numbers separated by spaces or commas, if the line only has numbers
+pub fn parse_numbers(line: &str) -> Option<Vec<usize>> {
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|number| !number.is_empty())
+        .map(|number| number.parse().ok())
+        .collect()
+}
+
+// The indexes of the apps matching the filter, the closest matches first
+fn filter_apps(apps: &[AuthedAppsList], filter: &str) -> Vec<usize> {
+    let mut matches: Vec<(usize, usize)> = apps
+        .iter()
+        .enumerate()
+        .filter_map(|(index, authed_app)| {
+            let app = &authed_app.app;
+            [&app.id, &app.name, &app.vendor]
+                .iter()
+                .filter_map(|text| fuzzy_score(filter, text))
+                .min()
+                .map(|score| (score, index))
+        })
+        .collect();
+    matches.sort();
+    matches.into_iter().map(|(_, index)| index).collect()
+}
+
+// How far apart the characters of the pattern are in the text, ignoring the case, if they
+// are all found in the same order: 0 for a substring, and `None` for no match
+fn fuzzy_score(pattern: &str, text: &str) -> Option<usize> {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    // The closest match is the shortest window of the text ending with the pattern's
+    // last character, found by matching the pattern backwards from each possible end
+    let mut best: Option<usize> = None;
+    for (end, last) in text.iter().enumerate() {
+        if *last != pattern[pattern.len() - 1] {
+            continue;
+        }
+        let mut remaining = pattern.len() - 1;
+        let mut start = end;
+        while remaining > 0 && start > 0 {
+            start -= 1;
+            if text[start] == pattern[remaining - 1] {
+                remaining -= 1;
+            }
+        }
+        if remaining == 0 {
+            let gaps = end + 1 - start - pattern.len();
+            best = Some(best.map_or(gaps, |best| best.min(gaps)));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{filter_apps, fuzzy_score, parse_numbers, select_apps};
+    use crate::cli::io::{CapturedIo, Console};
+    use crate::AuthedAppsList;
+    use safe_core::ipc::req::AppExchangeInfo;
+
+    fn authed_app(id: &str, name: &str, vendor: &str) -> AuthedAppsList {
+        AuthedAppsList {
+            app: AppExchangeInfo {
+                id: id.to_string(),
+                scope: None,
+                name: name.to_string(),
+                vendor: vendor.to_string(),
+            },
+            perms: vec![],
+        }
+    }
+
+    fn test_apps() -> Vec<AuthedAppsList> {
+        vec![
+            authed_app("net.maidsafe.cli", "SAFE CLI", "MaidSafe.net Ltd"),
+            authed_app("net.maidsafe.browser", "SAFE Browser", "MaidSafe.net Ltd"),
+            authed_app("org.example.chat", "Chat", "Example"),
+        ]
+    }
+
+    #[test]
+    fn fuzzy_score_tests() {
+        assert_eq!(fuzzy_score("", "net.maidsafe.cli"), Some(0));
+        assert_eq!(fuzzy_score("CLI", "net.maidsafe.cli"), Some(0));
+        assert_eq!(fuzzy_score("msc", "net.maidsafe.cli"), Some(7));
+        assert_eq!(fuzzy_score("ilc", "net.maidsafe.cli"), None);
+        assert_eq!(fuzzy_score("brwsr", "net.maidsafe.browser"), Some(2));
+    }
+
+    #[test]
+    fn filter_apps_tests() {
+        let apps = test_apps();
+        assert_eq!(filter_apps(&apps, ""), vec![0, 1, 2]);
+        assert_eq!(filter_apps(&apps, "chat"), vec![2]);
+        // The vendor matches as well, but the name is a closer match
+        assert_eq!(filter_apps(&apps, "safe b"), vec![1]);
+        assert_eq!(filter_apps(&apps, "mscl"), vec![0]);
+        assert!(filter_apps(&apps, "xyz").is_empty());
+        assert_eq!(parse_numbers("1, 3 2"), Some(vec![1, 3, 2]));
+        assert_eq!(parse_numbers("cli 1"), None);
+    }
+
+    #[test]
+    fn select_apps_tests() {
+        let apps = test_apps();
+
+        // Filtered down to the browser, which is selected, then all are listed again
+        let io = CapturedIo::new("browser\n1\n*\n3\n\ny\n");
+        let output = io.output();
+        let selected = select_apps(&Console::new(Box::new(io)), &apps);
+        assert_eq!(selected, vec!["net.maidsafe.browser", "org.example.chat"]);
+        assert!(output.stdout().contains("  1) [x] net.maidsafe.browser"));
+
+        // Selected twice is not selected
+        let io = CapturedIo::new("1 2 2\n7\n\ny\n");
+        let output = io.output();
+        let selected = select_apps(&Console::new(Box::new(io)), &apps);
+        assert_eq!(selected, vec!["net.maidsafe.cli"]);
+        assert!(output.stdout().contains("There is no app number 7"));
+
+        // Not confirmed, and the input ending before the selection is confirmed
+        for input in &["1\n\nn\n", "1\n"] {
+            let io = CapturedIo::new(input);
+            assert!(select_apps(&Console::new(Box::new(io)), &apps).is_empty());
+        }
+    }
+}
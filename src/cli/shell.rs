@@ -0,0 +1,219 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The interactive prompt of `safe_auth shell`, which runs the commands typed on the session it
+// logged in with, so they don't each log in again and ask for the credentials again. Each line
+// is a command: `apps`, `balance`, `auth <req>`, `revoke <app id>...`, `history`, `history clear`,
+// `help` or `exit`. The errors of a command are reported and the prompt goes on, only `exit` or
+// the end of the input ends it. Ctrl+C ends it too, once the command it interrupted returns.
+//
+// The commands typed are kept in the profile's history (see the `history` module), which the
+// `history` command lists and `history clear` empties.
+//
+// The commands are given the same prompt of the auth requests and user verification as the
+// other invocations, and their outputs are in the format given, e.g. with --json.
+
+use super::guardian::Guardian;
+use super::helpers::is_interrupted;
+use super::history::History;
+use super::io::Console;
+use super::output::{Output, OutputFormatter};
+use super::verification::{verify_user, UserVerification};
+use crate::{AuthAllowPrompt, AuthBackend, Error};
+use log::warn;
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Apps,
+    Balance,
+    Auth(String),
+    Revoke(Vec<String>),
+    History,
+    ClearHistory,
+    Help,
+    Exit,
+}
+
+pub fn run_shell(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    prompt: &'static AuthAllowPrompt,
+    verification: Option<UserVerification>,
+    guardian: Option<&Guardian>,
+    history: &mut History,
+) -> Result<(), Error> {
+    outln!(console, "{}", tr!(console, "shell-help"));
+    loop {
+        let line = match console.read_command(&tr!(console, "shell-prompt"), history.commands()) {
+            Ok(line) if !line.is_empty() => line,
+            // The end of the input, e.g. Ctrl+D
+            _ => return Ok(()),
+        };
+        let result = parse_command(&line).and_then(|command| match command {
+            Some(Command::Exit) => Ok(false),
+            Some(Command::ClearHistory) => history.clear().map(|()| true),
+            Some(command) => {
+                // Kept even if the command fails, so it can be fixed and run again
+                if let Err(err) = history.add(&line) {
+                    warn!("Failed to keep the command in the shell's history: {}", err);
+                }
+                if command == Command::History {
+                    for (index, command) in history.commands().iter().enumerate() {
+                        outln!(console, "{:>5}  {}", index + 1, command);
+                    }
+                } else {
+                    run_command(
+                        backend,
+                        console,
+                        output,
+                        prompt,
+                        verification,
+                        guardian,
+                        command,
+                    )?;
+                }
+                Ok(true)
+            }
+            None => Ok(true),
+        });
+        // The operations can't be performed anymore once cancelled
+        if is_interrupted() {
+            return Ok(());
+        }
+        match result {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(err) => errln!(console, "{}", tr!(console, "shell-error", error = err)),
+        }
+    }
+}
+
+fn run_command(
+    backend: &dyn AuthBackend,
+    console: &Console,
+    output: &dyn OutputFormatter,
+    prompt: &'static AuthAllowPrompt,
+    verification: Option<UserVerification>,
+    guardian: Option<&Guardian>,
+    command: Command,
+) -> Result<(), Error> {
+    match command {
+        Command::Apps => output.output(console, Output::AuthedApps(&backend.authed_apps()?)),
+        Command::Balance => output.output(console, Output::Balance(&backend.acc_info()?.balance)),
+        Command::Auth(req) => {
+            let auth_response = backend.authorise_app(&req, prompt)?;
+            output.output(console, Output::AuthResponse(&auth_response));
+        }
+        // The apps after one which couldn't be revoked are not tried, so the user can check why
+        Command::Revoke(app_ids) => {
+            if let Some(guardian) = guardian {
+                guardian.approve_revocations(backend, &app_ids)?;
+            }
+            for app_id in app_ids {
+                verify_user(
+                    verification,
+                    &tr!(console, "verify-revoke-reason", app_id = app_id),
+                )?;
+                backend.revoke_app(&app_id)?;
+                output.message(console, &tr!(console, "app-revoked", app_id = app_id));
+            }
+        }
+        Command::Help => outln!(console, "{}", tr!(console, "shell-help")),
+        // Run by the shell itself
+        Command::History | Command::ClearHistory | Command::Exit => {}
+    }
+    Ok(())
+}
+
+// The command of the line typed, none for an empty line
+fn parse_command(line: &str) -> Result<Option<Command>, Error> {
+    let mut words = line.split_whitespace();
+    let name = match words.next() {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let args: Vec<String> = words.map(String::from).collect();
+    let command = match (name, args.len()) {
+        ("apps", 0) => Command::Apps,
+        ("balance", 0) => Command::Balance,
+        ("auth", 1) => Command::Auth(args[0].clone()),
+        ("revoke", count) if count > 0 => Command::Revoke(args),
+        ("history", 0) => Command::History,
+        ("history", 1) if args[0] == "clear" => Command::ClearHistory,
+        ("help", 0) => Command::Help,
+        ("exit", 0) | ("quit", 0) => Command::Exit,
+        ("auth", _) => {
+            return Err(Error::InvalidInput(String::from(
+                "The auth command takes a single auth request string",
+            )))
+        }
+        ("revoke", _) => {
+            return Err(Error::InvalidInput(String::from(
+                "The revoke command takes the IDs of the applications to revoke",
+            )))
+        }
+        ("history", _) => {
+            return Err(Error::InvalidInput(String::from(
+                "The history command takes no arguments, or 'clear' to clear the history",
+            )))
+        }
+        ("apps", _) | ("balance", _) | ("help", _) | ("exit", _) | ("quit", _) => {
+            return Err(Error::InvalidInput(format!(
+                "The {} command takes no arguments",
+                name
+            )))
+        }
+        _ => {
+            return Err(Error::InvalidInput(format!(
+                "Unknown command '{}', type 'help' for the list of commands",
+                name
+            )))
+        }
+    };
+    Ok(Some(command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_command, Command};
+
+    #[test]
+    fn parse_command_tests() {
+        assert_eq!(unwrap!(parse_command(" \n")), None);
+        assert_eq!(unwrap!(parse_command("apps\n")), Some(Command::Apps));
+        assert_eq!(unwrap!(parse_command("quit")), Some(Command::Exit));
+        assert_eq!(
+            unwrap!(parse_command("auth  bAAAA \n")),
+            Some(Command::Auth(String::from("bAAAA")))
+        );
+        assert_eq!(
+            unwrap!(parse_command("revoke net.maidsafe.cli org.example.chat")),
+            Some(Command::Revoke(vec![
+                String::from("net.maidsafe.cli"),
+                String::from("org.example.chat")
+            ]))
+        );
+        assert_eq!(unwrap!(parse_command("history")), Some(Command::History));
+        assert_eq!(
+            unwrap!(parse_command("history clear")),
+            Some(Command::ClearHistory)
+        );
+
+        for line in &[
+            "auth",
+            "auth a b",
+            "revoke",
+            "balance now",
+            "login",
+            "history all",
+        ] {
+            assert!(parse_command(line).is_err());
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The snapshots of the authorised apps, written by `apps export`, compared by `apps diff`, and
+// re-granted by `apps restore`, e.g. after an account is recovered or migrated. A snapshot is
+// the JSON array of the apps with their permissions, e.g.
+// `[{"app": {"id", "scope", "name", "vendor"}, "perms": [[<container>, [<permission>]]]}]`.
+//
+// Restoring a snapshot sends the authorisation requests the apps would have sent themselves:
+// an auth request for the apps which are not authorised anymore, and a containers request
+// for the ones missing some of the permissions. The permissions the apps have now are kept,
+// so it can be restored again, e.g. if it's interrupted, to only grant the ones still missing.
+
+use crate::{AuthedAppsList, Error};
+use std::fs;
+use std::path::Path;
+
+// The name given instead of a snapshot's file for the apps currently authorised
+pub const LIVE_SNAPSHOT: &str = "live";
+
+pub fn read_snapshot(file: &Path) -> Result<Vec<AuthedAppsList>, Error> {
+    let content = fs::read_to_string(file).map_err(|err| {
+        Error::IoError(format!(
+            "Failed to read the snapshot '{}': {}",
+            file.display(),
+            err
+        ))
+    })?;
+    serde_json::from_str(&content).map_err(|err| {
+        Error::InvalidInput(format!(
+            "The snapshot '{}' is not a valid list of authorised apps: {}",
+            file.display(),
+            err
+        ))
+    })
+}
+
+pub fn write_snapshot(file: &Path, apps: &[AuthedAppsList]) -> Result<(), Error> {
+    let content = serde_json::to_string_pretty(apps).map_err(|err| {
+        Error::AuthenticatorError(format!("Failed to serialise the snapshot: {}", err))
+    })?;
+    fs::write(file, content).map_err(|err| {
+        Error::IoError(format!(
+            "Failed to write the snapshot '{}': {}",
+            file.display(),
+            err
+        ))
+    })
+}
@@ -0,0 +1,185 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The overview of the `status` command: the profile and network used, where the credentials are read from,
+// whether the Authenticator service is running, when the account was last logged in to, and
+// its balance. Nothing is prompted, so it can be run from scripts: the credentials are only
+// read from the providers which don't ask the user, and the balance is only fetched if they
+// provide them.
+//
+// The time of the last login to each network is recorded, by every command which logs in,
+// in the `logins.json` entry of the storage, i.e. the user's app directory by default, e.g. `{"SAFE Network": <seconds>}`.
+
+use super::helpers::network_name;
+use crate::storage::storage;
+use crate::Error;
+use log::debug;
+use safe_nd::Coins;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Name of the entry, outside of any area of the storage, with the time of the last logins
+const LOGINS_FILE_NAME: &str = "logins.json";
+// The highest balance recorded for each profile, in nano coins
+const BALANCES_FILE_NAME: &str = "balances.json";
+// The Authenticator service only listens on the local host, so it responds right away
+const DAEMON_CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Serialize, Debug)]
+pub struct Status {
+    /// The profile whose Authenticator service is looked for
+    pub profile: String,
+    pub network: String,
+    pub config_file: Option<String>,
+    /// The provider the credentials are read from without asking the user, if any has them
+    pub credentials: Option<String>,
+    /// The address the Authenticator service is listening on, if it's running
+    pub daemon: Option<String>,
+    /// Seconds since the UNIX epoch
+    pub last_login: Option<u64>,
+    pub balance: Option<String>,
+}
+
+// The name of the network given with `--network`, or of the default one
+pub fn network_label(network: &Option<String>) -> String {
+    network.clone().unwrap_or_else(network_name)
+}
+
+fn read_logins() -> HashMap<String, u64> {
+    storage()
+        .and_then(|storage| storage.read("", LOGINS_FILE_NAME))
+        .ok()
+        .and_then(|content| content)
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+// Records that the account was logged in to the network now. It's only logged if it fails,
+// the login itself succeeded.
+pub fn record_login(network: &str) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let mut logins = read_logins();
+    let _ = logins.insert(network.to_string(), now);
+    let result = serde_json::to_vec(&logins)
+        .map_err(|err| {
+            Error::AuthenticatorError(format!("Failed to serialise the logins: {}", err))
+        })
+        .and_then(|content| storage()?.write("", LOGINS_FILE_NAME, &content));
+    if let Err(err) = result {
+        debug!("The time of the login couldn't be recorded: {}", err);
+    }
+}
+
+pub fn last_login(network: &str) -> Option<u64> {
+    read_logins().get(network).cloned()
+}
+
+// Records the balance of the profile, returning the share of the balance it was funded with
+// which was spent, in percent, none if it's not known. The highest balance recorded is taken
+// as the one it was funded with, so the share is only known once it was recorded before
+// storing data with it.
+pub fn record_balance(profile: &str, balance: &str) -> Option<u64> {
+    let balance = Coins::from_str(balance).ok()?.as_nano();
+    let mut balances: HashMap<String, u64> = storage()
+        .and_then(|storage| storage.read("", BALANCES_FILE_NAME))
+        .ok()
+        .and_then(|content| content)
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default();
+    let funded = balances.get(profile).cloned().unwrap_or(0).max(balance);
+    if balances.insert(profile.to_string(), funded) != Some(funded) {
+        let result = serde_json::to_vec(&balances)
+            .map_err(|err| {
+                Error::AuthenticatorError(format!("Failed to serialise the balances: {}", err))
+            })
+            .and_then(|content| storage()?.write("", BALANCES_FILE_NAME, &content));
+        if let Err(err) = result {
+            debug!("The balance couldn't be recorded: {}", err);
+        }
+    }
+    used_percent(funded, balance)
+}
+
+fn used_percent(funded: u64, balance: u64) -> Option<u64> {
+    funded
+        .checked_sub(balance)
+        .and_then(|spent| (spent * 100).checked_div(funded))
+}
+
+// The address of the Authenticator service if one is listening on the port
+pub fn daemon_address(port: u16) -> Option<String> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    TcpStream::connect_timeout(&address, DAEMON_CONNECT_TIMEOUT)
+        .ok()
+        .map(|_| format!("http://{}", address))
+}
+
+// Formats seconds since the UNIX epoch as e.g. `2019-10-14 09:05:00 UTC`
+pub fn format_time(secs: u64) -> String {
+    let days = secs / 86_400;
+    let time = secs % 86_400;
+    // Days to civil date, from Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{daemon_address, format_time, used_percent};
+    use std::net::TcpListener;
+
+    #[test]
+    fn format_time_tests() {
+        assert_eq!(format_time(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_time(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_time(1_571_043_900), "2019-10-14 09:05:00 UTC");
+    }
+
+    #[test]
+    fn used_percent_tests() {
+        assert_eq!(used_percent(10_000, 10_000), Some(0));
+        assert_eq!(used_percent(10_000, 7_450), Some(25));
+        assert_eq!(used_percent(10_000, 0), Some(100));
+        // Nothing was recorded with a balance yet
+        assert_eq!(used_percent(0, 0), None);
+    }
+
+    #[test]
+    fn daemon_address_tests() {
+        let listener = unwrap!(TcpListener::bind("127.0.0.1:0"));
+        let port = unwrap!(listener.local_addr()).port();
+        assert_eq!(
+            daemon_address(port),
+            Some(format!("http://127.0.0.1:{}", port))
+        );
+        drop(listener);
+        assert_eq!(daemon_address(port), None);
+    }
+}
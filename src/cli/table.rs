@@ -0,0 +1,353 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The tables printed by the CLI. When built with the `pretty` feature they are the ones of
+// prettytable, otherwise a plain text version with the same API is used, and the styles given
+// to the cells with the `row!` macro, e.g. `row![bFg->"Id", app.id]`, are ignored.
+//
+// The tables can also be rendered in the style given with `--table-style`, and fitted to the
+// terminal's width with `--table-overflow`, wrapping or truncating the widest columns first.
+// They are then rendered from the text of their cells, without the cells' styles.
+
+#[cfg(feature = "pretty")]
+pub use prettytable::{Row, Table};
+use std::env;
+use std::str::FromStr;
+
+// The narrowest a column is made to fit the tables to the terminal's width
+const MIN_COLUMN_WIDTH: usize = 4;
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableStyle {
+    // With borders around all the cells
+    Grid,
+    // The columns aligned with spaces
+    Plain,
+    // GitHub flavoured markdown, the new lines in cells printed as `<br>`
+    Markdown,
+}
+
+impl FromStr for TableStyle {
+    type Err = String;
+
+    fn from_str(style: &str) -> Result<Self, Self::Err> {
+        match style {
+            "grid" => Ok(TableStyle::Grid),
+            "plain" => Ok(TableStyle::Plain),
+            "markdown" => Ok(TableStyle::Markdown),
+            other => Err(format!(
+                "'{}' is not a table style, it must be 'grid', 'plain' or 'markdown'",
+                other
+            )),
+        }
+    }
+}
+
+// What's done with the cells which don't fit in the terminal's width
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    Wrap,
+    Truncate,
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(overflow: &str) -> Result<Self, Self::Err> {
+        match overflow {
+            "wrap" => Ok(Overflow::Wrap),
+            "truncate" => Ok(Overflow::Truncate),
+            other => Err(format!(
+                "'{}' is not a table overflow, it must be 'wrap' or 'truncate'",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TableLayout {
+    pub style: Option<TableStyle>,
+    pub overflow: Option<Overflow>,
+}
+
+impl TableLayout {
+    // Whether the tables are printed as they have always been
+    pub fn is_default(&self) -> bool {
+        self.style.is_none() && self.overflow.is_none()
+    }
+}
+
+// The text of the cells of each row
+#[cfg(feature = "pretty")]
+pub fn table_cells(table: &Table) -> Vec<Vec<String>> {
+    table
+        .row_iter()
+        .map(|row| row.iter().map(|cell| cell.get_content()).collect())
+        .collect()
+}
+
+#[cfg(not(feature = "pretty"))]
+pub fn table_cells(table: &Table) -> Vec<Vec<String>> {
+    table.rows().to_vec()
+}
+
+// The width of the terminal, as set in `COLUMNS` or the one of the stdout's terminal
+pub fn terminal_width() -> Option<usize> {
+    let columns = env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok());
+    columns.or_else(|| {
+        terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| usize::from(width))
+    })
+}
+
+// Renders the rows, fitted into the width if any
+pub fn render(
+    rows: &[Vec<String>],
+    style: TableStyle,
+    width: Option<usize>,
+    overflow: Overflow,
+) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = column_widths(rows, columns);
+    if let Some(width) = width {
+        let borders = match style {
+            TableStyle::Plain => PLAIN_GAP.len() * columns.saturating_sub(1),
+            TableStyle::Grid | TableStyle::Markdown => 3 * columns + 1,
+        };
+        fit_widths(&mut widths, width.saturating_sub(borders));
+    }
+
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            (0..columns)
+                .map(|column| {
+                    let cell = row.get(column).map_or("", String::as_str);
+                    let lines = fit_cell(cell, widths[column], overflow, width.is_some());
+                    match style {
+                        TableStyle::Markdown => lines.join("<br>").replace('|', "\\|"),
+                        TableStyle::Grid | TableStyle::Plain => lines.join("\n"),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let widths = column_widths(&rows, columns);
+
+    let separator = |junction: &str| {
+        let dashes: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+        format!("{}{}{}\n", junction, dashes.join(junction), junction)
+    };
+    let mut text = String::new();
+    if style == TableStyle::Grid {
+        text += &separator("+");
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let cells: Vec<Vec<&str>> = row.iter().map(|cell| cell.lines().collect()).collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        for line in 0..height {
+            let parts: Vec<String> = cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(lines, width)| pad(lines.get(line).unwrap_or(&""), *width))
+                .collect();
+            match style {
+                TableStyle::Plain => text += parts.join(PLAIN_GAP).trim_end(),
+                TableStyle::Grid | TableStyle::Markdown => {
+                    text += &format!("| {} |", parts.join(" | "))
+                }
+            }
+            text += "\n";
+        }
+        match style {
+            TableStyle::Grid => text += &separator("+"),
+            TableStyle::Markdown if index == 0 => text += &separator("|"),
+            _ => {}
+        }
+    }
+    text
+}
+
+// Columns are separated by this in the plain style
+const PLAIN_GAP: &str = "  ";
+
+fn column_widths(rows: &[Vec<String>], columns: usize) -> Vec<usize> {
+    let mut widths = vec![0; columns];
+    for row in rows.iter() {
+        for (column, cell) in row.iter().enumerate() {
+            let width = cell.lines().map(|line| line.chars().count()).max();
+            widths[column] = widths[column].max(width.unwrap_or(0));
+        }
+    }
+    widths
+}
+
+// Narrows the widest columns until they all fit in the width available
+fn fit_widths(widths: &mut [usize], available: usize) {
+    while widths.iter().sum::<usize>() > available {
+        let widest = widths
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, width)| **width)
+            .map(|(column, _)| column);
+        match widest {
+            Some(column) if widths[column] > MIN_COLUMN_WIDTH => widths[column] -= 1,
+            _ => break,
+        }
+    }
+}
+
+// The lines of the cell, wrapped or truncated to the width if it's being fitted
+fn fit_cell(cell: &str, width: usize, overflow: Overflow, fitted: bool) -> Vec<String> {
+    let mut lines = vec![];
+    for line in cell.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if !fitted || chars.len() <= width || width == 0 {
+            lines.push(line.to_string());
+            continue;
+        }
+        match overflow {
+            Overflow::Wrap => {
+                for chunk in chars.chunks(width) {
+                    lines.push(chunk.iter().collect());
+                }
+            }
+            Overflow::Truncate => {
+                let mut truncated: String = chars[..width - 1].iter().collect();
+                truncated.push(ELLIPSIS);
+                lines.push(truncated);
+            }
+        }
+    }
+    lines
+}
+
+// Pads the text with spaces up to the width, in characters
+fn pad(text: &str, width: usize) -> String {
+    format!("{:width$}", text, width = width)
+}
+
+#[cfg(not(feature = "pretty"))]
+pub use self::plain::{Row, Table};
+
+#[cfg(not(feature = "pretty"))]
+macro_rules! row {
+    (@cells [$($cells:expr),*]) => {
+        vec![$($cells),*]
+    };
+    (@cells [$($cells:expr),*] $style:ident -> $cell:expr $(, $($rest:tt)*)?) => {
+        row!(@cells [$($cells,)* $cell.to_string()] $($($rest)*)?)
+    };
+    (@cells [$($cells:expr),*] $cell:expr $(, $($rest:tt)*)?) => {
+        row!(@cells [$($cells,)* $cell.to_string()] $($($rest)*)?)
+    };
+    ($($cells:tt)*) => {
+        row!(@cells [] $($cells)*)
+    };
+}
+
+#[cfg(not(feature = "pretty"))]
+mod plain {
+    use super::{render, Overflow, TableStyle};
+    use std::io::{self, Write};
+
+    pub type Row = Vec<String>;
+
+    #[derive(Default)]
+    pub struct Table {
+        rows: Vec<Vec<String>>,
+    }
+
+    impl Table {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn add_row(&mut self, row: Row) {
+            self.rows.push(row);
+        }
+
+        pub fn rows(&self) -> &[Vec<String>] {
+            &self.rows
+        }
+
+        // Each column is as wide as its widest line, cells spanning several lines
+        // are printed on as many lines, with the rest of cells of the row left empty
+        pub fn print(&self, out: &mut dyn Write) -> io::Result<()> {
+            write!(
+                out,
+                "{}",
+                render(&self.rows, TableStyle::Plain, None, Overflow::Wrap)
+            )
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::Table;
+
+        #[test]
+        fn plain_table_tests() {
+            let mut table = Table::new();
+            table.add_row(row![bFg->"Id", bFg->"Permissions"]);
+            table.add_row(row!["net.maidsafe.cli", "_public: Read\n_videos: Insert"]);
+            let mut output = Vec::new();
+            unwrap!(table.print(&mut output));
+            assert_eq!(
+                unwrap!(String::from_utf8(output)),
+                "Id                Permissions\nnet.maidsafe.cli  _public: Read\n                  _videos: Insert\n"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Overflow, TableStyle};
+
+    fn rows() -> Vec<Vec<String>> {
+        vec![
+            vec![String::from("Id"), String::from("Permissions")],
+            vec![
+                String::from("net.maidsafe.cli"),
+                String::from("_public: Read|Insert\n_videos: Insert"),
+            ],
+        ]
+    }
+
+    #[test]
+    fn render_tests() {
+        assert_eq!(
+            render(&rows(), TableStyle::Markdown, None, Overflow::Wrap),
+            "| Id               | Permissions                              |\n\
+             |------------------|------------------------------------------|\n\
+             | net.maidsafe.cli | _public: Read\\|Insert<br>_videos: Insert |\n"
+        );
+        // Fitted in 30 characters, the widest columns narrowed first
+        assert_eq!(
+            render(&rows(), TableStyle::Grid, Some(30), Overflow::Truncate),
+            "+--------------+-------------+\n\
+             | Id           | Permissions |\n\
+             +--------------+-------------+\n\
+             | net.maidsaf… | _public: R… |\n\
+             |              | _videos: I… |\n\
+             +--------------+-------------+\n"
+        );
+        assert_eq!(
+            render(&rows(), TableStyle::Plain, Some(24), Overflow::Wrap),
+            "Id           Permissions\n\
+             net.maidsaf  _public: Re\n\
+             e.cli        ad|Insert\n\
+             \x20            _videos: In\n\
+             \x20            sert\n"
+        );
+    }
+}
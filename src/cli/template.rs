@@ -0,0 +1,136 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The templates given with `--format`, e.g. `'{{app.id}}\t{{app.vendor}}'`. A template is
+// text with fields between double braces, each the path of a value in the JSON record being
+// printed, and the escapes `\t`, `\n` and `\\`, which are hard to type in a shell argument.
+// The strings are printed as they are, the other values as JSON, and the missing ones as
+// nothing, so the same template can be used for records which don't all have the field.
+
+use crate::Error;
+use serde_json::Value;
+
+#[derive(Debug, PartialEq)]
+enum Part {
+    Text(String),
+    // The keys to follow from the record, e.g. `["app", "id"]`
+    Field(Vec<String>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self, Error> {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut rest = template;
+        while !rest.is_empty() {
+            if rest.starts_with("{{") {
+                let end = rest.find("}}").ok_or_else(|| {
+                    Error::InvalidInput(format!(
+                        "The format '{}' has a '{{{{' which is not closed",
+                        template
+                    ))
+                })?;
+                let path = rest[2..end].trim();
+                if path.is_empty() {
+                    return Err(Error::InvalidInput(format!(
+                        "The format '{}' has an empty field",
+                        template
+                    )));
+                }
+                if !text.is_empty() {
+                    parts.push(Part::Text(text.split_off(0)));
+                }
+                parts.push(Part::Field(path.split('.').map(String::from).collect()));
+                rest = &rest[end + 2..];
+                continue;
+            }
+
+            let mut chars = rest.chars();
+            let c = chars.next().unwrap_or_default();
+            let escaped = match (c, chars.clone().next()) {
+                ('\\', Some('t')) => Some('\t'),
+                ('\\', Some('n')) => Some('\n'),
+                ('\\', Some('\\')) => Some('\\'),
+                _ => None,
+            };
+            match escaped {
+                Some(escaped) => {
+                    text.push(escaped);
+                    let _ = chars.next();
+                }
+                None => text.push(c),
+            }
+            rest = chars.as_str();
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn render(&self, record: &Value) -> String {
+        let mut rendered = String::new();
+        for part in self.parts.iter() {
+            match part {
+                Part::Text(text) => rendered += text,
+                Part::Field(path) => {
+                    let value = path.iter().try_fold(record, |value, key| match value {
+                        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                        _ => value.get(key),
+                    });
+                    match value {
+                        None | Some(Value::Null) => {}
+                        Some(Value::String(string)) => rendered += string,
+                        Some(other) => rendered += &other.to_string(),
+                    }
+                }
+            }
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Template;
+    use crate::Error;
+    use serde_json::json;
+
+    #[test]
+    fn template_tests() {
+        let record = json!({
+            "app": { "id": "net.maidsafe.cli", "name": "SAFE CLI", "scope": null },
+            "permissions": { "_public": ["Read"] },
+            "containers": ["_public", "_music"],
+            "count": 2
+        });
+        let render = |template: &str| unwrap!(Template::parse(template)).render(&record);
+
+        assert_eq!(
+            render("{{app.id}} {{ app.name }}"),
+            "net.maidsafe.cli SAFE CLI"
+        );
+        assert_eq!(render("{{app.id}}\\t{{count}}\\n"), "net.maidsafe.cli\t2\n");
+        assert_eq!(render("{{permissions._public}}"), "[\"Read\"]");
+        assert_eq!(render("{{containers.1}}"), "_music");
+        assert_eq!(render("[{{app.scope}}{{app.vendor}}]"), "[]");
+        assert_eq!(render("C:\\\\safe {x}"), "C:\\safe {x}");
+
+        for invalid in &["{{app.id", "{{ }}"] {
+            match Template::parse(invalid) {
+                Err(Error::InvalidInput(_)) => (),
+                other => panic!("Unexpected result: {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The toast notifications of Windows 10 and later, raised by the Authenticator service for the
+// auth requests its prompt waits on when the config file's `attention` sets `toast`. They have
+// Allow and Deny buttons, so the request can be decided without switching to the terminal,
+// whose prompt keeps waiting meanwhile, the first of the two answers deciding on it.
+//
+// The toasts are raised with PowerShell, as notifications of the PowerShell app, so nothing
+// needs to be installed. Their buttons open a `safe-auth:decide/<profile>/<id>/<decision>` URI,
+// the profile being hex encoded. The service registers the URI scheme for the user, to run
+// `safe_auth decide <uri>`, which posts the decision to `/decide/<id>/<decision>` of the
+// service of the profile, with its token. The toast is removed once the request is decided.
+
+use super::io::Console;
+use crate::helpers::{bytes_to_hex, parse_hex};
+use crate::{Error, PendingDecision, PendingDecisions};
+
+const URI_PREFIX: &str = "safe-auth:decide/";
+// The group of the toasts in the notification center, each one tagged with its request's ID
+// in hex, as the tags are limited to 16 characters
+#[cfg_attr(not(windows), allow(dead_code))]
+const TOAST_GROUP: &str = "safe_auth";
+
+// Raises the toasts of the requests waiting, which are decided on with the decisions given
+#[derive(Clone)]
+pub struct Toasts {
+    profile: String,
+    decisions: PendingDecisions,
+}
+
+// The request waiting, whose toast is removed once dropped
+pub struct Toast {
+    decision: PendingDecision,
+}
+
+impl Toasts {
+    pub fn new(profile: &str, decisions: PendingDecisions) -> Self {
+        register_uri_scheme();
+        Self {
+            profile: profile.to_string(),
+            decisions,
+        }
+    }
+
+    // The request waiting, the text of its toast being the description given
+    pub fn raise(&self, console: &Console, description: &str) -> Toast {
+        let decision = self.decisions.add();
+        let xml = toast_xml(
+            &tr!(console, "toast-title"),
+            description,
+            &[
+                (
+                    tr!(console, "toast-allow"),
+                    decision_uri(&self.profile, decision.id(), true),
+                ),
+                (
+                    tr!(console, "toast-deny"),
+                    decision_uri(&self.profile, decision.id(), false),
+                ),
+            ],
+        );
+        show_toast(&xml, decision.id());
+        Toast { decision }
+    }
+}
+
+impl Toast {
+    pub fn decision(&mut self) -> &mut PendingDecision {
+        &mut self.decision
+    }
+}
+
+impl Drop for Toast {
+    fn drop(&mut self) {
+        remove_toast(self.decision.id());
+    }
+}
+
+// The URI opened by a button of the toast
+fn decision_uri(profile: &str, id: u64, allowed: bool) -> String {
+    format!(
+        "{}{}/{}/{}",
+        URI_PREFIX,
+        bytes_to_hex(profile.as_bytes()),
+        id,
+        if allowed { "allow" } else { "deny" }
+    )
+}
+
+// The profile, the ID of the request and whether it's allowed, of a URI opened by a button
+pub fn parse_decision_uri(uri: &str) -> Result<(String, u64, bool), Error> {
+    let invalid = || Error::InvalidInput(format!("The decision URI '{}' is not valid", uri));
+    let parts: Vec<&str> = uri
+        .trim_end_matches('/')
+        .strip_prefix(URI_PREFIX)
+        .ok_or_else(invalid)?
+        .split('/')
+        .collect();
+    match parts.as_slice() {
+        [profile, id, decision] => {
+            let profile = String::from_utf8(parse_hex(profile)).map_err(|_| invalid())?;
+            let id = id.parse().map_err(|_| invalid())?;
+            let allowed = parse_decision(decision)?;
+            Ok((profile, id, allowed))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+// Whether the decision, `allow` or `deny`, allows the request
+fn parse_decision(decision: &str) -> Result<bool, Error> {
+    match decision {
+        "allow" => Ok(true),
+        "deny" => Ok(false),
+        _ => Err(Error::InvalidInput(format!(
+            "The decision must be 'allow' or 'deny', not '{}'",
+            decision
+        ))),
+    }
+}
+
+// The toast, its texts being escaped as they include the ones of the app, which chooses them
+fn toast_xml(title: &str, text: &str, buttons: &[(String, String)]) -> String {
+    let actions: String = buttons
+        .iter()
+        .map(|(content, uri)| {
+            format!(
+                r#"<action content="{}" activationType="protocol" arguments="{}"/>"#,
+                escape_xml(content),
+                escape_xml(uri)
+            )
+        })
+        .collect();
+    format!(
+        r#"<toast scenario="reminder"><visual><binding template="ToastGeneric"><text>{}</text><text>{}</text></binding></visual><actions>{}</actions></toast>"#,
+        escape_xml(title),
+        escape_xml(text),
+        actions
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control())
+        .map(|c| match c {
+            '&' => String::from("&amp;"),
+            '<' => String::from("&lt;"),
+            '>' => String::from("&gt;"),
+            '"' => String::from("&quot;"),
+            '\'' => String::from("&apos;"),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::TOAST_GROUP;
+    use log::debug;
+    use std::env;
+    use std::process::{Command, Stdio};
+
+    // The ID of the PowerShell app, which the toasts are raised as
+    const POWERSHELL_APP_ID: &str =
+        r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+    const URI_SCHEME_KEY: &str = r"HKCU\Software\Classes\safe-auth";
+    const LOAD_WINRT: &str = "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null; [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime] | Out-Null;";
+
+    // Runs the script without waiting for it, the XML of the toast, if any, being passed in
+    // the environment so it needs no quoting
+    fn powershell(script: &str, xml: Option<&str>) {
+        let mut command = Command::new("powershell");
+        let _ = command
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if let Some(xml) = xml {
+            let _ = command.env("SAFE_AUTH_TOAST", xml);
+        }
+        if let Err(err) = command.spawn() {
+            debug!("PowerShell couldn't be run for the toast: {}", err);
+        }
+    }
+
+    pub fn show_toast(xml: &str, id: u64) {
+        let script = format!(
+            "{} $xml = New-Object Windows.Data.Xml.Dom.XmlDocument; $xml.LoadXml($env:SAFE_AUTH_TOAST); $toast = New-Object Windows.UI.Notifications.ToastNotification $xml; $toast.Tag = '{:x}'; $toast.Group = '{}'; [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}').Show($toast)",
+            LOAD_WINRT, id, TOAST_GROUP, POWERSHELL_APP_ID
+        );
+        powershell(&script, Some(xml));
+    }
+
+    pub fn remove_toast(id: u64) {
+        let script = format!(
+            "{} [Windows.UI.Notifications.ToastNotificationManager]::History.Remove('{:x}', '{}', '{}')",
+            LOAD_WINRT, id, TOAST_GROUP, POWERSHELL_APP_ID
+        );
+        powershell(&script, None);
+    }
+
+    // The `safe-auth:` URIs are opened with this executable, for the user only, so it needs
+    // no elevation
+    pub fn register_uri_scheme() {
+        let exe = match env::current_exe() {
+            Ok(exe) => exe,
+            Err(err) => {
+                debug!("The safe-auth: URI scheme couldn't be registered: {}", err);
+                return;
+            }
+        };
+        let command_key = format!(r"{}\shell\open\command", URI_SCHEME_KEY);
+        let command = format!(r#""{}" decide "%1""#, exe.display());
+        let entries: [(&str, Option<&str>, &str); 3] = [
+            (URI_SCHEME_KEY, None, "URL:safe_auth decisions"),
+            (URI_SCHEME_KEY, Some("URL Protocol"), ""),
+            (&command_key, None, &command),
+        ];
+        for (key, name, data) in entries.iter() {
+            let mut reg = Command::new("reg");
+            let _ = reg.args(["add", key]);
+            let _ = match name {
+                Some(name) => reg.args(["/v", name]),
+                None => reg.arg("/ve"),
+            };
+            let result = reg
+                .args(["/d", data, "/f"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+            if let Err(err) = result {
+                debug!("The safe-auth: URI scheme couldn't be registered: {}", err);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+use self::windows::{register_uri_scheme, remove_toast, show_toast};
+
+// There are no toasts on the other platforms, the terminal's prompt decides alone
+#[cfg(not(windows))]
+fn show_toast(_xml: &str, _id: u64) {}
+
+#[cfg(not(windows))]
+fn remove_toast(_id: u64) {}
+
+#[cfg(not(windows))]
+fn register_uri_scheme() {}
+
+#[cfg(test)]
+mod tests {
+    use super::{decision_uri, parse_decision_uri, toast_xml};
+
+    #[test]
+    fn decision_uri_tests() {
+        let uri = decision_uri("work/laptop", 42, true);
+        assert_eq!(uri, "safe-auth:decide/776f726b2f6c6170746f70/42/allow");
+        assert_eq!(
+            unwrap!(parse_decision_uri(&uri)),
+            (String::from("work/laptop"), 42, true)
+        );
+        // As opened by the browsers, with a trailing slash
+        assert_eq!(
+            unwrap!(parse_decision_uri(&format!(
+                "{}/",
+                decision_uri("default", 7, false)
+            ))),
+            (String::from("default"), 7, false)
+        );
+
+        for uri in &[
+            "safe-auth:decide/64/42/maybe",
+            "safe-auth:decide/64/forty-two/allow",
+            "safe-auth:decide/64/42",
+            "safe-auth:revoke/64/42/allow",
+            "42",
+        ] {
+            assert!(parse_decision_uri(uri).is_err(), "{}", uri);
+        }
+    }
+
+    #[test]
+    fn toast_xml_tests() {
+        let xml = toast_xml(
+            "Request",
+            "'<b>Chat</b>' & \"co\"\u{7}",
+            &[(String::from("Allow"), decision_uri("a&b", 1, true))],
+        );
+        assert_eq!(
+            xml,
+            r#"<toast scenario="reminder"><visual><binding template="ToastGeneric"><text>Request</text><text>&apos;&lt;b&gt;Chat&lt;/b&gt;&apos; &amp; &quot;co&quot;</text></binding></visual><actions><action content="Allow" activationType="protocol" arguments="safe-auth:decide/612662/1/allow"/></actions></toast>"#
+        );
+    }
+}
@@ -0,0 +1,523 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The `update` command. The newest release is looked up in the GitHub releases of the repo,
+// and its archive for the current target is only installed if it matches the SHA-256
+// checksum published along with it, as `<archive name>.sha256`, and is signed by the release
+// key pinned in the binary. The detached signature is published as `<archive name>.sig`, the
+// hex encoded BLS signature of `safe_auth-release-v1\n<archive name>\n<hex SHA-256>`, so
+// neither a compromised release page nor the archive of another target passes for the release.
+// The public key is pinned when building with `SAFE_AUTH_RELEASE_PUBLIC_KEY`, the builds
+// without one can't update themselves.
+//
+// A profile can also opt in, with the `version_check` field of its config file, to be told on
+// startup when a newer release is available. The check is done at most once per interval, and
+// the time of the last one is cached in the user's app directory.
+
+use super::io::Console;
+use crate::Error;
+use serde::Deserialize;
+
+// Hours between the checks for a new version on startup, unless set in the config file
+#[cfg(not(feature = "mock-network"))]
+const DEFAULT_CHECK_INTERVAL_HOURS: u64 = 24;
+
+// The `version_check` field of the config file, e.g. `{"enabled": true, "interval_hours": 48}`
+// The checks are disabled with the mock network, which leaves the settings unused
+#[derive(Deserialize, Debug, Default)]
+#[cfg_attr(feature = "mock-network", allow(dead_code))]
+pub struct VersionCheckSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    pub interval_hours: Option<u64>,
+}
+
+#[cfg(not(feature = "mock-network"))]
+impl VersionCheckSettings {
+    fn interval_secs(&self) -> u64 {
+        self.interval_hours.unwrap_or(DEFAULT_CHECK_INTERVAL_HOURS) * 60 * 60
+    }
+}
+
+#[cfg(feature = "mock-network")]
+pub fn check_new_version(_console: &Console, _settings: &VersionCheckSettings) {}
+
+#[cfg(feature = "mock-network")]
+pub fn update_application(console: &Console, _check_only: bool) -> Result<(), Error> {
+    outln!(console, "{}", tr!(console, "update-not-supported"));
+    Ok(())
+}
+
+#[cfg(not(feature = "mock-network"))]
+pub use self::release::{check_new_version, update_application};
+
+#[cfg(not(feature = "mock-network"))]
+mod release {
+    use super::{Console, Error, VersionCheckSettings};
+    use crate::cli::receipts::hex_bytes;
+    use crate::storage::storage;
+    use log::debug;
+    use self_update::backends::github::{Release, ReleaseAsset, ReleaseList};
+    use self_update::{version, ArchiveKind, Compression, Download, Extract, Move};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use threshold_crypto::{PublicKey, Signature, PK_SIZE, SIG_SIZE};
+
+    const REPO_OWNER: &str = "maidsafe";
+    const REPO_NAME: &str = "safe-authenticator-cli";
+    const CHECKSUM_SUFFIX: &str = ".sha256";
+    const SIGNATURE_SUFFIX: &str = ".sig";
+    // Prefixed to the content signed, so the signatures can't be mistaken for other ones
+    const RELEASE_DOMAIN: &str = "safe_auth-release-v1";
+    // The BLS public key the releases are signed with, hex encoded
+    const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("SAFE_AUTH_RELEASE_PUBLIC_KEY");
+    // Name of the entry, within the root area of the storage, caching the last new version check
+    const VERSION_CHECK_FILE_NAME: &str = "version_check.json";
+    // The startup is not delayed any longer than this if GitHub is slow to respond
+    const VERSION_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub(super) struct VersionCheckCache {
+        /// Seconds since the UNIX epoch
+        pub checked_at: u64,
+        /// The newest release found, if newer than the version which checked it
+        pub latest: Option<String>,
+    }
+
+    // If the last check, if any, is older than the interval. A cache from the
+    // future, e.g. after the clock was changed, doesn't prevent checking again.
+    pub(super) fn is_due(cache: Option<&VersionCheckCache>, now: u64, interval_secs: u64) -> bool {
+        match cache {
+            Some(cache) => now < cache.checked_at || now - cache.checked_at >= interval_secs,
+            None => true,
+        }
+    }
+
+    // Tells the user, on stderr so the output of the command is not altered, when a newer
+    // release is available. Nothing is reported if the check fails, it's only logged.
+    pub fn check_new_version(console: &Console, settings: &VersionCheckSettings) {
+        if !settings.enabled {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let storage = match storage() {
+            Ok(storage) => storage,
+            Err(err) => {
+                debug!("Skipping the new version check: {}", err);
+                return;
+            }
+        };
+        let cache: Option<VersionCheckCache> = storage
+            .read("", VERSION_CHECK_FILE_NAME)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content?).ok());
+        if !is_due(cache.as_ref(), now, settings.interval_secs()) {
+            debug!("Skipping the new version check, last one was done recently");
+            return;
+        }
+
+        let current = cargo_crate_version!();
+        let (sender, receiver) = mpsc::channel();
+        let _ = thread::spawn(move || {
+            let result = self_update::get_target()
+                .map_err(update_error)
+                .and_then(|target| newest_release(&target, current));
+            let _ = sender.send(result);
+        });
+        let latest = match receiver.recv_timeout(VERSION_CHECK_TIMEOUT) {
+            Ok(Ok(release)) => release.map(|release| release.version().to_string()),
+            Ok(Err(err)) => {
+                debug!("Failed to check for a new version: {}", err);
+                return;
+            }
+            Err(_) => {
+                debug!("Timed out checking for a new version");
+                return;
+            }
+        };
+
+        if let Some(latest) = &latest {
+            errln!(
+                console,
+                "{}",
+                tr!(console, "update-notice", latest = latest)
+            );
+        }
+        let cache = VersionCheckCache {
+            checked_at: now,
+            latest,
+        };
+        let stored = serde_json::to_vec(&cache)
+            .map_err(|err| err.to_string())
+            .and_then(|content| {
+                storage
+                    .write("", VERSION_CHECK_FILE_NAME, &content)
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = stored {
+            debug!("Failed to cache the new version check: {}", err);
+        }
+    }
+
+    pub fn update_application(console: &Console, check_only: bool) -> Result<(), Error> {
+        let public_key = match RELEASE_PUBLIC_KEY {
+            Some(public_key) => public_key,
+            None if check_only => "",
+            None => {
+                return Err(Error::InvalidInput(String::from(
+                    "This build has no release key pinned to verify the releases with, it can't update itself",
+                )))
+            }
+        };
+        outln!(console, "{}", tr!(console, "update-checking"));
+        let current = cargo_crate_version!();
+        let target = self_update::get_target().map_err(update_error)?;
+        let release = match newest_release(&target, current)? {
+            Some(release) => release,
+            None => {
+                outln!(
+                    console,
+                    "{}",
+                    tr!(console, "update-up-to-date", current = current)
+                );
+                return Ok(());
+            }
+        };
+
+        if check_only {
+            outln!(
+                console,
+                "{}",
+                tr!(
+                    console,
+                    "update-available",
+                    latest = release.version(),
+                    current = current
+                )
+            );
+            return Ok(());
+        }
+
+        let archive = release.asset_for(&target).ok_or_else(|| {
+            Error::NetworkError(format!(
+                "Release {} has no binaries for the '{}' target",
+                release.version(),
+                target
+            ))
+        })?;
+        let checksum_name = format!("{}{}", archive.name, CHECKSUM_SUFFIX);
+        let checksum = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == checksum_name)
+            .ok_or_else(|| {
+                Error::NetworkError(format!(
+                    "Release {} doesn't publish the checksum of '{}', it won't be installed",
+                    release.version(),
+                    archive.name
+                ))
+            })?;
+        let signature_name = format!("{}{}", archive.name, SIGNATURE_SUFFIX);
+        let signature = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == signature_name)
+            .ok_or_else(|| {
+                Error::NetworkError(format!(
+                    "Release {} doesn't publish the signature of '{}', it won't be installed",
+                    release.version(),
+                    archive.name
+                ))
+            })?;
+
+        let tmp_dir = env::temp_dir().join(format!("safe_auth-update-{}", release.version()));
+        fs::create_dir_all(&tmp_dir).map_err(|err| {
+            Error::IoError(format!("Failed to create the download folder: {}", err))
+        })?;
+        let published = Published {
+            checksum,
+            signature,
+            public_key,
+        };
+        let result = install(console, &release, archive, &published, &tmp_dir);
+        if let Err(err) = fs::remove_dir_all(&tmp_dir) {
+            debug!("Failed to remove the download folder: {}", err);
+        }
+        result?;
+
+        outln!(
+            console,
+            "{}",
+            tr!(console, "update-done", version = release.version())
+        );
+        Ok(())
+    }
+
+    // The newest release, if it's newer than the current version
+    fn newest_release(target: &str, current: &str) -> Result<Option<Release>, Error> {
+        let releases = ReleaseList::configure()
+            .repo_owner(REPO_OWNER)
+            .repo_name(REPO_NAME)
+            .with_target(target)
+            .build()
+            .and_then(|list| list.fetch())
+            .map_err(update_error)?;
+        debug!("Found releases: {:#?}", releases);
+
+        let mut newest: Option<Release> = None;
+        for release in releases {
+            let newer = match &newest {
+                Some(newest) => version::bump_is_greater(newest.version(), release.version()),
+                None => version::bump_is_greater(current, release.version()),
+            };
+            if newer.map_err(update_error)? {
+                newest = Some(release);
+            }
+        }
+        Ok(newest)
+    }
+
+    // What the archive of the release is verified with
+    struct Published<'a> {
+        checksum: &'a ReleaseAsset,
+        signature: &'a ReleaseAsset,
+        public_key: &'a str,
+    }
+
+    fn install(
+        console: &Console,
+        release: &Release,
+        archive: ReleaseAsset,
+        published: &Published,
+        tmp_dir: &Path,
+    ) -> Result<(), Error> {
+        outln!(
+            console,
+            "{}",
+            tr!(console, "update-downloading", version = release.version())
+        );
+        let mut checksum = Vec::new();
+        download(&published.checksum.download_url, &mut checksum)?;
+        let mut signature = Vec::new();
+        download(&published.signature.download_url, &mut signature)?;
+        let archive_path = tmp_dir.join(&archive.name);
+        let mut archive_file = fs::File::create(&archive_path).map_err(|err| {
+            Error::IoError(format!("Failed to create the downloaded file: {}", err))
+        })?;
+        download(&archive.download_url, &mut archive_file)?;
+
+        let downloaded = fs::read(&archive_path).map_err(|err| {
+            Error::IoError(format!("Failed to read the downloaded file: {}", err))
+        })?;
+        verify_checksum(&downloaded, &checksum)?;
+        debug!("Checksum of '{}' verified", archive.name);
+        verify_signature(&archive.name, &downloaded, &signature, published.public_key)?;
+        debug!("Signature of '{}' verified", archive.name);
+
+        let bin_name = if cfg!(windows) {
+            "safe_auth.exe"
+        } else {
+            "safe_auth"
+        };
+        let kind = if archive.name.ends_with(".zip") {
+            ArchiveKind::Zip
+        } else {
+            ArchiveKind::Tar(Some(Compression::Gz))
+        };
+        Extract::from_source(&archive_path)
+            .archive(kind)
+            .extract_file(tmp_dir, bin_name)
+            .map_err(update_error)?;
+
+        let current_exe = env::current_exe().map_err(|err| {
+            Error::IoError(format!("Failed to find the current executable: {}", err))
+        })?;
+        Move::from_source(&tmp_dir.join(bin_name))
+            .replace_using_temp(&tmp_dir.join(format!("{}.old", bin_name)))
+            .to_dest(&current_exe)
+            .map_err(update_error)
+    }
+
+    fn download<W: std::io::Write>(url: &str, dest: &mut W) -> Result<(), Error> {
+        Download::from_url(url)
+            .show_progress(true)
+            .download_to(dest)
+            .map_err(update_error)
+    }
+
+    fn update_error(err: self_update::errors::Error) -> Error {
+        Error::NetworkError(format!("Error performing update: {}", err))
+    }
+
+    // The checksum files have the format of `sha256sum`, i.e. the hex encoded checksum
+    // followed by the file name, but only the checksum is required
+    pub(super) fn verify_checksum(downloaded: &[u8], published: &[u8]) -> Result<(), Error> {
+        let published = String::from_utf8_lossy(published);
+        let expected = published.split_whitespace().next().unwrap_or("");
+        let actual = sha256_hex(downloaded);
+        if expected.to_lowercase() == actual {
+            Ok(())
+        } else {
+            Err(Error::NetworkError(format!(
+                "The downloaded release doesn't match its published checksum (expected '{}', found '{}'), it won't be installed",
+                expected, actual
+            )))
+        }
+    }
+
+    // The signature file has the hex encoded signature, by the release key, of the archive's
+    // name and checksum
+    pub(super) fn verify_signature(
+        archive_name: &str,
+        downloaded: &[u8],
+        published: &[u8],
+        public_key: &str,
+    ) -> Result<(), Error> {
+        let public_key = hex_bytes(public_key, PK_SIZE)
+            .and_then(|bytes| {
+                let mut public_key = [0; PK_SIZE];
+                public_key.copy_from_slice(&bytes);
+                PublicKey::from_bytes(public_key).ok()
+            })
+            .ok_or_else(|| {
+                Error::InvalidInput(String::from(
+                    "The release key pinned in this build is not valid",
+                ))
+            })?;
+        let signature = hex_bytes(String::from_utf8_lossy(published).trim(), SIG_SIZE)
+            .and_then(|bytes| {
+                let mut signature = [0; SIG_SIZE];
+                signature.copy_from_slice(&bytes);
+                Signature::from_bytes(signature).ok()
+            })
+            .ok_or_else(|| {
+                Error::NetworkError(format!(
+                    "The published signature of '{}' is not valid, it won't be installed",
+                    archive_name
+                ))
+            })?;
+        if public_key.verify(&signature, signed_content(archive_name, downloaded)) {
+            Ok(())
+        } else {
+            Err(Error::NetworkError(format!(
+                "The downloaded release '{}' is not signed by the release key, it won't be installed",
+                archive_name
+            )))
+        }
+    }
+
+    pub(super) fn signed_content(archive_name: &str, archive: &[u8]) -> String {
+        format!(
+            "{}\n{}\n{}",
+            RELEASE_DOMAIN,
+            archive_name,
+            sha256_hex(archive)
+        )
+    }
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        Sha256::digest(bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+#[cfg(all(test, not(feature = "mock-network")))]
+mod tests {
+    use super::release::{
+        is_due, signed_content, verify_checksum, verify_signature, VersionCheckCache,
+    };
+    use super::VersionCheckSettings;
+    use crate::helpers::bytes_to_hex;
+    use crate::Error;
+    use threshold_crypto::SecretKey;
+
+    #[test]
+    fn verify_checksum_tests() {
+        // A checksum which is not the one of the content downloaded
+        let published = b"2f2bd2db0c3bd4e5c02029c19b3b2be2c9c8e1fe8b3b5cf1f3b0dbcb8ef2bd0c  safe_auth-0.3.1-x86_64-unknown-linux-gnu.tar.gz\n";
+        match verify_checksum(b"safe_auth\n", published) {
+            Err(Error::NetworkError(description)) => assert!(description.contains("checksum")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let empty = b"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        unwrap!(verify_checksum(b"", empty));
+        unwrap!(verify_checksum(b"", &empty.to_ascii_uppercase()));
+        assert!(verify_checksum(b"", b"").is_err());
+    }
+
+    #[test]
+    fn verify_signature_tests() {
+        let release_key = SecretKey::random();
+        let public_key = bytes_to_hex(&release_key.public_key().to_bytes());
+        let name = "safe_auth-0.3.1-x86_64-unknown-linux-gnu.tar.gz";
+        let sign = |name: &str, archive: &[u8]| {
+            let signature = release_key.sign(signed_content(name, archive));
+            format!("{}\n", bytes_to_hex(&signature.to_bytes())).into_bytes()
+        };
+        let signature = sign(name, b"safe_auth\n");
+        unwrap!(verify_signature(
+            name,
+            b"safe_auth\n",
+            &signature,
+            &public_key
+        ));
+
+        // Another archive, or the one of another target
+        match verify_signature(name, b"safe_auth!\n", &signature, &public_key) {
+            Err(Error::NetworkError(description)) => assert!(description.contains("signed")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        let other_target = "safe_auth-0.3.1-x86_64-pc-windows-gnu.zip";
+        let signature = sign(other_target, b"safe_auth\n");
+        assert!(verify_signature(name, b"safe_auth\n", &signature, &public_key).is_err());
+
+        // Signed by another key
+        let other_key = bytes_to_hex(&SecretKey::random().public_key().to_bytes());
+        let signature = sign(name, b"safe_auth\n");
+        assert!(verify_signature(name, b"safe_auth\n", &signature, &other_key).is_err());
+        assert!(verify_signature(name, b"safe_auth\n", b"", &public_key).is_err());
+        match verify_signature(name, b"safe_auth\n", &signature, "") {
+            Err(Error::InvalidInput(description)) => assert!(description.contains("pinned")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn version_check_tests() {
+        let settings: VersionCheckSettings = unwrap!(serde_json::from_str("{}"));
+        assert!(!settings.enabled);
+        let settings: VersionCheckSettings = unwrap!(serde_json::from_str(
+            r#"{"enabled": true, "interval_hours": 48}"#
+        ));
+        assert!(settings.enabled);
+        assert_eq!(settings.interval_secs(), 48 * 60 * 60);
+
+        let day = 24 * 60 * 60;
+        let cache = VersionCheckCache {
+            checked_at: 1_000_000,
+            latest: Some("0.4.0".to_string()),
+        };
+        assert!(is_due(None, 1_000_000, day));
+        assert!(!is_due(Some(&cache), 1_000_000 + day - 1, day));
+        assert!(is_due(Some(&cache), 1_000_000 + day, day));
+        // The clock was set back since the last check
+        assert!(is_due(Some(&cache), 999_999, day));
+    }
+}
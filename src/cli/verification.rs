@@ -0,0 +1,210 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The verification of the user required by a profile, with the `verify_user` field of its
+// config file, before an auth request is allowed or an app is revoked: once the request is
+// allowed, e.g. at the prompt, the system asks the user to prove who they are, and the request
+// is denied, or the app not revoked, unless they do.
+//
+// - `touch_id`: the LocalAuthentication dialog of macOS, with Touch ID or the user's password
+// - `polkit`: the authentication dialog of the Linux desktop, asking for the user's password,
+//   through polkit's `pkcheck` and the action defined in `resources/net.maidsafe.safe-auth.policy`
+
+use super::doctor::find_program;
+use crate::Error;
+use serde::Deserialize;
+use std::process::{self, Command};
+
+// The polkit action checked, which has to be installed in `/usr/share/polkit-1/actions/`
+const POLKIT_ACTION: &str = "net.maidsafe.safe-auth.verify-user";
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserVerification {
+    TouchId,
+    Polkit,
+}
+
+// The verifications available in this build
+pub fn available_verifications() -> Vec<&'static str> {
+    let mut verifications = vec![];
+    if cfg!(target_os = "macos") {
+        verifications.push("touch_id");
+    }
+    if cfg!(target_os = "linux") {
+        verifications.push("polkit");
+    }
+    verifications
+}
+
+impl UserVerification {
+    // Fails if the verification can't be done on this system, so a profile requiring it
+    // is not used without it
+    pub fn ensure_available(self) -> Result<(), Error> {
+        match self {
+            UserVerification::TouchId if cfg!(target_os = "macos") => Ok(()),
+            UserVerification::TouchId => Err(Error::InvalidInput(String::from(
+                "The config file requires verifying the user with Touch ID, which is only available on macOS",
+            ))),
+            UserVerification::Polkit if !cfg!(target_os = "linux") => Err(Error::InvalidInput(
+                String::from(
+                    "The config file requires verifying the user with polkit, which is only available on Linux",
+                ),
+            )),
+            UserVerification::Polkit => match find_program("pkcheck") {
+                Some(_) => Ok(()),
+                None => Err(Error::InvalidInput(String::from(
+                    "The config file requires verifying the user with polkit, but its 'pkcheck' command was not found",
+                ))),
+            },
+        }
+    }
+
+    // Asks the system to verify the user, the reason being shown in its dialog
+    pub fn verify(self, reason: &str) -> Result<(), Error> {
+        match self {
+            UserVerification::TouchId => local_authentication::evaluate(reason).map_err(|err| {
+                Error::PolicyDenied(format!(
+                    "The user couldn't be verified with Touch ID: {}",
+                    err
+                ))
+            }),
+            UserVerification::Polkit => polkit_check(reason).map_err(|err| {
+                Error::PolicyDenied(format!(
+                    "The user couldn't be verified with polkit: {}",
+                    err
+                ))
+            }),
+        }
+    }
+}
+
+// Verifies the user if the profile requires it
+pub fn verify_user(verification: Option<UserVerification>, reason: &str) -> Result<(), Error> {
+    match verification {
+        Some(verification) => verification.verify(reason),
+        None => Ok(()),
+    }
+}
+
+// Checks if this process is authorised for the action, the dialog of the desktop's
+// authentication agent asking for the user's password. The reason is substituted in the
+// action's message.
+fn polkit_check(reason: &str) -> Result<(), String> {
+    let output = Command::new("pkcheck")
+        .args(["--action-id", POLKIT_ACTION, "--process"])
+        .arg(process::id().to_string())
+        .args(["--allow-user-interaction", "--detail", "reason", reason])
+        .output()
+        .map_err(|err| format!("pkcheck couldn't be run: {}", err))?;
+    match output.status.code() {
+        Some(0) => Ok(()),
+        Some(2) => Err(String::from(
+            "no authentication agent is running, e.g. in an SSH session, it can be started with `pkttyagent`",
+        )),
+        Some(3) => Err(String::from("the authentication dialog was dismissed")),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            match stderr.trim() {
+                "" => Err(String::from("not authorised")),
+                stderr => Err(format!("not authorised ({})", stderr)),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod local_authentication {
+    use block::ConcreteBlock;
+    use objc::runtime::{Object, BOOL, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::sync::mpsc;
+
+    // `LAPolicyDeviceOwnerAuthentication`, i.e. Touch ID, or the user's password if it's
+    // not available or it fails
+    const DEVICE_OWNER_AUTHENTICATION: i64 = 2;
+    const NS_UTF8_STRING_ENCODING: usize = 4;
+
+    #[link(name = "LocalAuthentication", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "Foundation", kind = "framework")]
+    extern "C" {}
+
+    // Shows the dialog and waits for the user's answer, which is replied from another thread
+    pub fn evaluate(reason: &str) -> Result<(), String> {
+        let (sender, receiver) = mpsc::channel();
+        unsafe {
+            let context: *mut Object = msg_send![class!(LAContext), new];
+            let text: *mut Object = msg_send![class!(NSString), alloc];
+            let text: *mut Object = msg_send![text,
+                initWithBytes: reason.as_ptr()
+                length: reason.len()
+                encoding: NS_UTF8_STRING_ENCODING];
+            let reply = ConcreteBlock::new(move |success: BOOL, error: *mut Object| {
+                let result = if success == YES {
+                    Ok(())
+                } else {
+                    Err(description(error))
+                };
+                let _ = sender.send(result);
+            })
+            .copy();
+            let () = msg_send![context,
+                evaluatePolicy: DEVICE_OWNER_AUTHENTICATION
+                localizedReason: text
+                reply: &*reply];
+            let result = receiver
+                .recv()
+                .unwrap_or_else(|_| Err(String::from("no answer was received")));
+            let () = msg_send![text, release];
+            let () = msg_send![context, release];
+            result
+        }
+    }
+
+    unsafe fn description(error: *mut Object) -> String {
+        if error.is_null() {
+            return String::from("unknown error");
+        }
+        let description: *mut Object = msg_send![error, localizedDescription];
+        let utf8: *const c_char = msg_send![description, UTF8String];
+        CStr::from_ptr(utf8).to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod local_authentication {
+    // Not reached, the profiles requiring it are rejected by `ensure_available`
+    pub fn evaluate(_reason: &str) -> Result<(), String> {
+        Err(String::from("it's only available on macOS"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{available_verifications, verify_user, UserVerification};
+
+    #[test]
+    fn user_verification_tests() {
+        let verification: UserVerification = unwrap!(serde_json::from_str("\"touch_id\""));
+        assert_eq!(verification, UserVerification::TouchId);
+        assert_eq!(
+            verification.ensure_available().is_ok(),
+            available_verifications().contains(&"touch_id")
+        );
+        assert!(verify_user(None, "revoke 'net.maidsafe.cli'").is_ok());
+
+        let verification: UserVerification = unwrap!(serde_json::from_str("\"polkit\""));
+        if !cfg!(target_os = "linux") {
+            assert!(verification.ensure_available().is_err());
+        }
+    }
+}
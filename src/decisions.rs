@@ -0,0 +1,119 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The auth requests the prompt of the Authenticator service is waiting on, which can be decided
+// from outside of it too, e.g. from the buttons of a desktop notification, with
+// `POST /decide/{id}/{allow|deny}`. The IDs are random, so the notification of a request
+// decided already, or of a previous run of the service, can't decide on another one.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// # The auth requests waiting for a decision
+///
+/// Shared by the service's prompt, which adds the requests it waits on, and the service,
+/// which forwards to them the decisions posted to `/decide/{id}/{allow|deny}`.
+#[derive(Clone, Default)]
+pub struct PendingDecisions {
+    pending: Arc<Mutex<HashMap<u64, Sender<bool>>>>,
+}
+
+/// An auth request waiting for a decision, until it's dropped
+pub struct PendingDecision {
+    id: u64,
+    decision: Receiver<bool>,
+    decided: Option<bool>,
+    decisions: PendingDecisions,
+}
+
+impl PendingDecisions {
+    /// A request waiting for a decision, under a random ID
+    pub fn add(&self) -> PendingDecision {
+        let (sender, decision) = mpsc::channel();
+        let mut pending = self.lock();
+        let mut id = rand::random();
+        while pending.contains_key(&id) {
+            id = rand::random();
+        }
+        let _ = pending.insert(id, sender);
+        PendingDecision {
+            id,
+            decision,
+            decided: None,
+            decisions: self.clone(),
+        }
+    }
+
+    /// Decides on the request, returning whether it was waiting for a decision
+    pub fn decide(&self, id: u64, allowed: bool) -> bool {
+        match self.lock().remove(&id) {
+            Some(sender) => sender.send(allowed).is_ok(),
+            None => false,
+        }
+    }
+
+    // The lock is only held to add and remove the requests, so a poisoned one is still
+    // consistent
+    fn lock(&self) -> MutexGuard<'_, HashMap<u64, Sender<bool>>> {
+        match self.pending.lock() {
+            Ok(pending) => pending,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl PendingDecision {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Whether it was allowed, once it's decided
+    pub fn decided(&mut self) -> Option<bool> {
+        if self.decided.is_none() {
+            self.decided = self.decision.try_recv().ok();
+        }
+        self.decided
+    }
+}
+
+impl Drop for PendingDecision {
+    fn drop(&mut self) {
+        let _ = self.decisions.lock().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PendingDecisions;
+
+    #[test]
+    fn pending_decisions_tests() {
+        let decisions = PendingDecisions::default();
+        let mut first = decisions.add();
+        let mut second = decisions.add();
+        assert_ne!(first.id(), second.id());
+        assert_eq!(first.decided(), None);
+
+        assert!(decisions.decide(first.id(), true));
+        assert_eq!(first.decided(), Some(true));
+        assert_eq!(first.decided(), Some(true));
+        // Decided once only
+        assert!(!decisions.decide(first.id(), false));
+        assert_eq!(first.decided(), Some(true));
+
+        assert!(decisions.decide(second.id(), false));
+        assert_eq!(second.decided(), Some(false));
+
+        // Not waiting anymore once dropped
+        let third = decisions.add();
+        let id = third.id();
+        drop(third);
+        assert!(!decisions.decide(id, true));
+    }
+}
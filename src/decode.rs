@@ -0,0 +1,383 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The strings decoded here are received from the CLI arguments and the Authenticator service,
+// thus they are never trusted: the decoding is kept free of any global state or I/O so it can be
+// fuzzed (see the targets in the `fuzz` folder), and any malformed string results in an error.
+
+use crate::errors::Error;
+use log::debug;
+use safe_core::ipc::req::IpcReq;
+use safe_core::ipc::resp::IpcResp;
+use safe_core::ipc::{decode_msg, IpcMsg};
+use serde::Serialize;
+use std::cell::Cell;
+use std::panic;
+
+// Maximum length accepted for an encoded string, much longer than any valid request,
+// so malformed strings can't make the decoder allocate big amounts of memory
+const MAX_ENCODED_LEN: usize = 64 * 1024;
+
+// The multibase prefix of the strings encoded by the safe_core version we are built with
+const SUPPORTED_PREFIX: char = 'b';
+
+// Multibase prefixes of the encodings the IPC strings may have been produced with
+// by other versions of the SAFE apps and libraries, and the characters of their alphabets
+const OTHER_ENCODINGS: [(char, &str, InAlphabet); 8] = [
+    ('f', "base16", |c| {
+        c.is_ascii_digit() || ('a'..='f').contains(&c)
+    }),
+    ('F', "base16 upper case", |c| {
+        c.is_ascii_digit() || ('A'..='F').contains(&c)
+    }),
+    ('B', "base32 upper case", |c| {
+        c.is_ascii_uppercase() || ('2'..='7').contains(&c) || c == '='
+    }),
+    ('z', "base58", |c| {
+        c.is_ascii_alphanumeric() && !"0OIl".contains(c)
+    }),
+    ('m', "base64", is_base64),
+    ('M', "base64 with padding", |c| is_base64(c) || c == '='),
+    ('u', "base64url", is_base64url),
+    ('U', "base64url with padding", |c| {
+        is_base64url(c) || c == '='
+    }),
+];
+
+// Whether the character is one of an encoding's alphabet
+type InAlphabet = fn(char) -> bool;
+
+fn is_base64(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '+' || c == '/'
+}
+
+fn is_base64url(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+// Number of variants of the `IpcMsg`, `IpcReq` and `IpcResp` enums known to this version
+thread_local! {
+    // Set while decoding on this thread, whose panics are recovered from
+    static RECOVERING: Cell<bool> = const { Cell::new(false) };
+}
+
+// Whether a panic on this thread is recovered from, so it isn't reported as a crash
+pub(crate) fn is_recovering() -> bool {
+    RECOVERING.with(Cell::get)
+}
+
+const KNOWN_MSG_KINDS: u32 = 4;
+const KNOWN_REQ_KINDS: u32 = 4;
+const KNOWN_RESP_KINDS: u32 = 4;
+
+/// The format an IPC request or response string was produced with, as told by `ipc_format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IpcFormat {
+    /// The one of the apps using the same version of the IPC format, it can be decoded
+    /// unless the string is malformed
+    Current,
+    /// The string is encoded with another encoding, e.g. "base64"
+    OtherEncoding(&'static str),
+    /// The encoding is the expected one, but the kind of message or request it contains
+    /// is not known by this version, e.g. it was introduced by a newer one
+    UnknownMessageKind,
+    /// It's not an IPC string at all
+    Unrecognised,
+}
+
+/// # Detect the format of an IPC string
+///
+/// Tells if an auth request or response string was produced by an app or authenticator
+/// using the same version of the SAFE IPC format as this crate, by looking at its encoding
+/// and at the kind of message it contains, without fully decoding it. This is what's used
+/// to report the strings produced by incompatible versions, with an `IncompatibleVersion`
+/// error, rather than as malformed ones.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{ipc_format, IpcFormat};
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// assert_eq!(ipc_format(auth_req), IpcFormat::Current);
+/// assert_eq!(ipc_format("mAAAAAA"), IpcFormat::OtherEncoding("base64"));
+/// ```
+pub fn ipc_format(encoded: &str) -> IpcFormat {
+    let mut chars = encoded.chars();
+    match chars.next() {
+        Some(SUPPORTED_PREFIX) => (),
+        Some(prefix) => {
+            // The strings not made of the characters of the encoding are not IPC strings
+            let body = chars.as_str();
+            return OTHER_ENCODINGS
+                .iter()
+                .find(|(other, _, in_alphabet)| *other == prefix && body.chars().all(in_alphabet))
+                .map_or(IpcFormat::Unrecognised, |(_, name, _)| {
+                    IpcFormat::OtherEncoding(name)
+                });
+        }
+        None => return IpcFormat::Unrecognised,
+    }
+
+    let body = chars.as_str();
+    if body.chars().any(|c| base32_value(c).is_none()) {
+        return IpcFormat::Unrecognised;
+    }
+
+    // Each message starts with the kind of message, followed by the id of the request
+    // and the kind of request or response, all of them as 4 bytes little endian integers
+    let header = base32_decode_prefix(body, 12);
+    let msg_kind = header.get(0..4).map(read_u32);
+    let inner_kind = header.get(8..12).map(read_u32);
+    let known = match (msg_kind, inner_kind) {
+        (Some(0), Some(kind)) => kind < KNOWN_REQ_KINDS,
+        (Some(1), Some(kind)) => kind < KNOWN_RESP_KINDS,
+        (Some(kind), _) => kind < KNOWN_MSG_KINDS,
+        // Too short to tell, it's reported as malformed if it can't be decoded
+        (None, _) => true,
+    };
+    if known {
+        IpcFormat::Current
+    } else {
+        IpcFormat::UnknownMessageKind
+    }
+}
+
+/// # Decode an authorisation request
+///
+/// Decodes an authorisation request string, returning its id and the request.
+/// It doesn't connect to the network nor it depends on any global state.
+///
+/// ## Example
+/// ```
+/// use safe_auth::decode_req;
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// match decode_req(auth_req) {
+///    Ok(_) => assert!(true), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn decode_req(req: &str) -> Result<(u32, IpcReq), Error> {
+    match decode_auth_req(req)? {
+        IpcMsg::Req { req_id, request } => Ok((req_id, request)),
+        IpcMsg::Resp { .. } | IpcMsg::Revoked { .. } | IpcMsg::Err(..) => {
+            Err(Error::IpcDecodeError(String::from(
+                "The request was not recognised as a valid auth request",
+            )))
+        }
+    }
+}
+
+/// # Decode an authorisation response
+///
+/// Decodes a response string generated by the authenticator, returning the id
+/// of the request it responds to and the response, e.g. to verify if it was granted.
+///
+/// ## Example
+/// ```
+/// use safe_auth::decode_resp;
+/// match decode_resp("invalid-auth-resp") {
+///    Ok(_) => assert!(false), // This should not pass
+///    Err(err) => assert!(err.to_string().contains("Failed to decode"))
+/// }
+/// ```
+pub fn decode_resp(resp: &str) -> Result<(u32, IpcResp), Error> {
+    match decode_encoded(resp, "auth response")? {
+        IpcMsg::Resp { req_id, response } => Ok((req_id, response)),
+        IpcMsg::Req { .. } | IpcMsg::Revoked { .. } | IpcMsg::Err(..) => {
+            Err(Error::IpcDecodeError(String::from(
+                "The response was not recognised as a valid auth response",
+            )))
+        }
+    }
+}
+
+// Helper function to decode an auth request string
+pub(crate) fn decode_auth_req(req: &str) -> Result<IpcMsg, Error> {
+    let req_msg = decode_encoded(req, "auth request")?;
+    debug!("Auth request string decoded: {:?}", req_msg);
+    Ok(req_msg)
+}
+
+fn decode_encoded(encoded: &str, what: &str) -> Result<IpcMsg, Error> {
+    if encoded.len() > MAX_ENCODED_LEN {
+        return Err(Error::IpcDecodeError(format!(
+            "Failed to decode the {} string: it's longer than {} bytes",
+            what, MAX_ENCODED_LEN
+        )));
+    }
+
+    // The decoder is not expected to panic, but if it did with some input
+    // we still want to report it as an invalid string rather than aborting
+    let recovering = RECOVERING.with(|flag| flag.replace(true));
+    let decoded = panic::catch_unwind(|| decode_msg(encoded));
+    RECOVERING.with(|flag| flag.set(recovering));
+    match decoded {
+        Ok(Ok(msg)) => Ok(msg),
+        Ok(Err(err)) => Err(match ipc_format(encoded) {
+            IpcFormat::OtherEncoding(encoding) => Error::IncompatibleVersion(format!(
+                "The {} string was produced by an incompatible app version: it's encoded as {} rather than base32",
+                what, encoding
+            )),
+            IpcFormat::UnknownMessageKind => Error::IncompatibleVersion(format!(
+                "The {} string was produced by an incompatible app version: the kind of message it contains is not known by this version",
+                what
+            )),
+            IpcFormat::Current | IpcFormat::Unrecognised => Error::IpcDecodeError(format!(
+                "Failed to decode the {} string: {:?}",
+                what, err
+            )),
+        }),
+        Err(_) => Err(Error::IpcDecodeError(format!(
+            "Failed to decode the {} string: the decoder panicked",
+            what
+        ))),
+    }
+}
+
+fn base32_value(c: char) -> Option<u8> {
+    match c {
+        'A'..='Z' => Some(c as u8 - b'A'),
+        '2'..='7' => Some(c as u8 - b'2' + 26),
+        _ => None,
+    }
+}
+
+// Decodes up to `len` bytes from the start of a (valid) base32 string
+fn base32_decode_prefix(encoded: &str, len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for value in encoded.chars().filter_map(base32_value) {
+        buffer = (buffer << 5) | u32::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+            if bytes.len() == len {
+                break;
+            }
+        }
+    }
+    bytes
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0, |value, byte| (value << 8) | u32::from(*byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_req, decode_resp, ipc_format, IpcFormat, MAX_ENCODED_LEN};
+    use crate::apps::gen_auth_denied_response;
+    use crate::errors::Error;
+    use crate::test_utils::{APP_AUTH_REQ, CONT_AUTH_REQ};
+    use safe_core::ipc::req::IpcReq;
+    use safe_core::ipc::resp::IpcResp;
+    use safe_core::ipc::IpcError;
+
+    #[test]
+    fn decode_req_tests() {
+        match unwrap!(decode_req(APP_AUTH_REQ)) {
+            (_, IpcReq::Auth(auth_req)) => assert_eq!(auth_req.app.id, "net.maidsafe.cli"),
+            other => panic!("Unexpected request: {:?}", other),
+        }
+        match unwrap!(decode_req(CONT_AUTH_REQ)) {
+            (_, IpcReq::Containers(cont_req)) => assert_eq!(cont_req.containers.len(), 2),
+            other => panic!("Unexpected request: {:?}", other),
+        }
+
+        // A response is not a valid request
+        let resp = unwrap!(gen_auth_denied_response(1));
+        match decode_req(&resp) {
+            Err(Error::IpcDecodeError(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_resp_tests() {
+        let resp = unwrap!(gen_auth_denied_response(1));
+        match unwrap!(decode_resp(&resp)) {
+            (1, IpcResp::Auth(Err(IpcError::AuthDenied))) => (), // This should pass
+            other => panic!("Unexpected response: {:?}", other),
+        }
+
+        // A request is not a valid response
+        match decode_resp(APP_AUTH_REQ) {
+            Err(Error::IpcDecodeError(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_malformed_strings_tests() {
+        let too_long = "b".repeat(MAX_ENCODED_LEN + 1);
+        let truncated = &APP_AUTH_REQ[..APP_AUTH_REQ.len() / 2];
+        let malformed = [
+            "",
+            "b",
+            "bAAAA",
+            "not-an-auth-req",
+            "b////",
+            "b\u{0}\u{1F600}",
+            truncated,
+            &too_long,
+        ];
+        for encoded in malformed.iter() {
+            match decode_req(encoded) {
+                Err(Error::IpcDecodeError(_)) => (), // This should pass
+                Ok(_) => panic!("Malformed request decoded: {}", encoded),
+                Err(err) => panic!("Unexpected error: {:?}", err),
+            }
+            assert!(decode_resp(encoded).is_err());
+        }
+
+        // Corrupted requests may still be valid ones, but they must never make the decoder panic
+        for pos in 1..APP_AUTH_REQ.len() {
+            for replacement in ["A", "7", "Z"].iter() {
+                let mut corrupted = APP_AUTH_REQ.to_string();
+                corrupted.replace_range(pos..=pos, replacement);
+                let _ = decode_req(&corrupted);
+                let _ = decode_resp(&corrupted);
+            }
+        }
+    }
+
+    #[test]
+    fn ipc_format_tests() {
+        assert_eq!(ipc_format(APP_AUTH_REQ), IpcFormat::Current);
+        assert_eq!(ipc_format(CONT_AUTH_REQ), IpcFormat::Current);
+        let resp = unwrap!(gen_auth_denied_response(1));
+        assert_eq!(ipc_format(&resp), IpcFormat::Current);
+        assert_eq!(ipc_format(""), IpcFormat::Unrecognised);
+        assert_eq!(ipc_format("not-an-auth-req"), IpcFormat::Unrecognised);
+        assert_eq!(ipc_format("b////"), IpcFormat::Unrecognised);
+
+        // The same request encoded with another encoding
+        let base64 = format!("m{}", &APP_AUTH_REQ[1..]);
+        assert_eq!(ipc_format(&base64), IpcFormat::OtherEncoding("base64"));
+        match decode_req(&base64) {
+            Err(Error::IncompatibleVersion(description)) => {
+                assert!(description.contains("incompatible app version"))
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // A kind of request unknown to this version, e.g. introduced by a newer one
+        let mut newer = APP_AUTH_REQ.to_string();
+        newer.replace_range(16..17, "7");
+        assert_eq!(ipc_format(&newer), IpcFormat::UnknownMessageKind);
+        match decode_req(&newer) {
+            Err(Error::IncompatibleVersion(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use safe_authenticator::errors::AuthError;
+use safe_core::CoreError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+/// Errors returned by the operations exposed by this crate.
+/// The kind of error is kept when it's reported by the CLI, with its exit code,
+/// and by the Authenticator service in the JSON body of its responses.
+#[derive(Debug, Clone, PartialEq, ThisError, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "description")]
+pub enum Error {
+    /// The secret, password or secret key provided are not valid for the operation
+    #[error("{0}")]
+    CredentialsError(String),
+    /// Failed to communicate with the SAFE Network
+    #[error("{0}")]
+    NetworkError(String),
+    /// The SAFE Network didn't respond within the timeout set for the operation
+    #[error("{0}")]
+    Timeout(String),
+    /// The authorisation request couldn't be decoded
+    #[error("{0}")]
+    IpcDecodeError(String),
+    /// The operation is not allowed by the policies set
+    #[error("{0}")]
+    PolicyDenied(String),
+    /// Failed to read or write a local file
+    #[error("{0}")]
+    IoError(String),
+    /// An argument or the content of a file provided is not valid
+    #[error("{0}")]
+    InvalidInput(String),
+    /// The authenticator failed to perform the operation requested
+    #[error("{0}")]
+    AuthenticatorError(String),
+    /// The operation was cancelled with its `CancellationToken` before it finished
+    #[error("{0}")]
+    Cancelled(String),
+    /// The request or response was produced by an app using an incompatible version
+    /// of the SAFE IPC format, e.g. an older or a newer one
+    #[error("{0}")]
+    IncompatibleVersion(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// The start of the description of the errors caused by the secret key given to create an
+// account, e.g. as its balance was already spent, rather than by the secret or the password
+pub(crate) const SAFE_KEY_ERROR: &str = "The secret key can't pay for the account";
+
+// Whether another secret key may create the account which failed to be created
+pub(crate) fn is_safe_key_error(err: &Error) -> bool {
+    match err {
+        Error::CredentialsError(description) => description.starts_with(SAFE_KEY_ERROR),
+        _ => false,
+    }
+}
+
+// Classifies the errors returned by the authenticator when logging in or creating an account,
+// telling apart the ones caused by the credentials from the ones caused by the network
+pub(crate) fn login_error(msg: &str, err: AuthError) -> Error {
+    let description = format!("{}: {:?}", msg, err);
+    match err {
+        AuthError::SndError(safe_nd::Error::NoSuchBalance)
+        | AuthError::SndError(safe_nd::Error::InsufficientBalance) => {
+            Error::CredentialsError(format!("{}: {}", SAFE_KEY_ERROR, description))
+        }
+        AuthError::SndError(safe_nd::Error::LoginPacketExists)
+        | AuthError::SndError(safe_nd::Error::NoSuchLoginPacket)
+        | AuthError::CoreError(CoreError::SymmetricDecipherFailure) => {
+            Error::CredentialsError(description)
+        }
+        _ => Error::NetworkError(description),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_safe_key_error, login_error, Error};
+    use safe_authenticator::errors::AuthError;
+
+    #[test]
+    fn error_serde_test() {
+        let err = Error::IpcDecodeError("Failed to decode the auth request".to_string());
+        let serialised = unwrap!(serde_json::to_string(&err));
+        assert_eq!(
+            serialised,
+            r#"{"kind":"IpcDecodeError","description":"Failed to decode the auth request"}"#
+        );
+        let deserialised: Error = unwrap!(serde_json::from_str(&serialised));
+        assert_eq!(deserialised, err);
+    }
+
+    #[test]
+    fn safe_key_error_tests() {
+        for err in [
+            safe_nd::Error::NoSuchBalance,
+            safe_nd::Error::InsufficientBalance,
+        ] {
+            let err = login_error("Failed to create an account", AuthError::SndError(err));
+            assert!(is_safe_key_error(&err), "{:?}", err);
+        }
+        // Another secret key doesn't help if the secret is already used by an account
+        let err = login_error(
+            "Failed to create an account",
+            AuthError::SndError(safe_nd::Error::LoginPacketExists),
+        );
+        match &err {
+            Error::CredentialsError(_) => assert!(!is_safe_key_error(&err)),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+}
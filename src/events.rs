@@ -0,0 +1,520 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::account::{AccountInfo, AccountKeys};
+use crate::apps::{AuthedAppsDiff, AuthedAppsList};
+use crate::backend::AuthBackend;
+use crate::decode::decode_resp;
+use crate::errors::Error;
+use crate::inspect::{inspect_auth_req, AuthReqInfo};
+use crate::storage::{FileStorage, Storage};
+use crate::warnings::RequestWarning;
+use crate::AuthAllowPrompt;
+use log::{debug, warn};
+use safe_core::ipc::resp::IpcResp;
+use safe_core::ipc::IpcError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of events kept by an `EventHistory` unless another capacity is configured
+pub const DEFAULT_HISTORY_CAPACITY: usize = 1000;
+
+/// Events which occurred on a logged in account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum AuthEvent {
+    /// An auth request was received, and it's about to be allowed or denied
+    RequestReceived { info: AuthReqInfo },
+    /// A risky pattern was found in an auth request, by a `WarningBackend`
+    Warning {
+        req_id: u32,
+        warning: RequestWarning,
+    },
+    /// An auth request was allowed or denied
+    Decided { req_id: u32, allowed: bool },
+    /// The permissions granted to an app were revoked
+    Revoked { app_id: String },
+    /// An operation failed as the network couldn't be reached
+    NetworkDisconnected { description: String },
+    /// The session was closed, no more operations can be performed on it
+    Locked,
+    /// It's time to review the permissions granted: the ids of the apps authorised, and of
+    /// the ones among them which didn't send any request for `stale_days`
+    PermissionReview {
+        apps: Vec<String>,
+        stale: Vec<String>,
+        stale_days: u64,
+    },
+    /// A temporary grant expired, and the app it authorised was revoked
+    GrantExpired { app_id: String },
+}
+
+/// # Subscribe to the events of an account
+///
+/// Delivers each event emitted to all the subscribers, and records it in its `EventHistory`
+/// if it has one. It can be cloned, with all the clones sharing the same subscribers.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<AuthEvent>>>>,
+    history: Option<EventHistory>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A bus recording the events emitted in the history
+    pub fn with_history(history: EventHistory) -> Self {
+        Self {
+            subscribers: Default::default(),
+            history: Some(history),
+        }
+    }
+
+    pub fn history(&self) -> Option<&EventHistory> {
+        self.history.as_ref()
+    }
+
+    /// Returns a channel which receives all the events emitted from now on.
+    /// The subscription ends when the receiver is dropped.
+    pub fn subscribe(&self) -> Receiver<AuthEvent> {
+        let (sender, receiver) = channel();
+        unwrap!(self.subscribers.lock()).push(sender);
+        receiver
+    }
+
+    pub fn emit(&self, event: AuthEvent) {
+        debug!("Emitting event: {:?}", event);
+        if let Some(history) = &self.history {
+            history.record(event.clone());
+        }
+        unwrap!(self.subscribers.lock()).retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
+/// An event of the history, with its cursor and the time it was recorded, e.g.
+/// `{"cursor":3,"time":1570000000,"event":"Locked"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Increases with each event recorded, starting from 1
+    pub cursor: u64,
+    /// Seconds since the UNIX epoch, missing from the events persisted before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<u64>,
+    #[serde(flatten)]
+    pub event: AuthEvent,
+}
+
+/// The events of the history recorded after a cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub events: Vec<HistoryEntry>,
+    /// The cursor to get the next page with, the one of the last event of this page
+    pub next: u64,
+    /// Whether there are more events after this page
+    pub more: bool,
+    /// Whether some events after the cursor are not in the history anymore, as more than its
+    /// capacity were recorded since
+    pub missed: bool,
+}
+
+struct HistoryState {
+    entries: VecDeque<HistoryEntry>,
+    last_cursor: u64,
+    capacity: usize,
+    // The storage the events are written to, with the area and name of their entry
+    stored: Option<(Arc<dyn Storage>, String, String)>,
+}
+
+/// # Keep the recent events
+///
+/// A bounded history of the events, the oldest ones being dropped once its capacity is
+/// reached, so the clients which missed some events, e.g. reconnecting front-ends, can get
+/// them with `after` rather than starting from an empty state. Each event is given a cursor,
+/// increasing with each event recorded. It can be persisted to a file, or to a `Storage`, so
+/// the history and its cursors are kept across restarts. It can be cloned, with all the clones
+/// sharing the same events.
+#[derive(Clone)]
+pub struct EventHistory {
+    state: Arc<Mutex<HistoryState>>,
+}
+
+impl EventHistory {
+    /// A history kept in memory of the last `capacity` events
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(HistoryState {
+                entries: VecDeque::new(),
+                last_cursor: 0,
+                capacity,
+                stored: None,
+            })),
+        }
+    }
+
+    /// A history of the last `capacity` events, loaded from the file if it exists, and
+    /// written to it as JSON each time an event is recorded
+    pub fn persisted(capacity: usize, file: PathBuf) -> Result<Self, Error> {
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "The events history '{}' is not a file",
+                    file.display()
+                ))
+            })?;
+        let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::stored(capacity, Arc::new(FileStorage::new(dir)), "", &name)
+    }
+
+    /// Same as `persisted`, but the events are stored in the entry of the storage
+    pub fn stored(
+        capacity: usize,
+        storage: Arc<dyn Storage>,
+        area: &str,
+        name: &str,
+    ) -> Result<Self, Error> {
+        let mut entries: VecDeque<HistoryEntry> = match storage.read(area, name)? {
+            Some(content) => serde_json::from_slice(&content).map_err(|err| {
+                Error::InvalidInput(format!(
+                    "The events history '{}' is not valid: {}",
+                    name, err
+                ))
+            })?,
+            None => VecDeque::new(),
+        };
+        while entries.len() > capacity {
+            let _ = entries.pop_front();
+        }
+        let last_cursor = entries.back().map_or(0, |entry| entry.cursor);
+        Ok(Self {
+            state: Arc::new(Mutex::new(HistoryState {
+                entries,
+                last_cursor,
+                capacity,
+                stored: Some((storage, area.to_string(), name.to_string())),
+            })),
+        })
+    }
+
+    pub fn record(&self, event: AuthEvent) {
+        let mut state = unwrap!(self.state.lock());
+        state.last_cursor += 1;
+        let cursor = state.last_cursor;
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .ok();
+        state.entries.push_back(HistoryEntry {
+            cursor,
+            time,
+            event,
+        });
+        while state.entries.len() > state.capacity {
+            let _ = state.entries.pop_front();
+        }
+        if let Some((storage, area, name)) = &state.stored {
+            // The events are still kept in memory if they can't be written
+            let result = serde_json::to_vec(&state.entries)
+                .map_err(|err| err.to_string())
+                .and_then(|content| {
+                    storage
+                        .write(area, name, &content)
+                        .map_err(|err| err.to_string())
+                });
+            if let Err(err) = result {
+                warn!("The events history couldn't be written: {}", err);
+            }
+        }
+    }
+
+    /// At most `limit` events recorded after the cursor, the oldest first, or from the oldest
+    /// one kept without a cursor. A cursor after the last event recorded is one of a history
+    /// which was restarted since, e.g. not persisted, so the events are then got from the
+    /// oldest one kept too, as missed.
+    pub fn after(&self, cursor: Option<u64>, limit: usize) -> HistoryPage {
+        let state = unwrap!(self.state.lock());
+        let restarted = cursor.is_some_and(|cursor| cursor > state.last_cursor);
+        let after = if restarted { 0 } else { cursor.unwrap_or(0) };
+        let mut remaining = state.entries.iter().filter(|entry| entry.cursor > after);
+        let events: Vec<HistoryEntry> = remaining.by_ref().take(limit).cloned().collect();
+        let oldest = state
+            .entries
+            .front()
+            .map_or(state.last_cursor + 1, |entry| entry.cursor);
+        HistoryPage {
+            next: events.last().map_or(after, |entry| entry.cursor),
+            more: remaining.next().is_some(),
+            missed: restarted || (cursor.is_some() && oldest > after + 1),
+            events,
+        }
+    }
+}
+
+/// # Emit the events of the operations performed on a backend
+///
+/// Wraps an `AuthBackend`, e.g. a logged in `Authenticator`, emitting an `AuthEvent`
+/// to the `EventBus` provided for each auth request received and decided on, for
+/// each app revoked, and for each operation which failed due to the network.
+/// The `Locked` event is emitted when it's dropped.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{AuthBackend, AuthEvent, EventBus, EventingBackend, FakeBackend};
+/// let events = EventBus::new();
+/// let receiver = events.subscribe();
+/// let backend = EventingBackend::new(FakeBackend::default(), events);
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// let _ = backend.authorise_app(auth_req, &|_| true);
+/// match receiver.try_recv() {
+///    Ok(AuthEvent::RequestReceived { .. }) => assert!(true), // This should pass
+///    _ => assert!(false)
+/// }
+/// ```
+pub struct EventingBackend<B: AuthBackend> {
+    inner: B,
+    events: EventBus,
+}
+
+impl<B: AuthBackend> EventingBackend<B> {
+    pub fn new(inner: B, events: EventBus) -> Self {
+        Self { inner, events }
+    }
+
+    /// The bus the events are emitted to
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    fn check_network<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(Error::NetworkError(description)) = &result {
+            self.events.emit(AuthEvent::NetworkDisconnected {
+                description: description.clone(),
+            });
+        }
+        result
+    }
+}
+
+impl<B: AuthBackend> AuthBackend for EventingBackend<B> {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        let req_id = inspect_auth_req(req).ok().map(|info| {
+            let req_id = info.req_id;
+            self.events.emit(AuthEvent::RequestReceived { info });
+            req_id
+        });
+
+        let result = self.check_network(self.inner.authorise_app(req, allow));
+        if let (Some(req_id), Ok(resp)) = (req_id, &result) {
+            let denied = matches!(
+                decode_resp(resp),
+                Ok((_, IpcResp::Auth(Err(IpcError::AuthDenied))))
+            );
+            self.events.emit(AuthEvent::Decided {
+                req_id,
+                allowed: !denied,
+            });
+        }
+        result
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.check_network(self.inner.authed_apps())
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        let result = self.check_network(self.inner.revoke_app(app_id));
+        if result.is_ok() {
+            self.events.emit(AuthEvent::Revoked {
+                app_id: app_id.to_string(),
+            });
+        }
+        result
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.check_network(self.inner.acc_info())
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.check_network(self.inner.acc_keys())
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.check_network(self.inner.backup_account())
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.check_network(self.inner.restore_account(backup))
+    }
+}
+
+impl<B: AuthBackend> Drop for EventingBackend<B> {
+    fn drop(&mut self) {
+        self.events.emit(AuthEvent::Locked);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthEvent, EventBus, EventHistory, EventingBackend, HistoryEntry};
+    use crate::backend::{AuthBackend, FakeBackend};
+    use crate::storage::MemoryStorage;
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID, CONT_AUTH_REQ};
+    use std::sync::Arc;
+
+    #[test]
+    fn eventing_backend_tests() {
+        let events = EventBus::new();
+        let receiver = events.subscribe();
+        let backend = EventingBackend::new(FakeBackend::default(), events);
+
+        let _ = unwrap!(backend.authorise_app(APP_AUTH_REQ, &|_| true));
+        let _ = unwrap!(backend.authorise_app(CONT_AUTH_REQ, &|_| false));
+        unwrap!(backend.revoke_app(APP_ID));
+        // Invalid requests and failed operations don't emit any event
+        assert!(backend
+            .authorise_app("invalid-auth-req", &|_| true)
+            .is_err());
+        assert!(backend.revoke_app(APP_ID).is_err());
+        drop(backend);
+
+        let received: Vec<AuthEvent> = receiver.try_iter().collect();
+        assert_eq!(received.len(), 6);
+        match (&received[0], &received[1]) {
+            (
+                AuthEvent::RequestReceived { info },
+                AuthEvent::Decided {
+                    req_id,
+                    allowed: true,
+                },
+            ) => assert_eq!(info.req_id, *req_id),
+            other => panic!("Unexpected events: {:?}", other),
+        }
+        match &received[3] {
+            AuthEvent::Decided { allowed: false, .. } => (), // This should pass
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        assert_eq!(
+            received[4],
+            AuthEvent::Revoked {
+                app_id: APP_ID.to_string()
+            }
+        );
+        assert_eq!(received[5], AuthEvent::Locked);
+    }
+
+    #[test]
+    fn event_bus_subscribers_tests() {
+        let events = EventBus::new();
+        let first = events.subscribe();
+        let second = events.subscribe();
+        drop(second);
+        events.emit(AuthEvent::Locked);
+        assert_eq!(unwrap!(first.try_recv()), AuthEvent::Locked);
+        assert_eq!(unwrap!(events.subscribers.lock()).len(), 1);
+
+        let serialised = unwrap!(serde_json::to_string(&AuthEvent::Revoked {
+            app_id: APP_ID.to_string()
+        }));
+        assert_eq!(
+            serialised,
+            r#"{"event":"Revoked","app_id":"net.maidsafe.cli"}"#
+        );
+    }
+
+    #[test]
+    fn event_history_tests() {
+        let history = EventHistory::new(3);
+        let events = EventBus::with_history(history.clone());
+        for app_id in &["a", "b", "c", "d"] {
+            events.emit(AuthEvent::Revoked {
+                app_id: app_id.to_string(),
+            });
+        }
+
+        // The first event was dropped once the capacity was reached
+        let page = history.after(None, 2);
+        let cursors: Vec<u64> = page.events.iter().map(|entry| entry.cursor).collect();
+        assert_eq!(cursors, vec![2, 3]);
+        assert_eq!(page.next, 3);
+        assert!(page.more && !page.missed);
+        let page = history.after(Some(page.next), 2);
+        assert_eq!(page.events.len(), 1);
+        assert_eq!(page.next, 4);
+        assert!(!page.more);
+        // Nothing new since the last cursor
+        let page = history.after(Some(4), 2);
+        assert!(page.events.is_empty());
+        assert_eq!(page.next, 4);
+        assert!(!page.missed);
+        // The event after the cursor isn't kept anymore
+        assert!(history.after(Some(0), 10).missed);
+        // The cursor of another history
+        let page = history.after(Some(10), 10);
+        assert!(page.missed);
+        assert_eq!(page.events.len(), 3);
+
+        assert!(page.events.iter().all(|entry| entry.time.is_some()));
+
+        let serialised = unwrap!(serde_json::to_string(&HistoryEntry {
+            cursor: 5,
+            time: Some(1_570_000_000),
+            event: AuthEvent::Locked
+        }));
+        assert_eq!(
+            serialised,
+            r#"{"cursor":5,"time":1570000000,"event":"Locked"}"#
+        );
+        // As persisted before the time was recorded
+        let entry: HistoryEntry = unwrap!(serde_json::from_str(r#"{"cursor":5,"event":"Locked"}"#));
+        assert_eq!(entry.time, None);
+    }
+
+    #[test]
+    fn persisted_event_history_tests() {
+        let file = std::env::temp_dir().join("safe_auth-events-history-tests.json");
+        let _ = std::fs::remove_file(&file);
+        let history = unwrap!(EventHistory::persisted(2, file.clone()));
+        history.record(AuthEvent::Locked);
+        history.record(AuthEvent::Revoked {
+            app_id: APP_ID.to_string(),
+        });
+        history.record(AuthEvent::Locked);
+
+        // The cursors go on from the ones of the events loaded
+        let history = unwrap!(EventHistory::persisted(2, file.clone()));
+        assert_eq!(history.after(None, 10).events.len(), 2);
+        history.record(AuthEvent::Locked);
+        let page = history.after(Some(3), 10);
+        assert_eq!(page.events[0].cursor, 4);
+        assert!(!page.missed);
+        let _ = std::fs::remove_file(&file);
+    }
+
+    #[test]
+    fn stored_event_history_tests() {
+        let storage = Arc::new(MemoryStorage::new());
+        let history = unwrap!(EventHistory::stored(2, storage.clone(), "events", "a.json"));
+        history.record(AuthEvent::Locked);
+        history.record(AuthEvent::Locked);
+
+        let history = unwrap!(EventHistory::stored(2, storage.clone(), "events", "a.json"));
+        history.record(AuthEvent::Locked);
+        assert_eq!(history.after(Some(2), 10).events[0].cursor, 3);
+        // Each entry has its own history
+        let history = unwrap!(EventHistory::stored(2, storage, "events", "b.json"));
+        assert!(history.after(None, 10).events.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The high-risk operations which a guardian, i.e. the Authenticator service of another device
+// or person the account's owner paired with, must co-approve. An operation is sent to the
+// guardian's service as the hex encoded JSON of its `Approval`, in the path of the request so
+// it's covered by the proof of the token they share, see the `service_auth` module.
+
+use crate::helpers::{bytes_to_hex, parse_hex};
+use crate::Error;
+use safe_core::ipc::req::IpcReq;
+use safe_nd::MDataAction;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// # An operation to be co-approved by a guardian
+///
+/// Sent by the account's owner to the guardian's Authenticator service, whose
+/// `ApprovalPrompt` decides on it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum Approval {
+    /// Revoking all the apps authorised by the account at once
+    RevokeAll { app_ids: Vec<String> },
+    /// Deciding on an auth request sharing mutable data with the `ManagePermissions`
+    /// permission, which lets the app give access to the data to anyone, encoded
+    ShareMData { req: String },
+}
+
+impl Approval {
+    /// The encoding sent in the path of the request to the guardian's service
+    pub fn encode(&self) -> Result<String, Error> {
+        serde_json::to_vec(self)
+            .map(|json| bytes_to_hex(&json))
+            .map_err(|err| {
+                Error::AuthenticatorError(format!("Failed to serialise the approval: {}", err))
+            })
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self, Error> {
+        serde_json::from_slice(&parse_hex(encoded))
+            .map_err(|err| Error::InvalidInput(format!("The approval is not valid: {}", err)))
+    }
+}
+
+/// Type of the function invoked by the guardian's service for querying if an operation of
+/// the account it guards shall be approved.
+pub type ApprovalPrompt = dyn Fn(&Approval) -> bool + Send + Sync;
+
+/// The account a service is the guardian of: its owner sends the high-risk operations to be
+/// co-approved to `POST /guardian/approve/{approval}`, proving they have the token the owner
+/// and the guardian share, and the prompt decides on them.
+#[derive(Clone)]
+pub struct Guarding {
+    pub token: String,
+    pub prompt: Arc<ApprovalPrompt>,
+}
+
+/// Whether the guardian must co-approve the auth request, i.e. it shares mutable data with
+/// the `ManagePermissions` permission
+pub fn needs_approval(req: &IpcReq) -> bool {
+    match req {
+        IpcReq::ShareMData(share_mdata_req) => share_mdata_req
+            .mdata
+            .iter()
+            .any(|mdata| mdata.perms.is_allowed(MDataAction::ManagePermissions)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{needs_approval, Approval};
+    use safe_core::ipc::req::{AppExchangeInfo, IpcReq, ShareMData, ShareMDataReq};
+    use safe_nd::{MDataAction, MDataPermissionSet, XorName};
+
+    #[test]
+    fn approval_encoding_tests() {
+        let approval = Approval::RevokeAll {
+            app_ids: vec![String::from("net.maidsafe.cli"), String::from("a/b c")],
+        };
+        let encoded = unwrap!(approval.encode());
+        assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(unwrap!(Approval::decode(&encoded)), approval);
+        assert!(Approval::decode("7b7d").is_err());
+    }
+
+    #[test]
+    fn needs_approval_tests() {
+        let req = |perms| {
+            IpcReq::ShareMData(ShareMDataReq {
+                app: AppExchangeInfo {
+                    id: String::from("org.example.chat"),
+                    scope: None,
+                    name: String::from("Chat"),
+                    vendor: String::from("Example"),
+                },
+                mdata: vec![ShareMData {
+                    type_tag: 15000,
+                    name: XorName([1; 32]),
+                    perms,
+                }],
+            })
+        };
+        assert!(!needs_approval(&req(
+            MDataPermissionSet::new().allow(MDataAction::Insert)
+        )));
+        assert!(needs_approval(&req(MDataPermissionSet::new()
+            .allow(MDataAction::Insert)
+            .allow(MDataAction::ManagePermissions))));
+    }
+}
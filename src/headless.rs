@@ -0,0 +1,238 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::backend::AuthBackend;
+use crate::errors::Error;
+use crate::inspect::{inspect_auth_req, AuthReqInfo};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, Write};
+use std::sync::Mutex;
+
+/// An authorisation request received which is waiting for the user to decide on it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingReq {
+    /// The encoded auth request string, as it was received
+    pub req: String,
+    /// The details of the request to be shown to the user
+    pub info: AuthReqInfo,
+}
+
+/// # Embedding the authenticator in a UI
+///
+/// Exposes the operations needed by a UI which manages the authorisations, like the
+/// SAFE Browser's: the auth requests received are queued as pending until the user decides
+/// on them, and the list of authorised apps and the account's information can be queried.
+///
+/// The same operations can be sent as JSON commands, one per line, with `run_stdio`,
+/// which is what `safe_auth --headless` does through its stdin and stdout.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{FakeBackend, Headless};
+/// let headless = Headless::new(Box::new(FakeBackend::default()));
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// let pending = headless.submit(auth_req).unwrap();
+/// let response = headless.decide(pending.info.req_id, true);
+/// match response {
+///    Ok(_) => assert!(headless.pending().is_empty()), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub struct Headless {
+    backend: Box<dyn AuthBackend>,
+    pending: Mutex<Vec<PendingReq>>,
+}
+
+impl Headless {
+    pub fn new(backend: Box<dyn AuthBackend>) -> Self {
+        Self {
+            backend,
+            pending: Mutex::new(vec![]),
+        }
+    }
+
+    /// Decodes an auth request and queues it until it's decided on.
+    /// A request with the same id as one already pending replaces it.
+    pub fn submit(&self, req: &str) -> Result<PendingReq, Error> {
+        let pending_req = PendingReq {
+            req: req.to_string(),
+            info: inspect_auth_req(req)?,
+        };
+        debug!("Auth request queued: {:?}", pending_req.info);
+        let mut pending = unwrap!(self.pending.lock());
+        pending.retain(|queued| queued.info.req_id != pending_req.info.req_id);
+        pending.push(pending_req.clone());
+        Ok(pending_req)
+    }
+
+    /// The requests waiting to be decided on, in the order they were received
+    pub fn pending(&self) -> Vec<PendingReq> {
+        unwrap!(self.pending.lock()).clone()
+    }
+
+    /// Allows or denies a pending request, returning the response to be sent to the app
+    pub fn decide(&self, req_id: u32, allow: bool) -> Result<String, Error> {
+        let pending_req = {
+            let mut pending = unwrap!(self.pending.lock());
+            match pending
+                .iter()
+                .position(|queued| queued.info.req_id == req_id)
+            {
+                Some(index) => pending.remove(index),
+                None => {
+                    return Err(Error::InvalidInput(format!(
+                        "There is no pending auth request with id {}",
+                        req_id
+                    )))
+                }
+            }
+        };
+        debug!(
+            "Auth request {} was {}",
+            req_id,
+            if allow { "allowed" } else { "denied" }
+        );
+        if allow {
+            self.backend.authorise_app(&pending_req.req, &|_| true)
+        } else {
+            self.backend.authorise_app(&pending_req.req, &|_| false)
+        }
+    }
+
+    /// The backend the operations are performed with, e.g. to get the authorised apps
+    pub fn backend(&self) -> &dyn AuthBackend {
+        &*self.backend
+    }
+
+    /// Performs a command, returning its output as a JSON value
+    pub fn handle(&self, cmd: HeadlessCmd) -> Result<Value, Error> {
+        match cmd {
+            HeadlessCmd::Submit { req } => self.submit(&req).map(to_json),
+            HeadlessCmd::Pending => Ok(to_json(self.pending())),
+            HeadlessCmd::Decide { req_id, allow } => self.decide(req_id, allow).map(to_json),
+            HeadlessCmd::Apps => self.backend.authed_apps().map(to_json),
+            HeadlessCmd::Account => self.backend.acc_info().map(to_json),
+            HeadlessCmd::Revoke { app_id } => self.backend.revoke_app(&app_id).map(to_json),
+        }
+    }
+}
+
+/// Commands accepted by `run_stdio`, e.g. `{"cmd":"decide","req_id":1,"allow":true}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum HeadlessCmd {
+    /// Queue an auth request
+    Submit { req: String },
+    /// List the pending auth requests
+    Pending,
+    /// Allow or deny a pending auth request
+    Decide { req_id: u32, allow: bool },
+    /// List the authorised apps
+    Apps,
+    /// Get the account's information
+    Account,
+    /// Revoke the permissions granted to an app
+    Revoke { app_id: String },
+}
+
+// Each command gets one of these as response, e.g. {"ok":[...]} or {"error":{"kind":...}}
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum HeadlessResponse {
+    Ok(Value),
+    Error(Error),
+}
+
+/// # Run the commands read from a stream
+///
+/// Reads a JSON command from each line of `input` and writes the response on a line
+/// of `output`, until the end of the `input` stream is reached.
+pub fn run_stdio<R: BufRead, W: Write>(
+    headless: &Headless,
+    input: R,
+    mut output: W,
+) -> Result<(), Error> {
+    for line in input.lines() {
+        let line =
+            line.map_err(|err| Error::IoError(format!("Failed to read the command: {}", err)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = serde_json::from_str::<HeadlessCmd>(&line)
+            .map_err(|err| Error::InvalidInput(format!("Invalid command: {}", err)))
+            .and_then(|cmd| headless.handle(cmd));
+        let response = match result {
+            Ok(value) => HeadlessResponse::Ok(value),
+            Err(err) => HeadlessResponse::Error(err),
+        };
+        let serialised = unwrap!(serde_json::to_string(&response));
+        writeln!(output, "{}", serialised)
+            .and_then(|_| output.flush())
+            .map_err(|err| Error::IoError(format!("Failed to write the response: {}", err)))?;
+    }
+    Ok(())
+}
+
+fn to_json<T: Serialize>(output: T) -> Value {
+    serde_json::to_value(output).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_stdio, Headless};
+    use crate::backend::FakeBackend;
+    use crate::errors::Error;
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID, CONT_AUTH_REQ};
+    use serde_json::Value;
+
+    #[test]
+    fn headless_pending_queue_tests() {
+        let headless = Headless::new(Box::new(FakeBackend::default()));
+        let app_req = unwrap!(headless.submit(APP_AUTH_REQ));
+        let cont_req = unwrap!(headless.submit(CONT_AUTH_REQ));
+        // Submitting the same request again doesn't queue it twice
+        let _ = unwrap!(headless.submit(APP_AUTH_REQ));
+        assert_eq!(headless.pending().len(), 2);
+
+        let _ = unwrap!(headless.decide(app_req.info.req_id, true));
+        let _ = unwrap!(headless.decide(cont_req.info.req_id, false));
+        assert!(headless.pending().is_empty());
+        let apps = unwrap!(headless.backend().authed_apps());
+        assert_eq!(apps.len(), 1);
+        assert!(apps[0].perms.is_empty());
+
+        match headless.decide(app_req.info.req_id, true) {
+            Err(Error::InvalidInput(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn headless_stdio_tests() {
+        let headless = Headless::new(Box::new(FakeBackend::default()));
+        let input = format!(
+            "{{\"cmd\":\"submit\",\"req\":\"{}\"}}\n\n{{\"cmd\":\"pending\"}}\nnot a command\n{{\"cmd\":\"apps\"}}\n",
+            APP_AUTH_REQ
+        );
+        let mut output = Vec::new();
+        unwrap!(run_stdio(&headless, input.as_bytes(), &mut output));
+
+        let responses: Vec<Value> = unwrap!(String::from_utf8(output))
+            .lines()
+            .map(|line| unwrap!(serde_json::from_str(line)))
+            .collect();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["ok"]["info"]["req_type"], "Auth");
+        assert_eq!(responses[1]["ok"][0]["info"]["app"]["id"], APP_ID);
+        assert_eq!(responses[2]["error"]["kind"], "InvalidInput");
+        assert_eq!(responses[3]["ok"], Value::Array(vec![]));
+    }
+}
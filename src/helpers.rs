@@ -0,0 +1,86 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::errors::Error;
+use futures::sync::oneshot;
+use futures::{future, Future, IntoFuture};
+use safe_authenticator::AuthClient;
+use safe_authenticator::Authenticator;
+use safe_nd::PublicKey;
+use threshold_crypto::SecretKey;
+
+/// Future returned by the non-blocking versions of the operations
+pub type AsyncResult<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+pub fn parse_hex(hex_str: &str) -> Vec<u8> {
+    let mut hex_bytes = hex_str
+        .as_bytes()
+        .iter()
+        .filter_map(|b| match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        })
+        .fuse();
+
+    let mut bytes = Vec::new();
+    while let (Some(h), Some(l)) = (hex_bytes.next(), hex_bytes.next()) {
+        bytes.push(h << 4 | l)
+    }
+    bytes
+}
+
+pub(crate) fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn pk_to_hex(pk: &PublicKey) -> String {
+    match pk {
+        PublicKey::Ed25519(pk) => bytes_to_hex(&pk.to_bytes()),
+        PublicKey::Bls(pk) => bytes_to_hex(&pk.to_bytes()),
+        PublicKey::BlsShare(pk) => bytes_to_hex(&pk.to_bytes()),
+    }
+}
+
+pub(crate) fn sk_from_hex(hex_str: &str) -> Result<SecretKey, Error> {
+    let sk_bytes = parse_hex(hex_str);
+    bincode::deserialize(&sk_bytes).map_err(|_| {
+        Error::CredentialsError("Failed to deserialize provided secret key".to_string())
+    })
+}
+
+// Helper function to run a future in the authenticator's event loop without blocking,
+// the future returned resolves with its result once it's finished
+pub(crate) fn run_async<F, I, T>(authenticator: &Authenticator, f: F) -> AsyncResult<T>
+where
+    F: FnOnce(&AuthClient) -> I + Send + 'static,
+    I: IntoFuture<Item = T, Error = Error> + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let sent = authenticator.send(move |client| {
+        let future = f(client).into_future().then(move |result| {
+            let _ = tx.send(result);
+            Ok(())
+        });
+        let future: Box<dyn Future<Item = (), Error = ()>> = Box::new(future);
+        Some(future)
+    });
+    if let Err(err) = sent {
+        return Box::new(future::err(Error::NetworkError(format!(
+            "Failed to run the operation in the authenticator: {}",
+            err
+        ))));
+    }
+
+    Box::new(rx.then(|result| match result {
+        Ok(result) => result,
+        Err(oneshot::Canceled) => Err(Error::NetworkError("The operation was aborted".to_string())),
+    }))
+}
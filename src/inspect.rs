@@ -0,0 +1,175 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::decode::decode_req;
+use crate::errors::Error;
+use crate::ipc::{AppExchangeInfo, ContainerPermissions, IpcReq, ReqKind};
+use safe_nd::{MDataAction, MDataPermissionSet};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Details of an authorisation request, in a form which can be displayed to the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthReqInfo {
+    pub req_id: u32,
+    /// Type of the request, as displayed by its `ReqKind`: "Auth", "Containers", etc.
+    pub req_type: String,
+    /// Application which sent the request, not known for unregistered requests
+    pub app: Option<AppExchangeInfo>,
+    /// If the application requests its own container, only for "Auth" requests
+    pub app_container: bool,
+    /// The name of the container the application gets if it requests its own one, e.g.
+    /// `apps/net.maidsafe.cli`
+    pub app_container_name: Option<String>,
+    /// Permissions requested, one line for each container or MutableData
+    pub permissions: Vec<String>,
+}
+
+/// # Inspect an authorisation request
+///
+/// Decodes an authorisation request string and returns its details.
+/// It doesn't connect to the network nor it requires any credentials, thus
+/// it can be used to show the request to the user before it's sent to be authorised.
+///
+/// ## Example
+/// ```
+/// use safe_auth::inspect_auth_req;
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// match inspect_auth_req(auth_req) {
+///    Ok(info) => assert_eq!(info.req_type, "Auth"), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+pub fn inspect_auth_req(req: &str) -> Result<AuthReqInfo, Error> {
+    let (req_id, req) = decode_req(req)?;
+
+    let info = match req {
+        IpcReq::Auth(auth_req) => AuthReqInfo {
+            req_id,
+            req_type: ReqKind::Auth.to_string(),
+            app_container: auth_req.app_container,
+            app_container_name: if auth_req.app_container {
+                Some(app_container_name(&auth_req.app.id))
+            } else {
+                None
+            },
+            app: Some(auth_req.app),
+            permissions: containers_perms(&auth_req.containers),
+        },
+        IpcReq::Containers(cont_req) => AuthReqInfo {
+            req_id,
+            req_type: ReqKind::Containers.to_string(),
+            app: Some(cont_req.app),
+            app_container: false,
+            app_container_name: None,
+            permissions: containers_perms(&cont_req.containers),
+        },
+        IpcReq::Unregistered(_) => AuthReqInfo {
+            req_id,
+            req_type: ReqKind::Unregistered.to_string(),
+            app: None,
+            app_container: false,
+            app_container_name: None,
+            permissions: vec![],
+        },
+        IpcReq::ShareMData(share_mdata_req) => AuthReqInfo {
+            req_id,
+            req_type: ReqKind::ShareMData.to_string(),
+            permissions: share_mdata_req
+                .mdata
+                .iter()
+                .map(|mdata| {
+                    format!(
+                        "MutableData {:?} (type tag {}): {}",
+                        mdata.name,
+                        mdata.type_tag,
+                        mdata_perms(&mdata.perms)
+                    )
+                })
+                .collect(),
+            app: Some(share_mdata_req.app),
+            app_container: false,
+            app_container_name: None,
+        },
+    };
+
+    Ok(info)
+}
+
+/// # The app's own container
+///
+/// The name of the container the authenticator creates for an app which requests its own
+/// container, and which the app is given all the permissions on.
+pub fn app_container_name(app_id: &str) -> String {
+    format!("apps/{}", app_id)
+}
+
+// Renders the permissions requested for each container, sorted by container name
+fn containers_perms(containers: &HashMap<String, ContainerPermissions>) -> Vec<String> {
+    let mut perms: Vec<String> = containers
+        .iter()
+        .map(|(name, perms)| {
+            let perms: Vec<String> = perms.iter().map(|perm| format!("{:?}", perm)).collect();
+            format!("{}: {}", name, perms.join(", "))
+        })
+        .collect();
+    perms.sort();
+    perms
+}
+
+fn mdata_perms(perms: &MDataPermissionSet) -> String {
+    let actions = [
+        MDataAction::Read,
+        MDataAction::Insert,
+        MDataAction::Update,
+        MDataAction::Delete,
+        MDataAction::ManagePermissions,
+    ];
+    let allowed: Vec<String> = actions
+        .iter()
+        .filter(|action| perms.is_allowed(**action))
+        .map(|action| format!("{:?}", action))
+        .collect();
+    allowed.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{app_container_name, inspect_auth_req};
+    use crate::errors::Error;
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID, APP_NAME, CONT_AUTH_REQ};
+
+    #[test]
+    fn inspect_auth_req_tests() {
+        let info = unwrap!(inspect_auth_req(APP_AUTH_REQ));
+        assert_eq!(info.req_type, "Auth");
+        assert!(!info.app_container);
+        assert_eq!(info.app_container_name, None);
+        let app = unwrap!(info.app);
+        assert_eq!(app.id, APP_ID);
+        assert_eq!(app.name, APP_NAME);
+        assert_eq!(app_container_name(&app.id), format!("apps/{}", APP_ID));
+
+        let info = unwrap!(inspect_auth_req(CONT_AUTH_REQ));
+        assert_eq!(info.req_type, "Containers");
+        assert_eq!(
+            info.permissions,
+            vec!["_music: Insert, Update", "_public: Read"]
+        );
+
+        match inspect_auth_req("fddfds") {
+            Ok(_) => panic!("It should have failed to decode the invalid request"),
+            Err(err) => assert_eq!(
+                err,
+                Error::IpcDecodeError(
+                    "Failed to decode the auth request string: EncodeDecodeError".to_string()
+                )
+            ),
+        }
+    }
+}
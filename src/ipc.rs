@@ -0,0 +1,84 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! # Types of the SAFE IPC messages
+//!
+//! The `safe_core` types found in the results of the operations of this crate, e.g. the
+//! `AppExchangeInfo` and `ContainerPermissions` of an `AuthedAppsList`, or the `IpcReq`
+//! passed to an `AuthAllowPrompt`. They are re-exported from here so embedders can read
+//! them without depending on the specific `safe_core` version this crate is built with.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub use safe_core::ipc::req::{
+    AppExchangeInfo, AuthReq, ContainerPermissions, ContainersReq, IpcReq, ShareMDataReq,
+};
+pub use safe_core::ipc::resp::IpcResp;
+pub use safe_core::ipc::{IpcError, Permission};
+
+/// The kind of an authorisation request, displayed as "Auth", "Containers",
+/// "Unregistered" or "ShareMData"
+///
+/// ## Example
+/// ```
+/// use safe_auth::decode_req;
+/// use safe_auth::ipc::ReqKind;
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// let (_, req) = decode_req(auth_req).unwrap();
+/// assert_eq!(ReqKind::of(&req), ReqKind::Auth);
+/// assert_eq!(ReqKind::Auth.to_string(), "Auth");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReqKind {
+    /// An app requests to be authorised, with access to some containers
+    Auth,
+    /// An authorised app requests access to more containers
+    Containers,
+    /// An app requests access to the network without an account
+    Unregistered,
+    /// An app requests access to MutableData owned by another app
+    ShareMData,
+}
+
+impl ReqKind {
+    pub fn of(req: &IpcReq) -> Self {
+        match req {
+            IpcReq::Auth(_) => ReqKind::Auth,
+            IpcReq::Containers(_) => ReqKind::Containers,
+            IpcReq::Unregistered(_) => ReqKind::Unregistered,
+            IpcReq::ShareMData(_) => ReqKind::ShareMData,
+        }
+    }
+}
+
+impl fmt::Display for ReqKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReqKind;
+    use crate::decode::decode_req;
+    use crate::test_utils::{APP_AUTH_REQ, CONT_AUTH_REQ};
+
+    #[test]
+    fn req_kind_tests() {
+        let (_, app_req) = unwrap!(decode_req(APP_AUTH_REQ));
+        let (_, cont_req) = unwrap!(decode_req(CONT_AUTH_REQ));
+        assert_eq!(ReqKind::of(&app_req), ReqKind::Auth);
+        assert_eq!(ReqKind::of(&cont_req), ReqKind::Containers);
+        assert_eq!(ReqKind::ShareMData.to_string(), "ShareMData");
+        assert_eq!(
+            unwrap!(serde_json::to_string(&ReqKind::Unregistered)),
+            r#""Unregistered""#
+        );
+    }
+}
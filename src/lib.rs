@@ -6,925 +6,136 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+//! # SAFE Authenticator
+//!
+//! This crate exposes the operations needed to manage a SAFE Network account and
+//! the authorisations granted to applications: creating an account or logging in to it,
+//! authorising, listing or revoking applications, and running the Authenticator
+//! service (see the `authd` module, built with the `daemon` feature). The `safe_auth` CLI is a thin layer on top of it,
+//! which can also be run in-process with `run_with_args`.
+//!
+//! All the items exposed from the root of the crate are considered its public API,
+//! and any breaking change to them is reflected by a major version bump.
+//!
+//! All the operations fail with an `Error` which tells the kind of failure apart,
+//! e.g. invalid credentials, network errors, or auth requests which couldn't be decoded.
+//! The requests and responses produced by apps using an incompatible version of the SAFE IPC
+//! format fail with an `IncompatibleVersion` error instead, and `ipc_format` tells them apart.
+//!
+//! The operations on authorised apps have also a non-blocking version, e.g. `authorise_app_async`,
+//! which returns a future, so embedders can drive many of them concurrently.
+//! The ones which connect to the network have also a `_cancellable` version, e.g. `log_in_cancellable`,
+//! which fails with a `Cancelled` error as soon as the `CancellationToken` provided is cancelled.
+//!
+//! The `AuthBackend` trait abstracts the operations on a logged in account, it's implemented
+//! by `Authenticator` and by `FakeBackend`, an in-memory account to be used in tests.
+//! The operations performed on any backend can be recorded with a `RecordingBackend`,
+//! and replayed later with a `ReplayBackend`, without connecting to the network.
+//! A `SharedBackend` lets several components, e.g. the Authenticator service and a UI,
+//! operate on the same logged in account from different threads.
+//! The `safe_core` types found in the results, e.g. the `AppExchangeInfo` of the authorised apps,
+//! are re-exported from the `ipc` module, so embedders don't need to depend on `safe_core` to use them.
+//! Wrapping a backend with an `EventingBackend` emits an `AuthEvent` for each request received
+//! and decided on, so UIs can subscribe to them from an `EventBus` rather than polling.
+//! A `WarningBackend` flags the risky auth requests before they are decided on, e.g. from an
+//! app id already authorised with another vendor, with a `RequestWarning` the prompt can display.
+//! The high-risk operations, e.g. revoking all the apps, can be required to be co-approved by a
+//! guardian's Authenticator service, which decides on their `Approval` with an `ApprovalPrompt`.
+//! The state the CLI keeps locally, e.g. the receipts and the labels of the apps, is kept in a
+//! `Storage`, an SQLite database of the user's app directory (`SqliteStorage`, with the default
+//! `sqlite` feature, otherwise its files) unless another one is set with `set_storage`.
+
+#[cfg(feature = "pretty")]
 #[macro_use]
-extern crate unwrap;
+extern crate prettytable;
 
-use futures::{stream, Future, Stream};
-use log::{debug, info};
-use maidsafe_utilities::serialisation::deserialise;
-use safe_authenticator::ipc::{decode_ipc_msg, update_container_perms};
-use safe_authenticator::revocation::revoke_app as safe_authenticator_revoke_app;
-use safe_authenticator::{
-    access_container, app_auth::authenticate, config, errors::AuthError, run as auth_run_helper,
-    Authenticator,
-};
-use safe_core::client::Client;
-use safe_core::ipc::req::{
-    AppExchangeInfo, AuthReq, ContainerPermissions, ContainersReq, IpcReq, ShareMDataReq,
-};
-use safe_core::ipc::resp::{AccessContainerEntry, IpcResp};
-use safe_core::ipc::{access_container_enc_key, decode_msg, encode_msg, IpcError, IpcMsg};
-use safe_core::utils::symmetric_decrypt;
-use safe_core::{client as safe_core_client, CoreError};
-use safe_nd::{MDataAddress, PublicKey};
-use threshold_crypto::SecretKey;
+#[cfg(not(feature = "mock-network"))]
+#[macro_use]
+extern crate self_update;
+
+#[macro_use]
+extern crate unwrap;
 
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
 
-#[derive(Debug)]
-pub struct AuthedAppsList {
-    pub app: AppExchangeInfo,
-    pub perms: Vec<(String, ContainerPermissions)>,
-}
+mod account;
+mod apps;
+#[cfg(feature = "daemon")]
+pub mod authd;
+mod backend;
+mod cancel;
+mod cli;
+mod decisions;
+mod decode;
+mod errors;
+mod events;
+mod guardian;
+mod headless;
+mod helpers;
+mod inspect;
+pub mod ipc;
+#[cfg(feature = "node")]
+mod node;
+mod pacing;
+mod progress;
+#[cfg(feature = "python")]
+mod python;
+mod replay;
+mod service;
+mod service_auth;
+mod shared;
+mod storage;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod timeouts;
+mod warnings;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use account::{
+    acc_info, acc_keys, backup_account, create_acc, create_acc_cancellable,
+    create_acc_with_progress, log_in, log_in_cancellable, log_in_with_progress, restore_account,
+    AccountInfo, AccountKeys,
+};
+pub use apps::{
+    authed_apps, authed_apps_async, authorise_app, authorise_app_async, authorise_app_cancellable,
+    diff_authed_apps, revoke_app, revoke_app_async, revoke_app_cancellable, revoke_apps,
+    revoke_apps_cancellable, AppPermsChange, AuthedAppsDiff, AuthedAppsList,
+};
+pub use backend::{AuthBackend, FakeBackend};
+pub use cancel::{CancellableAuthenticator, CancellationToken};
+pub use cli::{
+    init_logger, install_panic_handler, run_with_args, CapturedIo, CapturedOutput, CliIo,
+    CliResult, StdIo,
+};
+pub use decisions::{PendingDecision, PendingDecisions};
+pub use decode::{decode_req, decode_resp, ipc_format, IpcFormat};
+pub use errors::{Error, Result};
+pub use events::{
+    AuthEvent, EventBus, EventHistory, EventingBackend, HistoryEntry, HistoryPage,
+    DEFAULT_HISTORY_CAPACITY,
+};
+pub use guardian::{needs_approval, Approval, ApprovalPrompt, Guarding};
+pub use headless::{run_stdio, Headless, HeadlessCmd, PendingReq};
+pub use helpers::{parse_hex, AsyncResult};
+pub use inspect::{app_container_name, inspect_auth_req, AuthReqInfo};
+pub use pacing::{set_pacing, PacingSettings};
+pub use progress::{OperationProgress, ProgressReporter};
+pub use replay::{BackendCall, RecordedCall, RecordingBackend, ReplayBackend};
+pub use service::ServiceOptions;
+pub use shared::SharedBackend;
+#[cfg(feature = "sqlite")]
+pub use storage::SqliteStorage;
+pub use storage::{set_storage, FileStorage, MemoryStorage, Storage};
+pub use timeouts::{set_timeouts, OperationTimeouts};
+pub use warnings::{RequestWarning, RequestWarnings, WarningBackend};
+
+use ipc::IpcReq;
 
 // Type of the function/callback invoked for querying if an authorisation request shall be allowed.
 // All the relevant information about the authorisation request is passed as args to the callback.
 pub type AuthAllowPrompt = dyn Fn(IpcReq) -> bool + std::marker::Send + std::marker::Sync;
-
-/// # Create Account
-/// Creates a new account on the SAFE Network.
-/// Returns an error if an account exists or if there was some
-/// problem during the account creation process.
-///
-/// Note: This does _not_ perform any strength checks on the
-/// strings used to create the account.
-///
-/// ## Example
-/// ```
-/// use safe_auth::create_acc;
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// # fn main() -> Result<(), String> {
-///     let my_secret = "mysecretstring";
-///     let my_password = "mypassword";
-/// #   let my_secret = &(random_str());
-/// #   let my_password = &(random_str());
-/// #   let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-///     let auth = create_acc(sk, my_secret, my_password)?;
-/// #   Ok(())
-/// # }
-///```
-///
-/// ## Error Example
-/// If an account with same secret already exists,
-/// the function will return an error:
-/// ```
-/// use safe_auth::create_acc;
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// /// Using an already existing account's secret and password:
-/// let my_secret = "mysecretstring";
-/// let my_password = "mypassword";
-/// # let my_secret = &(random_str());
-/// # let my_password = &(random_str());
-/// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-/// # create_acc(sk, my_secret, my_password).unwrap();
-/// let acc_not_created = create_acc(sk, my_secret, my_password);
-/// match acc_not_created {
-///    Ok(_) => assert!(false), // This should not pass
-///    Err(message) => {
-///         assert!(message.contains("Failed to create an account"));
-///     }
-/// }
-///```
-pub fn create_acc(sk: &str, secret: &str, password: &str) -> Result<Authenticator, String> {
-    debug!("Attempting to create a SAFE account...");
-    let secret_key = sk_from_hex(sk)?;
-    match Authenticator::create_acc(secret, password, secret_key, || {
-        // eprintln!("{}", "Disconnected from network");
-    }) {
-        Ok(auth) => {
-            debug!("Returning account just created");
-            Ok(auth)
-        }
-        Err(err) => Err(format!("Failed to create an account: {:?}", err)),
-    }
-}
-
-/// # Log in
-///
-/// Using an account already created, you can log in to
-/// the SAFE Network and return an `Authenticator` instance.
-///
-/// ## Example
-/// ```
-/// # use safe_auth::create_acc;
-/// use safe_auth::log_in;
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// /// Using an already existing account's secret and password:
-/// let my_secret = "mysecretstring";
-/// let my_password = "mypassword";
-/// # let my_secret = &(random_str());
-/// # let my_password = &(random_str());
-/// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-/// # create_acc(sk, my_secret, my_password).unwrap();
-/// let logged_in = log_in(my_secret, my_password);
-/// match logged_in {
-///    Ok(_) => assert!(true), // This should pass
-///    Err(_) => assert!(false)
-/// }
-///```
-///
-/// ## Error Example
-/// If the account does not exist, the function will return an appropriate error:
-///```
-/// # use safe_auth::log_in;
-/// let not_logged_in = log_in("non", "existant");
-/// match not_logged_in {
-///    Ok(_) => assert!(false), // This should not pass
-///    Err(message) => {
-///         assert!(message.contains("Failed to log in"));
-///    }
-/// }
-///```
-pub fn log_in(secret: &str, password: &str) -> Result<Authenticator, String> {
-    debug!("Attempting to log in...");
-    match Authenticator::login(secret, password, || info!("Disconnected from network")) {
-        Ok(auth) => {
-            debug!("Returning logged-in Authenticator instance");
-            Ok(auth)
-        }
-        Err(err) => Err(format!("Failed to log in: {:?}", err)),
-    }
-}
-
-/// # Authorise an application
-///
-/// Using an account already created, you can log in to
-/// the SAFE Network and authorise an application.
-///
-/// ## Example
-/// ```
-/// # use safe_auth::create_acc;
-/// use safe_auth::{log_in, authorise_app};
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// /// Using an already existing account's secret and password:
-/// let my_secret = "mysecretstring";
-/// let my_password = "mypassword";
-/// # let my_secret = &(random_str());
-/// # let my_password = &(random_str());
-/// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-/// # create_acc(sk, my_secret, my_password).unwrap();
-/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAAAAAAAAAAAAOAAAAAAAAAAAL5YHKYTMNFRQCAAAAAAAAAAAAAAAAAAB";
-/// let authenticator = log_in(my_secret, my_password).unwrap();
-/// let auth_response = authorise_app(&authenticator, auth_req, &|_| true);
-/// match auth_response {
-///    Ok(_) => assert!(true), // This should pass
-///    Err(_) => assert!(false)
-/// }
-///```
-/// ## Error Example
-/// ```
-/// # use safe_auth::create_acc;
-/// use safe_auth::{log_in, authorise_app};
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// /// Using an already existing account's secret and password:
-/// let my_secret = "mysecretstring";
-/// let my_password = "mypassword";
-/// # let my_secret = &(random_str());
-/// # let my_password = &(random_str());
-/// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-/// # create_acc(sk, my_secret, my_password).unwrap();
-/// /// Using an invalid auth request string
-/// let auth_req = "invalid-auth-req-string";
-/// let authenticator = log_in(my_secret, my_password).unwrap();
-/// let auth_response = authorise_app(&authenticator, auth_req, &|_| true);
-/// match auth_response {
-///    Ok(_) => assert!(false), // This should not pass
-///    Err(message) => assert!(message.contains("EncodeDecodeError"))
-/// }
-///```
-pub fn authorise_app(
-    authenticator: &Authenticator,
-    req: &str,
-    allow: &'static AuthAllowPrompt,
-) -> Result<String, String> {
-    let req_msg = match decode_msg(req) {
-        Ok(msg) => msg,
-        Err(err) => {
-            return Err(format!(
-                "Failed to decode the auth request string: {:?}",
-                err
-            ));
-        }
-    };
-    debug!("Auth request string decoded: {:?}", req_msg);
-
-    let ipc_req = unwrap!(auth_run_helper(
-        authenticator,
-        move |client| decode_ipc_msg(client, req_msg)
-    ));
-    match ipc_req {
-        Ok(IpcMsg::Req {
-            req: IpcReq::Auth(app_auth_req),
-            req_id,
-        }) => {
-            info!("Request was recognised as a general app auth request");
-            debug!("Decoded request (req_id={:?}): {:?}", req_id, app_auth_req);
-            debug!("Checking if the authorisation shall be allowed...");
-            if !allow(IpcReq::Auth(app_auth_req.clone())) {
-                debug!("Authorisation request was denied!");
-                return gen_auth_denied_response(req_id);
-            }
-
-            debug!("Allowed!. Attempting to authorise application...");
-            gen_auth_response(authenticator, req_id, app_auth_req)
-        }
-        Ok(IpcMsg::Req {
-            req: IpcReq::Containers(cont_req),
-            req_id,
-        }) => {
-            info!("Request was recognised as a containers auth request");
-            debug!("Decoded request (req_id={:?}): {:?}", req_id, cont_req);
-
-            debug!("Checking if the containers authorisation shall be allowed...");
-            if !allow(IpcReq::Containers(cont_req.clone())) {
-                debug!("Authorisation request was denied!");
-                return gen_auth_denied_response(req_id);
-            }
-
-            debug!("Allowed!. Attempting to grant permissions to the containers...");
-            gen_cont_auth_response(authenticator, req_id, cont_req)
-        }
-        Ok(IpcMsg::Req {
-            req: IpcReq::Unregistered(user_data),
-            req_id,
-        }) => {
-            info!("Request was recognised as an unregistered auth request");
-            debug!("Decoded request (req_id={:?}): {:?}", req_id, user_data);
-
-            debug!("Checking if the authorisation shall be allowed...");
-            if !allow(IpcReq::Unregistered(user_data)) {
-                debug!("Authorisation request was denied!");
-                return gen_auth_denied_response(req_id);
-            }
-
-            debug!("Allowed!");
-            gen_unreg_auth_response(req_id)
-        }
-        Ok(IpcMsg::Req {
-            req: IpcReq::ShareMData(share_mdata_req),
-            req_id,
-        }) => {
-            info!("Request was recognised as a share MD auth request");
-            debug!(
-                "Decoded request (req_id={:?}): {:?}",
-                req_id, share_mdata_req
-            );
-
-            debug!("Checking if the authorisation to share a MD shall be allowed...");
-            if !allow(IpcReq::ShareMData(share_mdata_req.clone())) {
-                debug!("Authorisation request was denied!");
-                return gen_auth_denied_response(req_id);
-            }
-
-            debug!("Allowed!. Attempting to grant permissions to the MD...");
-            gen_shared_md_auth_response(authenticator, req_id, share_mdata_req)
-        }
-        Err((error_code, description, _err)) => Err(format!(
-            "Failed decoding the auth request: {} - {:?}",
-            error_code, description
-        )),
-        Ok(IpcMsg::Resp { .. }) | Ok(IpcMsg::Revoked { .. }) | Ok(IpcMsg::Err(..)) => Err(
-            String::from("The request was not recognised as a valid auth request"),
-        ),
-    }
-}
-
-/// # Get the list of applications authorised by this account
-///
-/// Using an account already created, you can log in to
-/// the SAFE Network and get the list of all the applications that have
-/// been authorised so far.
-///
-/// ## Example
-/// ```
-/// # use safe_auth::{create_acc, authorise_app};
-/// use safe_auth::{log_in, authed_apps};
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// /// Using an already existing account which has been used
-/// /// to authorise some application already:
-/// let my_secret = "mysecretstring";
-/// let my_password = "mypassword";
-/// # let my_secret = &(random_str());
-/// # let my_password = &(random_str());
-/// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-/// # create_acc(sk, my_secret, my_password).unwrap();
-/// let authenticator = log_in(my_secret, my_password).unwrap();
-/// # let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAAAAAAAAAAAAOAAAAAAAAAAAL5YHKYTMNFRQCAAAAAAAAAAAAAAAAAAB";
-/// # authorise_app(&authenticator, auth_req, &|_| true).unwrap();
-/// /// Get the list of authorised apps
-/// let authed_apps = authed_apps(&authenticator);
-/// match authed_apps {
-///    Ok(_) => assert!(true), // This should pass
-///    Err(_) => assert!(false)
-/// }
-///```
-pub fn authed_apps(authenticator: &Authenticator) -> Result<Vec<AuthedAppsList>, String> {
-    debug!("Attempting to fetch list of authorised apps...");
-    let authed_apps = unwrap!(auth_run_helper(authenticator, move |client| {
-        let c2 = client.clone();
-        let c3 = client.clone();
-        config::list_apps(client)
-            .map(move |(_, auth_cfg)| (c2.access_container(), auth_cfg))
-            .and_then(move |(access_container, auth_cfg)| {
-                c3.list_seq_mdata_entries(access_container.name(), access_container.type_tag())
-                    .map_err(From::from)
-                    .map(move |entries| (access_container, entries, auth_cfg))
-            })
-            .and_then(move |(access_container, entries, auth_cfg)| {
-                let nonce = access_container
-                    .nonce()
-                    .ok_or_else(|| AuthError::from("No nonce on access container's MDataInfo"))?;
-
-                let mut apps = Vec::new();
-                for app in auth_cfg.values() {
-                    let key = access_container_enc_key(&app.info.id, &app.keys.enc_key, nonce)?;
-
-                    // Empty entry means it has been deleted.
-                    let entry = match entries.get(&key) {
-                        Some(entry) if !entry.data.is_empty() => Some(entry),
-                        _ => None,
-                    };
-
-                    let mut cont_perms = Vec::new();
-                    if let Some(entry) = entry {
-                        let plaintext = symmetric_decrypt(&entry.data, &app.keys.enc_key)?;
-                        let app_access = deserialise::<AccessContainerEntry>(&plaintext)?;
-
-                        for (key, (_mdata_info, perms)) in app_access.into_iter() {
-                            cont_perms.push((key, perms));
-                        }
-
-                        apps.push(AuthedAppsList {
-                            app: app.info.clone(),
-                            perms: cont_perms,
-                        });
-                    }
-                }
-
-                debug!("Returning list of authorised applications: {:?}", apps);
-                Ok(apps)
-            })
-            .map_err(AuthError::from)
-    }));
-
-    Ok(authed_apps)
-}
-
-/// # Revoke all permissions from an application
-///
-/// Using an account already created, you can log in to
-/// the SAFE Network and revoke all permissions previously granted to an
-/// application by providing its ID.
-///
-/// ## Example
-/// ```
-/// # use safe_auth::{create_acc, authorise_app};
-/// use safe_auth::{log_in, revoke_app};
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// /// Using an already existing account which has been used
-/// /// to authorise some application already:
-/// let my_secret = "mysecretstring";
-/// let my_password = "mypassword";
-/// # let my_secret = &(random_str());
-/// # let my_password = &(random_str());
-/// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-/// # create_acc(sk, my_secret, my_password).unwrap();
-/// let authenticator = log_in(my_secret, my_password).unwrap();
-/// # let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAAAAAAAAAAAAOAAAAAAAAAAAL5YHKYTMNFRQCAAAAAAAAAAAAAAAAAAB";
-/// # authorise_app(&authenticator, auth_req, &|_| true).unwrap();
-/// /// Revoke all permissions from app with ID `net.maidsafe.cli`
-/// let revoked = revoke_app(&authenticator, String::from("net.maidsafe.cli"));
-/// match revoked {
-///    Ok(_) => assert!(true), // This should pass
-///    Err(_) => assert!(false)
-/// }
-/// ```
-///
-/// ## Error Example
-/// ```
-/// # use safe_auth::{create_acc, authorise_app};
-/// use safe_auth::{log_in, revoke_app};
-/// # fn random_str() -> String { (0..4).map(|_| rand::random::<char>()).collect() }
-/// /// Using an already existing account which has been used
-/// /// to authorise some application already:
-/// let my_secret = "mysecretstring";
-/// let my_password = "mypassword";
-/// # let my_secret = &(random_str());
-/// # let my_password = &(random_str());
-/// # let sk = "83c055c5efdc483bd967adba5c1769daee0a17bc5fa2b6e129cd6b596c217617";
-/// # create_acc(sk, my_secret, my_password).unwrap();
-/// let authenticator = log_in(my_secret, my_password).unwrap();
-/// /// Try to revoke permissions with an incorrect app ID
-/// let revoked = revoke_app(&authenticator, String::from("invalid-app-id"));
-/// match revoked {
-///    Ok(_) => assert!(false), // This should not pass
-///    Err(message) => assert!(message.contains("UnknownApp"))
-/// }
-///```
-pub fn revoke_app(authenticator: &Authenticator, app_id: String) -> Result<(), String> {
-    auth_run_helper(authenticator, move |client| {
-        safe_authenticator_revoke_app(client, &app_id).and_then(move |_| {
-            debug!("Application sucessfully revoked: {}", app_id);
-            Ok(())
-        })
-    })
-    .map_err(|err| format!("Failed to revoke permissions: {}", err))
-}
-
-// Helper function to generate an app authorisation response
-fn gen_auth_denied_response(req_id: u32) -> Result<String, String> {
-    debug!("Encoding auth denied response...");
-    let resp = unwrap!(encode_msg(&IpcMsg::Resp {
-        req_id,
-        resp: IpcResp::Auth(Err(IpcError::AuthDenied)),
-    }));
-    debug!("Returning auth response generated: {:?}", resp);
-
-    Ok(resp)
-}
-
-// Helper function to generate an app authorisation response
-fn gen_auth_response(
-    authenticator: &Authenticator,
-    req_id: u32,
-    auth_req: AuthReq,
-) -> Result<String, String> {
-    let auth_granted = unwrap!(auth_run_helper(authenticator, move |client| authenticate(
-        client, auth_req
-    )));
-
-    debug!("Encoding response... {:?}", auth_granted);
-    let resp = unwrap!(encode_msg(&IpcMsg::Resp {
-        req_id,
-        resp: IpcResp::Auth(Ok(auth_granted)),
-    }));
-    debug!("Returning auth response generated: {:?}", resp);
-
-    Ok(resp)
-}
-
-// Helper function to generate a containers authorisation response
-fn gen_cont_auth_response(
-    authenticator: &Authenticator,
-    req_id: u32,
-    cont_req: ContainersReq,
-) -> Result<String, String> {
-    let permissions = cont_req.containers.clone();
-    let app_id = cont_req.app.id.clone();
-
-    auth_run_helper(authenticator, move |client| {
-        let c2 = client.clone();
-        let c3 = client.clone();
-        let c4 = client.clone();
-
-        config::get_app(client, &app_id)
-            .and_then(move |app| {
-                let sign_pk = PublicKey::from(app.keys.bls_pk);
-                update_container_perms(&c2, permissions, sign_pk).map(move |perms| (app, perms))
-            })
-            .and_then(move |(app, mut perms)| {
-                let app_keys = app.keys;
-                access_container::fetch_entry(&c3, &app_id, app_keys.clone()).then(move |res| {
-                    let version = match res {
-                        // Updating an existing entry
-                        Ok((version, Some(mut existing_perms))) => {
-                            for (key, val) in perms {
-                                let _ = existing_perms.insert(key, val);
-                            }
-                            perms = existing_perms;
-                            version + 1
-                        }
-
-                        // Adding a new access container entry
-                        Ok((_, None))
-                        | Err(AuthError::CoreError(CoreError::DataError(
-                            safe_nd::Error::NoSuchEntry,
-                        ))) => 0,
-
-                        // Error has occurred while trying to get an
-                        // existing entry
-                        Err(e) => return Err(e),
-                    };
-                    Ok((version, app_id, app_keys, perms))
-                })
-            })
-            .and_then(move |(version, app_id, app_keys, perms)| {
-                access_container::put_entry(&c4, &app_id, &app_keys, &perms, version)
-            })
-            .and_then(move |_| {
-                debug!("Encoding response...");
-                let resp = encode_msg(&IpcMsg::Resp {
-                    req_id,
-                    resp: IpcResp::Containers(Ok(())),
-                })?;
-
-                debug!("Returning containers auth response generated: {:?}", resp);
-                Ok(resp)
-            })
-            .map_err(AuthError::from)
-    })
-    .map_err(|err| format!("Failed to generate response: {}", err))
-}
-
-// Helper function to generate an unregistered authorisation response
-fn gen_unreg_auth_response(req_id: u32) -> Result<String, String> {
-    let bootstrap_cfg = unwrap!(safe_core_client::bootstrap_config());
-
-    debug!("Encoding response... {:?}", bootstrap_cfg);
-    let resp = unwrap!(encode_msg(&IpcMsg::Resp {
-        req_id,
-        resp: IpcResp::Unregistered(Ok(bootstrap_cfg)),
-    }));
-
-    debug!("Returning unregistered auth response generated: {:?}", resp);
-    Ok(resp)
-}
-
-// Helper function to generate an authorisation response for sharing MD
-fn gen_shared_md_auth_response(
-    authenticator: &Authenticator,
-    req_id: u32,
-    share_mdata_req: ShareMDataReq,
-) -> Result<String, String> {
-    auth_run_helper(authenticator, move |client| {
-        let client_cloned0 = client.clone();
-        let client_cloned1 = client.clone();
-        config::get_app(client, &share_mdata_req.app.id).and_then(move |app_info| {
-            let user = PublicKey::from(app_info.keys.bls_pk);
-            let num_mdata = share_mdata_req.mdata.len();
-            stream::iter_ok(share_mdata_req.mdata.into_iter())
-                .map(move |mdata| {
-                    client_cloned0
-                        .get_seq_mdata_shell(mdata.name, mdata.type_tag)
-                        .map(|md| (md.version(), mdata))
-                })
-                .buffer_unordered(num_mdata)
-                .map(move |(version, mdata)| {
-                    let address = MDataAddress::Seq {
-                        name: mdata.name,
-                        tag: mdata.type_tag,
-                    };
-                    client_cloned1.set_mdata_user_permissions(
-                        address,
-                        user,
-                        mdata.perms,
-                        version + 1,
-                    )
-                })
-                .buffer_unordered(num_mdata)
-                .map_err(AuthError::from)
-                .for_each(|()| Ok(()))
-                .and_then(move |()| {
-                    debug!("Encoding response...");
-                    let resp = encode_msg(&IpcMsg::Resp {
-                        req_id,
-                        resp: IpcResp::ShareMData(Ok(())),
-                    })?;
-
-                    debug!("Returning shared MD auth response generated: {:?}", resp);
-                    Ok(resp)
-                })
-        })
-    })
-    .map_err(|err| format!("Failed to generate response: {}", err))
-}
-
-pub fn parse_hex(hex_str: &str) -> Vec<u8> {
-    let mut hex_bytes = hex_str
-        .as_bytes()
-        .iter()
-        .filter_map(|b| match b {
-            b'0'..=b'9' => Some(b - b'0'),
-            b'a'..=b'f' => Some(b - b'a' + 10),
-            b'A'..=b'F' => Some(b - b'A' + 10),
-            _ => None,
-        })
-        .fuse();
-
-    let mut bytes = Vec::new();
-    while let (Some(h), Some(l)) = (hex_bytes.next(), hex_bytes.next()) {
-        bytes.push(h << 4 | l)
-    }
-    bytes
-}
-
-fn sk_from_hex(hex_str: &str) -> Result<SecretKey, String> {
-    let sk_bytes = parse_hex(&hex_str);
-    bincode::deserialize(&sk_bytes)
-        .map_err(|_| "Failed to deserialize provided secret key".to_string())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{authed_apps, authorise_app, create_acc, log_in, revoke_app};
-    use safe_core::client::test_create_balance;
-    use safe_core::ipc::req::IpcReq;
-    use safe_core::ipc::Permission;
-    use safe_nd::Coins;
-    use std::collections::{BTreeSet, HashMap};
-    use std::str::FromStr;
-    use threshold_crypto::{serde_impl::SerdeSecret, SecretKey};
-
-    fn gen_random_sk_hex() -> (String, SecretKey) {
-        let sk = SecretKey::random();
-        let sk_serialised = bincode::serialize(&SerdeSecret(&sk))
-            .expect("Failed to serialise the generated secret key");
-        let sk_hex = sk_serialised.iter().map(|b| format!("{:02x}", b)).collect();
-        (sk_hex, sk)
-    }
-
-    // The app auth request strings encode the following app info:
-    /*
-        id: 'net.maidsafe.cli',
-        name: 'SAFE CLI',
-        vendor: 'MaidSafe.net Ltd'
-    */
-    // perms: [ ("_public", {Read} ) ]
-    static APP_AUTH_REQ: &str = "bAAAAAACNZZQ4WAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAAAAAAAAAAAAAB";
-    // perms: [ ("_public", {Read} ), ("_music", {Insert, Update}) ]
-    static CONT_AUTH_REQ: &str = "bAAAAAADLZ663OAIAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAEAAAAAAAAAAAAYAAAAAAAAAAAX3NOVZWSYYCAAAAAAAAAAAACAAAAABAAAAAA4AAAAAAAAAAAX3QOVRGY2LDAEAAAAAAAAAAAAAAAAAAC";
-
-    static APP_NAME: &str = "SAFE CLI";
-    static APP_ID: &str = "net.maidsafe.cli";
-
-    fn random_str() -> String {
-        (0..4).map(|_| rand::random::<char>()).collect()
-    }
-
-    #[test]
-    fn account_creation_and_login_test() {
-        let (sk, secret_key) = &gen_random_sk_hex();
-        test_create_balance(secret_key, Coins::from_str("10").unwrap()).unwrap();
-        let my_secret = &(random_str());
-        let my_password = &(random_str());
-
-        // successfully create an account
-        let acc_created = create_acc(sk, my_secret, my_password);
-        match acc_created {
-            Ok(_) => assert!(true),
-            Err(err) => panic!(err),
-        }
-
-        // fail to create an account with same secret
-        let acc_not_created = create_acc(sk, my_secret, my_password);
-        match acc_not_created {
-            Ok(_) => panic!("Account shouldn't have been created successfully"),
-            Err(err) => assert_eq!(
-                err,
-                "Failed to create an account: SndError(LoginPacketExists)"
-            ),
-        }
-
-        // successfully log in
-        let auth = log_in(my_secret, my_password);
-        match auth {
-            Ok(_) => assert!(true),
-            Err(err) => panic!(err),
-        }
-
-        // fail to log in with invalid secret
-        let other_secret = &(random_str());
-        let auth = log_in(other_secret, my_password);
-        match auth {
-            Ok(_) => panic!("Shouldn't have logged in sucessfully"),
-            Err(err) => assert_eq!(err, "Failed to log in: SndError(NoSuchLoginPacket)"),
-        }
-
-        // fail to log in with invalid password
-        let other_password = &(random_str());
-        let auth = log_in(my_secret, other_password);
-        match auth {
-            Ok(_) => panic!("Shouldn't have logged in sucessfully"),
-            Err(err) => assert_eq!(err, "Failed to log in: CoreError(Symmetric decryption failure - CoreError::SymmetricDecipherFailure)"),
-        }
-    }
-
-    #[test]
-    fn authorise_apps_tests() {
-        let (sk, secret_key) = &gen_random_sk_hex();
-        test_create_balance(secret_key, Coins::from_str("20").unwrap()).unwrap();
-        let my_secret = &(random_str());
-        let my_password = &(random_str());
-
-        let auth = unwrap!(create_acc(sk, my_secret, my_password));
-
-        // fail to authorise app with invalid request
-        let invalid_auth_req = "fddfds";
-        let auth_response = authorise_app(&auth, invalid_auth_req, &|_| true);
-        match auth_response {
-            Ok(_) => panic!("It should have failed to authorise"),
-            Err(err) => assert_eq!(
-                err,
-                "Failed to decode the auth request string: EncodeDecodeError"
-            ),
-        }
-
-        // successfully authorise a registered app auth request
-        let auth_response = authorise_app(&auth, APP_AUTH_REQ, &|_| true);
-        match auth_response {
-            Ok(res) => assert!(!res.is_empty()),
-            Err(err) => panic!(err),
-        }
-
-        // successfully authorise an unregistered app auth request
-        /*let unreg_auth_req =
-            "bAAAAAAFVMRTRUAQAAAACMAAAAAAAAAAANZSXILTNMFUWI43BMZSS45DFON2C4YLVORUGK3TUNFRWC5DPOIXGG3DJFZUWIAI";
-        let unreg_auth_res = "bAEAAAAFVMRTRUAQAAAAAAAAAAAAQAAAAAAAAAAAOAAAAAAAAAAADCMRXFYYC4MBOGE5DSOJZHEAAAAAAAAAAAAAB";
-        let auth_response = authorise_app(&auth, unreg_auth_req, &|_| true);
-        match auth_response {
-            Ok(res) => assert_eq!(res, unreg_auth_res),
-            Err(err) => panic!(err),
-        }*/
-
-        // successfully authorise containers request
-        let cont_auth_res = "bAEAAAADLZ663OAIAAAAAAAAAAAAQ";
-        let auth_response = authorise_app(&auth, CONT_AUTH_REQ, &|_| true);
-        match auth_response {
-            Ok(res) => assert_eq!(res, cont_auth_res),
-            Err(err) => panic!(err),
-        }
-
-        // fail to authorise share MD request for an inexisting MD
-        let shared_md_auth_req = "bAAAAAAFG5WDFKAYAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSACAAAAAAAAAAACATQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAI";
-        let auth_response = authorise_app(&auth, shared_md_auth_req, &|_| true);
-        match auth_response {
-            Ok(_) => panic!("It should have failed to authorise to share MD"),
-            Err(err) => assert_eq!(
-                err,
-                "Failed to generate response: Core error: Data error -> Requested data not found"
-            ),
-        }
-
-        // fail to authorise containers request for an inexisting container
-        let invalid_cont_auth_req = "bAAAAAAG5SZSKYAIAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSACAAAAAAAAAAABAAAAAAAAAAAAX3JNZ3GC3DJMQBAAAAAAAAAAAABAAAAAAQAAAAAC";
-        let auth_response = authorise_app(&auth, invalid_cont_auth_req, &|_| true);
-        match auth_response {
-            Ok(_) => panic!("It should have failed to authorise invalid container request"),
-            Err(err) => assert_eq!(
-                err,
-                "Failed to generate response: \'_invalid\' not found in the access container"
-            ),
-        }
-
-        // fail to authorise app with invalid request due to inexisting container in the list of requested perms
-        /* TODO: this doesn't fail as it was expected
-        let invalid_app_auth_req = "bAAAAAAF5Q66DAAAAAAACMAAAAAAAAAAANZSXILTNMFUWI43BMZSS45DFON2C4YLVORUGK3TUNFRWC5DPOIXGG3DJFZUWIAILAAAAAAAAAAAF65DFON2F643DN5YGKGYAAAAAAAAAABJHK43UEBAXK5DIMVXHI2LDMF2G64RAINGESICUMVZXIEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIAAAAAAAAAAAEAAAAAAAAAAAC7NFXHMYLMNFSACAAAAAAAAAAAAAAAAAAB";
-        let auth_response = authorise_app(&auth, invalid_app_auth_req, &|_| true);
-        match auth_response {
-            Ok(_) => panic!("It should have failed to authorise app request"),
-            Err(err) => assert_eq!(err, "Failed to generate response: \'_invalid_container\' not found in the access container"),
-        }*/
-    }
-
-    #[test]
-    fn deny_authorisation_reqs_tests() {
-        let (sk, secret_key) = &gen_random_sk_hex();
-        test_create_balance(secret_key, Coins::from_str("30").unwrap()).unwrap();
-        let my_secret = &(random_str());
-        let my_password = &(random_str());
-
-        let auth = unwrap!(create_acc(sk, my_secret, my_password));
-
-        // verify app info passed to allow/deny callback for auth requests, and verify the AuthDenied response
-        let auth_denied_encoded_response = "bAEAAAACNZZQ4WAAAAAAACAAAAAAAAAAAAE";
-        let auth_response = authorise_app(&auth, APP_AUTH_REQ, &|auth_req| {
-            let (app_exchange_info, containers) = match auth_req {
-                IpcReq::Auth(app_auth_req) => {
-                    assert_eq!(app_auth_req.app_container, false);
-                    (app_auth_req.app, app_auth_req.containers)
-                }
-                _ => panic!("Auth req info not received"),
-            };
-            assert_eq!(app_exchange_info.id, APP_ID);
-            assert_eq!(app_exchange_info.name, APP_NAME);
-            assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
-            assert_eq!(containers, HashMap::new());
-            false
-        });
-        match auth_response {
-            Ok(res) => assert_eq!(res, auth_denied_encoded_response),
-            Err(_) => panic!("It should have returned an AuthDenied response rather than erroing"),
-        };
-
-        authorise_app(&auth, APP_AUTH_REQ, &|_| true).expect("Failed to authorise an app");
-        let auth_denied_encoded_response = "bAEAAAADLZ663OAAAAAAACAAAAAAAAAAAAE";
-        let auth_response = authorise_app(&auth, CONT_AUTH_REQ, &|authed_apps_res| {
-            let (app_exchange_info, containers) = match authed_apps_res {
-                IpcReq::Containers(cont_req) => (cont_req.app, cont_req.containers),
-                _ => panic!("Containers auth req info not received"),
-            };
-
-            assert_eq!(app_exchange_info.id, APP_ID);
-            assert_eq!(app_exchange_info.name, APP_NAME);
-            assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
-
-            let mut music_perms = BTreeSet::new();
-            music_perms.insert(Permission::Insert);
-            music_perms.insert(Permission::Update);
-
-            let mut public_perms = BTreeSet::new();
-            public_perms.insert(Permission::Read);
-
-            let mut cont_perms = HashMap::new();
-            cont_perms.insert("_music".to_string(), music_perms);
-            cont_perms.insert("_public".to_string(), public_perms);
-            assert_eq!(containers, cont_perms);
-
-            false
-        });
-        match auth_response {
-            Ok(res) => assert_eq!(res, auth_denied_encoded_response),
-            Err(err) => panic!(
-                "It should have returned an AuthDenied response rather than erroing: {:?}",
-                err
-            ),
-        }
-    }
-
-    #[test]
-    fn authed_apps_tests() {
-        let (sk, secret_key) = &gen_random_sk_hex();
-        test_create_balance(secret_key, Coins::from_str("40").unwrap()).unwrap();
-        let my_secret = &(random_str());
-        let my_password = &(random_str());
-
-        let auth = unwrap!(create_acc(sk, my_secret, my_password));
-
-        // list of authorised apps shall be empty with a freshly created account
-        let authed_apps_res = authed_apps(&auth);
-        match authed_apps_res {
-            Ok(authed_vec) => assert_eq!(authed_vec.len(), 0), // This should pass
-            Err(_) => panic!("It should have retieved the list of authorised apps"),
-        }
-
-        // after authorising an app it is returned in the list retrieved by authed_apps
-        authorise_app(&auth, APP_AUTH_REQ, &|_| true)
-            .expect("Failed to authorise an app before calling authed_apps");
-        let authed_apps_res = authed_apps(&auth);
-        match authed_apps_res {
-            Ok(authed_vec) => {
-                assert_eq!(authed_vec.len(), 1);
-                let app_exchange_info = &authed_vec[0].app;
-                assert_eq!(app_exchange_info.id, APP_ID);
-                assert_eq!(app_exchange_info.name, APP_NAME);
-                assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
-                assert!(authed_vec[0].perms.is_empty());
-            }
-            Err(_) => panic!("It should have retrieved the list of authorised apps"),
-        }
-
-        // after authorising a containers auth request it is returned in the permissions list retrieved by authed_apps
-        authorise_app(&auth, CONT_AUTH_REQ, &|_| true)
-            .expect("Failed to authorise containers auth req before calling authed_apps");
-        let authed_apps_res = authed_apps(&auth);
-        match authed_apps_res {
-            Ok(mut authed_vec) => {
-                assert_eq!(authed_vec.len(), 1);
-                let app_exchange_info = &authed_vec[0].app;
-                assert_eq!(app_exchange_info.id, APP_ID);
-                assert_eq!(app_exchange_info.name, APP_NAME);
-                assert_eq!(app_exchange_info.vendor, "MaidSafe.net Ltd");
-
-                let mut public_perms = BTreeSet::new();
-                public_perms.insert(Permission::Read);
-
-                let mut music_perms = BTreeSet::new();
-                music_perms.insert(Permission::Insert);
-                music_perms.insert(Permission::Update);
-
-                let mut cont_perms = vec![
-                    ("_public".to_string(), public_perms),
-                    ("_music".to_string(), music_perms),
-                ];
-                cont_perms.sort();
-                authed_vec[0].perms.sort();
-                assert_eq!(authed_vec[0].perms, cont_perms);
-            }
-            Err(_) => panic!("It should have retrieved the list of authorised apps"),
-        }
-    }
-
-    #[test]
-    fn revoke_app_tests() {
-        let (sk, secret_key) = &gen_random_sk_hex();
-        test_create_balance(secret_key, Coins::from_str("50").unwrap()).unwrap();
-        let my_secret = &(random_str());
-        let my_password = &(random_str());
-
-        let auth = unwrap!(create_acc(sk, my_secret, my_password));
-
-        // after revoking an app it is removed from the list retrieved by authed_apps
-        authorise_app(&auth, APP_AUTH_REQ, &|_| true)
-            .expect("Failed to authorise an app before calling authed_apps");
-        revoke_app(&auth, APP_ID.to_string())
-            .expect("Failed to revoke the previously authorised app");
-
-        let authed_apps_res = authed_apps(&auth);
-        match authed_apps_res {
-            Ok(authed_vec) => assert_eq!(authed_vec.len(), 0),
-            Err(_) => panic!("It should have retrieved the list of authorised apps"),
-        }
-    }
-}
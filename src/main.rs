@@ -6,31 +6,20 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-#[macro_use]
-extern crate prettytable;
-
-#[cfg(not(feature = "mock-network"))]
-#[macro_use]
-extern crate self_update;
-
-#[macro_use]
-extern crate unwrap;
-
-mod authd;
-mod cli;
-mod cli_helpers;
-
-use cli::run;
-use env_logger;
-use log::{debug, error};
+use log::debug;
+use safe_auth::{init_logger, install_panic_handler, run_with_args, StdIo};
+use std::env;
 use std::process;
 
 fn main() {
-    env_logger::init();
+    init_logger();
+    install_panic_handler();
     debug!("Starting Authenticator...");
 
-    if let Err(e) = run() {
-        error!("safe_auth error: {}", e);
-        process::exit(1);
+    let args: Vec<String> = env::args().collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = run_with_args(&args, StdIo::default());
+    if !result.is_success() {
+        process::exit(result.exit_code);
     }
 }
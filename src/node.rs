@@ -0,0 +1,221 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// Node.js module exposed when built with the `node` feature, e.g.:
+//
+//     const safeAuth = require('./safe_auth.node');
+//     safeAuth.logIn('my secret', 'my password', (err, handle) => {
+//         const info = safeAuth.inspectAuthReq(req);
+//         safeAuth.authorise(handle, req, info.app.id === 'net.maidsafe.cli', (err, resp) => {});
+//     });
+//
+// The operations which connect to the network run in a background thread and report
+// their result to the callback passed as last argument, following Node's conventions.
+// Logged in authenticators are referred to by a numeric handle until `logOut` is called.
+
+use crate::{authed_apps, authorise_app, create_acc, inspect_auth_req, log_in, revoke_app};
+use lazy_static::lazy_static;
+use neon::prelude::*;
+use neon::register_module;
+use safe_authenticator::Authenticator;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    static ref AUTHENTICATORS: Mutex<HashMap<u32, Arc<Mutex<Authenticator>>>> =
+        Mutex::new(HashMap::new());
+}
+static NEXT_HANDLE: AtomicU32 = AtomicU32::new(1);
+
+// Operations which are performed in a background thread
+enum Op {
+    LogIn {
+        secret: String,
+        password: String,
+    },
+    CreateAcc {
+        sk: String,
+        secret: String,
+        password: String,
+    },
+    Authorise {
+        handle: u32,
+        req: String,
+        allow: bool,
+    },
+    Revoke {
+        handle: u32,
+        app_id: String,
+    },
+    AuthedApps {
+        handle: u32,
+    },
+}
+
+// Result of an operation, to be converted into a JavaScript value
+enum OpOutput {
+    Handle(u32),
+    Text(String),
+    Json(String),
+    Done,
+}
+
+struct OpTask(Op);
+
+impl Task for OpTask {
+    type Output = OpOutput;
+    type Error = String;
+    type JsEvent = JsValue;
+
+    fn perform(&self) -> Result<OpOutput, String> {
+        match &self.0 {
+            Op::LogIn { secret, password } => log_in(secret, password)
+                .map(|authenticator| OpOutput::Handle(store(authenticator)))
+                .map_err(|err| err.to_string()),
+            Op::CreateAcc {
+                sk,
+                secret,
+                password,
+            } => create_acc(sk, secret, password)
+                .map(|authenticator| OpOutput::Handle(store(authenticator)))
+                .map_err(|err| err.to_string()),
+            Op::Authorise { handle, req, allow } => {
+                let authenticator = get(*handle)?;
+                let authenticator = unwrap!(authenticator.lock());
+                let resp = if *allow {
+                    authorise_app(&authenticator, req, &|_| true)
+                } else {
+                    authorise_app(&authenticator, req, &|_| false)
+                };
+                resp.map(OpOutput::Text).map_err(|err| err.to_string())
+            }
+            Op::Revoke { handle, app_id } => {
+                let authenticator = get(*handle)?;
+                let authenticator = unwrap!(authenticator.lock());
+                revoke_app(&authenticator, app_id.clone())
+                    .map(|_| OpOutput::Done)
+                    .map_err(|err| err.to_string())
+            }
+            Op::AuthedApps { handle } => {
+                let authenticator = get(*handle)?;
+                let authenticator = unwrap!(authenticator.lock());
+                let apps = authed_apps(&authenticator).map_err(|err| err.to_string())?;
+                serde_json::to_string(&apps)
+                    .map(OpOutput::Json)
+                    .map_err(|err| err.to_string())
+            }
+        }
+    }
+
+    fn complete(self, mut cx: TaskContext, result: Result<OpOutput, String>) -> JsResult<JsValue> {
+        match result {
+            Ok(OpOutput::Handle(handle)) => Ok(cx.number(handle).upcast()),
+            Ok(OpOutput::Text(text)) => Ok(cx.string(text).upcast()),
+            Ok(OpOutput::Json(json)) => json_parse(&mut cx, &json),
+            Ok(OpOutput::Done) => Ok(cx.undefined().upcast()),
+            Err(err) => cx.throw_error(err),
+        }
+    }
+}
+
+fn store(authenticator: Authenticator) -> u32 {
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::SeqCst);
+    let _ = unwrap!(AUTHENTICATORS.lock()).insert(handle, Arc::new(Mutex::new(authenticator)));
+    handle
+}
+
+fn get(handle: u32) -> Result<Arc<Mutex<Authenticator>>, String> {
+    unwrap!(AUTHENTICATORS.lock())
+        .get(&handle)
+        .cloned()
+        .ok_or_else(|| format!("No authenticator is logged in with handle {}", handle))
+}
+
+// Converts a JSON string into a JavaScript value with `JSON.parse`
+fn json_parse<'a, C: Context<'a>>(cx: &mut C, json: &str) -> JsResult<'a, JsValue> {
+    let json_obj: Handle<JsObject> = cx.global().get(cx, "JSON")?.downcast_or_throw(cx)?;
+    let parse: Handle<JsFunction> = json_obj.get(cx, "parse")?.downcast_or_throw(cx)?;
+    let args = vec![cx.string(json)];
+    parse.call(cx, json_obj, args)
+}
+
+fn js_log_in(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let secret = cx.argument::<JsString>(0)?.value();
+    let password = cx.argument::<JsString>(1)?.value();
+    let cb = cx.argument::<JsFunction>(2)?;
+    OpTask(Op::LogIn { secret, password }).schedule(cb);
+    Ok(cx.undefined())
+}
+
+fn js_create_acc(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let sk = cx.argument::<JsString>(0)?.value();
+    let secret = cx.argument::<JsString>(1)?.value();
+    let password = cx.argument::<JsString>(2)?.value();
+    let cb = cx.argument::<JsFunction>(3)?;
+    OpTask(Op::CreateAcc {
+        sk,
+        secret,
+        password,
+    })
+    .schedule(cb);
+    Ok(cx.undefined())
+}
+
+fn js_log_out(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let handle = cx.argument::<JsNumber>(0)?.value() as u32;
+    let _ = unwrap!(AUTHENTICATORS.lock()).remove(&handle);
+    Ok(cx.undefined())
+}
+
+fn js_inspect_auth_req(mut cx: FunctionContext) -> JsResult<JsValue> {
+    let req = cx.argument::<JsString>(0)?.value();
+    let info = match inspect_auth_req(&req) {
+        Ok(info) => info,
+        Err(err) => return cx.throw_error(err.to_string()),
+    };
+    match serde_json::to_string(&info) {
+        Ok(json) => json_parse(&mut cx, &json),
+        Err(err) => cx.throw_error(err.to_string()),
+    }
+}
+
+fn js_authorise(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let handle = cx.argument::<JsNumber>(0)?.value() as u32;
+    let req = cx.argument::<JsString>(1)?.value();
+    let allow = cx.argument::<JsBoolean>(2)?.value();
+    let cb = cx.argument::<JsFunction>(3)?;
+    OpTask(Op::Authorise { handle, req, allow }).schedule(cb);
+    Ok(cx.undefined())
+}
+
+fn js_revoke(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let handle = cx.argument::<JsNumber>(0)?.value() as u32;
+    let app_id = cx.argument::<JsString>(1)?.value();
+    let cb = cx.argument::<JsFunction>(2)?;
+    OpTask(Op::Revoke { handle, app_id }).schedule(cb);
+    Ok(cx.undefined())
+}
+
+fn js_authed_apps(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let handle = cx.argument::<JsNumber>(0)?.value() as u32;
+    let cb = cx.argument::<JsFunction>(1)?;
+    OpTask(Op::AuthedApps { handle }).schedule(cb);
+    Ok(cx.undefined())
+}
+
+register_module!(mut cx, {
+    cx.export_function("logIn", js_log_in)?;
+    cx.export_function("createAcc", js_create_acc)?;
+    cx.export_function("logOut", js_log_out)?;
+    cx.export_function("inspectAuthReq", js_inspect_auth_req)?;
+    cx.export_function("authorise", js_authorise)?;
+    cx.export_function("revoke", js_revoke)?;
+    cx.export_function("authedApps", js_authed_apps)?;
+    Ok(())
+});
@@ -0,0 +1,208 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::errors::Error;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The interval the mutations start being spaced by once throttled, if no interval is set
+const DEFAULT_THROTTLED_INTERVAL_MS: u64 = 500;
+const DEFAULT_MAX_INTERVAL_MS: u64 = 30_000;
+const DEFAULT_RETRIES: u64 = 3;
+
+// The pacing currently set, zero meaning the default one. The retries are stored plus one,
+// as no retries can be set as well.
+static INTERVAL_MS: AtomicU64 = AtomicU64::new(0);
+static MAX_INTERVAL_MS: AtomicU64 = AtomicU64::new(0);
+static RETRIES: AtomicU64 = AtomicU64::new(0);
+
+/// Pacing of the mutations sent to the network by the bulk operations, e.g. revoking several
+/// apps, so they stay under the rate limits of the vaults. The interval between mutations is
+/// doubled each time the network throttles one of them, up to the maximum interval, and the
+/// mutation is retried; it then goes back down to the interval set as they succeed.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PacingSettings {
+    /// The minimum time between two mutations, in milliseconds, none by default
+    pub interval_ms: Option<u64>,
+    /// The maximum time between two mutations once throttled, 30 seconds by default
+    pub max_interval_ms: Option<u64>,
+    /// How many times a throttled mutation is retried, 3 by default
+    pub retries: Option<u64>,
+}
+
+/// # Set the pacing of the bulk operations
+///
+/// Sets the pacing to be applied to the mutations of the bulk operations from now on.
+pub fn set_pacing(pacing: &PacingSettings) {
+    debug!("Pacing of the bulk operations set to: {:?}", pacing);
+    INTERVAL_MS.store(pacing.interval_ms.unwrap_or(0), Ordering::Relaxed);
+    MAX_INTERVAL_MS.store(pacing.max_interval_ms.unwrap_or(0), Ordering::Relaxed);
+    RETRIES.store(
+        pacing.retries.map_or(0, |retries| retries + 1),
+        Ordering::Relaxed,
+    );
+}
+
+// Whether the network rejected the mutation because too many were sent, or didn't respond
+// in time, which is how the vaults under load behave
+pub(crate) fn is_throttled(err: &Error) -> bool {
+    match err {
+        Error::NetworkError(_) | Error::Timeout(_) => true,
+        Error::AuthenticatorError(msg) => {
+            let msg = msg.to_lowercase();
+            [
+                "requesttimeout",
+                "rate limit",
+                "throttl",
+                "too many requests",
+            ]
+            .iter()
+            .any(|pattern| msg.contains(pattern))
+        }
+        _ => false,
+    }
+}
+
+// Spaces the mutations of a bulk operation, each one being run with `run`
+pub(crate) struct Pacer {
+    min_interval: Duration,
+    max_interval: Duration,
+    retries: u64,
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl Pacer {
+    // A pacer with the pacing currently set
+    pub fn current() -> Self {
+        let retries = match RETRIES.load(Ordering::Relaxed) {
+            0 => DEFAULT_RETRIES,
+            retries => retries - 1,
+        };
+        Self::with_settings(
+            INTERVAL_MS.load(Ordering::Relaxed),
+            match MAX_INTERVAL_MS.load(Ordering::Relaxed) {
+                0 => DEFAULT_MAX_INTERVAL_MS,
+                max => max,
+            },
+            retries,
+        )
+    }
+
+    fn with_settings(interval_ms: u64, max_interval_ms: u64, retries: u64) -> Self {
+        let min_interval = Duration::from_millis(interval_ms);
+        Self {
+            min_interval,
+            max_interval: Duration::from_millis(max_interval_ms.max(interval_ms)),
+            retries,
+            interval: min_interval,
+            last: None,
+        }
+    }
+
+    // Runs the mutation once the interval since the previous one elapsed, retrying it
+    // more slowly while it's throttled
+    pub fn run<T, F>(&mut self, mut mutation: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Result<T, Error>,
+    {
+        let mut attempt = 0;
+        loop {
+            if let Some(last) = self.last {
+                let elapsed = last.elapsed();
+                if elapsed < self.interval {
+                    thread::sleep(self.interval - elapsed);
+                }
+            }
+            let result = mutation();
+            self.last = Some(Instant::now());
+            match result {
+                Err(err) if is_throttled(&err) && attempt < self.retries => {
+                    attempt += 1;
+                    self.slow_down();
+                    warn!(
+                        "The network throttled the operation, retrying it in {} ms: {}",
+                        self.interval.as_millis(),
+                        err
+                    );
+                }
+                Err(err) if is_throttled(&err) => {
+                    self.slow_down();
+                    return Err(err);
+                }
+                Ok(value) => {
+                    self.interval = (self.interval / 2).max(self.min_interval);
+                    return Ok(value);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn slow_down(&mut self) {
+        let interval = if self.interval.as_millis() == 0 {
+            Duration::from_millis(DEFAULT_THROTTLED_INTERVAL_MS)
+        } else {
+            self.interval * 2
+        };
+        self.interval = interval.min(self.max_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_throttled, Pacer};
+    use crate::errors::Error;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn pacer_tests() {
+        assert!(is_throttled(&Error::AuthenticatorError(String::from(
+            "Failed to revoke permissions: CoreError(RequestTimeout)"
+        ))));
+        assert!(!is_throttled(&Error::AuthenticatorError(String::from(
+            "Failed to revoke permissions: app 'net.maidsafe.cli' is not registered"
+        ))));
+
+        // Throttled twice, retried with the interval doubled each time
+        let mut pacer = Pacer::with_settings(10, 25, 3);
+        let mut attempts = 0;
+        let started = Instant::now();
+        let result = pacer.run(|| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(Error::Timeout(String::from(
+                    "Revoke operation timed out after 5 seconds",
+                )))
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(unwrap!(result), 3);
+        assert!(started.elapsed() >= Duration::from_millis(45));
+        assert_eq!(pacer.interval, Duration::from_micros(12_500));
+
+        // Not retried once the retries are exhausted, nor if it's not throttled
+        let mut pacer = Pacer::with_settings(0, 10, 1);
+        let mut attempts = 0;
+        let result: Result<(), Error> = pacer.run(|| {
+            attempts += 1;
+            Err(Error::NetworkError(String::from(
+                "Failed to connect to the network",
+            )))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 2);
+        let result: Result<(), Error> =
+            pacer.run(|| Err(Error::InvalidInput(String::from("Not throttled"))));
+        assert!(result.is_err());
+    }
+}
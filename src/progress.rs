@@ -0,0 +1,63 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+/// Progress of an operation, reported each time one of its steps starts,
+/// and once more when all of them were completed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationProgress {
+    /// Name of the operation, e.g. "Log in"
+    pub operation: String,
+    /// Description of the step which started, or of the outcome once all of them were completed
+    pub step: String,
+    /// Number of steps already completed
+    pub completed: usize,
+    /// Total number of steps of the operation
+    pub total: usize,
+}
+
+impl OperationProgress {
+    pub fn is_finished(&self) -> bool {
+        self.completed == self.total
+    }
+}
+
+/// Receives the progress of the long operations, e.g. to show a progress bar.
+/// It's implemented for any `Fn(&OperationProgress)` closure.
+pub trait ProgressReporter {
+    fn report(&self, progress: &OperationProgress);
+}
+
+impl<F: Fn(&OperationProgress)> ProgressReporter for F {
+    fn report(&self, progress: &OperationProgress) {
+        self(progress)
+    }
+}
+
+// Reporter used by the operations invoked without one
+pub(crate) fn no_progress(_: &OperationProgress) {}
+
+// Helper function to report the progress of an operation, logging it as well
+pub(crate) fn report(
+    reporter: &dyn ProgressReporter,
+    operation: &str,
+    step: &str,
+    completed: usize,
+    total: usize,
+) {
+    let progress = OperationProgress {
+        operation: operation.to_string(),
+        step: step.to_string(),
+        completed,
+        total,
+    };
+    debug!("{} ({}/{}): {}", operation, completed, total, step);
+    reporter.report(&progress);
+}
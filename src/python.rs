@@ -0,0 +1,96 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// Python module exposed when built with the `python` feature, e.g.:
+//
+//     import safe_auth
+//     auth = safe_auth.log_in("my secret", "my password")
+//     resp = auth.authorise(req, lambda info: info["app"]["id"] == "net.maidsafe.cli")
+//     auth.revoke("net.maidsafe.cli")
+//
+// Lists and request details are passed to Python as dicts, and errors are raised
+// as `RuntimeError` exceptions with the description of the error.
+
+use crate::{authed_apps, authorise_app, create_acc, inspect_auth_req, log_in, revoke_app, Error};
+use pyo3::exceptions::RuntimeError;
+use pyo3::prelude::*;
+use safe_authenticator::Authenticator;
+use serde::Serialize;
+
+#[pyclass(name = Authenticator)]
+struct PyAuthenticator {
+    authenticator: Authenticator,
+}
+
+#[pymethods]
+impl PyAuthenticator {
+    /// Authorises the request, the `allow` callable receives the details of
+    /// the request as a dict and returns whether it shall be allowed.
+    /// It returns the auth response string to be sent to the app.
+    fn authorise(&self, py: Python, req: &str, allow: PyObject) -> PyResult<String> {
+        let info = to_py_dict(py, &inspect_auth_req(req).map_err(to_py_err)?)?;
+        let allowed: bool = allow.call1(py, (info,))?.extract(py)?;
+        let resp = if allowed {
+            authorise_app(&self.authenticator, req, &|_| true)
+        } else {
+            authorise_app(&self.authenticator, req, &|_| false)
+        };
+        resp.map_err(to_py_err)
+    }
+
+    /// Revokes all the permissions granted to the app
+    fn revoke(&self, app_id: String) -> PyResult<()> {
+        revoke_app(&self.authenticator, app_id).map_err(to_py_err)
+    }
+
+    /// Returns the list of authorised apps, with the permissions granted to each of them
+    fn authed_apps(&self, py: Python) -> PyResult<PyObject> {
+        to_py_dict(py, &authed_apps(&self.authenticator).map_err(to_py_err)?)
+    }
+}
+
+#[pymodule]
+fn safe_auth(_py: Python, m: &PyModule) -> PyResult<()> {
+    /// Logs in to an existing account and returns an `Authenticator`
+    #[pyfn(m, "log_in")]
+    fn log_in_py(py: Python, secret: &str, password: &str) -> PyResult<Py<PyAuthenticator>> {
+        let authenticator = py
+            .allow_threads(|| log_in(secret, password))
+            .map_err(to_py_err)?;
+        Py::new(py, PyAuthenticator { authenticator })
+    }
+
+    /// Creates a new account, paying with the coins of the secret key provided,
+    /// and returns an `Authenticator` logged in to it
+    #[pyfn(m, "create_acc")]
+    fn create_acc_py(
+        py: Python,
+        sk: &str,
+        secret: &str,
+        password: &str,
+    ) -> PyResult<Py<PyAuthenticator>> {
+        let authenticator = py
+            .allow_threads(|| create_acc(sk, secret, password))
+            .map_err(to_py_err)?;
+        Py::new(py, PyAuthenticator { authenticator })
+    }
+
+    m.add_class::<PyAuthenticator>()?;
+    Ok(())
+}
+
+fn to_py_err(err: Error) -> PyErr {
+    PyErr::new::<RuntimeError, _>(err.to_string())
+}
+
+// Converts any serialisable value into its Python equivalent, going through JSON
+fn to_py_dict<T: Serialize>(py: Python, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value)
+        .map_err(|err| PyErr::new::<RuntimeError, _>(err.to_string()))?;
+    Ok(py.import("json")?.call1("loads", (json,))?.to_object(py))
+}
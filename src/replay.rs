@@ -0,0 +1,314 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::account::{AccountInfo, AccountKeys};
+use crate::apps::{
+    auth_action, gen_auth_denied_response, AuthAction, AuthedAppsDiff, AuthedAppsList,
+};
+use crate::backend::AuthBackend;
+use crate::decode::decode_auth_req;
+use crate::errors::Error;
+use crate::AuthAllowPrompt;
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Version of the format used to store the recordings
+const RECORDING_FORMAT_VERSION: u64 = 1;
+
+/// An operation performed on an `AuthBackend`, with its arguments
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "args")]
+pub enum BackendCall {
+    AuthoriseApp { req: String },
+    AuthedApps,
+    RevokeApp { app_id: String },
+    AccInfo,
+    AccKeys,
+    BackupAccount,
+    RestoreAccount { backup: Vec<u8> },
+}
+
+/// An operation performed during a recorded run, and the outcome it had
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub call: BackendCall,
+    pub result: Result<Value, Error>,
+}
+
+// Content of a recording file
+#[derive(Serialize, Deserialize)]
+struct Recording {
+    version: u64,
+    calls: Vec<RecordedCall>,
+}
+
+/// # Record the operations performed on a backend
+///
+/// Wraps an `AuthBackend`, e.g. a logged in `Authenticator`, and writes each operation
+/// performed on it, together with its outcome, to a JSON file. The file is rewritten after
+/// each operation so the recording is kept even if the execution is interrupted.
+/// The recording can be replayed later with a `ReplayBackend`.
+pub struct RecordingBackend<B: AuthBackend> {
+    inner: B,
+    path: PathBuf,
+    calls: Mutex<Vec<RecordedCall>>,
+}
+
+impl<B: AuthBackend> RecordingBackend<B> {
+    pub fn new<P: AsRef<Path>>(inner: B, path: P) -> Self {
+        Self {
+            inner,
+            path: path.as_ref().to_path_buf(),
+            calls: Mutex::new(vec![]),
+        }
+    }
+
+    fn record<T: Serialize>(
+        &self,
+        call: BackendCall,
+        result: Result<T, Error>,
+    ) -> Result<T, Error> {
+        let recorded = RecordedCall {
+            call,
+            result: result
+                .as_ref()
+                .map(|output| serde_json::to_value(output).unwrap_or(Value::Null))
+                .map_err(Clone::clone),
+        };
+        debug!("Recording operation: {:?}", recorded.call);
+
+        let mut calls = unwrap!(self.calls.lock());
+        calls.push(recorded);
+        let recording = Recording {
+            version: RECORDING_FORMAT_VERSION,
+            calls: calls.clone(),
+        };
+        let serialised = unwrap!(serde_json::to_string_pretty(&recording));
+        fs::write(&self.path, serialised)
+            .map_err(|err| Error::IoError(format!("Failed to write the recording: {}", err)))?;
+
+        result
+    }
+}
+
+impl<B: AuthBackend> AuthBackend for RecordingBackend<B> {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        let call = BackendCall::AuthoriseApp {
+            req: req.to_string(),
+        };
+        self.record(call, self.inner.authorise_app(req, allow))
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.record(BackendCall::AuthedApps, self.inner.authed_apps())
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        let call = BackendCall::RevokeApp {
+            app_id: app_id.to_string(),
+        };
+        self.record(call, self.inner.revoke_app(app_id))
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.record(BackendCall::AccInfo, self.inner.acc_info())
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.record(BackendCall::AccKeys, self.inner.acc_keys())
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.record(BackendCall::BackupAccount, self.inner.backup_account())
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        let call = BackendCall::RestoreAccount {
+            backup: backup.clone(),
+        };
+        self.record(call, self.inner.restore_account(backup))
+    }
+}
+
+/// # Replay a recording
+///
+/// An `AuthBackend` which returns the outcomes stored in a file by a `RecordingBackend`,
+/// without connecting to any network. The operations are expected to be performed in the
+/// same order and with the same arguments as when they were recorded, otherwise they fail
+/// with an `InvalidInput` error.
+///
+/// The authorisation requests are still decoded and the `allow` callback is invoked, so
+/// the prompts can be tested as well. If a request is denied the denial response is
+/// returned, otherwise the response recorded is.
+pub struct ReplayBackend {
+    calls: Mutex<VecDeque<RecordedCall>>,
+}
+
+impl ReplayBackend {
+    pub fn new(calls: Vec<RecordedCall>) -> Self {
+        Self {
+            calls: Mutex::new(calls.into_iter().collect()),
+        }
+    }
+
+    /// Reads a recording from a file written by a `RecordingBackend`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| Error::IoError(format!("Failed to read the recording: {}", err)))?;
+        let recording: Recording = serde_json::from_str(&content)
+            .map_err(|err| Error::InvalidInput(format!("Invalid recording: {}", err)))?;
+        if recording.version != RECORDING_FORMAT_VERSION {
+            return Err(Error::InvalidInput(format!(
+                "Recording format version {} is not supported",
+                recording.version
+            )));
+        }
+        Ok(Self::new(recording.calls))
+    }
+
+    // Takes the next operation recorded, returning its outcome if it's the one expected
+    fn next_outcome(&self, call: BackendCall) -> Result<Result<Value, Error>, Error> {
+        let recorded = match unwrap!(self.calls.lock()).pop_front() {
+            Some(recorded) => recorded,
+            None => {
+                return Err(Error::InvalidInput(format!(
+                    "No more operations were recorded, {:?} was not expected",
+                    call
+                )))
+            }
+        };
+        if recorded.call != call {
+            return Err(Error::InvalidInput(format!(
+                "Operation {:?} was recorded, {:?} was not expected",
+                recorded.call, call
+            )));
+        }
+        debug!("Replaying operation: {:?}", recorded.call);
+        Ok(recorded.result)
+    }
+
+    fn replay<T: DeserializeOwned>(&self, call: BackendCall) -> Result<T, Error> {
+        let output = self.next_outcome(call)??;
+        serde_json::from_value(output)
+            .map_err(|err| Error::InvalidInput(format!("Invalid recorded outcome: {}", err)))
+    }
+}
+
+impl AuthBackend for ReplayBackend {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        let call = BackendCall::AuthoriseApp {
+            req: req.to_string(),
+        };
+        let ipc_msg = decode_auth_req(req)?;
+        match auth_action(ipc_msg, allow)? {
+            AuthAction::Deny(req_id) => {
+                // The outcome recorded is skipped as the request was denied this time
+                let _ = self.next_outcome(call)?;
+                gen_auth_denied_response(req_id)
+            }
+            _ => self.replay(call),
+        }
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.replay(BackendCall::AuthedApps)
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        self.replay(BackendCall::RevokeApp {
+            app_id: app_id.to_string(),
+        })
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.replay(BackendCall::AccInfo)
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.replay(BackendCall::AccKeys)
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.replay(BackendCall::BackupAccount)
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.replay(BackendCall::RestoreAccount { backup })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordingBackend, ReplayBackend};
+    use crate::backend::{AuthBackend, FakeBackend};
+    use crate::errors::Error;
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID};
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn record_and_replay_tests() {
+        let path = env::temp_dir().join(format!(
+            "safe_auth_recording_{}.json",
+            rand::random::<u32>()
+        ));
+        let recorder = RecordingBackend::new(FakeBackend::default(), &path);
+        let response = unwrap!(recorder.authorise_app(APP_AUTH_REQ, &|_| true));
+        let apps = unwrap!(recorder.authed_apps());
+        unwrap!(recorder.revoke_app(APP_ID));
+        let revoke_err = recorder.revoke_app(APP_ID);
+        assert!(revoke_err.is_err());
+
+        let replayer = unwrap!(ReplayBackend::from_file(&path));
+        unwrap!(fs::remove_file(&path));
+        assert_eq!(
+            unwrap!(replayer.authorise_app(APP_AUTH_REQ, &|_| true)),
+            response
+        );
+        let replayed_apps = unwrap!(replayer.authed_apps());
+        assert_eq!(replayed_apps.len(), apps.len());
+        assert_eq!(replayed_apps[0].app.id, APP_ID);
+        unwrap!(replayer.revoke_app(APP_ID));
+        assert_eq!(replayer.revoke_app(APP_ID), revoke_err);
+
+        // Nothing else was recorded
+        match replayer.authed_apps() {
+            Err(Error::InvalidInput(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn replay_mismatch_tests() {
+        let path = env::temp_dir().join(format!(
+            "safe_auth_recording_{}.json",
+            rand::random::<u32>()
+        ));
+        let recorder = RecordingBackend::new(FakeBackend::default(), &path);
+        let _ = unwrap!(recorder.authorise_app(APP_AUTH_REQ, &|_| true));
+        let _ = unwrap!(recorder.authed_apps());
+
+        // A request denied while replaying gets the denial response
+        let replayer = unwrap!(ReplayBackend::from_file(&path));
+        unwrap!(fs::remove_file(&path));
+        let denied = unwrap!(replayer.authorise_app(APP_AUTH_REQ, &|_| false));
+        assert!(!denied.ends_with(APP_ID));
+
+        // Operations not performed in the order recorded fail
+        match replayer.acc_info() {
+            Err(Error::InvalidInput(_)) => (), // This should pass
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The options the Authenticator service of the `authd` module is run with. They don't depend on
+// the `daemon` feature, so the builds without it can still prepare them.
+
+use crate::{
+    AuthBackend, EventBus, EventHistory, Guarding, PendingDecisions, DEFAULT_HISTORY_CAPACITY,
+};
+
+/// # The options of the Authenticator service
+///
+/// All of them have a default, so only the ones needed are set, e.g.
+/// `ServiceOptions { backend: Some(backend), ..ServiceOptions::default() }`.
+pub struct ServiceOptions {
+    /// The account operated on, with any `AuthBackend`, e.g. a `FakeBackend` to exercise the
+    /// service without a network. None by default, until an account is logged in to.
+    pub backend: Option<Box<dyn AuthBackend>>,
+    /// The token the clients operating on the account, e.g. listing the authorised apps or
+    /// revoking them, send in their `Authorization: Bearer <token>` header, or prove they have,
+    /// as described in the `service_auth` module. None by default, so they can't.
+    pub token: Option<String>,
+    /// The bus the events of the account are emitted to, whose history, e.g. persisted, the
+    /// clients operating on the account get with `GET /events/history?after=<cursor>&limit=<count>`.
+    /// By default a bus keeping the last `DEFAULT_HISTORY_CAPACITY` events in memory.
+    pub events: EventBus,
+    /// The account the service is the guardian of, none by default
+    pub guarding: Option<Guarding>,
+    /// The auth requests the prompt waits on, which the clients sending the token can decide on
+    /// with `POST /decide/{id}/allow` or `POST /decide/{id}/deny`, e.g. from the buttons of a
+    /// desktop notification. None are added by default.
+    pub decisions: PendingDecisions,
+}
+
+impl Default for ServiceOptions {
+    fn default() -> Self {
+        Self {
+            backend: None,
+            token: None,
+            events: EventBus::with_history(EventHistory::new(DEFAULT_HISTORY_CAPACITY)),
+            guarding: None,
+            decisions: PendingDecisions::default(),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The mutual authentication of the Authenticator service and of the other invocations of the
+// CLI operating on the account through it, with the token of the profile's service, so the
+// token is never sent and a process listening on the service's port in its place, e.g. of
+// another user once it stopped, can neither learn it nor pass its responses off as the
+// service's ones.
+//
+// The client first gets a nonce from the service with `GET /nonce`, which the service accepts
+// once, for a minute at most, so a proof captured can't be replayed. It then sends it in the
+// `X-Safe-Auth-Nonce` header, along with a random challenge in the `X-Safe-Auth-Challenge`
+// header, and proves it has the token with `Authorization: SafeAuth-HMAC <proof>`, the
+// HMAC-SHA256 with the token of the nonce, the challenge, the method, the path with the query
+// string and the body of the request. The service then proves it has the token too with the
+// `X-Safe-Auth-Proof` header of its response, the HMAC-SHA256 of the challenge and of the
+// response's body, checked by the client before using the response. The proofs of the client
+// and of the service are computed over different labels, so one can't be sent back as the
+// other.
+//
+// The bodies of the responses to the clients sending a challenge are encrypted with
+// `symmetric_encrypt`, with a key derived from the token, as they can hold the apps' keys,
+// e.g. the auth responses.
+
+use crate::helpers::bytes_to_hex;
+use crate::Error;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use safe_core::utils::{symmetric_decrypt, symmetric_encrypt, SymEncKey, SYM_ENC_KEY_LEN};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub(crate) const NONCE_HEADER: &str = "X-Safe-Auth-Nonce";
+pub(crate) const CHALLENGE_HEADER: &str = "X-Safe-Auth-Challenge";
+pub(crate) const PROOF_HEADER: &str = "X-Safe-Auth-Proof";
+// The scheme of the `Authorization` header with the client's proof
+pub(crate) const HMAC_SCHEME: &str = "SafeAuth-HMAC ";
+
+const CLIENT_LABEL: &str = "safe_auth-client-v2";
+const SERVICE_LABEL: &str = "safe_auth-service-v2";
+const PAYLOAD_LABEL: &str = "safe_auth-payload-v1";
+// How long a nonce is accepted for once issued
+const NONCE_LIFETIME: Duration = Duration::from_secs(60);
+// The most nonces waiting to be used, the oldest ones being dropped first
+const MAX_NONCES: usize = 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// A new challenge for a request, hex encoded
+pub(crate) fn new_challenge() -> String {
+    let challenge: [u8; 16] = rand::thread_rng().gen();
+    bytes_to_hex(&challenge)
+}
+
+// The nonces issued by the service, each accepted once
+#[derive(Default)]
+pub(crate) struct Nonces {
+    issued: Mutex<HashMap<String, Instant>>,
+}
+
+impl Nonces {
+    // A new nonce, hex encoded
+    pub fn issue(&self) -> String {
+        let nonce = new_challenge();
+        let mut issued = unwrap!(self.issued.lock());
+        issued.retain(|_, at| at.elapsed() < NONCE_LIFETIME);
+        if issued.len() >= MAX_NONCES {
+            let oldest = issued
+                .iter()
+                .min_by_key(|(_, at)| **at)
+                .map(|(nonce, _)| nonce.clone());
+            if let Some(oldest) = oldest {
+                let _ = issued.remove(&oldest);
+            }
+        }
+        let _ = issued.insert(nonce.clone(), Instant::now());
+        nonce
+    }
+
+    // Whether the nonce was issued and not used yet, which it is from now on
+    pub fn consume(&self, nonce: &str) -> bool {
+        unwrap!(self.issued.lock())
+            .remove(nonce)
+            .is_some_and(|at| at.elapsed() < NONCE_LIFETIME)
+    }
+}
+
+// The proof of the client that it has the token, and sent the request
+pub(crate) fn client_proof(
+    token: &str,
+    nonce: &str,
+    challenge: &str,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+) -> String {
+    let mut message = format!(
+        "{}\n{}\n{}\n{}\n{}\n",
+        CLIENT_LABEL, nonce, challenge, method, path_and_query
+    )
+    .into_bytes();
+    message.extend_from_slice(body);
+    bytes_to_hex(&hmac_sha256(token.as_bytes(), &message))
+}
+
+// The proof of the service that it has the token, and sent the body of the response
+pub(crate) fn service_proof(token: &str, challenge: &str, body: &[u8]) -> String {
+    let mut message = format!("{}\n{}\n", SERVICE_LABEL, challenge).into_bytes();
+    message.extend_from_slice(body);
+    bytes_to_hex(&hmac_sha256(token.as_bytes(), &message))
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    // HMAC takes keys of any length
+    let mut mac = unwrap!(HmacSha256::new_varkey(key));
+    mac.input(message);
+    mac.result().code().to_vec()
+}
+
+// The key the payloads are encrypted with
+fn payload_key(token: &str) -> SymEncKey {
+    let mut key = [0; SYM_ENC_KEY_LEN];
+    key.copy_from_slice(&hmac_sha256(token.as_bytes(), PAYLOAD_LABEL.as_bytes()));
+    key
+}
+
+pub(crate) fn encrypt_payload(token: &str, payload: &[u8]) -> Result<Vec<u8>, Error> {
+    symmetric_encrypt(payload, &payload_key(token), None).map_err(|err| {
+        Error::AuthenticatorError(format!("Failed to encrypt the response: {}", err))
+    })
+}
+
+pub(crate) fn decrypt_payload(token: &str, payload: &[u8]) -> Result<Vec<u8>, Error> {
+    symmetric_decrypt(payload, &payload_key(token)).map_err(|err| {
+        Error::AuthenticatorError(format!(
+            "Failed to decrypt the response of the Authenticator service: {}",
+            err
+        ))
+    })
+}
+
+// Compares the tokens or proofs without leaking through the time taken how much of them
+// matched
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        client_proof, constant_time_eq, decrypt_payload, encrypt_payload, hmac_sha256,
+        new_challenge, service_proof, Nonces,
+    };
+    use crate::helpers::bytes_to_hex;
+
+    #[test]
+    fn hmac_sha256_tests() {
+        // The test cases 2 and 6 of RFC 4231, with a short and a long key
+        assert_eq!(
+            bytes_to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            bytes_to_hex(&hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn proof_tests() {
+        let challenge = new_challenge();
+        assert_eq!(challenge.len(), 32);
+        assert_ne!(challenge, new_challenge());
+
+        let nonce = new_challenge();
+        let proof = |token: &str, nonce: &str, method: &str, path: &str, body: &[u8]| {
+            client_proof(token, nonce, &challenge, method, path, body)
+        };
+        let sent = proof("token", &nonce, "GET", "/apps", b"");
+        assert_eq!(sent, proof("token", &nonce, "GET", "/apps", b""));
+        assert_ne!(sent, proof("other-token", &nonce, "GET", "/apps", b""));
+        assert_ne!(sent, proof("token", &new_challenge(), "GET", "/apps", b""));
+        assert_ne!(
+            sent,
+            client_proof("token", &nonce, &new_challenge(), "GET", "/apps", b"")
+        );
+        assert_ne!(sent, proof("token", &nonce, "POST", "/apps", b""));
+        assert_ne!(sent, proof("token", &nonce, "GET", "/apps?limit=1", b""));
+        assert_ne!(sent, proof("token", &nonce, "GET", "/apps", b"{}"));
+        // The client's proof can't be passed off as the service's one
+        assert_ne!(sent, service_proof("token", &challenge, b"GET\n/apps"));
+        assert_ne!(
+            service_proof("token", &challenge, b"[]"),
+            service_proof("token", &challenge, b"[{}]")
+        );
+
+        assert!(constant_time_eq(
+            &sent,
+            &proof("token", &nonce, "GET", "/apps", b"")
+        ));
+        assert!(!constant_time_eq(&sent, &sent[1..]));
+    }
+
+    #[test]
+    fn nonces_tests() {
+        let nonces = Nonces::default();
+        let nonce = nonces.issue();
+        assert_ne!(nonce, nonces.issue());
+        // Each nonce is accepted once, and only if it was issued
+        assert!(nonces.consume(&nonce));
+        assert!(!nonces.consume(&nonce));
+        assert!(!nonces.consume(&new_challenge()));
+    }
+
+    #[test]
+    fn payload_tests() {
+        let encrypted = unwrap!(encrypt_payload("token", b"[]"));
+        assert_ne!(encrypted, b"[]".to_vec());
+        assert_ne!(encrypted, unwrap!(encrypt_payload("token", b"[]")));
+        assert_eq!(
+            unwrap!(decrypt_payload("token", &encrypted)),
+            b"[]".to_vec()
+        );
+        assert!(decrypt_payload("other-token", &encrypted).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::account::{AccountInfo, AccountKeys};
+use crate::apps::{AuthedAppsDiff, AuthedAppsList};
+use crate::backend::AuthBackend;
+use crate::errors::Error;
+use crate::ipc::IpcReq;
+use crate::AuthAllowPrompt;
+use std::cell::RefCell;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+thread_local! {
+    // The shared backends authorising on this thread, the innermost last, with the prompt each
+    // was given: the prompt they give their backend decides with it
+    static AUTHORISING: RefCell<Vec<(Arc<Shared>, &'static AuthAllowPrompt)>> = RefCell::new(Vec::new());
+}
+
+/// # Share a logged in account
+///
+/// A handle to a backend, e.g. a logged in `Authenticator`, which can be cloned and
+/// sent to other threads, so the Authenticator service, a `Headless` UI and any other
+/// component can all operate on the same session. The operations performed through
+/// any of the clones are serialised, i.e. they are performed one at a time, except while
+/// the user decides on an authorisation: the other operations are performed meanwhile,
+/// e.g. the apps are listed, and the decisions are prompted for one at a time.
+///
+/// The prompt given to `authorise_app` must be called from the thread the authorisation is
+/// performed on, as the backends of this crate do; the requests it's called for from another
+/// thread are denied.
+///
+/// ## Example
+/// ```
+/// use safe_auth::{AuthBackend, FakeBackend, SharedBackend};
+/// let shared = SharedBackend::new(FakeBackend::default());
+/// let for_thread = shared.clone();
+/// let auth_req = "bAAAAAAEXVK4SGAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
+/// std::thread::spawn(move || for_thread.authorise_app(auth_req, &|_| true))
+///     .join()
+///     .unwrap()
+///     .unwrap();
+/// match shared.authed_apps() {
+///    Ok(apps) => assert_eq!(apps.len(), 1), // This should pass
+///    Err(_) => assert!(false)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SharedBackend {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    backend: Box<dyn AuthBackend>,
+    // Whether an operation is being performed, the others waiting for their turn
+    busy: Mutex<bool>,
+    turn_given_up: Condvar,
+    // Held while the user decides on an authorisation
+    deciding: Mutex<()>,
+}
+
+impl SharedBackend {
+    pub fn new<B: AuthBackend + 'static>(backend: B) -> Self {
+        Self::from_boxed(Box::new(backend))
+    }
+
+    pub fn from_boxed(backend: Box<dyn AuthBackend>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                backend,
+                busy: Mutex::new(false),
+                turn_given_up: Condvar::new(),
+                deciding: Mutex::new(()),
+            }),
+        }
+    }
+
+    // Performs an operation once it's its turn, the turn being given up when it's done, even
+    // if it panicked, as the backend itself is still consistent
+    fn with_backend<T, F: FnOnce(&dyn AuthBackend) -> T>(&self, f: F) -> T {
+        let _turn = Turn::take(&self.shared);
+        f(&*self.shared.backend)
+    }
+}
+
+impl Shared {
+    fn wait_for_turn(&self) {
+        let mut busy = lock(&self.busy);
+        while *busy {
+            busy = match self.turn_given_up.wait(busy) {
+                Ok(busy) => busy,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+        }
+        *busy = true;
+    }
+
+    fn give_up_turn(&self) {
+        *lock(&self.busy) = false;
+        self.turn_given_up.notify_one();
+    }
+}
+
+// The turn taken at the shared backend, given up when dropped
+struct Turn<'a>(&'a Shared);
+
+impl<'a> Turn<'a> {
+    fn take(shared: &'a Shared) -> Self {
+        shared.wait_for_turn();
+        Turn(shared)
+    }
+}
+
+impl<'a> Drop for Turn<'a> {
+    fn drop(&mut self) {
+        self.0.give_up_turn();
+    }
+}
+
+// The authorisation performed on this thread, until dropped
+struct Authorising;
+
+impl Authorising {
+    fn start(shared: &Arc<Shared>, allow: &'static AuthAllowPrompt) -> Self {
+        AUTHORISING.with(|stack| stack.borrow_mut().push((shared.clone(), allow)));
+        Authorising
+    }
+}
+
+impl Drop for Authorising {
+    fn drop(&mut self) {
+        let _ = AUTHORISING.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+// The user deciding on an authorisation with the prompt of the outermost shared backend
+// authorising on this thread, all of them giving up their turn until the user decided, or the
+// prompt panicked. The turns are taken back from the outermost, as the operations take them.
+struct Deciding(Vec<(Arc<Shared>, &'static AuthAllowPrompt)>);
+
+impl Deciding {
+    fn start() -> Self {
+        let authorising = AUTHORISING.with(|stack| stack.take());
+        for (shared, _) in authorising.iter().rev() {
+            shared.give_up_turn();
+        }
+        Deciding(authorising)
+    }
+}
+
+impl Drop for Deciding {
+    fn drop(&mut self) {
+        for (shared, _) in &self.0 {
+            shared.wait_for_turn();
+        }
+        let authorising = std::mem::take(&mut self.0);
+        AUTHORISING.with(|stack| stack.replace(authorising));
+    }
+}
+
+// The prompt the shared backends give theirs
+fn decide_aside(req: IpcReq) -> bool {
+    let deciding = Deciding::start();
+    match deciding.0.first() {
+        Some((shared, allow)) => {
+            let _deciding = lock(&shared.deciding);
+            allow(req)
+        }
+        None => false,
+    }
+}
+
+// The locks are only held to update the state of the turns, so a poisoned one is still consistent
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+impl AuthBackend for SharedBackend {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        self.with_backend(|backend| {
+            let _authorising = Authorising::start(&self.shared, allow);
+            backend.authorise_app(req, &decide_aside)
+        })
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.with_backend(|backend| backend.authed_apps())
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        self.with_backend(|backend| backend.revoke_app(app_id))
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.with_backend(|backend| backend.acc_info())
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.with_backend(|backend| backend.acc_keys())
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.with_backend(|backend| backend.backup_account())
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.with_backend(|backend| backend.restore_account(backup))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedBackend;
+    use crate::backend::{AuthBackend, FakeBackend};
+    use crate::events::{AuthEvent, EventBus, EventingBackend};
+    use crate::headless::Headless;
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    // How long the user takes to decide on the authorisations
+    const DECISION_DELAY: Duration = Duration::from_secs(2);
+
+    #[test]
+    fn shared_backend_concurrent_tests() {
+        let events = EventBus::new();
+        let receiver = events.subscribe();
+        let shared = SharedBackend::new(EventingBackend::new(FakeBackend::default(), events));
+        let headless = Headless::new(Box::new(shared.clone()));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    let _ = unwrap!(shared.authorise_app(APP_AUTH_REQ, &|_| true));
+                    unwrap!(shared.authed_apps())
+                })
+            })
+            .collect();
+        for handle in handles {
+            let apps = unwrap!(handle.join());
+            assert_eq!(apps.len(), 1);
+        }
+
+        // The operations performed through any of the handles are seen from the rest
+        let apps = unwrap!(headless.backend().authed_apps());
+        assert_eq!(apps[0].app.id, APP_ID);
+        unwrap!(headless.backend().revoke_app(APP_ID));
+        assert!(unwrap!(shared.authed_apps()).is_empty());
+
+        let decided = receiver
+            .try_iter()
+            .filter(|event| matches!(event, AuthEvent::Decided { allowed: true, .. }))
+            .count();
+        assert_eq!(decided, 4);
+    }
+
+    #[test]
+    fn shared_backend_outlives_clones_test() {
+        let shared = SharedBackend::new(FakeBackend::default());
+        {
+            let clone = shared.clone();
+            let _ = unwrap!(clone.authorise_app(APP_AUTH_REQ, &|_| true));
+        }
+        assert_eq!(unwrap!(shared.authed_apps()).len(), 1);
+    }
+
+    #[test]
+    fn shared_backend_operates_while_deciding_test() {
+        // Shared again, as the Authenticator service shares the backend it's given
+        let shared = SharedBackend::new(SharedBackend::new(FakeBackend::default()));
+        let authorising = {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                shared.authorise_app(APP_AUTH_REQ, &|_| {
+                    thread::sleep(DECISION_DELAY);
+                    true
+                })
+            })
+        };
+        thread::sleep(Duration::from_millis(200));
+
+        // The apps are listed while the user decides, not once they decided
+        let started = Instant::now();
+        assert!(unwrap!(shared.authed_apps()).is_empty());
+        assert!(started.elapsed() < DECISION_DELAY / 2);
+
+        let _ = unwrap!(unwrap!(authorising.join()));
+        assert_eq!(unwrap!(shared.authed_apps()).len(), 1);
+    }
+}
@@ -0,0 +1,734 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// The storage of the state kept locally across the invocations, e.g. the receipts of the
+// decisions, the labels of the apps or the history of the events. Each entry is named after
+// the area of the state it belongs to, e.g. `receipts`, and its name within it, e.g. the
+// profile's `work.jsonl`, so `FileStorage` keeps the same files the CLI always wrote. By
+// default the state is stored in an SQLite database of the user's app directory, the files
+// written by the previous versions being moved into it as they are read.
+
+use crate::Error;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+// Name of the directory, within the user's app directory, of the copies of the entries
+// the libraries read from files, e.g. safe_core reading the crust config
+const FILES_DIR: &str = "files";
+
+lazy_static! {
+    // The storage set with `set_storage`, none for the default one of the user's app directory
+    static ref STORAGE: RwLock<Option<Arc<dyn Storage>>> = RwLock::new(None);
+    // The default storage, opened once for all the operations of the process
+    static ref DEFAULT_STORAGE: Mutex<Option<Arc<dyn Storage>>> = Mutex::new(None);
+}
+
+/// # Where the local state is stored
+///
+/// The CLI stores the state of the profiles with it, i.e. the receipts, their signing keys,
+/// the labels of the apps, the ages of the credentials, the times of the permission reviews
+/// and the history of the events, as well as the networks defined, the registrations of the
+/// Authenticator services running and the last check for a new version. Embedders can provide
+/// their own storage, e.g. one managed by the browser, with `set_storage` before running the
+/// CLI. The entries are opaque bytes, and reading one which doesn't exist isn't an error.
+pub trait Storage: Send + Sync {
+    /// The content of the entry, none if it doesn't exist
+    fn read(&self, area: &str, name: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Replaces the content of the entry, creating it if it doesn't exist
+    fn write(&self, area: &str, name: &str, content: &[u8]) -> Result<(), Error>;
+
+    /// Adds the record at the end of the entry, as a line, e.g. to a log of receipts. By
+    /// default the entry is read and written again.
+    fn append(&self, area: &str, name: &str, record: &[u8]) -> Result<(), Error> {
+        let mut content = self.read(area, name)?.unwrap_or_default();
+        content.extend_from_slice(record);
+        content.push(b'\n');
+        self.write(area, name, &content)
+    }
+
+    /// Same as `write` but the entry has a secret, e.g. a signing key, so only the user can
+    /// read it. By default it's the same as `write`.
+    fn write_private(&self, area: &str, name: &str, content: &[u8]) -> Result<(), Error> {
+        self.write(area, name, content)
+    }
+
+    /// Creates the entry, only readable by the user, unless it already exists, returning if
+    /// it was created, e.g. for a process to register the Authenticator service it runs. By
+    /// default the entry is read then written, which the storages shared by several processes
+    /// must do at once instead.
+    fn create(&self, area: &str, name: &str, content: &[u8]) -> Result<bool, Error> {
+        if self.read(area, name)?.is_some() {
+            return Ok(false);
+        }
+        self.write_private(area, name, content)?;
+        Ok(true)
+    }
+
+    /// Removes the entry, if it exists
+    fn remove(&self, area: &str, name: &str) -> Result<(), Error>;
+
+    /// The names of the entries of the area, sorted
+    fn names(&self, area: &str) -> Result<Vec<String>, Error>;
+
+    /// The file the entry is kept in, if the storage keeps it in a file, for the libraries which
+    /// read their config from files, e.g. safe_core reading the crust config. By default none,
+    /// the entry being copied to a file for them.
+    fn file(&self, _area: &str, _name: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// # Set where the local state is stored
+///
+/// The state stored from now on, and read, is the one of the storage, rather than the files
+/// of the user's app directory.
+pub fn set_storage(storage: Arc<dyn Storage>) {
+    if let Ok(mut current) = STORAGE.write() {
+        *current = Some(storage);
+    }
+}
+
+// The storage set, otherwise the default one of the user's app directory
+pub(crate) fn storage() -> Result<Arc<dyn Storage>, Error> {
+    if let Some(storage) = STORAGE.read().ok().and_then(|storage| storage.clone()) {
+        return Ok(storage);
+    }
+    let mut default = unwrap!(DEFAULT_STORAGE.lock());
+    if let Some(storage) = &*default {
+        return Ok(storage.clone());
+    }
+    let storage = default_storage(&app_dir()?)?;
+    *default = Some(storage.clone());
+    Ok(storage)
+}
+
+#[cfg(feature = "sqlite")]
+fn default_storage(app_dir: &Path) -> Result<Arc<dyn Storage>, Error> {
+    Ok(Arc::new(SqliteStorage::open(app_dir)?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn default_storage(app_dir: &Path) -> Result<Arc<dyn Storage>, Error> {
+    Ok(Arc::new(FileStorage::new(app_dir)))
+}
+
+fn app_dir() -> Result<PathBuf, Error> {
+    config_file_handler::user_app_dir().map_err(|err| {
+        Error::IoError(format!(
+            "Failed to locate the user's app directory: {}",
+            err
+        ))
+    })
+}
+
+// The file with the content of the entry, for the libraries reading their config from files,
+// e.g. the crust config given to safe_core: the storage's own if it keeps the entry in a file,
+// otherwise a copy written to the `files` directory of the user's app directory. None if the
+// entry doesn't exist.
+pub(crate) fn entry_file(area: &str, name: &str) -> Result<Option<PathBuf>, Error> {
+    let storage = storage()?;
+    if let Some(file) = storage.file(area, name) {
+        return Ok(Some(file).filter(|file| file.is_file()));
+    }
+    match storage.read(area, name)? {
+        Some(content) => {
+            let copies = FileStorage::new(app_dir()?.join(FILES_DIR));
+            copies.write(area, name, &content)?;
+            Ok(Some(copies.path(area, name)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The storage in files, each entry being the file `<area>/<name>` within the directory, e.g.
+/// the user's app directory the CLI uses by default
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The file of the entry
+    pub fn path(&self, area: &str, name: &str) -> PathBuf {
+        self.root.join(area).join(name)
+    }
+
+    // The file of the entry, with its directory created
+    fn created_path(&self, area: &str, name: &str) -> Result<PathBuf, Error> {
+        let file = self.path(area, name);
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                Error::IoError(format!(
+                    "Failed to create the directory '{}': {}",
+                    parent.display(),
+                    err
+                ))
+            })?;
+        }
+        Ok(file)
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(&self, area: &str, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        let file = self.path(area, name);
+        match fs::read(&file) {
+            Ok(content) => Ok(Some(content)),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::IoError(format!(
+                "Failed to read '{}': {}",
+                file.display(),
+                err
+            ))),
+        }
+    }
+
+    fn write(&self, area: &str, name: &str, content: &[u8]) -> Result<(), Error> {
+        let file = self.created_path(area, name)?;
+        fs::write(&file, content).map_err(|err| write_error(&file, err))
+    }
+
+    fn append(&self, area: &str, name: &str, record: &[u8]) -> Result<(), Error> {
+        let file = self.created_path(area, name)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file)
+            .and_then(|mut log| {
+                log.write_all(record)?;
+                log.write_all(b"\n")
+            })
+            .map_err(|err| write_error(&file, err))
+    }
+
+    fn write_private(&self, area: &str, name: &str, content: &[u8]) -> Result<(), Error> {
+        let file = self.created_path(area, name)?;
+        create_private(&file)
+            .and_then(|mut private| private.write_all(content))
+            .map_err(|err| write_error(&file, err))
+    }
+
+    // The entry is written to a file of its own first, then linked to its name, which fails if
+    // it already exists, so the other processes never read a partial one
+    fn create(&self, area: &str, name: &str, content: &[u8]) -> Result<bool, Error> {
+        let file = self.created_path(area, name)?;
+        let mut written = file.clone().into_os_string();
+        written.push(format!(".{}.tmp", std::process::id()));
+        let written = PathBuf::from(written);
+        create_private(&written)
+            .and_then(|mut private| private.write_all(content))
+            .map_err(|err| write_error(&written, err))?;
+        let created = match fs::hard_link(&written, &file) {
+            Ok(()) => Ok(true),
+            Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+            Err(err) => Err(Error::IoError(format!(
+                "Failed to create '{}': {}",
+                file.display(),
+                err
+            ))),
+        };
+        let _ = fs::remove_file(&written);
+        created
+    }
+
+    fn remove(&self, area: &str, name: &str) -> Result<(), Error> {
+        let file = self.path(area, name);
+        match fs::remove_file(&file) {
+            Err(ref err) if err.kind() != io::ErrorKind::NotFound => Err(Error::IoError(format!(
+                "Failed to remove '{}': {}",
+                file.display(),
+                err
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // The files within the area's directory, the names of those in its sub-directories
+    // having their path within it, e.g. `testnet/safe_auth.crust.config`
+    fn names(&self, area: &str) -> Result<Vec<String>, Error> {
+        let mut names = vec![];
+        let mut dirs = vec![(self.root.join(area), String::new())];
+        while let Some((dir, prefix)) = dirs.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(ref err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(Error::IoError(format!(
+                        "Failed to read the directory '{}': {}",
+                        dir.display(),
+                        err
+                    )))
+                }
+            };
+            for entry in entries.filter_map(Result::ok) {
+                let name = match entry.file_name().into_string() {
+                    Ok(name) => format!("{}{}", prefix, name),
+                    Err(_) => continue,
+                };
+                match entry.file_type() {
+                    Ok(file_type) if file_type.is_dir() => {
+                        dirs.push((entry.path(), format!("{}/", name)))
+                    }
+                    Ok(file_type) if file_type.is_file() && !name.ends_with(".tmp") => {
+                        names.push(name)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn file(&self, area: &str, name: &str) -> Option<PathBuf> {
+        Some(self.path(area, name))
+    }
+}
+
+fn write_error(file: &Path, err: io::Error) -> Error {
+    Error::IoError(format!("Failed to write '{}': {}", file.display(), err))
+}
+
+// Creates a file only the user can read, e.g. as it has a token or a key
+#[cfg(unix)]
+pub(crate) fn create_private(file: &Path) -> io::Result<fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(file)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn create_private(file: &Path) -> io::Result<fs::File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(file)
+}
+
+// Same as `create_private` but the content of the file is kept if it already exists
+#[cfg(all(unix, feature = "sqlite"))]
+fn create_private_keeping(file: &Path) -> io::Result<()> {
+    use std::os::unix::fs::OpenOptionsExt;
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .mode(0o600)
+        .open(file)
+        .map(|_| ())
+}
+
+#[cfg(all(not(unix), feature = "sqlite"))]
+fn create_private_keeping(file: &Path) -> io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(file)
+        .map(|_| ())
+}
+
+/// The storage in memory, e.g. for the tests or the embedders persisting nothing. It can be
+/// cloned, with all the clones sharing the same entries.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    entries: Arc<Mutex<MemoryEntries>>,
+}
+
+// The entries by area and name
+type MemoryEntries = HashMap<(String, String), Vec<u8>>;
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, area: &str, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        let entries = unwrap!(self.entries.lock());
+        Ok(entries.get(&(area.to_string(), name.to_string())).cloned())
+    }
+
+    fn write(&self, area: &str, name: &str, content: &[u8]) -> Result<(), Error> {
+        let mut entries = unwrap!(self.entries.lock());
+        let _ = entries.insert((area.to_string(), name.to_string()), content.to_vec());
+        Ok(())
+    }
+
+    fn create(&self, area: &str, name: &str, content: &[u8]) -> Result<bool, Error> {
+        let mut entries = unwrap!(self.entries.lock());
+        let key = (area.to_string(), name.to_string());
+        if entries.contains_key(&key) {
+            return Ok(false);
+        }
+        let _ = entries.insert(key, content.to_vec());
+        Ok(true)
+    }
+
+    fn remove(&self, area: &str, name: &str) -> Result<(), Error> {
+        let mut entries = unwrap!(self.entries.lock());
+        let _ = entries.remove(&(area.to_string(), name.to_string()));
+        Ok(())
+    }
+
+    fn names(&self, area: &str) -> Result<Vec<String>, Error> {
+        let entries = unwrap!(self.entries.lock());
+        let mut names: Vec<String> = entries
+            .keys()
+            .filter(|(entry_area, _)| entry_area == area)
+            .map(|(_, name)| name.clone())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteStorage;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::{create_private_keeping, FileStorage, Storage};
+    use crate::Error;
+    use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, MutexGuard};
+    use std::time::Duration;
+
+    // Name of the database, within the directory of the storage
+    const DATABASE_FILE_NAME: &str = "safe_auth.state.sqlite";
+    // How long an operation waits for the other processes using the database to complete theirs
+    const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// The storage in an SQLite database, the CLI's default, each entry being a row of its
+    /// `entries` table. The database is only readable by the user, as some entries have secrets,
+    /// and can be used by several processes at once, e.g. the Authenticator service and the
+    /// other invocations of the CLI. The files a `FileStorage` kept in the same directory, e.g.
+    /// written by the previous versions of the CLI, are moved into the database as they are read.
+    pub struct SqliteStorage {
+        db: Mutex<Connection>,
+        files: FileStorage,
+        path: PathBuf,
+    }
+
+    impl SqliteStorage {
+        /// The storage in the database of the directory, created if it doesn't exist
+        pub fn open<P: Into<PathBuf>>(dir: P) -> Result<Self, Error> {
+            let dir = dir.into();
+            let path = dir.join(DATABASE_FILE_NAME);
+            fs::create_dir_all(&dir)
+                .and_then(|()| create_private_keeping(&path))
+                .map_err(|err| {
+                    Error::IoError(format!("Failed to create '{}': {}", path.display(), err))
+                })?;
+            let db = Connection::open(&path).map_err(|err| db_error(&path, err))?;
+            db.busy_timeout(BUSY_TIMEOUT)
+                .and_then(|()| db.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(())))
+                .and_then(|()| {
+                    db.execute_batch(
+                        "CREATE TABLE IF NOT EXISTS entries (
+                            area TEXT NOT NULL,
+                            name TEXT NOT NULL,
+                            content BLOB NOT NULL,
+                            PRIMARY KEY (area, name)
+                        )",
+                    )
+                })
+                .map_err(|err| db_error(&path, err))?;
+            Ok(Self {
+                db: Mutex::new(db),
+                files: FileStorage::new(dir),
+                path,
+            })
+        }
+
+        /// The file of the database
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        // The connection to the database. A previous operation panicking poisons the lock, but
+        // the database itself is still consistent.
+        fn db(&self) -> MutexGuard<'_, Connection> {
+            match self.db.lock() {
+                Ok(db) => db,
+                Err(poisoned) => poisoned.into_inner(),
+            }
+        }
+
+        fn select(
+            &self,
+            db: &Connection,
+            area: &str,
+            name: &str,
+        ) -> Result<Option<Vec<u8>>, Error> {
+            db.query_row(
+                "SELECT content FROM entries WHERE area = ?1 AND name = ?2",
+                params![area, name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|err| db_error(&self.path, err))
+        }
+
+        // Moves the file of the entry, if any, into the database, unless another process did
+        // it first, returning the entry's content
+        fn import(
+            &self,
+            db: &Connection,
+            area: &str,
+            name: &str,
+        ) -> Result<Option<Vec<u8>>, Error> {
+            let content = match self.files.read(area, name)? {
+                Some(content) => content,
+                None => return Ok(None),
+            };
+            let _ = db
+                .execute(
+                    "INSERT OR IGNORE INTO entries (area, name, content) VALUES (?1, ?2, ?3)",
+                    params![area, name, content],
+                )
+                .map_err(|err| db_error(&self.path, err))?;
+            self.files.remove(area, name)?;
+            self.select(db, area, name)
+        }
+
+        fn read_with(
+            &self,
+            db: &Connection,
+            area: &str,
+            name: &str,
+        ) -> Result<Option<Vec<u8>>, Error> {
+            match self.select(db, area, name)? {
+                Some(content) => Ok(Some(content)),
+                None => self.import(db, area, name),
+            }
+        }
+
+        fn write_with(
+            &self,
+            db: &Connection,
+            area: &str,
+            name: &str,
+            content: &[u8],
+        ) -> Result<(), Error> {
+            let _ = db
+                .execute(
+                    "INSERT OR REPLACE INTO entries (area, name, content) VALUES (?1, ?2, ?3)",
+                    params![area, name, content],
+                )
+                .map_err(|err| db_error(&self.path, err))?;
+            // The file written by a previous version is outdated from now on
+            self.files.remove(area, name)
+        }
+    }
+
+    impl Storage for SqliteStorage {
+        fn read(&self, area: &str, name: &str) -> Result<Option<Vec<u8>>, Error> {
+            self.read_with(&self.db(), area, name)
+        }
+
+        fn write(&self, area: &str, name: &str, content: &[u8]) -> Result<(), Error> {
+            self.write_with(&self.db(), area, name, content)
+        }
+
+        fn append(&self, area: &str, name: &str, record: &[u8]) -> Result<(), Error> {
+            let mut db = self.db();
+            let tx = db
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .map_err(|err| db_error(&self.path, err))?;
+            let mut content = self.read_with(&tx, area, name)?.unwrap_or_default();
+            content.extend_from_slice(record);
+            content.push(b'\n');
+            self.write_with(&tx, area, name, &content)?;
+            tx.commit().map_err(|err| db_error(&self.path, err))
+        }
+
+        fn create(&self, area: &str, name: &str, content: &[u8]) -> Result<bool, Error> {
+            let mut db = self.db();
+            let tx = db
+                .transaction_with_behavior(TransactionBehavior::Immediate)
+                .map_err(|err| db_error(&self.path, err))?;
+            if self.read_with(&tx, area, name)?.is_some() {
+                return Ok(false);
+            }
+            self.write_with(&tx, area, name, content)?;
+            tx.commit().map_err(|err| db_error(&self.path, err))?;
+            Ok(true)
+        }
+
+        fn remove(&self, area: &str, name: &str) -> Result<(), Error> {
+            let _ = self
+                .db()
+                .execute(
+                    "DELETE FROM entries WHERE area = ?1 AND name = ?2",
+                    params![area, name],
+                )
+                .map_err(|err| db_error(&self.path, err))?;
+            self.files.remove(area, name)
+        }
+
+        fn names(&self, area: &str) -> Result<Vec<String>, Error> {
+            let db = self.db();
+            for name in self.files.names(area)? {
+                let _ = self.import(&db, area, &name)?;
+            }
+            let mut statement = db
+                .prepare("SELECT name FROM entries WHERE area = ?1 ORDER BY name")
+                .map_err(|err| db_error(&self.path, err))?;
+            let names = statement
+                .query_map(params![area], |row| row.get(0))
+                .and_then(|rows| rows.collect())
+                .map_err(|err| db_error(&self.path, err))?;
+            Ok(names)
+        }
+    }
+
+    fn db_error(path: &Path, err: rusqlite::Error) -> Error {
+        Error::IoError(format!(
+            "Failed to use the database '{}': {}",
+            path.display(),
+            err
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileStorage, MemoryStorage, Storage};
+
+    fn check_storage(storage: &dyn Storage) {
+        assert_eq!(unwrap!(storage.read("labels", "work.json")), None);
+        unwrap!(storage.write("labels", "work.json", b"{}"));
+        unwrap!(storage.write("labels", "work.json", b"{\"a\":\"b\"}"));
+        assert_eq!(
+            unwrap!(storage.read("labels", "work.json")),
+            Some(b"{\"a\":\"b\"}".to_vec())
+        );
+
+        unwrap!(storage.append("receipts", "work.jsonl", b"first"));
+        unwrap!(storage.append("receipts", "work.jsonl", b"second"));
+        assert_eq!(
+            unwrap!(storage.read("receipts", "work.jsonl")),
+            Some(b"first\nsecond\n".to_vec())
+        );
+
+        unwrap!(storage.write_private("keys", "work.key", b"secret"));
+        assert_eq!(
+            unwrap!(storage.read("keys", "work.key")),
+            Some(b"secret".to_vec())
+        );
+        // The areas are apart
+        assert_eq!(unwrap!(storage.read("keys", "work.json")), None);
+
+        assert!(unwrap!(storage.create("instances", "work.json", b"first")));
+        assert!(!unwrap!(storage.create(
+            "instances",
+            "work.json",
+            b"second"
+        )));
+        assert_eq!(
+            unwrap!(storage.read("instances", "work.json")),
+            Some(b"first".to_vec())
+        );
+        unwrap!(storage.remove("instances", "work.json"));
+        unwrap!(storage.remove("instances", "work.json"));
+        assert_eq!(unwrap!(storage.read("instances", "work.json")), None);
+
+        unwrap!(storage.write("networks", "testnet/safe_auth.crust.config", b"{}"));
+        unwrap!(storage.write("networks", "local/safe_auth.crust.config", b"{}"));
+        assert_eq!(
+            unwrap!(storage.names("networks")),
+            vec![
+                "local/safe_auth.crust.config",
+                "testnet/safe_auth.crust.config"
+            ]
+        );
+        assert!(unwrap!(storage.names("instances")).is_empty());
+    }
+
+    #[test]
+    fn memory_storage_tests() {
+        check_storage(&MemoryStorage::new());
+    }
+
+    #[test]
+    fn file_storage_tests() {
+        let root = std::env::temp_dir().join("safe_auth-storage-tests");
+        let _ = std::fs::remove_dir_all(&root);
+        let storage = FileStorage::new(&root);
+        check_storage(&storage);
+        assert!(storage.path("labels", "work.json").is_file());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = unwrap!(std::fs::metadata(storage.path("keys", "work.key")));
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_storage_tests() {
+        use super::SqliteStorage;
+
+        let root = std::env::temp_dir().join("safe_auth-sqlite-storage-tests");
+        let _ = std::fs::remove_dir_all(&root);
+        check_storage(&unwrap!(SqliteStorage::open(&root)));
+
+        // The files written by the previous versions are moved into the database
+        let files = FileStorage::new(&root);
+        unwrap!(files.write("labels", "home.json", b"{}"));
+        unwrap!(files.write("networks", "alpha/safe_auth.crust.config", b"{}"));
+        let storage = unwrap!(SqliteStorage::open(&root));
+        unwrap!(storage.append("receipts", "work.jsonl", b"third"));
+        assert_eq!(
+            unwrap!(storage.read("labels", "home.json")),
+            Some(b"{}".to_vec())
+        );
+        assert_eq!(unwrap!(files.read("labels", "home.json")), None);
+        assert_eq!(unwrap!(storage.names("networks")).len(), 3);
+        assert!(!files
+            .path("networks", "alpha/safe_auth.crust.config")
+            .exists());
+
+        // Another process sees the entries
+        let other = unwrap!(SqliteStorage::open(&root));
+        assert_eq!(
+            unwrap!(other.read("receipts", "work.jsonl")),
+            Some(b"first\nsecond\nthird\n".to_vec())
+        );
+        assert!(!unwrap!(other.create("labels", "home.json", b"")));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = unwrap!(std::fs::metadata(storage.path()));
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
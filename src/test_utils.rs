@@ -0,0 +1,35 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use threshold_crypto::{serde_impl::SerdeSecret, SecretKey};
+
+// The app auth request strings encode the following app info:
+/*
+    id: 'net.maidsafe.cli',
+    name: 'SAFE CLI',
+    vendor: 'MaidSafe.net Ltd'
+*/
+// perms: [ ("_public", {Read} ) ]
+pub static APP_AUTH_REQ: &str = "bAAAAAACNZZQ4WAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAAAAAAAAAAAAAB";
+// perms: [ ("_public", {Read} ), ("_music", {Insert, Update}) ]
+pub static CONT_AUTH_REQ: &str = "bAAAAAADLZ663OAIAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAEAAAAAAAAAAAAYAAAAAAAAAAAX3NOVZWSYYCAAAAAAAAAAAACAAAAABAAAAAA4AAAAAAAAAAAX3QOVRGY2LDAEAAAAAAAAAAAAAAAAAAC";
+
+pub static APP_NAME: &str = "SAFE CLI";
+pub static APP_ID: &str = "net.maidsafe.cli";
+
+pub fn gen_random_sk_hex() -> (String, SecretKey) {
+    let sk = SecretKey::random();
+    let sk_serialised = bincode::serialize(&SerdeSecret(&sk))
+        .expect("Failed to serialise the generated secret key");
+    let sk_hex = sk_serialised.iter().map(|b| format!("{:02x}", b)).collect();
+    (sk_hex, sk)
+}
+
+pub fn random_str() -> String {
+    (0..4).map(|_| rand::random::<char>()).collect()
+}
@@ -0,0 +1,256 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! # Test support
+//!
+//! Helpers to exercise the whole stack from tests, exposed when built with the `testing` feature:
+//! creating throwaway accounts, running the Authenticator service on a random port, and
+//! asserting on the outcome of the operations. When built with the `mock-network` feature as
+//! well, the accounts are stored in the mock vault, so no network is needed.
+//!
+//! ## Example
+//! ```
+//! use safe_auth::testing::{spawn_daemon, APP_AUTH_REQ};
+//! use safe_auth::FakeBackend;
+//! let daemon = spawn_daemon(Some(Box::new(FakeBackend::default())), &|_| true).unwrap();
+//! let response = daemon.authorise(APP_AUTH_REQ);
+//! assert!(response.is_ok());
+//! ```
+
+use crate::{create_acc, AuthAllowPrompt, AuthBackend, Error, ServiceOptions};
+use safe_authenticator::Authenticator;
+use safe_core::client::test_create_balance;
+use safe_nd::{ClientFullId, Coins};
+use serde_json::Value;
+use std::fmt::Debug;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+use threshold_crypto::{serde_impl::SerdeSecret, SecretKey};
+
+// How long to wait for the Authenticator service to start listening
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// App auth request from `net.maidsafe.cli`, requesting `Read` permission for `_public`
+pub const APP_AUTH_REQ: &str = "bAAAAAACNZZQ4WAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAAAAAAAAAAAAAB";
+/// Containers auth request from `net.maidsafe.cli`, for `_public` and `_music`
+pub const CONT_AUTH_REQ: &str = "bAAAAAADLZ663OAIAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAEAAAAAAAAAAAAYAAAAAAAAAAAX3NOVZWSYYCAAAAAAAAAAAACAAAAABAAAAAA4AAAAAAAAAAAX3QOVRGY2LDAEAAAAAAAAAAAAAAAAAAC";
+/// Id of the app sending the `APP_AUTH_REQ` and `CONT_AUTH_REQ` requests
+pub const APP_ID: &str = "net.maidsafe.cli";
+
+/// Credentials of an account created for a test
+#[derive(Debug, Clone)]
+pub struct TestAccount {
+    pub secret: String,
+    pub password: String,
+    /// Hex encoded secret key of the balance which paid for the account
+    pub sk: String,
+}
+
+/// # Create a throwaway account
+///
+/// Creates an account with random credentials, paid with test-coins,
+/// returning the authenticator logged in to it.
+pub fn create_test_account() -> Result<(Authenticator, TestAccount), Error> {
+    let secret_key = SecretKey::random();
+    let sk_serialised = bincode::serialize(&SerdeSecret(&secret_key)).map_err(|err| {
+        Error::InvalidInput(format!("Failed to serialise the secret key: {}", err))
+    })?;
+    let sk: String = sk_serialised.iter().map(|b| format!("{:02x}", b)).collect();
+    test_create_balance(
+        &ClientFullId::from(secret_key),
+        unwrap!(Coins::from_str("10")),
+    )
+    .map_err(|err| {
+        Error::NetworkError(format!("Failed to create the test-coins balance: {}", err))
+    })?;
+
+    let account = TestAccount {
+        secret: random_credential(),
+        password: random_credential(),
+        sk,
+    };
+    let authenticator = create_acc(&account.sk, &account.secret, &account.password)?;
+    Ok((authenticator, account))
+}
+
+/// An Authenticator service started for a test. It keeps running until the test process exits.
+#[derive(Debug, Clone)]
+pub struct TestDaemon {
+    port: u16,
+    token: Option<String>,
+}
+
+impl TestDaemon {
+    /// Port number the service is listening to
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Sends a `GET` request for the path provided, returning the status code and the body.
+    /// The service's token is sent with it, if it was started with one.
+    pub fn get(&self, path: &str) -> Result<(u16, String), Error> {
+        let network_error = |err: std::io::Error| {
+            Error::NetworkError(format!(
+                "Failed to send the request to the service: {}",
+                err
+            ))
+        };
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).map_err(network_error)?;
+        let authorization = self
+            .token
+            .as_ref()
+            .map(|token| format!("Authorization: Bearer {}\r\n", token))
+            .unwrap_or_default();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: 127.0.0.1:{}\r\n{}Connection: close\r\n\r\n",
+            path, self.port, authorization
+        );
+        stream
+            .write_all(request.as_bytes())
+            .map_err(network_error)?;
+        let mut response = String::new();
+        let _ = stream
+            .read_to_string(&mut response)
+            .map_err(network_error)?;
+
+        let invalid_response =
+            || Error::NetworkError(format!("Invalid response from the service: {}", response));
+        let status = response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(invalid_response)?;
+        let (_, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(invalid_response)?;
+        Ok((status, body.to_string()))
+    }
+
+    /// Sends an auth request to the service, returning the response string,
+    /// or the error the service responded with
+    pub fn authorise(&self, req: &str) -> Result<String, Error> {
+        let (status, body) = self.get(&format!("/authorise/{}", req))?;
+        if status == 200 {
+            return Ok(body);
+        }
+
+        let json: Value = serde_json::from_str(&body).map_err(|_| {
+            Error::NetworkError(format!("Unexpected response from the service: {}", body))
+        })?;
+        let err = serde_json::from_value(json["error"].clone()).map_err(|_| {
+            Error::NetworkError(format!("Unexpected response from the service: {}", body))
+        })?;
+        Err(err)
+    }
+}
+
+/// # Run the Authenticator service on a random port
+///
+/// Starts the service in a background thread with the backend provided,
+/// and waits until it's listening for requests.
+pub fn spawn_daemon(
+    backend: Option<Box<dyn AuthBackend>>,
+    prompt_to_allow: &'static AuthAllowPrompt,
+) -> Result<TestDaemon, Error> {
+    spawn_daemon_with_token(backend, prompt_to_allow, None)
+}
+
+/// # Run the Authenticator service operating on the account with a token
+///
+/// Same as `spawn_daemon` but the account can also be operated on, e.g. with
+/// `GET /apps`, by the requests sent with the token given, as `TestDaemon::get` does.
+pub fn spawn_daemon_with_token(
+    backend: Option<Box<dyn AuthBackend>>,
+    prompt_to_allow: &'static AuthAllowPrompt,
+    token: Option<String>,
+) -> Result<TestDaemon, Error> {
+    // Let the OS pick a free port, it's released right away for the service to bind to it
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|err| Error::IoError(format!("Failed to find a free port: {}", err)))?;
+
+    let service_token = token.clone();
+    let _ = thread::spawn(move || {
+        crate::authd::run_service(
+            port,
+            prompt_to_allow,
+            ServiceOptions {
+                backend,
+                token: service_token,
+                ..ServiceOptions::default()
+            },
+        )
+    });
+
+    let started = Instant::now();
+    while TcpStream::connect(("127.0.0.1", port)).is_err() {
+        if started.elapsed() > DAEMON_START_TIMEOUT {
+            return Err(Error::NetworkError(format!(
+                "The Authenticator service didn't start listening on port {}",
+                port
+            )));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(TestDaemon { port, token })
+}
+
+/// # Run the Authenticator service logged in to a throwaway account
+///
+/// Same as `spawn_daemon` but the service is logged in to an account
+/// created with `create_test_account`, and all the requests are allowed.
+pub fn spawn_daemon_with_account() -> Result<(TestDaemon, TestAccount), Error> {
+    let (authenticator, account) = create_test_account()?;
+    let daemon = spawn_daemon(Some(Box::new(authenticator)), &|_| true)?;
+    Ok((daemon, account))
+}
+
+/// Panics if the app is not authorised in the account
+pub fn assert_app_authorised(backend: &dyn AuthBackend, app_id: &str) {
+    let apps = unwrap!(backend.authed_apps());
+    assert!(
+        apps.iter().any(|authed| authed.app.id == app_id),
+        "App '{}' is not authorised, the authorised apps are: {:?}",
+        app_id,
+        apps
+    );
+}
+
+/// Panics if the app is authorised in the account
+pub fn assert_app_not_authorised(backend: &dyn AuthBackend, app_id: &str) {
+    let apps = unwrap!(backend.authed_apps());
+    assert!(
+        !apps.iter().any(|authed| authed.app.id == app_id),
+        "App '{}' is authorised",
+        app_id
+    );
+}
+
+/// Panics if the operation didn't fail with an error of the kind provided, e.g. "IpcDecodeError"
+pub fn assert_error_kind<T: Debug>(result: Result<T, Error>, kind: &str) {
+    match result {
+        Ok(output) => panic!("Expected a {} error but it succeeded: {:?}", kind, output),
+        Err(err) => {
+            let json = unwrap!(serde_json::to_value(&err));
+            assert_eq!(json["kind"], kind, "Unexpected error: {:?}", err);
+        }
+    }
+}
+
+fn random_credential() -> String {
+    use rand::distributions::Alphanumeric;
+    use rand::Rng;
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(30)
+        .collect()
+}
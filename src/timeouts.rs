@@ -0,0 +1,233 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::cancel::CancellationToken;
+use crate::errors::Error;
+use futures::{Future, IntoFuture};
+use log::debug;
+use safe_authenticator::AuthClient;
+use safe_authenticator::{errors::AuthError, run as auth_run_helper, Authenticator};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often the operations waiting for the network check if they were cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Timeouts (in seconds) currently set for each of the operations, zero means no timeout.
+// They are indexed by the `Operation` enum.
+static OPERATION_TIMEOUTS: [AtomicU64; 4] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Operation {
+    Login = 0,
+    CreateAcc = 1,
+    Authorise = 2,
+    Revoke = 3,
+}
+
+/// Timeouts, in seconds, for each of the operations which connect to the network.
+/// An operation without a timeout set waits for the network indefinitely.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct OperationTimeouts {
+    pub login: Option<u64>,
+    pub create_acc: Option<u64>,
+    pub authorise: Option<u64>,
+    pub revoke: Option<u64>,
+}
+
+impl OperationTimeouts {
+    /// The same timeout, in seconds, for all the operations
+    pub fn all(timeout: u64) -> Self {
+        Self {
+            login: Some(timeout),
+            create_acc: Some(timeout),
+            authorise: Some(timeout),
+            revoke: Some(timeout),
+        }
+    }
+}
+
+/// # Set the operations' timeouts
+///
+/// Sets the timeouts to be applied to each of the operations from now on.
+/// For operations which require several requests to the network the timeout
+/// is applied to each of them individually.
+pub fn set_timeouts(timeouts: &OperationTimeouts) {
+    let timeouts = [
+        (Operation::Login, timeouts.login),
+        (Operation::CreateAcc, timeouts.create_acc),
+        (Operation::Authorise, timeouts.authorise),
+        (Operation::Revoke, timeouts.revoke),
+    ];
+    for (op, timeout) in timeouts.iter() {
+        debug!("Timeout for {:?} operation set to: {:?}", op, timeout);
+        OPERATION_TIMEOUTS[*op as usize].store(timeout.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+fn op_timeout(op: Operation) -> Option<Duration> {
+    match OPERATION_TIMEOUTS[op as usize].load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+fn timeout_error_msg(op: Operation, timeout: Duration) -> String {
+    format!(
+        "{:?} operation timed out after {} seconds",
+        op,
+        timeout.as_secs()
+    )
+}
+
+fn check_cancelled(op: Operation, cancel: Option<&CancellationToken>) -> Result<(), Error> {
+    if cancel.is_some_and(CancellationToken::is_cancelled) {
+        Err(Error::Cancelled(format!(
+            "{:?} operation was cancelled",
+            op
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+// Waits for the outcome of an operation sent through the channel, failing if it
+// doesn't arrive within the timeout or if the operation is cancelled in the meantime
+fn wait_for<T>(
+    rx: &Receiver<T>,
+    op: Operation,
+    timeout: Option<Duration>,
+    cancel: Option<&CancellationToken>,
+) -> Result<T, Error> {
+    let started = Instant::now();
+    loop {
+        check_cancelled(op, cancel)?;
+        let remaining = match timeout {
+            Some(timeout) if started.elapsed() >= timeout => {
+                return Err(Error::Timeout(timeout_error_msg(op, timeout)))
+            }
+            Some(timeout) => Some(timeout - started.elapsed()),
+            None => None,
+        };
+        let wait = match (remaining, cancel) {
+            (Some(remaining), Some(_)) => remaining.min(CANCEL_POLL_INTERVAL),
+            (Some(remaining), None) => remaining,
+            (None, Some(_)) => CANCEL_POLL_INTERVAL,
+            (None, None) => {
+                return rx
+                    .recv()
+                    .map_err(|_| Error::NetworkError(format!("{:?} operation was aborted", op)))
+            }
+        };
+        match rx.recv_timeout(wait) {
+            Ok(result) => return Ok(result),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(Error::NetworkError(format!(
+                    "{:?} operation was aborted",
+                    op
+                )))
+            }
+        }
+    }
+}
+
+// Helper function to execute a blocking operation in a separate thread,
+// failing if it doesn't finish within the timeout set for the operation,
+// or if it's cancelled with the token provided before it finishes
+pub(crate) fn with_timeout<T, F>(
+    op: Operation,
+    cancel: Option<&CancellationToken>,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+    T: Send + 'static,
+{
+    let timeout = op_timeout(op);
+    if timeout.is_none() && cancel.is_none() {
+        return f();
+    }
+    // Nothing is sent to the network if it was cancelled already
+    check_cancelled(op, cancel)?;
+
+    let (tx, rx) = mpsc::channel();
+    let _ = thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    wait_for(&rx, op, timeout, cancel)?
+}
+
+// Helper function to run a future in the authenticator's event loop,
+// failing if it doesn't resolve within the timeout set for the operation,
+// or if it's cancelled with the token provided before it resolves.
+// The outer result reports the failures to reach the network, while the inner
+// one is the outcome of the operation itself.
+pub(crate) fn run_with_timeout<F, I, T>(
+    authenticator: &Authenticator,
+    op: Operation,
+    cancel: Option<&CancellationToken>,
+    f: F,
+) -> Result<Result<T, AuthError>, Error>
+where
+    F: FnOnce(&AuthClient) -> I + Send + 'static,
+    I: IntoFuture<Item = T, Error = AuthError> + 'static,
+    T: Send + 'static,
+{
+    let timeout = op_timeout(op);
+    if timeout.is_none() && cancel.is_none() {
+        return Ok(auth_run_helper(authenticator, f));
+    }
+    check_cancelled(op, cancel)?;
+
+    let (tx, rx) = mpsc::channel();
+    authenticator
+        .send(move |client| {
+            let future = f(client).into_future().then(move |result| {
+                let _ = tx.send(result);
+                Ok(())
+            });
+            let future: Box<dyn Future<Item = (), Error = ()>> = Box::new(future);
+            Some(future)
+        })
+        .map_err(|err| Error::NetworkError(format!("{:?} operation failed: {}", op, err)))?;
+
+    wait_for(&rx, op, timeout, cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wait_for, Operation};
+    use crate::errors::Error;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn wait_for_tests() {
+        let (tx, rx) = mpsc::channel();
+        unwrap!(tx.send(1));
+        assert_eq!(unwrap!(wait_for(&rx, Operation::Login, None, None)), 1);
+
+        // The network never answers
+        let timeout = Some(Duration::from_millis(50));
+        match wait_for(&rx, Operation::Revoke, timeout, None) {
+            Err(Error::Timeout(msg)) => assert!(msg.contains("Revoke operation timed out")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        drop(tx);
+        assert!(wait_for(&rx, Operation::Login, timeout, None).is_err());
+    }
+}
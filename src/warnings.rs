@@ -0,0 +1,501 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::account::{AccountInfo, AccountKeys};
+use crate::apps::{AuthedAppsDiff, AuthedAppsList};
+use crate::backend::AuthBackend;
+use crate::decode::{decode_req, decode_resp};
+use crate::errors::Error;
+use crate::events::{AuthEvent, EventBus};
+use crate::helpers::bytes_to_hex;
+use crate::ipc::{AppExchangeInfo, ContainerPermissions, IpcReq, Permission};
+use crate::AuthAllowPrompt;
+use log::{debug, warn};
+use safe_core::ipc::resp::IpcResp;
+use safe_core::ipc::IpcError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+// The default containers of an account, all of which are requested with all the permissions
+// by the requests for full access
+const DEFAULT_CONTAINERS: [&str; 7] = [
+    "_public",
+    "_publicNames",
+    "_documents",
+    "_downloads",
+    "_music",
+    "_pictures",
+    "_videos",
+];
+const ALL_PERMISSIONS: [Permission; 5] = [
+    Permission::Read,
+    Permission::Insert,
+    Permission::Update,
+    Permission::Delete,
+    Permission::ManagePermissions,
+];
+// How long after being denied the requests of an app are flagged as repeated
+const REPEAT_WINDOW: Duration = Duration::from_secs(5 * 60);
+// How long after being decided on the identical requests are flagged as possible replays
+const REPLAY_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// A risky pattern found in an auth request, flagged before it's decided on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "warning")]
+pub enum RequestWarning {
+    /// An app already authorised with the same id has another vendor
+    VendorMismatch {
+        app_id: String,
+        vendor: String,
+        authorised_vendor: String,
+    },
+    /// Every default container is requested with all the permissions
+    FullAccess { app_id: String },
+    /// The app sent the request again shortly after being denied
+    RepeatedAfterDenial { app_id: String, denials: usize },
+    /// The identical request, with the same id, was already decided on, so another app may
+    /// be replaying it
+    PossibleReplay { app_id: String, seconds_ago: u64 },
+}
+
+#[derive(Default)]
+struct WarningsState {
+    // When the recent requests of each app were denied
+    denials: HashMap<String, Vec<Instant>>,
+    // The hashes of the recent requests of each app decided on, and when they were
+    decided: HashMap<String, Vec<(String, Instant)>>,
+    // The warnings of the request of each app being decided on
+    pending: HashMap<String, Vec<RequestWarning>>,
+}
+
+/// # Flag the risky auth requests
+///
+/// Checks the auth requests for risky patterns before they are decided on: an app id already
+/// authorised with another vendor, a request for every default container with all the
+/// permissions, a request sent again shortly after the app was denied, or a request identical
+/// to one already decided on, which may be a replay of it. The warnings of the
+/// request being decided on can be got by the prompt with `pending`, e.g. to display them.
+/// It can be cloned, with all the clones sharing the same history of decisions.
+#[derive(Clone, Default)]
+pub struct RequestWarnings {
+    state: Arc<Mutex<WarningsState>>,
+}
+
+impl RequestWarnings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The warnings of the request, the encoded `raw` one being `req` once decoded, given the
+    /// apps currently authorised
+    pub fn check(
+        &self,
+        raw: &str,
+        req: &IpcReq,
+        authed_apps: &[AuthedAppsList],
+    ) -> Vec<RequestWarning> {
+        let app = match req_app(req) {
+            Some(app) => app,
+            None => return vec![],
+        };
+        let containers = match req {
+            IpcReq::Auth(auth_req) => Some(&auth_req.containers),
+            IpcReq::Containers(cont_req) => Some(&cont_req.containers),
+            _ => None,
+        };
+
+        let mut warnings = vec![];
+        if let Some(authed) = authed_apps
+            .iter()
+            .find(|authed| authed.app.id == app.id && authed.app.vendor != app.vendor)
+        {
+            warnings.push(RequestWarning::VendorMismatch {
+                app_id: app.id.clone(),
+                vendor: app.vendor.clone(),
+                authorised_vendor: authed.app.vendor.clone(),
+            });
+        }
+        if containers.is_some_and(is_full_access) {
+            warnings.push(RequestWarning::FullAccess {
+                app_id: app.id.clone(),
+            });
+        }
+
+        let mut state = unwrap!(self.state.lock());
+        let denials = state.denials.get_mut(&app.id).map_or(0, |denials| {
+            denials.retain(|denied| denied.elapsed() < REPEAT_WINDOW);
+            denials.len()
+        });
+        if denials > 0 {
+            warnings.push(RequestWarning::RepeatedAfterDenial {
+                app_id: app.id.clone(),
+                denials,
+            });
+        }
+        let hash = request_hash(raw);
+        if let Some(decided) = state.decided.get_mut(&app.id) {
+            decided.retain(|(_, decided)| decided.elapsed() < REPLAY_WINDOW);
+            if let Some((_, decided)) = decided
+                .iter()
+                .find(|(decided_hash, _)| *decided_hash == hash)
+            {
+                warnings.push(RequestWarning::PossibleReplay {
+                    app_id: app.id.clone(),
+                    seconds_ago: decided.elapsed().as_secs(),
+                });
+            }
+        }
+        let _ = state.pending.insert(app.id.clone(), warnings.clone());
+        warnings
+    }
+
+    /// The warnings of the request of the app being decided on
+    pub fn pending(&self, app_id: &str) -> Vec<RequestWarning> {
+        unwrap!(self.state.lock())
+            .pending
+            .get(app_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Records the decision on the encoded `raw` request of the app
+    pub fn decided(&self, raw: &str, app_id: &str, allowed: bool) {
+        let mut state = unwrap!(self.state.lock());
+        let _ = state.pending.remove(app_id);
+        state
+            .decided
+            .entry(app_id.to_string())
+            .or_insert_with(Vec::new)
+            .push((request_hash(raw), Instant::now()));
+        if allowed {
+            let _ = state.denials.remove(app_id);
+        } else {
+            state
+                .denials
+                .entry(app_id.to_string())
+                .or_insert_with(Vec::new)
+                .push(Instant::now());
+        }
+    }
+}
+
+fn request_hash(req: &str) -> String {
+    bytes_to_hex(&Sha256::digest(req.as_bytes()))
+}
+
+// The app which sent the request, unless it's unregistered
+fn req_app(req: &IpcReq) -> Option<&AppExchangeInfo> {
+    match req {
+        IpcReq::Auth(auth_req) => Some(&auth_req.app),
+        IpcReq::Containers(cont_req) => Some(&cont_req.app),
+        IpcReq::ShareMData(share_mdata_req) => Some(&share_mdata_req.app),
+        IpcReq::Unregistered(_) => None,
+    }
+}
+
+fn is_full_access(containers: &HashMap<String, ContainerPermissions>) -> bool {
+    DEFAULT_CONTAINERS.iter().all(|name| {
+        containers
+            .get(*name)
+            .is_some_and(|perms| ALL_PERMISSIONS.iter().all(|perm| perms.contains(perm)))
+    })
+}
+
+/// # Flag the risky auth requests authorised with a backend
+///
+/// Wraps an `AuthBackend`, checking each auth request with the `RequestWarnings` provided
+/// before it's decided on, and emitting an `AuthEvent::Warning` for each warning to the
+/// `EventBus` if one is given.
+///
+/// A request received again while the identical one is still being decided on, e.g. sent
+/// twice to the Authenticator service, is not prompted for a second time: it waits for the
+/// decision on the first one, and is given the same response.
+pub struct WarningBackend<B: AuthBackend> {
+    inner: B,
+    warnings: RequestWarnings,
+    events: Option<EventBus>,
+    in_flight: Mutex<HashMap<String, Arc<InFlight>>>,
+}
+
+// The response to a request being decided on, once it is
+#[derive(Default)]
+struct InFlight {
+    resp: Mutex<Option<Result<String, Error>>>,
+    done: Condvar,
+}
+
+impl InFlight {
+    fn wait(&self) -> Result<String, Error> {
+        let mut resp = unwrap!(self.resp.lock());
+        loop {
+            if let Some(resp) = &*resp {
+                return resp.clone();
+            }
+            resp = unwrap!(self.done.wait(resp));
+        }
+    }
+
+    fn finish(&self, result: &Result<String, Error>) {
+        *unwrap!(self.resp.lock()) = Some(result.clone());
+        self.done.notify_all();
+    }
+}
+
+impl<B: AuthBackend> WarningBackend<B> {
+    pub fn new(inner: B, warnings: RequestWarnings, events: Option<EventBus>) -> Self {
+        Self {
+            inner,
+            warnings,
+            events,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn check_and_authorise(
+        &self,
+        req: &str,
+        allow: &'static AuthAllowPrompt,
+    ) -> Result<String, Error> {
+        let decoded = decode_req(req).ok();
+        let app_id = decoded.as_ref().and_then(|(req_id, ipc_req)| {
+            // The apps authorised are only needed to compare the vendors, the other warnings
+            // are still checked if they can't be fetched
+            let authed_apps = self.inner.authed_apps().unwrap_or_default();
+            let warnings = self.warnings.check(req, ipc_req, &authed_apps);
+            for warning in &warnings {
+                warn!("Suspicious auth request: {:?}", warning);
+                if let Some(events) = &self.events {
+                    events.emit(AuthEvent::Warning {
+                        req_id: *req_id,
+                        warning: warning.clone(),
+                    });
+                }
+            }
+            req_app(ipc_req).map(|app| app.id.clone())
+        });
+
+        let result = self.inner.authorise_app(req, allow);
+        if let Some(app_id) = app_id {
+            let denied = match &result {
+                Ok(resp) => matches!(
+                    decode_resp(resp),
+                    Ok((_, IpcResp::Auth(Err(IpcError::AuthDenied))))
+                ),
+                Err(_) => false,
+            };
+            self.warnings.decided(req, &app_id, !denied);
+        }
+        result
+    }
+}
+
+impl<B: AuthBackend> AuthBackend for WarningBackend<B> {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        let hash = request_hash(req);
+        let (in_flight, duplicate) = {
+            let mut in_flight = unwrap!(self.in_flight.lock());
+            match in_flight.get(&hash) {
+                Some(first) => (Arc::clone(first), true),
+                None => {
+                    let first = Arc::new(InFlight::default());
+                    let _ = in_flight.insert(hash.clone(), Arc::clone(&first));
+                    (first, false)
+                }
+            }
+        };
+        if duplicate {
+            debug!("Auth request received again while it's decided on, waiting for the decision");
+            return in_flight.wait();
+        }
+
+        let result = self.check_and_authorise(req, allow);
+        let _ = unwrap!(self.in_flight.lock()).remove(&hash);
+        in_flight.finish(&result);
+        result
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        self.inner.authed_apps()
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        self.inner.revoke_app(app_id)
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.inner.acc_info()
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.inner.acc_keys()
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.inner.backup_account()
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.inner.restore_account(backup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RequestWarning, RequestWarnings, WarningBackend, ALL_PERMISSIONS, DEFAULT_CONTAINERS,
+    };
+    use crate::apps::AuthedAppsList;
+    use crate::backend::{AuthBackend, FakeBackend};
+    use crate::ipc::{AppExchangeInfo, AuthReq, IpcReq};
+    use crate::test_utils::{APP_AUTH_REQ, APP_ID};
+    use safe_nd::AppPermissions;
+    use std::collections::{BTreeSet, HashMap};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    // The encoded request the decoded ones of the tests stand for
+    const RAW: &str = "bAAAAAAEXVK4SGAAAAAA";
+    const DENIED: &str = "bAAAAAAEXVK4SGAAAAAC";
+
+    fn app(id: &str, vendor: &str) -> AppExchangeInfo {
+        AppExchangeInfo {
+            id: id.to_string(),
+            scope: None,
+            name: "Chat".to_string(),
+            vendor: vendor.to_string(),
+        }
+    }
+
+    fn auth_req(app: AppExchangeInfo, containers: &[&str]) -> IpcReq {
+        let perms: BTreeSet<_> = ALL_PERMISSIONS.iter().cloned().collect();
+        IpcReq::Auth(AuthReq {
+            app,
+            app_container: false,
+            app_permissions: AppPermissions {
+                transfer_coins: false,
+                perform_mutations: false,
+                get_balance: false,
+            },
+            containers: containers
+                .iter()
+                .map(|name| (name.to_string(), perms.clone()))
+                .collect::<HashMap<_, _>>(),
+        })
+    }
+
+    #[test]
+    fn request_warnings_tests() {
+        let warnings = RequestWarnings::new();
+        let req = auth_req(app("org.example.chat", "Example Ltd"), &["_public"]);
+        assert!(warnings.check(RAW, &req, &[]).is_empty());
+
+        // Another vendor than the one of the app authorised with the same id
+        let authed_apps = vec![AuthedAppsList {
+            app: app("org.example.chat", "Example Ltd"),
+            perms: vec![],
+        }];
+        let spoofed = auth_req(app("org.example.chat", "Other Ltd"), &["_public"]);
+        assert_eq!(
+            warnings.check(RAW, &spoofed, &authed_apps),
+            vec![RequestWarning::VendorMismatch {
+                app_id: "org.example.chat".to_string(),
+                vendor: "Other Ltd".to_string(),
+                authorised_vendor: "Example Ltd".to_string(),
+            }]
+        );
+        assert_eq!(warnings.pending("org.example.chat").len(), 1);
+        assert!(warnings.check(RAW, &req, &authed_apps).is_empty());
+
+        // Every default container with all the permissions
+        let full_access = auth_req(app("org.example.chat", "Example Ltd"), &DEFAULT_CONTAINERS);
+        assert_eq!(
+            warnings.check(RAW, &full_access, &[]),
+            vec![RequestWarning::FullAccess {
+                app_id: "org.example.chat".to_string()
+            }]
+        );
+
+        // Requested again once denied, until it's allowed
+        warnings.decided(DENIED, "org.example.chat", false);
+        warnings.decided(DENIED, "org.example.chat", false);
+        assert!(warnings.pending("org.example.chat").is_empty());
+        assert_eq!(
+            warnings.check(RAW, &req, &[]),
+            vec![RequestWarning::RepeatedAfterDenial {
+                app_id: "org.example.chat".to_string(),
+                denials: 2
+            }]
+        );
+        warnings.decided(DENIED, "org.example.chat", true);
+        assert!(warnings.check(RAW, &req, &[]).is_empty());
+    }
+
+    #[test]
+    fn possible_replay_tests() {
+        let warnings = RequestWarnings::new();
+        let req = auth_req(app("org.example.chat", "Example Ltd"), &["_public"]);
+        assert!(warnings.check(RAW, &req, &[]).is_empty());
+        warnings.decided(RAW, "org.example.chat", true);
+
+        // The identical request is flagged, not another one of the same app
+        match warnings.check(RAW, &req, &[]).as_slice() {
+            [RequestWarning::PossibleReplay { app_id, .. }] => {
+                assert_eq!(app_id, "org.example.chat")
+            }
+            warnings => panic!("Unexpected warnings: {:?}", warnings),
+        }
+        assert!(warnings.check("bAAAAAAEXVK4SGAAAAAB", &req, &[]).is_empty());
+        // Nor the same request of another app
+        let other = auth_req(app("org.example.mail", "Example Ltd"), &["_public"]);
+        assert!(warnings.check(RAW, &other, &[]).is_empty());
+    }
+
+    static PROMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn duplicate_requests_tests() {
+        let backend = Arc::new(WarningBackend::new(
+            FakeBackend::default(),
+            RequestWarnings::new(),
+            None,
+        ));
+        // The request sent again while the first one is prompted for waits for its decision
+        let threads: Vec<_> = (0..2)
+            .map(|i| {
+                let backend = Arc::clone(&backend);
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(i * 100));
+                    backend.authorise_app(APP_AUTH_REQ, &|_| {
+                        let _ = PROMPTS.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(500));
+                        true
+                    })
+                })
+            })
+            .collect();
+        let resps: Vec<String> = threads
+            .into_iter()
+            .map(|thread| unwrap!(unwrap!(thread.join())))
+            .collect();
+        assert_eq!(PROMPTS.load(Ordering::SeqCst), 1);
+        assert_eq!(resps[0], resps[1]);
+        assert!(resps[0].contains(APP_ID));
+
+        // Once decided on, it's prompted for again, flagged as a possible replay
+        let _ = unwrap!(backend.authorise_app(APP_AUTH_REQ, &|_| {
+            let _ = PROMPTS.fetch_add(1, Ordering::SeqCst);
+            true
+        }));
+        assert_eq!(PROMPTS.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+// Bindings exposed to JavaScript when built for WebAssembly with the `wasm` feature.
+// Only the logic which doesn't need the network nor any credentials is exposed.
+
+use crate::inspect_auth_req;
+use wasm_bindgen::prelude::*;
+
+/// Decodes an authorisation request string and returns its details as a JSON string
+#[wasm_bindgen(js_name = inspectAuthReq)]
+pub fn inspect_auth_req_json(req: &str) -> Result<String, JsValue> {
+    let info = inspect_auth_req(req).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    serde_json::to_string(&info).map_err(|err| JsValue::from_str(&err.to_string()))
+}
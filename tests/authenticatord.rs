@@ -6,12 +6,14 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+#![cfg(feature = "daemon")]
+
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use safe_core::client::test_create_balance;
-use safe_nd::Coins;
+use safe_nd::{ClientFullId, Coins};
 use std::process::{Child, Command};
 use std::str::FromStr;
 use std::{thread, time};
@@ -43,12 +45,19 @@ fn init_server() -> (Child, u16) {
 
     let mut cmd = Command::cargo_bin("safe_auth").unwrap();
     let (sk, secret_key) = gen_random_sk_hex();
-    test_create_balance(&secret_key, Coins::from_str("666").unwrap()).unwrap();
+    test_create_balance(
+        &ClientFullId::from(secret_key),
+        Coins::from_str("666").unwrap(),
+    )
+    .unwrap();
 
     let child = cmd
         .env("SAFE_AUTH_SECRET", &rand_string)
         .env("SAFE_AUTH_PASSWORD", "password")
+        // A profile of its own, as a single service runs for each profile
         .args(vec![
+            "--profile",
+            &rand_string,
             "--allow-all-auth",
             "--daemon",
             &port.to_string(),
@@ -65,6 +74,11 @@ fn init_server() -> (Child, u16) {
     (child, port)
 }
 
+fn stop_server(server_process: &mut Child) {
+    server_process.kill().expect("Process was not running");
+    let _ = server_process.wait();
+}
+
 #[test]
 fn curl_create_account() {
     let (mut server_process, port) = init_server();
@@ -77,11 +91,11 @@ fn curl_create_account() {
         port, &rand_string, &rand_string, &rand_string
     );
     let mut cmd = Command::new("curl");
-    cmd.args(&vec!["-X", "POST", &endpoint])
+    cmd.args(vec!["-X", "POST", &endpoint])
         .assert()
         .stdout(predicate::str::contains("Create service not supported yet"))
         .success();
-    server_process.kill().expect("Process was not running");
+    stop_server(&mut server_process);
 }
 
 #[test]
@@ -110,11 +124,11 @@ fn curl_login() {
         port, &rand_string, &rand_string
     );
     let mut cmd = Command::new("curl");
-    cmd.args(&vec!["-X", "POST", &endpoint])
+    cmd.args(vec!["-X", "POST", &endpoint])
         .assert()
         .stdout(predicate::str::contains("Login service not supported yet"))
         .success();
-    server_process.kill().expect("Process was not running");
+    stop_server(&mut server_process);
 }
 
 #[test]
@@ -122,6 +136,6 @@ fn curl_authorise() {
     let (mut server_process, port) = init_server();
     let endpoint = format!("http://localhost:{}/authorise/{}", port, AUTHED_REQ);
     let mut cmd = Command::new("curl");
-    cmd.args(&vec!["-X", "GET", &endpoint]).assert().success();
-    server_process.kill().expect("Process was not running");
+    cmd.args(vec!["-X", "GET", &endpoint]).assert().success();
+    stop_server(&mut server_process);
 }
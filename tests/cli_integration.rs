@@ -12,10 +12,12 @@ use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use safe_auth::{run_with_args, CapturedIo, Error};
 use safe_core::client::test_create_balance;
-use safe_nd::Coins;
+use safe_nd::{ClientFullId, Coins};
 use std::fs;
 use std::io::Write;
+use std::net::TcpListener;
 use std::process::Command;
 use std::str::FromStr;
 use threshold_crypto::{serde_impl::SerdeSecret, SecretKey};
@@ -27,15 +29,23 @@ static UNAUTHED_RESPONSE: &str = "bAEAAAAGY45BPQAQAAAAAAAAAAAAAAAAAAAAAAAAB\n";
 
 static AUTHED_REQ: &str = "bAAAAAACNZZQ4WAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAAAAAAAAAAAAAB";
 static AUTHED_RESPONSE_START: &str = "bAEAAAACNZZQ4W";
+// The same app as `AUTHED_REQ`, requesting the `_public` container with Read permission
+static PUBLIC_READ_REQ: &str = "bAAAAAACNZZQ4WAAAAAABAAAAAAAAAAAANZSXILTNMFUWI43BMZSS4Y3MNEAAQAAAAAAAAAAAKNAUMRJAINGESEAAAAAAAAAAABGWC2LEKNQWMZJONZSXIICMORSAAAIBAEAQAAAAAAAAAAAHAAAAAAAAAAAF64DVMJWGSYYBAAAAAAAAAAAAAAAAAAAQ";
 
 static CONFIG_FILE: &str = "./tests/test.config.json";
+static AUTHED_APPS_RECORDING: &str = "./tests/authed_apps.recording.json";
+static PARSABLE_AUTHED_APPS: &str = "APP ID\tNAME\tVENDOR\tPERMISSIONS\nnet.maidsafe.cli\t\"SAFE CLI\"\t\"MaidSafe.net Ltd\"\t[\"_public\":Read]\n";
 
 fn gen_random_sk_with_balance() -> String {
     let sk = SecretKey::random();
     let sk_serialised = bincode::serialize(&SerdeSecret(&sk))
         .expect("Failed to serialise the generated secret key");
     let sk_hex = sk_serialised.iter().map(|b| format!("{:02x}", b)).collect();
-    test_create_balance(&sk, Coins::from_str("500").unwrap()).unwrap();
+    test_create_balance(
+        &ClientFullId::from(sk.clone()),
+        Coins::from_str("500").unwrap(),
+    )
+    .unwrap();
     sk_hex
 }
 
@@ -47,8 +57,8 @@ fn write_random_config_credentials() -> String {
         "{{ \"secret\": \"{}\", \"password\": \"{}\"}}",
         rand_string, rand_string
     );
-    let mut file = fs::File::create(&CONFIG_FILE).unwrap();
-    file.write(login_credentials.as_bytes()).unwrap();
+    let mut file = fs::File::create(CONFIG_FILE).unwrap();
+    file.write_all(login_credentials.as_bytes()).unwrap();
 
     rand_string
 }
@@ -59,14 +69,14 @@ fn calling_safe_create_acc_and_login() {
     let _ = write_random_config_credentials();
 
     let mut cmd = Command::cargo_bin("safe_auth").unwrap();
-    cmd.args(&vec!["--sk", &sk, "--config", &CONFIG_FILE])
+    cmd.args(vec!["--sk", &sk, "--config", &CONFIG_FILE])
         .assert()
         .stdout(predicate::str::starts_with(PRETTY_ACCOUNT_CREATION_RESPONSE).from_utf8())
         .success();
 
     let mut auth_cmd = Command::cargo_bin("safe_auth").unwrap();
     auth_cmd
-        .args(&vec!["--config", &CONFIG_FILE])
+        .args(vec!["--config", &CONFIG_FILE])
         .assert()
         .stdout(PRETTY_LOGIN_RESPONSE)
         .success();
@@ -79,7 +89,7 @@ fn calling_safe_create_acc_with_env_vars() {
 
     cmd.env("SAFE_AUTH_SECRET", format!("random-{}", sk))
         .env("SAFE_AUTH_PASSWORD", "password")
-        .args(&vec!["--sk", &sk])
+        .args(vec!["--sk", &sk])
         .assert()
         .stdout(PRETTY_ACCOUNT_CREATION_RESPONSE)
         .success();
@@ -91,12 +101,13 @@ fn calling_safe_create_acc_with_only_one_env_var() {
     let sk = gen_random_sk_with_balance();
 
     cmd.env("SAFE_AUTH_SECRET", format!("random-{}", sk))
-        .args(&vec!["--sk", &sk])
+        .args(vec!["--sk", &sk])
         .assert()
-        .failure();
+        .failure()
+        .code(3);
 
     cmd.env("SAFE_AUTH_PASSWORD", "password")
-        .args(&vec!["--sk", &sk])
+        .args(vec!["--sk", &sk])
         .assert()
         .failure();
 }
@@ -110,7 +121,7 @@ fn calling_safe_auth_with_unregistered_req() {
     auth_cmd
         .env("SAFE_AUTH_SECRET", format!("random-{}", sk))
         .env("SAFE_AUTH_PASSWORD", "password")
-        .args(&vec!["--sk", &sk, "-r", &UNAUTHED_REQ, "--json"])
+        .args(vec!["--sk", &sk, "-r", &UNAUTHED_REQ, "--json"])
         .assert()
         .stdout(UNAUTHED_RESPONSE)
         .success();
@@ -124,7 +135,7 @@ fn calling_safe_auth_with_registered_req() {
     auth_cmd
         .env("SAFE_AUTH_SECRET", format!("random-{}", sk))
         .env("SAFE_AUTH_PASSWORD", "password")
-        .args(&vec![
+        .args(vec![
             "--allow-all-auth",
             "--sk",
             &sk,
@@ -145,14 +156,999 @@ fn create_acc_with_env_vars_log_in_with_config() {
     let mut cmd = Command::cargo_bin("safe_auth").unwrap();
     cmd.env("SAFE_AUTH_SECRET", rand_string.clone())
         .env("SAFE_AUTH_PASSWORD", rand_string.clone())
-        .args(&vec!["--sk", &sk, "--json"])
+        .args(vec!["--sk", &sk, "--json"])
         .assert()
         .success();
 
     // and now verify it can log in if reading the same credentials from the config
     let mut cmd = Command::cargo_bin("safe_auth").unwrap();
-    cmd.args(&vec!["--config", &CONFIG_FILE])
+    cmd.args(vec!["--config", &CONFIG_FILE])
         .assert()
         .stdout(predicate::str::starts_with(PRETTY_LOGIN_RESPONSE).from_utf8())
         .success();
 }
+
+#[test]
+fn replay_authed_apps_recording() {
+    let mut cmd = Command::cargo_bin("safe_auth").unwrap();
+    cmd.args(vec!["--replay", &AUTHED_APPS_RECORDING, "--apps", "--json"])
+        .assert()
+        .stdout(PARSABLE_AUTHED_APPS)
+        .success();
+}
+
+#[test]
+fn replay_recording_with_unexpected_operation() {
+    let mut cmd = Command::cargo_bin("safe_auth").unwrap();
+    cmd.args(vec![
+        "--replay",
+        &AUTHED_APPS_RECORDING,
+        "--revoke",
+        "net.maidsafe.cli",
+    ])
+    .assert()
+    .failure()
+    .code(2);
+}
+
+#[test]
+fn run_in_process_with_replayed_recording() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--replay",
+            AUTHED_APPS_RECORDING,
+            "--apps",
+            "--json",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert_eq!(output.stdout(), PARSABLE_AUTHED_APPS);
+
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--replay",
+            AUTHED_APPS_RECORDING,
+            "--revoke",
+            "net.maidsafe.cli",
+        ],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 2);
+    match result.error {
+        Some(Error::InvalidInput(_)) => (), // This should pass
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn run_in_process_with_mock_account() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "--apps",
+            "--json",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().contains("net.maidsafe.cli"));
+
+    // The shorter aliases allow the requests without a terminal to answer the prompt too
+    for flag in &["--yes", "-y", "--allow"] {
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let result = run_with_args(
+            &["safe_auth", "--mock", flag, "--req", AUTHED_REQ, "--apps"],
+            io,
+        );
+        assert!(result.is_success());
+        assert!(output.stdout().contains("net.maidsafe.cli"));
+    }
+
+    // A mock account can't be created with a secret key, nor with test coins
+    let result = run_with_args(
+        &["safe_auth", "--mock", "--test-coins"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 1);
+}
+
+#[test]
+fn run_in_process_with_subcommands() {
+    // The commands replacing the flags work the same way
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "auth",
+            AUTHED_REQ,
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().contains("Authorisation response string"));
+
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "--json",
+            "apps",
+            "list",
+            "--with-permission",
+            "Read",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().contains("net.maidsafe.cli"));
+
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--mock", "balance"], io);
+    assert!(result.is_success());
+    assert!(output.stdout().contains("Balance: "));
+
+    // The app authorised is in the timeline, from its receipt or from the network
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "--output",
+            "jsonl",
+            "account",
+            "activity",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    let activities: Vec<serde_json::Value> = output
+        .stdout()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .filter(|record: &serde_json::Value| record.get("activity").is_some())
+        .collect();
+    assert!(activities
+        .iter()
+        .any(|activity| activity["app_id"] == "net.maidsafe.cli"
+            && (activity["activity"] == "allowed" || activity["activity"] == "authorised")));
+
+    // But not with the flags they replace
+    let result = run_with_args(
+        &["safe_auth", "--mock", "--apps", "apps", "list"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 2);
+    let result = run_with_args(
+        &["safe_auth", "create", "--sk", "00", "--test-coins"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 1);
+}
+
+#[test]
+fn run_in_process_apps_export() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "apps",
+            "--export",
+            "csv",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    let stdout = output.stdout();
+    // The apps follow the auth response
+    let mut rows = stdout
+        .split("\r\n")
+        .skip_while(|row| !row.starts_with("id,"));
+    assert_eq!(rows.next(), Some("id,name,vendor,permissions,label"));
+    assert!(rows.next().unwrap().starts_with("net.maidsafe.cli,"));
+
+    // Only CSV is supported, and only to list the apps
+    for args in &[
+        &["safe_auth", "--mock", "apps", "--export", "xml"][..],
+        &["safe_auth", "--mock", "--json", "apps", "--export", "csv"][..],
+        &[
+            "safe_auth",
+            "--mock",
+            "apps",
+            "--export",
+            "csv",
+            "edit",
+            "a",
+        ][..],
+        &["safe_auth", "--mock", "apps"][..],
+    ] {
+        let result = run_with_args(args, CapturedIo::new(""));
+        assert_eq!(result.exit_code, 2, "{:?}", args);
+    }
+}
+
+#[test]
+fn run_in_process_apps_label() {
+    let profile: String = thread_rng().sample_iter(&Alphanumeric).take(10).collect();
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--profile",
+            &profile,
+            "apps",
+            "label",
+            "net.maidsafe.cli",
+            "work laptop",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().contains("was set to \"work laptop\""));
+
+    // Shown in the list of the authorised apps
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--profile",
+            &profile,
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "--format",
+            "{{app.id}}: {{label}}",
+            "apps",
+            "list",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().contains("net.maidsafe.cli: work laptop"));
+
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--profile",
+            &profile,
+            "apps",
+            "label",
+            "net.maidsafe.cli",
+            "--remove",
+        ],
+        CapturedIo::new(""),
+    );
+    assert!(result.is_success());
+}
+
+#[test]
+fn run_in_process_migrate() {
+    let profile: String = thread_rng().sample_iter(&Alphanumeric).take(10).collect();
+    let file = std::env::temp_dir().join(format!("safe_auth-legacy-{}.json", profile));
+    std::fs::write(
+        &file,
+        r#"{ "apps": [
+                { "id": "net.maidsafe.cli", "label": "work laptop", "granted_at": 1546300800 },
+                { "id": "net.example.old", "label": "old", "revoked": true }
+            ],
+            "denylist": ["net.example.bad"] }"#,
+    )
+    .unwrap();
+    let migrate = || {
+        // The credentials unlock the key the grants imported are signed with
+        let io = CapturedIo::new("secret\npassword\n");
+        let output = io.output();
+        let result = run_with_args(
+            &[
+                "safe_auth",
+                "--profile",
+                &profile,
+                "migrate",
+                file.to_str().unwrap(),
+            ],
+            io,
+        );
+        assert!(result.is_success());
+        output.stdout()
+    };
+    assert!(migrate().contains("1 label(s), 1 grant time(s) and 1 app(s)"));
+    // Nothing is imported twice
+    assert!(migrate().contains("0 label(s), 0 grant time(s) and 0 app(s)"));
+
+    // The label imported is shown in the list of the authorised apps
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--profile",
+            &profile,
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "--format",
+            "{{app.id}}: {{label}}",
+            "apps",
+            "list",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().contains("net.maidsafe.cli: work laptop"));
+    let _ = std::fs::remove_file(&file);
+
+    let result = run_with_args(
+        &["safe_auth", "migrate", "./tests/missing.json"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 7);
+}
+
+#[test]
+fn run_in_process_shell() {
+    // The commands run on the same mock account, the errors don't end the shell
+    let io = CapturedIo::new(&format!(
+        "auth {}\napps\nbalance\n\nrevoke net.maidsafe.cli\nrevoke net.maidsafe.cli\nlogin\nexit\nbalance\n",
+        AUTHED_REQ
+    ));
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--mock", "--allow-all-auth", "shell"], io);
+    assert!(result.is_success());
+    let stdout = output.stdout();
+    assert!(stdout.contains("Authorisation response string"));
+    assert!(stdout.contains("net.maidsafe.cli"));
+    assert_eq!(stdout.matches("Balance: ").count(), 1);
+    assert_eq!(stdout.matches("permissions were revoked").count(), 1);
+    let stderr = output.stderr();
+    assert!(stderr.contains("is not registered"));
+    assert!(stderr.contains("Unknown command 'login'"));
+
+    // The end of the input ends it too
+    let result = run_with_args(
+        &["safe_auth", "--mock", "shell"],
+        CapturedIo::new("balance\n"),
+    );
+    assert!(result.is_success());
+    let result = run_with_args(
+        &["safe_auth", "--mock", "--headless", "shell"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 2);
+    let result = run_with_args(
+        &["safe_auth", "--mock", "--daemon", "41899", "shell"],
+        CapturedIo::new(""),
+    );
+    match result.error {
+        Some(Error::InvalidInput(message)) => assert!(message.contains("--headless or --daemon")),
+        other => panic!("Unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn run_in_process_shell_history() {
+    // The commands are kept across the sessions of the profile, until cleared
+    let args = ["safe_auth", "--mock", "--profile", "shell-history", "shell"];
+    let result = run_with_args(&args, CapturedIo::new("history clear\napps\nbalance\n"));
+    assert!(result.is_success());
+    let io = CapturedIo::new("history\nhistory clear\nhistory\n");
+    let output = io.output();
+    assert!(run_with_args(&args, io).is_success());
+    let stdout = output.stdout();
+    assert!(stdout.contains("    1  apps\n    2  balance\n    3  history\n"));
+    assert_eq!(stdout.matches("  apps\n").count(), 1);
+}
+
+// The output contract of --strict, which the tools driving the CLI rely on: only the results
+// are written to stdout, with the same fields from one release to the next, and the rest,
+// including the errors as JSON objects, to stderr
+fn strict_error(stderr: &str) -> serde_json::Value {
+    let last_line = stderr.lines().last().unwrap();
+    serde_json::from_str(last_line).unwrap()
+}
+
+#[test]
+fn strict_contract_results() {
+    // The prompt is written to stderr, the response and the apps to stdout
+    let io = CapturedIo::new("y\n");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--strict",
+            "--mock",
+            "--req",
+            PUBLIC_READ_REQ,
+            "--apps",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    let stdout = output.stdout();
+    let mut lines = stdout.splitn(2, '\n');
+    assert!(lines.next().unwrap().starts_with("fake-auth-granted:"));
+    assert_eq!(lines.next().unwrap(), PARSABLE_AUTHED_APPS);
+    assert!(output.stderr().contains("Allow authorisation?"));
+
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--strict", "--mock", "balance"], io);
+    assert!(result.is_success());
+    let balance: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert!(balance["balance"].is_string());
+
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--strict", "--capabilities"], io);
+    assert!(result.is_success());
+    let capabilities: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert_eq!(capabilities["strict"], true);
+
+    // Another parsable output can be given, but not the tables
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--strict",
+            "--mock",
+            "--output",
+            "plain",
+            "--allow-all-auth",
+            "--req",
+            PUBLIC_READ_REQ,
+            "apps",
+            "list",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().ends_with(PARSABLE_AUTHED_APPS));
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--strict",
+            "--mock",
+            "--output",
+            "table",
+            "balance",
+        ],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 2);
+}
+
+#[test]
+fn strict_contract_errors() {
+    // Each error is reported with its kind, and the exit code of the kind
+    let cases: &[(&[&str], i32, &str)] = &[
+        (&["safe_auth", "--strict", "--bogus"], 1, "InvalidInput"),
+        (
+            &["safe_auth", "--strict", "--mock", "revoke"],
+            2,
+            "InvalidInput",
+        ),
+        (
+            &["safe_auth", "--strict", "--mock", "--req", "invalid"],
+            5,
+            "IpcDecodeError",
+        ),
+        (
+            &[
+                "safe_auth",
+                "--strict",
+                "decide",
+                "safe-auth:decide/64/42/maybe",
+            ],
+            2,
+            "InvalidInput",
+        ),
+        // The profile `no-such-profile`, which has no service running
+        (
+            &[
+                "safe_auth",
+                "--strict",
+                "decide",
+                "safe-auth:decide/6e6f2d737563682d70726f66696c65/42/allow",
+            ],
+            2,
+            "InvalidInput",
+        ),
+        (
+            &[
+                "safe_auth",
+                "--strict",
+                "--replay",
+                "./tests/missing.json",
+                "balance",
+            ],
+            7,
+            "IoError",
+        ),
+    ];
+    for (args, exit_code, kind) in cases {
+        let io = CapturedIo::new("");
+        let output = io.output();
+        let result = run_with_args(args, io);
+        assert_eq!(result.exit_code, *exit_code, "{:?}", args);
+        assert_eq!(output.stdout(), "", "{:?}", args);
+        let error = strict_error(&output.stderr());
+        assert_eq!(error["kind"], *kind, "{:?}", args);
+        assert!(error["description"].is_string());
+    }
+
+    // The outcome of each app is still the result when some of them couldn't be revoked
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--strict",
+            "--mock",
+            "revoke",
+            "net.maidsafe.cli",
+        ],
+        io,
+    );
+    assert_eq!(result.exit_code, 1);
+    let outcomes: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert_eq!(outcomes[0]["app_id"], "net.maidsafe.cli");
+    assert_eq!(outcomes[0]["revoked"], false);
+    assert_eq!(outcomes[0]["error"]["kind"], "AuthenticatorError");
+    assert_eq!(strict_error(&output.stderr())["kind"], "AuthenticatorError");
+}
+
+#[test]
+fn quiet_prints_only_the_data() {
+    // The prompt and the login message go to stderr, only the response to stdout
+    let io = CapturedIo::new("y\n");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--quiet", "--mock", "--req", AUTHED_REQ], io);
+    assert!(result.is_success());
+    let stdout = output.stdout();
+    assert!(stdout.starts_with("fake-auth-granted:"));
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(output.stderr().contains("Allow authorisation?"));
+
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "-q", "--mock", "balance"], io);
+    assert!(result.is_success());
+    let balance = output.stdout();
+    assert!(balance.trim_end().parse::<f64>().is_ok());
+
+    let result = run_with_args(
+        &["safe_auth", "--quiet", "--strict", "--mock", "balance"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 1);
+}
+
+#[test]
+fn run_in_process_non_interactive() {
+    // The answer typed is never read, the request fails rather than being prompted for
+    let io = CapturedIo::new("y\n");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--non-interactive",
+            "--mock",
+            "--req",
+            AUTHED_REQ,
+        ],
+        io,
+    );
+    assert_eq!(result.exit_code, 2);
+    assert!(!output.stdout().contains("Allow authorisation?"));
+
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--non-interactive",
+            "--mock",
+            "--yes",
+            "--req",
+            AUTHED_REQ,
+            "--apps",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output.stdout().contains("net.maidsafe.cli"));
+
+    for cmd in &[&["shell"][..], &["revoke", "--interactive"][..]] {
+        let mut args = vec!["safe_auth", "--non-interactive", "--mock"];
+        args.extend_from_slice(cmd);
+        let result = run_with_args(&args, CapturedIo::new(""));
+        assert_eq!(result.exit_code, 2, "{:?}", cmd);
+    }
+}
+
+#[test]
+fn run_in_process_listen_once() {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let listener = std::thread::spawn(move || {
+        let port = port.to_string();
+        let args = [
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--output",
+            "plain",
+            "listen",
+            "--once",
+            "--port",
+            &port,
+        ];
+        run_with_args(&args, io)
+    });
+
+    // The handler of the app's URI sends it the same as to the service
+    let mut stream = loop {
+        match std::net::TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => break stream,
+            Err(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+        }
+    };
+    write!(stream, "GET /authorise/{} HTTP/1.1\r\n\r\n", AUTHED_REQ).unwrap();
+    let mut response = String::new();
+    std::io::Read::read_to_string(&mut stream, &mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+    assert!(listener.join().unwrap().is_success());
+    let auth_response = output.stdout();
+    assert!(response.ends_with(auth_response.trim()));
+    assert!(output
+        .stderr()
+        .contains("Listening for auth requests on 127.0.0.1"));
+
+    let result = run_with_args(
+        &["safe_auth", "--mock", "--headless", "listen"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 2);
+}
+
+#[test]
+fn run_in_process_status() {
+    // Nothing is listening on a port which was just released
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--json",
+            "status",
+            "--port",
+            &port.to_string(),
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    let status: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert!(status["credentials"].is_null());
+    assert!(status["daemon"].is_null());
+    assert_eq!(status["balance"], "0");
+    // The user is never prompted for the credentials
+    assert!(output.stderr().is_empty());
+}
+
+#[test]
+fn run_in_process_capabilities() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--capabilities"], io);
+    assert!(result.is_success());
+    let capabilities: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert_eq!(capabilities["mock_account"], true);
+    assert!(capabilities["daemon_transports"].is_array());
+}
+
+#[test]
+fn run_in_process_doctor() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--config",
+            "no-such-config.json",
+            "--json",
+            "doctor",
+        ],
+        io,
+    );
+    // The config file can't be read
+    assert_eq!(result.exit_code, 1);
+    let checks: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert_eq!(checks[0]["check"], "config");
+    assert_eq!(checks[0]["status"], "failed");
+    assert!(checks[0]["fix"].is_string());
+}
+
+#[test]
+fn run_in_process_revoke_interactive() {
+    // The app authorised is found by its name and selected
+    let io = CapturedIo::new("safe cli\n1\n\ny\n");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "revoke",
+            "--interactive",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    assert!(output
+        .stdout()
+        .contains("Authorised permissions were revoked for app 'net.maidsafe.cli'"));
+
+    // Without any IDs the apps are only picked from the list on the console
+    let result = run_with_args(&["safe_auth", "--mock", "revoke"], CapturedIo::new(""));
+    assert_eq!(result.exit_code, 2);
+}
+
+#[test]
+fn run_in_process_revoke_from_file() {
+    let file = std::env::temp_dir().join("safe_auth-revoke-from-file.json");
+    std::fs::write(&file, r#"["net.maidsafe.cli", "org.example.unknown"]"#).unwrap();
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "--output",
+            "plain",
+            "revoke",
+            "--from-file",
+            &file.display().to_string(),
+        ],
+        io,
+    );
+    // All the apps are tried, but one of them is not authorised
+    assert_eq!(result.exit_code, 1);
+    let stdout = output.stdout();
+    assert!(stdout.contains("net.maidsafe.cli\trevoked\n"));
+    assert!(stdout.contains("org.example.unknown\tfailed\t"));
+    let _ = std::fs::remove_file(&file);
+}
+
+#[test]
+fn run_in_process_apps_diff_snapshots() {
+    let old = std::env::temp_dir().join("safe_auth-apps-diff-old.json");
+    let new = std::env::temp_dir().join("safe_auth-apps-diff-new.json");
+    std::fs::write(
+        &old,
+        r#"[{"app": {"id": "net.maidsafe.cli", "scope": null, "name": "SAFE CLI",
+            "vendor": "MaidSafe.net Ltd"}, "perms": [["_public", ["Read"]]]}]"#,
+    )
+    .unwrap();
+    std::fs::write(&new, "[]").unwrap();
+    let (old, new) = (old.display().to_string(), new.display().to_string());
+
+    // Two snapshots are compared without logging in, and the differences are a failure
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &["safe_auth", "--output", "plain", "apps", "diff", &old, &new],
+        io,
+    );
+    assert_eq!(result.exit_code, 1);
+    assert_eq!(
+        output.stdout(),
+        "APP ID\tCHANGE\nnet.maidsafe.cli\tremoved\n"
+    );
+
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "apps", "diff", &old, &old], io);
+    assert!(result.is_success());
+    assert!(output
+        .stdout()
+        .contains("The authorised apps didn't change"));
+
+    let _ = std::fs::remove_file(&old);
+    let _ = std::fs::remove_file(&new);
+}
+
+#[test]
+fn run_in_process_apps_export_and_restore() {
+    let file = std::env::temp_dir().join("safe_auth-apps-export.json");
+    let snapshot = file.display().to_string();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            AUTHED_REQ,
+            "apps",
+            "export",
+            &snapshot,
+        ],
+        CapturedIo::new(""),
+    );
+    assert!(result.is_success());
+    assert!(std::fs::read_to_string(&file)
+        .unwrap()
+        .contains("net.maidsafe.cli"));
+
+    // Restored to an account where the app is not authorised anymore, once confirmed
+    let io = CapturedIo::new("y\n");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--mock", "apps", "restore", &snapshot], io);
+    assert!(result.is_success());
+    let stdout = output.stdout();
+    assert!(stdout.contains("Grant the permissions again to 1 app(s)? [y/N]:"));
+    assert!(stdout.contains("granted again to app 'net.maidsafe.cli'"));
+    let _ = std::fs::remove_file(&file);
+}
+
+#[test]
+fn run_in_process_apps_edit() {
+    // The first permission of the app authorised is unchecked
+    let io = CapturedIo::new("1\n\ny\n");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--allow-all-auth",
+            "--req",
+            PUBLIC_READ_REQ,
+            "apps",
+            "edit",
+            "net.maidsafe.cli",
+        ],
+        io,
+    );
+    assert!(result.is_success());
+    let stdout = output.stdout();
+    assert!(stdout.contains("  1) [ ] "));
+    assert!(stdout.contains("The permissions of 'net.maidsafe.cli' were changed"));
+
+    let result = run_with_args(
+        &["safe_auth", "--mock", "apps", "edit", "org.example.chat"],
+        CapturedIo::new(""),
+    );
+    assert_eq!(result.exit_code, 2);
+}
+
+#[test]
+fn run_in_process_with_json_progress() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--mock", "--apps", "--progress", "json"], io);
+    assert!(result.is_success());
+    let events: Vec<serde_json::Value> = output
+        .stderr()
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events[0]["stage"], "Fetch list of authorised applications");
+    assert_eq!(events[0]["percent"], 0);
+    assert_eq!(events[events.len() - 1]["percent"], 100);
+    // The legacy flags are deprecated, without the logs enabled
+    assert!(output
+        .stderr()
+        .contains("--apps is deprecated, use the apps list command instead"));
+
+    // Each subcommand has its own stage, completed even if it fails
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(
+        &[
+            "safe_auth",
+            "--mock",
+            "--progress",
+            "json",
+            "apps",
+            "edit",
+            "missing",
+        ],
+        io,
+    );
+    assert!(!result.is_success());
+    let stages: Vec<serde_json::Value> = output
+        .stderr()
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|event| event["stage"] == "Edit permissions of application")
+        .collect();
+    assert_eq!(stages[0]["percent"], 0);
+    assert_eq!(stages[stages.len() - 1]["percent"], 100);
+}
+
+#[test]
+fn run_in_process_with_lang() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--lang", "en_GB", "--capabilities"], io);
+    assert!(result.is_success());
+    let capabilities: serde_json::Value = serde_json::from_str(&output.stdout()).unwrap();
+    assert_eq!(capabilities["languages"][0], "en-US");
+
+    // The languages without a translation fall back to English
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--lang", "xx", "--capabilities"], io);
+    assert!(result.is_success());
+    assert!(output
+        .stderr()
+        .contains("The language 'xx' is not available, the messages are in English"));
+
+    // The errors are reported with the localised message of their kind
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--mock", "apps", "edit", "missing"], io);
+    assert_eq!(result.exit_code, 2);
+    assert!(output.stderr().starts_with("Invalid input: "));
+}
+
+#[test]
+fn run_in_process_with_invalid_args() {
+    let io = CapturedIo::new("");
+    let output = io.output();
+    let result = run_with_args(&["safe_auth", "--no-such-arg"], io);
+    assert_eq!(result.exit_code, 1);
+    assert!(output.stderr().contains("--no-such-arg"));
+    assert!(output.stdout().is_empty());
+}
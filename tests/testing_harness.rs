@@ -0,0 +1,149 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+#![cfg(feature = "testing")]
+
+use safe_auth::testing::{
+    assert_app_authorised, assert_app_not_authorised, assert_error_kind, create_test_account,
+    spawn_daemon, spawn_daemon_with_account, spawn_daemon_with_token, APP_AUTH_REQ, APP_ID,
+};
+use safe_auth::{
+    log_in, revoke_app, AccountInfo, AccountKeys, AuthAllowPrompt, AuthBackend, AuthedAppsDiff,
+    AuthedAppsList, Error, FakeBackend,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long the slow network takes to list or revoke the apps
+const SLOW_NETWORK_DELAY: Duration = Duration::from_secs(3);
+
+// A `FakeBackend` whose listing and revoking of the apps are as slow as over a slow network
+#[derive(Default)]
+struct SlowBackend(FakeBackend);
+
+impl AuthBackend for SlowBackend {
+    fn authorise_app(&self, req: &str, allow: &'static AuthAllowPrompt) -> Result<String, Error> {
+        self.0.authorise_app(req, allow)
+    }
+
+    fn authed_apps(&self) -> Result<Vec<AuthedAppsList>, Error> {
+        thread::sleep(SLOW_NETWORK_DELAY);
+        self.0.authed_apps()
+    }
+
+    fn revoke_app(&self, app_id: &str) -> Result<(), Error> {
+        thread::sleep(SLOW_NETWORK_DELAY);
+        self.0.revoke_app(app_id)
+    }
+
+    fn acc_info(&self) -> Result<AccountInfo, Error> {
+        self.0.acc_info()
+    }
+
+    fn acc_keys(&self) -> Result<AccountKeys, Error> {
+        self.0.acc_keys()
+    }
+
+    fn backup_account(&self) -> Result<Vec<u8>, Error> {
+        self.0.backup_account()
+    }
+
+    fn restore_account(&self, backup: Vec<u8>) -> Result<AuthedAppsDiff, Error> {
+        self.0.restore_account(backup)
+    }
+}
+
+#[test]
+fn daemon_with_fake_backend() {
+    let daemon = spawn_daemon(Some(Box::new(FakeBackend::default())), &|_| true).unwrap();
+    let response = daemon.authorise(APP_AUTH_REQ).unwrap();
+    assert!(response.ends_with(APP_ID));
+    assert_error_kind(daemon.authorise("invalid-auth-req"), "IpcDecodeError");
+}
+
+#[test]
+fn daemon_responsive_while_listing_slowly() {
+    let daemon = spawn_daemon_with_token(
+        Some(Box::new(SlowBackend::default())),
+        &|_| true,
+        Some(String::from("e3b0c44298fc1c149afbf4c8996fb924")),
+    )
+    .unwrap();
+
+    let started = Instant::now();
+    let listing = {
+        let daemon = daemon.clone();
+        thread::spawn(move || daemon.get("/apps").unwrap())
+    };
+    // Let the listing start before the other requests are sent
+    thread::sleep(Duration::from_millis(500));
+
+    // The requests not needing the account are responded to right away
+    let (status, _) = daemon.get("/").unwrap();
+    assert_eq!(status, 200);
+    assert!(started.elapsed() < SLOW_NETWORK_DELAY);
+
+    let (status, body) = listing.join().unwrap();
+    assert_eq!((status, body.as_str()), (200, "[]"));
+}
+
+#[test]
+fn daemon_responsive_while_user_decides() {
+    // The user takes as long to decide on an authorisation as the network to list the apps
+    let daemon = spawn_daemon_with_token(
+        Some(Box::new(SlowBackend::default())),
+        &|_| {
+            thread::sleep(SLOW_NETWORK_DELAY);
+            true
+        },
+        Some(String::from("e3b0c44298fc1c149afbf4c8996fb924")),
+    )
+    .unwrap();
+
+    let started = Instant::now();
+    let authorising = {
+        let daemon = daemon.clone();
+        thread::spawn(move || daemon.authorise(APP_AUTH_REQ))
+    };
+    // Let the user be prompted before the apps are listed
+    thread::sleep(Duration::from_millis(500));
+
+    // The apps are listed while the user decides rather than once they decided
+    let (status, body) = daemon.get("/apps").unwrap();
+    assert_eq!((status, body.as_str()), (200, "[]"));
+    assert!(started.elapsed() < SLOW_NETWORK_DELAY * 2);
+
+    let response = authorising.join().unwrap().unwrap();
+    assert!(response.ends_with(APP_ID));
+}
+
+#[test]
+fn daemon_not_logged_in() {
+    let daemon = spawn_daemon(None, &|_| true).unwrap();
+    assert_error_kind(daemon.authorise(APP_AUTH_REQ), "CredentialsError");
+}
+
+#[test]
+fn daemon_with_throwaway_account() {
+    let (daemon, account) = spawn_daemon_with_account().unwrap();
+    let _ = daemon.authorise(APP_AUTH_REQ).unwrap();
+
+    let authenticator = log_in(&account.secret, &account.password).unwrap();
+    assert_app_authorised(&authenticator, APP_ID);
+}
+
+#[test]
+fn throwaway_account_revoke() {
+    let (authenticator, _) = create_test_account().unwrap();
+    let _ = authenticator
+        .authorise_app(APP_AUTH_REQ, &|_| true)
+        .unwrap();
+    assert_app_authorised(&authenticator, APP_ID);
+    revoke_app(&authenticator, APP_ID.to_string()).unwrap();
+    assert_app_not_authorised(&authenticator, APP_ID);
+}